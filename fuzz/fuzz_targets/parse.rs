@@ -0,0 +1,17 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Feed arbitrary text to the SystemVerilog parser.
+//!
+//! Run with `cargo fuzz run parse` from the repository root. Any panic, hang,
+//! or unbounded memory growth is a bug.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use moore_svlog_syntax::{ast, parser};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let arena = ast::Arena::default();
+        let _ = parser::parse_str(input, &arena);
+    }
+});
@@ -93,6 +93,13 @@ class CheckFile:
             finally:
                 pass
             raise Exception("Next line does not match")
+        elif dirname == "CHECK-NOT":
+            for line in copy(state):
+                line = line.split("//")[0].strip()
+                line = self.ansi_escape.sub("", line)
+                if line == directive[1]:
+                    raise Exception("Excluded line found")
+            return state
         else:
             raise Exception("Unknown directive `{}`".format(directive[0]))
 
@@ -212,15 +219,23 @@ class TestCase(object):
                 cmd.append(x)
         self.cmd = list([x.__str__() for x in cmd])
 
+        # Feed a file to stdin if the command ends in `< FILE`.
+        self.stdin = None
+        if len(self.cmd) >= 2 and self.cmd[-2] == "<":
+            self.stdin = self.cmd[-1]
+            self.cmd = self.cmd[:-2]
+
     def launch(self):
         if self.ignore:
             return
         try:
             self.info += "Command: {}\n".format(self.cmd)
+            if self.stdin:
+                self.info += "Stdin: {}\n".format(self.stdin)
             self.proc = subprocess.Popen(
                 [x.__str__() for x in self.cmd],
                 universal_newlines=True,
-                stdin=subprocess.DEVNULL,
+                stdin=open(self.stdin) if self.stdin else subprocess.DEVNULL,
                 stdout=subprocess.PIPE,
                 stderr=subprocess.PIPE,
                 cwd=crate_dir.__str__(),
@@ -0,0 +1,482 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! C bindings to parse SystemVerilog and access the syntax tree.
+//!
+//! The functions declared in `moore.h` parse a buffer of SystemVerilog source
+//! text into a `MooreFile`, which holds the diagnostics produced while
+//! parsing and a simplified syntax tree. The tree is a table of nodes, each
+//! with the kind and name of the corresponding AST node, its location, and
+//! its children. Nodes and diagnostics are referred to by their index; the
+//! root node has index 0.
+//!
+//! All strings returned by these functions are null-terminated UTF-8 and
+//! owned by the file. They remain valid until the file is freed with
+//! `moore_free`. Files may be used from any thread, but not concurrently.
+
+use moore_common::errors::{DiagBuilder2, DiagSegment, Severity};
+use moore_common::source::Span;
+use moore_svlog_syntax::{
+    ast::{self, AnyNode},
+    parser,
+};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// The index returned in place of a node which does not exist, such as the
+/// parent of the root node.
+pub const MOORE_NO_NODE: usize = usize::MAX;
+
+/// A parsed file.
+pub struct MooreFile {
+    diags: Vec<Diag>,
+    nodes: Vec<Node>,
+}
+
+/// A diagnostic produced while parsing.
+struct Diag {
+    severity: Severity,
+    message: CString,
+    location: Location,
+}
+
+/// A node of the simplified syntax tree.
+struct Node {
+    kind: CString,
+    name: Option<CString>,
+    location: Location,
+    parent: usize,
+    children: Vec<usize>,
+}
+
+/// The location of a node or diagnostic in the input.
+#[derive(Default)]
+struct Location {
+    /// The byte offsets of the first and one past the last character.
+    begin: usize,
+    end: usize,
+    /// The line and column of the first character, starting at 1.
+    line: usize,
+    column: usize,
+}
+
+impl Location {
+    fn new(span: Span) -> Location {
+        let (line, column, _) = span.begin().human();
+        Location {
+            begin: span.begin as usize,
+            end: span.end as usize,
+            line,
+            column,
+        }
+    }
+}
+
+impl MooreFile {
+    /// Parse the source text of a file.
+    fn parse(text: &str) -> MooreFile {
+        let arena = ast::Arena::default();
+        // The source is released once `_source` is dropped, so resolve all
+        // locations before returning.
+        let (root, diags, _source) = parser::parse_str(text, &arena);
+        let mut file = MooreFile {
+            diags: diags.iter().map(Diag::new).collect(),
+            nodes: vec![],
+        };
+        if let Some(root) = root {
+            file.add_node(&root, MOORE_NO_NODE);
+        }
+        file
+    }
+
+    /// Add a node and all nodes below it to the table.
+    fn add_node<'a>(&mut self, node: &'a dyn AnyNode<'a>, parent: usize) {
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            kind: c_string(node.type_name()),
+            name: node
+                .get_name()
+                .map(|name| c_string(&name.value.to_string())),
+            location: Location::new(node.span()),
+            parent,
+            children: vec![],
+        });
+        if parent != MOORE_NO_NODE {
+            self.nodes[parent].children.push(index);
+        }
+        node.for_each_child(&mut |child| self.add_node(child, index));
+    }
+
+    fn diag(&self, index: usize) -> Option<&Diag> {
+        self.diags.get(index)
+    }
+
+    fn node(&self, index: usize) -> Option<&Node> {
+        self.nodes.get(index)
+    }
+}
+
+impl Diag {
+    fn new(diag: &DiagBuilder2) -> Diag {
+        let span = diag.segments.iter().find_map(|seg| match seg {
+            DiagSegment::Span(span) => Some(*span),
+            _ => None,
+        });
+        Diag {
+            severity: diag.severity,
+            message: c_string(&diag.message),
+            location: span.map(Location::new).unwrap_or_default(),
+        }
+    }
+}
+
+/// Convert a string to a C string, dropping interior null characters.
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap()
+}
+
+/// Parse a buffer of SystemVerilog source text.
+///
+/// The `name` identifies the file to the caller; diagnostics are located by
+/// line and column within `text`. The `text` needs not be null-terminated.
+/// Returns null if either pointer is null, if the name or text are not valid
+/// UTF-8, or if the parser panics. Otherwise the file has to be freed with
+/// `moore_free`, even if it failed to parse.
+///
+/// # Safety
+///
+/// `name` must point to a null-terminated string, and `text` to `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn moore_parse(
+    name: *const c_char,
+    text: *const c_char,
+    len: usize,
+) -> *mut MooreFile {
+    if name.is_null() || text.is_null() {
+        return ptr::null_mut();
+    }
+    if CStr::from_ptr(name).to_str().is_err() {
+        return ptr::null_mut();
+    }
+    let text = std::slice::from_raw_parts(text as *const u8, len);
+    let text = match std::str::from_utf8(text) {
+        Ok(x) => x,
+        Err(_) => return ptr::null_mut(),
+    };
+    // Unwinding across the FFI boundary is undefined behaviour.
+    match panic::catch_unwind(AssertUnwindSafe(|| MooreFile::parse(text))) {
+        Ok(file) => Box::into_raw(Box::new(file)),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Free a file returned by `moore_parse`. Does nothing if `file` is null.
+///
+/// # Safety
+///
+/// `file` must be null or a file returned by `moore_parse` which has not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn moore_free(file: *mut MooreFile) {
+    if !file.is_null() {
+        drop(Box::from_raw(file));
+    }
+}
+
+/// Check whether a file parsed without errors.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_ok(file: *const MooreFile) -> bool {
+    match file.as_ref() {
+        Some(file) => {
+            !file.nodes.is_empty() && file.diags.iter().all(|d| d.severity < Severity::Error)
+        }
+        None => false,
+    }
+}
+
+/// Get the number of diagnostics of a file.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_diag_count(file: *const MooreFile) -> usize {
+    file.as_ref().map(|f| f.diags.len()).unwrap_or(0)
+}
+
+/// Get the severity of a diagnostic: 0 for notes, 1 for warnings, 2 for
+/// errors, 3 for fatal errors, and 4 for compiler bugs. Returns -1 if the
+/// diagnostic does not exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_diag_severity(file: *const MooreFile, index: usize) -> c_int {
+    match file.as_ref().and_then(|f| f.diag(index)) {
+        Some(diag) => match diag.severity {
+            Severity::Note => 0,
+            Severity::Warning => 1,
+            Severity::Error => 2,
+            Severity::Fatal => 3,
+            Severity::Bug => 4,
+        },
+        None => -1,
+    }
+}
+
+/// Get the message of a diagnostic, or null if it does not exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_diag_message(file: *const MooreFile, index: usize) -> *const c_char {
+    match file.as_ref().and_then(|f| f.diag(index)) {
+        Some(diag) => diag.message.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Get the line of a diagnostic, starting at 1. Returns 0 if the diagnostic
+/// does not exist or has no location.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_diag_line(file: *const MooreFile, index: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.diag(index))
+        .map(|d| d.location.line)
+        .unwrap_or(0)
+}
+
+/// Get the column of a diagnostic, starting at 1. Returns 0 if the diagnostic
+/// does not exist or has no location.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_diag_column(file: *const MooreFile, index: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.diag(index))
+        .map(|d| d.location.column)
+        .unwrap_or(0)
+}
+
+/// Get the number of nodes of a file. Returns 0 if the file failed to parse.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_count(file: *const MooreFile) -> usize {
+    file.as_ref().map(|f| f.nodes.len()).unwrap_or(0)
+}
+
+/// Get the kind of a node, such as `"Module"` or `"PortDecl"`, or null if the
+/// node does not exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_kind(file: *const MooreFile, node: usize) -> *const c_char {
+    match file.as_ref().and_then(|f| f.node(node)) {
+        Some(node) => node.kind.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Get the name of a node, or null if the node does not exist or has no
+/// name.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_name(file: *const MooreFile, node: usize) -> *const c_char {
+    match file
+        .as_ref()
+        .and_then(|f| f.node(node))
+        .and_then(|n| n.name.as_ref())
+    {
+        Some(name) => name.as_ptr(),
+        None => ptr::null(),
+    }
+}
+
+/// Get the byte offset of the first character of a node in the input.
+/// Returns 0 if the node does not exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_begin(file: *const MooreFile, node: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.node(node))
+        .map(|n| n.location.begin)
+        .unwrap_or(0)
+}
+
+/// Get the byte offset one past the last character of a node in the input.
+/// Returns 0 if the node does not exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_end(file: *const MooreFile, node: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.node(node))
+        .map(|n| n.location.end)
+        .unwrap_or(0)
+}
+
+/// Get the line of a node, starting at 1. Returns 0 if the node does not
+/// exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_line(file: *const MooreFile, node: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.node(node))
+        .map(|n| n.location.line)
+        .unwrap_or(0)
+}
+
+/// Get the column of a node, starting at 1. Returns 0 if the node does not
+/// exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_column(file: *const MooreFile, node: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.node(node))
+        .map(|n| n.location.column)
+        .unwrap_or(0)
+}
+
+/// Get the parent of a node. Returns `MOORE_NO_NODE` for the root node, or
+/// if the node does not exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_parent(file: *const MooreFile, node: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.node(node))
+        .map(|n| n.parent)
+        .unwrap_or(MOORE_NO_NODE)
+}
+
+/// Get the number of children of a node. Returns 0 if the node does not
+/// exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_child_count(file: *const MooreFile, node: usize) -> usize {
+    file.as_ref()
+        .and_then(|f| f.node(node))
+        .map(|n| n.children.len())
+        .unwrap_or(0)
+}
+
+/// Get a child of a node, in source order. Returns `MOORE_NO_NODE` if the node
+/// or child does not exist.
+///
+/// # Safety
+///
+/// `file` must be null or a valid file.
+#[no_mangle]
+pub unsafe extern "C" fn moore_node_child(
+    file: *const MooreFile,
+    node: usize,
+    index: usize,
+) -> usize {
+    file.as_ref()
+        .and_then(|f| f.node(node))
+        .and_then(|n| n.children.get(index).cloned())
+        .unwrap_or(MOORE_NO_NODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> *mut MooreFile {
+        let name = CString::new("test.sv").unwrap();
+        unsafe { moore_parse(name.as_ptr(), text.as_ptr() as *const c_char, text.len()) }
+    }
+
+    fn string(s: *const c_char) -> Option<String> {
+        if s.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string())
+        }
+    }
+
+    #[test]
+    fn walk() {
+        let file = parse("module foo (input a); endmodule");
+        unsafe {
+            assert!(moore_ok(file));
+            assert_eq!(moore_diag_count(file), 0);
+            assert_eq!(moore_node_parent(file, 0), MOORE_NO_NODE);
+            assert_eq!(moore_node_child_count(file, 0), 1);
+            let item = moore_node_child(file, 0, 0);
+            assert_eq!(moore_node_parent(file, item), 0);
+            let module = (item..moore_node_count(file))
+                .find(|&n| string(moore_node_kind(file, n)).unwrap() == "Module")
+                .unwrap();
+            assert_eq!(string(moore_node_name(file, module)).unwrap(), "foo");
+            assert_eq!(moore_node_line(file, module), 1);
+            assert_eq!(moore_node_column(file, module), 1);
+            assert_eq!(moore_node_begin(file, module), 0);
+            assert!(moore_node_end(file, module) > 0);
+            assert_eq!(moore_node_kind(file, moore_node_count(file)), ptr::null());
+            moore_free(file);
+        }
+    }
+
+    #[test]
+    fn diagnostics() {
+        let file = parse("module foo;\n  wire = 1;\nendmodule");
+        unsafe {
+            assert!(!moore_ok(file));
+            assert_eq!(moore_node_count(file), 0);
+            assert!(moore_diag_count(file) > 0);
+            assert!(moore_diag_severity(file, 0) >= 2);
+            assert!(string(moore_diag_message(file, 0)).is_some());
+            assert_eq!(moore_diag_line(file, 0), 2);
+            assert_eq!(moore_diag_severity(file, moore_diag_count(file)), -1);
+            moore_free(file);
+        }
+    }
+
+    #[test]
+    fn null() {
+        unsafe {
+            assert!(moore_parse(ptr::null(), ptr::null(), 0).is_null());
+            assert!(!moore_ok(ptr::null()));
+            assert_eq!(moore_node_count(ptr::null()), 0);
+            moore_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,75 @@
+/* Copyright (c) 2016-2021 Fabian Schuiki */
+
+/* C bindings to parse SystemVerilog and access the syntax tree.
+ *
+ * Link against the `moore_capi` shared or static library built from this
+ * directory. Nodes and diagnostics are referred to by their index; the root
+ * node has index 0. All returned strings are owned by the file and remain
+ * valid until it is freed with `moore_free`.
+ */
+
+#ifndef MOORE_H
+#define MOORE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The index returned in place of a node which does not exist, such as the
+ * parent of the root node. */
+#define MOORE_NO_NODE SIZE_MAX
+
+/* The severities of diagnostics. */
+#define MOORE_NOTE 0
+#define MOORE_WARNING 1
+#define MOORE_ERROR 2
+#define MOORE_FATAL 3
+#define MOORE_BUG 4
+
+/* A parsed file. */
+typedef struct MooreFile MooreFile;
+
+/* Parse `len` bytes of SystemVerilog source text. The `name` identifies the
+ * file to the caller. Returns NULL if a pointer is NULL, the input is not valid
+ * UTF-8, or the parser panics; otherwise the file must be freed with
+ * `moore_free`. */
+MooreFile *moore_parse(const char *name, const char *text, size_t len);
+
+/* Free a parsed file. Does nothing if `file` is NULL. */
+void moore_free(MooreFile *file);
+
+/* Check whether a file parsed without errors. */
+bool moore_ok(const MooreFile *file);
+
+/* The diagnostics produced while parsing. The line and column start at 1,
+ * and are 0 if the diagnostic has no location. */
+size_t moore_diag_count(const MooreFile *file);
+int moore_diag_severity(const MooreFile *file, size_t index);
+const char *moore_diag_message(const MooreFile *file, size_t index);
+size_t moore_diag_line(const MooreFile *file, size_t index);
+size_t moore_diag_column(const MooreFile *file, size_t index);
+
+/* The nodes of the syntax tree. There are no nodes if the file failed to
+ * parse. The kind is the name of the AST node type, such as "Module". The
+ * name is NULL for nodes without a name. The begin and end are byte offsets
+ * into the text. */
+size_t moore_node_count(const MooreFile *file);
+const char *moore_node_kind(const MooreFile *file, size_t node);
+const char *moore_node_name(const MooreFile *file, size_t node);
+size_t moore_node_begin(const MooreFile *file, size_t node);
+size_t moore_node_end(const MooreFile *file, size_t node);
+size_t moore_node_line(const MooreFile *file, size_t node);
+size_t moore_node_column(const MooreFile *file, size_t node);
+size_t moore_node_parent(const MooreFile *file, size_t node);
+size_t moore_node_child_count(const MooreFile *file, size_t node);
+size_t moore_node_child(const MooreFile *file, size_t node, size_t index);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MOORE_H */
@@ -4,7 +4,10 @@
 
 #![deny(missing_docs)]
 
+use std::alloc::Layout;
 use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::mem::MaybeUninit;
 pub use typed_arena::Arena as TypedArena;
 
 /// Allocates values.
@@ -122,7 +125,199 @@ where
 {
 }
 
+/// The number of values and bytes allocated in a typed arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaStats {
+    /// The name of the arena.
+    pub name: &'static str,
+    /// The number of values allocated.
+    pub count: usize,
+    /// The number of bytes occupied by the allocated values.
+    pub bytes: usize,
+}
+
+impl ArenaStats {
+    /// Determine the statistics of a typed arena.
+    pub fn of<T>(name: &'static str, arena: &TypedArena<T>) -> ArenaStats {
+        let count = arena.len();
+        ArenaStats {
+            name,
+            count,
+            bytes: count * std::mem::size_of::<T>(),
+        }
+    }
+}
+
+/// An arena for values which need not be dropped.
+///
+/// Values of any `Copy` type are bump-allocated into large chunks of raw
+/// memory, without a separate list of chunks for each type as `TypedArena`
+/// keeps. This suits small values such as node IDs and spans which are
+/// allocated often, and keeps values allocated after one another close
+/// together in memory.
+pub struct DroplessArena {
+    /// The start of the free space in the current chunk.
+    ptr: Cell<*mut u8>,
+    /// The end of the current chunk.
+    end: Cell<*mut u8>,
+    /// The chunks allocated so far.
+    chunks: RefCell<Vec<Box<[MaybeUninit<u8>]>>>,
+    /// The number of allocations and bytes allocated, for `stats()`.
+    count: Cell<usize>,
+    bytes: Cell<usize>,
+}
+
+/// The size of the first chunk of a dropless arena.
+const DROPLESS_FIRST_CHUNK: usize = 4096;
+
+/// The size beyond which the chunks of a dropless arena stop growing.
+const DROPLESS_MAX_CHUNK: usize = 2 * 1024 * 1024;
+
+impl DroplessArena {
+    /// Create a new empty arena.
+    pub fn new() -> DroplessArena {
+        DroplessArena {
+            ptr: Cell::new(std::ptr::null_mut()),
+            end: Cell::new(std::ptr::null_mut()),
+            chunks: RefCell::new(vec![]),
+            count: Cell::new(0),
+            bytes: Cell::new(0),
+        }
+    }
+
+    /// Allocate a value.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
+        let ptr = self.alloc_raw(Layout::new::<T>()) as *mut T;
+        unsafe {
+            ptr.write(value);
+            &mut *ptr
+        }
+    }
+
+    /// Allocate a copy of a slice.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice<T: Copy>(&self, values: &[T]) -> &mut [T] {
+        if values.is_empty() {
+            return &mut [];
+        }
+        let ptr = self.alloc_raw(slice_layout::<T>(values.len())) as *mut T;
+        unsafe {
+            ptr.copy_from_nonoverlapping(values.as_ptr(), values.len());
+            std::slice::from_raw_parts_mut(ptr, values.len())
+        }
+    }
+
+    /// Allocate the values of an iterator as a slice.
+    ///
+    /// If the iterator knows its exact length, the values are written into the
+    /// arena directly. Otherwise they are collected into a temporary vector
+    /// first.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_from_iter<T: Copy>(&self, values: impl IntoIterator<Item = T>) -> &mut [T] {
+        let mut values = values.into_iter();
+        let len = match values.size_hint() {
+            (min, Some(max)) if min == max => min,
+            _ => {
+                let values: Vec<T> = values.collect();
+                return self.alloc_slice(&values);
+            }
+        };
+        if len == 0 {
+            return &mut [];
+        }
+        // The memory is reserved before the first value is computed, such that
+        // the iterator may allocate into the arena itself.
+        let ptr = self.alloc_raw(slice_layout::<T>(len)) as *mut T;
+        let mut written = 0;
+        for value in values.by_ref().take(len) {
+            unsafe { ptr.add(written).write(value) };
+            written += 1;
+        }
+        assert!(
+            written == len && values.next().is_none(),
+            "iterator yielded a different number of values than its size hint"
+        );
+        unsafe { std::slice::from_raw_parts_mut(ptr, len) }
+    }
+
+    /// Report the number of allocations and bytes allocated.
+    pub fn stats(&self, name: &'static str) -> ArenaStats {
+        ArenaStats {
+            name,
+            count: self.count.get(),
+            bytes: self.bytes.get(),
+        }
+    }
+
+    /// Allocate uninitialized memory with a given layout.
+    fn alloc_raw(&self, layout: Layout) -> *mut u8 {
+        self.count.set(self.count.get() + 1);
+        self.bytes.set(self.bytes.get() + layout.size());
+        if layout.size() == 0 {
+            return layout.align() as *mut u8;
+        }
+        if let Some(ptr) = self.bump(layout) {
+            return ptr;
+        }
+        self.grow(layout.size() + layout.align() - 1);
+        self.bump(layout).expect("new chunk fits the allocation")
+    }
+
+    /// Allocate memory from the current chunk, if it has enough space left.
+    fn bump(&self, layout: Layout) -> Option<*mut u8> {
+        let ptr = self.ptr.get() as usize;
+        let start = ptr.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+        let end = start.checked_add(layout.size())?;
+        if ptr == 0 || end > self.end.get() as usize {
+            return None;
+        }
+        // Offset the existing pointer rather than casting the address back,
+        // such that the result keeps the provenance of the chunk.
+        let start = unsafe { self.ptr.get().add(start - ptr) };
+        self.ptr.set(unsafe { start.add(layout.size()) });
+        Some(start)
+    }
+
+    /// Start a new chunk with room for at least `size` bytes.
+    fn grow(&self, size: usize) {
+        let mut chunks = self.chunks.borrow_mut();
+        let prev = chunks.last().map(|c| c.len()).unwrap_or(0);
+        let capacity = match prev {
+            0 => DROPLESS_FIRST_CHUNK,
+            _ => (prev * 2).min(DROPLESS_MAX_CHUNK),
+        }
+        .max(size);
+        let mut chunk: Vec<MaybeUninit<u8>> = Vec::with_capacity(capacity);
+        unsafe { chunk.set_len(capacity) };
+        let mut chunk = chunk.into_boxed_slice();
+        let start = chunk.as_mut_ptr() as *mut u8;
+        self.ptr.set(start);
+        self.end.set(unsafe { start.add(capacity) });
+        chunks.push(chunk);
+    }
+}
+
+/// Determine the layout of a slice of `len` values.
+fn slice_layout<T>(len: usize) -> Layout {
+    std::mem::size_of::<T>()
+        .checked_mul(len)
+        .and_then(|size| Layout::from_size_align(size, std::mem::align_of::<T>()).ok())
+        .expect("slice too large for arena")
+}
+
+impl Default for DroplessArena {
+    fn default() -> DroplessArena {
+        DroplessArena::new()
+    }
+}
+
 /// Generate a collection of arenas for different types.
+///
+/// Besides the arenas, the collection provides a `stats()` method which
+/// reports the values allocated in each arena. It implements `AllocOwned` for
+/// all member types which are their own owned form, such that `maybe_alloc`
+/// and `force_alloc` may be used to allocate a `Cow`.
 #[macro_export]
 macro_rules! make_arenas {
     ($(#[$arena_attr:meta])* pub struct $arena_name:ident { $($name:ident: $type:ty,)* }) => {
@@ -153,6 +348,21 @@ macro_rules! make_arenas {
                     $($name: $crate::arenas::TypedArena::new(),)*
                 }
             }
+
+            /// Report the number of values and bytes allocated in each arena.
+            pub fn stats(&self) -> Vec<$crate::arenas::ArenaStats> {
+                vec![$($crate::arenas::ArenaStats::of(stringify!($name), &self.$name),)*]
+            }
+        }
+
+        impl<'a, $($lt,)* T> $crate::arenas::AllocOwned<'a, 'a, T> for $arena_name<$($lt),*>
+        where
+            T: ToOwned<Owned = T> + 'a,
+            Self: $crate::arenas::Alloc<'a, 'a, T>,
+        {
+            fn alloc_owned(&'a self, value: T) -> &'a T {
+                $crate::arenas::Alloc::alloc(self, value)
+            }
         }
 
         impl<$($lt),*> Default for $arena_name<$($lt),*> {
@@ -176,3 +386,69 @@ macro_rules! make_arenas {
 
     (TRAIT_IMPL $arena_name:ident; [$($lt:tt),*];) => {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    make_arenas!(
+        pub struct TestArena {
+            strings: String,
+            numbers: u32,
+        }
+    );
+
+    #[test]
+    fn dropless() {
+        let arena = DroplessArena::new();
+        let a = arena.alloc(1u8);
+        let b = arena.alloc(2u64);
+        let c = arena.alloc_slice(&[3u32, 4, 5]);
+        let d = arena.alloc_from_iter((0..2000).map(|i| i as u16));
+        let e = arena.alloc(());
+        assert_eq!(*a, 1);
+        assert_eq!(*b, 2);
+        assert_eq!(b as *const u64 as usize % std::mem::align_of::<u64>(), 0);
+        assert_eq!(c, &[3, 4, 5]);
+        assert_eq!(d.len(), 2000);
+        assert!(d.iter().enumerate().all(|(i, &x)| x == i as u16));
+        assert_eq!(*e, ());
+        assert!(arena.alloc_slice::<u32>(&[]).is_empty());
+        let large = arena.alloc_slice(&[7u8; 3 * DROPLESS_MAX_CHUNK]);
+        assert!(large.iter().all(|&x| x == 7));
+        assert_eq!(*a, 1);
+        assert_eq!(arena.stats("test").count, 6);
+    }
+
+    #[test]
+    fn dropless_from_iter() {
+        let arena = DroplessArena::new();
+
+        // Iterators of known length are written into the arena directly, such
+        // that the slice is reserved before the values allocated while
+        // computing its elements.
+        let refs = arena.alloc_from_iter((0..4u32).map(|i| &*arena.alloc(i * 2)));
+        assert_eq!(refs.iter().map(|&&x| x).collect::<Vec<_>>(), [0, 2, 4, 6]);
+        assert!((refs.as_ptr() as usize) < (refs[0] as *const u32 as usize));
+
+        // Other iterators are collected first.
+        let evens = arena.alloc_from_iter((0..10u8).filter(|x| x % 2 == 0));
+        assert_eq!(evens, &[0, 2, 4, 6, 8]);
+        assert!(arena.alloc_from_iter(std::iter::empty::<u8>()).is_empty());
+    }
+
+    #[test]
+    fn alloc_cow() {
+        let arena = TestArena::new();
+        let hello = String::from("hello");
+        let borrowed: &String = arena.maybe_alloc(Cow::Borrowed(&hello));
+        assert!(std::ptr::eq(borrowed, &hello));
+        let owned: &String = arena.maybe_alloc(Cow::Owned(String::from("world")));
+        assert_eq!(owned, "world");
+        let forced: &String = arena.force_alloc(Cow::Borrowed(&hello));
+        assert!(!std::ptr::eq(forced, &hello));
+        let number: &u32 = arena.maybe_alloc(Cow::Owned(42));
+        assert_eq!(*number, 42);
+        assert_eq!(arena.stats()[0].count, 2);
+    }
+}
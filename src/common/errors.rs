@@ -2,21 +2,19 @@
 
 //! Utilities to implement diagnostics and error reporting facilities.
 
-use crate::source::Span;
-use std::fmt;
+use crate::source::{Span, INVALID_SOURCE};
+use std::cell::RefCell;
+use std::fmt::{self, Write};
 
-/// Print debug information. Omitted in release builds.
-#[macro_export]
-#[cfg(debug_assertions)]
-macro_rules! debugln {
-    ($($arg:tt)*) => { eprintln!("\x1B[34;1mdebug:\x1B[m {}", format!($($arg)*)); }
-}
-
-/// Print debug information. Omitted in release builds.
+/// Print debug information.
+///
+/// This is a thin wrapper around `log::debug!`, such that the output is
+/// tagged with the module it originates from and can be enabled, filtered, or
+/// redirected through whichever logger the user of the library installs. The
+/// `moore` binary enables it with `MOORE_LOG` or `--log`.
 #[macro_export]
-#[cfg(not(debug_assertions))]
 macro_rules! debugln {
-    ($($arg:tt)*) => {};
+    ($($arg:tt)*) => { $crate::__log::debug!($($arg)*) };
 }
 
 /// A handler deals with errors.
@@ -69,6 +67,56 @@ impl<T, E: EmitError> EmitError for Result<T, E> {
     }
 }
 
+thread_local!(static CAPTURED: RefCell<Option<CapturedDiags>> = RefCell::new(None));
+
+/// The diagnostics printed while running [`capture_diags`].
+#[derive(Debug, Default)]
+pub struct CapturedDiags {
+    /// The diagnostics, in the order they were printed.
+    pub diags: Vec<DiagBuilder2>,
+    /// The output that would have been printed to stderr.
+    pub output: String,
+}
+
+/// Print a diagnostic to stderr, formatted as `text`.
+///
+/// The parsers print their diagnostics through this function. The diagnostics
+/// of a thread that runs [`capture_diags`] are recorded instead.
+pub fn print_diag(diag: &DiagBuilder2, text: fmt::Arguments) {
+    let printed = CAPTURED.with(|c| match *c.borrow_mut() {
+        Some(ref mut captured) => {
+            captured.diags.push(diag.clone());
+            writeln!(captured.output, "{}", text).unwrap();
+            true
+        }
+        None => false,
+    });
+    if !printed {
+        eprintln!("{}", text);
+    }
+}
+
+/// Capture the diagnostics printed by `f` through [`print_diag`].
+///
+/// Allows files to be parsed on several threads, with the diagnostics of each
+/// file printed in a deterministic order afterwards.
+pub fn capture_diags<R>(f: impl FnOnce() -> R) -> (R, CapturedDiags) {
+    // Restore the outer capture even if `f` panics.
+    struct Guard(Option<Option<CapturedDiags>>);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            if let Some(outer) = self.0.take() {
+                CAPTURED.with(|c| c.replace(outer));
+            }
+        }
+    }
+    let mut guard = Guard(Some(CAPTURED.with(|c| c.replace(Some(Default::default())))));
+    let result = f();
+    let outer = guard.0.take().unwrap();
+    let captured = CAPTURED.with(|c| c.replace(outer)).unwrap_or_default();
+    (result, captured)
+}
+
 #[must_use]
 #[derive(Clone, Debug)]
 pub struct DiagBuilder2 {
@@ -190,8 +238,12 @@ impl fmt::Display for DiagBuilder2 {
 
         for segment in &self.segments {
             match *segment {
+                // Spans which do not point into a source file (e.g. at the
+                // end of an empty input) cannot be printed.
+                DiagSegment::Span(sp) if sp.source == INVALID_SOURCE => (),
                 DiagSegment::Span(sp) => {
                     let c = sp.source.get_content();
+                    let (begin, end) = (sp.begin as usize, sp.end as usize);
 
                     // Look for the start of the line.
                     let (line, col, line_offset) = sp.begin().human();
@@ -214,11 +266,11 @@ impl fmt::Display for DiagBuilder2 {
                     write!(f, "   | ")?;
                     for (mut i, c) in text.char_indices() {
                         i += line_offset;
-                        if sp.begin != sp.end {
-                            if i == sp.begin {
+                        if begin != end {
+                            if i == begin {
                                 write!(f, "{}", colorcode)?;
                             }
-                            if i == sp.end {
+                            if i == end {
                                 write!(f, "\x1B[m")?;
                             }
                         }
@@ -234,9 +286,7 @@ impl fmt::Display for DiagBuilder2 {
                     let mut pd = ' ';
                     for (mut i, c) in text.char_indices() {
                         i += line_offset;
-                        let d = if (i >= sp.begin && i < sp.end)
-                            || (i == sp.begin && sp.begin == sp.end)
-                        {
+                        let d = if (i >= begin && i < end) || (i == begin && begin == end) {
                             '^'
                         } else {
                             ' '
@@ -272,3 +322,15 @@ impl fmt::Display for DiagBuilder2 {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_diags_restores_after_panic() {
+        let result = std::panic::catch_unwind(|| capture_diags(|| panic!("boom")));
+        assert!(result.is_err());
+        assert!(CAPTURED.with(|c| c.borrow().is_none()));
+    }
+}
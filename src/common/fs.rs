@@ -0,0 +1,261 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Access to the files read and written by the compiler.
+//!
+//! The source manager, and through it the `` `include`` directives, as well as
+//! the library indices and file lists, access files through the global
+//! `FileSystem`. By default this is the file system of the operating system.
+//! Tools which have no file system, such as a parser compiled to WebAssembly
+//! and run in a browser, install a `MemoryFileSystem` with the files they
+//! want to process instead.
+//!
+//! The paths of the files read are recorded, such that build systems can be
+//! told which files a compilation depends on.
+
+use crate::source::{CharIter, SourceContent, VirtualSourceContent, MAX_SOURCE_SIZE};
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// A file system the compiler reads and writes files in.
+pub trait FileSystem: Send + Sync {
+    /// Check whether a path refers to a file, as opposed to a directory or
+    /// nothing at all.
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Read the contents of a file.
+    ///
+    /// Characters which are not valid UTF-8 are replaced.
+    fn read(&self, path: &Path) -> io::Result<Arc<dyn SourceContent>>;
+
+    /// Write the contents of a file, creating its directory if needed.
+    fn write(&self, path: &Path, content: &str) -> io::Result<()>;
+}
+
+static FILE_SYSTEM: OnceCell<Box<dyn FileSystem>> = OnceCell::new();
+
+/// Get the global file system.
+///
+/// This is the file system of the operating system, unless another one has
+/// been installed with `set_file_system`.
+pub fn get_file_system() -> &'static dyn FileSystem {
+    FILE_SYSTEM.get_or_init(|| Box::new(OsFileSystem)).as_ref()
+}
+
+/// Install the global file system.
+///
+/// Has to be called before any file is accessed. Returns the file system
+/// back if the global file system is already in use.
+pub fn set_file_system(fs: Box<dyn FileSystem>) -> Result<(), Box<dyn FileSystem>> {
+    FILE_SYSTEM.set(fs)
+}
+
+/// The paths of the files read so far, in the order they were first read.
+static FILES_READ: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Read a file of the global file system, and record its path.
+pub fn read(path: &Path) -> io::Result<Arc<dyn SourceContent>> {
+    let content = get_file_system().read(path)?;
+    let mut files = FILES_READ.lock().unwrap();
+    if !files.iter().any(|p| p == path) {
+        files.push(path.to_path_buf());
+    }
+    Ok(content)
+}
+
+/// Read a file of the global file system into a string, and record its path.
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let content = read(path)?;
+    Ok(String::from_utf8_lossy(content.bytes()).into_owned())
+}
+
+/// Get the paths of the files read through `read` and `read_to_string`, in
+/// the order they were first read.
+pub fn files_read() -> Vec<PathBuf> {
+    FILES_READ.lock().unwrap().clone()
+}
+
+/// The file system of the operating system.
+pub struct OsFileSystem;
+
+impl FileSystem for OsFileSystem {
+    fn is_file(&self, path: &Path) -> bool {
+        path.exists() && !path.is_dir()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Arc<dyn SourceContent>> {
+        Ok(Arc::new(OsFileContent::load(path)?))
+    }
+
+    fn write(&self, path: &Path, content: &str) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, content)
+    }
+}
+
+/// Files of at least this size are mapped into memory instead of being read,
+/// such that large generated files need not be held in memory as a copy.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) const MMAP_THRESHOLD: u64 = 1 << 20;
+
+/// The contents of a file of the operating system.
+#[derive(Debug)]
+enum OsFileContent {
+    /// The file mapped into memory. Its contents are valid UTF-8.
+    #[cfg(not(target_arch = "wasm32"))]
+    Mapped(memmap::Mmap),
+    /// The file read into memory.
+    Read(String),
+}
+
+impl OsFileContent {
+    /// Load the contents of a file.
+    ///
+    /// Large files are mapped into memory. Files which are small, cannot be
+    /// mapped, or are not valid UTF-8 are read instead, replacing invalid
+    /// characters.
+    fn load(path: &Path) -> io::Result<OsFileContent> {
+        use std::io::Read;
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        if len > MAX_SOURCE_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "source files larger than 4 GiB are not supported",
+            ));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if len >= MMAP_THRESHOLD {
+                if let Ok(map) = unsafe { memmap::Mmap::map(&file) } {
+                    if std::str::from_utf8(&map).is_ok() {
+                        return Ok(OsFileContent::Mapped(map));
+                    }
+                }
+            }
+        }
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        Ok(OsFileContent::Read(match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        }))
+    }
+
+    /// The contents of the file.
+    fn as_str(&self) -> &str {
+        match self {
+            // Mapped files are checked to be valid UTF-8 when they are loaded.
+            #[cfg(not(target_arch = "wasm32"))]
+            OsFileContent::Mapped(map) => unsafe { std::str::from_utf8_unchecked(&map[..]) },
+            OsFileContent::Read(s) => s,
+        }
+    }
+}
+
+impl SourceContent for OsFileContent {
+    fn iter(&self) -> Box<CharIter> {
+        Box::new(self.as_str().char_indices())
+    }
+
+    fn iter_from(&self, offset: usize) -> Box<CharIter> {
+        Box::new(self.as_str()[offset..].char_indices())
+    }
+
+    fn extract(&self, begin: usize, end: usize) -> String {
+        self.as_str()[begin..end].to_string()
+    }
+
+    fn extract_iter(&self, begin: usize, end: usize) -> Box<CharIter> {
+        Box::new(self.as_str()[begin..end].char_indices())
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+/// A file system which holds its files in memory.
+///
+/// Paths are compared component by component, ignoring `.` components, such
+/// that `./rtl/top.sv` and `rtl//top.sv` refer to the same file. Directories
+/// exist implicitly through the files in them.
+#[derive(Default)]
+pub struct MemoryFileSystem {
+    files: RwLock<HashMap<PathBuf, Arc<VirtualSourceContent>>>,
+}
+
+impl MemoryFileSystem {
+    /// Create an empty file system.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a file, replacing any previous file with the same path.
+    pub fn add(&self, path: impl AsRef<Path>, content: impl Into<String>) {
+        self.files.write().unwrap().insert(
+            normalize(path.as_ref()),
+            Arc::new(VirtualSourceContent(content.into())),
+        );
+    }
+
+    /// Get the paths of all files, sorted.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<_> = self.files.read().unwrap().keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+}
+
+impl FileSystem for MemoryFileSystem {
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.read().unwrap().contains_key(&normalize(path))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Arc<dyn SourceContent>> {
+        match self.files.read().unwrap().get(&normalize(path)) {
+            Some(content) => Ok(content.clone()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no such file in memory",
+            )),
+        }
+    }
+
+    fn write(&self, path: &Path, content: &str) -> io::Result<()> {
+        self.add(path, content);
+        Ok(())
+    }
+}
+
+/// Remove the `.` components of a path.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| *c != Component::CurDir)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory() {
+        let fs = MemoryFileSystem::new();
+        fs.add("rtl/top.sv", "module top; endmodule");
+        assert!(fs.is_file(Path::new("./rtl/top.sv")));
+        assert!(!fs.is_file(Path::new("rtl")));
+        let content = fs.read(Path::new("rtl//top.sv")).unwrap();
+        assert_eq!(content.extract(7, 10), "top");
+        assert!(fs.read(Path::new("top.sv")).is_err());
+        fs.write(Path::new("lib/index"), "foo").unwrap();
+        assert_eq!(
+            fs.paths(),
+            vec![PathBuf::from("lib/index"), PathBuf::from("rtl/top.sv")]
+        );
+    }
+}
@@ -5,10 +5,13 @@
 
 #[macro_use]
 extern crate bitflags;
+#[doc(hidden)]
+pub use log as __log;
 
 #[macro_use]
 pub mod arenas;
 pub mod errors;
+pub mod fs;
 pub mod grind;
 pub mod id;
 pub mod lexer;
@@ -19,12 +22,12 @@ pub mod util;
 
 pub use self::id::NodeId;
 use crate::errors::{DiagBuilder2, DiagEmitter, Severity};
-use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub struct Session {
     pub opts: SessionOptions,
     /// Whether any error diagnostics were produced.
-    pub failed: Cell<bool>,
+    pub failed: AtomicBool,
 }
 
 impl Session {
@@ -32,19 +35,19 @@ impl Session {
     pub fn new() -> Session {
         Session {
             opts: Default::default(),
-            failed: Cell::new(false),
+            failed: AtomicBool::new(false),
         }
     }
 
     pub fn failed(&self) -> bool {
-        self.failed.get()
+        self.failed.load(Ordering::Relaxed)
     }
 }
 
 impl DiagEmitter for Session {
     fn emit(&self, diag: DiagBuilder2) {
         if diag.severity >= Severity::Error {
-            self.failed.set(true);
+            self.failed.store(true, Ordering::Relaxed);
         }
         eprintln!("{}", diag);
     }
@@ -75,6 +78,32 @@ pub struct SessionOptions {
     pub verbosity: Verbosity,
     /// The optimization level.
     pub opt_level: usize,
+    /// Attach source locations to the generated code.
+    pub debug_info: bool,
+    /// Name generated units after their source declarations only.
+    pub keep_names: bool,
+    /// How values with `x` and `z` bits are lowered.
+    pub four_state: FourState,
+    /// Emit modules without a definition as external unit declarations.
+    pub allow_blackbox: bool,
+    /// Treat all input files as a single compilation unit.
+    pub single_unit: bool,
+}
+
+/// A strategy to lower four-valued types to hardware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FourState {
+    /// Lower to two-valued types. The `x` and `z` bits of constants become 0.
+    TwoState,
+    /// Encode each four-valued value as a pair of the two-valued value and a
+    /// mask of its unknown bits.
+    Paired,
+}
+
+impl Default for FourState {
+    fn default() -> FourState {
+        FourState::TwoState
+    }
 }
 
 bitflags! {
@@ -92,5 +121,6 @@ bitflags! {
         const PORTS         = 1 << 6;
         const CONSTS        = 1 << 7;
         const INSTS         = 1 << 8;
+        const PROCS         = 1 << 9;
     }
 }
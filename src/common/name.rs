@@ -4,14 +4,13 @@
 //! to be referred to by a lightweight tag. This structure is heavily inspired
 //! by the interner used in the Rust compiler.
 
+use once_cell::sync::Lazy;
 use std::borrow::Borrow;
-use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
-use std::hash::Hash;
 use std::ops::Deref;
-use std::rc::Rc;
+use std::sync::{Arc, RwLock};
 
 /// A name is a lightweight 32 bit tag that refers to a string in a name table.
 /// During parsing, encountered strings are inserted into the name table and
@@ -45,6 +44,12 @@ impl fmt::Display for Name {
     }
 }
 
+impl serde::Serialize for Name {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_str())
+    }
+}
+
 // impl Encodable for Name {
 //     fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
 //         s.emit_bool(self.is_case_sensitive())?;
@@ -74,20 +79,20 @@ impl<'a> From<&'a str> for Name {
 }
 
 /// A reference-counted string that acts like a regular str slice, hiding the
-/// fact that it is wrapped in Rc<>.
+/// fact that it is wrapped in Arc<>.
 #[derive(Clone, PartialEq, Hash, PartialOrd)]
-pub struct RcStr(Rc<String>);
+pub struct RcStr(Arc<String>);
 
 impl RcStr {
     /// Create a new ref-counted string which is a copy of `value`.
     pub fn new(value: &str) -> RcStr {
-        RcStr(Rc::new(value.to_string()))
+        RcStr(Arc::new(value.to_string()))
     }
 
     /// Create a new ref-counted string that contains `value`, without
     /// allocating any new storage.
     pub fn from(value: String) -> RcStr {
-        RcStr(Rc::new(value))
+        RcStr(Arc::new(value))
     }
 }
 
@@ -130,77 +135,160 @@ impl Into<String> for RcStr {
     }
 }
 
+/// The number of shards of a name table. Must be a power of two.
+const NUM_SHARDS: usize = 16;
+
 /// A lookup table of names. Internalizes strings either in a case sensitive or
 /// case insensitive way. Allows for bidirectional lookup, i.e. by string or by
-/// assigned name.
+/// assigned name. The table may be shared among threads.
+///
+/// The table is split into shards, each with its own lock, such that threads
+/// interning different names rarely wait for each other. A string and its
+/// lowercase form always go into the same shard. Each name encodes the shard
+/// it lives in, such that looking up its string only locks that shard.
 pub struct NameTable {
-    map: RefCell<HashMap<RcStr, Name>>,
-    vect: RefCell<Vec<RcStr>>,
+    shards: [RwLock<Shard>; NUM_SHARDS],
+}
+
+/// One shard of a name table.
+#[derive(Default)]
+struct Shard {
+    map: HashMap<RcStr, Name>,
+    vect: Vec<RcStr>,
 }
 
 impl NameTable {
     /// Create a new empty name table.
     pub fn new() -> NameTable {
         NameTable {
-            map: RefCell::new(HashMap::new()),
-            vect: RefCell::new(Vec::new()),
+            shards: Default::default(),
         }
     }
 
+    /// Determine the shard a string goes into.
+    ///
+    /// Hashes the lowercase form of the string, such that the case insensitive
+    /// variants of a string end up in the same shard.
+    fn shard_index(value: &str) -> usize {
+        let hash = value
+            .chars()
+            .flat_map(char::to_lowercase)
+            .fold(0xcbf29ce484222325u64, |h, c| {
+                (h ^ c as u64).wrapping_mul(0x100000001b3)
+            });
+        (hash >> 32) as usize % NUM_SHARDS
+    }
+
     /// Obtain a name for a string. This either inserts the string into the
     /// table and returns the new name, or returns the existing name if the
     /// string already exists in the table.
     pub fn intern(&self, value: &str, case_sensitive: bool) -> Name {
-        let mut map = self.map.borrow_mut();
-        if let Some(&idx) = map.get(value) {
+        let index = Self::shard_index(value);
+        if let Some(&idx) = self.shards[index].read().unwrap().map.get(value) {
+            return idx;
+        }
+        let mut shard = self.shards[index].write().unwrap();
+        if let Some(&idx) = shard.map.get(value) {
             return idx;
         }
 
         // Since the name is not present in the table yet, we allocate a new idx
         // for it. Also, if it is a case-insensitive name, we insert both its
         // original form as well as its lowercase form into the lookup table.
-        let mut vect = self.vect.borrow_mut();
+        let new_idx = ((shard.vect.len() * NUM_SHARDS + index) as u32) << 1;
         if case_sensitive {
-            let new_idx = Name((vect.len() as u32) << 1 | 1);
+            let new_idx = Name(new_idx | 1);
             let v = RcStr::new(value);
-            map.insert(v.clone(), new_idx);
-            vect.push(v);
+            shard.map.insert(v.clone(), new_idx);
+            shard.vect.push(v);
             new_idx
         } else {
-            let new_idx = Name((vect.len() as u32) << 1 | 0);
+            let new_idx = Name(new_idx);
             let lower = value.to_lowercase();
-            if let Some(&idx) = map.get(lower.as_str()) {
+            if let Some(&idx) = shard.map.get(lower.as_str()) {
                 return idx;
             }
             let v = RcStr::new(value);
-            map.insert(RcStr::from(lower), new_idx);
-            map.insert(v.clone(), new_idx);
-            vect.push(v);
+            shard.map.insert(RcStr::from(lower), new_idx);
+            shard.map.insert(v.clone(), new_idx);
+            shard.vect.push(v);
             new_idx
         }
     }
 
     /// Retrieve the string given a name tag.
     pub fn get(&self, idx: Name) -> RcStr {
-        (*self.vect.borrow())[(idx.0 >> 1) as usize].clone()
+        let idx = (idx.0 >> 1) as usize;
+        self.shards[idx % NUM_SHARDS].read().unwrap().vect[idx / NUM_SHARDS].clone()
     }
 
     /// Try to find a string.
-    pub fn find<Q: ?Sized>(&self, value: &Q) -> Option<Name>
-    where
-        RcStr: Borrow<Q>,
-        Q: Eq + Hash,
-    {
-        (*self.map.borrow()).get(value).map(|v| *v)
-    }
-}
-
-/// Get this thread's current name table.
-pub fn get_name_table() -> Rc<NameTable> {
-    thread_local!(static TBL: Rc<NameTable> = {
-        let nt = NameTable::new();
-        // token::prefill_name_table(&mut nt);
-        Rc::new(nt)
-    });
-    TBL.with(|x| x.clone())
+    pub fn find(&self, value: &str) -> Option<Name> {
+        self.shards[Self::shard_index(value)]
+            .read()
+            .unwrap()
+            .map
+            .get(value)
+            .copied()
+    }
+}
+
+/// Get the global name table.
+pub fn get_name_table() -> &'static NameTable {
+    static TBL: Lazy<NameTable> = Lazy::new(NameTable::new);
+    &TBL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Names are compared with `assert!`, since printing them would look them
+    // up in the global name table rather than the one under test.
+
+    #[test]
+    fn intern() {
+        let tbl = NameTable::new();
+        let a = tbl.intern("foo", true);
+        let b = tbl.intern("bar", true);
+        assert!(a != b);
+        assert!(tbl.intern("foo", true) == a);
+        assert_eq!(&*tbl.get(a), "foo");
+        assert_eq!(&*tbl.get(b), "bar");
+        assert!(tbl.find("bar") == Some(b));
+        assert!(tbl.find("baz").is_none());
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let tbl = NameTable::new();
+        let a = tbl.intern("Foo", false);
+        assert!(!a.is_case_sensitive());
+        assert!(tbl.intern("FOO", false) == a);
+        assert!(tbl.intern("foo", false) == a);
+        assert_eq!(&*tbl.get(a), "Foo");
+        assert!(tbl.intern("FOO", true).is_case_sensitive());
+    }
+
+    #[test]
+    fn concurrent() {
+        let tbl = Arc::new(NameTable::new());
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let tbl = tbl.clone();
+                std::thread::spawn(move || {
+                    (0..1000)
+                        .map(|i| tbl.intern(&format!("n{}", i), true))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let names: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+        for other in &names[1..] {
+            assert!(other == &names[0]);
+        }
+        for (i, &name) in names[0].iter().enumerate() {
+            assert_eq!(&*tbl.get(name), format!("n{}", i));
+        }
+    }
 }
@@ -2,19 +2,19 @@
 
 //! A global source file table that assigns an opaque ID to each processed
 //! source file. This helps keeping the source location lean and allow for
-//! simple querying of information.
+//! simple querying of information. The table is shared among all threads,
+//! such that files may be processed concurrently.
 
+use crate::fs::get_file_system;
 use crate::name::RcStr;
-use memmap::Mmap;
+use once_cell::sync::Lazy;
 use std;
 use std::borrow::Borrow;
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
-use std::fs::File;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex, RwLock};
 
 pub const INVALID_SOURCE: Source = Source(0);
 pub const INVALID_LOCATION: Location = Location {
@@ -37,7 +37,7 @@ impl Source {
     }
 
     /// Access the contents of this source file.
-    pub fn get_content(self) -> Rc<dyn SourceContent> {
+    pub fn get_content(self) -> Arc<dyn SourceContent> {
         get_source_manager().with(self, |x| x.get_content())
     }
 
@@ -65,6 +65,16 @@ impl fmt::Display for Source {
     }
 }
 
+impl serde::Serialize for Source {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0 > 0 {
+            serializer.serialize_str(&self.get_path())
+        } else {
+            serializer.serialize_none()
+        }
+    }
+}
+
 // impl Encodable for Source {
 //     fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
 //         s.emit_bool(self.0 == 0)?;
@@ -90,7 +100,7 @@ impl fmt::Display for Source {
 //     }
 // }
 
-pub trait SourceFile {
+pub trait SourceFile: Send + Sync {
     fn get_id(&self) -> Source;
     fn get_path(&self) -> RcStr;
     // TODO: getter for character iterator
@@ -98,7 +108,7 @@ pub trait SourceFile {
 
     /// Obtain the content of this source file. The returned object may be used
     /// to iterate over the characters in the file or extract portions of it.
-    fn get_content(&self) -> Rc<dyn SourceContent>;
+    fn get_content(&self) -> Arc<dyn SourceContent>;
 
     /// Copy a range of the source content into a String instance owned by the
     /// caller, possibly converting the encoding such that the result is in
@@ -108,7 +118,7 @@ pub trait SourceFile {
     }
 }
 
-pub trait SourceContent {
+pub trait SourceContent: Send + Sync {
     /// Obtain an iterator over the characters within the source file, together
     /// with their respective byte positions.
     fn iter(&self) -> Box<CharIter>;
@@ -135,15 +145,18 @@ pub trait SourceContent {
 
 /// A manager for source files and their assigned IDs.
 pub struct SourceManager {
-    map: RefCell<HashMap<RcStr, Source>>,
-    vect: RefCell<Vec<Box<dyn SourceFile>>>,
+    map: RwLock<HashMap<RcStr, Source>>,
+    vect: RwLock<Vec<Arc<dyn SourceFile>>>,
+    /// The released anonymous sources, whose IDs may be reused.
+    free: Mutex<Vec<Source>>,
 }
 
 impl SourceManager {
     fn new() -> SourceManager {
         SourceManager {
-            map: RefCell::new(HashMap::new()),
-            vect: RefCell::new(Vec::new()),
+            map: RwLock::new(HashMap::new()),
+            vect: RwLock::new(Vec::new()),
+            free: Mutex::new(Vec::new()),
         }
     }
 
@@ -152,15 +165,20 @@ impl SourceManager {
     where
         F: FnOnce(&dyn SourceFile) -> R,
     {
-        let ref vect = *self.vect.borrow();
-        assert!(id.0 > 0, "invalid source");
-        assert!(
-            (id.0 as usize - 1) < vect.len(),
-            "unknown source file: Source({}) >= {}",
-            id.0,
-            vect.len()
-        );
-        f(&*vect[id.0 as usize - 1])
+        // Release the lock before calling `f`, which may access the manager
+        // itself.
+        let file = {
+            let vect = self.vect.read().unwrap();
+            assert!(id.0 > 0, "invalid source");
+            assert!(
+                (id.0 as usize - 1) < vect.len(),
+                "unknown source file: Source({}) >= {}",
+                id.0,
+                vect.len()
+            );
+            vect[id.0 as usize - 1].clone()
+        };
+        f(&*file)
     }
 
     pub fn find<Q: ?Sized>(&self, filename: &Q) -> Option<Source>
@@ -168,26 +186,27 @@ impl SourceManager {
         RcStr: Borrow<Q>,
         Q: Eq + Hash,
     {
-        (*self.map.borrow()).get(filename).map(|v| *v)
+        self.map.read().unwrap().get(filename).copied()
     }
 
     pub fn open(&self, filename: &str) -> Option<Source> {
         // Check if the file has already been opened and return its pointer.
-        let mut map = self.map.borrow_mut();
+        let mut map = self.map.write().unwrap();
         if let Some(&id) = map.get(filename) {
             return Some(id);
         }
 
         // Check whether the file exists and allocate a new index for it.
-        if Path::new(filename).exists() {
-            let mut vect = self.vect.borrow_mut();
+        // Directories exist, but cannot be read as a file.
+        if get_file_system().is_file(Path::new(filename)) {
+            let mut vect = self.vect.write().unwrap();
             let new_id = Source(vect.len() as u32 + 1);
             let v = RcStr::new(filename);
             map.insert(v.clone(), new_id);
-            vect.push(Box::new(DiskSourceFile {
+            vect.push(Arc::new(DiskSourceFile {
                 id: new_id,
                 filename: v,
-                content: RefCell::new(None),
+                content: Mutex::new(None),
             }));
             Some(new_id)
         } else {
@@ -199,20 +218,39 @@ impl SourceManager {
     /// source manager. Future calls to `open()` with the given filename will
     /// yield the provided contents.
     pub fn add(&self, filename: &str, content: &str) -> Source {
-        let mut map = self.map.borrow_mut();
+        let mut map = self.map.write().unwrap();
         assert!(
             !map.contains_key(filename),
             "add failed: source \"{}\" already exists",
             filename
         );
-        let mut vect = self.vect.borrow_mut();
+        let mut vect = self.vect.write().unwrap();
+        let new_id = Source(vect.len() as u32 + 1);
+        let v = RcStr::new(filename);
+        map.insert(v.clone(), new_id);
+        vect.push(Arc::new(VirtualSourceFile {
+            id: new_id,
+            filename: v,
+            content: Arc::new(VirtualSourceContent(content.to_string())),
+        }));
+        new_id
+    }
+
+    /// Create a virtual file from the contents of a string and add it to the
+    /// source manager, replacing any file of the same name. Future calls to
+    /// `open()` with the given filename will yield the provided contents,
+    /// whereas sources previously obtained for the file keep their contents.
+    /// Useful for the unsaved contents of an editor.
+    pub fn replace(&self, filename: &str, content: &str) -> Source {
+        let mut map = self.map.write().unwrap();
+        let mut vect = self.vect.write().unwrap();
         let new_id = Source(vect.len() as u32 + 1);
         let v = RcStr::new(filename);
         map.insert(v.clone(), new_id);
-        vect.push(Box::new(VirtualSourceFile {
+        vect.push(Arc::new(VirtualSourceFile {
             id: new_id,
             filename: v,
-            content: Rc::new(VirtualSourceContent(content.to_string())),
+            content: Arc::new(VirtualSourceContent(content.to_string())),
         }));
         new_id
     }
@@ -225,23 +263,78 @@ impl SourceManager {
     where
         S: Into<String>,
     {
-        let mut vect = self.vect.borrow_mut();
-        let new_id = Source(vect.len() as u32 + 1);
-        vect.push(Box::new(VirtualSourceFile {
+        let mut vect = self.vect.write().unwrap();
+        let reused = self.free.lock().unwrap().pop();
+        let new_id = reused.unwrap_or(Source(vect.len() as u32 + 1));
+        let file = Arc::new(VirtualSourceFile {
             id: new_id,
             filename: RcStr::new("<anonymous>"),
-            content: Rc::new(VirtualSourceContent(content.into())),
-        }));
+            content: Arc::new(VirtualSourceContent(content.into())),
+        });
+        if reused.is_some() {
+            vect[new_id.0 as usize - 1] = file;
+        } else {
+            vect.push(file);
+        }
         new_id
     }
+
+    /// Drop the contents of an anonymous file and allow its ID to be reused by
+    /// a later call to `add_anonymous`.
+    fn release_anonymous(&self, id: Source) {
+        let mut vect = self.vect.write().unwrap();
+        vect[id.0 as usize - 1] = Arc::new(VirtualSourceFile {
+            id,
+            filename: RcStr::new("<anonymous>"),
+            content: Arc::new(VirtualSourceContent(String::new())),
+        });
+        self.free.lock().unwrap().push(id);
+    }
+
+    /// Drop the contents of a file created with `replace` and allow its ID to
+    /// be reused. Spans into the file must no longer be used. Future calls to
+    /// `open()` with its filename read the file from disk again, unless it has
+    /// been replaced since.
+    pub fn release(&self, id: Source) {
+        self.map.write().unwrap().retain(|_, &mut v| v != id);
+        self.release_anonymous(id);
+    }
+}
+
+/// An anonymous file which is released from the global source manager when
+/// dropped.
+///
+/// Spans into the file must not be used after it has been dropped, since its
+/// ID may then refer to a different file. Useful for tools which process a
+/// large number of short-lived inputs, such as fuzz targets.
+pub struct AnonymousSource(Source);
+
+impl AnonymousSource {
+    /// Add a virtual file with the contents of a string to the global source
+    /// manager.
+    pub fn new<S>(content: S) -> AnonymousSource
+    where
+        S: Into<String>,
+    {
+        AnonymousSource(get_source_manager().add_anonymous(content))
+    }
+
+    /// Get the ID of the file.
+    pub fn source(&self) -> Source {
+        self.0
+    }
+}
+
+impl Drop for AnonymousSource {
+    fn drop(&mut self) {
+        get_source_manager().release_anonymous(self.0);
+    }
 }
 
 /// Get the global source manager.
-pub fn get_source_manager() -> Rc<SourceManager> {
-    thread_local!(static MNGR: Rc<SourceManager> = {
-        Rc::new(SourceManager::new())
-    });
-    MNGR.with(|x| x.clone())
+pub fn get_source_manager() -> &'static SourceManager {
+    static MNGR: Lazy<SourceManager> = Lazy::new(SourceManager::new);
+    &MNGR
 }
 
 /// A virtual source file that has no correspondence in the file system. Useful
@@ -249,10 +342,10 @@ pub fn get_source_manager() -> Rc<SourceManager> {
 struct VirtualSourceFile {
     id: Source,
     filename: RcStr,
-    content: Rc<VirtualSourceContent>,
+    content: Arc<VirtualSourceContent>,
 }
 
-struct VirtualSourceContent(pub String);
+pub(crate) struct VirtualSourceContent(pub String);
 
 impl SourceFile for VirtualSourceFile {
     fn get_id(&self) -> Source {
@@ -263,7 +356,7 @@ impl SourceFile for VirtualSourceFile {
         self.filename.clone()
     }
 
-    fn get_content(&self) -> Rc<dyn SourceContent> {
+    fn get_content(&self) -> Arc<dyn SourceContent> {
         self.content.clone()
     }
 }
@@ -290,16 +383,14 @@ impl SourceContent for VirtualSourceContent {
     }
 }
 
-/// A source file on disk.
+/// A source file in the file system, which is read when its contents are
+/// first accessed.
 struct DiskSourceFile {
     id: Source,
     filename: RcStr,
-    content: RefCell<Option<Rc<DiskSourceContent>>>,
+    content: Mutex<Option<Arc<dyn SourceContent>>>,
 }
 
-#[derive(Debug)]
-struct DiskSourceContent(pub Mmap);
-
 impl SourceFile for DiskSourceFile {
     fn get_id(&self) -> Source {
         self.id
@@ -309,45 +400,20 @@ impl SourceFile for DiskSourceFile {
         self.filename.clone()
     }
 
-    fn get_content(&self) -> Rc<dyn SourceContent> {
-        let is_none = self.content.borrow().is_none();
-        if is_none {
-            let c = Rc::new(DiskSourceContent(unsafe {
-                Mmap::map(&File::open(&*self.filename).unwrap()).unwrap()
-            }));
-            *self.content.borrow_mut() = Some(c.clone());
-            c
-        } else {
-            self.content.borrow().clone().unwrap()
+    fn get_content(&self) -> Arc<dyn SourceContent> {
+        let mut content = self.content.lock().unwrap();
+        if content.is_none() {
+            *content = Some(
+                crate::fs::read(Path::new(&*self.filename))
+                    .unwrap_or_else(|e| panic!("unable to read `{}`: {}", self.filename, e)),
+            );
         }
+        content.clone().unwrap()
     }
 }
 
-impl SourceContent for DiskSourceContent {
-    fn iter(&self) -> Box<CharIter> {
-        use std::str;
-        Box::new(str::from_utf8(&self.0[..]).unwrap().char_indices())
-    }
-
-    fn iter_from(&self, offset: usize) -> Box<CharIter> {
-        use std::str;
-        Box::new(str::from_utf8(&self.0[offset..]).unwrap().char_indices())
-    }
-
-    fn extract(&self, begin: usize, end: usize) -> String {
-        use std::str;
-        str::from_utf8(&self.0[begin..end]).unwrap().to_string()
-    }
-
-    fn extract_iter(&self, begin: usize, end: usize) -> Box<CharIter> {
-        use std::str;
-        Box::new(str::from_utf8(&self.0[begin..end]).unwrap().char_indices())
-    }
-
-    fn bytes(&self) -> &[u8] {
-        &self.0[..]
-    }
-}
+/// The size of the largest source file that spans can refer into.
+pub(crate) const MAX_SOURCE_SIZE: u64 = u32::MAX as u64;
 
 /// An iterator that yields the characters from an input file together with the
 /// byte positions within the stream.
@@ -370,7 +436,7 @@ impl Location {
     }
 
     /// Obtain an iterator into the source file at this location.
-    pub fn iter<'a>(self, content: &'a Rc<dyn SourceContent>) -> Box<CharIter<'a>> {
+    pub fn iter<'a>(self, content: &'a Arc<dyn SourceContent>) -> Box<CharIter<'a>> {
         content.iter_from(self.offset)
     }
 
@@ -423,8 +489,42 @@ impl Location {
     pub fn human_line_offset(self) -> usize {
         self.human().2
     }
+
+    /// Obtain an integer which refers to this location.
+    ///
+    /// This is useful to attach locations to LLHD instructions as location
+    /// hints, which are plain integers. The locations are kept in a global
+    /// table which the hint indexes into, such that any location fits into a
+    /// hint regardless of the width of `usize`.
+    pub fn to_hint(self) -> usize {
+        let mut table = HINTS.lock().unwrap();
+        if let Some(&hint) = table.indices.get(&self) {
+            return hint;
+        }
+        let hint = table.locations.len();
+        table.locations.push(self);
+        table.indices.insert(self, hint);
+        hint
+    }
+
+    /// Look up the location referred to by a hint obtained from `to_hint`.
+    ///
+    /// Returns `None` for other hints, such as the ones read from an LLHD
+    /// assembly file.
+    pub fn from_hint(hint: usize) -> Option<Location> {
+        HINTS.lock().unwrap().locations.get(hint).copied()
+    }
 }
 
+/// The locations handed out as hints by `Location::to_hint`.
+#[derive(Default)]
+struct HintTable {
+    locations: Vec<Location>,
+    indices: HashMap<Location, usize>,
+}
+
+static HINTS: Lazy<Mutex<HintTable>> = Lazy::new(Default::default);
+
 impl fmt::Debug for Location {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}:{}", self.source, self.offset)
@@ -439,20 +539,24 @@ impl From<Location> for Span {
 
 /// A span of locations within a source file, expressed as a half-open interval
 /// of bytes `[begin,end)`.
-#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+///
+/// Spans are stored in almost every node of the syntax trees, so they are kept
+/// compact at 12 bytes. This limits source files to 4 GiB.
+#[derive(Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Hash, serde::Serialize)]
 pub struct Span {
     pub source: Source,
-    pub begin: usize,
-    pub end: usize,
+    pub begin: u32,
+    pub end: u32,
 }
 
 impl Span {
     /// Create a new span from two byte offsets.
     pub fn new(source: Source, begin: usize, end: usize) -> Span {
+        debug_assert!(end <= MAX_SOURCE_SIZE as usize);
         Span {
             source: source,
-            begin: begin,
-            end: end,
+            begin: begin as u32,
+            end: end as u32,
         }
     }
 
@@ -488,23 +592,121 @@ impl Span {
 
     /// Return the location just before the first character in this span.
     pub fn begin(&self) -> Location {
-        Location::new(self.source, self.begin)
+        Location::new(self.source, self.begin as usize)
     }
 
     /// Return the location just after the last character in this span.
     pub fn end(&self) -> Location {
-        Location::new(self.source, self.end)
+        Location::new(self.source, self.end as usize)
     }
 
     /// Copy the portion of the source file in this span into an owned string.
     pub fn extract(&self) -> String {
-        self.source.get_content().extract(self.begin, self.end)
+        self.source
+            .get_content()
+            .extract(self.begin as usize, self.end as usize)
+    }
+
+    /// Find the doc comment immediately preceding this span.
+    ///
+    /// A doc comment is either a run of consecutive lines which start with the
+    /// line comment marker `line`, such as `///`, or a single block comment
+    /// starting with `/**`. Only whitespace may separate the comment from the
+    /// span. Returns the text of the comment without the comment markers,
+    /// together with the span of the comment.
+    pub fn doc_comment(&self, line: &str) -> Option<Spanned<String>> {
+        if self.source == INVALID_SOURCE {
+            return None;
+        }
+        let content = self.source.get_content();
+        let bytes = content.bytes();
+        let skip_space = |mut pos: usize| {
+            while pos > 0 && bytes[pos - 1].is_ascii_whitespace() {
+                pos -= 1;
+            }
+            pos
+        };
+        let end = skip_space(self.begin as usize);
+
+        // Block comments.
+        if bytes[..end].ends_with(b"*/") {
+            let begin = (0..end - 2)
+                .rev()
+                .find(|&i| bytes[i..].starts_with(b"/*"))?;
+            let text = &bytes[begin..end];
+            if !text.starts_with(b"/**") || text.starts_with(b"/***") || text.len() < 5 {
+                return None;
+            }
+            let text = String::from_utf8_lossy(&text[3..text.len() - 2]);
+            let lines: Vec<_> = text
+                .lines()
+                .map(|l| {
+                    let l = l.trim_start();
+                    let l = l.strip_prefix('*').unwrap_or(l);
+                    l.strip_prefix(' ').unwrap_or(l).trim_end()
+                })
+                .collect();
+            let first = lines.iter().position(|l| !l.is_empty()).unwrap_or(0);
+            let last = lines
+                .iter()
+                .rposition(|l| !l.is_empty())
+                .map_or(0, |i| i + 1);
+            return Some(Spanned::new(
+                lines[first..last.max(first)].join("\n"),
+                Span::new(self.source, begin, end),
+            ));
+        }
+
+        // Consecutive line comments.
+        let marker = line.as_bytes();
+        let mut lines = vec![];
+        let mut begin = end;
+        let mut pos = end;
+        loop {
+            let line_begin = bytes[..pos]
+                .iter()
+                .rposition(|&c| c == b'\n')
+                .map_or(0, |i| i + 1);
+            let indent = bytes[line_begin..pos]
+                .iter()
+                .take_while(|c| c.is_ascii_whitespace())
+                .count();
+            let text = &bytes[line_begin + indent..pos];
+            if !text.starts_with(marker)
+                || text[marker.len()..].starts_with(&marker[marker.len() - 1..])
+            {
+                break;
+            }
+            let text = String::from_utf8_lossy(&text[marker.len()..]);
+            lines.push(
+                text.strip_prefix(' ')
+                    .unwrap_or(&text)
+                    .trim_end()
+                    .to_string(),
+            );
+            begin = line_begin + indent;
+            if line_begin == 0 {
+                break;
+            }
+            pos = line_begin - 1;
+            if pos > 0 && bytes[pos - 1] == b'\r' {
+                pos -= 1;
+            }
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        lines.reverse();
+        Some(Spanned::new(
+            lines.join("\n"),
+            Span::new(self.source, begin, end),
+        ))
     }
 
     /// Obtain an iterator over the extract of the source file describe by this
     /// span.
-    pub fn iter<'a>(self, content: &'a Rc<dyn SourceContent>) -> Box<CharIter<'a>> {
-        content.extract_iter(self.begin, self.end)
+    pub fn iter<'a>(self, content: &'a Arc<dyn SourceContent>) -> Box<CharIter<'a>> {
+        content.extract_iter(self.begin as usize, self.end as usize)
     }
 }
 
@@ -515,7 +717,7 @@ impl fmt::Debug for Span {
 }
 
 /// A wrapper that associates a span with a value.
-#[derive(PartialOrd, Ord, PartialEq, Eq)]
+#[derive(PartialOrd, Ord, PartialEq, Eq, serde::Serialize)]
 pub struct Spanned<T> {
     pub value: T,
     pub span: Span,
@@ -604,7 +806,7 @@ mod tests {
     #[test]
     #[should_panic(expected = "unknown source file")]
     fn unknown_source_id() {
-        get_source_manager().with(Source(1), |_| ());
+        get_source_manager().with(Source(u32::max_value()), |_| ());
     }
 
     #[test]
@@ -615,6 +817,52 @@ mod tests {
         assert_eq!(source, id);
     }
 
+    #[test]
+    fn reuse_anonymous() {
+        let sm = SourceManager::new();
+        let a = sm.add_anonymous("first");
+        let b = sm.add_anonymous("second");
+        sm.release_anonymous(a);
+        sm.with(a, |f| assert_eq!(f.extract(0, 0), ""));
+        let c = sm.add_anonymous("third");
+        assert_eq!(c, a);
+        sm.with(c, |f| assert_eq!(f.extract(0, 5), "third"));
+        sm.with(b, |f| assert_eq!(f.extract(0, 6), "second"));
+    }
+
+    #[test]
+    fn location_hint() {
+        let loc = Location::new(Source(u32::MAX), u32::MAX as usize);
+        assert_eq!(Location::from_hint(loc.to_hint()), Some(loc));
+        assert_eq!(loc.to_hint(), loc.to_hint());
+        assert_eq!(Location::from_hint(usize::MAX), None);
+    }
+
+    #[test]
+    fn compact_span() {
+        assert_eq!(std::mem::size_of::<Span>(), 12);
+        let sp = Span::new(Source(42), 1234, 5678);
+        assert_eq!(sp.begin(), Location::new(Source(42), 1234));
+        assert_eq!(sp.end(), Location::new(Source(42), 5678));
+    }
+
+    #[test]
+    fn doc_comment() {
+        let sm = get_source_manager();
+        let text =
+            "x;\n/// Hello\n///   World\nmodule a;\n// x\nmodule b;\n/**\n * Block\n */ module c;";
+        let source = sm.add("doc_comment.sv", text);
+        let doc = |name: &str| {
+            let begin = text.find(name).unwrap();
+            Span::new(source, begin, begin + name.len())
+                .doc_comment("///")
+                .map(|d| d.value)
+        };
+        assert_eq!(doc("module a").as_deref(), Some("Hello\n  World"));
+        assert_eq!(doc("module b"), None);
+        assert_eq!(doc("module c").as_deref(), Some("Block"));
+    }
+
     #[test]
     fn inexistent_file() {
         let sm = get_source_manager();
@@ -651,4 +899,52 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn large_file() {
+        use std::fs::File;
+        use std::io::Write;
+        use std::path::Path;
+
+        let path = Path::new("/tmp/moore-test-large");
+        let line = "assign x = 老虎;\n";
+        let data = line.repeat(crate::fs::MMAP_THRESHOLD as usize / line.len() + 1);
+        File::create(path)
+            .unwrap()
+            .write_all(data.as_bytes())
+            .unwrap();
+
+        let sm = get_source_manager();
+        let source = sm.open(path.to_str().unwrap()).expect("file should exist");
+        let content = source.get_content();
+        assert_eq!(content.bytes(), data.as_bytes());
+        assert_eq!(content.extract(7, 8), "x");
+    }
+
+    #[test]
+    fn empty_file() {
+        use std::fs::File;
+        use std::path::Path;
+
+        let path = Path::new("/tmp/moore-test-empty");
+        File::create(path).unwrap();
+
+        let sm = get_source_manager();
+        let source = sm.open(path.to_str().unwrap()).expect("file should exist");
+        assert_eq!(source.get_content().iter().count(), 0);
+    }
+
+    #[test]
+    fn invalid_utf8_file() {
+        use std::fs::File;
+        use std::io::Write;
+        use std::path::Path;
+
+        let path = Path::new("/tmp/moore-test-invalid");
+        File::create(path).unwrap().write_all(b"a\xffb").unwrap();
+
+        let sm = get_source_manager();
+        let source = sm.open(path.to_str().unwrap()).expect("file should exist");
+        assert_eq!(source.get_content().extract(0, 5), "a\u{fffd}b");
+    }
 }
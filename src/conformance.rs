@@ -0,0 +1,267 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Conformance testing of the SystemVerilog parser.
+//!
+//! Runs the parser over a corpus of test files laid out like
+//! [sv-tests](https://github.com/chipsalliance/sv-tests), and checks that each
+//! file parses or fails to parse as expected. Each test file describes itself
+//! in `:key: value` lines, usually in a comment at the top of the file:
+//!
+//! ```text
+//! /*
+//! :name: ansi_port_list
+//! :description: module with an ANSI port list
+//! :tags: 23.2.2.2
+//! */
+//! ```
+//!
+//! A file with a `:should_fail_because:` or `:should_fail: 1` line is expected
+//! to be rejected, unless its `:type:` line names only later stages such as
+//! `elaboration` or `simulation`, which the parser cannot check. The first of
+//! the `:tags:` is the section of the standard the file tests, and the results
+//! are summarized per clause. Files without tags are summarized under the name
+//! of their directory.
+
+use crate::common::errors::{capture_diags, DiagBuilder2};
+use crate::common::source::get_source_manager;
+use crate::svlog::{lexer::Lexer, parser, preproc::Preprocessor, token::Standard};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// How to parse the test files.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// The revision of the standard to parse the files as.
+    pub standard: Standard,
+    /// Whether to parse the files as Verilog.
+    pub verilog: bool,
+    /// The include search paths, besides the directory of each file.
+    pub include_paths: Vec<PathBuf>,
+    /// The macros to predefine, with their optional value.
+    pub defines: Vec<(String, Option<String>)>,
+    /// The number of threads to parse the files with.
+    pub jobs: usize,
+}
+
+/// The outcome of a test file.
+#[derive(Debug)]
+pub struct TestResult {
+    /// The path of the file.
+    pub path: String,
+    /// The section of the standard the file tests, or its directory.
+    pub section: String,
+    /// Whether the file is expected to parse.
+    pub should_pass: bool,
+    /// Whether the file parsed.
+    pub passed: bool,
+    /// Whether the parser panicked.
+    pub crashed: bool,
+}
+
+impl TestResult {
+    /// Whether the file behaved as expected.
+    pub fn ok(&self) -> bool {
+        !self.crashed && self.passed == self.should_pass
+    }
+}
+
+/// The outcomes of all files of a corpus, in the order of their paths.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub results: Vec<TestResult>,
+}
+
+impl Report {
+    /// Run the parser over the SystemVerilog and Verilog files below a
+    /// directory.
+    pub fn run(dir: &str, opts: &Options) -> Result<Report, DiagBuilder2> {
+        let files: Vec<_> = crate::inputs::expand(std::iter::once(dir))?
+            .into_iter()
+            .filter(|f| f.ends_with(".sv") || f.ends_with(".v"))
+            .collect();
+        if files.is_empty() {
+            return Err(DiagBuilder2::fatal(format!(
+                "no SystemVerilog or Verilog files in `{}`",
+                dir
+            )));
+        }
+
+        // Panics are reported as crashes of the test file instead of being
+        // printed.
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|_| {}));
+        let jobs = opts.jobs;
+        let opts = opts.clone();
+        let results = crate::parallel::map(files, jobs, move |path| run_test(path, &opts));
+        std::panic::set_hook(hook);
+        Ok(Report { results })
+    }
+
+    /// The files which did not behave as expected.
+    pub fn failures(&self) -> impl Iterator<Item = &TestResult> {
+        self.results.iter().filter(|r| !r.ok())
+    }
+
+    /// Render a table of the number of tests and the fraction that behave as
+    /// expected, per clause of the standard and in total.
+    pub fn summary(&self) -> String {
+        let mut sections: BTreeMap<SectionKey, (usize, usize)> = BTreeMap::new();
+        for result in &self.results {
+            let clause = result.section.split('.').next().unwrap_or_default();
+            let entry = sections.entry(SectionKey::new(clause)).or_default();
+            entry.0 += 1;
+            entry.1 += result.ok() as usize;
+        }
+        let total = sections
+            .values()
+            .fold((0, 0), |acc, x| (acc.0 + x.0, acc.1 + x.1));
+        let width = sections
+            .keys()
+            .map(|k| k.name.len())
+            .chain(Some(7))
+            .max()
+            .unwrap();
+        let mut output = String::new();
+        let mut row = |name: &str, (tests, passed): (usize, usize)| {
+            writeln!(
+                output,
+                "{:<width$}  {:>6}  {:>6}  {:>6.1}%",
+                name,
+                tests,
+                passed,
+                passed as f64 * 100.0 / tests as f64,
+                width = width
+            )
+            .unwrap();
+        };
+        row("total", total);
+        for (key, &counts) in &sections {
+            row(&key.name, counts);
+        }
+        let mut header = String::new();
+        writeln!(
+            header,
+            "{:<width$}  {:>6}  {:>6}  {:>7}",
+            "section",
+            "tests",
+            "passed",
+            "rate",
+            width = width
+        )
+        .unwrap();
+        header + &output
+    }
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = if self.crashed {
+            "parser crashed on"
+        } else if self.passed {
+            "unexpected pass of"
+        } else {
+            "unexpected failure of"
+        };
+        write!(f, "{} {}", what, self.path)
+    }
+}
+
+/// A section name, ordered numerically if it is a number. Numbered sections
+/// go before named ones.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct SectionKey {
+    number: u32,
+    name: String,
+}
+
+impl SectionKey {
+    fn new(name: &str) -> Self {
+        SectionKey {
+            number: name.parse().unwrap_or(u32::MAX),
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Parse a test file and compare the outcome against its metadata.
+fn run_test(path: String, opts: &Options) -> TestResult {
+    let content = crate::fs::read_to_string(Path::new(&path)).unwrap_or_default();
+    let meta = Metadata::parse(&content);
+    let section = meta.tags.first().cloned().unwrap_or_else(|| {
+        Path::new(&path)
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+    let checks_parsing = meta.types.is_empty() || meta.types.iter().any(|t| t == "parsing");
+    let should_pass = !(meta.should_fail && checks_parsing);
+
+    let include_paths: Vec<_> = opts.include_paths.iter().map(|p| p.as_path()).collect();
+    let defines: Vec<_> = opts
+        .defines
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_deref()))
+        .collect();
+    let parsed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let source = match get_source_manager().open(&path) {
+            Some(x) => x,
+            None => return false,
+        };
+        let (result, _) = capture_diags(|| {
+            let arena = crate::svlog::ast::Arena::default();
+            let preproc =
+                Preprocessor::new(source, &include_paths, &defines).with_standard(opts.standard);
+            let lexer = Lexer::new(preproc);
+            let result = if opts.verilog {
+                parser::parse_verilog(lexer, &arena)
+            } else {
+                parser::parse(lexer, &arena)
+            };
+            result.is_ok()
+        });
+        result
+    }));
+    TestResult {
+        path,
+        section,
+        should_pass,
+        crashed: parsed.is_err(),
+        passed: parsed.unwrap_or(false),
+    }
+}
+
+/// The `:key: value` lines of a test file.
+#[derive(Debug, Default)]
+struct Metadata {
+    tags: Vec<String>,
+    types: Vec<String>,
+    should_fail: bool,
+}
+
+impl Metadata {
+    fn parse(content: &str) -> Metadata {
+        let mut meta = Metadata::default();
+        for line in content.lines() {
+            let line = line.trim_start();
+            let rest = match line.strip_prefix(':') {
+                Some(x) => x,
+                None => continue,
+            };
+            let (key, value) = match rest.find(':') {
+                Some(i) => (&rest[..i], rest[i + 1..].trim()),
+                None => continue,
+            };
+            let words = || value.split_whitespace().map(String::from).collect();
+            match key {
+                "tags" => meta.tags = words(),
+                "type" => meta.types = words(),
+                "should_fail_because" => meta.should_fail = true,
+                "should_fail" => meta.should_fail = value != "0",
+                _ => (),
+            }
+        }
+        meta
+    }
+}
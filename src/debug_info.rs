@@ -0,0 +1,82 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Source location debug info for LLHD output.
+//!
+//! The code generator annotates instructions with the source location they
+//! originate from as LLHD location hints. This module renders these hints as
+//! comments in front of each unit of the LLHD assembly, which keeps the output
+//! readable by LLHD tools. Each unit is preceded by a line of the form
+//!
+//! ```text
+//! ; loc @foo foo.sv:3:1
+//! ```
+//!
+//! which locates the unit itself, followed by one line per located line of
+//! the unit body, where `+N` refers to the N-th line after the unit header:
+//!
+//! ```text
+//! ; loc +2 foo.sv:5:5
+//! ```
+
+use crate::source::Location;
+use llhd::ir::prelude::*;
+
+/// Emit the assembly for an LLHD module, with source locations as comments.
+pub fn write_module(module: &Module) -> String {
+    let asm = llhd::assembly::write_module_string(module);
+    let mut units = module.units();
+    let mut output = String::with_capacity(asm.len());
+    for line in asm.lines() {
+        let is_header = ["entity ", "proc ", "func "]
+            .iter()
+            .any(|prefix| line.starts_with(prefix));
+        if is_header {
+            if let Some(unit) = units.next() {
+                write_unit_locations(&mut output, unit);
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// Emit the location comments for a single unit.
+fn write_unit_locations(output: &mut String, unit: Unit) {
+    // Determine the locations of the lines in the unit body, in the same
+    // order in which the assembly writer emits them.
+    let mut lines = vec![];
+    for block in unit.blocks() {
+        if !unit.is_entity() {
+            lines.push(None);
+        }
+        for inst in unit.insts(block) {
+            if unit[inst].opcode().is_terminator() && unit.is_entity() {
+                continue;
+            }
+            lines.push(unit.location_hint(inst));
+        }
+    }
+
+    // The unit itself is located at its earliest instruction.
+    let first = unit
+        .all_insts()
+        .filter_map(|inst| unit.location_hint(inst))
+        .filter_map(Location::from_hint)
+        .min();
+    if let Some(loc) = first {
+        output.push_str(&format!("; loc {} {}\n", unit.name(), format_location(loc)));
+    }
+    for (index, hint) in lines.into_iter().enumerate() {
+        if let Some(loc) = hint.and_then(Location::from_hint) {
+            let loc = format_location(loc);
+            output.push_str(&format!("; loc +{} {}\n", index + 1, loc));
+        }
+    }
+}
+
+/// Format a location as `file:line:column`.
+fn format_location(loc: Location) -> String {
+    let (line, column, _) = loc.human();
+    format!("{}:{}:{}", loc.source.get_path(), line, column)
+}
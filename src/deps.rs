@@ -0,0 +1,333 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Dependency graphs.
+//!
+//! Determines the design units defined in a set of parsed files and the units
+//! each of them depends on, such that build systems can derive the order in
+//! which files have to be compiled. A unit depends on the modules, entities,
+//! and components it instantiates, on the packages it imports or refers to as
+//! in `pkg::name`, and for VHDL architectures and package bodies, on their
+//! entity or package. Dependencies may name units which are not among the
+//! parsed files, such as library units.
+
+use crate::common::source::Source;
+use crate::library::used_units;
+use crate::score::Ast;
+use crate::svlog::ast as sv;
+use crate::vhdl::syntax::ast as vhdl;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// A design unit and its dependencies.
+#[derive(Debug, Serialize)]
+pub struct Unit {
+    /// The name of the unit. VHDL names are lowercase.
+    pub name: String,
+    /// The kind of unit, e.g. `"module"` or `"architecture"`.
+    pub kind: &'static str,
+    /// The file the unit is defined in.
+    pub file: Source,
+    /// The units this unit depends on, without duplicates.
+    pub deps: Vec<Dep>,
+}
+
+/// A dependency of a design unit.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Dep {
+    /// How the unit depends on the target.
+    pub kind: DepKind,
+    /// The name of the unit depended on. VHDL units in a library other than
+    /// `work` are named `lib.unit`.
+    pub target: String,
+}
+
+/// The ways in which a unit may depend on another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DepKind {
+    /// The unit instantiates the target.
+    Instance,
+    /// The unit imports the target, or refers to names within it.
+    Import,
+    /// The unit is the secondary unit of the target, e.g. an architecture of
+    /// an entity.
+    Primary,
+}
+
+impl DepKind {
+    /// Get the name of the dependency kind.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DepKind::Instance => "instance",
+            DepKind::Import => "import",
+            DepKind::Primary => "primary",
+        }
+    }
+}
+
+/// The design units in a set of files, in the order they are defined.
+#[derive(Debug, Default, Serialize)]
+pub struct Graph {
+    pub units: Vec<Unit>,
+}
+
+impl Graph {
+    /// Determine the units and dependencies in a list of parsed files.
+    pub fn new<'a>(asts: &'a [Ast<'a>]) -> Graph {
+        let mut graph = Graph::default();
+        for ast in asts {
+            match ast {
+                Ast::Svlog(file) => graph.add_svlog(file),
+                Ast::Vhdl(units) => graph.add_vhdl(units),
+            }
+        }
+        graph
+    }
+
+    fn add_svlog<'a>(&mut self, file: &'a sv::SourceFile<'a>) {
+        for item in &file.items {
+            let (kind, name, node): (_, _, &dyn sv::AcceptVisitor) = match item.data {
+                sv::ItemData::ModuleDecl(ref x) => ("module", x.name.value, x),
+                sv::ItemData::InterfaceDecl(ref x) => ("interface", x.name.value, x),
+                sv::ItemData::ProgramDecl(ref x) => ("program", x.name.value, x),
+                sv::ItemData::UdpDecl(ref x) => ("primitive", x.name.value, x),
+                sv::ItemData::PackageDecl(ref x) => ("package", x.name.value, x),
+                _ => continue,
+            };
+            let mut visitor = SvlogDeps::default();
+            node.accept(&mut visitor);
+            self.units.push(Unit {
+                name: name.to_string(),
+                kind,
+                file: item.span.source,
+                deps: visitor.deps,
+            });
+        }
+    }
+
+    fn add_vhdl(&mut self, units: &[vhdl::DesignUnit]) {
+        for unit in units {
+            let mut deps = vec![];
+            let (kind, name) = match unit.data {
+                vhdl::DesignUnitData::EntityDecl(ref x) => ("entity", x.name),
+                vhdl::DesignUnitData::CfgDecl(ref x) => {
+                    add_dep(&mut deps, DepKind::Primary, last_ident(&x.target));
+                    ("configuration", x.name)
+                }
+                vhdl::DesignUnitData::PkgDecl(ref x) => ("package", x.name),
+                vhdl::DesignUnitData::PkgInst(ref x) => {
+                    add_dep(&mut deps, DepKind::Instance, last_ident(&x.target));
+                    ("package instance", x.name)
+                }
+                vhdl::DesignUnitData::CtxDecl(ref x) => ("context", x.name),
+                vhdl::DesignUnitData::PkgBody(ref x) => {
+                    add_dep(
+                        &mut deps,
+                        DepKind::Primary,
+                        Some(x.name.value.as_str().to_lowercase()),
+                    );
+                    ("package body", x.name)
+                }
+                vhdl::DesignUnitData::ArchBody(ref x) => {
+                    add_dep(&mut deps, DepKind::Primary, last_ident(&x.target));
+                    vhdl_insts(&x.stmts, &mut deps);
+                    ("architecture", x.name)
+                }
+            };
+            for (lib, used) in used_units(unit) {
+                let target = if lib == "work" {
+                    used
+                } else {
+                    format!("{}.{}", lib, used)
+                };
+                add_dep(&mut deps, DepKind::Import, Some(target));
+            }
+            self.units.push(Unit {
+                name: name.value.as_str().to_lowercase(),
+                kind,
+                file: name.span.source,
+                deps,
+            });
+        }
+    }
+
+    /// Render the graph in the DOT format of Graphviz.
+    ///
+    /// Dependencies on a name that several units share point to the first
+    /// unit that is not an architecture or package body. Units which are not
+    /// defined in the graph are drawn dashed.
+    pub fn to_dot(&self) -> String {
+        let mut nodes: HashMap<&str, usize> = HashMap::new();
+        for (i, unit) in self.units.iter().enumerate() {
+            if unit.kind != "architecture" && unit.kind != "package body" {
+                nodes.entry(&unit.name).or_insert(i);
+            }
+        }
+        let mut external = vec![];
+        let mut output = String::from("digraph deps {\n");
+        for (i, unit) in self.units.iter().enumerate() {
+            writeln!(
+                output,
+                "    n{} [label=\"{} {}\"];",
+                i,
+                unit.kind,
+                escape(&unit.name)
+            )
+            .unwrap();
+        }
+        for (i, unit) in self.units.iter().enumerate() {
+            for dep in &unit.deps {
+                let target = match nodes.get(dep.target.as_str()) {
+                    Some(&index) => format!("n{}", index),
+                    None => {
+                        if !external.contains(&dep.target) {
+                            external.push(dep.target.clone());
+                        }
+                        format!("\"{}\"", escape(&dep.target))
+                    }
+                };
+                writeln!(
+                    output,
+                    "    n{} -> {} [label=\"{}\"];",
+                    i,
+                    target,
+                    dep.kind.as_str()
+                )
+                .unwrap();
+            }
+        }
+        for name in external {
+            writeln!(output, "    \"{}\" [style=dashed];", escape(&name)).unwrap();
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+/// Collects the dependencies of a SystemVerilog unit.
+#[derive(Default)]
+struct SvlogDeps {
+    deps: Vec<Dep>,
+}
+
+impl<'a> sv::Visitor<'a> for SvlogDeps {
+    fn pre_visit_inst(&mut self, node: &'a sv::Inst<'a>) -> bool {
+        add_dep(
+            &mut self.deps,
+            DepKind::Instance,
+            Some(node.target.value.to_string()),
+        );
+        true
+    }
+
+    fn pre_visit_import_item(&mut self, node: &'a sv::ImportItem<'a>) -> bool {
+        add_dep(
+            &mut self.deps,
+            DepKind::Import,
+            Some(node.pkg.value.to_string()),
+        );
+        true
+    }
+
+    fn pre_visit_export_item(&mut self, node: &'a sv::ExportItem<'a>) -> bool {
+        if let Some(pkg) = node.pkg {
+            add_dep(&mut self.deps, DepKind::Import, Some(pkg.value.to_string()));
+        }
+        true
+    }
+
+    fn pre_visit_expr(&mut self, node: &'a sv::Expr<'a>) -> bool {
+        if let sv::ScopeExpr(ref scope, _) = node.data {
+            if let sv::IdentExpr(name) = scope.data {
+                add_dep(
+                    &mut self.deps,
+                    DepKind::Import,
+                    Some(name.value.to_string()),
+                );
+            }
+        }
+        true
+    }
+
+    fn pre_visit_type(&mut self, node: &'a sv::Type<'a>) -> bool {
+        if let sv::ScopedType { ref ty, .. } = node.kind.data {
+            if let sv::NamedType(name) = ty.kind.data {
+                add_dep(
+                    &mut self.deps,
+                    DepKind::Import,
+                    Some(name.value.to_string()),
+                );
+            }
+        }
+        true
+    }
+}
+
+/// Collect the entities and components instantiated in VHDL statements.
+fn vhdl_insts(stmts: &[vhdl::Stmt], deps: &mut Vec<Dep>) {
+    for stmt in stmts {
+        match stmt.data {
+            // Without a keyword, only component instantiations have maps; the
+            // others are procedure calls.
+            vhdl::InstOrCallStmt {
+                target,
+                ref name,
+                ref generics,
+                ref ports,
+            } if target.is_some() || generics.is_some() || ports.is_some() => {
+                add_dep(deps, DepKind::Instance, last_ident(name))
+            }
+            vhdl::BlockStmt { ref stmts, .. } => vhdl_insts(stmts, deps),
+            vhdl::IfGenStmt { ref conds, ref alt } => {
+                for (_, body) in conds {
+                    vhdl_insts(&body.stmts, deps);
+                }
+                if let Some(ref body) = *alt {
+                    vhdl_insts(&body.stmts, deps);
+                }
+            }
+            vhdl::CaseGenStmt { ref cases, .. } => {
+                for (_, body) in cases {
+                    vhdl_insts(&body.stmts, deps);
+                }
+            }
+            vhdl::ForGenStmt { ref body, .. } => vhdl_insts(&body.stmts, deps),
+            _ => (),
+        }
+    }
+}
+
+/// Get the last identifier in a VHDL name, as in the `foo` of `work.foo`.
+fn last_ident(name: &vhdl::CompoundName) -> Option<String> {
+    let mut last = match name.primary.kind {
+        vhdl::PrimaryNameKind::Ident(x) => Some(x),
+        _ => None,
+    };
+    for part in &name.parts {
+        if let vhdl::NamePart::Select(vhdl::PrimaryName {
+            kind: vhdl::PrimaryNameKind::Ident(x),
+            ..
+        }) = *part
+        {
+            last = Some(x);
+        }
+    }
+    last.map(|x| x.as_str().to_lowercase())
+}
+
+/// Add a dependency, unless the unit already depends on the target.
+fn add_dep(deps: &mut Vec<Dep>, kind: DepKind, target: Option<String>) {
+    let target = match target {
+        Some(x) => x,
+        None => return,
+    };
+    if !deps.iter().any(|d| d.target == target) {
+        deps.push(Dep { kind, target });
+    }
+}
+
+/// Escape a name for use in a quoted DOT string.
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -5,6 +5,14 @@ use quote::{format_ident, quote};
 use syn::DeriveInput;
 
 pub(crate) fn accept_visitor(input: TokenStream, with_foreach: bool) -> TokenStream {
+    accept_visitor_impl(input, with_foreach, false)
+}
+
+pub(crate) fn accept_visitor_mut(input: TokenStream) -> TokenStream {
+    accept_visitor_impl(input, false, true)
+}
+
+fn accept_visitor_impl(input: TokenStream, with_foreach: bool, mutable: bool) -> TokenStream {
     // Parse the input.
     let input = syn::parse_macro_input!(input as DeriveInput);
     let mut output = proc_macro2::TokenStream::new();
@@ -18,7 +26,7 @@ pub(crate) fn accept_visitor(input: TokenStream, with_foreach: bool) -> TokenStr
     let dont_visit = has_dont_visit(&input.attrs);
     match &input.data {
         syn::Data::Struct(input) => {
-            let (visit, each) = visit_fields(&input.fields, dont_visit);
+            let (visit, each) = visit_fields(&input.fields, dont_visit, mutable);
             visits.push(quote! {
                 #name #visit
             });
@@ -30,7 +38,7 @@ pub(crate) fn accept_visitor(input: TokenStream, with_foreach: bool) -> TokenStr
             for variant in &input.variants {
                 let dont_visit = dont_visit || has_dont_visit(&variant.attrs);
                 let variant_name = &variant.ident;
-                let (visit, each) = visit_fields(&variant.fields, dont_visit);
+                let (visit, each) = visit_fields(&variant.fields, dont_visit, mutable);
                 visits.push(quote! {
                     #name::#variant_name #visit
                 });
@@ -46,16 +54,29 @@ pub(crate) fn accept_visitor(input: TokenStream, with_foreach: bool) -> TokenStr
     let mut impl_generics = generics.clone();
     let lt = crate::first_lifetime(&mut impl_generics);
 
-    // Generate the implementation of the `AcceptVisitor` trait.
-    output.extend(quote! {
-        impl #impl_generics AcceptVisitor<#lt> for #name #generics {
-            fn accept(&#lt self, visitor: &mut dyn Visitor<#lt>) {
-                match self {
-                    #(#visits,)*
+    // Generate the implementation of the `AcceptVisitor` or
+    // `AcceptVisitorMut` trait.
+    if mutable {
+        output.extend(quote! {
+            impl #impl_generics AcceptVisitorMut<#lt> for #name #generics {
+                fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<#lt>) {
+                    match self {
+                        #(#visits,)*
+                    }
                 }
             }
-        }
-    });
+        });
+    } else {
+        output.extend(quote! {
+            impl #impl_generics AcceptVisitor<#lt> for #name #generics {
+                fn accept(&#lt self, visitor: &mut dyn Visitor<#lt>) {
+                    match self {
+                        #(#visits,)*
+                    }
+                }
+            }
+        });
+    }
     if with_foreach {
         output.extend(quote! {
             impl #impl_generics ForEachChild<#lt> for #name #generics {
@@ -86,6 +107,7 @@ fn has_dont_visit(attrs: &[syn::Attribute]) -> bool {
 fn visit_fields(
     fields: &syn::Fields,
     dont_visit: bool,
+    mutable: bool,
 ) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
     // Generate a destructuring pattern that assigns predictable names to all
     // fields.
@@ -122,10 +144,15 @@ fn visit_fields(
     };
 
     // Generate the visit calls for the names that we do not skip or ignore.
+    let walk = if mutable {
+        format_ident!("walk_mut")
+    } else {
+        format_ident!("walk")
+    };
     (
         quote! {
             #pat => {
-                #(#names.walk(visitor);)*
+                #(#names.#walk(visitor);)*
             }
         },
         quote! {
@@ -94,6 +94,7 @@ pub(crate) fn derive_arena(_args: TokenStream) -> TokenStream {
     // Generate the various bits and pieces.
     let mut fields = vec![];
     let mut allocs = vec![];
+    let mut stats = vec![];
 
     for (name, generics) in types {
         // Assemble the generics for the alloc implementation.
@@ -118,6 +119,10 @@ pub(crate) fn derive_arena(_args: TokenStream) -> TokenStream {
         fields.push(quote! {
             #field_name: moore_common::arenas::TypedArena<#name #generics>
         });
+        let stats_name = name.to_string().to_snake_case();
+        stats.push(quote! {
+            moore_common::arenas::ArenaStats::of(#stats_name, &self.#field_name)
+        });
 
         // Render the corresponding alloc function.
         let (alloc_generics, _, whc) = alloc_generics.split_for_impl();
@@ -141,6 +146,13 @@ pub(crate) fn derive_arena(_args: TokenStream) -> TokenStream {
             #(#fields,)*
         }
 
+        impl #arena_generics #arena_name #arena_generics {
+            /// Report the number of values and bytes allocated in each arena.
+            pub fn stats(&self) -> Vec<moore_common::arenas::ArenaStats> {
+                vec![#(#stats,)*]
+            }
+        }
+
         #(#allocs)*
     };
     // println!("{}", output);
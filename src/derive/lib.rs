@@ -44,6 +44,12 @@ pub fn accept_visitor_and_foreach(input: TokenStream) -> TokenStream {
     accept_visitor::accept_visitor(input, true)
 }
 
+/// Generate an `AcceptVisitorMut` implementation.
+#[proc_macro_derive(AcceptVisitorMut, attributes(dont_visit))]
+pub fn accept_visitor_mut(input: TokenStream) -> TokenStream {
+    accept_visitor::accept_visitor_mut(input)
+}
+
 /// Wrap a struct or enum in a `Node`.
 #[proc_macro_attribute]
 pub fn node(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -59,16 +65,24 @@ pub fn node_data(input: TokenStream) -> TokenStream {
 /// Generate corresponding `*_visit_*` functions in a visitor.
 #[proc_macro_attribute]
 pub fn walk_visitor(args: TokenStream, input: TokenStream) -> TokenStream {
-    walk_visitor::walk_visitor(args, input)
+    walk_visitor::walk_visitor(args, input, false)
+}
+
+/// Generate corresponding `*_visit_*` functions in a mutable visitor.
+#[proc_macro_attribute]
+pub fn walk_visitor_mut(args: TokenStream, input: TokenStream) -> TokenStream {
+    walk_visitor::walk_visitor(args, input, true)
 }
 
-/// Convenience macro to derive `AcceptVisitorAndForeach` and `walk_visitor`.
+/// Convenience macro to derive `AcceptVisitorAndForeach`, `AcceptVisitorMut`,
+/// `walk_visitor`, and `walk_visitor_mut`.
 #[proc_macro_attribute]
 pub fn visit(_args: TokenStream, input: TokenStream) -> TokenStream {
     let input = proc_macro2::TokenStream::from(input);
     TokenStream::from(quote! {
         #[moore_derive::walk_visitor]
-        #[derive(moore_derive::AcceptVisitorAndForeach)]
+        #[moore_derive::walk_visitor_mut]
+        #[derive(moore_derive::AcceptVisitorAndForeach, moore_derive::AcceptVisitorMut)]
         #input
     })
 }
@@ -99,7 +113,13 @@ pub fn all_node(args: TokenStream, input: TokenStream) -> TokenStream {
 /// Generate a `Visitor` trait.
 #[proc_macro]
 pub fn derive_visitor(input: TokenStream) -> TokenStream {
-    visitor::visitor(input)
+    visitor::visitor(input, false)
+}
+
+/// Generate a `VisitorMut` trait.
+#[proc_macro]
+pub fn derive_visitor_mut(input: TokenStream) -> TokenStream {
+    visitor::visitor(input, true)
 }
 
 /// Mark a function as a compiler query.
@@ -31,6 +31,7 @@ pub(crate) fn node(args: TokenStream, input: TokenStream) -> TokenStream {
     output.extend(quote! {
         #[moore_derive::arena]
         #[moore_derive::walk_visitor(node)]
+        #[moore_derive::walk_visitor_mut(node)]
         #vis type #node_name #impl_generics = Node<#lt, #data_name #generics>;
     });
 
@@ -38,6 +39,7 @@ pub(crate) fn node(args: TokenStream, input: TokenStream) -> TokenStream {
     output.extend(quote! {
         #[derive(
             moore_derive::AcceptVisitorAndForeach,
+            moore_derive::AcceptVisitorMut,
             moore_derive::AnyNodeData
         )]
         #input
@@ -54,6 +54,8 @@ pub(crate) fn derive_query_db(input: TokenStream) -> TokenStream {
     let mut caches = vec![];
     let mut tags = vec![];
     let mut tag_debugs = vec![];
+    let mut removes = vec![];
+    let mut cached_tags = vec![];
     let mut keys = vec![];
 
     for raw_query in &queries {
@@ -191,6 +193,19 @@ pub(crate) fn derive_query_db(input: TokenStream) -> TokenStream {
                 let query_tag = QueryTag::#tag_name(query_key.clone());
                 self.before_query(&query_tag);
 
+                // Record that the calling query depends on this one, such that
+                // it is recomputed when this query is invalidated.
+                if query_storage.track_dependencies.get() {
+                    if let Some(caller) = query_storage.stack.borrow().last() {
+                        query_storage
+                            .dependents
+                            .borrow_mut()
+                            .entry(query_tag.clone())
+                            .or_insert_with(HashSet::new)
+                            .insert(caller.clone());
+                    }
+                }
+
                 // Check if we already have a result for this query.
                 if let Some(result) = query_storage.#cache_name.borrow().get(&query_key) {
                     trace!("Serving {}{:?} from cache", stringify!(#name), query_key);
@@ -225,6 +240,12 @@ pub(crate) fn derive_query_db(input: TokenStream) -> TokenStream {
             #[doc = #doc]
             pub #cache_name: RefCell<HashMap<#key_name #key_generics, #result>>,
         });
+        removes.push(quote! {
+            QueryTag::#tag_name(x) => self.#cache_name.borrow_mut().remove(x).is_some(),
+        });
+        cached_tags.push(quote! {
+            tags.extend(self.#cache_name.borrow().keys().cloned().map(QueryTag::#tag_name));
+        });
     }
 
     // Extract query lifetimes.
@@ -265,6 +286,35 @@ pub(crate) fn derive_query_db(input: TokenStream) -> TokenStream {
             /// Called after a query is executed.
             fn after_query(&self, tag: &QueryTag #lts) {}
 
+            /// Discard the cached result of a query and of all queries which
+            /// depend on it, such that they are recomputed when called again.
+            ///
+            /// Dependencies are only known for queries executed while
+            /// `QueryStorage::track_dependencies` is set. Only query results
+            /// are discarded; state filled outside of queries, such as the AST
+            /// map, is kept, and there are no input queries for the source
+            /// text which a change to a file could invalidate. Returns the
+            /// number of discarded results.
+            fn invalidate(&self, tag: &QueryTag #lts) -> usize {
+                let storage = self.storage();
+                let mut todo = vec![tag.clone()];
+                let mut seen = HashSet::new();
+                let mut num_removed = 0;
+                while let Some(tag) = todo.pop() {
+                    if !seen.insert(tag.clone()) {
+                        continue;
+                    }
+                    if storage.remove(&tag) {
+                        trace!("Invalidated {:?}", tag);
+                        num_removed += 1;
+                    }
+                    if let Some(dependents) = storage.dependents.borrow_mut().remove(&tag) {
+                        todo.extend(dependents);
+                    }
+                }
+                num_removed
+            }
+
             #(#funcs)*
         }
     });
@@ -278,9 +328,35 @@ pub(crate) fn derive_query_db(input: TokenStream) -> TokenStream {
             pub stack: RefCell<Vec<QueryTag #lts>>,
             /// A set of the currently-executing queries.
             pub inflight: RefCell<HashSet<QueryTag #lts>>,
+            /// Whether to record the dependencies between queries. Off by
+            /// default, since it is only needed to invalidate results.
+            pub track_dependencies: std::cell::Cell<bool>,
+            /// The queries which called each query, and thus depend on its
+            /// result.
+            pub dependents: RefCell<HashMap<QueryTag #lts, HashSet<QueryTag #lts>>>,
 
             #(#caches)*
         }
+
+        impl #lts QueryStorage #lts {
+            /// Remove the cached result of a query.
+            ///
+            /// Returns whether there was a cached result. Prefer
+            /// `QueryDatabase::invalidate`, which also removes the results of
+            /// the queries which depend on this one.
+            pub fn remove(&self, tag: &QueryTag #lts) -> bool {
+                match tag {
+                    #(#removes)*
+                }
+            }
+
+            /// List the queries with a cached result.
+            pub fn cached_tags(&self) -> Vec<QueryTag #lts> {
+                let mut tags = vec![];
+                #(#cached_tags)*
+                tags
+            }
+        }
     });
 
     // Generate the query tag enum.
@@ -12,6 +12,7 @@ use syn::Generics;
 // minute. Better have a robust CI.
 thread_local! {
     static CALLS: RefCell<Vec<Call>> = Default::default();
+    static CALLS_MUT: RefCell<Vec<Call>> = Default::default();
 }
 
 struct Call {
@@ -19,31 +20,43 @@ struct Call {
     generics: String,
 }
 
-pub(crate) fn add_call(name: &Ident, generics: &Generics) {
+pub(crate) fn add_call(name: &Ident, generics: &Generics, mutable: bool) {
     // Map everything to a string here. Compiler panics horribly if we hand out
     // the actual idents and generics.
     let call = Call {
         name: name.to_string(),
         generics: generics.to_token_stream().to_string(),
     };
-    CALLS.with(|c| c.borrow_mut().push(call));
+    let calls = if mutable { &CALLS_MUT } else { &CALLS };
+    calls.with(|c| c.borrow_mut().push(call));
 }
 
-pub(crate) fn visitor(input: TokenStream) -> TokenStream {
+pub(crate) fn visitor(input: TokenStream, mutable: bool) -> TokenStream {
     let input = proc_macro2::TokenStream::from(input);
 
     // Flush the accumulated calls.
-    let calls = CALLS.with(|c| std::mem::replace(&mut *c.borrow_mut(), Default::default()));
+    let calls = if mutable { &CALLS_MUT } else { &CALLS };
+    let calls = calls.with(|c| std::mem::replace(&mut *c.borrow_mut(), Default::default()));
 
     // Determine a lifetime for the visitor.
     let lt: syn::Lifetime = syn::parse_str("'a").unwrap();
 
     // Generate some documentation.
-    let mut doc = format!(
-        "A visitor.\n\nUse the `accept()` function to start visiting nodes. For \
-         example:\n\n```ignore\nstruct MagicVisitor;\n\nimpl Visitor for MagicVisitor \
-         {{\n}}\n\nnode.accept(&mut MagicVisitor);\n```\n\n"
-    );
+    let mut doc = if mutable {
+        format!(
+            "A visitor which may modify the nodes it visits.\n\nUse the `accept_mut()` \
+             function to start visiting nodes. For example:\n\n```ignore\nstruct \
+             MagicVisitor;\n\nimpl VisitorMut for MagicVisitor {{\n}}\n\nnode.accept_mut(&mut \
+             MagicVisitor);\n```\n\nNodes behind shared references cannot be modified and \
+             are not visited.\n\n"
+        )
+    } else {
+        format!(
+            "A visitor.\n\nUse the `accept()` function to start visiting nodes. For \
+             example:\n\n```ignore\nstruct MagicVisitor;\n\nimpl Visitor for MagicVisitor \
+             {{\n}}\n\nnode.accept(&mut MagicVisitor);\n```\n\n"
+        )
+    };
     doc.push_str("Implements the visitor pattern over the following nodes:\n\n");
     for call in &calls {
         doc.push_str(&format!("- `{}`\n", call.name));
@@ -83,22 +96,32 @@ pub(crate) fn visitor(input: TokenStream) -> TokenStream {
         );
 
         // Render the corresponding call.
+        let node_ref = if mutable {
+            quote! { &mut #name #generics }
+        } else {
+            quote! { &#lt #name #generics }
+        };
         pre_calls.push(quote! {
             #[doc = #pre_doc]
-            fn #pre_visit_fn (&mut self, node: &#lt #name #generics) -> bool {
+            fn #pre_visit_fn (&mut self, node: #node_ref) -> bool {
                 true
             }
         });
         post_calls.push(quote! {
             #[doc = #post_doc]
-            fn #post_visit_fn (&mut self, node: &#lt #name #generics) {
+            fn #post_visit_fn (&mut self, node: #node_ref) {
             }
         });
     }
 
+    let trait_name = if mutable {
+        format_ident!("VisitorMut")
+    } else {
+        format_ident!("Visitor")
+    };
     let output = quote! {
         #[doc = #doc]
-        pub trait Visitor<#lt> {
+        pub trait #trait_name<#lt> {
             #input
             #(#pre_calls)*
             #(#post_calls)*
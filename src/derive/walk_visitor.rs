@@ -5,7 +5,11 @@ use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::Item;
 
-pub(crate) fn walk_visitor(args: TokenStream, raw_input: TokenStream) -> TokenStream {
+pub(crate) fn walk_visitor(
+    args: TokenStream,
+    raw_input: TokenStream,
+    mutable: bool,
+) -> TokenStream {
     // Parse the input.
     let input = syn::parse_macro_input!(raw_input as Item);
     let (name, generics) = match &input {
@@ -29,7 +33,7 @@ pub(crate) fn walk_visitor(args: TokenStream, raw_input: TokenStream) -> TokenSt
         name.to_string().to_snake_case(),
         span = name.span()
     );
-    crate::visitor::add_call(&name, &generics);
+    crate::visitor::add_call(&name, &generics, mutable);
 
     // Determine the impl generics, which may add another lifetime.
     let mut impl_generics = generics.clone();
@@ -43,10 +47,12 @@ pub(crate) fn walk_visitor(args: TokenStream, raw_input: TokenStream) -> TokenSt
 
         Calling this function is equivalent to calling:
         - `visitor.{}(self)`
-        - `self.accept(visitor)`
+        - `self.{}(visitor)`
         - `visitor.{}(self);`
         "#,
-        pre_visit_fn, post_visit_fn,
+        pre_visit_fn,
+        if mutable { "accept_mut" } else { "accept" },
+        post_visit_fn,
     );
 
     // Assemble the sequence of pre- and post-visit functions.
@@ -72,18 +78,36 @@ pub(crate) fn walk_visitor(args: TokenStream, raw_input: TokenStream) -> TokenSt
 
     post_visit_calls.reverse();
 
-    // Generate the implementation of the `WalkVisitor` trait.
-    let output = quote! {
-        #input
+    // Generate the implementation of the `WalkVisitor` or `WalkVisitorMut`
+    // trait.
+    let output = if mutable {
+        quote! {
+            #input
 
-        impl #impl_generics WalkVisitor<#lt> for #name #generics {
-            #[doc = #doc]
-            fn walk(&#lt self, visitor: &mut dyn Visitor<#lt>) {
-                #(#pre_visit_calls)*
-                if visit {
-                    self.accept(visitor);
+            impl #impl_generics WalkVisitorMut<#lt> for #name #generics {
+                #[doc = #doc]
+                fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<#lt>) {
+                    #(#pre_visit_calls)*
+                    if visit {
+                        self.accept_mut(visitor);
+                    }
+                    #(#post_visit_calls)*
+                }
+            }
+        }
+    } else {
+        quote! {
+            #input
+
+            impl #impl_generics WalkVisitor<#lt> for #name #generics {
+                #[doc = #doc]
+                fn walk(&#lt self, visitor: &mut dyn Visitor<#lt>) {
+                    #(#pre_visit_calls)*
+                    if visit {
+                        self.accept(visitor);
+                    }
+                    #(#post_visit_calls)*
                 }
-                #(#post_visit_calls)*
             }
         }
     };
@@ -0,0 +1,103 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Checks of the instance hierarchy.
+
+use super::*;
+use crate::common::errors::*;
+
+/// Check the instance hierarchy of a design.
+///
+/// Reports units which instantiate themselves, directly or through other
+/// units, outside of generate constructs. Such a hierarchy never ends and
+/// cannot be elaborated. Instances of units which are not part of the design
+/// are ignored.
+pub fn check(design: &Design) -> Vec<DiagBuilder2> {
+    let targets = design
+        .units
+        .iter()
+        .map(|unit| {
+            unit.instances
+                .iter()
+                .filter(|inst| !inst.conditional)
+                .filter_map(|inst| design.unit_index(inst.target.value).map(|t| (t, inst)))
+                .collect()
+        })
+        .collect();
+    let mut cx = CycleFinder {
+        design,
+        targets,
+        state: vec![State::Unvisited; design.units.len()],
+        path: vec![],
+        diags: vec![],
+    };
+    for index in 0..design.units.len() {
+        cx.visit(index);
+    }
+    cx.diags
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Unvisited,
+    Active,
+    Done,
+}
+
+/// Finds cycles in the instance hierarchy with a depth-first search.
+struct CycleFinder<'a> {
+    design: &'a Design,
+    /// The units instantiated by each unit, and the instance.
+    targets: Vec<Vec<(usize, &'a Instance)>>,
+    state: Vec<State>,
+    /// The units being visited, and the instance through which the next one
+    /// was reached.
+    path: Vec<(usize, Option<&'a Instance>)>,
+    diags: Vec<DiagBuilder2>,
+}
+
+impl<'a> CycleFinder<'a> {
+    fn visit(&mut self, index: usize) {
+        if self.state[index] != State::Unvisited {
+            return;
+        }
+        self.state[index] = State::Active;
+        self.path.push((index, None));
+        for i in 0..self.targets[index].len() {
+            let (target, inst) = self.targets[index][i];
+            self.path.last_mut().unwrap().1 = Some(inst);
+            match self.state[target] {
+                State::Unvisited => self.visit(target),
+                State::Active => self.report(target, inst),
+                State::Done => (),
+            }
+        }
+        self.path.pop();
+        self.state[index] = State::Done;
+    }
+
+    /// Report the cycle from `target` through the current path back to
+    /// `target`, which is closed by `inst`.
+    fn report(&mut self, target: usize, inst: &Instance) {
+        let units = &self.design.units;
+        let mut diag = DiagBuilder2::error(format!(
+            "recursive instantiation of {}",
+            units[target].desc()
+        ))
+        .span(inst.target.span);
+        let start = self.path.iter().position(|&(i, _)| i == target).unwrap();
+        let cycle = &self.path[start..self.path.len() - 1];
+        for (&(from, via), &(to, _)) in cycle.iter().zip(&self.path[start + 1..]) {
+            diag = diag
+                .add_note(format!(
+                    "{} instantiates {} here:",
+                    units[from].desc(),
+                    units[to].desc()
+                ))
+                .span(via.unwrap().target.span);
+        }
+        diag = diag.add_note(
+            "Recursive instances must be inside a generate construct which ends the recursion",
+        );
+        self.diags.push(diag);
+    }
+}
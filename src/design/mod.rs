@@ -0,0 +1,282 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! A language-independent outline of a design.
+//!
+//! The modules and entities of both frontends are summarized as the same
+//! `Unit` structure, listing the parameters, ports, signals, processes, and
+//! instances declared in them. The checks of the instance hierarchy in
+//! `check`, the passes registered in `crate::pass`, and the renaming of ports
+//! across instances in `crate::rename` are written once against this outline.
+//!
+//! The outline is a side product for such analyses and not an intermediate
+//! representation: elaboration and code generation do not go through it, and
+//! still use the HIR of each language in `svlog::hir` and `vhdl::hir`. It is
+//! built directly from the syntax trees and is not type checked. Names are
+//! kept as written; VHDL units are looked up case-insensitively. A VHDL entity
+//! and its most recently declared architecture form a single unit.
+
+use crate::common::name::Name;
+use crate::common::source::{Span, Spanned};
+use crate::score::Ast;
+use serde::Serialize;
+
+pub mod check;
+mod svlog;
+mod vhdl;
+
+pub use self::check::check;
+
+/// The units of a design.
+///
+/// The SystemVerilog modules come first, followed by the VHDL entities, each
+/// in the order they are defined.
+#[derive(Debug, Default, Serialize)]
+pub struct Design {
+    pub units: Vec<Unit>,
+}
+
+/// A SystemVerilog module or a VHDL entity and its architecture.
+#[derive(Debug, Serialize)]
+pub struct Unit {
+    /// The name of the unit.
+    pub name: Spanned<Name>,
+    /// The language the unit is written in.
+    pub language: Language,
+    /// The span of the module or entity.
+    pub span: Span,
+    /// The parameters or generics of the unit.
+    pub params: Vec<Param>,
+    /// The ports of the unit.
+    pub ports: Vec<Port>,
+    /// The nets, variables, and signals declared in the unit.
+    pub signals: Vec<Signal>,
+    /// The processes and continuous assignments of the unit.
+    pub processes: Vec<Process>,
+    /// The instances of other units.
+    pub instances: Vec<Instance>,
+}
+
+/// The language of a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Svlog,
+    Vhdl,
+}
+
+/// A parameter of a unit, or a generic in VHDL.
+#[derive(Debug, Serialize)]
+pub struct Param {
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// Whether this is a type parameter.
+    pub is_type: bool,
+    /// Whether this is a local parameter, which cannot be overridden.
+    pub local: bool,
+    /// The default value of the parameter.
+    pub default: Option<Expr>,
+}
+
+/// A port of a unit.
+#[derive(Debug, Serialize)]
+pub struct Port {
+    pub name: Spanned<Name>,
+    pub span: Span,
+    pub dir: PortDir,
+}
+
+/// The direction of a port.
+///
+/// SystemVerilog `ref` ports and VHDL `linkage` ports are considered `inout`,
+/// and VHDL `buffer` ports are considered outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortDir {
+    Input,
+    Output,
+    Inout,
+}
+
+/// A net, variable, or signal declared in a unit.
+#[derive(Debug, Serialize)]
+pub struct Signal {
+    pub name: Spanned<Name>,
+    pub span: Span,
+}
+
+/// A process or continuous assignment.
+#[derive(Debug, Serialize)]
+pub struct Process {
+    pub kind: ProcessKind,
+    pub span: Span,
+    /// The targets of the assignments in the process.
+    pub drives: Vec<Expr>,
+    /// The other expressions in the process, such as the assigned values,
+    /// conditions, and sensitivity lists.
+    pub reads: Vec<Expr>,
+}
+
+/// The different kinds of processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessKind {
+    Initial,
+    Final,
+    Always,
+    AlwaysComb,
+    AlwaysLatch,
+    AlwaysFf,
+    /// A SystemVerilog continuous assignment, or a VHDL concurrent signal
+    /// assignment.
+    Assign,
+    /// A VHDL process.
+    Process,
+}
+
+/// An instance of another unit.
+#[derive(Debug, Serialize)]
+pub struct Instance {
+    pub name: Spanned<Name>,
+    pub span: Span,
+    /// The name of the instantiated unit. For VHDL component instances, this
+    /// is the name of the component, which binds to the entity of the same
+    /// name.
+    pub target: Spanned<Name>,
+    /// The parameter or generic assignments.
+    pub params: Vec<Conn>,
+    /// The port connections.
+    pub ports: Vec<Conn>,
+    /// Whether the instance is inside a generate construct, such that it
+    /// exists only for some parameter values.
+    pub conditional: bool,
+}
+
+/// A parameter assignment or port connection of an instance.
+#[derive(Debug, Serialize)]
+pub struct Conn {
+    pub span: Span,
+    /// The name of the parameter or port, or `None` for positional
+    /// connections.
+    pub name: Option<Spanned<Name>>,
+    /// The connected expression, or `None` if the port is left open.
+    pub expr: Option<Expr>,
+}
+
+/// An expression.
+#[derive(Debug, Serialize)]
+pub struct Expr {
+    pub span: Span,
+    pub kind: ExprKind,
+}
+
+/// The different kinds of expressions.
+#[derive(Debug, Serialize)]
+pub enum ExprKind {
+    /// A reference to a declaration by name.
+    Name(Name),
+    /// A literal, whose text is the span of the expression.
+    Literal,
+    /// A unary operator, like `!a` or `not a`.
+    Unary(String, Box<Expr>),
+    /// A binary operator, like `a + b` or `a and b`.
+    Binary(String, Box<Expr>, Box<Expr>),
+    /// A conditional expression, like `a ? b : c`.
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// An index or slice, like `a[i]` or `a(3 downto 0)`. VHDL does not
+    /// distinguish function calls from indexing syntactically; both are
+    /// lowered to an index.
+    Index(Box<Expr>, Vec<Expr>),
+    /// A field of a struct or record, like `a.b`.
+    Field(Box<Expr>, Spanned<Name>),
+    /// A function call, like `f(a, b)`.
+    Call(Box<Expr>, Vec<Expr>),
+    /// A concatenation, like `{a, b}`.
+    Concat(Vec<Expr>),
+    /// Any other expression, with the expressions it contains.
+    Other(Vec<Expr>),
+}
+
+impl Design {
+    /// Lower the units in a list of parsed files.
+    pub fn new<'a>(asts: &[&'a Ast<'a>]) -> Design {
+        let mut design = Design::default();
+        let mut vhdl_units = vec![];
+        for ast in asts {
+            match ast {
+                Ast::Svlog(file) => svlog::lower_file(file, &mut design.units),
+                Ast::Vhdl(units) => vhdl_units.extend(units),
+            }
+        }
+        // Architectures may be in other files than their entities.
+        vhdl::lower_units(&vhdl_units, &mut design.units);
+        design
+    }
+
+    /// Find a unit by name.
+    ///
+    /// SystemVerilog units are matched exactly, VHDL units case-insensitively.
+    /// Modules are preferred over entities of the same name, and later units
+    /// over earlier ones.
+    pub fn unit(&self, name: Name) -> Option<&Unit> {
+        self.unit_index(name).map(|index| &self.units[index])
+    }
+
+    fn unit_index(&self, name: Name) -> Option<usize> {
+        let name = name.as_str();
+        let units = self.units.iter().enumerate().rev();
+        let mut vhdl = None;
+        for (index, unit) in units {
+            match unit.language {
+                Language::Svlog if unit.name.value.as_str() == name => return Some(index),
+                Language::Vhdl
+                    if vhdl.is_none() && unit.name.value.as_str().eq_ignore_ascii_case(&name) =>
+                {
+                    vhdl = Some(index)
+                }
+                _ => (),
+            }
+        }
+        vhdl
+    }
+}
+
+impl Unit {
+    /// Describe the unit as `module `foo`` or `entity `foo``.
+    pub fn desc(&self) -> String {
+        match self.language {
+            Language::Svlog => format!("module `{}`", self.name.value),
+            Language::Vhdl => format!("entity `{}`", self.name.value),
+        }
+    }
+}
+
+impl Expr {
+    /// Apply a function to every name referred to in the expression.
+    pub fn for_each_name(&self, each: &mut dyn FnMut(Spanned<Name>)) {
+        match self.kind {
+            ExprKind::Name(name) => each(Spanned::new(name, self.span)),
+            ExprKind::Literal => (),
+            ExprKind::Unary(_, ref arg) | ExprKind::Field(ref arg, _) => arg.for_each_name(each),
+            ExprKind::Binary(_, ref lhs, ref rhs) => {
+                lhs.for_each_name(each);
+                rhs.for_each_name(each);
+            }
+            ExprKind::Cond(ref cond, ref then, ref other) => {
+                cond.for_each_name(each);
+                then.for_each_name(each);
+                other.for_each_name(each);
+            }
+            ExprKind::Index(ref target, ref args) | ExprKind::Call(ref target, ref args) => {
+                target.for_each_name(each);
+                for arg in args {
+                    arg.for_each_name(each);
+                }
+            }
+            ExprKind::Concat(ref args) | ExprKind::Other(ref args) => {
+                for arg in args {
+                    arg.for_each_name(each);
+                }
+            }
+        }
+    }
+}
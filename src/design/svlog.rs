@@ -0,0 +1,366 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Lowering of SystemVerilog modules.
+
+use super::*;
+use crate::svlog::ast as sv;
+use crate::svlog::token::Op;
+use std::collections::HashMap;
+
+/// Lower the modules of a file.
+///
+/// Modules derived from VHDL entities are skipped, since the entity itself is
+/// lowered from the VHDL side.
+pub(super) fn lower_file<'a>(file: &'a sv::SourceFile<'a>, units: &mut Vec<Unit>) {
+    for item in &file.items {
+        if let sv::ItemData::ModuleDecl(ref module) = item.data {
+            if !module.foreign {
+                units.push(lower_module(module));
+            }
+        }
+    }
+}
+
+fn lower_module<'a>(module: &'a sv::Module<'a>) -> Unit {
+    let mut unit = Unit {
+        name: module.name,
+        language: Language::Svlog,
+        span: module.span,
+        params: vec![],
+        ports: vec![],
+        signals: vec![],
+        processes: vec![],
+        instances: vec![],
+    };
+    for decl in &module.params {
+        lower_params(decl, decl.local, &mut unit.params);
+    }
+    lower_ports(module, &mut unit.ports);
+
+    // Parameters in the body are local if the module has a parameter port
+    // list.
+    let local = !module.params.is_empty();
+    for item in &module.items {
+        if let sv::ItemData::ParamDecl(ref decl) = item.data {
+            lower_params(decl, decl.local || local, &mut unit.params);
+        }
+    }
+    lower_items(&module.items, false, &mut unit);
+    unit
+}
+
+fn lower_params(decl: &sv::ParamDecl, local: bool, into: &mut Vec<Param>) {
+    match decl.kind {
+        sv::ParamKind::Type(ref decls) => into.extend(decls.iter().map(|decl| Param {
+            name: decl.name,
+            span: decl.span,
+            is_type: true,
+            local,
+            default: None,
+        })),
+        sv::ParamKind::Value(ref decls) => into.extend(decls.iter().map(|decl| Param {
+            name: decl.name,
+            span: decl.span,
+            is_type: false,
+            local,
+            default: decl.expr.as_ref().map(lower_expr),
+        })),
+    }
+}
+
+/// Lower the ports of a module.
+///
+/// Ports without a direction take the direction of their declaration in the
+/// module body, or otherwise of the preceding port. The first port defaults
+/// to `inout`.
+fn lower_ports(module: &sv::Module, into: &mut Vec<Port>) {
+    let mut decls = HashMap::new();
+    for item in &module.items {
+        if let sv::ItemData::PortDecl(ref decl) = item.data {
+            for name in &decl.names {
+                decls.insert(name.name, lower_dir(decl.dir));
+            }
+        }
+    }
+    let mut dir = PortDir::Inout;
+    for port in &module.ports {
+        let (name, explicit) = match port.data {
+            sv::PortData::Intf { name, .. } => (name, Some(PortDir::Inout)),
+            sv::PortData::Explicit { name, dir, .. } | sv::PortData::Named { name, dir, .. } => {
+                (name, dir.map(lower_dir))
+            }
+            sv::PortData::Implicit(ref expr) => match expr.data {
+                sv::IdentExpr(name) => (name, None),
+                _ => continue,
+            },
+        };
+        dir = explicit
+            .or_else(|| decls.get(&name.value).copied())
+            .unwrap_or(dir);
+        into.push(Port {
+            name,
+            span: port.span,
+            dir,
+        });
+    }
+}
+
+fn lower_dir(dir: sv::PortDir) -> PortDir {
+    match dir {
+        sv::PortDir::Input => PortDir::Input,
+        sv::PortDir::Output => PortDir::Output,
+        sv::PortDir::Inout | sv::PortDir::Ref => PortDir::Inout,
+    }
+}
+
+/// Lower the items of a module or generate block.
+fn lower_items<'a>(items: &'a [sv::Item<'a>], conditional: bool, unit: &mut Unit) {
+    for item in items {
+        match item.data {
+            sv::ItemData::NetDecl(ref decl) => lower_signals(&decl.names, unit),
+            sv::ItemData::VarDecl(ref decl) => lower_signals(&decl.names, unit),
+            sv::ItemData::Procedure(ref proc) => {
+                let kind = match proc.kind {
+                    sv::ProcedureKind::Initial => ProcessKind::Initial,
+                    sv::ProcedureKind::Final => ProcessKind::Final,
+                    sv::ProcedureKind::Always => ProcessKind::Always,
+                    sv::ProcedureKind::AlwaysComb => ProcessKind::AlwaysComb,
+                    sv::ProcedureKind::AlwaysLatch => ProcessKind::AlwaysLatch,
+                    sv::ProcedureKind::AlwaysFf => ProcessKind::AlwaysFf,
+                };
+                let mut accesses = Accesses::default();
+                sv::AcceptVisitor::accept(&proc.stmt, &mut accesses);
+                unit.processes.push(Process {
+                    kind,
+                    span: item.span,
+                    drives: accesses.drives,
+                    reads: accesses.reads,
+                });
+            }
+            sv::ItemData::ContAssign(ref assign) => {
+                for (lhs, rhs) in &assign.assignments {
+                    unit.processes.push(Process {
+                        kind: ProcessKind::Assign,
+                        span: Span::union(lhs.span, rhs.span),
+                        drives: vec![lower_expr(lhs)],
+                        reads: vec![lower_expr(rhs)],
+                    });
+                }
+            }
+            sv::ItemData::Inst(ref inst) => {
+                for name in &inst.names {
+                    unit.instances.push(lower_inst(inst, name, conditional));
+                }
+            }
+            sv::ItemData::GenerateRegion(_, ref items) => lower_items(items, conditional, unit),
+            sv::ItemData::GenerateFor(ref gen) => lower_items(&gen.block.items, true, unit),
+            sv::ItemData::GenerateIf(ref gen) => {
+                lower_items(&gen.main_block.items, true, unit);
+                if let Some(ref block) = gen.else_block {
+                    lower_items(&block.items, true, unit);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn lower_signals(names: &[sv::VarDeclName], unit: &mut Unit) {
+    unit.signals.extend(names.iter().map(|name| Signal {
+        name: Spanned::new(name.name, name.name_span),
+        span: name.span,
+    }));
+}
+
+/// Lower one of the instances of an instantiation.
+///
+/// Implicit `.*` port connections are not included.
+fn lower_inst(inst: &sv::Inst, name: &sv::InstName, conditional: bool) -> Instance {
+    let params = inst
+        .params
+        .iter()
+        .map(|param| Conn {
+            span: param.span,
+            name: param.name.map(|n| Spanned::new(n.name, n.span)),
+            expr: match param.expr {
+                sv::TypeOrExpr::Expr(expr) => Some(lower_expr(expr)),
+                sv::TypeOrExpr::Type(_) => None,
+            },
+        })
+        .collect();
+    let ports = name
+        .conns
+        .iter()
+        .filter_map(|conn| {
+            let (name, expr) = match conn.data {
+                sv::PortConnData::Auto => return None,
+                sv::PortConnData::Named(name, sv::PortConnMode::Auto) => (
+                    Some(name),
+                    Some(Expr {
+                        span: name.span,
+                        kind: ExprKind::Name(name.value),
+                    }),
+                ),
+                sv::PortConnData::Named(name, sv::PortConnMode::Unconnected) => (Some(name), None),
+                sv::PortConnData::Named(name, sv::PortConnMode::Connected(ref expr)) => {
+                    (Some(name), Some(lower_expr(expr)))
+                }
+                sv::PortConnData::Named(name, sv::PortConnMode::Event(_)) => (Some(name), None),
+                sv::PortConnData::Positional(ref expr) => (None, Some(lower_expr(expr))),
+                sv::PortConnData::PositionalEvent(_) => (None, None),
+            };
+            Some(Conn {
+                span: conn.span,
+                name,
+                expr,
+            })
+        })
+        .collect();
+    Instance {
+        name: name.name,
+        span: name.span,
+        target: inst.target,
+        params,
+        ports,
+        conditional,
+    }
+}
+
+/// Collects the assigned and read expressions of a procedure.
+#[derive(Default)]
+struct Accesses {
+    drives: Vec<Expr>,
+    reads: Vec<Expr>,
+}
+
+impl Accesses {
+    fn assign(&mut self, lhs: &sv::Expr, rhs: &sv::Expr, op: sv::AssignOp) {
+        self.drives.push(lower_expr(lhs));
+        if op != sv::AssignOp::Identity {
+            self.reads.push(lower_expr(lhs));
+        }
+        self.reads.push(lower_expr(rhs));
+    }
+}
+
+impl<'a> sv::Visitor<'a> for Accesses {
+    fn pre_visit_stmt(&mut self, node: &'a sv::Stmt<'a>) -> bool {
+        match node.kind {
+            sv::BlockingAssignStmt {
+                ref lhs,
+                ref rhs,
+                op,
+                ..
+            } => self.assign(lhs, rhs, op),
+            sv::NonblockingAssignStmt {
+                ref lhs, ref rhs, ..
+            } => self.assign(lhs, rhs, sv::AssignOp::Identity),
+            _ => return true,
+        }
+        false
+    }
+
+    fn pre_visit_expr(&mut self, node: &'a sv::Expr<'a>) -> bool {
+        match node.data {
+            sv::AssignExpr {
+                ref lhs,
+                ref rhs,
+                op,
+            } => self.assign(lhs, rhs, op),
+            sv::UnaryExpr {
+                op: Op::Inc,
+                ref expr,
+                ..
+            }
+            | sv::UnaryExpr {
+                op: Op::Dec,
+                ref expr,
+                ..
+            } => {
+                self.drives.push(lower_expr(expr));
+                self.reads.push(lower_expr(expr));
+            }
+            _ => self.reads.push(lower_expr(node)),
+        }
+        false
+    }
+}
+
+fn lower_expr(expr: &sv::Expr) -> Expr {
+    let lower_box = |expr| Box::new(lower_expr(expr));
+    let lower_all = |exprs: &[sv::Expr]| exprs.iter().map(lower_expr).collect();
+    let kind = match expr.data {
+        sv::IdentExpr(name) | sv::SysIdentExpr(name) => ExprKind::Name(name.value),
+        sv::LiteralExpr(_) => ExprKind::Literal,
+        sv::UnaryExpr { op, ref expr, .. } => ExprKind::Unary(op.to_string(), lower_box(expr)),
+        sv::BinaryExpr {
+            op,
+            ref lhs,
+            ref rhs,
+        } => ExprKind::Binary(op.to_string(), lower_box(lhs), lower_box(rhs)),
+        sv::RangeExpr {
+            mode,
+            ref lhs,
+            ref rhs,
+        } => {
+            let op = match mode {
+                sv::RangeMode::Absolute => ":",
+                sv::RangeMode::RelativeUp => "+:",
+                sv::RangeMode::RelativeDown => "-:",
+            };
+            ExprKind::Binary(op.to_string(), lower_box(lhs), lower_box(rhs))
+        }
+        sv::TernaryExpr {
+            ref cond,
+            ref true_expr,
+            ref false_expr,
+        } => ExprKind::Cond(lower_box(cond), lower_box(true_expr), lower_box(false_expr)),
+        sv::IndexExpr {
+            ref indexee,
+            ref index,
+        } => ExprKind::Index(lower_box(indexee), vec![lower_expr(index)]),
+        sv::MemberExpr { ref expr, name } => ExprKind::Field(lower_box(expr), name),
+        sv::CallExpr(ref callee, ref args) => ExprKind::Call(
+            lower_box(callee),
+            args.iter()
+                .filter_map(|arg| arg.expr.as_ref())
+                .map(lower_expr)
+                .collect(),
+        ),
+        sv::ConcatExpr {
+            repeat: None,
+            ref exprs,
+        } => ExprKind::Concat(lower_all(exprs)),
+        sv::ConcatExpr {
+            repeat: Some(ref repeat),
+            ref exprs,
+        } => {
+            let mut exprs: Vec<_> = lower_all(exprs);
+            exprs.insert(0, lower_expr(repeat));
+            ExprKind::Other(exprs)
+        }
+        sv::AssignExpr {
+            ref lhs, ref rhs, ..
+        } => ExprKind::Other(vec![lower_expr(lhs), lower_expr(rhs)]),
+        sv::MinTypMaxExpr {
+            ref min,
+            ref typ,
+            ref max,
+        } => ExprKind::Other(vec![lower_expr(min), lower_expr(typ), lower_expr(max)]),
+        sv::CastExpr(_, ref expr) | sv::CastSignExpr(_, ref expr) | sv::InsideExpr(ref expr, _) => {
+            ExprKind::Other(vec![lower_expr(expr)])
+        }
+        sv::CastSizeExpr(ref size, ref expr) => {
+            ExprKind::Other(vec![lower_expr(size), lower_expr(expr)])
+        }
+        sv::BitsExpr {
+            arg: sv::TypeOrExpr::Expr(expr),
+            ..
+        } => ExprKind::Other(vec![lower_expr(expr)]),
+        _ => ExprKind::Other(vec![]),
+    };
+    Expr {
+        span: expr.span,
+        kind,
+    }
+}
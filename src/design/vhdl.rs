@@ -0,0 +1,441 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Lowering of VHDL entities and architectures.
+
+use super::*;
+use crate::vhdl::syntax::ast as vhdl;
+use std::collections::HashMap;
+
+/// Lower the entities among a list of design units.
+///
+/// Each entity is lowered together with its last architecture, which is the
+/// one VHDL binds to by default.
+pub(super) fn lower_units(units: &[&vhdl::DesignUnit], into: &mut Vec<Unit>) {
+    let mut archs = HashMap::new();
+    for unit in units {
+        if let vhdl::DesignUnitData::ArchBody(ref arch) = unit.data {
+            if let Some(entity) = last_ident(&arch.target) {
+                archs.insert(entity.value.as_str().to_lowercase(), arch);
+            }
+        }
+    }
+    for unit in units {
+        if let vhdl::DesignUnitData::EntityDecl(ref entity) = unit.data {
+            let arch = archs.get(&entity.name.value.as_str().to_lowercase());
+            into.push(lower_entity(entity, arch.copied()));
+        }
+    }
+}
+
+fn lower_entity(entity: &vhdl::EntityDecl, arch: Option<&vhdl::ArchBody>) -> Unit {
+    let mut unit = Unit {
+        name: entity.name,
+        language: Language::Vhdl,
+        span: entity.span,
+        params: vec![],
+        ports: vec![],
+        signals: vec![],
+        processes: vec![],
+        instances: vec![],
+    };
+    for decl in &entity.decls {
+        if let vhdl::DeclItem::PortgenClause(_, kind, ref intfs) = *decl {
+            for intf in &intfs.value {
+                match kind.value {
+                    vhdl::PortgenKind::Generic => lower_generic(intf, &mut unit.params),
+                    vhdl::PortgenKind::Port => lower_port(intf, &mut unit.ports),
+                }
+            }
+        }
+    }
+    lower_decls(&entity.decls, &mut unit);
+    if let Some(ref stmts) = entity.stmts {
+        lower_stmts(stmts, false, &mut unit);
+    }
+    if let Some(arch) = arch {
+        lower_decls(&arch.decls, &mut unit);
+        lower_stmts(&arch.stmts, false, &mut unit);
+    }
+    unit
+}
+
+fn lower_generic(intf: &vhdl::IntfDecl, into: &mut Vec<Param>) {
+    match *intf {
+        vhdl::IntfDecl::ObjDecl(ref decl) => into.extend(decl.names.iter().map(|name| Param {
+            name: Spanned::new(name.name, name.span),
+            span: decl.span,
+            is_type: false,
+            local: false,
+            default: decl.default.as_ref().map(lower_expr),
+        })),
+        vhdl::IntfDecl::TypeDecl(ref decl) => into.push(Param {
+            name: decl.name,
+            span: decl.span,
+            is_type: true,
+            local: false,
+            default: None,
+        }),
+        _ => (),
+    }
+}
+
+fn lower_port(intf: &vhdl::IntfDecl, into: &mut Vec<Port>) {
+    if let vhdl::IntfDecl::ObjDecl(ref decl) = *intf {
+        let dir = match decl.mode {
+            None | Some(vhdl::IntfMode::In) => PortDir::Input,
+            Some(vhdl::IntfMode::Out) | Some(vhdl::IntfMode::Buffer) => PortDir::Output,
+            Some(vhdl::IntfMode::Inout) | Some(vhdl::IntfMode::Linkage) => PortDir::Inout,
+        };
+        into.extend(decl.names.iter().map(|name| Port {
+            name: Spanned::new(name.name, name.span),
+            span: decl.span,
+            dir,
+        }));
+    }
+}
+
+/// Lower the signals among a list of declarations.
+fn lower_decls(decls: &[vhdl::DeclItem], unit: &mut Unit) {
+    for decl in decls {
+        if let vhdl::DeclItem::ObjDecl(ref decl) = *decl {
+            if decl.kind == vhdl::ObjKind::Signal {
+                unit.signals.extend(decl.names.iter().map(|name| Signal {
+                    name: Spanned::new(name.name, name.span),
+                    span: decl.span,
+                }));
+            }
+        }
+    }
+}
+
+/// Lower concurrent statements.
+fn lower_stmts(stmts: &[vhdl::Stmt], conditional: bool, unit: &mut Unit) {
+    for stmt in stmts {
+        match stmt.data {
+            vhdl::ProcStmt {
+                ref sensitivity,
+                ref stmts,
+                ..
+            } => {
+                let mut accesses = Accesses::default();
+                if let Some(vhdl::Sensitivity::List(ref names)) = *sensitivity {
+                    accesses.reads.extend(names.iter().map(lower_name));
+                }
+                accesses.stmts(stmts);
+                unit.processes
+                    .push(accesses.finish(ProcessKind::Process, stmt.span));
+            }
+            vhdl::AssignStmt { .. } | vhdl::SelectAssignStmt { .. } => {
+                let mut accesses = Accesses::default();
+                accesses.stmt(stmt);
+                unit.processes
+                    .push(accesses.finish(ProcessKind::Assign, stmt.span));
+            }
+            // Without a keyword, only component instantiations have maps; the
+            // others are procedure calls.
+            vhdl::InstOrCallStmt {
+                target,
+                ref name,
+                ref generics,
+                ref ports,
+            } if target.is_some() || generics.is_some() || ports.is_some() => {
+                let (label, target) = match (stmt.label, last_ident(name)) {
+                    (Some(label), Some(target)) => (label, target),
+                    _ => continue,
+                };
+                unit.instances.push(Instance {
+                    name: label,
+                    span: stmt.span,
+                    target,
+                    params: generics.iter().flat_map(lower_elems).collect(),
+                    ports: ports.iter().flat_map(lower_elems).collect(),
+                    conditional,
+                });
+            }
+            vhdl::BlockStmt {
+                ref decls,
+                ref stmts,
+                ..
+            } => {
+                lower_decls(decls, unit);
+                lower_stmts(stmts, conditional, unit);
+            }
+            vhdl::IfGenStmt { ref conds, ref alt } => {
+                for body in conds.iter().map(|(_, body)| body).chain(alt) {
+                    lower_decls(&body.decls, unit);
+                    lower_stmts(&body.stmts, true, unit);
+                }
+            }
+            vhdl::CaseGenStmt { ref cases, .. } => {
+                for (_, body) in cases {
+                    lower_decls(&body.decls, unit);
+                    lower_stmts(&body.stmts, true, unit);
+                }
+            }
+            vhdl::ForGenStmt { ref body, .. } => {
+                lower_decls(&body.decls, unit);
+                lower_stmts(&body.stmts, true, unit);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Lower the generic or port map of an instance.
+fn lower_elems(elems: &vhdl::ParenElems) -> Vec<Conn> {
+    elems
+        .value
+        .iter()
+        .map(|elem| Conn {
+            span: elem.span,
+            name: elem
+                .choices
+                .value
+                .first()
+                .and_then(|choice| match choice.data {
+                    vhdl::NameExpr(ref name) => match name.primary.kind {
+                        vhdl::PrimaryNameKind::Ident(n) => Some(Spanned::new(n, name.primary.span)),
+                        _ => None,
+                    },
+                    _ => None,
+                }),
+            expr: match elem.expr.data {
+                vhdl::OpenExpr => None,
+                _ => Some(lower_expr(&elem.expr)),
+            },
+        })
+        .collect()
+}
+
+/// Collects the assigned and read expressions of sequential statements.
+#[derive(Default)]
+struct Accesses {
+    drives: Vec<Expr>,
+    reads: Vec<Expr>,
+}
+
+impl Accesses {
+    fn finish(self, kind: ProcessKind, span: Span) -> Process {
+        Process {
+            kind,
+            span,
+            drives: self.drives,
+            reads: self.reads,
+        }
+    }
+
+    fn read(&mut self, expr: &Option<vhdl::Expr>) {
+        self.reads.extend(expr.iter().map(lower_expr));
+    }
+
+    fn stmts(&mut self, stmts: &[vhdl::Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &vhdl::Stmt) {
+        match stmt.data {
+            vhdl::AssignStmt {
+                ref target,
+                ref mode,
+                ..
+            } => {
+                self.target(&target.value);
+                match mode.value {
+                    vhdl::AssignMode::Normal(_, ref waves)
+                    | vhdl::AssignMode::Force(_, ref waves) => {
+                        for vhdl::CondWave(wave, cond) in waves {
+                            self.wave(wave);
+                            self.read(cond);
+                        }
+                    }
+                    vhdl::AssignMode::Release(_) => (),
+                }
+            }
+            vhdl::SelectAssignStmt {
+                ref select,
+                ref target,
+                ref waves,
+                ..
+            } => {
+                self.target(&target.value);
+                self.reads.push(lower_expr(select));
+                for vhdl::SelectWave(wave, _) in waves {
+                    self.wave(wave);
+                }
+            }
+            vhdl::IfStmt { ref conds, ref alt } => {
+                for (cond, body) in conds {
+                    self.reads.push(lower_expr(cond));
+                    self.stmts(&body.stmts);
+                }
+                if let Some(ref body) = *alt {
+                    self.stmts(&body.stmts);
+                }
+            }
+            vhdl::CaseStmt {
+                ref switch,
+                ref cases,
+                ..
+            } => {
+                self.reads.push(lower_expr(switch));
+                for (_, body) in cases {
+                    self.stmts(&body.stmts);
+                }
+            }
+            vhdl::LoopStmt {
+                ref scheme,
+                ref body,
+            } => {
+                match *scheme {
+                    vhdl::LoopScheme::While(ref expr) | vhdl::LoopScheme::For(_, ref expr) => {
+                        self.reads.push(lower_expr(expr))
+                    }
+                    vhdl::LoopScheme::Loop => (),
+                }
+                self.stmts(&body.stmts);
+            }
+            vhdl::WaitStmt {
+                ref on,
+                ref until,
+                ref time,
+            } => {
+                if let Some(ref names) = *on {
+                    self.reads.extend(names.value.iter().map(lower_name));
+                }
+                self.read(until);
+                self.read(time);
+            }
+            vhdl::AssertStmt {
+                ref cond,
+                ref report,
+                ref severity,
+            } => {
+                self.reads.push(lower_expr(cond));
+                self.read(report);
+                self.read(severity);
+            }
+            vhdl::ReportStmt {
+                ref msg,
+                ref severity,
+            } => {
+                self.reads.push(lower_expr(msg));
+                self.read(severity);
+            }
+            vhdl::NexitStmt { ref cond, .. } => self.read(cond),
+            vhdl::ReturnStmt(ref expr) => self.read(expr),
+            vhdl::InstOrCallStmt { ref name, .. } => self.reads.push(lower_name(name)),
+            _ => (),
+        }
+    }
+
+    fn target(&mut self, target: &vhdl::AssignTarget) {
+        match *target {
+            vhdl::AssignTarget::Name(ref name) => self.drives.push(lower_name(name)),
+            vhdl::AssignTarget::Aggregate(ref elems) => self
+                .drives
+                .extend(elems.value.iter().map(|elem| lower_expr(&elem.expr))),
+        }
+    }
+
+    fn wave(&mut self, wave: &vhdl::Wave) {
+        for (value, after) in wave.elems.iter().flatten() {
+            self.reads.push(lower_expr(value));
+            self.read(after);
+        }
+    }
+}
+
+fn lower_expr(expr: &vhdl::Expr) -> Expr {
+    let lower_elems = |elems: &vhdl::ParenElems| {
+        elems
+            .value
+            .iter()
+            .map(|elem| lower_expr(&elem.expr))
+            .collect()
+    };
+    let kind = match expr.data {
+        vhdl::NameExpr(ref name) => return lower_name(name),
+        vhdl::LitExpr(..) => ExprKind::Literal,
+        vhdl::UnaryExpr(op, ref arg) => {
+            ExprKind::Unary(op.span.extract().to_lowercase(), Box::new(lower_expr(arg)))
+        }
+        vhdl::BinaryExpr(op, ref lhs, ref rhs) => ExprKind::Binary(
+            op.span.extract().to_lowercase(),
+            Box::new(lower_expr(lhs)),
+            Box::new(lower_expr(rhs)),
+        ),
+        // A single element without choices is a parenthesized expression.
+        vhdl::ParenExpr(ref elems) => match elems.value.as_slice() {
+            [elem] if elem.choices.value.is_empty() => return lower_expr(&elem.expr),
+            _ => ExprKind::Other(lower_elems(elems)),
+        },
+        vhdl::QualExpr(_, ref elems) => ExprKind::Other(lower_elems(elems)),
+        vhdl::NewExpr(ref expr) => ExprKind::Other(vec![lower_expr(expr)]),
+        _ => ExprKind::Other(vec![]),
+    };
+    Expr {
+        span: expr.span,
+        kind,
+    }
+}
+
+/// Lower a name, such as `a`, `a.b`, `a(i)`, or `a'event`.
+fn lower_name(name: &vhdl::CompoundName) -> Expr {
+    let mut expr = Expr {
+        span: name.primary.span,
+        kind: match name.primary.kind {
+            vhdl::PrimaryNameKind::Ident(n) => ExprKind::Name(n),
+            _ => ExprKind::Literal,
+        },
+    };
+    for part in &name.parts {
+        let prefix = Box::new(expr);
+        let kind = match *part {
+            vhdl::NamePart::Select(vhdl::PrimaryName {
+                kind: vhdl::PrimaryNameKind::Ident(n),
+                span,
+                ..
+            }) => ExprKind::Field(prefix, Spanned::new(n, span)),
+            vhdl::NamePart::Call(ref elems) => ExprKind::Index(
+                prefix,
+                elems
+                    .value
+                    .iter()
+                    .map(|elem| lower_expr(&elem.expr))
+                    .collect(),
+            ),
+            vhdl::NamePart::Range(ref range) => ExprKind::Index(prefix, vec![lower_expr(range)]),
+            vhdl::NamePart::Signature(_) => {
+                expr = *prefix;
+                continue;
+            }
+            _ => ExprKind::Other(vec![*prefix]),
+        };
+        expr = Expr {
+            span: name.span,
+            kind,
+        };
+    }
+    expr
+}
+
+/// Get the last identifier in a name, as in the `foo` of `work.foo`.
+fn last_ident(name: &vhdl::CompoundName) -> Option<Spanned<Name>> {
+    let mut last = match name.primary.kind {
+        vhdl::PrimaryNameKind::Ident(x) => Some(Spanned::new(x, name.primary.span)),
+        _ => None,
+    };
+    for part in &name.parts {
+        if let vhdl::NamePart::Select(vhdl::PrimaryName {
+            kind: vhdl::PrimaryNameKind::Ident(x),
+            span,
+            ..
+        }) = *part
+        {
+            last = Some(Spanned::new(x, span));
+        }
+    }
+    last
+}
@@ -0,0 +1,323 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Documentation extracted from doc comments.
+//!
+//! Lists the modules, interfaces, packages, and entities of a set of parsed
+//! files together with their parameters and ports, and the doc comments which
+//! precede them. SystemVerilog doc comments start with `///` or `/**`, VHDL
+//! doc comments with `--!` or `/**`. The documentation can be serialized to
+//! JSON, or rendered as a standalone HTML page.
+
+use crate::common::name::Name;
+use crate::common::source::{Source, Span, Spanned};
+use crate::score::Ast;
+use crate::svlog::ast as sv;
+use crate::symbols::SymbolKind;
+use crate::vhdl::syntax::ast as vhdl;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// The documentation of a module, interface, package, or entity.
+#[derive(Debug, Serialize)]
+pub struct UnitDoc {
+    /// The name of the unit.
+    pub name: String,
+    /// The kind of unit.
+    pub kind: SymbolKind,
+    /// The file the unit is declared in.
+    pub file: Source,
+    /// The line of the name, starting at 1.
+    pub line: usize,
+    /// The text of the doc comment, if any.
+    pub doc: Option<String>,
+    /// The parameters or generics of the unit.
+    pub params: Vec<ItemDoc>,
+    /// The ports of the unit.
+    pub ports: Vec<ItemDoc>,
+}
+
+/// The documentation of a parameter or port.
+#[derive(Debug, Serialize)]
+pub struct ItemDoc {
+    /// The name of the parameter or port.
+    pub name: String,
+    /// The declaration as written in the source. Names declared together
+    /// share their declaration.
+    pub decl: String,
+    /// The text of the doc comment, if any.
+    pub doc: Option<String>,
+}
+
+/// The documentation of a set of files, in the order the units appear.
+#[derive(Debug, Default, Serialize)]
+pub struct Documentation {
+    pub units: Vec<UnitDoc>,
+}
+
+impl Documentation {
+    /// Collect the documentation of a list of parsed files.
+    pub fn new<'a>(asts: &'a [Ast<'a>]) -> Documentation {
+        let mut docs = Documentation::default();
+        for ast in asts {
+            match ast {
+                Ast::Svlog(file) => {
+                    for item in &file.items {
+                        docs.add_svlog(item);
+                    }
+                }
+                Ast::Vhdl(units) => {
+                    for unit in units {
+                        docs.add_vhdl(unit);
+                    }
+                }
+            }
+        }
+        docs
+    }
+
+    fn add_svlog(&mut self, item: &sv::Item) {
+        let (kind, name, doc, params, ports, items) = match item.data {
+            sv::ItemData::ModuleDecl(ref m) if !m.foreign => (
+                SymbolKind::Module,
+                m.name,
+                m.doc,
+                &m.params,
+                &m.ports,
+                &m.items,
+            ),
+            sv::ItemData::InterfaceDecl(ref i) => (
+                SymbolKind::Interface,
+                i.name,
+                i.doc,
+                &i.params,
+                &i.ports,
+                &i.items,
+            ),
+            sv::ItemData::PackageDecl(ref p) => {
+                self.units.push(unit(SymbolKind::Package, p.name, p.doc));
+                return;
+            }
+            _ => return,
+        };
+        let mut unit = unit(kind, name, doc);
+
+        // Without a parameter port list, the parameters are declared in the
+        // body. Without ANSI ports, the ports are declared in the body.
+        let mut body_params = vec![];
+        let mut body_ports = vec![];
+        for item in items {
+            match item.data {
+                sv::ItemData::ParamDecl(ref p) => body_params.push(p),
+                sv::ItemData::PortDecl(ref p) => body_ports.push(p),
+                _ => (),
+            }
+        }
+        let params = if params.is_empty() {
+            body_params
+        } else {
+            params.iter().collect()
+        };
+        for param in params.into_iter().filter(|p| !p.local) {
+            let decl = param.span.extract();
+            let names: Vec<_> = match param.kind {
+                sv::ParamKind::Type(ref decls) => decls.iter().map(|d| d.name.value).collect(),
+                sv::ParamKind::Value(ref decls) => decls.iter().map(|d| d.name.value).collect(),
+            };
+            for name in names {
+                unit.params.push(item_doc(name, &decl, param.doc));
+            }
+        }
+        if body_ports.is_empty() {
+            for port in ports {
+                let (name, doc) = match port.data {
+                    sv::PortData::Intf { name, doc, .. }
+                    | sv::PortData::Explicit { name, doc, .. }
+                    | sv::PortData::Named { name, doc, .. } => (name, doc),
+                    sv::PortData::Implicit(_) => continue,
+                };
+                unit.ports
+                    .push(item_doc(name.value, &port.span.extract(), doc));
+            }
+        } else {
+            for port in body_ports {
+                let decl = port.span.extract();
+                let decl = decl.trim_end_matches(';');
+                for name in &port.names {
+                    unit.ports.push(item_doc(name.name, decl, port.doc));
+                }
+            }
+        }
+        self.units.push(unit);
+    }
+
+    fn add_vhdl(&mut self, unit: &vhdl::DesignUnit) {
+        let entity = match unit.data {
+            vhdl::DesignUnitData::EntityDecl(ref e) => e,
+            vhdl::DesignUnitData::PkgDecl(ref p) => {
+                self.units
+                    .push(self::unit(SymbolKind::Package, p.name, p.doc));
+                return;
+            }
+            _ => return,
+        };
+        let mut unit = self::unit(SymbolKind::Entity, entity.name, entity.doc);
+        for decl in &entity.decls {
+            let (kind, decls) = match decl {
+                vhdl::DeclItem::PortgenClause(_, kind, decls) => (kind.value, &decls.value),
+                _ => continue,
+            };
+            for decl in decls {
+                let decl = match decl {
+                    vhdl::IntfDecl::ObjDecl(x) => x,
+                    _ => continue,
+                };
+                let text = decl.span.extract();
+                let text = text.trim_end_matches(';');
+                for name in &decl.names {
+                    let item = item_doc(name.name, text, decl.doc);
+                    match kind {
+                        vhdl::PortgenKind::Generic => unit.params.push(item),
+                        vhdl::PortgenKind::Port => unit.ports.push(item),
+                    }
+                }
+            }
+        }
+        self.units.push(unit);
+    }
+
+    /// Render the documentation as a standalone HTML page.
+    pub fn to_html(&self) -> String {
+        let mut output = String::new();
+        output.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        output.push_str("<title>Design Documentation</title>\n");
+        output.push_str("<style>\n");
+        output.push_str("body { font-family: sans-serif; max-width: 60em; margin: auto; }\n");
+        output.push_str("table { border-collapse: collapse; }\n");
+        output.push_str("th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; vertical-align: top; }\n");
+        output.push_str(".location { color: #666; }\n");
+        output.push_str("</style>\n</head>\n<body>\n");
+        output.push_str("<h1>Design Documentation</h1>\n<ul>\n");
+        for unit in &self.units {
+            writeln!(
+                output,
+                "<li><a href=\"#{}-{}\">{} <code>{}</code></a></li>",
+                kind_name(unit.kind),
+                escape(&unit.name),
+                unit.kind.as_str(),
+                escape(&unit.name)
+            )
+            .unwrap();
+        }
+        output.push_str("</ul>\n");
+        for unit in &self.units {
+            writeln!(
+                output,
+                "<section id=\"{}-{}\">\n<h2>{} <code>{}</code></h2>",
+                kind_name(unit.kind),
+                escape(&unit.name),
+                unit.kind.as_str(),
+                escape(&unit.name)
+            )
+            .unwrap();
+            writeln!(
+                output,
+                "<p class=\"location\">{}:{}</p>",
+                escape(&unit.file.get_path()),
+                unit.line
+            )
+            .unwrap();
+            if let Some(ref doc) = unit.doc {
+                write_paragraphs(&mut output, doc);
+            }
+            let title = match unit.kind {
+                SymbolKind::Entity => "Generics",
+                _ => "Parameters",
+            };
+            write_items(&mut output, title, &unit.params);
+            write_items(&mut output, "Ports", &unit.ports);
+            output.push_str("</section>\n");
+        }
+        output.push_str("</body>\n</html>\n");
+        output
+    }
+}
+
+fn unit(kind: SymbolKind, name: Spanned<Name>, doc: Option<Spanned<Name>>) -> UnitDoc {
+    UnitDoc {
+        name: name.value.to_string(),
+        kind,
+        file: name.span.source,
+        line: line(name.span),
+        doc: doc.map(|d| d.value.to_string()),
+        params: vec![],
+        ports: vec![],
+    }
+}
+
+fn item_doc(name: Name, decl: &str, doc: Option<Spanned<Name>>) -> ItemDoc {
+    ItemDoc {
+        name: name.to_string(),
+        decl: decl.to_string(),
+        doc: doc.map(|d| d.value.to_string()),
+    }
+}
+
+fn line(span: Span) -> usize {
+    span.begin().human_line()
+}
+
+/// Render a table of parameters or ports, if there are any.
+fn write_items(output: &mut String, title: &str, items: &[ItemDoc]) {
+    if items.is_empty() {
+        return;
+    }
+    writeln!(output, "<h3>{}</h3>", title).unwrap();
+    output.push_str("<table>\n<tr><th>Name</th><th>Declaration</th><th>Description</th></tr>\n");
+    for item in items {
+        writeln!(
+            output,
+            "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>",
+            escape(&item.name),
+            escape(&item.decl),
+            item.doc.as_ref().map(|d| escape(d)).unwrap_or_default()
+        )
+        .unwrap();
+    }
+    output.push_str("</table>\n");
+}
+
+/// Render the text of a doc comment, with paragraphs separated by empty
+/// lines.
+fn write_paragraphs(output: &mut String, doc: &str) {
+    let mut para = vec![];
+    for line in doc.lines().chain(std::iter::once("")) {
+        if line.trim().is_empty() {
+            if !para.is_empty() {
+                writeln!(output, "<p>{}</p>", escape(&para.join("\n"))).unwrap();
+                para.clear();
+            }
+        } else {
+            para.push(line);
+        }
+    }
+}
+
+/// The name of a kind in an HTML anchor, which may not contain spaces.
+fn kind_name(kind: SymbolKind) -> String {
+    kind.as_str().replace(' ', "-")
+}
+
+/// Escape the characters which have a special meaning in HTML.
+fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(c),
+        }
+    }
+    output
+}
@@ -0,0 +1,2102 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! The command-line driver of the compiler.
+//!
+//! The `moore` executable only calls `main`. Downstream crates may provide
+//! their own executable which registers additional analysis passes with
+//! `pass::register` before calling `main`.
+
+use crate::common::score::NodeRef;
+use crate::common::util::{HasDesc, HasSpan};
+use crate::errors::*;
+use crate::name::Name;
+use crate::score::{ScoreBoard, ScoreContext};
+use crate::svlog::{hir::Visitor as _, QueryDatabase as _};
+use crate::*;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use llhd;
+use llhd::opt::{Pass, PassContext};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Verilog,
+    SystemVerilog,
+    Vhdl,
+}
+
+/// What to do with the elaborated design.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Emit the design in an output format.
+    Compile,
+    /// Simulate the design and emit a waveform.
+    Simulate,
+}
+
+/// Parse the command-line arguments and run the compiler.
+pub fn main() {
+    // Expand the file lists and parse the command-line arguments.
+    let args = match crate::file_list::expand_args(std::env::args()) {
+        Ok(args) => args,
+        Err(diag) => {
+            Session::new().emit(diag);
+            std::process::exit(1);
+        }
+    };
+    let app_matches = App::new(env!("CARGO_PKG_NAME"))
+        .version(clap::crate_version!())
+        .author(clap::crate_authors!())
+        .about(clap::crate_description!())
+        .arg(
+            Arg::with_name("trace_scoreboard")
+                .long("trace-scoreboard")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("log")
+                .long("log")
+                .value_name("FILTER")
+                .help("Sets the debug log filter, e.g. `debug` or `moore_svlog::parser=trace`; overrides MOORE_LOG")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("verbosity-opts")
+                .short("V")
+                .help("Sets verbosity settings")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&[
+                    "types",
+                    "expr-types",
+                    "type-contexts",
+                    "typeck",
+                    "names",
+                    "casts",
+                    "ports",
+                    "consts",
+                    "insts",
+                    "procs",
+                ])
+                .global(true),
+        )
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .args(&compile_args())
+        .subcommand(
+            SubCommand::with_name("sim")
+                .about("Simulate a design and write a VCD waveform")
+                .args(&compile_args())
+                .arg(
+                    Arg::with_name("until")
+                        .long("until")
+                        .value_name("TIME")
+                        .help("Stop the simulation after a physical time, e.g. `100ns`")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lsp")
+                .about("Run a language server on stdin and stdout")
+                .args(&parse_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("fmt")
+                .about("Format SystemVerilog source files")
+                .args(&parse_args())
+                .args(&fmt_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("conformance")
+                .about("Check the parser against a corpus of tests laid out like sv-tests")
+                .args(&parse_args())
+                .arg(
+                    Arg::with_name("jobs")
+                        .short("j")
+                        .long("jobs")
+                        .value_name("N")
+                        .help("Number of threads to parse the tests with [default: number of CPUs]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("DIR")
+                        .help("The directory of the test files")
+                        .required(true),
+                ),
+        )
+        .get_matches_from(args);
+
+    // Configure the logger.
+    let filter = app_matches
+        .subcommand()
+        .1
+        .and_then(|m| m.value_of("log"))
+        .or_else(|| app_matches.value_of("log"))
+        .map(String::from)
+        .or_else(|| std::env::var("MOORE_LOG").ok());
+    let mut builder = pretty_env_logger::formatted_builder();
+    builder.parse_filters(filter.as_ref().map(|s| s.as_str()).unwrap_or("off"));
+    builder.try_init().unwrap();
+
+    match app_matches.subcommand() {
+        ("lsp", Some(matches)) => std::process::exit(lsp(matches)),
+        ("fmt", Some(matches)) => std::process::exit(fmt(matches)),
+        ("conformance", Some(matches)) => std::process::exit(conformance(matches)),
+        _ => (),
+    }
+    let (matches, command) = match app_matches.subcommand() {
+        ("sim", Some(matches)) => (matches, Command::Simulate),
+        _ => (&app_matches, Command::Compile),
+    };
+
+    // Configure the session.
+    let mut session = Session::new();
+    session.opts.trace_scoreboard = matches.is_present("trace_scoreboard");
+    for v in matches
+        .values_of("verbosity-opts")
+        .into_iter()
+        .flat_map(|v| v)
+    {
+        session.opts.verbosity |= match v {
+            "types" => Verbosity::TYPES,
+            "expr-types" => Verbosity::EXPR_TYPES,
+            "type-contexts" => Verbosity::TYPE_CONTEXTS,
+            "typeck" => Verbosity::TYPECK,
+            "names" => Verbosity::NAMES,
+            "casts" => Verbosity::CASTS,
+            "ports" => Verbosity::PORTS,
+            "consts" => Verbosity::CONSTS,
+            "insts" => Verbosity::INSTS,
+            "procs" => Verbosity::PROCS,
+            _ => unreachable!(),
+        };
+    }
+    session.opts.opt_level = matches.value_of("opt-level").unwrap().parse().unwrap();
+    session.opts.debug_info = matches.is_present("debug-info");
+    session.opts.keep_names = matches.is_present("keep-names");
+    session.opts.four_state = match matches.value_of("four-state").unwrap() {
+        "paired" => FourState::Paired,
+        _ => FourState::TwoState,
+    };
+    session.opts.allow_blackbox = matches.is_present("allow-blackbox");
+    session.opts.single_unit = matches.is_present("single-unit");
+
+    // Invoke the compiler.
+    score(&session, matches, command);
+}
+
+/// Serve the Language Server Protocol until the client shuts the server down.
+fn lsp(matches: &ArgMatches) -> i32 {
+    let sess = Session::new();
+    let standards = Standards::from_matches(matches);
+    let opts = crate::lsp::Options {
+        include_paths: matches
+            .values_of("inc")
+            .into_iter()
+            .flatten()
+            .map(std::path::PathBuf::from)
+            .collect(),
+        defines: parse_defines(&sess, matches)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.map(String::from)))
+            .collect(),
+        svlog_standard: standards.svlog,
+        vhdl_standard: standards.vhdl,
+        verilog: standards.verilog,
+    };
+    match crate::lsp::run(&opts) {
+        Ok(()) => 0,
+        Err(e) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "language server failed: {}",
+                e
+            )));
+            1
+        }
+    }
+}
+
+/// Print the available lints for `-W help`.
+fn list_lints() {
+    println!("Available lints:");
+    for (rule, _) in svlog::lint::Linter::new().rules() {
+        println!(
+            "    {}  {:<20}  {}",
+            rule.code(),
+            rule.name(),
+            rule.description()
+        );
+    }
+}
+
+/// Check the SystemVerilog files for the lints selected with `-W`.
+///
+/// Fails if a lint is unknown, or if a lint is reported as an error.
+fn lint<'a>(sess: &Session, matches: &ArgMatches, asts: &'a [score::Ast<'a>]) -> Result<(), ()> {
+    let mut linter = svlog::lint::Linter::new();
+    for flag in matches.values_of("lint").into_iter().flatten() {
+        if linter.configure(flag).is_err() {
+            sess.emit(
+                DiagBuilder2::fatal(format!("unknown lint `{}`", flag))
+                    .add_note("Use `-W help` to list the available lints"),
+            );
+            return Err(());
+        }
+    }
+    let files: Vec<_> = asts
+        .iter()
+        .filter_map(|ast| match ast {
+            score::Ast::Svlog(x) => Some(x),
+            _ => None,
+        })
+        .collect();
+    let mut failed = false;
+    for diag in linter.check(&files) {
+        failed |= diag.severity >= Severity::Error;
+        sess.emit(diag);
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(())
+    }
+}
+
+/// Format SystemVerilog source files, printing them to stdout or writing
+/// them back in place.
+///
+/// Files are parsed first, such that files with syntax errors are left alone.
+/// The formatted text is lexed again and compared against the original tokens
+/// as a safeguard against the formatter changing the meaning of a file.
+fn fmt(matches: &ArgMatches) -> i32 {
+    let sess = Session::new();
+    let standards = Standards::from_matches(matches);
+    let include_paths: Vec<_> = matches
+        .values_of("inc")
+        .into_iter()
+        .flatten()
+        .map(Path::new)
+        .collect();
+    let defines = parse_defines(&sess, matches);
+    let number = |name: &str| match matches.value_of(name).map(|x| x.parse()) {
+        None => None,
+        Some(Ok(x)) => Some(x),
+        Some(Err(_)) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "invalid value `{}` for --{}",
+                matches.value_of(name).unwrap(),
+                name
+            )));
+            std::process::exit(1);
+        }
+    };
+    let defaults = svlog::format::FormatOptions::default();
+    let opts = svlog::format::FormatOptions {
+        indent_width: number("indent").unwrap_or(defaults.indent_width),
+        use_tabs: matches.is_present("tabs"),
+        max_width: number("max-width").unwrap_or(defaults.max_width),
+        ..defaults
+    };
+
+    let lex = |source| {
+        let preproc = svlog::preproc::Preprocessor::new(source, &include_paths, &defines)
+            .with_standard(standards.svlog);
+        svlog::lexer::Lexer::new(preproc)
+    };
+    let input_files = match crate::inputs::expand(matches.values_of("INPUT").into_iter().flatten())
+    {
+        Ok(x) => x,
+        Err(diag) => {
+            sess.emit(diag);
+            return 1;
+        }
+    };
+    let mut failed = false;
+    for filename in input_files.iter().map(String::as_str) {
+        match detect_language(&sess, filename) {
+            Some(Language::SystemVerilog) | Some(Language::Verilog) => (),
+            Some(Language::Vhdl) => {
+                sess.emit(
+                    DiagBuilder2::warning(format!("ignoring `{}`", filename))
+                        .add_note("Only SystemVerilog and Verilog files can be formatted"),
+                );
+                continue;
+            }
+            None => continue,
+        }
+        let input = match read_input(filename) {
+            Ok(x) => x,
+            Err(e) => {
+                sess.emit(DiagBuilder2::fatal(format!(
+                    "unable to open `{}`: {}",
+                    filename, e
+                )));
+                failed = true;
+                continue;
+            }
+        };
+        let path = if filename == "-" {
+            STDIN_PATH
+        } else {
+            filename
+        };
+        let source = source::get_source_manager().replace(path, &input);
+        let arena = svlog::ast::Arena::default();
+        if standards.parse_svlog(lex(source), &arena).is_err() {
+            failed = true;
+            continue;
+        }
+        let output = svlog::format::format(&input, &opts);
+
+        // Make sure only whitespace has changed.
+        let formatted = source::get_source_manager().replace(path, &output);
+        let tokens = |source| -> Vec<_> {
+            lex(source)
+                .map(|t| t.map(|(tkn, _)| tkn).map_err(|_| ()))
+                .collect()
+        };
+        if tokens(source) != tokens(formatted) {
+            sess.emit(
+                DiagBuilder2::bug(format!("formatting `{}` changes its tokens", path))
+                    .add_note("The file has been left unchanged"),
+            );
+            failed = true;
+            continue;
+        }
+
+        if matches.is_present("check") {
+            if output != input {
+                println!("{}", path);
+                failed = true;
+            }
+        } else if matches.is_present("write") && filename != "-" {
+            if output != input {
+                if let Err(e) = std::fs::write(filename, &output) {
+                    sess.emit(DiagBuilder2::fatal(format!(
+                        "unable to write `{}`: {}",
+                        filename, e
+                    )));
+                    failed = true;
+                }
+            }
+        } else {
+            print!("{}", output);
+        }
+    }
+    failed as i32
+}
+
+/// Load the files of the library units referenced by the input files.
+///
+/// Returns the parsed VHDL files of each mapped library. SystemVerilog files,
+/// whose modules and packages share one global namespace, and files of the
+/// library the input files are compiled into are added to `asts` instead.
+fn load_libraries<'a>(
+    sess: &Session,
+    libs: &[crate::library::Library],
+    target: Option<usize>,
+    asts: &mut Vec<score::Ast<'a>>,
+    inputs: &[(&Path, source::Source)],
+    include_paths: &[&Path],
+    defines: &[(&str, Option<&str>)],
+    standards: Standards,
+    arena: &'a svlog::ast::Arena<'a>,
+) -> Result<Vec<Vec<score::Ast<'a>>>, ()> {
+    use crate::library::{referenced_units, scoped_names, Loader};
+    let mut lib_asts: Vec<Vec<_>> = libs.iter().map(|_| vec![]).collect();
+    if libs.is_empty() {
+        return Ok(lib_asts);
+    }
+    let scan = |ast: &score::Ast, source| match ast {
+        score::Ast::Svlog(_) => scoped_names(source, include_paths, defines),
+        score::Ast::Vhdl(_) => vec![],
+    };
+
+    // Find the units referenced by the input files.
+    let mut loader = Loader::new(libs).map_err(|d| sess.emit(d))?;
+    for &(path, _) in inputs {
+        loader.mark_loaded(path);
+    }
+    for (ast, &(_, source)) in asts.iter().zip(inputs) {
+        loader.reference(target, referenced_units(ast, scan(ast, source)));
+    }
+
+    // Parse the library files which define these units, and the units they
+    // refer to in turn.
+    let mut failed = false;
+    while let Some((index, path)) = loader.next_file() {
+        let filename = path.display().to_string();
+        let language = match detect_language(sess, &filename) {
+            Some(x) => x,
+            None => continue,
+        };
+        let source = match source::get_source_manager().open(&filename) {
+            Some(x) => x,
+            None => {
+                sess.emit(
+                    DiagBuilder2::fatal(format!("unable to open `{}`", filename)).add_note(
+                        format!("Listed in the index of library `{}`", libs[index].name),
+                    ),
+                );
+                failed = true;
+                continue;
+            }
+        };
+        let ast = match language {
+            Language::SystemVerilog | Language::Verilog => {
+                let preproc = svlog::preproc::Preprocessor::with_macros(
+                    source,
+                    include_paths,
+                    svlog::preproc::MacroTable::new(defines),
+                )
+                .with_standard(standards.svlog);
+                standards
+                    .parse_svlog(svlog::lexer::Lexer::new(preproc), arena)
+                    .map(score::Ast::Svlog)
+            }
+            Language::Vhdl => {
+                vhdl::syntax::parse_with_standard(source, standards.vhdl).map(score::Ast::Vhdl)
+            }
+        };
+        let ast = match ast {
+            Ok(x) => x,
+            Err(()) => {
+                failed = true;
+                continue;
+            }
+        };
+        loader.reference(Some(index), referenced_units(&ast, scan(&ast, source)));
+        if Some(index) == target || matches!(ast, score::Ast::Svlog(_)) {
+            asts.push(ast);
+        } else {
+            lib_asts[index].push(ast);
+        }
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(lib_asts)
+    }
+}
+
+/// The revisions of the language standards that input files are parsed as.
+#[derive(Clone, Copy)]
+struct Standards {
+    svlog: svlog::token::Standard,
+    vhdl: vhdl::syntax::lexer::token::Standard,
+    /// Whether SystemVerilog input is parsed as Verilog.
+    verilog: bool,
+}
+
+impl Standards {
+    /// Determine the standards selected with `--std`, `--vhdl-std`, and
+    /// `--verilog`.
+    fn from_matches(matches: &ArgMatches) -> Self {
+        let verilog = matches.is_present("verilog");
+        Standards {
+            svlog: if verilog {
+                svlog::token::Standard::Ieee1364_2005
+            } else {
+                matches
+                    .value_of("std")
+                    .and_then(svlog::token::Standard::parse)
+                    .unwrap_or_default()
+            },
+            vhdl: matches
+                .value_of("vhdl-std")
+                .and_then(vhdl::syntax::lexer::token::Standard::parse)
+                .unwrap_or_default(),
+            verilog,
+        }
+    }
+
+    /// Parse the tokens of a SystemVerilog file, as Verilog with `--verilog`.
+    fn parse_svlog<'a>(
+        self,
+        input: impl svlog::lexer::TokenSource,
+        arena: &'a svlog::ast::Arena<'a>,
+    ) -> Result<svlog::ast::SourceFile<'a>, ()> {
+        if self.verilog {
+            svlog::parser::parse_verilog(input, arena)
+        } else {
+            svlog::parser::parse(input, arena)
+        }
+    }
+}
+
+/// An input file which has been lexed or parsed on a worker thread.
+enum Lexed {
+    /// The tokens of a SystemVerilog file, which are parsed on the main thread.
+    Svlog(svlog::lexer::Tokens),
+    /// The design units of a VHDL file.
+    Vhdl(Result<Vec<vhdl::syntax::ast::DesignUnit>, ()>),
+}
+
+/// Preprocess and lex a SystemVerilog file, or parse a VHDL file.
+fn lex_or_parse(
+    source: source::Source,
+    language: Language,
+    include_paths: &[&Path],
+    macros: svlog::preproc::MacroTable,
+    standards: Standards,
+) -> Lexed {
+    match language {
+        Language::SystemVerilog | Language::Verilog => {
+            let preproc = svlog::preproc::Preprocessor::with_macros(source, include_paths, macros)
+                .with_standard(standards.svlog);
+            Lexed::Svlog(svlog::lexer::Tokens::new(svlog::lexer::Lexer::new(preproc)))
+        }
+        Language::Vhdl => Lexed::Vhdl(vhdl::syntax::parse_with_standard(source, standards.vhdl)),
+    }
+}
+
+/// Determine the language of a source file from its extension.
+fn detect_language(sess: &Session, filename: &str) -> Option<Language> {
+    if filename == "-" {
+        return Some(Language::SystemVerilog);
+    }
+    match Path::new(&filename).extension().and_then(|s| s.to_str()) {
+        Some("sv") | Some("svh") => Some(Language::SystemVerilog),
+        Some("v") | Some("vh") => Some(Language::Verilog),
+        Some("vhd") | Some("vhdl") => Some(Language::Vhdl),
+        Some(ext) => {
+            sess.emit(
+                DiagBuilder2::warning(format!("ignoring `{}`", filename)).add_note(format!(
+                    "Cannot determine language from extension `.{}`",
+                    ext
+                )),
+            );
+            None
+        }
+        None => {
+            sess.emit(
+                DiagBuilder2::warning(format!("ignoring `{}`", filename)).add_note(format!(
+                    "No file extension that can be used to guess language"
+                )),
+            );
+            None
+        }
+    }
+}
+
+/// The path of the virtual file which holds the source read from stdin.
+const STDIN_PATH: &str = "<stdin>";
+
+/// Read an input file, or stdin if the file name is `-`.
+fn read_input(filename: &str) -> std::io::Result<String> {
+    if filename == "-" {
+        let mut input = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)?;
+        Ok(input)
+    } else {
+        std::fs::read_to_string(filename)
+    }
+}
+
+/// Parse the macros to predefine, given as `-D NAME[=VALUE]`.
+///
+/// Only the first `=` separates the name from the value, such that values may
+/// contain `=` themselves.
+fn parse_defines<'a>(sess: &Session, matches: &'a ArgMatches) -> Vec<(&'a str, Option<&'a str>)> {
+    let mut defines = vec![];
+    for def in matches.values_of("def").into_iter().flatten() {
+        let mut iter = def.splitn(2, '=');
+        let name = iter.next().unwrap();
+        let valid = name
+            .chars()
+            .next()
+            .map(|c| c.is_ascii_alphabetic() || c == '_')
+            .unwrap_or(false)
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "invalid macro name `{}` in define `{}`",
+                name, def
+            )));
+            std::process::exit(1);
+        }
+        defines.push((name, iter.next()));
+    }
+    defines
+}
+
+/// Run the parser over a test corpus and summarize the results.
+fn conformance(matches: &ArgMatches) -> i32 {
+    let sess = Session::new();
+    let standards = Standards::from_matches(matches);
+    let jobs = match matches.value_of("jobs").map(|x| x.parse()) {
+        None => num_cpus::get(),
+        Some(Ok(x)) if x > 0 => x,
+        Some(_) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "invalid number of jobs `{}`",
+                matches.value_of("jobs").unwrap()
+            )));
+            return 1;
+        }
+    };
+    let opts = crate::conformance::Options {
+        standard: standards.svlog,
+        verilog: standards.verilog,
+        include_paths: matches
+            .values_of("inc")
+            .into_iter()
+            .flatten()
+            .map(PathBuf::from)
+            .collect(),
+        defines: parse_defines(&sess, matches)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.map(String::from)))
+            .collect(),
+        jobs,
+    };
+    let report = match crate::conformance::Report::run(matches.value_of("DIR").unwrap(), &opts) {
+        Ok(x) => x,
+        Err(diag) => {
+            sess.emit(diag);
+            return 1;
+        }
+    };
+    for failure in report.failures() {
+        println!("{}", failure);
+    }
+    print!("{}", report.summary());
+    0
+}
+
+/// The arguments which configure how source files are parsed.
+fn parse_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("inc")
+            .short("I")
+            .value_name("DIR")
+            .help("Add a search path for SystemVerilog includes (also `+incdir+DIR`)")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("def")
+            .short("D")
+            .value_name("DEFINE")
+            .help("Define a preprocessor macro as NAME[=VALUE]")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("std")
+            .long("std")
+            .value_name("STD")
+            .help("Parse Verilog and SystemVerilog input as a revision of the standard")
+            .takes_value(true)
+            .possible_values(&[
+                "1364-1995",
+                "1364-2001",
+                "1364-2005",
+                "1800-2005",
+                "1800-2009",
+                "1800-2012",
+                "1800-2017",
+            ]),
+        Arg::with_name("verilog")
+            .long("verilog")
+            .help("Parse Verilog and SystemVerilog input as Verilog (IEEE 1364-2005)")
+            .conflicts_with("std"),
+        Arg::with_name("vhdl-std")
+            .long("vhdl-std")
+            .value_name("STD")
+            .help("Parse VHDL input as a revision of the standard")
+            .takes_value(true)
+            .possible_values(&["93", "2002", "2008"]),
+    ]
+}
+
+/// The arguments which configure how source files are formatted.
+fn fmt_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("write")
+            .short("w")
+            .long("write")
+            .help("Write the formatted files back in place instead of to stdout"),
+        Arg::with_name("check")
+            .long("check")
+            .conflicts_with("write")
+            .help("Print the files which are not formatted and fail if there are any"),
+        Arg::with_name("indent")
+            .long("indent")
+            .value_name("N")
+            .help("Number of columns to indent each level by [default: 4]")
+            .takes_value(true),
+        Arg::with_name("tabs")
+            .long("tabs")
+            .help("Indent with tabs instead of spaces"),
+        Arg::with_name("max-width")
+            .long("max-width")
+            .value_name("N")
+            .help("Break lines longer than this, or 0 to keep long lines [default: 100]")
+            .takes_value(true),
+        Arg::with_name("INPUT")
+            .help("The files, directories, or glob patterns to format, or `-` to read from stdin")
+            .multiple(true)
+            .required(true),
+    ]
+}
+
+/// The arguments which configure how input files are compiled.
+fn compile_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    let mut args = parse_args();
+    args.extend(vec![
+        Arg::with_name("file-list")
+            .short("f")
+            .value_name("FILE")
+            .help("Read additional arguments from a file list")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("file-list-relative")
+            .short("F")
+            .value_name("FILE")
+            .help("Read additional arguments from a file list, with paths relative to the list")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("preproc")
+            .short("E")
+            .help("Write preprocessed input files to stdout"),
+        Arg::with_name("line-markers")
+            .long("line-markers")
+            .requires("preproc")
+            .help("Emit `line directives in the output of -E to mark included files"),
+        Arg::with_name("dump-ast")
+            .long("dump-ast")
+            .value_name("FORMAT")
+            .help("Dump the parsed abstract syntax tree")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .possible_values(&["debug", "json", "ron"]),
+        Arg::with_name("dump-ast-output")
+            .long("dump-ast-output")
+            .value_name("FILE")
+            .requires("dump-ast")
+            .help("Write the dump of --dump-ast to a file instead of stdout")
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("dump-deps")
+            .long("dump-deps")
+            .value_name("FORMAT")
+            .help("Print the instantiation and import dependencies between design units")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .possible_values(&["dot", "json"]),
+        Arg::with_name("dump-macro-xref")
+            .long("dump-macro-xref")
+            .help("Print where each macro is defined and expanded, and where each file is included"),
+        Arg::with_name("dump-hierarchy")
+            .long("dump-hierarchy")
+            .help("Print the elaborated instance hierarchy as JSON instead of generating code"),
+        Arg::with_name("emit-tags")
+            .long("emit-tags")
+            .value_name("FORMAT")
+            .help("Print an index of the declarations in the input files for editors")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .possible_values(&["ctags", "json"]),
+        Arg::with_name("emit-doc")
+            .long("emit-doc")
+            .value_name("FORMAT")
+            .help("Print the documentation of the units in the input files from their doc comments")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true)
+            .possible_values(&["html", "json"]),
+        Arg::with_name("emit-stubs")
+            .long("emit-stubs")
+            .help("Print a stub of each module and entity with its ports and an empty body"),
+        Arg::with_name("rename")
+            .long("rename")
+            .value_name("OLD=NEW")
+            .help("Print the edits which rename a module, interface, or package, or a member of one given as `unit.member`")
+            .takes_value(true),
+        Arg::with_name("apply")
+            .long("apply")
+            .help("Apply the edits of `--rename` to the input files")
+            .requires("rename"),
+        Arg::with_name("lint")
+            .short("W")
+            .value_name("LINT")
+            .help("Enable a lint by name, or `all`; disable it with `no-NAME`; `error` reports lints as errors; `help` lists the lints")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("list-passes")
+            .long("list-passes")
+            .help("List the analysis passes registered with the driver"),
+        Arg::with_name("check-syntax")
+            .long("syntax")
+            .help("Preprocess and check the input for syntax errors"),
+        Arg::with_name("emit_pkgs")
+            .long("emit-pkgs")
+            .help("Dump VHDL packages for debugging"),
+        Arg::with_name("stats")
+            .long("stats")
+            .help("Print the number of values and bytes allocated in each arena"),
+        Arg::with_name("opt-level")
+            .short("O")
+            .long("opt-level")
+            .help("Sets optimization level applied to the output")
+            .default_value("1")
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("debug-info")
+            .short("g")
+            .long("debug-info")
+            .help("Annotate the output with source locations"),
+        Arg::with_name("flatten")
+            .long("flatten")
+            .value_name("DEPTH")
+            .help("Inline instantiated entities into their parents, optionally up to a depth")
+            .takes_value(true)
+            .min_values(0)
+            .require_equals(true),
+        Arg::with_name("remove-dead")
+            .long("remove-dead")
+            .help("Remove signals that are never read and instances whose outputs are unused"),
+        Arg::with_name("keep-names")
+            .long("keep-names")
+            .help("Do not mangle parameters and node IDs into unit names"),
+        Arg::with_name("four-state")
+            .long("four-state")
+            .value_name("STRATEGY")
+            .help("How to lower `x` and `z` values")
+            .takes_value(true)
+            .possible_values(&["two-state", "paired"])
+            .default_value("two-state"),
+        Arg::with_name("allow-blackbox")
+            .long("allow-blackbox")
+            .help("Emit `extern module` declarations as external units"),
+        Arg::with_name("single-unit").long("single-unit").help(
+            "Compile all input files as one compilation unit, sharing macros and declarations",
+        ),
+        Arg::with_name("jobs")
+            .short("j")
+            .long("jobs")
+            .value_name("N")
+            .help("Number of threads to parse input files with [default: number of CPUs]")
+            .takes_value(true),
+        Arg::with_name("lib")
+            .short("l")
+            .long("lib")
+            .value_name("LIB")
+            .help("Name of the library to compile into, or `NAME=DIR` to map a library to a directory")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("elaborate")
+            .short("e")
+            .long("elaborate")
+            .value_name("ENTITY")
+            .help("Elaborate an entity or module")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("top")
+            .long("top")
+            .value_name("NAME")
+            .help("Elaborate the hierarchy below a top module; may be repeated")
+            .multiple(true)
+            .takes_value(true)
+            .number_of_values(1),
+        Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .help("Output file (`-` for stdout)")
+            .takes_value(true),
+        Arg::with_name("MD")
+            .long("MD")
+            .help("Write the files read to `-MF FILE`, or next to the output file, as Makefile dependencies of the output"),
+        Arg::with_name("MF")
+            .long("MF")
+            .value_name("FILE")
+            .help("File to write the Makefile dependencies to (`-` for stdout)")
+            .takes_value(true)
+            .requires("MD"),
+        Arg::with_name("output-format")
+            .long("format")
+            .help("Output format")
+            .takes_value(true)
+            .possible_values(&["llhd", "mlir", "verilog"]),
+        Arg::with_name("INPUT")
+            .help("The input files, directories, or glob patterns to compile, or `-` to read SystemVerilog from stdin")
+            .multiple(true)
+            .required_unless_one(&["lint", "list-passes"]),
+    ]);
+    args
+}
+
+fn score(sess: &Session, matches: &ArgMatches, command: Command) {
+    use crate::name::get_name_table;
+    let svlog_arenas = svlog::GlobalArenas::default();
+
+    // List the lints and stop if requested.
+    if matches
+        .values_of("lint")
+        .into_iter()
+        .flatten()
+        .any(|x| x == "help")
+    {
+        list_lints();
+        std::process::exit(0);
+    }
+
+    // List the registered analysis passes and stop if requested.
+    if matches.is_present("list-passes") {
+        for name in crate::pass::names() {
+            println!("{}", name);
+        }
+        std::process::exit(0);
+    }
+    if !matches.is_present("INPUT") {
+        sess.emit(DiagBuilder2::fatal("no input files"));
+        std::process::exit(1);
+    }
+
+    // Prepare a list of include paths.
+    let include_paths: Vec<_> = match matches.values_of("inc") {
+        Some(args) => args.map(|x| std::path::Path::new(x)).collect(),
+        None => Vec::new(),
+    };
+
+    // Prepare a list of macros to predefine.
+    let defines = parse_defines(sess, matches);
+
+    // Establish into which library the entities will be compiled, and which
+    // libraries are mapped to a directory. Later on this should be made
+    // configurable per entity.
+    let mut lib_name = "work";
+    let mut libs = vec![];
+    for arg in matches.values_of("lib").into_iter().flatten() {
+        if !arg.contains('=') {
+            lib_name = arg;
+            continue;
+        }
+        match crate::library::Library::parse(arg) {
+            Some(x) => libs.push(x),
+            None => {
+                sess.emit(
+                    DiagBuilder2::fatal(format!("invalid library mapping `{}`", arg))
+                        .add_note("Library mappings are of the form `NAME=DIR`"),
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+    let target = libs
+        .iter()
+        .position(|x| x.name.eq_ignore_ascii_case(lib_name));
+    let lib = get_name_table().intern(lib_name, true);
+
+    // Determine the number of threads to parse the input files with.
+    let jobs = match matches.value_of("jobs").map(|x| x.parse()) {
+        None => num_cpus::get(),
+        Some(Ok(x)) if x > 0 => x,
+        Some(_) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "invalid number of jobs `{}`",
+                matches.value_of("jobs").unwrap()
+            )));
+            std::process::exit(1);
+        }
+    };
+
+    // Open the input files.
+    let standards = Standards::from_matches(matches);
+    let mut macros = svlog::preproc::MacroTable::new(&defines);
+    let xref = svlog::preproc::Xref::default();
+    let mut failed = false;
+    let mut inputs = Vec::new();
+    let mut input_paths = Vec::new();
+    let mut stdin_read = false;
+    let input_files = match crate::inputs::expand(matches.values_of("INPUT").unwrap()) {
+        Ok(x) => x,
+        Err(diag) => {
+            sess.emit(diag);
+            std::process::exit(1);
+        }
+    };
+    for filename in input_files.iter().map(String::as_str) {
+        if filename.is_empty() {
+            continue;
+        }
+        if filename == "-" && std::mem::replace(&mut stdin_read, true) {
+            sess.emit(
+                DiagBuilder2::warning("ignoring `-`")
+                    .add_note("The standard input can only be read once"),
+            );
+            continue;
+        }
+
+        // Detect the file type.
+        let language = match detect_language(sess, filename) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        // Add the file to the source manager.
+        let sm = source::get_source_manager();
+        let source = if filename == "-" {
+            match read_input(filename) {
+                Ok(x) => sm.replace(STDIN_PATH, &x),
+                Err(e) => {
+                    sess.emit(DiagBuilder2::fatal(format!(
+                        "unable to read standard input: {}",
+                        e
+                    )));
+                    continue;
+                }
+            }
+        } else {
+            match sm.open(&filename) {
+                Some(s) => s,
+                None => {
+                    sess.emit(DiagBuilder2::fatal(format!(
+                        "unable to open `{}`",
+                        filename
+                    )));
+                    continue;
+                }
+            }
+        };
+
+        // Print the preprocessed file if so requested.
+        if matches.is_present("preproc") && language != Language::Vhdl {
+            // Unless all files form one compilation unit, each file starts out
+            // with only the macros defined on the command line.
+            if !sess.opts.single_unit {
+                macros = svlog::preproc::MacroTable::new(&defines);
+            }
+            let preproc =
+                svlog::preproc::Preprocessor::with_macros(source, &include_paths, macros.clone())
+                    .with_standard(standards.svlog);
+            let preproc = if matches.is_present("line-markers") {
+                preproc.with_line_markers()
+            } else {
+                preproc
+            };
+            for token in preproc {
+                print!(
+                    "{}",
+                    match token {
+                        Ok((_token, span)) => span.extract(),
+                        Err(diag) => {
+                            sess.emit(diag);
+                            failed = true;
+                            continue;
+                        }
+                    }
+                );
+            }
+            continue;
+        }
+
+        // Only preprocess the file if a macro cross-reference is requested.
+        if matches.is_present("dump-macro-xref") {
+            if language == Language::Vhdl {
+                continue;
+            }
+            if !sess.opts.single_unit {
+                macros = svlog::preproc::MacroTable::new(&defines);
+            }
+            let preproc =
+                svlog::preproc::Preprocessor::with_macros(source, &include_paths, macros.clone())
+                    .with_standard(standards.svlog)
+                    .with_xref(xref.clone());
+            for token in preproc {
+                if let Err(diag) = token {
+                    sess.emit(diag);
+                    failed = true;
+                }
+            }
+            continue;
+        }
+        inputs.push((source, language));
+        input_paths.push((Path::new(filename), source));
+    }
+
+    // Print the macro cross-reference and stop if requested.
+    if matches.is_present("dump-macro-xref") {
+        if failed || sess.failed() {
+            std::process::exit(1);
+        }
+        print!("{}", xref);
+        std::process::exit(0);
+    }
+
+    // Preprocess and lex the SystemVerilog files and parse the VHDL files on
+    // a pool of threads. Files which form one compilation unit share their
+    // macros and are processed in order on this thread instead.
+    let lexed = if sess.opts.single_unit {
+        inputs
+            .into_iter()
+            .map(|(source, language)| {
+                capture_diags(|| {
+                    lex_or_parse(source, language, &include_paths, macros.clone(), standards)
+                })
+            })
+            .collect()
+    } else {
+        let include_paths: Vec<_> = include_paths.iter().map(|x| x.to_path_buf()).collect();
+        let defines: Vec<_> = defines
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value.map(String::from)))
+            .collect();
+        crate::parallel::map(inputs, jobs, move |(source, language)| {
+            let include_paths: Vec<_> = include_paths.iter().map(|x| x.as_path()).collect();
+            let defines: Vec<_> = defines
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_ref().map(|x| x.as_str())))
+                .collect();
+            let macros = svlog::preproc::MacroTable::new(&defines);
+            capture_diags(|| lex_or_parse(source, language, &include_paths, macros, standards))
+        })
+    };
+
+    // Parse the SystemVerilog files into the AST arena, which can only be
+    // accessed from this thread, and report the diagnostics of each file in
+    // order.
+    let mut asts = Vec::new();
+    for (lexed, captured) in lexed {
+        eprint!("{}", captured.output);
+        match lexed {
+            Lexed::Svlog(tokens) => match standards.parse_svlog(tokens, &svlog_arenas.ast) {
+                Ok(x) => asts.push(score::Ast::Svlog(x)),
+                Err(()) => failed = true,
+            },
+            Lexed::Vhdl(Ok(x)) => asts.push(score::Ast::Vhdl(x)),
+            Lexed::Vhdl(Err(())) => failed = true,
+        }
+    }
+    if failed || sess.failed() {
+        std::process::exit(1);
+    }
+    if matches.is_present("preproc") {
+        return;
+    }
+
+    // Record the units of the input files in the index of the library they
+    // are compiled into, if that library is mapped to a directory.
+    if let Some(target) = target {
+        let units: Vec<_> = asts
+            .iter()
+            .zip(&input_paths)
+            .filter(|(_, &(path, _))| path != Path::new("-"))
+            .flat_map(|(ast, &(path, _))| {
+                let path = crate::library::canonicalize(path);
+                crate::library::defined_units(ast)
+                    .into_iter()
+                    .map(move |unit| (unit, path.clone()))
+            })
+            .collect();
+        if let Err(d) = libs[target].update_index(&units) {
+            sess.emit(d);
+            std::process::exit(1);
+        }
+    }
+
+    // Dump the AST if so requested.
+    if matches.is_present("dump-ast") {
+        if dump_ast(sess, &matches, &asts).is_err() {
+            std::process::exit(1);
+        }
+    }
+
+    if matches.is_present("emit_pkgs") {
+        vhdl::debug::emit_pkgs(
+            sess,
+            asts.iter()
+                .flat_map(|ast| match *ast {
+                    score::Ast::Vhdl(ref x) => x.iter(),
+                    _ => [].iter(),
+                })
+                .collect(),
+        );
+    }
+
+    // Print the symbol index and stop if requested.
+    if matches.is_present("emit-tags") {
+        let index = crate::tags::Index::new(&asts);
+        match matches.value_of("emit-tags").unwrap_or("ctags") {
+            "json" => println!("{}", serde_json::to_string_pretty(&index).unwrap()),
+            _ => print!("{}", index.to_ctags()),
+        }
+        std::process::exit(0);
+    }
+
+    // Print the documentation and stop if requested.
+    if matches.is_present("emit-doc") {
+        let docs = crate::doc::Documentation::new(&asts);
+        match matches.value_of("emit-doc").unwrap_or("html") {
+            "json" => println!("{}", serde_json::to_string_pretty(&docs).unwrap()),
+            _ => print!("{}", docs.to_html()),
+        }
+        std::process::exit(0);
+    }
+
+    // Rename a declaration and stop if requested.
+    if let Some(arg) = matches.value_of("rename") {
+        let files: Vec<_> = input_paths
+            .iter()
+            .map(|&(_, source)| source)
+            .zip(&asts)
+            .collect();
+        if rename(sess, arg, &files, matches.is_present("apply")).is_err() {
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    // Print the blackbox stubs and stop if requested.
+    if matches.is_present("emit-stubs") {
+        print!("{}", crate::stubs::write_stubs(&asts));
+        std::process::exit(0);
+    }
+
+    // Stop processing if requested. The input files are checked for lints
+    // here already, since the full compilation below does not run.
+    if matches.is_present("check-syntax") {
+        if matches.is_present("lint") && lint(sess, matches, &asts).is_err() {
+            std::process::exit(1);
+        }
+        if write_make_deps(sess, matches).is_err() {
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    // Load the library units which the input files refer to.
+    let num_inputs = asts.len();
+    let lib_asts = match load_libraries(
+        sess,
+        &libs,
+        target,
+        &mut asts,
+        &input_paths,
+        &include_paths,
+        &defines,
+        standards,
+        &svlog_arenas.ast,
+    ) {
+        Ok(x) => x,
+        Err(()) => std::process::exit(1),
+    };
+
+    // All files have been read at this point, so the Makefile dependencies
+    // are complete.
+    if write_make_deps(sess, matches).is_err() {
+        std::process::exit(1);
+    }
+
+    // Print the dependency graph and stop if requested.
+    if matches.is_present("dump-deps") {
+        let graph = crate::deps::Graph::new(&asts);
+        match matches.value_of("dump-deps").unwrap_or("dot") {
+            "json" => println!("{}", serde_json::to_string_pretty(&graph).unwrap()),
+            _ => print!("{}", graph.to_dot()),
+        }
+        std::process::exit(0);
+    }
+
+    // Declare the VHDL entities instantiated in SystemVerilog.
+    let foreign = {
+        let all: Vec<_> = asts.iter().chain(lib_asts.iter().flatten()).collect();
+        crate::mixed::foreign_modules(sess, &all)
+    };
+    match foreign {
+        Ok(Some(x)) => asts.push(score::Ast::Svlog(x)),
+        Ok(None) => (),
+        Err(()) => std::process::exit(1),
+    }
+
+    // Check the input files for lints if requested.
+    if matches.is_present("lint") && lint(sess, matches, &asts[..num_inputs]).is_err() {
+        std::process::exit(1);
+    }
+
+    // Check the instance hierarchy before elaborating it, which would not
+    // terminate for recursive instances, and run the registered analysis
+    // passes.
+    {
+        let all: Vec<_> = asts.iter().chain(lib_asts.iter().flatten()).collect();
+        let design = crate::design::Design::new(&all);
+        for diag in crate::design::check(&design) {
+            sess.emit(diag);
+        }
+        if !sess.failed() {
+            crate::pass::run(&design, sess);
+        }
+        if sess.failed() {
+            std::process::exit(1);
+        }
+    }
+
+    // Create the scoreboard and add the initial map of libraries.
+    let arenas = score::Arenas::new();
+    let sb = ScoreBoard::new(&arenas);
+    let vhdl_sb = vhdl::score::ScoreBoard::new(&arenas.vhdl);
+    let svlog_sb = svlog::GlobalContext::new(&sess, &svlog_arenas);
+
+    // Elaborate the requested entities or modules.
+    {
+        let vhdl_phases = vhdl::lazy::LazyPhaseTable::new(&vhdl_sb);
+        let ctx = ScoreContext {
+            sess: sess,
+            sb: &sb,
+            vhdl: &vhdl_sb,
+            vhdl_phases: &vhdl_phases,
+            svlog: &svlog_sb,
+        };
+        let lib_id = ctx.add_library(lib, &asts);
+        for (mapped, asts) in libs.iter().zip(&lib_asts) {
+            if !mapped.name.eq_ignore_ascii_case(lib_name) {
+                ctx.add_library(get_name_table().intern(&mapped.name, true), asts);
+            }
+        }
+        if let Some(names) = matches.values_of("elaborate") {
+            debug!("lib_id = {:?}", lib_id);
+            debug!("{:?}", sb);
+            for name in names {
+                match elaborate_name(matches, command, &ctx, lib_id, name) {
+                    Ok(_) => (),
+                    Err(_) => failed = true,
+                };
+            }
+            if sess.failed() {
+                failed = true;
+            }
+        }
+        if let Some(names) = matches.values_of("top") {
+            if elaborate_tops(matches, command, &ctx, lib_id, names).is_err() || sess.failed() {
+                failed = true;
+            }
+        }
+    }
+    if matches.is_present("stats") {
+        let mut stats = svlog_arenas.stats();
+        stats.extend(arenas.stats());
+        print_stats(&stats);
+    }
+    if failed || sess.failed() {
+        std::process::exit(1);
+    }
+
+    // Extract the populated LLHD modules from the scoreboards and link them
+    // together.
+    let _vhdl_module = vhdl_sb.llmod.into_inner();
+
+    // Emit the module.
+    // TODO: Re-enable this once the VHDL crate has been moved over to llhd v0.8.
+    // llhd::assembly::write_module(&mut std::io::stdout().lock(), &vhdl_module);
+
+    if sess.failed() {
+        std::process::exit(1);
+    }
+}
+
+/// Print the number of values and bytes allocated in each arena to stderr.
+///
+/// Arenas which are empty are omitted.
+fn print_stats(groups: &[(&str, Vec<crate::common::arenas::ArenaStats>)]) {
+    eprintln!("{:<32} {:>10} {:>12}", "arena", "count", "bytes");
+    let mut count = 0;
+    let mut bytes = 0;
+    for (group, stats) in groups {
+        for stat in stats.iter().filter(|s| s.count > 0) {
+            let name = format!("{}.{}", group, stat.name);
+            eprintln!("{:<32} {:>10} {:>12}", name, stat.count, stat.bytes);
+            count += stat.count;
+            bytes += stat.bytes;
+        }
+    }
+    eprintln!("{:<32} {:>10} {:>12}", "total", count, bytes);
+}
+
+/// An item to be elaborated.
+#[derive(Debug)]
+enum Elaborate {
+    VhdlEntity(vhdl::score::EntityRef, vhdl::score::ArchRef),
+    VhdlPkg(vhdl::score::PkgDeclRef),
+    Svlog(NodeId),
+}
+
+/// Elaborate an entity/module specification of the form
+/// `[lib.]entity[.arch]`.
+fn elaborate_name(
+    matches: &ArgMatches,
+    command: Command,
+    ctx: &ScoreContext,
+    lib_id: score::LibRef,
+    input_name: &str,
+) -> Result<(), ()> {
+    match resolve_name(ctx, lib_id, input_name)? {
+        Elaborate::Svlog(m) => elaborate_svlog(matches, command, ctx, &[m]),
+        elab => elaborate_vhdl(ctx, elab),
+    }
+}
+
+/// Elaborate the design hierarchy below one or more top modules.
+///
+/// All SystemVerilog tops are emitted into the same LLHD module, such that
+/// modules instantiated by several tops are emitted only once.
+fn elaborate_tops<'a>(
+    matches: &ArgMatches,
+    command: Command,
+    ctx: &ScoreContext,
+    lib_id: score::LibRef,
+    names: impl Iterator<Item = &'a str>,
+) -> Result<(), ()> {
+    let mut roots = vec![];
+    let mut failed = false;
+    for name in names {
+        match resolve_name(ctx, lib_id, name) {
+            Ok(Elaborate::Svlog(m)) => roots.push(m),
+            Ok(elab) => failed |= elaborate_vhdl(ctx, elab).is_err(),
+            Err(()) => failed = true,
+        }
+    }
+    if failed {
+        return Err(());
+    }
+    if roots.is_empty() {
+        return Ok(());
+    }
+    elaborate_svlog(matches, command, ctx, &roots)
+}
+
+/// Resolve an entity/module specificaiton of the form `[lib.]entity[.arch]` for
+/// elaboration.
+fn resolve_name(
+    ctx: &ScoreContext,
+    lib_id: score::LibRef,
+    input_name: &str,
+) -> Result<Elaborate, ()> {
+    let (lib, name, arch) = parse_elaborate_name(input_name)?;
+    debug!(
+        "parsed `{}` into (lib: {:?}, name: {:?}, arch: {:?})",
+        input_name, lib, name, arch
+    );
+
+    // Resolve the library name if one was provided.
+    let lib = {
+        if let Some(lib) = lib {
+            let rid = ctx.sb.root;
+            let defs = ctx.defs(score::ScopeRef::Root(rid))?;
+            match defs.get(&lib) {
+                Some(&score::Def::Lib(d)) => d,
+                _ => {
+                    let mut d = DiagBuilder2::error(format!("Library `{}` does not exist", lib))
+                        .add_note("The following libraries do exist:");
+                    let mut names: Vec<_> = defs.iter().map(|(&k, _)| k).collect();
+                    names.sort(); // sorts by name ID, roughly equivalent to order of declaration
+                    for name in names {
+                        d = d.add_note(format!("- {}", name));
+                    }
+                    ctx.sess.emit(d);
+                    return Err(());
+                }
+            }
+        } else {
+            lib_id
+        }
+    };
+    debug!("using library {:?}", lib);
+
+    // Resolve the entity name.
+    let defs = ctx.defs(lib.into())?;
+    let elab = match defs.get(&name) {
+        Some(&score::Def::Vhdl(vhdl::score::Def::Entity(entity))) => {
+            let archs = ctx
+                .vhdl()
+                .archs(vhdl::score::LibRef::new(lib.into()))?
+                .by_entity
+                .get(&entity)
+                .unwrap();
+            let arch_ref = if let Some(arch) = arch {
+                match archs.by_name.get(&arch) {
+                    Some(&id) => id,
+                    None => {
+                        ctx.sess.emit(DiagBuilder2::error(format!(
+                            "`{}` is not an architecture of entity `{}`",
+                            arch, name
+                        )));
+                        return Err(());
+                    }
+                }
+            } else {
+                match archs.ordered.last() {
+                    Some(&id) => id,
+                    None => {
+                        ctx.sess.emit(DiagBuilder2::error(format!(
+                            "Entity `{}` has no architecture defined",
+                            name
+                        )));
+                        return Err(());
+                    }
+                }
+            };
+            Elaborate::VhdlEntity(entity, arch_ref)
+        }
+        Some(&score::Def::Vhdl(vhdl::score::Def::Pkg(p))) => Elaborate::VhdlPkg(p),
+        Some(&score::Def::Svlog(e)) => Elaborate::Svlog(e),
+        _ => {
+            let mut d = DiagBuilder2::error(format!("Item `{}` does not exist", name))
+                .add_note("The following items are defined:");
+            let mut names: Vec<_> = defs.iter().map(|(&k, _)| k).collect();
+            names.sort(); // sorts by name ID, roughly equivalent to order of declaration
+            for name in names {
+                d = d.add_note(format!("- {}", name));
+            }
+            ctx.sess.emit(d);
+            return Err(());
+        }
+    };
+    debug!("elaborating {:?}", elab);
+    Ok(elab)
+}
+
+/// Generate the definition of a VHDL entity or package.
+fn elaborate_vhdl(ctx: &ScoreContext, elab: Elaborate) -> Result<(), ()> {
+    match elab {
+        Elaborate::VhdlEntity(_entity, arch) => {
+            // let decl = ctx.vhdl.lldecl(arch);
+            // println!("Architecture declared as {:?}", decl);
+            let def = ctx.vhdl().llunit(arch)?;
+            eprintln!("Architecture declared as {:?}", def);
+        }
+        Elaborate::VhdlPkg(pkg) => {
+            use crate::vhdl::typeck::{Typeck, TypeckContext};
+            let sbc = ctx.vhdl();
+            let tyc = TypeckContext::new(&sbc);
+            tyc.typeck(pkg);
+            // use crate::vhdl::codegen::Codegen;
+            // ctx.vhdl().codegen(pkg, &mut ())?;
+        }
+        Elaborate::Svlog(_) => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Generate the LLHD code for the hierarchy below one or more SystemVerilog
+/// modules.
+fn elaborate_svlog(
+    matches: &ArgMatches,
+    command: Command,
+    ctx: &ScoreContext,
+    roots: &[NodeId],
+) -> Result<(), ()> {
+    use svlog::Context;
+    for &m in roots {
+        match ctx.svlog.hir_of(m)? {
+            svlog::hir::HirNode::Module(_) => (),
+            hir => {
+                ctx.sess.emit(
+                    DiagBuilder2::error(format!("{} cannot be elaborated", hir.desc_full()))
+                        .span(hir.human_span())
+                        .add_note("Only modules can be elaborated"),
+                );
+                return Err(());
+            }
+        }
+    }
+
+    // Emit the detailed type analysis if requested.
+    if ctx.sess.has_verbosity(Verbosity::TYPES) {
+        for &m in roots {
+            TypeVerbosityVisitor(ctx.svlog, ctx.svlog.default_param_env())
+                .visit_node_with_id(m, false);
+        }
+    }
+
+    // Emit the instantiation details if requested.
+    if ctx.sess.has_verbosity(Verbosity::INSTS) {
+        for &m in roots {
+            svlog::InstVerbosityVisitor::new(ctx.svlog).visit_node_with_id(m, false);
+        }
+    }
+
+    // Resolve all instances in the hierarchy before generating any code, such
+    // that all unresolved instances are reported at once.
+    let mut hierarchy = svlog::InstHierarchyVisitor::new(ctx.svlog);
+    for &m in roots {
+        hierarchy.visit_node_with_id(m, false);
+    }
+    if hierarchy.failed() {
+        return Err(());
+    }
+
+    // Print the elaborated hierarchy and stop if requested.
+    if matches.is_present("dump-hierarchy") {
+        let mut tops = vec![];
+        for &m in roots {
+            tops.push(svlog::hierarchy::hierarchy(ctx.svlog, m)?);
+        }
+        println!("{}", serde_json::to_string_pretty(&tops).unwrap());
+        return Ok(());
+    }
+
+    // Simulation starts from a single top.
+    if command == Command::Simulate && roots.len() > 1 {
+        ctx.sess.emit(
+            DiagBuilder2::fatal("cannot simulate more than one top module")
+                .add_note("Use `--top` only once with `moore sim`"),
+        );
+        return Err(());
+    }
+
+    let mut cg = svlog::CodeGenerator::new(ctx.svlog);
+    let mut tops = vec![];
+    for &m in roots {
+        tops.push(cg.emit_module(m)?.name().clone());
+    }
+    let top = tops.swap_remove(0);
+    let instance_names = cg.instance_names().clone();
+    let mut module = cg.finalize();
+    if matches.is_present("flatten") {
+        let depth = match matches.value_of("flatten").map(|x| x.parse()) {
+            Some(Ok(x)) => Some(x),
+            Some(Err(_)) => {
+                ctx.sess.emit(DiagBuilder2::fatal(format!(
+                    "invalid flatten depth: `{}`",
+                    matches.value_of("flatten").unwrap()
+                )));
+                return Err(());
+            }
+            None => None,
+        };
+        crate::opt::flatten(&mut module, depth, &instance_names);
+    }
+    if matches.is_present("remove-dead") {
+        for removed in crate::opt::remove_dead(&mut module) {
+            ctx.sess.emit(DiagBuilder2::note(format!("{}", removed)));
+        }
+    }
+    // The LLHD passes consider calls to be free of side effects and
+    // would remove the assertion checks, so simulate the design as
+    // emitted.
+    let pass_ctx = PassContext;
+    if ctx.sess.opts.opt_level > 0 && command == Command::Compile {
+        llhd::pass::ConstFolding::run_on_module(&pass_ctx, &mut module);
+        if crate::opt::canonicalize(&mut module) {
+            llhd::pass::ConstFolding::run_on_module(&pass_ctx, &mut module);
+        }
+        // llhd::pass::VarToPhiPromotion::run_on_module(&pass_ctx, &mut module); // broken in llhd 0.13
+        llhd::pass::DeadCodeElim::run_on_module(&pass_ctx, &mut module);
+        llhd::pass::GlobalCommonSubexprElim::run_on_module(&pass_ctx, &mut module);
+        llhd::pass::InstSimplification::run_on_module(&pass_ctx, &mut module);
+        llhd::pass::DeadCodeElim::run_on_module(&pass_ctx, &mut module);
+    }
+
+    // Simulate the design or decide what format to use for the output.
+    match command {
+        Command::Compile => emit_output(matches, ctx, &mut module, &instance_names)?,
+        Command::Simulate => emit_simulation(matches, ctx, &module, &instance_names, &top)?,
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+enum OutputFormat {
+    Llhd,
+    Mlir,
+    Verilog,
+}
+
+fn emit_output(
+    matches: &ArgMatches,
+    ctx: &ScoreContext,
+    module: &mut llhd::ir::Module,
+    instance_names: &HashMap<usize, String>,
+) -> Result<(), ()> {
+    // Check if the user has provided an explicit output format.
+    let fmt = match matches.value_of("output-format") {
+        Some("llhd") => Some(OutputFormat::Llhd),
+        Some("mlir") => Some(OutputFormat::Mlir),
+        Some("verilog") => Some(OutputFormat::Verilog),
+        Some(x) => {
+            ctx.sess.emit(DiagBuilder2::fatal(format!(
+                "unknown output format: `{}`",
+                x
+            )));
+            return Err(());
+        }
+        _ => None,
+    };
+
+    // Check if the output format can be inferred from the output file suffix.
+    let fmt = fmt.or_else(|| {
+        match matches
+            .value_of("output")
+            .and_then(|x| Path::new(x).extension())
+            .and_then(|x| x.to_str())
+        {
+            Some("llhd") => Some(OutputFormat::Llhd),
+            Some("mlir") => Some(OutputFormat::Mlir),
+            Some("v") => Some(OutputFormat::Verilog),
+            _ => None,
+        }
+    });
+
+    // Otherwise fall back to the LLHD default output format.
+    let fmt = fmt.unwrap_or(OutputFormat::Llhd);
+    debug!("Using {:?} output format", fmt);
+
+    // Generate the netlist before opening the output, such that no file is
+    // created if the design cannot be expressed as a netlist.
+    let netlist = match fmt {
+        OutputFormat::Verilog => {
+            netlist::lower_processes(module);
+            Some(netlist::write_module(ctx.sess, module, instance_names)?)
+        }
+        _ => None,
+    };
+
+    // Open the output.
+    let output = open_output(matches, ctx)?;
+
+    // Emit the appropriate output.
+    match fmt {
+        OutputFormat::Llhd if ctx.sess.opts.debug_info => {
+            let mut output = output;
+            output
+                .write_all(debug_info::write_module(&module).as_bytes())
+                .map_err(|e| {
+                    ctx.sess.emit(
+                        DiagBuilder2::fatal("unable to write output").add_note(format!("{}", e)),
+                    );
+                })?;
+        }
+        OutputFormat::Llhd => llhd::assembly::write_module(output, &module),
+        OutputFormat::Mlir => llhd::mlir::write_module(output, &module),
+        OutputFormat::Verilog => {
+            let mut output = output;
+            output.write_all(netlist.unwrap().as_bytes()).map_err(|e| {
+                ctx.sess
+                    .emit(DiagBuilder2::fatal("unable to write output").add_note(format!("{}", e)));
+            })?;
+        }
+    };
+    Ok(())
+}
+
+/// Simulate a module and write the waveform to the output.
+fn emit_simulation(
+    matches: &ArgMatches,
+    ctx: &ScoreContext,
+    module: &llhd::ir::Module,
+    instance_names: &HashMap<usize, String>,
+    top: &llhd::ir::UnitName,
+) -> Result<(), ()> {
+    let until = match matches.value_of("until") {
+        Some(x) => match crate::sim::parse_time(x) {
+            Some(t) => Some(t),
+            None => {
+                ctx.sess.emit(DiagBuilder2::fatal(format!(
+                    "invalid simulation time: `{}`",
+                    x
+                )));
+                return Err(());
+            }
+        },
+        None => None,
+    };
+    let vcd = crate::sim::simulate(ctx.sess, module, instance_names, top, until.as_ref())?;
+    let mut output = open_output(matches, ctx)?;
+    output.write_all(vcd.as_bytes()).map_err(|e| {
+        ctx.sess
+            .emit(DiagBuilder2::fatal("unable to write output").add_note(format!("{}", e)));
+    })
+}
+
+/// Dump the parsed ASTs in the format selected with `--dump-ast`.
+///
+/// The structured formats include the span of every node, such that other
+/// tools can consume the parse result.
+fn dump_ast(sess: &Session, matches: &ArgMatches, asts: &[score::Ast]) -> Result<(), ()> {
+    use std::io::Write;
+    let output = match matches.value_of("dump-ast-output") {
+        Some(path) => std::fs::File::create(path)
+            .map(|f| Box::new(f) as Box<dyn std::io::Write>)
+            .map_err(|e| {
+                sess.emit(
+                    DiagBuilder2::fatal(format!("unable to create file: `{}`", path))
+                        .add_note(format!("{}", e)),
+                );
+            })?,
+        None => Box::new(std::io::stdout()),
+    };
+    let mut output = std::io::BufWriter::new(output);
+    let result = match matches.value_of("dump-ast").unwrap_or("debug") {
+        "json" => serde_json::to_writer_pretty(&mut output, asts).map_err(|e| e.to_string()),
+        "ron" => ron::ser::to_writer_pretty(&mut output, asts, Default::default())
+            .map_err(|e| e.to_string()),
+        _ => write!(output, "{:#99?}", asts).map_err(|e| e.to_string()),
+    };
+    result
+        .and_then(|_| {
+            writeln!(output)
+                .and_then(|_| output.flush())
+                .map_err(|e| e.to_string())
+        })
+        .map_err(|e| {
+            sess.emit(DiagBuilder2::fatal("unable to dump the syntax tree").add_note(e));
+        })
+}
+
+/// Rename a declaration given as `OLD=NEW` in the input files.
+///
+/// Prints the location of each edit, or applies the edits and writes the
+/// files back in place.
+fn rename<'a>(
+    sess: &Session,
+    arg: &str,
+    files: &[(source::Source, &'a score::Ast<'a>)],
+    apply: bool,
+) -> Result<(), ()> {
+    let (old, new) = match arg.find('=') {
+        Some(i) => (&arg[..i], &arg[i + 1..]),
+        None => {
+            sess.emit(
+                DiagBuilder2::fatal(format!("invalid rename `{}`", arg))
+                    .add_note("Renames are of the form `OLD=NEW`, e.g. `top.clk=clk_i`"),
+            );
+            return Err(());
+        }
+    };
+    let edits = crate::rename::rename(files, old, new).map_err(|d| sess.emit(d))?;
+    if !apply {
+        for edit in &edits {
+            let (line, column, _) = edit.span.begin().human();
+            println!(
+                "{}:{}:{}: `{}` -> `{}`",
+                edit.span.source.get_path(),
+                line,
+                column,
+                edit.span.extract(),
+                edit.text.trim_end()
+            );
+        }
+        return Ok(());
+    }
+    for (source, _) in files {
+        let edits: Vec<_> = edits
+            .iter()
+            .filter(|e| e.span.source == *source)
+            .cloned()
+            .collect();
+        if edits.is_empty() {
+            continue;
+        }
+        let content = source.get_content();
+        let text = crate::rename::apply(&String::from_utf8_lossy(content.bytes()), &edits);
+        let path = source.get_path();
+        if &*path == STDIN_PATH {
+            print!("{}", text);
+            continue;
+        }
+        crate::fs::get_file_system()
+            .write(Path::new(&*path), &text)
+            .map_err(|e| {
+                sess.emit(
+                    DiagBuilder2::fatal(format!("unable to write `{}`", path))
+                        .add_note(format!("{}", e)),
+                )
+            })?;
+    }
+    Ok(())
+}
+
+/// Write the files read so far as Makefile dependencies, if `-MD` is given.
+///
+/// The target of the rule is the output file, or the dependency file itself
+/// if there is no output file. Without `-MF`, the dependencies are written to
+/// the output file with its extension replaced by `.d`.
+fn write_make_deps(sess: &Session, matches: &ArgMatches) -> Result<(), ()> {
+    if !matches.is_present("MD") {
+        return Ok(());
+    }
+    let output = matches.value_of("output").filter(|&x| x != "-");
+    let path = match (matches.value_of("MF"), output) {
+        (Some(x), _) => x.to_string(),
+        (None, Some(x)) => Path::new(x).with_extension("d").display().to_string(),
+        (None, None) => {
+            sess.emit(
+                DiagBuilder2::fatal("no file to write the Makefile dependencies to")
+                    .add_note("Use `-MF FILE`, or `-o FILE` to write them next to the output file"),
+            );
+            return Err(());
+        }
+    };
+
+    // Escape the characters which are special in Makefile rules.
+    let escape = |x: &str| x.replace('$', "$$").replace('#', "\\#").replace(' ', "\\ ");
+    let mut rule = format!("{}:", escape(output.unwrap_or(&path)));
+    for file in crate::fs::files_read() {
+        rule.push_str(" \\\n  ");
+        rule.push_str(&escape(&file.display().to_string()));
+    }
+    rule.push('\n');
+
+    let result = if path == "-" {
+        print!("{}", rule);
+        Ok(())
+    } else {
+        std::fs::write(&path, rule)
+    };
+    result.map_err(|e| {
+        sess.emit(
+            DiagBuilder2::fatal(format!("unable to write `{}`", path)).add_note(format!("{}", e)),
+        );
+    })
+}
+
+/// Open the output file, or stdout if none is given.
+fn open_output(matches: &ArgMatches, ctx: &ScoreContext) -> Result<Box<dyn std::io::Write>, ()> {
+    match matches.value_of("output") {
+        Some("-") | None => Ok(Box::new(std::io::stdout())),
+        Some(x) => Ok(Box::new(std::fs::File::create(x).map_err(|e| {
+            ctx.sess.emit(
+                DiagBuilder2::fatal(format!("unable to create file: `{}`", x))
+                    .add_note(format!("{}", e)),
+            );
+        })?)),
+    }
+}
+
+/// Parse an entity name of the form `(first\.)?second((arch))?` for
+/// elaboration.
+fn parse_elaborate_name<S: AsRef<str>>(name: S) -> Result<(Option<Name>, Name, Option<Name>), ()> {
+    use self::name::get_name_table;
+    let name = name.as_ref();
+    let nt = get_name_table();
+
+    // Isolate the first name.
+    let x: &[_] = &['.', '('];
+    let (first, rest) = {
+        if let Some(pos) = name.find(x) {
+            let (a, b) = name.split_at(pos);
+            (a, Some(b))
+        } else {
+            (name, None)
+        }
+    };
+    let first = nt.intern(first, true);
+
+    // Isolate the second name.
+    let (second, rest) = {
+        if let Some(rest) = rest {
+            if rest.starts_with('.') {
+                let rest = &rest[1..];
+                if let Some(pos) = rest.find('(') {
+                    let (a, b) = rest.split_at(pos);
+                    (Some(a), Some(b))
+                } else {
+                    (Some(rest), None)
+                }
+            } else {
+                (None, Some(rest))
+            }
+        } else {
+            (None, None)
+        }
+    };
+    let second = second.map(|s| nt.intern(s, true));
+
+    // Isolate the architecture name.
+    let third = {
+        if let Some(rest) = rest {
+            if rest.starts_with('(') && rest.ends_with(')') {
+                Some(&rest[1..rest.len() - 1])
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    };
+    let third = third.map(|t| nt.intern(t, true));
+
+    // Return the names in the appropriate order.
+    let (lib, ent) = {
+        if let Some(second) = second {
+            (Some(first), second)
+        } else {
+            (None, first)
+        }
+    };
+
+    Ok((lib, ent, third))
+}
+
+/// A visitor that emits detailed type information to stdout.
+pub struct TypeVerbosityVisitor<'a, 'gcx>(&'a svlog::GlobalContext<'gcx>, svlog::ParamEnv);
+
+impl<'a, 'gcx> svlog::hir::Visitor<'gcx> for TypeVerbosityVisitor<'a, 'gcx> {
+    type Context = svlog::GlobalContext<'gcx>;
+
+    fn context(&self) -> &Self::Context {
+        self.0
+    }
+
+    fn visit_expr(&mut self, expr: &'gcx svlog::hir::Expr<'gcx>, lvalue: bool) {
+        self.print(expr.id);
+        svlog::hir::walk_expr(self, expr, lvalue);
+    }
+
+    fn visit_var_decl(&mut self, decl: &'gcx svlog::hir::VarDecl) {
+        self.print(decl.id);
+        svlog::hir::walk_var_decl(self, decl);
+    }
+}
+
+impl<'a, 'gcx> TypeVerbosityVisitor<'a, 'gcx> {
+    fn print(&mut self, id: NodeId) {
+        use svlog::Context;
+        let span = self.0.span(id);
+        let ext = span.extract();
+        let line = span.begin().human_line();
+
+        // Report the type.
+        if let Ok(ty) = self.0.type_of(id, self.1) {
+            println!("{}: type({}) = {}", line, ext, ty);
+        }
+
+        // Report the cast type.
+        if let Some(cast) = self.0.cast_type(id, self.1) {
+            println!("{}: cast_type({}) = {}", line, ext, cast.ty);
+            println!("{}: cast_chain({}) = {}", line, ext, cast);
+        }
+
+        // Report the self-determined type.
+        if let Some(ty) = self.0.self_determined_type(id, self.1) {
+            println!("{}: self_type({}) = {}", line, ext, ty);
+        }
+
+        // Report the operation type.
+        if let Some(ty) = self.0.operation_type(id, self.1) {
+            println!("{}: operation_type({}) = {}", line, ext, ty);
+        }
+
+        // Report the type context.
+        if let Some(ty) = self.0.type_context(id, self.1) {
+            println!(
+                "{}: type_context({}) = {}",
+                line,
+                ext,
+                match ty {
+                    svlog::typeck::TypeContext::Type(ty) => format!("{}", ty),
+                    svlog::typeck::TypeContext::Bool => "<bool>".to_string(),
+                }
+            );
+        }
+    }
+}
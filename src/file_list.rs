@@ -0,0 +1,195 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! File lists.
+//!
+//! Most flows describe the sources of a design in file list files, which are
+//! passed to the compiler with `-f <file>` or `-F <file>`. This module expands
+//! these options into the arguments listed in the file, before the command
+//! line is parsed. A file list contains whitespace-separated arguments, and
+//! `//` or `#` comments until the end of the line:
+//!
+//! ```text
+//! // Sources of the core.
+//! +incdir+include
+//! +define+WIDTH=8+SIMULATION
+//! core.sv
+//! -f ${IP_ROOT}/ip.f   # nested file list
+//! ```
+//!
+//! Besides the source files and the options the compiler accepts on the
+//! command line, file lists may contain `+define+NAME[=VALUE]+...` and
+//! `+incdir+DIR+...`, which become `-D` and `-I` options. Environment
+//! variables of the form `$VAR` or `${VAR}` are substituted. Relative paths
+//! in a file list passed with `-F` are relative to the file list itself,
+//! whereas those passed with `-f` are relative to the working directory.
+
+use crate::errors::*;
+use std::path::{Path, PathBuf};
+
+/// Expand the `-f` and `-F` options in a list of command line arguments.
+///
+/// Also translates `+define+NAME[=VALUE]+...` and `+incdir+DIR+...` into `-D`
+/// and `-I` options, and `-MD` and `-MF` into `--MD` and `--MF`, such that
+/// these can be passed in the same form as to other tools.
+pub fn expand_args(args: impl IntoIterator<Item = String>) -> Result<Vec<String>, DiagBuilder2> {
+    let mut output = vec![];
+    let mut stack = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "-F" => {
+                let path = args.next().ok_or_else(|| {
+                    DiagBuilder2::fatal(format!("missing file list after `{}`", arg))
+                })?;
+                read_file_list(Path::new(&path), arg == "-F", &mut stack, &mut output)?;
+            }
+            "-MD" | "-MF" => output.push(format!("-{}", arg)),
+            _ if arg.starts_with("+define+") => push_defines(&arg, &mut output),
+            _ if arg.starts_with("+incdir+") => push_incdirs(&arg, |d| d.to_string(), &mut output),
+            _ => output.push(arg),
+        }
+    }
+    Ok(output)
+}
+
+/// Read the arguments in a file list.
+///
+/// The `stack` contains the file lists currently being read, to detect file
+/// lists which include themselves.
+fn read_file_list(
+    path: &Path,
+    relative: bool,
+    stack: &mut Vec<PathBuf>,
+    output: &mut Vec<String>,
+) -> Result<(), DiagBuilder2> {
+    let content = crate::fs::read_to_string(path).map_err(|e| {
+        DiagBuilder2::fatal(format!("unable to read file list `{}`", path.display()))
+            .add_note(format!("{}", e))
+    })?;
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if stack.contains(&canonical) {
+        return Err(DiagBuilder2::fatal(format!(
+            "file list `{}` includes itself",
+            path.display()
+        )));
+    }
+    stack.push(canonical);
+
+    // Relative paths are resolved against this directory, if any.
+    let base = match path.parent() {
+        Some(dir) if relative => Some(dir),
+        _ => None,
+    };
+    let resolve = |p: &str| -> String {
+        match base {
+            Some(base) if Path::new(p).is_relative() => base.join(p).display().to_string(),
+            _ => p.to_string(),
+        }
+    };
+
+    let mut args = tokenize(&content)
+        .into_iter()
+        .map(|token| substitute_env(&token, path))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |arg: &str| {
+            args.next().ok_or_else(|| {
+                DiagBuilder2::fatal(format!(
+                    "missing value after `{}` in file list `{}`",
+                    arg,
+                    path.display()
+                ))
+            })
+        };
+        if arg == "-f" || arg == "-F" {
+            let nested = value(&arg)?;
+            read_file_list(Path::new(&resolve(&nested)), arg == "-F", stack, output)?;
+        } else if arg == "-I" {
+            let dir = value(&arg)?;
+            output.push(arg);
+            output.push(resolve(&dir));
+        } else if arg == "-D" {
+            let def = value(&arg)?;
+            output.push(arg);
+            output.push(def);
+        } else if arg.starts_with("+define+") {
+            push_defines(&arg, output);
+        } else if arg.starts_with("+incdir+") {
+            push_incdirs(&arg, &resolve, output);
+        } else if arg.starts_with('-') || arg.starts_with('+') {
+            output.push(arg);
+        } else {
+            output.push(resolve(&arg));
+        }
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+/// Translate a `+define+NAME[=VALUE]+...` argument into `-D` options.
+fn push_defines(arg: &str, output: &mut Vec<String>) {
+    for def in arg["+define+".len()..].split('+').filter(|d| !d.is_empty()) {
+        output.push("-D".to_string());
+        output.push(def.to_string());
+    }
+}
+
+/// Translate a `+incdir+DIR+...` argument into `-I` options.
+fn push_incdirs(arg: &str, resolve: impl Fn(&str) -> String, output: &mut Vec<String>) {
+    for dir in arg["+incdir+".len()..].split('+').filter(|d| !d.is_empty()) {
+        output.push("-I".to_string());
+        output.push(resolve(dir));
+    }
+}
+
+/// Split the content of a file list into arguments, dropping comments.
+fn tokenize(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .flat_map(|line| {
+            let line = match line.find("//") {
+                Some(i) => &line[..i],
+                None => line,
+            };
+            let line = match line.find('#') {
+                Some(i) => &line[..i],
+                None => line,
+            };
+            line.split_whitespace().map(String::from)
+        })
+        .collect()
+}
+
+/// Substitute the environment variables in an argument.
+fn substitute_env(arg: &str, path: &Path) -> Result<String, DiagBuilder2> {
+    let mut output = String::new();
+    let mut rest = arg;
+    while let Some(i) = rest.find('$') {
+        output.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let (name, len) = if rest.starts_with('{') {
+            match rest.find('}') {
+                Some(end) => (&rest[1..end], end + 1),
+                None => (&rest[1..], rest.len()),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or_else(|| rest.len());
+            (&rest[..end], end)
+        };
+        let value = std::env::var(name).map_err(|_| {
+            DiagBuilder2::fatal(format!(
+                "environment variable `{}` in file list `{}` is not set",
+                name,
+                path.display()
+            ))
+        })?;
+        output.push_str(&value);
+        rest = &rest[len..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
@@ -0,0 +1,94 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Input files given as directories or glob patterns.
+//!
+//! Besides plain files, the input files on the command line may be
+//! directories or glob patterns such as `rtl/**/*.sv`. This module expands
+//! them into the files they refer to, in an order which only depends on the
+//! file names, such that repeated runs compile the files in the same order.
+//! Directories expand to the SystemVerilog, Verilog, and VHDL files below
+//! them, leaving out include files such as `.svh`. Glob patterns expand to
+//! all matching files, which are then dispatched to a frontend by their
+//! extension like any other input.
+
+use crate::errors::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The extensions of the files a directory expands to.
+const SOURCE_EXTENSIONS: &[&str] = &["sv", "v", "vhd", "vhdl"];
+
+/// Expand the directories and glob patterns in a list of input files.
+///
+/// Plain files and `-` are passed through unchanged. A file which is found by
+/// more than one directory or pattern is only listed once.
+pub fn expand<'a>(inputs: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>, DiagBuilder2> {
+    let mut output = vec![];
+    let mut seen = HashSet::new();
+    for input in inputs {
+        let files = if Path::new(input).is_dir() {
+            let mut files = vec![];
+            read_dir(Path::new(input), &mut files)?;
+            files
+        } else if is_pattern(input) && !Path::new(input).exists() {
+            glob_files(input)?
+        } else {
+            output.push(input.to_string());
+            continue;
+        };
+        for file in files {
+            if seen.insert(file.clone()) {
+                output.push(file.to_string_lossy().into_owned());
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Check whether an input contains glob metacharacters.
+fn is_pattern(input: &str) -> bool {
+    input.contains(&['*', '?', '['][..])
+}
+
+/// Collect the source files below a directory, sorted by name.
+fn read_dir(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), DiagBuilder2> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        DiagBuilder2::fatal(format!("unable to read directory `{}`", dir.display()))
+            .add_note(format!("{}", e))
+    })?;
+    let mut paths: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            read_dir(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(|x| SOURCE_EXTENSIONS.contains(&x))
+            .unwrap_or(false)
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Collect the files matching a glob pattern, sorted by name.
+fn glob_files(pattern: &str) -> Result<Vec<PathBuf>, DiagBuilder2> {
+    let paths = glob::glob(pattern).map_err(|e| {
+        DiagBuilder2::fatal(format!("invalid glob pattern `{}`", pattern))
+            .add_note(format!("{}", e))
+    })?;
+    let mut files: Vec<_> = paths
+        .filter_map(|p| p.ok())
+        .filter(|p| p.is_file())
+        .collect();
+    if files.is_empty() {
+        return Err(DiagBuilder2::fatal(format!(
+            "no input files match `{}`",
+            pattern
+        )));
+    }
+    files.sort();
+    Ok(files)
+}
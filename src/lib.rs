@@ -15,4 +15,24 @@ pub use moore_common::*;
 pub use moore_svlog as svlog;
 pub use moore_vhdl as vhdl;
 
+pub mod conformance;
+pub mod debug_info;
+pub mod deps;
+pub mod design;
+pub mod doc;
+pub mod driver;
+pub mod file_list;
+pub mod inputs;
+pub mod library;
+pub mod lsp;
+pub mod mixed;
+pub mod netlist;
+pub mod opt;
+pub mod parallel;
+pub mod pass;
+pub mod rename;
 pub mod score;
+pub mod sim;
+pub mod stubs;
+pub mod symbols;
+pub mod tags;
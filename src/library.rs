@@ -0,0 +1,342 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Library mapping.
+//!
+//! A library is mapped to a directory with `--lib NAME=DIR`. The directory
+//! holds an index of the design units compiled into the library, together
+//! with the source files they are defined in:
+//!
+//! ```text
+//! std_logic_1164 /src/ieee/std_logic_1164.vhd
+//! numeric_std /src/ieee/numeric_std.vhd
+//! ```
+//!
+//! Compiling files into a mapped library records their units in the index.
+//! Later runs load the files of the units which the input files refer to:
+//! VHDL units named in `use` clauses, and SystemVerilog packages and modules
+//! named in `pkg::` references and instantiations. This happens transitively,
+//! such that the units used by a library file are loaded as well. Relative
+//! paths in an index are relative to the library directory.
+
+use crate::common::errors::*;
+use crate::common::source::Source;
+use crate::mixed::InstTargets;
+use crate::score::Ast;
+use crate::svlog::{
+    ast as sv,
+    lexer::Lexer,
+    preproc::{MacroTable, Preprocessor},
+    token::Token,
+};
+use crate::vhdl::syntax::ast as vhdl;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// The name of the index file in a library directory.
+pub const INDEX_FILE: &str = "moore.index";
+
+/// A library mapped to a directory.
+#[derive(Debug)]
+pub struct Library {
+    /// The name of the library.
+    pub name: String,
+    /// The directory which holds the library index.
+    pub dir: PathBuf,
+}
+
+impl Library {
+    /// Parse a library mapping of the form `NAME=DIR`.
+    pub fn parse(arg: &str) -> Option<Library> {
+        let mut iter = arg.splitn(2, '=');
+        let name = iter.next()?;
+        let dir = iter.next()?;
+        if name.is_empty() || dir.is_empty() {
+            return None;
+        }
+        Some(Library {
+            name: name.to_string(),
+            dir: PathBuf::from(dir),
+        })
+    }
+
+    /// Read the index of the library.
+    ///
+    /// A library that does not have an index yet is empty.
+    pub fn read_index(&self) -> Result<Vec<(String, PathBuf)>, DiagBuilder2> {
+        let path = self.dir.join(INDEX_FILE);
+        let content = match crate::fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => {
+                return Err(DiagBuilder2::fatal(format!(
+                    "unable to read index of library `{}`",
+                    self.name
+                ))
+                .add_note(format!("{}: {}", path.display(), e)))
+            }
+        };
+        let mut entries = vec![];
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, char::is_whitespace);
+            match (parts.next(), parts.next().map(str::trim)) {
+                (Some(unit), Some(file)) if !file.is_empty() => {
+                    entries.push((unit.to_string(), self.dir.join(file)))
+                }
+                _ => {
+                    return Err(DiagBuilder2::fatal(format!(
+                        "malformed line {} in index of library `{}`",
+                        index + 1,
+                        self.name
+                    ))
+                    .add_note(format!("{}", path.display())))
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Record units and the files they are defined in in the index.
+    ///
+    /// Replaces any previous entries for the same units or files.
+    pub fn update_index(&self, units: &[(String, PathBuf)]) -> Result<(), DiagBuilder2> {
+        let mut entries = self.read_index()?;
+        entries.retain(|(unit, file)| !units.iter().any(|(u, f)| u == unit || f == file));
+        entries.extend(units.iter().cloned());
+        entries.sort();
+        entries.dedup();
+
+        let mut content = format!("# Units in library `{}`\n", self.name);
+        for (unit, file) in entries {
+            content.push_str(&format!("{} {}\n", unit, file.display()));
+        }
+        let path = self.dir.join(INDEX_FILE);
+        crate::fs::get_file_system()
+            .write(&path, &content)
+            .map_err(|e| {
+                DiagBuilder2::fatal(format!("unable to write index of library `{}`", self.name))
+                    .add_note(format!("{}: {}", path.display(), e))
+            })
+    }
+}
+
+/// A reference to a design unit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum UnitRef {
+    /// A VHDL unit in a library, as in `use lib.unit.all`.
+    Vhdl(String, String),
+    /// A SystemVerilog package or module.
+    Svlog(String),
+}
+
+/// Determine the units defined in a source file, as named in the index.
+///
+/// VHDL names are case-insensitive and thus lowercase. Architectures and
+/// package bodies are listed under their entity or package.
+pub fn defined_units(ast: &Ast) -> Vec<String> {
+    match ast {
+        Ast::Vhdl(units) => units
+            .iter()
+            .filter_map(|unit| match unit.data {
+                vhdl::DesignUnitData::EntityDecl(ref x) => Some(x.name.value),
+                vhdl::DesignUnitData::CfgDecl(ref x) => Some(x.name.value),
+                vhdl::DesignUnitData::PkgDecl(ref x) => Some(x.name.value),
+                vhdl::DesignUnitData::PkgInst(ref x) => Some(x.name.value),
+                vhdl::DesignUnitData::CtxDecl(ref x) => Some(x.name.value),
+                vhdl::DesignUnitData::PkgBody(ref x) => Some(x.name.value),
+                vhdl::DesignUnitData::ArchBody(ref x) => match x.target.primary.kind {
+                    vhdl::PrimaryNameKind::Ident(name) => Some(name),
+                    _ => None,
+                },
+            })
+            .map(|name| name.as_str().to_lowercase())
+            .collect(),
+        Ast::Svlog(file) => file
+            .items
+            .iter()
+            .filter_map(|item| match item.data {
+                sv::ItemData::ModuleDecl(ref x) => Some(x.name.value),
+                sv::ItemData::InterfaceDecl(ref x) => Some(x.name.value),
+                sv::ItemData::ProgramDecl(ref x) => Some(x.name.value),
+                sv::ItemData::UdpDecl(ref x) => Some(x.name.value),
+                sv::ItemData::PackageDecl(ref x) => Some(x.name.value),
+                _ => None,
+            })
+            .map(|name| name.to_string())
+            .collect(),
+    }
+}
+
+/// Determine the units referenced by a source file.
+///
+/// For SystemVerilog files, `scoped` are the names that appear in front of a
+/// `::`, as determined by [`scoped_names`].
+pub fn referenced_units(ast: &Ast, scoped: Vec<String>) -> Vec<UnitRef> {
+    match ast {
+        Ast::Vhdl(units) => units
+            .iter()
+            .flat_map(used_units)
+            .map(|(lib, unit)| UnitRef::Vhdl(lib, unit))
+            .collect(),
+        Ast::Svlog(file) => {
+            let mut insts = InstTargets::default();
+            insts.collect(&file.items);
+            scoped
+                .into_iter()
+                .chain(insts.targets.into_iter().map(|(name, _)| name.to_string()))
+                .map(UnitRef::Svlog)
+                .collect()
+        }
+    }
+}
+
+/// Determine the units named in the `use` and context clauses of a VHDL design
+/// unit, as pairs of lowercase library and unit names.
+pub(crate) fn used_units(unit: &vhdl::DesignUnit) -> Vec<(String, String)> {
+    let mut refs = vec![];
+    for item in &unit.ctx {
+        let names = match *item {
+            vhdl::CtxItem::UseClause(ref names) => names,
+            vhdl::CtxItem::CtxRef(ref names) => names,
+            vhdl::CtxItem::LibClause(..) => continue,
+        };
+        for name in &names.value {
+            let lib = match name.primary.kind {
+                vhdl::PrimaryNameKind::Ident(lib) => lib,
+                _ => continue,
+            };
+            let unit = match name.parts.first() {
+                Some(vhdl::NamePart::Select(vhdl::PrimaryName {
+                    kind: vhdl::PrimaryNameKind::Ident(unit),
+                    ..
+                })) => unit,
+                _ => continue,
+            };
+            refs.push((lib.as_str().to_lowercase(), unit.as_str().to_lowercase()));
+        }
+    }
+    refs
+}
+
+/// Find the names which appear in front of a `::` in a SystemVerilog file.
+///
+/// These are the packages and classes a file refers to, either through
+/// `import pkg::*` or directly as in `pkg::name`.
+pub fn scoped_names(
+    source: Source,
+    include_paths: &[&Path],
+    defines: &[(&str, Option<&str>)],
+) -> Vec<String> {
+    let preproc = Preprocessor::with_macros(source, include_paths, MacroTable::new(defines));
+    let mut names = vec![];
+    let mut last = None;
+    for token in Lexer::new(preproc) {
+        let token = match token {
+            Ok((token, _)) => token,
+            Err(_) => break,
+        };
+        if let (Some(name), Token::Namespace) = (last, token) {
+            names.push(name);
+        }
+        last = match token {
+            Token::Ident(name) | Token::EscIdent(name) => Some(name),
+            _ => None,
+        };
+    }
+    let mut seen = HashSet::new();
+    names
+        .into_iter()
+        .filter(|name| seen.insert(*name))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Determines which library files need to be loaded.
+///
+/// Add the references of the input files with [`reference`] and call
+/// [`next_file`] to obtain the next file to be loaded, until it returns
+/// `None`. The references of each loaded file should be added as well.
+///
+/// [`reference`]: Loader::reference
+/// [`next_file`]: Loader::next_file
+pub struct Loader<'a> {
+    libs: &'a [Library],
+    /// The files of each unit, for each library.
+    indices: Vec<HashMap<String, Vec<PathBuf>>>,
+    /// The files which have been loaded or queued.
+    loaded: HashSet<PathBuf>,
+    /// The files to be loaded, and the library they belong to.
+    queue: VecDeque<(usize, PathBuf)>,
+}
+
+impl<'a> Loader<'a> {
+    /// Read the indices of a list of libraries.
+    pub fn new(libs: &'a [Library]) -> Result<Self, DiagBuilder2> {
+        let mut indices = vec![];
+        for lib in libs {
+            let mut index: HashMap<String, Vec<PathBuf>> = HashMap::new();
+            for (unit, file) in lib.read_index()? {
+                index.entry(unit).or_default().push(canonicalize(&file));
+            }
+            indices.push(index);
+        }
+        Ok(Loader {
+            libs,
+            indices,
+            loaded: Default::default(),
+            queue: Default::default(),
+        })
+    }
+
+    /// Mark a file as loaded, such that it is not loaded again.
+    pub fn mark_loaded(&mut self, file: &Path) {
+        self.loaded.insert(canonicalize(file));
+    }
+
+    /// Queue the files which define the units referenced by a file.
+    ///
+    /// `lib` is the library the referencing file belongs to, which VHDL
+    /// refers to as `work`.
+    pub fn reference(&mut self, lib: Option<usize>, refs: Vec<UnitRef>) {
+        for r in refs {
+            let found = match r {
+                UnitRef::Vhdl(lib_name, unit) => {
+                    let index = if lib_name == "work" {
+                        lib
+                    } else {
+                        self.libs
+                            .iter()
+                            .position(|l| l.name.to_lowercase() == lib_name)
+                    };
+                    index.and_then(|i| self.indices[i].get(&unit).map(|files| (i, files)))
+                }
+                UnitRef::Svlog(unit) => self
+                    .indices
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, index)| index.get(&unit).map(|files| (i, files)))
+                    .next(),
+            };
+            if let Some((i, files)) = found {
+                for file in files.clone() {
+                    if self.loaded.insert(file.clone()) {
+                        self.queue.push_back((i, file));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the next file to be loaded, and the library it belongs to.
+    pub fn next_file(&mut self) -> Option<(usize, PathBuf)> {
+        self.queue.pop_front()
+    }
+}
+
+/// Canonicalize a path, if possible.
+pub fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
@@ -0,0 +1,684 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! A language server.
+//!
+//! Implements the Language Server Protocol over stdin and stdout, such that
+//! editors can show the diagnostics of the parsers while a file is edited,
+//! outline the declarations in a file, and jump to the declaration of a name
+//! within the same file.
+
+use crate::common::errors::{capture_diags, DiagBuilder2, DiagSegment, Severity};
+use crate::common::source::{get_source_manager, Source, Span};
+use crate::score::Ast;
+use crate::svlog;
+use crate::symbols::{self, Symbol, SymbolKind};
+use crate::vhdl;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::Notification as _;
+use lsp_types::request::Request as _;
+use lsp_types::{self as lsp, Url};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The errors which terminate the language server.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// The options which configure how the language server parses files.
+#[derive(Debug, Default)]
+pub struct Options {
+    /// The search paths for SystemVerilog includes.
+    pub include_paths: Vec<PathBuf>,
+    /// The macros to predefine, as name and optional value.
+    pub defines: Vec<(String, Option<String>)>,
+    /// The revision of the SystemVerilog standard to parse files as.
+    pub svlog_standard: svlog::token::Standard,
+    /// The revision of the VHDL standard to parse files as.
+    pub vhdl_standard: vhdl::syntax::lexer::token::Standard,
+    /// Whether to parse SystemVerilog files as Verilog.
+    pub verilog: bool,
+}
+
+/// Serve the Language Server Protocol over stdin and stdout.
+///
+/// Returns once the client has shut down the server.
+pub fn run(opts: &Options) -> Result<(), Error> {
+    let (connection, io_threads) = Connection::stdio();
+    serve(opts, &connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+/// Serve the Language Server Protocol over a connection.
+///
+/// Returns once the client has shut down the server.
+fn serve(opts: &Options, connection: &Connection) -> Result<(), Error> {
+    let caps = lsp::ServerCapabilities {
+        text_document_sync: Some(lsp::TextDocumentSyncCapability::Kind(
+            lsp::TextDocumentSyncKind::FULL,
+        )),
+        document_symbol_provider: Some(lsp::OneOf::Left(true)),
+        definition_provider: Some(lsp::OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(caps)?)?;
+    let mut server = Server {
+        opts,
+        connection,
+        documents: HashMap::new(),
+    };
+    for msg in &connection.receiver {
+        match msg {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    break;
+                }
+                server.handle_request(req)?;
+            }
+            Message::Notification(not) => server.handle_notification(not)?,
+            Message::Response(_) => (),
+        }
+    }
+    Ok(())
+}
+
+/// The state of the language server.
+struct Server<'a> {
+    opts: &'a Options,
+    connection: &'a Connection,
+    /// The documents currently open in the editor.
+    documents: HashMap<Url, Document>,
+}
+
+/// A document open in the editor.
+///
+/// Releases its source when dropped.
+struct Document {
+    /// The contents of the document.
+    text: String,
+    /// The source which holds the contents.
+    source: Source,
+    /// Whether the document is a VHDL file, whose names are case-insensitive.
+    vhdl: bool,
+    /// The symbols declared in the document.
+    symbols: Vec<Symbol>,
+}
+
+impl<'a> Server<'a> {
+    fn handle_request(&mut self, req: Request) -> Result<(), Error> {
+        let resp = match self.respond(&req) {
+            Ok(result) => Response::new_ok(req.id, result),
+            Err((code, message)) => Response::new_err(req.id, code as i32, message),
+        };
+        self.connection.sender.send(Message::Response(resp))?;
+        Ok(())
+    }
+
+    /// Compute the result of a request, or the error to reply with.
+    fn respond(&self, req: &Request) -> Result<serde_json::Value, (ErrorCode, String)> {
+        let result = match req.method.as_str() {
+            lsp::request::DocumentSymbolRequest::METHOD => {
+                let params: lsp::DocumentSymbolParams = request_params(req)?;
+                let symbols = self
+                    .documents
+                    .get(&params.text_document.uri)
+                    .map(|doc| doc.document_symbols(&doc.symbols))
+                    .unwrap_or_default();
+                serde_json::to_value(lsp::DocumentSymbolResponse::Nested(symbols))
+            }
+            lsp::request::GotoDefinition::METHOD => {
+                let params: lsp::GotoDefinitionParams = request_params(req)?;
+                let pos = params.text_document_position_params;
+                let location = self.documents.get(&pos.text_document.uri).and_then(|doc| {
+                    let span = doc.definition(pos.position)?;
+                    Some(lsp::Location::new(
+                        pos.text_document.uri.clone(),
+                        doc.range(span),
+                    ))
+                });
+                serde_json::to_value(location)
+            }
+            _ => {
+                return Err((
+                    ErrorCode::MethodNotFound,
+                    format!("unsupported request `{}`", req.method),
+                ))
+            }
+        };
+        result.map_err(|err| (ErrorCode::InternalError, err.to_string()))
+    }
+
+    fn handle_notification(&mut self, not: Notification) -> Result<(), Error> {
+        match not.method.as_str() {
+            lsp::notification::DidOpenTextDocument::METHOD => {
+                if let Some(params) = notification_params::<lsp::DidOpenTextDocumentParams>(not) {
+                    let doc = params.text_document;
+                    self.update(doc.uri, doc.text, Some(doc.version))?;
+                }
+            }
+            lsp::notification::DidChangeTextDocument::METHOD => {
+                if let Some(params) = notification_params::<lsp::DidChangeTextDocumentParams>(not) {
+                    // With full synchronization the last change holds the
+                    // entire contents of the document.
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        let doc = params.text_document;
+                        self.update(doc.uri, change.text, Some(doc.version))?;
+                    }
+                }
+            }
+            lsp::notification::DidCloseTextDocument::METHOD => {
+                if let Some(params) = notification_params::<lsp::DidCloseTextDocumentParams>(not) {
+                    let uri = params.text_document.uri;
+                    self.documents.remove(&uri);
+                    self.publish(uri, vec![], None)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Parse the new contents of a document and publish its diagnostics.
+    ///
+    /// If the contents fail to parse, the last version of the document which
+    /// parsed is kept, such that its symbols remain available while an edit
+    /// is in progress.
+    fn update(&mut self, uri: Url, text: String, version: Option<i32>) -> Result<(), Error> {
+        let filename = match uri.to_file_path() {
+            Ok(path) => path.display().to_string(),
+            Err(()) => uri.to_string(),
+        };
+        let vhdl = match Path::new(&filename).extension().and_then(|s| s.to_str()) {
+            Some("sv") | Some("svh") | Some("v") | Some("vh") => false,
+            Some("vhd") | Some("vhdl") => true,
+            _ => {
+                debug!("Ignoring `{}` of unknown language", filename);
+                return Ok(());
+            }
+        };
+        let source = get_source_manager().replace(&filename, &text);
+        let (symbols, captured) = capture_diags(|| {
+            if vhdl {
+                self.parse_vhdl(source)
+            } else {
+                self.parse_svlog(source)
+            }
+        });
+        let parsed = symbols.is_some();
+        let doc = Document {
+            text,
+            source,
+            vhdl,
+            symbols: symbols.unwrap_or_default(),
+        };
+        let diags = captured
+            .diags
+            .iter()
+            .map(|diag| doc.diagnostic(diag))
+            .collect();
+        if parsed || !self.documents.contains_key(&uri) {
+            self.documents.insert(uri.clone(), doc);
+        }
+        self.publish(uri, diags, version)
+    }
+
+    fn parse_svlog(&self, source: Source) -> Option<Vec<Symbol>> {
+        let include_paths: Vec<_> = self
+            .opts
+            .include_paths
+            .iter()
+            .map(|x| x.as_path())
+            .collect();
+        let defines: Vec<_> = self
+            .opts
+            .defines
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_ref().map(|x| x.as_str())))
+            .collect();
+        let macros = svlog::preproc::MacroTable::new(&defines);
+        let preproc = svlog::preproc::Preprocessor::with_macros(source, &include_paths, macros)
+            .with_standard(self.opts.svlog_standard);
+        let tokens = svlog::lexer::Tokens::new(svlog::lexer::Lexer::new(preproc));
+        let arena = svlog::ast::Arena::default();
+        let file = if self.opts.verilog {
+            svlog::parser::parse_verilog(tokens, &arena)
+        } else {
+            svlog::parser::parse(tokens, &arena)
+        };
+        match file {
+            Ok(file) => Some(symbols::collect(&Ast::Svlog(file))),
+            Err(()) => None,
+        }
+    }
+
+    fn parse_vhdl(&self, source: Source) -> Option<Vec<Symbol>> {
+        vhdl::syntax::parse_with_standard(source, self.opts.vhdl_standard)
+            .ok()
+            .map(|units| symbols::collect(&Ast::Vhdl(units)))
+    }
+
+    fn publish(
+        &self,
+        uri: Url,
+        diagnostics: Vec<lsp::Diagnostic>,
+        version: Option<i32>,
+    ) -> Result<(), Error> {
+        let params = lsp::PublishDiagnosticsParams {
+            uri,
+            diagnostics,
+            version,
+        };
+        let not = Notification::new(
+            lsp::notification::PublishDiagnostics::METHOD.to_string(),
+            params,
+        );
+        self.connection.sender.send(Message::Notification(not))?;
+        Ok(())
+    }
+}
+
+impl Drop for Document {
+    fn drop(&mut self) {
+        get_source_manager().release(self.source);
+    }
+}
+
+impl Document {
+    /// Convert a span in the document to a range of UTF-16 positions.
+    ///
+    /// Spans in other files, such as an included header, map to the start of
+    /// the document.
+    fn range(&self, span: Span) -> lsp::Range {
+        if span.source != self.source {
+            return lsp::Range::default();
+        }
+        lsp::Range::new(
+            self.position(span.begin as usize),
+            self.position(span.end as usize),
+        )
+    }
+
+    /// Convert a byte offset in the document to a UTF-16 position.
+    fn position(&self, offset: usize) -> lsp::Position {
+        let mut pos = lsp::Position::default();
+        for (i, c) in self.text.char_indices() {
+            if i >= offset {
+                break;
+            }
+            if c == '\n' {
+                pos.line += 1;
+                pos.character = 0;
+            } else {
+                pos.character += c.len_utf16() as u32;
+            }
+        }
+        pos
+    }
+
+    /// Convert a UTF-16 position to a byte offset in the document.
+    fn offset(&self, pos: lsp::Position) -> usize {
+        let mut line = 0;
+        let mut character = 0;
+        for (i, c) in self.text.char_indices() {
+            if line == pos.line && (character >= pos.character || c == '\n') {
+                return i;
+            }
+            if c == '\n' {
+                line += 1;
+                character = 0;
+            } else {
+                character += c.len_utf16() as u32;
+            }
+        }
+        self.text.len()
+    }
+
+    /// Convert a diagnostic of the parsers.
+    fn diagnostic(&self, diag: &DiagBuilder2) -> lsp::Diagnostic {
+        let mut range = None;
+        let mut message = diag.message.clone();
+        for segment in &diag.segments {
+            match *segment {
+                DiagSegment::Span(span) => {
+                    range.get_or_insert_with(|| self.range(span));
+                }
+                DiagSegment::Note(ref note) => {
+                    message.push('\n');
+                    message.push_str(note);
+                }
+            }
+        }
+        let severity = match diag.severity {
+            Severity::Note => lsp::DiagnosticSeverity::INFORMATION,
+            Severity::Warning => lsp::DiagnosticSeverity::WARNING,
+            Severity::Error | Severity::Fatal | Severity::Bug => lsp::DiagnosticSeverity::ERROR,
+        };
+        lsp::Diagnostic {
+            range: range.unwrap_or_default(),
+            severity: Some(severity),
+            source: Some("moore".to_string()),
+            message,
+            ..Default::default()
+        }
+    }
+
+    /// Convert symbols declared in the document.
+    #[allow(deprecated)]
+    fn document_symbols(&self, symbols: &[Symbol]) -> Vec<lsp::DocumentSymbol> {
+        symbols
+            .iter()
+            .filter(|sym| sym.name_span.source == self.source)
+            .map(|sym| {
+                let mut range = self.range(sym.span);
+                let selection_range = self.range(sym.name_span);
+                // The declaration may start in an included file, yet the range
+                // must contain the selection range.
+                if sym.span.source != self.source {
+                    range = selection_range;
+                }
+                lsp::DocumentSymbol {
+                    name: sym.name.clone(),
+                    detail: Some(sym.kind.as_str().to_string()),
+                    kind: symbol_kind(sym.kind),
+                    tags: None,
+                    deprecated: None,
+                    range,
+                    selection_range,
+                    children: Some(self.document_symbols(&sym.children)),
+                }
+            })
+            .collect()
+    }
+
+    /// Find the declaration of the name at a position.
+    ///
+    /// Prefers the innermost declaration whose enclosing declarations contain
+    /// the position, and falls back to any declaration of the name in the
+    /// document.
+    fn definition(&self, pos: lsp::Position) -> Option<Span> {
+        let offset = self.offset(pos);
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+        let begin = self.text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| is_ident(c))
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(offset);
+        let end = self.text[offset..]
+            .char_indices()
+            .find(|&(_, c)| !is_ident(c))
+            .map(|(i, _)| offset + i)
+            .unwrap_or_else(|| self.text.len());
+        let word = &self.text[begin..end];
+        if word.is_empty() {
+            return None;
+        }
+        let mut best = None;
+        self.find_in_scope(&self.symbols, word, offset, 0, &mut best);
+        best.map(|(_, span)| span)
+            .or_else(|| self.find_anywhere(&self.symbols, word))
+    }
+
+    fn matches(&self, sym: &Symbol, word: &str) -> bool {
+        sym.name_span.source == self.source
+            && if self.vhdl {
+                sym.name.eq_ignore_ascii_case(word)
+            } else {
+                sym.name == word
+            }
+    }
+
+    fn find_in_scope(
+        &self,
+        symbols: &[Symbol],
+        word: &str,
+        offset: usize,
+        depth: usize,
+        best: &mut Option<(usize, Span)>,
+    ) {
+        for sym in symbols {
+            if self.matches(sym, word) && best.map(|(d, _)| depth > d).unwrap_or(true) {
+                *best = Some((depth, sym.name_span));
+            }
+            if sym.span.source == self.source
+                && sym.span.begin as usize <= offset
+                && offset <= sym.span.end as usize
+            {
+                self.find_in_scope(&sym.children, word, offset, depth + 1, best);
+            }
+        }
+    }
+
+    fn find_anywhere(&self, symbols: &[Symbol], word: &str) -> Option<Span> {
+        symbols.iter().find_map(|sym| {
+            if self.matches(sym, word) {
+                Some(sym.name_span)
+            } else {
+                self.find_anywhere(&sym.children, word)
+            }
+        })
+    }
+}
+
+/// Decode the parameters of a request, or produce the error to reply with.
+fn request_params<P: DeserializeOwned>(req: &Request) -> Result<P, (ErrorCode, String)> {
+    serde_json::from_value(req.params.clone()).map_err(|err| {
+        (
+            ErrorCode::InvalidParams,
+            format!("invalid parameters for `{}`: {}", req.method, err),
+        )
+    })
+}
+
+/// Decode the parameters of a notification, which is ignored if they are
+/// invalid.
+fn notification_params<P: DeserializeOwned>(not: Notification) -> Option<P> {
+    match serde_json::from_value(not.params) {
+        Ok(params) => Some(params),
+        Err(err) => {
+            warn!("Ignoring `{}` with invalid parameters: {}", not.method, err);
+            None
+        }
+    }
+}
+
+/// Map the kind of a declaration to the closest kind in the protocol.
+fn symbol_kind(kind: SymbolKind) -> lsp::SymbolKind {
+    match kind {
+        SymbolKind::Module | SymbolKind::Entity | SymbolKind::Architecture => {
+            lsp::SymbolKind::MODULE
+        }
+        SymbolKind::Interface | SymbolKind::Component => lsp::SymbolKind::INTERFACE,
+        SymbolKind::Package | SymbolKind::PackageBody | SymbolKind::Context => {
+            lsp::SymbolKind::PACKAGE
+        }
+        SymbolKind::Configuration => lsp::SymbolKind::NAMESPACE,
+        SymbolKind::Class => lsp::SymbolKind::CLASS,
+        SymbolKind::Subprogram => lsp::SymbolKind::FUNCTION,
+        SymbolKind::Type => lsp::SymbolKind::STRUCT,
+        SymbolKind::Parameter | SymbolKind::Constant => lsp::SymbolKind::CONSTANT,
+        SymbolKind::Port => lsp::SymbolKind::FIELD,
+        SymbolKind::Signal | SymbolKind::Variable => lsp::SymbolKind::VARIABLE,
+        SymbolKind::Variant => lsp::SymbolKind::ENUM_MEMBER,
+        SymbolKind::Instance => lsp::SymbolKind::OBJECT,
+        SymbolKind::Block => lsp::SymbolKind::NAMESPACE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a document with the given contents, under a name unique to the
+    /// test.
+    fn document(name: &str, text: &str) -> Document {
+        let source = get_source_manager().replace(name, text);
+        let (vhdl, symbols) = if name.ends_with(".vhd") {
+            let units = vhdl::syntax::parse(source).unwrap();
+            (true, symbols::collect(&Ast::Vhdl(units)))
+        } else {
+            let arena = svlog::ast::Arena::default();
+            let preproc = svlog::preproc::Preprocessor::new(source, &[], &[]);
+            let file = svlog::parser::parse(svlog::lexer::Lexer::new(preproc), &arena).unwrap();
+            (false, symbols::collect(&Ast::Svlog(file)))
+        };
+        Document {
+            text: text.to_string(),
+            source,
+            vhdl,
+            symbols,
+        }
+    }
+
+    /// Find the position of the `nth` occurrence of a string.
+    fn find(doc: &Document, needle: &str, nth: usize) -> lsp::Position {
+        let offset = doc.text.match_indices(needle).nth(nth).unwrap().0;
+        doc.position(offset)
+    }
+
+    #[test]
+    fn utf16_positions() {
+        let doc = document("lsp_utf16.sv", "// é𝄞\nmodule é;\r\nendmodule\n");
+        let positions = [
+            (0, (0, 0)),
+            (3, (0, 3)),
+            (5, (0, 4)),
+            (9, (0, 6)),
+            (10, (1, 0)),
+            (17, (1, 7)),
+            (21, (1, 10)),
+            (22, (2, 0)),
+        ];
+        for &(offset, (line, character)) in &positions {
+            let pos = lsp::Position::new(line, character);
+            assert_eq!(doc.position(offset), pos, "position of offset {}", offset);
+            assert_eq!(doc.offset(pos), offset, "offset of {:?}", pos);
+        }
+        // Positions past the end of a line clamp to the line break, and past
+        // the end of the document to its end.
+        assert_eq!(doc.offset(lsp::Position::new(0, 42)), 9);
+        assert_eq!(doc.offset(lsp::Position::new(9, 0)), doc.text.len());
+    }
+
+    #[test]
+    fn document_symbol_ranges() {
+        let doc = document("lsp_symbols.sv", "module 𝄞foo;\n  logic x;\nendmodule\n");
+        let symbols = doc.document_symbols(&doc.symbols);
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "𝄞foo");
+        assert_eq!(symbols[0].kind, lsp::SymbolKind::MODULE);
+        assert_eq!(
+            symbols[0].selection_range,
+            lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 12))
+        );
+        let children = symbols[0].children.as_ref().unwrap();
+        assert_eq!(children[0].name, "x");
+        assert_eq!(
+            children[0].selection_range,
+            lsp::Range::new(lsp::Position::new(1, 8), lsp::Position::new(1, 9))
+        );
+    }
+
+    #[test]
+    fn definition_prefers_innermost_scope() {
+        let doc = document(
+            "lsp_scopes.sv",
+            "module foo;
+               logic x;
+               function void f(int x);
+                 x = 0;
+               endfunction
+               assign x = 1;
+             endmodule
+             module bar;
+               foo u0 ();
+             endmodule",
+        );
+        let def = |needle, nth| doc.definition(find(&doc, needle, nth));
+        let outer = doc.symbols[0].children[0].name_span;
+        let inner = doc.symbols[0].children[1].children[0].name_span;
+        // The use inside the function refers to its argument, while the use
+        // in the module refers to the variable.
+        assert_eq!(def("x = 0", 0), Some(inner));
+        assert_eq!(def("x = 1", 0), Some(outer));
+        // A name not declared in the enclosing scopes falls back to any
+        // declaration in the document.
+        assert_eq!(def("foo u0", 0), Some(doc.symbols[0].name_span));
+        assert_eq!(def("();", 0), None);
+    }
+
+    #[test]
+    fn definition_ignores_vhdl_case() {
+        let doc = document(
+            "lsp_case.vhd",
+            "entity Foo is end entity;
+             architecture rtl of FOO is
+               signal s : bit;
+             begin
+               S <= '0';
+             end architecture;",
+        );
+        let def = |needle| doc.definition(find(&doc, needle, 0));
+        assert_eq!(def("FOO"), Some(doc.symbols[0].name_span));
+        assert_eq!(def("S <="), Some(doc.symbols[1].children[0].name_span));
+    }
+
+    #[test]
+    fn invalid_params_keep_server_running() {
+        let (server, client) = Connection::memory();
+        let thread = std::thread::spawn(move || serve(&Options::default(), &server));
+        let request = |id: i32, method: &str, params: serde_json::Value| {
+            let req = Request::new(id.into(), method.to_string(), params);
+            client.sender.send(Message::Request(req)).unwrap();
+            loop {
+                if let Message::Response(resp) = client.receiver.recv().unwrap() {
+                    return resp;
+                }
+            }
+        };
+        let notify = |method: &str, params: serde_json::Value| {
+            let not = Notification::new(method.to_string(), params);
+            client.sender.send(Message::Notification(not)).unwrap();
+        };
+        let symbols = |id| {
+            let params = serde_json::json!({ "textDocument": { "uri": "file:///lsp_params.sv" } });
+            let resp = request(id, lsp::request::DocumentSymbolRequest::METHOD, params);
+            resp.result.unwrap().as_array().unwrap().len()
+        };
+        let open = |version, text: &str| {
+            let params = serde_json::json!({ "textDocument": {
+                "uri": "file:///lsp_params.sv",
+                "languageId": "systemverilog",
+                "version": version,
+                "text": text,
+            }});
+            notify(lsp::notification::DidOpenTextDocument::METHOD, params);
+        };
+        request(0, "initialize", serde_json::json!({ "capabilities": {} }));
+        notify("initialized", serde_json::json!({}));
+
+        // Invalid parameters are rejected without terminating the server.
+        let resp = request(
+            1,
+            lsp::request::DocumentSymbolRequest::METHOD,
+            serde_json::json!({ "foo": 1 }),
+        );
+        assert_eq!(resp.error.unwrap().code, ErrorCode::InvalidParams as i32);
+        notify(
+            lsp::notification::DidOpenTextDocument::METHOD,
+            serde_json::json!(42),
+        );
+        open(1, "module foo; endmodule");
+        assert_eq!(symbols(2), 1);
+
+        // Contents which fail to parse keep the last symbols.
+        open(2, "module foo; endmodule module");
+        assert_eq!(symbols(3), 1);
+
+        request(4, "shutdown", serde_json::Value::Null);
+        notify("exit", serde_json::Value::Null);
+        thread.join().unwrap().unwrap();
+    }
+}
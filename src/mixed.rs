@@ -0,0 +1,449 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Mixed-language designs.
+//!
+//! SystemVerilog modules may instantiate VHDL entities. For every entity that
+//! is instantiated from SystemVerilog, this module derives an `extern module`
+//! declaration from the entity's generics and ports, such that the instance
+//! can be checked and emitted like any other module instance. The entity is
+//! emitted as an LLHD unit declaration of the same name, and the definition is
+//! left to the VHDL side. The VHDL types map to SystemVerilog as follows:
+//!
+//! | VHDL                                               | SystemVerilog       |
+//! |----------------------------------------------------|---------------------|
+//! | `std_logic`, `std_ulogic`                          | `logic`             |
+//! | `bit`, `boolean`                                   | `bit`               |
+//! | `integer`, `natural`, `positive`                   | `int`               |
+//! | `std_logic_vector(a downto b)`, `unsigned(..)`     | `logic [a:b]`       |
+//! | `signed(a downto b)`                               | `logic signed [a:b]`|
+//! | `bit_vector(a downto b)`                           | `bit [a:b]`         |
+//!
+//! Generics become parameters, and the bounds of vector ranges may refer to
+//! them through simple arithmetic expressions.
+
+use crate::common::errors::*;
+use crate::common::name::Name;
+use crate::common::source::{Span, Spanned, INVALID_SPAN};
+use crate::common::util::{HasDesc, HasSpan};
+use crate::common::Session;
+use crate::score::Ast;
+use crate::svlog::ast as sv;
+use crate::svlog::token::{Lit, Op};
+use crate::vhdl::syntax::ast as vhdl;
+use crate::vhdl::syntax::lexer::token::Literal;
+use std::collections::{HashMap, HashSet};
+
+/// Derive `extern module` declarations for the VHDL entities that are
+/// instantiated from SystemVerilog.
+///
+/// Returns `None` if no SystemVerilog module instantiates a VHDL entity.
+/// Entities whose interface cannot be mapped to SystemVerilog are reported as
+/// errors at the port or generic in question.
+pub fn foreign_modules<'a>(
+    sess: &Session,
+    asts: &[&Ast],
+) -> Result<Option<sv::SourceFile<'a>>, ()> {
+    // Find the modules defined and instantiated in SystemVerilog.
+    let mut defined = HashSet::new();
+    let mut insts = InstTargets::default();
+    for ast in asts {
+        if let Ast::Svlog(file) = ast {
+            for item in &file.items {
+                if let sv::ItemData::ModuleDecl(ref m) = item.data {
+                    defined.insert(m.name.value);
+                }
+            }
+            insts.collect(&file.items);
+        }
+    }
+
+    // Find the VHDL entities. VHDL names are case-insensitive.
+    let entities: HashMap<String, &vhdl::EntityDecl> = asts
+        .iter()
+        .flat_map(|ast| match ast {
+            Ast::Vhdl(units) => units.iter(),
+            _ => [].iter(),
+        })
+        .filter_map(|unit| match unit.data {
+            vhdl::DesignUnitData::EntityDecl(ref e) => Some(e),
+            _ => None,
+        })
+        .map(|e| (e.name.value.as_str().to_lowercase(), e))
+        .collect();
+
+    // Declare the entities which are instantiated but not defined in
+    // SystemVerilog.
+    let mut items = vec![];
+    let mut failed = false;
+    for (target, span) in insts.targets {
+        if defined.contains(&target) {
+            continue;
+        }
+        let entity = match entities.get(&target.as_str().to_lowercase()) {
+            Some(&e) => e,
+            None => continue,
+        };
+        let mapper = Mapper {
+            sess,
+            entity: entity.name.value,
+            inst_span: span,
+        };
+        match mapper.declare(entity, target) {
+            Ok(m) => items.push(sv::Item::new(entity.span, sv::ItemData::ModuleDecl(m))),
+            Err(()) => failed = true,
+        }
+    }
+    if failed {
+        return Err(());
+    }
+    if items.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(sv::SourceFile::new(
+        INVALID_SPAN,
+        sv::SourceFileData {
+            timeunits: sv::Timeunit {
+                unit: None,
+                prec: None,
+            },
+            items,
+        },
+    )))
+}
+
+/// A visitor that collects the names of instantiated modules.
+#[derive(Default)]
+pub(crate) struct InstTargets {
+    seen: HashSet<Name>,
+    pub(crate) targets: Vec<(Name, Span)>,
+}
+
+impl InstTargets {
+    /// Collect the instantiated modules in a list of items.
+    pub(crate) fn collect(&mut self, items: &[sv::Item]) {
+        for item in items {
+            match item.data {
+                sv::ItemData::ModuleDecl(ref m) => self.collect(&m.items),
+                sv::ItemData::InterfaceDecl(ref i) => self.collect(&i.items),
+                sv::ItemData::GenerateRegion(_, ref items) => self.collect(items),
+                sv::ItemData::GenerateFor(ref g) => self.collect(&g.block.items),
+                sv::ItemData::GenerateIf(ref g) => {
+                    self.collect(&g.main_block.items);
+                    if let Some(ref b) = g.else_block {
+                        self.collect(&b.items);
+                    }
+                }
+                sv::ItemData::Inst(ref inst) => {
+                    if self.seen.insert(inst.target.value) {
+                        self.targets.push((inst.target.value, inst.target.span));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// Maps the interface of a VHDL entity to SystemVerilog.
+struct Mapper<'s> {
+    sess: &'s Session,
+    /// The name of the entity being mapped.
+    entity: Name,
+    /// The location where the entity is instantiated.
+    inst_span: Span,
+}
+
+impl Mapper<'_> {
+    /// Emit an error about a part of the entity that cannot be mapped.
+    fn error(&self, diag: DiagBuilder2) {
+        self.sess.emit(
+            diag.add_note(format!(
+                "Needed to instantiate VHDL entity `{}` here:",
+                self.entity
+            ))
+            .span(self.inst_span),
+        );
+    }
+
+    /// Declare an entity as an extern module called `name`.
+    fn declare<'a>(&self, entity: &vhdl::EntityDecl, name: Name) -> Result<sv::Module<'a>, ()> {
+        let mut params = vec![];
+        let mut ports = vec![];
+        let mut failed = false;
+        for decl in &entity.decls {
+            let (kind, intfs) = match decl {
+                vhdl::DeclItem::PortgenClause(_, kind, intfs) => (kind.value, &intfs.value),
+                _ => continue,
+            };
+            for intf in intfs {
+                let decl = match intf {
+                    vhdl::IntfDecl::ObjDecl(decl) => decl,
+                    _ => {
+                        self.error(
+                            DiagBuilder2::error(format!(
+                                "{} cannot be mapped to SystemVerilog",
+                                intf.desc()
+                            ))
+                            .span(intf.span()),
+                        );
+                        failed = true;
+                        continue;
+                    }
+                };
+                let result = match kind {
+                    vhdl::PortgenKind::Generic => self.map_generic(decl).map(|x| params.push(x)),
+                    vhdl::PortgenKind::Port => self.map_port(decl).map(|x| ports.extend(x)),
+                };
+                failed |= result.is_err();
+            }
+        }
+        if failed {
+            return Err(());
+        }
+        Ok(sv::Module::new(
+            entity.span,
+            sv::ModuleData {
+                lifetime: sv::Lifetime::Static,
+                external: true,
+                foreign: true,
+                name: Spanned::new(name, entity.name.span),
+                imports: vec![],
+                param_list: !params.is_empty(),
+                params,
+                ports,
+                items: vec![],
+                doc: entity.doc,
+            },
+        ))
+    }
+
+    /// Map a generic to a parameter.
+    fn map_generic<'a>(&self, decl: &vhdl::IntfObjDecl) -> Result<sv::ParamDecl<'a>, ()> {
+        let ty = self.map_type(&decl.ty)?;
+        let expr = match decl.default {
+            Some(ref expr) => Some(self.map_expr(expr)?),
+            None => None,
+        };
+        let values = decl
+            .names
+            .iter()
+            .map(|name| {
+                sv::ParamValueDecl::new(
+                    name.span,
+                    sv::ParamValueDeclData {
+                        ty: ty.clone(),
+                        name: Spanned::new(name.name, name.span),
+                        dims: vec![],
+                        expr: expr.clone(),
+                    },
+                )
+            })
+            .collect();
+        Ok(sv::ParamDecl::new(
+            decl.span,
+            sv::ParamDeclData {
+                local: false,
+                kind: sv::ParamKind::Value(values),
+                doc: decl.doc,
+            },
+        ))
+    }
+
+    /// Map a port declaration to a list of ports.
+    fn map_port<'a>(&self, decl: &vhdl::IntfObjDecl) -> Result<Vec<sv::Port<'a>>, ()> {
+        let dir = match decl.mode {
+            None | Some(vhdl::IntfMode::In) => sv::PortDir::Input,
+            Some(vhdl::IntfMode::Out) | Some(vhdl::IntfMode::Buffer) => sv::PortDir::Output,
+            Some(vhdl::IntfMode::Inout) => sv::PortDir::Inout,
+            Some(vhdl::IntfMode::Linkage) => {
+                self.error(
+                    DiagBuilder2::error("linkage ports cannot be mapped to SystemVerilog")
+                        .span(decl.span),
+                );
+                return Err(());
+            }
+        };
+        let ty = self.map_type(&decl.ty)?;
+        Ok(decl
+            .names
+            .iter()
+            .map(|name| {
+                sv::Port::new(
+                    name.span,
+                    sv::PortData::Named {
+                        dir: Some(dir),
+                        kind: None,
+                        ty: ty.clone(),
+                        name: Spanned::new(name.name, name.span),
+                        dims: vec![],
+                        expr: None,
+                        doc: decl.doc,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Map a subtype indication to a type.
+    fn map_type<'a>(&self, ty: &vhdl::SubtypeInd) -> Result<sv::Type<'a>, ()> {
+        // Find the type mark, skipping over the library and package names.
+        let mut mark = match ty.name.primary.kind {
+            vhdl::PrimaryNameKind::Ident(n) => Some(n),
+            _ => None,
+        };
+        let mut constraint = &ty.name.parts[..];
+        while let Some((vhdl::NamePart::Select(name), rest)) = constraint.split_first() {
+            mark = match name.kind {
+                vhdl::PrimaryNameKind::Ident(n) => Some(n),
+                _ => None,
+            };
+            constraint = rest;
+        }
+        let mark = match mark {
+            Some(mark) if ty.res.is_none() => mark.as_str().to_lowercase(),
+            _ => {
+                self.unsupported_type(ty);
+                return Err(());
+            }
+        };
+
+        // Map the type mark.
+        let (kind, sign, vector) = match mark.as_str() {
+            "std_logic" | "std_ulogic" => (sv::LogicType, sv::TypeSign::None, false),
+            "bit" | "boolean" => (sv::BitType, sv::TypeSign::None, false),
+            "integer" | "natural" | "positive" => (sv::IntType, sv::TypeSign::None, false),
+            "std_logic_vector" | "std_ulogic_vector" | "unsigned" => {
+                (sv::LogicType, sv::TypeSign::None, true)
+            }
+            "signed" => (sv::LogicType, sv::TypeSign::Signed, true),
+            "bit_vector" => (sv::BitType, sv::TypeSign::None, true),
+            _ => {
+                self.unsupported_type(ty);
+                return Err(());
+            }
+        };
+
+        // Map the range constraint of vectors. Integer ranges do not affect
+        // the type.
+        let dims = match (vector, constraint) {
+            (true, [vhdl::NamePart::Call(elems)]) => match &elems.value[..] {
+                [vhdl::ParenElem { choices, expr, .. }] if choices.value.is_empty() => {
+                    match expr.data {
+                        vhdl::ExprData::BinaryExpr(
+                            Spanned {
+                                value: vhdl::BinaryOp::Dir(_),
+                                ..
+                            },
+                            ref lhs,
+                            ref rhs,
+                        ) => vec![sv::TypeDim::Range(self.map_expr(lhs)?, self.map_expr(rhs)?)],
+                        _ => {
+                            self.unsupported_type(ty);
+                            return Err(());
+                        }
+                    }
+                }
+                _ => {
+                    self.unsupported_type(ty);
+                    return Err(());
+                }
+            },
+            (true, []) => {
+                self.error(
+                    DiagBuilder2::error(format!(
+                        "unconstrained type `{}` cannot be mapped to SystemVerilog",
+                        ty.span.extract()
+                    ))
+                    .span(ty.span)
+                    .add_note(
+                        "Constrain the type with a range, like `std_logic_vector(7 downto 0)`",
+                    ),
+                );
+                return Err(());
+            }
+            (false, []) | (false, [vhdl::NamePart::Range(_)]) => vec![],
+            _ => {
+                self.unsupported_type(ty);
+                return Err(());
+            }
+        };
+
+        Ok(sv::Type::new(
+            ty.span,
+            sv::TypeData {
+                kind: sv::TypeKind::new(ty.span, kind),
+                sign,
+                dims,
+            },
+        ))
+    }
+
+    /// Report a type that cannot be mapped.
+    fn unsupported_type(&self, ty: &vhdl::SubtypeInd) {
+        self.error(
+            DiagBuilder2::error(format!(
+                "type `{}` cannot be mapped to SystemVerilog",
+                ty.span.extract()
+            ))
+            .span(ty.span)
+            .add_note(
+                "Supported are std_logic, std_ulogic, bit, boolean, integer, natural, positive, \
+                 and ranges of std_logic_vector, std_ulogic_vector, bit_vector, signed, and \
+                 unsigned",
+            ),
+        );
+    }
+
+    /// Map an expression in a generic default or range bound.
+    fn map_expr<'a>(&self, expr: &vhdl::Expr) -> Result<sv::Expr<'a>, ()> {
+        let data = match expr.data {
+            vhdl::ExprData::LitExpr(Literal::Abstract(None, int, None, None), None) => {
+                sv::ExprData::LiteralExpr(Lit::Number(int, None))
+            }
+            vhdl::ExprData::NameExpr(vhdl::CompoundName {
+                primary:
+                    vhdl::PrimaryName {
+                        kind: vhdl::PrimaryNameKind::Ident(name),
+                        span,
+                        ..
+                    },
+                ref parts,
+                ..
+            }) if parts.is_empty() => sv::ExprData::IdentExpr(Spanned::new(name, span)),
+            vhdl::ExprData::ParenExpr(ref elems)
+                if elems.value.len() == 1 && elems.value[0].choices.value.is_empty() =>
+            {
+                return self.map_expr(&elems.value[0].expr);
+            }
+            vhdl::ExprData::BinaryExpr(op, ref lhs, ref rhs) => {
+                let op = match op.value {
+                    vhdl::BinaryOp::Add => Op::Add,
+                    vhdl::BinaryOp::Sub => Op::Sub,
+                    vhdl::BinaryOp::Mul => Op::Mul,
+                    vhdl::BinaryOp::Div => Op::Div,
+                    _ => return self.unsupported_expr(expr),
+                };
+                sv::ExprData::BinaryExpr {
+                    op,
+                    lhs: Box::new(self.map_expr(lhs)?),
+                    rhs: Box::new(self.map_expr(rhs)?),
+                }
+            }
+            _ => return self.unsupported_expr(expr),
+        };
+        Ok(sv::Expr::new(expr.span, data))
+    }
+
+    /// Report an expression that cannot be mapped.
+    fn unsupported_expr<T>(&self, expr: &vhdl::Expr) -> Result<T, ()> {
+        self.error(
+            DiagBuilder2::error(format!(
+                "expression `{}` cannot be mapped to SystemVerilog",
+                expr.span.extract()
+            ))
+            .span(expr.span)
+            .add_note("Only integers, generics, and `+`, `-`, `*`, `/` are supported"),
+        );
+        Err(())
+    }
+}
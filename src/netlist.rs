@@ -0,0 +1,868 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Structural Verilog netlist emission.
+//!
+//! This module translates the entities of an LLHD module into plain
+//! Verilog-2005 modules. Signals become wires, or regs if they are driven by
+//! a register or a conditional drive, and instances become module instances.
+//! Processes cannot be expressed structurally and must be lowered to entities
+//! beforehand. Memories inferred from unpacked arrays are flattened into a
+//! single reg, whose write ports become indexed part-select assignments.
+
+use crate::errors::*;
+use crate::svlog::token::{find_keyword, Standard};
+use crate::Session;
+use llhd::ir::prelude::*;
+use llhd::ty::Type;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Emit the entities in an LLHD module as a Verilog netlist.
+///
+/// Instances are named after the names in `instance_names`, keyed by the
+/// location hint of their `inst` instruction, as provided by
+/// `CodeGenerator::instance_names`. Other instances are named `inst`.
+pub fn write_module(
+    sess: &Session,
+    module: &Module,
+    instance_names: &HashMap<usize, String>,
+) -> Result<String, ()> {
+    let mut output = String::new();
+    let mut failed = false;
+    for unit in module.units() {
+        if unit.is_entity() {
+            match UnitWriter::new(sess, module, unit, instance_names).emit() {
+                Ok(x) => output.push_str(&x),
+                Err(()) => failed = true,
+            }
+        }
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(output)
+    }
+}
+
+/// Lower processes into entities where possible.
+///
+/// This runs the sequence of LLHD passes which turns combinational processes
+/// into entities and sequential processes into entities with registers.
+pub fn lower_processes(module: &mut Module) {
+    use llhd::opt::{Pass, PassContext};
+    use llhd::pass::*;
+    let ctx = PassContext;
+    ConstFolding::run_on_module(&ctx, module);
+    VarToPhiPromotion::run_on_module(&ctx, module);
+    DeadCodeElim::run_on_module(&ctx, module);
+    GlobalCommonSubexprElim::run_on_module(&ctx, module);
+    for _ in 0..2 {
+        EarlyCodeMotion::run_on_module(&ctx, module);
+        TemporalCodeMotion::run_on_module(&ctx, module);
+    }
+    GlobalCommonSubexprElim::run_on_module(&ctx, module);
+    TemporalCodeMotion::run_on_module(&ctx, module);
+    ConstFolding::run_on_module(&ctx, module);
+    EarlyCodeMotion::run_on_module(&ctx, module);
+    GlobalCommonSubexprElim::run_on_module(&ctx, module);
+    InstSimplification::run_on_module(&ctx, module);
+    DeadCodeElim::run_on_module(&ctx, module);
+    ControlFlowSimplification::run_on_module(&ctx, module);
+    InstSimplification::run_on_module(&ctx, module);
+    DeadCodeElim::run_on_module(&ctx, module);
+    ProcessLowering::run_on_module(&ctx, module);
+    Desequentialization::run_on_module(&ctx, module);
+}
+
+/// Format a unit name as a Verilog module name.
+fn module_name(name: &UnitName) -> String {
+    let name = match name {
+        UnitName::Global(n) | UnitName::Local(n) => n.clone(),
+        UnitName::Anonymous(i) => format!("unit{}", i),
+    };
+    if is_identifier(&name) && !is_keyword(&name) {
+        name
+    } else {
+        format!("\\{} ", name)
+    }
+}
+
+/// Check whether a string is a simple Verilog identifier.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Format the range of a vector with a given width.
+fn range(width: usize) -> String {
+    if width == 1 {
+        String::new()
+    } else {
+        format!("[{}:0] ", width - 1)
+    }
+}
+
+/// Check whether a name is a Verilog-2005 keyword, which cannot be used as an
+/// identifier.
+fn is_keyword(name: &str) -> bool {
+    find_keyword(name).map_or(false, |kw| kw.standard() <= Standard::Ieee1364_2005)
+}
+
+/// A reference to a signal or a slice thereof.
+#[derive(Clone)]
+struct SigRef {
+    /// The name of the declared signal.
+    base: String,
+    /// The width of the declared signal.
+    base_width: usize,
+    /// The offset of the referenced bits.
+    offset: usize,
+    /// A dynamic offset of the referenced bits, added to `offset`.
+    index: Option<String>,
+    /// The type of the referenced signal.
+    ty: Type,
+}
+
+/// The Verilog equivalent of an LLHD value.
+#[derive(Clone)]
+enum Operand {
+    /// A simple identifier that may be indexed into.
+    Ident(String),
+    /// An arbitrary expression.
+    Expr(String),
+    /// A signal.
+    Sig(SigRef),
+    /// A time value, which has no equivalent in a netlist.
+    Time,
+}
+
+/// A drive of a signal.
+struct Drive {
+    lhs: SigRef,
+    rhs: String,
+    cond: Option<String>,
+}
+
+/// Emits a single entity as a Verilog module.
+struct UnitWriter<'a> {
+    sess: &'a Session,
+    module: &'a Module,
+    unit: Unit<'a>,
+    instance_names: &'a HashMap<usize, String>,
+    names: HashSet<String>,
+    operands: HashMap<Value, Operand>,
+    /// Signal declarations, with their width and initial value.
+    signals: Vec<(String, usize, Option<String>)>,
+    /// Wires holding intermediate values.
+    wires: Vec<(String, usize)>,
+    /// Continuous assignments to intermediate values.
+    assigns: Vec<(String, String)>,
+    drives: Vec<Drive>,
+    /// Statements of the module body, such as instances and always blocks.
+    body: Vec<String>,
+    /// The signals which must be declared as `reg`.
+    regs: HashSet<String>,
+    failed: bool,
+}
+
+impl<'a> UnitWriter<'a> {
+    fn new(
+        sess: &'a Session,
+        module: &'a Module,
+        unit: Unit<'a>,
+        instance_names: &'a HashMap<usize, String>,
+    ) -> Self {
+        UnitWriter {
+            sess,
+            module,
+            unit,
+            instance_names,
+            names: Default::default(),
+            operands: Default::default(),
+            signals: Default::default(),
+            wires: Default::default(),
+            assigns: Default::default(),
+            drives: Default::default(),
+            body: Default::default(),
+            regs: Default::default(),
+            failed: false,
+        }
+    }
+
+    /// Report an error for something that cannot be expressed as Verilog.
+    fn unsupported(&mut self, msg: impl Into<String>) {
+        self.sess.emit(DiagBuilder2::error(format!(
+            "{} in {} cannot be expressed as a Verilog netlist",
+            msg.into(),
+            self.unit.name()
+        )));
+        self.failed = true;
+    }
+
+    /// Pick a unique Verilog identifier for a value.
+    fn uniquify(&mut self, hint: Option<&str>) -> String {
+        let mut base: String = hint
+            .unwrap_or("")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            base.insert(0, '_');
+        }
+        if is_keyword(&base) {
+            base.push('_');
+        }
+        let mut index = 0;
+        let mut name = if hint.is_some() {
+            base.clone()
+        } else {
+            format!("{}{}", base, index)
+        };
+        while self.names.contains(&name) {
+            index += 1;
+            name = format!("{}{}", base, index);
+        }
+        self.names.insert(name.clone());
+        name
+    }
+
+    /// Determine the number of bits needed to represent a type.
+    ///
+    /// Verilog has no zero-width vectors, such that types without any bits are
+    /// unsupported. These and other unsupported types are treated as a single
+    /// bit after reporting them, such that the remaining instructions can
+    /// still be checked.
+    fn width(&mut self, ty: &Type) -> usize {
+        let width = if ty.is_int() {
+            ty.unwrap_int()
+        } else if ty.is_enum() {
+            let n = ty.unwrap_enum();
+            (usize::BITS - n.saturating_sub(1).leading_zeros()) as usize
+        } else if ty.is_array() {
+            let (len, elem) = ty.unwrap_array();
+            len * self.width(elem)
+        } else if ty.is_struct() {
+            ty.unwrap_struct().iter().map(|f| self.width(f)).sum()
+        } else if ty.is_signal() {
+            self.width(ty.unwrap_signal())
+        } else {
+            self.unsupported(format!("type `{}`", ty));
+            return 1;
+        };
+        if width == 0 {
+            self.unsupported(format!("zero-width type `{}`", ty));
+            return 1;
+        }
+        width
+    }
+
+    /// Determine the bit offset and type of a field or element.
+    fn field(&mut self, ty: &Type, index: usize) -> (usize, Type) {
+        if ty.is_array() {
+            let (_, elem) = ty.unwrap_array();
+            (index * self.width(elem), elem.clone())
+        } else if ty.is_struct() {
+            let fields = ty.unwrap_struct();
+            let offset = fields[..index].iter().map(|f| self.width(f)).sum();
+            (offset, fields[index].clone())
+        } else {
+            (index, llhd::int_ty(1))
+        }
+    }
+
+    /// Determine the bit width of the elements that make up a type.
+    fn element_width(&mut self, ty: &Type) -> usize {
+        if ty.is_array() {
+            let (_, elem) = ty.unwrap_array();
+            self.width(elem)
+        } else {
+            1
+        }
+    }
+
+    /// Map a value to a Verilog expression.
+    fn expr(&mut self, value: Value) -> String {
+        match self.operands.get(&value).cloned() {
+            Some(Operand::Ident(x)) | Some(Operand::Expr(x)) => x,
+            Some(Operand::Sig(sig)) => self.sig_expr(&sig),
+            Some(Operand::Time) => {
+                self.unsupported("use of a time value");
+                String::new()
+            }
+            None => {
+                self.unsupported(format!("use of undefined value {}", value));
+                String::new()
+            }
+        }
+    }
+
+    /// Map a value to a Verilog identifier, introducing a wire if needed.
+    fn ident(&mut self, value: Value) -> String {
+        if let Some(Operand::Ident(x)) = self.operands.get(&value) {
+            return x.clone();
+        }
+        let expr = self.expr(value);
+        let ty = self.unit.value_type(value);
+        let width = self.width(&ty);
+        let name = self.uniquify(None);
+        self.wires.push((name.clone(), width));
+        self.assigns.push((name.clone(), expr));
+        self.operands.insert(value, Operand::Ident(name.clone()));
+        name
+    }
+
+    /// Map a value to a signal reference.
+    fn sig(&mut self, value: Value) -> Option<SigRef> {
+        match self.operands.get(&value) {
+            Some(Operand::Sig(x)) => Some(x.clone()),
+            _ => {
+                self.unsupported(format!("use of {} as a signal", value));
+                None
+            }
+        }
+    }
+
+    /// Format a signal reference as a Verilog expression.
+    fn sig_expr(&mut self, sig: &SigRef) -> String {
+        let width = self.width(&sig.ty);
+        if sig.offset + width > sig.base_width {
+            self.unsupported(format!("access beyond the end of signal `{}`", sig.base));
+        }
+        if let Some(ref index) = sig.index {
+            if sig.offset == 0 {
+                format!("{}[{} +: {}]", sig.base, index, width)
+            } else {
+                format!("{}[{} + {} +: {}]", sig.base, index, sig.offset, width)
+            }
+        } else if sig.offset == 0 && width == sig.base_width {
+            sig.base.clone()
+        } else if width == 1 {
+            format!("{}[{}]", sig.base, sig.offset)
+        } else {
+            format!("{}[{}:{}]", sig.base, sig.offset + width - 1, sig.offset)
+        }
+    }
+
+    /// Get the constant integer value of an operand.
+    fn const_usize(&mut self, value: Value) -> Option<usize> {
+        use num::ToPrimitive;
+        self.unit
+            .get_const_int(value)
+            .and_then(|x| x.value.to_usize())
+    }
+
+    /// Define the result of an instruction as a wire with a given expression.
+    fn define(&mut self, inst: Inst, expr: String) {
+        let value = self.unit.inst_result(inst);
+        let ty = self.unit.value_type(value);
+        let width = self.width(&ty);
+        let name = self.uniquify(self.unit.get_name(value));
+        self.wires.push((name.clone(), width));
+        self.assigns.push((name.clone(), expr));
+        self.operands.insert(value, Operand::Ident(name));
+    }
+
+    /// Emit the module.
+    fn emit(mut self) -> Result<String, ()> {
+        let unit = self.unit;
+
+        // Declare the ports.
+        let mut ports = vec![];
+        for (index, arg) in unit.args().enumerate() {
+            let name = match unit.get_name(arg) {
+                Some(name) => self.uniquify(Some(name)),
+                None => self.uniquify(Some(&format!("arg{}", index))),
+            };
+            let ty = unit.value_type(arg);
+            let width = self.width(&ty);
+            let dir = if unit.input_args().any(|a| a == arg) {
+                "input"
+            } else {
+                "output"
+            };
+            ports.push((dir, name.clone(), width));
+            self.operands.insert(
+                arg,
+                Operand::Sig(SigRef {
+                    base: name,
+                    base_width: width,
+                    offset: 0,
+                    index: None,
+                    ty: ty.unwrap_signal().clone(),
+                }),
+            );
+        }
+
+        // Translate the instructions.
+        for inst in unit.all_insts() {
+            self.emit_inst(inst);
+        }
+
+        // Turn drives into continuous assignments, or always blocks if the
+        // signal is a reg.
+        for drive in std::mem::take(&mut self.drives) {
+            let lhs = self.sig_expr(&drive.lhs);
+            if self.regs.contains(&drive.lhs.base) {
+                let mut stmt = format!("always @* ");
+                if let Some(cond) = drive.cond {
+                    write!(stmt, "if ({}) ", cond).unwrap();
+                }
+                write!(stmt, "{} = {};", lhs, drive.rhs).unwrap();
+                self.body.push(stmt);
+            } else if drive.lhs.index.is_some() {
+                self.unsupported(format!("drive of an indexed part of `{}`", drive.lhs.base));
+            } else {
+                self.body.push(format!("assign {} = {};", lhs, drive.rhs));
+            }
+        }
+
+        if self.failed {
+            return Err(());
+        }
+
+        // Assemble the module.
+        let mut out = String::new();
+        write!(out, "module {}(", module_name(unit.name())).unwrap();
+        for (i, (dir, name, width)) in ports.iter().enumerate() {
+            let kind = if self.regs.contains(name) {
+                "reg"
+            } else {
+                "wire"
+            };
+            let sep = if i == 0 { "" } else { "," };
+            write!(
+                out,
+                "{}\n    {} {} {}{}",
+                sep,
+                dir,
+                kind,
+                range(*width),
+                name
+            )
+            .unwrap();
+        }
+        write!(out, "\n);\n").unwrap();
+        for (name, width, init) in &self.signals {
+            if self.regs.contains(name) {
+                write!(out, "    reg {}{}", range(*width), name).unwrap();
+                if let Some(init) = init {
+                    write!(out, " = {}", init).unwrap();
+                }
+                write!(out, ";\n").unwrap();
+            } else {
+                write!(out, "    wire {}{};\n", range(*width), name).unwrap();
+            }
+        }
+        for (name, width) in &self.wires {
+            write!(out, "    wire {}{};\n", range(*width), name).unwrap();
+        }
+        for (name, expr) in &self.assigns {
+            write!(out, "    assign {} = {};\n", name, expr).unwrap();
+        }
+        for stmt in &self.body {
+            write!(out, "    {}\n", stmt).unwrap();
+        }
+        write!(out, "endmodule\n\n").unwrap();
+        Ok(out)
+    }
+
+    /// Translate a single instruction.
+    fn emit_inst(&mut self, inst: Inst) {
+        let unit = self.unit;
+        let data = &unit[inst];
+        let args = data.args();
+        let result = unit.get_inst_result(inst);
+        let ty = unit.inst_type(inst);
+        match data.opcode() {
+            Opcode::ConstInt => {
+                let imm = data.get_const_int().unwrap();
+                self.operands.insert(
+                    result.unwrap(),
+                    Operand::Expr(format!("{}'d{}", imm.width, imm.value)),
+                );
+            }
+            Opcode::ConstTime => {
+                self.operands.insert(result.unwrap(), Operand::Time);
+            }
+            Opcode::Alias => {
+                if let Some(x) = self.operands.get(&args[0]).cloned() {
+                    self.operands.insert(result.unwrap(), x);
+                }
+            }
+            Opcode::ArrayUniform => {
+                let x = self.expr(args[0]);
+                let expr = format!("{{{}{{{}}}}}", data.imms()[0], x);
+                self.operands.insert(result.unwrap(), Operand::Expr(expr));
+            }
+            Opcode::Array | Opcode::Struct => {
+                let fields: Vec<_> = args.iter().rev().map(|&a| self.expr(a)).collect();
+                let expr = format!("{{{}}}", fields.join(", "));
+                self.operands.insert(result.unwrap(), Operand::Expr(expr));
+            }
+            Opcode::Not | Opcode::Neg => {
+                let op = if data.opcode() == Opcode::Not {
+                    "~"
+                } else {
+                    "-"
+                };
+                let expr = format!("{}{}", op, self.expr(args[0]));
+                self.define(inst, expr);
+            }
+            Opcode::Add
+            | Opcode::Sub
+            | Opcode::And
+            | Opcode::Or
+            | Opcode::Xor
+            | Opcode::Umul
+            | Opcode::Udiv
+            | Opcode::Umod
+            | Opcode::Urem
+            | Opcode::Eq
+            | Opcode::Neq
+            | Opcode::Ult
+            | Opcode::Ugt
+            | Opcode::Ule
+            | Opcode::Uge => {
+                let op = match data.opcode() {
+                    Opcode::Add => "+",
+                    Opcode::Sub => "-",
+                    Opcode::And => "&",
+                    Opcode::Or => "|",
+                    Opcode::Xor => "^",
+                    Opcode::Umul => "*",
+                    Opcode::Udiv => "/",
+                    Opcode::Umod | Opcode::Urem => "%",
+                    Opcode::Eq => "==",
+                    Opcode::Neq => "!=",
+                    Opcode::Ult => "<",
+                    Opcode::Ugt => ">",
+                    Opcode::Ule => "<=",
+                    _ => ">=",
+                };
+                let expr = format!("{} {} {}", self.expr(args[0]), op, self.expr(args[1]));
+                self.define(inst, expr);
+            }
+            Opcode::Smul
+            | Opcode::Sdiv
+            | Opcode::Srem
+            | Opcode::Slt
+            | Opcode::Sgt
+            | Opcode::Sle
+            | Opcode::Sge => {
+                let op = match data.opcode() {
+                    Opcode::Smul => "*",
+                    Opcode::Sdiv => "/",
+                    Opcode::Srem => "%",
+                    Opcode::Slt => "<",
+                    Opcode::Sgt => ">",
+                    Opcode::Sle => "<=",
+                    _ => ">=",
+                };
+                let expr = format!(
+                    "$signed({}) {} $signed({})",
+                    self.expr(args[0]),
+                    op,
+                    self.expr(args[1])
+                );
+                self.define(inst, expr);
+            }
+            Opcode::Smod => {
+                // The remainder takes the sign of the dividend, whereas the
+                // modulus takes the sign of the divisor.
+                let a = self.ident(args[0]);
+                let b = self.ident(args[1]);
+                let width = self.width(&ty);
+                let rem = self.uniquify(None);
+                self.wires.push((rem.clone(), width));
+                self.assigns
+                    .push((rem.clone(), format!("$signed({}) % $signed({})", a, b)));
+                let msb = width - 1;
+                let expr = format!(
+                    "({0} != 0 && {0}[{1}] != {2}[{1}]) ? {0} + {2} : {0}",
+                    rem, msb, b
+                );
+                self.define(inst, expr);
+            }
+            Opcode::Shl | Opcode::Shr if ty.is_signal() => {
+                let sig = match self.sig(args[0]) {
+                    Some(x) => x,
+                    None => return,
+                };
+                let unit_width = self.element_width(&sig.ty);
+                let sig = match self.const_usize(args[2]) {
+                    Some(0) => sig,
+                    Some(_) | None if data.opcode() == Opcode::Shl => {
+                        self.unsupported("left shift of a signal");
+                        return;
+                    }
+                    Some(amount) => SigRef {
+                        offset: sig.offset + amount * unit_width,
+                        ..sig
+                    },
+                    // Dynamic shifts of a signal select a part of it by index,
+                    // as is the case for writes to memories.
+                    None => {
+                        let amount = self.expr(args[2]);
+                        let amount = if unit_width == 1 {
+                            amount
+                        } else {
+                            format!("{} * {}", amount, unit_width)
+                        };
+                        let index = match sig.index {
+                            Some(index) => format!("{} + {}", index, amount),
+                            None => amount,
+                        };
+                        SigRef {
+                            index: Some(index),
+                            ..sig
+                        }
+                    }
+                };
+                self.operands.insert(result.unwrap(), Operand::Sig(sig));
+            }
+            Opcode::Shl | Opcode::Shr => {
+                // Shift the value and the hidden value as one, and pick the
+                // relevant half of the result.
+                let width = self.width(&ty);
+                let unit_width = self.element_width(&ty);
+                let a = self.expr(args[0]);
+                let hidden = self.expr(args[1]);
+                let amount = self.expr(args[2]);
+                let amount = if unit_width == 1 {
+                    amount
+                } else {
+                    format!("{} * {}", amount, unit_width)
+                };
+                let tmp = self.uniquify(None);
+                self.wires.push((tmp.clone(), 2 * width));
+                let expr = if data.opcode() == Opcode::Shl {
+                    self.assigns
+                        .push((tmp.clone(), format!("{{{}, {}}} << {}", a, hidden, amount)));
+                    format!("{}[{}:{}]", tmp, 2 * width - 1, width)
+                } else {
+                    self.assigns
+                        .push((tmp.clone(), format!("{{{}, {}}} >> {}", hidden, a, amount)));
+                    format!("{}[{}:0]", tmp, width - 1)
+                };
+                self.define(inst, expr);
+            }
+            Opcode::Mux => {
+                let width = self.width(&ty);
+                let array = self.ident(args[0]);
+                let sel = self.expr(args[1]);
+                self.define(inst, format!("{}[{} * {} +: {}]", array, sel, width, width));
+            }
+            Opcode::ExtField | Opcode::ExtSlice => {
+                let target_ty = unit.value_type(args[0]);
+                let (offset, _) = self.field(
+                    if target_ty.is_signal() {
+                        target_ty.unwrap_signal()
+                    } else {
+                        &target_ty
+                    },
+                    data.imms()[0],
+                );
+                if ty.is_signal() {
+                    let sig = match self.sig(args[0]) {
+                        Some(x) => x,
+                        None => return,
+                    };
+                    self.operands.insert(
+                        result.unwrap(),
+                        Operand::Sig(SigRef {
+                            offset: sig.offset + offset,
+                            ty: ty.unwrap_signal().clone(),
+                            ..sig
+                        }),
+                    );
+                } else {
+                    let width = self.width(&ty);
+                    let target = self.ident(args[0]);
+                    let expr = if width == 1 {
+                        format!("{}[{}]", target, offset)
+                    } else {
+                        format!("{}[{}:{}]", target, offset + width - 1, offset)
+                    };
+                    self.define(inst, expr);
+                }
+            }
+            Opcode::InsField | Opcode::InsSlice => {
+                let (offset, _) = self.field(&ty, data.imms()[0]);
+                let width = self.width(&ty);
+                let value_width = self.width(&unit.value_type(args[1]));
+                let target = self.ident(args[0]);
+                let mut parts = vec![];
+                if offset + value_width < width {
+                    parts.push(format!(
+                        "{}[{}:{}]",
+                        target,
+                        width - 1,
+                        offset + value_width
+                    ));
+                }
+                parts.push(self.expr(args[1]));
+                if offset > 0 {
+                    parts.push(format!("{}[{}:0]", target, offset - 1));
+                }
+                self.define(inst, format!("{{{}}}", parts.join(", ")));
+            }
+            Opcode::Sig => {
+                let value = result.unwrap();
+                let width = self.width(&ty);
+                let name = self.uniquify(unit.get_name(value));
+                let init = match self.operands.get(&args[0]) {
+                    Some(Operand::Expr(x)) | Some(Operand::Ident(x)) => Some(x.clone()),
+                    _ => None,
+                };
+                self.signals.push((name.clone(), width, init));
+                self.operands.insert(
+                    value,
+                    Operand::Sig(SigRef {
+                        base: name,
+                        base_width: width,
+                        offset: 0,
+                        index: None,
+                        ty: ty.unwrap_signal().clone(),
+                    }),
+                );
+            }
+            Opcode::Prb => {
+                if let Some(sig) = self.sig(args[0]) {
+                    let operand = if sig.offset == 0
+                        && sig.index.is_none()
+                        && self.width(&sig.ty) == sig.base_width
+                    {
+                        Operand::Ident(sig.base)
+                    } else {
+                        Operand::Expr(self.sig_expr(&sig))
+                    };
+                    self.operands.insert(result.unwrap(), operand);
+                }
+            }
+            Opcode::Drv | Opcode::DrvCond => {
+                let lhs = match self.sig(args[0]) {
+                    Some(x) => x,
+                    None => return,
+                };
+                let rhs = self.expr(args[1]);
+                let cond = if data.opcode() == Opcode::DrvCond {
+                    self.regs.insert(lhs.base.clone());
+                    Some(self.expr(args[3]))
+                } else {
+                    None
+                };
+                self.drives.push(Drive { lhs, rhs, cond });
+            }
+            Opcode::Con => {
+                if let (Some(a), Some(b)) = (self.sig(args[0]), self.sig(args[1])) {
+                    let a = self.sig_expr(&a);
+                    let b = self.sig_expr(&b);
+                    self.body.push(format!("assign {} = {};", a, b));
+                }
+            }
+            Opcode::Reg => self.emit_reg(inst),
+            Opcode::Inst => {
+                let ext = data.get_ext_unit().unwrap();
+                let name = &unit[ext].name;
+                let target = self.module.units().find(|u| u.name() == name);
+                if let Some(target) = target {
+                    if !target.is_entity() {
+                        self.unsupported(format!("instance of process {}", name));
+                        return;
+                    }
+                }
+                let conns: Vec<_> = args
+                    .iter()
+                    .map(|&a| match self.sig(a) {
+                        Some(sig) => self.sig_expr(&sig),
+                        None => String::new(),
+                    })
+                    .collect();
+                let inst_name = unit
+                    .location_hint(inst)
+                    .and_then(|hint| self.instance_names.get(&hint))
+                    .map(String::as_str)
+                    .unwrap_or("inst");
+                let inst_name = self.uniquify(Some(inst_name));
+                self.body.push(format!(
+                    "{} {}({});",
+                    module_name(name),
+                    inst_name,
+                    conns.join(", ")
+                ));
+            }
+            Opcode::Halt => (),
+            op => self.unsupported(format!("`{}` instruction", op)),
+        }
+    }
+
+    /// Translate a register instruction into an always block.
+    fn emit_reg(&mut self, inst: Inst) {
+        let unit = self.unit;
+        let data = &unit[inst];
+        let target = match self.sig(data.args()[0]) {
+            Some(x) => x,
+            None => return,
+        };
+        self.regs.insert(target.base.clone());
+        let lhs = self.sig_expr(&target);
+        let triggers: Vec<_> = data.triggers().collect();
+
+        // Assemble the sensitivity list. Level-sensitive triggers become
+        // asynchronous controls if there is also an edge-sensitive trigger.
+        let edges = triggers
+            .iter()
+            .filter(|t| !matches!(t.mode, RegMode::Low | RegMode::High))
+            .count();
+        if edges > 1 {
+            self.unsupported("register with multiple edge triggers");
+            return;
+        }
+        let mut sensitivity = vec![];
+        let mut branches = vec![];
+        let mut edge_branch = None;
+        for t in &triggers {
+            let trigger = self.expr(t.trigger);
+            let data = self.expr(t.data);
+            let gate = t.gate.map(|g| self.expr(g));
+            let (event, cond) = match t.mode {
+                RegMode::Low => (
+                    format!("negedge {}", trigger),
+                    Some(format!("!{}", trigger)),
+                ),
+                RegMode::High => (format!("posedge {}", trigger), Some(trigger.clone())),
+                RegMode::Rise => (format!("posedge {}", trigger), None),
+                RegMode::Fall => (format!("negedge {}", trigger), None),
+                RegMode::Both => (trigger.clone(), None),
+            };
+            sensitivity.push(event);
+            let cond = match (cond, gate) {
+                (Some(c), Some(g)) => Some(format!("{} && {}", c, g)),
+                (c, g) => c.or(g),
+            };
+            let assign = format!("{} <= {};", lhs, data);
+            if matches!(t.mode, RegMode::Low | RegMode::High) {
+                branches.push((cond, assign));
+            } else {
+                edge_branch = Some((cond, assign));
+            }
+        }
+        let mut stmt = if edges == 0 {
+            String::from("always @*")
+        } else {
+            format!("always @({})", sensitivity.join(" or "))
+        };
+        for (i, (cond, assign)) in branches.into_iter().chain(edge_branch).enumerate() {
+            let keyword = if i == 0 { " " } else { " else " };
+            match cond {
+                Some(cond) => write!(stmt, "{}if ({}) {}", keyword, cond, assign).unwrap(),
+                None => write!(stmt, "{}{}", keyword, assign).unwrap(),
+            }
+        }
+        self.body.push(stmt);
+    }
+}
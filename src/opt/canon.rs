@@ -0,0 +1,201 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Canonicalization of the generated LLHD code.
+//!
+//! The rewrites in this module bring instructions into a canonical form which
+//! the constant folding and common subexpression elimination passes of LLHD
+//! can pick up on. Instructions which become unused are left for dead code
+//! elimination to remove.
+
+use llhd::ir::{prelude::*, InstData};
+
+/// Canonicalize all units in a module.
+///
+/// Returns true if any changes were made.
+pub fn canonicalize(module: &mut Module) -> bool {
+    let mut modified = false;
+    for mut unit in module.units_mut() {
+        let insts: Vec<_> = unit.all_insts().collect();
+        for inst in insts {
+            modified |= canonicalize_inst(&mut unit, inst);
+        }
+    }
+    modified
+}
+
+/// Canonicalize a single instruction.
+fn canonicalize_inst(unit: &mut UnitBuilder, inst: Inst) -> bool {
+    let data = unit[inst].clone();
+    let ty = unit.inst_type(inst);
+    let replacement = match data {
+        // Arrays of identical elements become uniform arrays, such that
+        // signal initial values share the same form.
+        InstData::Aggregate {
+            opcode: Opcode::Array,
+            ref args,
+        } if args.len() > 1 && args.iter().all(|&a| same_value(unit, a, args[0])) => {
+            InstData::Array {
+                opcode: Opcode::ArrayUniform,
+                imms: [args.len()],
+                args: [args[0]],
+            }
+        }
+
+        // Move constants to the right-hand side of commutative operations.
+        InstData::Binary {
+            opcode,
+            args: [a, b],
+        } if is_commutative(opcode) && is_const(unit, a) && !is_const(unit, b) => {
+            InstData::Binary {
+                opcode,
+                args: [b, a],
+            }
+        }
+
+        // Group constants in associative operations, e.g. `(x + 1) + 2`
+        // becomes `x + (1 + 2)`, which constant folding then reduces.
+        InstData::Binary {
+            opcode,
+            args: [a, c2],
+        } if is_associative(opcode) && is_const(unit, c2) => match inner_binary(unit, a, opcode) {
+            Some([x, c1]) if is_const(unit, c1) && !is_const(unit, x) => {
+                unit.insert_before(inst);
+                let folded = unit.build_inst(
+                    InstData::Binary {
+                        opcode,
+                        args: [c1, c2],
+                    },
+                    ty.clone(),
+                );
+                let folded = unit.inst_result(folded);
+                InstData::Binary {
+                    opcode,
+                    args: [x, folded],
+                }
+            }
+            _ => return false,
+        },
+
+        // Fold constant shifts into subsequent field and slice extractions,
+        // e.g. `extf (shr x, h, 1), 0` becomes `extf x, 1`.
+        InstData::InsExt {
+            opcode: opcode @ Opcode::ExtField,
+            args: [target, unused],
+            imms: [index, len],
+        }
+        | InstData::InsExt {
+            opcode: opcode @ Opcode::ExtSlice,
+            args: [target, unused],
+            imms: [index, len],
+        } => {
+            let (base, amount) = match const_shr(unit, target) {
+                Some(x) => x,
+                None => return false,
+            };
+            let width = shift_len(unit, base);
+            let end = if opcode == Opcode::ExtField { 1 } else { len };
+            if index + amount + end > width {
+                return false;
+            }
+            InstData::InsExt {
+                opcode,
+                args: [base, unused],
+                imms: [index + amount, len],
+            }
+        }
+        _ => return false,
+    };
+    unit.insert_before(inst);
+    let new_inst = unit.build_inst(replacement, ty);
+    let old = unit.inst_result(inst);
+    let new = unit.inst_result(new_inst);
+    if let Some(name) = unit.get_name(old).map(String::from) {
+        unit.set_name(new, name);
+    }
+    if let Some(loc) = unit.location_hint(inst) {
+        unit.set_location_hint(new_inst, loc);
+    }
+    unit.replace_use(old, new);
+    unit.prune_if_unused(inst);
+    true
+}
+
+/// Check whether an opcode is commutative.
+fn is_commutative(opcode: Opcode) -> bool {
+    match opcode {
+        Opcode::Add
+        | Opcode::And
+        | Opcode::Or
+        | Opcode::Xor
+        | Opcode::Umul
+        | Opcode::Smul
+        | Opcode::Eq
+        | Opcode::Neq => true,
+        _ => false,
+    }
+}
+
+/// Check whether an opcode is associative.
+fn is_associative(opcode: Opcode) -> bool {
+    match opcode {
+        Opcode::Add | Opcode::And | Opcode::Or | Opcode::Xor | Opcode::Umul | Opcode::Smul => true,
+        _ => false,
+    }
+}
+
+/// Check whether a value is produced by a constant instruction.
+fn is_const(unit: &Unit, value: Value) -> bool {
+    unit.get_const(value).is_some()
+}
+
+/// Check whether two values are identical or equal constants.
+fn same_value(unit: &Unit, a: Value, b: Value) -> bool {
+    a == b
+        || match (unit.get_const(a), unit.get_const(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+}
+
+/// Get the arguments of the single-use binary instruction producing a value.
+fn inner_binary(unit: &Unit, value: Value, opcode: Opcode) -> Option<[Value; 2]> {
+    let inst = unit.get_value_inst(value)?;
+    match unit[inst] {
+        InstData::Binary { opcode: op, args } if op == opcode && unit.has_one_use(value) => {
+            Some(args)
+        }
+        _ => None,
+    }
+}
+
+/// Match a right shift by a constant amount.
+fn const_shr(unit: &Unit, value: Value) -> Option<(Value, usize)> {
+    use num::ToPrimitive;
+    let inst = unit.get_value_inst(value)?;
+    match unit[inst] {
+        InstData::Ternary {
+            opcode: Opcode::Shr,
+            args: [base, _, amount],
+        } => Some((base, unit.get_const_int(amount)?.value.to_usize()?)),
+        _ => None,
+    }
+}
+
+/// Determine the number of elements or bits a value can be shifted by.
+fn shift_len(unit: &Unit, value: Value) -> usize {
+    let ty = unit.value_type(value);
+    let ty = if ty.is_signal() {
+        ty.unwrap_signal()
+    } else if ty.is_pointer() {
+        ty.unwrap_pointer()
+    } else {
+        &ty
+    };
+    if ty.is_array() {
+        ty.unwrap_array().0
+    } else if ty.is_int() {
+        ty.unwrap_int()
+    } else {
+        0
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Dead signal and unused instance elimination.
+//!
+//! This module removes signals from entities which are never read, together
+//! with the drives onto them, and instances whose outputs only feed such
+//! signals. Removing an instance may render the signals connected to its
+//! inputs dead in turn, so the analysis is repeated until nothing changes.
+
+use llhd::ir::{prelude::*, InstData};
+use std::collections::HashSet;
+
+/// Something that was removed from the design.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removed {
+    /// The name of the entity the item was removed from.
+    pub unit: String,
+    /// What kind of item was removed.
+    pub kind: RemovedKind,
+    /// The name of the signal or instantiated unit.
+    pub name: String,
+}
+
+/// The kind of an item that was removed from the design.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovedKind {
+    /// A signal that was never read.
+    Signal,
+    /// An instance whose outputs were never read.
+    Instance,
+}
+
+impl std::fmt::Display for Removed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.kind {
+            RemovedKind::Signal => write!(f, "removed unused signal `{}`", self.name)?,
+            RemovedKind::Instance => write!(f, "removed unused instance of `{}`", self.name)?,
+        }
+        write!(f, " in `{}`", self.unit)
+    }
+}
+
+/// Remove dead signals and unused instances from all entities in a module.
+///
+/// Signals are dead if they are not probed, not connected to an instance
+/// input, and not used in any other way than being driven. Instances are
+/// unused if they have at least one output, and all of their outputs are
+/// dead signals. Entity ports are never removed. Returns a report of what has
+/// been removed, in the order in which it was removed.
+pub fn remove_dead(module: &mut Module) -> Vec<Removed> {
+    let mut report = vec![];
+    for mut unit in module.units_mut() {
+        if unit.is_entity() {
+            while remove_dead_in_unit(&mut unit, &mut report) {}
+        }
+    }
+    report
+}
+
+/// Run one round of dead signal and instance elimination on an entity.
+///
+/// Returns true if anything was removed.
+fn remove_dead_in_unit(unit: &mut UnitBuilder, report: &mut Vec<Removed>) -> bool {
+    let insts: Vec<Inst> = unit.all_insts().collect();
+
+    // Find the signals which are never read.
+    let mut read = HashSet::new();
+    for &inst in &insts {
+        let data = &unit[inst];
+        let reads: &[Value] = match data.opcode() {
+            Opcode::Sig | Opcode::Drv | Opcode::DrvCond | Opcode::Reg => &data.args()[1..],
+            Opcode::Inst => data.input_args(),
+            _ if is_projection(unit, inst) => &[],
+            _ => data.args(),
+        };
+        for &arg in reads {
+            read.extend(signal_roots(unit, arg));
+        }
+    }
+    let mut dead: HashSet<Value> = insts
+        .iter()
+        .filter(|&&inst| unit[inst].opcode() == Opcode::Sig)
+        .map(|&inst| unit.inst_result(inst))
+        .filter(|value| !read.contains(value))
+        .collect();
+
+    // Find the instances whose outputs all go to dead signals. Signals that
+    // are driven by instances which are kept cannot be removed either.
+    let unused_insts = loop {
+        let unused: HashSet<Inst> = insts
+            .iter()
+            .cloned()
+            .filter(|&inst| unit[inst].opcode() == Opcode::Inst)
+            .filter(|&inst| {
+                let outputs = unit[inst].output_args();
+                !outputs.is_empty() && outputs.iter().all(|&arg| is_dead(unit, &dead, arg))
+            })
+            .collect();
+        let kept: HashSet<Value> = insts
+            .iter()
+            .filter(|&&inst| unit[inst].opcode() == Opcode::Inst && !unused.contains(&inst))
+            .flat_map(|&inst| unit[inst].output_args().to_vec())
+            .flat_map(|arg| signal_roots(unit, arg))
+            .collect();
+        if kept.iter().all(|value| !dead.contains(value)) {
+            break unused;
+        }
+        dead.retain(|value| !kept.contains(value));
+    };
+    // Remove the instances and drives, then the signals and projections.
+    let unit_name = unit.name().to_string();
+    let mut modified = false;
+    for &inst in &insts {
+        let remove = match unit[inst].opcode() {
+            Opcode::Inst => unused_insts.contains(&inst),
+            Opcode::Drv | Opcode::DrvCond | Opcode::Reg => {
+                is_dead(unit, &dead, unit[inst].args()[0])
+            }
+            _ => false,
+        };
+        if !remove {
+            continue;
+        }
+        if unit[inst].opcode() == Opcode::Inst {
+            let ext = unit[inst].get_ext_unit().unwrap();
+            report.push(Removed {
+                unit: unit_name.clone(),
+                kind: RemovedKind::Instance,
+                name: unit[ext].name.to_string(),
+            });
+        }
+        unit.delete_inst(inst);
+        modified = true;
+    }
+    let mut removed_sigs = vec![];
+    for &inst in insts.iter().rev() {
+        if !unit.is_inst_inserted(inst) {
+            continue;
+        }
+        let result = match unit.get_inst_result(inst) {
+            Some(result) => result,
+            None => continue,
+        };
+        let remove = if unit[inst].opcode() == Opcode::Sig {
+            dead.contains(&result)
+        } else {
+            is_projection(unit, inst) && is_dead(unit, &dead, result)
+        };
+        if !remove || unit.has_uses(result) {
+            continue;
+        }
+        if unit[inst].opcode() == Opcode::Sig {
+            removed_sigs.push(Removed {
+                unit: unit_name.clone(),
+                kind: RemovedKind::Signal,
+                name: match unit.get_name(result) {
+                    Some(name) => format!("%{}", name),
+                    None => format!("{}", result),
+                },
+            });
+        }
+        unit.delete_inst(inst);
+        modified = true;
+    }
+    report.extend(removed_sigs.into_iter().rev());
+    modified
+}
+
+/// Check whether an instruction derives a signal from other signals.
+fn is_projection(unit: &Unit, inst: Inst) -> bool {
+    match unit[inst].opcode() {
+        Opcode::ExtField | Opcode::ExtSlice | Opcode::Shl | Opcode::Shr => {
+            unit.inst_type(inst).is_signal()
+        }
+        _ => false,
+    }
+}
+
+/// Check whether a signal value refers only to dead signals.
+fn is_dead(unit: &Unit, dead: &HashSet<Value>, value: Value) -> bool {
+    let roots = signal_roots(unit, value);
+    !roots.is_empty() && roots.iter().all(|root| dead.contains(root))
+}
+
+/// Determine the `sig` instructions a signal value is derived from.
+///
+/// Entity arguments and non-signal values have no roots.
+fn signal_roots(unit: &Unit, value: Value) -> Vec<Value> {
+    let mut roots = vec![];
+    let mut todo = vec![value];
+    while let Some(value) = todo.pop() {
+        if value.is_invalid() || !unit.value_type(value).is_signal() {
+            continue;
+        }
+        let inst = match unit.get_value_inst(value) {
+            Some(inst) => inst,
+            None => continue,
+        };
+        if unit[inst].opcode() == Opcode::Sig {
+            roots.push(value);
+        } else if is_projection(unit, inst) {
+            if let InstData::InsExt { args, .. } = unit[inst] {
+                todo.push(args[0]);
+            } else {
+                todo.extend(unit[inst].args().iter().cloned());
+            }
+        }
+    }
+    roots
+}
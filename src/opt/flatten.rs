@@ -0,0 +1,293 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Hierarchy flattening.
+//!
+//! This module implements inlining of instantiated entities into their
+//! parents, which some downstream LLHD consumers require.
+
+use llhd::ir::{prelude::*, ExtUnit, ExtUnitData, InstData};
+use llhd::ty::Type;
+use std::collections::{HashMap, HashSet};
+
+/// Inline instantiated entities into their parents.
+///
+/// Every round inlines one level of entity instances in all entities of the
+/// module. Flattening stops after `depth` rounds, or once no instances are
+/// left to inline if `depth` is `None`. Entities that are no longer
+/// instantiated afterwards are removed, except for the ones that were not
+/// instantiated to begin with. Returns the number of inlined instances.
+///
+/// The names of inlined values are prefixed with the hierarchical path of the
+/// instance they stem from, such as `i0.i1.x`. Instances are named after the
+/// names in `instance_names`, keyed by the location hint of their `inst`
+/// instruction, as provided by `CodeGenerator::instance_names`. Other
+/// instances are named after the instantiated entity.
+pub fn flatten(
+    module: &mut Module,
+    depth: Option<usize>,
+    instance_names: &HashMap<usize, String>,
+) -> usize {
+    let roots: Vec<UnitId> = {
+        let used = instantiated_names(module);
+        module
+            .entities()
+            .filter(|unit| !used.contains(unit.name()))
+            .map(|unit| unit.id())
+            .collect()
+    };
+
+    // The paths of the instances that have been copied into an entity by
+    // inlining its instances.
+    let mut paths = InstPaths::new();
+    let mut total = 0;
+    let mut round = 0;
+    while depth.map(|d| round < d).unwrap_or(true) {
+        let templates: HashMap<UnitName, Template> = module
+            .entities()
+            .map(|unit| {
+                let template = Template::new(unit, instance_names, &paths);
+                (unit.name().clone(), template)
+            })
+            .collect();
+        let mut count = 0;
+        for mut unit in module.units_mut() {
+            if !unit.is_entity() {
+                continue;
+            }
+            let unit_id = unit.id();
+            let insts: Vec<_> = unit
+                .all_insts()
+                .filter(|&inst| unit[inst].opcode() == Opcode::Inst)
+                .collect();
+            for inst in insts {
+                let ext = unit[inst].get_ext_unit().unwrap();
+                if let Some(template) = templates.get(&unit[ext].name) {
+                    debug!("Inlining {} into {}", template.name, unit.name());
+                    let path = paths.remove(&(unit_id, inst)).unwrap_or_else(|| {
+                        instance_name(&unit, inst, instance_names)
+                            .unwrap_or_else(|| template.name.clone())
+                    });
+                    for (new_inst, inner) in template.inline(&mut unit, inst, &path) {
+                        paths.insert((unit_id, new_inst), inner);
+                    }
+                    count += 1;
+                }
+            }
+        }
+        if count == 0 {
+            break;
+        }
+        total += count;
+        round += 1;
+    }
+
+    // Remove entities that are no longer instantiated.
+    let used = instantiated_names(module);
+    let unused: Vec<_> = module
+        .entities()
+        .filter(|unit| !used.contains(unit.name()) && !roots.contains(&unit.id()))
+        .map(|unit| unit.id())
+        .collect();
+    for unit in unused {
+        module.remove_unit(unit);
+    }
+    total
+}
+
+/// The hierarchical paths of instances, relative to the entity they are in.
+type InstPaths = HashMap<(UnitId, Inst), String>;
+
+/// Look up the name of an instance.
+fn instance_name(
+    unit: &Unit,
+    inst: Inst,
+    instance_names: &HashMap<usize, String>,
+) -> Option<String> {
+    unit.location_hint(inst)
+        .and_then(|hint| instance_names.get(&hint))
+        .cloned()
+}
+
+/// Format the name of a unit without its sigil.
+fn plain_name(name: &UnitName) -> String {
+    match name {
+        UnitName::Global(n) | UnitName::Local(n) => n.clone(),
+        UnitName::Anonymous(i) => format!("{}", i),
+    }
+}
+
+/// Collect the names of all units that are instantiated in a module.
+fn instantiated_names(module: &Module) -> HashSet<UnitName> {
+    let mut names = HashSet::new();
+    for unit in module.units() {
+        for inst in unit.all_insts() {
+            if unit[inst].opcode() == Opcode::Inst {
+                let ext = unit[inst].get_ext_unit().unwrap();
+                names.insert(unit[ext].name.clone());
+            }
+        }
+    }
+    names
+}
+
+/// A copy of an entity's body that can be inlined elsewhere.
+struct Template {
+    /// The name of the entity, used to name its instances if their name is
+    /// not known.
+    name: String,
+    /// The input and output arguments of the entity.
+    args: Vec<Value>,
+    /// The instructions in the entity, with their type, result, name, and
+    /// location hint. The name of an `inst` instruction is the path of the
+    /// instance within the entity.
+    insts: Vec<(InstData, Type, Option<Value>, Option<String>, Option<usize>)>,
+    /// The external units referenced by the instructions.
+    ext_units: HashMap<ExtUnit, ExtUnitData>,
+}
+
+impl Template {
+    /// Capture the body of an entity.
+    fn new(unit: Unit, instance_names: &HashMap<usize, String>, paths: &InstPaths) -> Self {
+        Template {
+            name: plain_name(unit.name()),
+            args: unit.args().collect(),
+            insts: unit
+                .all_insts()
+                .filter(|&inst| !unit[inst].opcode().is_terminator())
+                .map(|inst| {
+                    let result = unit.get_inst_result(inst);
+                    let name = if unit[inst].opcode() == Opcode::Inst {
+                        let ext = unit[inst].get_ext_unit().unwrap();
+                        paths
+                            .get(&(unit.id(), inst))
+                            .cloned()
+                            .or_else(|| instance_name(&unit, inst, instance_names))
+                            .or_else(|| Some(plain_name(&unit[ext].name)))
+                    } else {
+                        result.and_then(|v| unit.get_name(v)).map(String::from)
+                    };
+                    (
+                        unit[inst].clone(),
+                        unit.inst_type(inst),
+                        result,
+                        name,
+                        unit.location_hint(inst),
+                    )
+                })
+                .collect(),
+            ext_units: unit
+                .extern_units()
+                .map(|(ext, data)| (ext, data.clone()))
+                .collect(),
+        }
+    }
+
+    /// Replace an instance of this entity with a copy of its body.
+    ///
+    /// The names of the copied values are prefixed with `path`. Returns the
+    /// copied instances together with their path.
+    fn inline(&self, unit: &mut UnitBuilder, inst: Inst, path: &str) -> Vec<(Inst, String)> {
+        let mut values: HashMap<Value, Value> = self
+            .args
+            .iter()
+            .cloned()
+            .zip(unit[inst].args().iter().cloned())
+            .collect();
+        let mut placeholders = HashMap::new();
+        let mut exts = HashMap::new();
+        let mut insts = vec![];
+        unit.insert_before(inst);
+
+        for (data, ty, result, name, loc) in &self.insts {
+            // Map the arguments into the parent, using placeholders for values
+            // which have not been inlined yet. Invalid values mark unused
+            // operands, e.g. in `extf` or an ungated `reg`, and are kept.
+            let mut data = map_args(data, |arg| match values.get(&arg) {
+                Some(&v) => v,
+                None if arg.is_invalid() => arg,
+                None => *placeholders
+                    .entry(arg)
+                    .or_insert_with(|| unit.add_placeholder(self.value_type(arg))),
+            });
+            if let InstData::Call {
+                unit: ref mut ext, ..
+            } = data
+            {
+                *ext = *exts
+                    .entry(*ext)
+                    .or_insert_with(|| import_ext_unit(unit, &self.ext_units[ext]));
+            }
+            let new_inst = unit.build_inst(data, ty.clone());
+            if let Some(loc) = *loc {
+                unit.set_location_hint(new_inst, loc);
+            }
+            if unit[new_inst].opcode() == Opcode::Inst {
+                if let Some(name) = name {
+                    insts.push((new_inst, format!("{}.{}", path, name)));
+                }
+            }
+
+            // Hook up the result and resolve any placeholders.
+            if let Some(result) = *result {
+                let new_result = unit.inst_result(new_inst);
+                if let Some(name) = name {
+                    unit.set_name(new_result, format!("{}.{}", path, name));
+                }
+                if let Some(ph) = placeholders.remove(&result) {
+                    unit.replace_use(ph, new_result);
+                    unit.remove_placeholder(ph);
+                }
+                values.insert(result, new_result);
+            }
+        }
+        unit.delete_inst(inst);
+        insts
+    }
+
+    /// Determine the type of a value in the template.
+    fn value_type(&self, value: Value) -> Type {
+        self.insts
+            .iter()
+            .find(|&&(_, _, result, _, _)| result == Some(value))
+            .map(|(_, ty, _, _, _)| ty.clone())
+            .expect("value not defined in template")
+    }
+}
+
+/// Find or declare an external unit in a unit.
+fn import_ext_unit(unit: &mut UnitBuilder, data: &ExtUnitData) -> ExtUnit {
+    let existing = unit
+        .extern_units()
+        .find(|(_, d)| d.name == data.name && d.sig == data.sig)
+        .map(|(ext, _)| ext);
+    match existing {
+        Some(ext) => ext,
+        None => unit.add_extern(data.name.clone(), data.sig.clone()),
+    }
+}
+
+/// Apply a function to all value arguments of an instruction.
+fn map_args(data: &InstData, mut f: impl FnMut(Value) -> Value) -> InstData {
+    let mut data = data.clone();
+    match data {
+        InstData::Array { ref mut args, .. } | InstData::Unary { ref mut args, .. } => {
+            args.iter_mut().for_each(|a| *a = f(*a))
+        }
+        InstData::Branch { ref mut args, .. } => args.iter_mut().for_each(|a| *a = f(*a)),
+        InstData::Binary { ref mut args, .. } | InstData::InsExt { ref mut args, .. } => {
+            args.iter_mut().for_each(|a| *a = f(*a))
+        }
+        InstData::Ternary { ref mut args, .. } => args.iter_mut().for_each(|a| *a = f(*a)),
+        InstData::Quaternary { ref mut args, .. } => args.iter_mut().for_each(|a| *a = f(*a)),
+        InstData::Aggregate { ref mut args, .. }
+        | InstData::Phi { ref mut args, .. }
+        | InstData::Wait { ref mut args, .. }
+        | InstData::Call { ref mut args, .. }
+        | InstData::Reg { ref mut args, .. } => args.iter_mut().for_each(|a| *a = f(*a)),
+        InstData::ConstInt { .. }
+        | InstData::ConstTime { .. }
+        | InstData::Nullary { .. }
+        | InstData::Jump { .. } => (),
+    }
+    data
+}
@@ -0,0 +1,11 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Transformations on the generated LLHD code.
+
+mod canon;
+mod dead;
+mod flatten;
+
+pub use self::canon::*;
+pub use self::dead::*;
+pub use self::flatten::*;
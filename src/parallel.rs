@@ -0,0 +1,57 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Parallel processing.
+//!
+//! Distributes independent work items, such as the input files to be parsed,
+//! across a pool of threads.
+
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// Apply a function to each item on a pool of `jobs` threads.
+///
+/// Returns the results in the order of the items. Runs on the current thread
+/// if there is only one job or item. A panic on one of the threads is resumed
+/// on the current thread.
+pub fn map<T, R, F>(items: Vec<T>, jobs: usize, f: F) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let num_items = items.len();
+    let jobs = jobs.min(num_items);
+    if jobs <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let queue = Arc::new(Mutex::new(items.into_iter().enumerate()));
+    let f = Arc::new(f);
+    let (tx, rx) = mpsc::channel();
+    let threads: Vec<_> = (0..jobs)
+        .map(|_| {
+            let queue = queue.clone();
+            let f = f.clone();
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                let next = queue.lock().unwrap().next();
+                match next {
+                    Some((index, item)) => tx.send((index, f(item))).unwrap(),
+                    None => break,
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let mut results: Vec<Option<R>> = (0..num_items).map(|_| None).collect();
+    for (index, result) in rx {
+        results[index] = Some(result);
+    }
+    for thread in threads {
+        if let Err(e) = thread.join() {
+            std::panic::resume_unwind(e);
+        }
+    }
+    results.into_iter().map(Option::unwrap).collect()
+}
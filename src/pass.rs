@@ -0,0 +1,104 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Custom analysis passes.
+//!
+//! Downstream crates add their own checks to the compiler by implementing
+//! `AnalysisPass` and registering it before running the driver:
+//!
+//! ```ignore
+//! struct NoLatches;
+//!
+//! impl moore::pass::AnalysisPass for NoLatches {
+//!     fn name(&self) -> &str {
+//!         "no-latches"
+//!     }
+//!
+//!     fn run(&self, design: &moore::design::Design, diag: &dyn DiagEmitter) {
+//!         // ...
+//!     }
+//! }
+//!
+//! fn main() {
+//!     moore::pass::register(Box::new(NoLatches));
+//!     moore::driver::main();
+//! }
+//! ```
+//!
+//! The driver runs the registered passes in the order they were registered,
+//! after the design has been parsed and its instance hierarchy checked, and
+//! before it is elaborated. Compilation stops if a pass emits an error.
+
+use crate::common::errors::DiagEmitter;
+use crate::design::Design;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// An analysis of a design.
+pub trait AnalysisPass: Send + Sync {
+    /// The name of the pass, as listed by `--list-passes`.
+    fn name(&self) -> &str;
+
+    /// Analyze a design and emit diagnostics for the problems found.
+    fn run(&self, design: &Design, diag: &dyn DiagEmitter);
+}
+
+/// The registered passes.
+static PASSES: Lazy<Mutex<Vec<Box<dyn AnalysisPass>>>> = Lazy::new(Default::default);
+
+/// Register a pass to be run by the driver.
+pub fn register(pass: Box<dyn AnalysisPass>) {
+    PASSES.lock().unwrap().push(pass);
+}
+
+/// Get the names of the registered passes, in the order they run.
+pub fn names() -> Vec<String> {
+    PASSES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|pass| pass.name().to_string())
+        .collect()
+}
+
+/// Run the registered passes on a design.
+pub fn run(design: &Design, diag: &dyn DiagEmitter) {
+    for pass in PASSES.lock().unwrap().iter() {
+        debug!("Running pass {}", pass.name());
+        pass.run(design, diag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::errors::DiagBuilder2;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct CountRuns(Arc<AtomicUsize>);
+
+    impl AnalysisPass for CountRuns {
+        fn name(&self) -> &str {
+            "count-runs"
+        }
+
+        fn run(&self, _design: &Design, _diag: &dyn DiagEmitter) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    struct IgnoreDiags;
+
+    impl DiagEmitter for IgnoreDiags {
+        fn emit(&self, _diag: DiagBuilder2) {}
+    }
+
+    #[test]
+    fn registered_pass_runs() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        register(Box::new(CountRuns(runs.clone())));
+        assert!(names().iter().any(|name| name == "count-runs"));
+        run(&Design::default(), &IgnoreDiags);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+}
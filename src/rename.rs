@@ -0,0 +1,438 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Renaming of declarations.
+//!
+//! Renames a module, interface, or package, or a parameter, port, or signal
+//! declared in one, across a set of SystemVerilog files. The renaming is
+//! expressed as a list of edits to the source text, which replace each
+//! occurrence of the name and leave everything else as written.
+//!
+//! The declaration is looked up in the symbol index, using the same
+//! `unit.member` paths as the scopes of `--emit-tags`. Its occurrences are
+//! found by scanning the source text for identifiers, such that names in
+//! inactive `` `ifdef`` branches and macro definitions are renamed as well:
+//!
+//! - A unit is renamed where it is declared, in `endmodule : name` labels, in
+//!   instantiations and interface ports, and before `::`.
+//! - A member is renamed anywhere in the unit, in `unit::name` references, and
+//!   in named parameter assignments and port connections of instances of the
+//!   unit. Hierarchical references such as `top.a` and implicit `.*` port
+//!   connections are not renamed.
+//!
+//! The escaped identifier `\foo ` refers to the same declaration as `foo`, and
+//! is renamed as well. A new name which is not a simple identifier is written
+//! as an escaped identifier.
+
+use crate::common::errors::DiagBuilder2;
+use crate::common::source::{Source, Span};
+use crate::design::Design;
+use crate::score::Ast;
+use crate::svlog::token::find_keyword;
+use crate::symbols::{self, Symbol};
+
+/// A replacement of a span of source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+/// Determine the edits which rename a declaration in a set of files.
+///
+/// The declaration is given as `unit` or `unit.member`. Only SystemVerilog
+/// files are considered. Fails if the declaration does not exist, if the
+/// member is declared more than once in the unit, or if the new name is
+/// already declared in the same place. The edits are sorted by file and
+/// offset.
+pub fn rename<'a>(
+    files: &[(Source, &'a Ast<'a>)],
+    old: &str,
+    new: &str,
+) -> Result<Vec<Edit>, DiagBuilder2> {
+    let new = new.strip_prefix('\\').unwrap_or(new);
+    if new.is_empty() || new.contains(char::is_whitespace) {
+        return Err(DiagBuilder2::fatal(format!("invalid name `{}`", new)));
+    }
+    let files: Vec<_> = files
+        .iter()
+        .filter(|(_, ast)| match ast {
+            Ast::Svlog(_) => true,
+            Ast::Vhdl(_) => false,
+        })
+        .map(|&(source, ast)| (source, ast, symbols::collect(ast)))
+        .collect();
+    let units: Vec<_> = files.iter().flat_map(|(_, _, syms)| syms).collect();
+    let (unit_name, member) = match old.find('.') {
+        Some(i) => (&old[..i], Some(&old[i + 1..])),
+        None => (old, None),
+    };
+    let unit_name = unit_name.strip_prefix('\\').unwrap_or(unit_name);
+    let unit = match units.iter().find(|u| u.name == unit_name) {
+        Some(x) => x,
+        None => {
+            return Err(DiagBuilder2::fatal(format!(
+                "no module, interface, or package `{}`",
+                unit_name
+            )))
+        }
+    };
+
+    let mut edits = vec![];
+    match member {
+        None => {
+            if let Some(other) = units.iter().find(|u| u.name == new) {
+                return Err(DiagBuilder2::fatal(format!(
+                    "{} `{}` already exists",
+                    other.kind.as_str(),
+                    new
+                ))
+                .span(other.name_span));
+            }
+            for (source, _, _) in &files {
+                let tokens = scan(*source);
+                for (i, token) in tokens.iter().enumerate() {
+                    if token.ident() == Some(unit_name) && names_unit(&tokens, i, unit) {
+                        edits.push(replace(token.span(), token.escaped, new));
+                    }
+                }
+            }
+        }
+        Some(member) => {
+            let member = member.strip_prefix('\\').unwrap_or(member);
+            let decls = declarations(&unit.children, member);
+            match decls.len() {
+                0 => {
+                    return Err(DiagBuilder2::fatal(format!(
+                        "no `{}` in {} `{}`",
+                        member,
+                        unit.kind.as_str(),
+                        unit.name
+                    ))
+                    .span(unit.name_span))
+                }
+                1 => (),
+                _ => {
+                    return Err(DiagBuilder2::fatal(format!(
+                        "`{}` is declared more than once in {} `{}`",
+                        member,
+                        unit.kind.as_str(),
+                        unit.name
+                    ))
+                    .span(decls[1].name_span)
+                    .add_note("Nested declarations with the same name cannot be told apart"))
+                }
+            }
+            if let Some(other) = declarations(&unit.children, new).first() {
+                return Err(DiagBuilder2::fatal(format!(
+                    "`{}` is already declared in {} `{}`",
+                    new,
+                    unit.kind.as_str(),
+                    unit.name
+                ))
+                .span(other.name_span));
+            }
+            for (source, _, _) in &files {
+                let tokens = scan(*source);
+                for (i, token) in tokens.iter().enumerate() {
+                    if token.ident() != Some(member) {
+                        continue;
+                    }
+                    let in_unit = *source == unit.span.source
+                        && token.begin >= unit.span.begin as usize
+                        && token.end <= unit.span.end as usize;
+                    let scoped = i >= 2
+                        && tokens[i - 1].punct() == Some("::")
+                        && tokens[i - 2].ident() == Some(unit_name);
+                    if (in_unit && !after_dot(&tokens, i)) || scoped {
+                        edits.push(replace(token.span(), token.escaped, new));
+                    } else if in_unit && implicit_conn(&tokens, i) {
+                        let mut edit = replace(token.span(), token.escaped, new);
+                        edit.text = format!("{}({})", token.span().extract(), edit.text);
+                        edits.push(edit);
+                    }
+                }
+            }
+            let asts: Vec<_> = files.iter().map(|&(_, ast, _)| ast).collect();
+            rename_conns(
+                &crate::design::Design::new(&asts),
+                unit_name,
+                member,
+                new,
+                &mut edits,
+            );
+        }
+    }
+    edits.sort_by_key(|e| (e.span.source, e.span.begin));
+    edits.dedup();
+    Ok(edits)
+}
+
+/// Apply the edits of one file to its text.
+pub fn apply(text: &str, edits: &[Edit]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut offset = 0;
+    for edit in edits {
+        output.push_str(&text[offset..edit.span.begin as usize]);
+        output.push_str(&edit.text);
+        offset = edit.span.end as usize;
+    }
+    output.push_str(&text[offset..]);
+    output
+}
+
+/// Find the declarations of a name among some symbols and the symbols nested
+/// in them.
+fn declarations<'s>(symbols: &'s [Symbol], name: &str) -> Vec<&'s Symbol> {
+    let mut found = vec![];
+    for sym in symbols {
+        if sym.name == name {
+            found.push(sym);
+        }
+        found.extend(declarations(&sym.children, name));
+    }
+    found
+}
+
+/// Rename the named parameter assignments and port connections of the
+/// instances of a unit.
+///
+/// An implicit connection `.a` becomes `.b(a)`, since it still connects to
+/// `a` in the instantiating module.
+fn rename_conns(design: &Design, unit: &str, member: &str, new: &str, edits: &mut Vec<Edit>) {
+    let insts = design
+        .units
+        .iter()
+        .flat_map(|u| &u.instances)
+        .filter(|inst| &*inst.target.value.as_str() == unit);
+    for inst in insts {
+        for conn in inst.params.iter().chain(&inst.ports) {
+            let name = match conn.name {
+                Some(name) if &*name.value.as_str() == member => name,
+                _ => continue,
+            };
+            let escaped = name.span.extract().starts_with('\\');
+            let mut edit = replace(name.span, escaped, new);
+            let implicit = match conn.expr {
+                Some(ref expr) => expr.span == name.span,
+                None => false,
+            };
+            if implicit {
+                let space = if escaped { " " } else { "" };
+                edit.text = format!("{}({}{})", edit.text.trim_end(), name.span.extract(), space);
+            }
+            edits.push(edit);
+        }
+    }
+}
+
+/// Check whether an occurrence of a unit's name refers to the unit.
+fn names_unit(tokens: &[Token], index: usize, unit: &Symbol) -> bool {
+    let token = &tokens[index];
+    if unit.name_span.source == token.source && unit.name_span.begin as usize == token.begin {
+        return true;
+    }
+    if after_dot(tokens, index) {
+        return false;
+    }
+    let prev = |n: usize| index.checked_sub(n).map(|i| &tokens[i]);
+    let next = |n: usize| tokens.get(index + n);
+    let is_name = |t: Option<&Token>| match t.and_then(Token::ident) {
+        Some(name) => find_keyword(name).is_none() || t.unwrap().escaped,
+        None => false,
+    };
+
+    // A label after the end of a unit, as in `endmodule : top`.
+    if prev(1).and_then(Token::punct) == Some(":")
+        && prev(2)
+            .and_then(Token::ident)
+            .map(|kw| kw.starts_with("end") && find_keyword(kw).is_some())
+            .unwrap_or(false)
+    {
+        return true;
+    }
+
+    // A package or class scope, as in `pkg::a`, or a unit used as a type or
+    // instantiated, as in `top #(8) i_top (...)`, `bus_if bus`, or
+    // `bus_if.master bus`.
+    match next(1).and_then(Token::punct) {
+        Some("::") | Some("#") => return true,
+        Some(".") => return is_name(next(2)) && is_name(next(3)),
+        _ => (),
+    }
+    is_name(next(1))
+}
+
+/// Check whether a token is an implicit named port connection, as in
+/// `.a` in `leaf i_leaf (.a, .b(x))`.
+fn implicit_conn(tokens: &[Token], index: usize) -> bool {
+    let punct = |i: Option<usize>| i.and_then(|i| tokens.get(i)).and_then(Token::punct);
+    punct(index.checked_sub(1)) == Some(".")
+        && matches!(punct(index.checked_sub(2)), Some("(") | Some(","))
+        && matches!(punct(Some(index + 1)), Some(")") | Some(","))
+}
+
+/// Check whether a token follows a `.`, as in a hierarchical name, a struct
+/// field, or a named port connection, or a `::`.
+fn after_dot(tokens: &[Token], index: usize) -> bool {
+    match index.checked_sub(1).and_then(|i| tokens[i].punct()) {
+        Some(".") | Some("::") => true,
+        _ => false,
+    }
+}
+
+/// The edit which replaces an identifier with a new name.
+fn replace(span: Span, escaped: bool, new: &str) -> Edit {
+    let simple = new.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && new
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && find_keyword(new).is_none();
+    let text = if simple {
+        new.to_string()
+    } else if escaped {
+        // The whitespace which ends the escaped identifier is kept.
+        format!("\\{}", new)
+    } else {
+        format!("\\{} ", new)
+    };
+    Edit { span, text }
+}
+
+/// A token of source text, as far as it matters for renaming.
+#[derive(Debug)]
+struct Token {
+    source: Source,
+    begin: usize,
+    end: usize,
+    kind: TokenKind,
+    /// Whether this is an escaped identifier.
+    escaped: bool,
+}
+
+#[derive(Debug)]
+enum TokenKind {
+    /// An identifier or keyword, without the backslash of an escaped
+    /// identifier.
+    Ident(String),
+    /// One of `.`, `::`, `:`, `#`, `(`, `)`, or `,`.
+    Punct(&'static str),
+    /// Any other token, such as a number, a string, or a macro.
+    Other,
+}
+
+impl Token {
+    fn span(&self) -> Span {
+        Span::new(self.source, self.begin, self.end)
+    }
+
+    fn ident(&self) -> Option<&str> {
+        match self.kind {
+            TokenKind::Ident(ref name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn punct(&self) -> Option<&'static str> {
+        match self.kind {
+            TokenKind::Punct(p) => Some(p),
+            _ => None,
+        }
+    }
+}
+
+/// Split the text of a file into tokens, dropping whitespace and comments.
+///
+/// This is not a full lexer: compiler directives are kept as text, and
+/// numbers and strings are skipped without being checked.
+fn scan(source: Source) -> Vec<Token> {
+    let content = source.get_content();
+    let text = std::str::from_utf8(content.bytes()).unwrap_or("");
+    let bytes = text.as_bytes();
+    let is_ident = |c: u8| c.is_ascii_alphanumeric() || c == b'_' || c == b'$';
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let begin = i;
+        let c = bytes[i];
+        let mut escaped = false;
+        let kind = if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if text[i..].starts_with("//") {
+            i = text[i..].find('\n').map(|n| i + n).unwrap_or(bytes.len());
+            continue;
+        } else if text[i..].starts_with("/*") {
+            i = text[i + 2..]
+                .find("*/")
+                .map(|n| i + n + 4)
+                .unwrap_or(bytes.len());
+            continue;
+        } else if c == b'"' {
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' && bytes[i] != b'\n' {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i += 1;
+            TokenKind::Other
+        } else if c == b'\\' {
+            i += 1;
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            escaped = true;
+            TokenKind::Ident(text[begin + 1..i].to_string())
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && is_ident(bytes[i]) {
+                i += 1;
+            }
+            TokenKind::Ident(text[begin..i].to_string())
+        } else if c == b'`' || c == b'$' || c.is_ascii_digit() {
+            // Macros, system names, and numbers such as `12ns` or `1e3`.
+            i += 1;
+            while i < bytes.len() && (is_ident(bytes[i]) || bytes[i] == b'.') {
+                i += 1;
+            }
+            TokenKind::Other
+        } else if c == b'\'' {
+            // The base and digits of a number, as in `8'sh ff`.
+            i += 1;
+            if i < bytes.len() && (bytes[i] == b's' || bytes[i] == b'S') {
+                i += 1;
+            }
+            if i < bytes.len() && b"bBoOdDhH".contains(&bytes[i]) {
+                i += 1;
+                while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t') {
+                    i += 1;
+                }
+                while i < bytes.len()
+                    && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] == b'?')
+                {
+                    i += 1;
+                }
+            }
+            TokenKind::Other
+        } else if text[i..].starts_with("::") {
+            i += 2;
+            TokenKind::Punct("::")
+        } else {
+            i += text[i..].chars().next().unwrap().len_utf8();
+            match c {
+                b'.' => TokenKind::Punct("."),
+                b':' => TokenKind::Punct(":"),
+                b'#' => TokenKind::Punct("#"),
+                b'(' => TokenKind::Punct("("),
+                b')' => TokenKind::Punct(")"),
+                b',' => TokenKind::Punct(","),
+                _ => TokenKind::Other,
+            }
+        };
+        tokens.push(Token {
+            source,
+            begin,
+            end: i.min(bytes.len()),
+            kind,
+            escaped,
+        });
+    }
+    tokens
+}
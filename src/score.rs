@@ -6,6 +6,7 @@
 //! additional compilation steps are initiated. This enables on-demand
 //! compilation.
 
+use crate::common::arenas::ArenaStats;
 use crate::common::errors::*;
 use crate::common::name::Name;
 use crate::common::score::{GenericContext, NodeMaker, NodeRef, Result};
@@ -195,7 +196,15 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ScopeRef, &'ctx Defs>
                         Some(v) => v.iter(),
                         None => [].iter(),
                     };
-                    let svlog_defs = self.svlog.find_module(name.into());
+                    // Extern module declarations of VHDL entities do not
+                    // count as separate definitions.
+                    let svlog_defs = self.svlog.find_module(name.into()).filter(|&id| {
+                        vhdl_defs.len() == 0
+                            || match self.svlog.ast_for_id(id).as_all() {
+                                svlog_ast::AllNode::Module(m) => !m.external,
+                                _ => true,
+                            }
+                    });
                     let both_defs: Vec<Spanned<Def>> = vhdl_defs
                         .map(|d| Spanned::new(Def::Vhdl(d.value), d.span))
                         .chain(
@@ -243,11 +252,19 @@ impl Arenas {
             defs: Arena::new(),
         }
     }
+
+    /// Report the number of values and bytes allocated in each arena, grouped
+    /// by the representation they belong to.
+    pub fn stats(&self) -> Vec<(&'static str, Vec<ArenaStats>)> {
+        let mut stats = self.vhdl.stats();
+        stats.push(("score", vec![ArenaStats::of("defs", &self.defs)]));
+        stats
+    }
 }
 
 /// Roots for every AST that we support. During parsing, a list of these entries
 /// is generated that is then passed to the `ScoreBoard` as a reference.
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize)]
 pub enum Ast<'a> {
     Vhdl(Vec<vhdl_ast::DesignUnit>),
     Svlog(svlog_ast::SourceFile<'a>),
@@ -0,0 +1,1247 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Event-driven simulation of LLHD modules.
+//!
+//! This module interprets the units of an LLHD module directly, without any
+//! external tools. The design is elaborated into a flat set of signals and
+//! instances of entities and processes. Drives onto signals are scheduled in
+//! an event queue ordered by physical time, delta cycles, and epsilon steps.
+//! Whenever signals change, the entities reading them are re-evaluated as a
+//! whole, and processes waiting on them are resumed. The signal values at the
+//! end of each physical time step are recorded as a VCD waveform. Failed
+//! assertions are reported as errors located at the assertion.
+
+use crate::errors::*;
+use crate::source::Location;
+use crate::svlog::ASSERTION_FAILED;
+use crate::Session;
+use llhd::ir::{prelude::*, RegMode};
+use llhd::value::{ArrayValue, IntValue, StructValue, TimeValue, Value as Data};
+use num::{BigInt, BigRational, ToPrimitive, Zero};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+
+/// The maximum number of delta and epsilon steps at a single point in time.
+const MAX_STEPS: usize = 100_000;
+
+/// The maximum number of instructions a process may execute without
+/// suspending.
+const MAX_PROCESS_INSTS: usize = 10_000_000;
+
+/// Simulate a module and return the resulting VCD waveform.
+///
+/// Elaborates the unit named `top` and runs the simulation until no more
+/// events are pending, or until the physical time exceeds `until`. The scopes
+/// of instances in the waveform are named after `instance_names`, keyed by
+/// the location hint of the `inst` instruction, as produced by
+/// `CodeGenerator::instance_names`.
+pub fn simulate(
+    sess: &Session,
+    module: &Module,
+    instance_names: &HashMap<usize, String>,
+    top: &UnitName,
+    until: Option<&TimeValue>,
+) -> Result<String, ()> {
+    let top = match module.units().find(|unit| unit.name() == top) {
+        Some(unit) => unit,
+        None => {
+            sess.emit(DiagBuilder2::error(format!("no unit {} to simulate", top)));
+            return Err(());
+        }
+    };
+    let mut sim = Simulator {
+        sess,
+        module,
+        instance_names,
+        signals: vec![],
+        instances: vec![],
+        queue: BTreeMap::new(),
+        now: TimeValue::zero(),
+    };
+
+    // Create signals for the ports of the top unit and elaborate it.
+    let mut scope = Scope::new(scope_name(top.name()));
+    let mut args = vec![];
+    for arg in top.args() {
+        let ty = top.value_type(arg);
+        if !ty.is_signal() {
+            return sim.error(format!(
+                "argument {} of {} is not a signal",
+                arg,
+                top.name()
+            ));
+        }
+        let r = sim.add_signal(Data::zero(ty.unwrap_signal()));
+        if let Some(name) = top.get_name(arg) {
+            scope.vars.push((name.to_string(), r.clone()));
+        }
+        args.push(r);
+    }
+    sim.instantiate(top, args, &mut scope)?;
+
+    // Run the simulation.
+    let mut vcd = Vcd::new(&sim, &scope);
+    sim.run(until, &mut vcd)?;
+    Ok(vcd.output)
+}
+
+/// Parse a physical time such as `10ns` or `2.5us`.
+pub fn parse_time(text: &str) -> Option<TimeValue> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let exponent = match unit {
+        "s" => 0,
+        "ms" => 3,
+        "us" => 6,
+        "ns" => 9,
+        "ps" => 12,
+        "fs" => 15,
+        _ => return None,
+    };
+    let mut parts = number.splitn(2, '.');
+    let integer = parts.next().unwrap_or("");
+    let fraction = parts.next().unwrap_or("");
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let digits: BigInt = format!("{}{}", integer, fraction).parse().ok()?;
+    let scale = num::pow(BigInt::from(10), exponent + fraction.len());
+    Some(TimeValue::new(BigRational::new(digits, scale), 0, 0))
+}
+
+/// A part of a signal or variable that a signal or pointer refers to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Piece {
+    /// The index of the signal or variable.
+    target: usize,
+    /// The fields and elements selected, outermost first.
+    path: Vec<usize>,
+    /// The range of bits or elements selected in the innermost value.
+    slice: Option<(usize, usize)>,
+}
+
+/// A signal or pointer, as a concatenation of pieces, lowest first.
+type Ref = Vec<Piece>;
+
+/// A value computed by an instruction.
+#[derive(Debug, Clone)]
+enum Slot {
+    /// A plain value.
+    Data(Data),
+    /// A signal.
+    Signal(Ref),
+    /// A pointer to a variable.
+    Pointer(Ref),
+}
+
+/// Something scheduled to happen at a point in time.
+#[derive(Debug)]
+enum Event {
+    /// Drive a new value onto a part of a signal.
+    Drive(Piece, Data),
+    /// Resume a process, unless it has resumed since the event was scheduled.
+    Wake(usize, usize),
+}
+
+/// A signal in the elaborated design.
+struct Signal {
+    /// The current value.
+    value: Data,
+    /// The signal this one has been connected to, if any.
+    alias: Option<usize>,
+}
+
+/// An instance of an entity or process in the elaborated design.
+struct Instance {
+    /// The instantiated unit.
+    unit: UnitId,
+    /// The values of the unit's arguments, and for entities the signals and
+    /// instances created during elaboration.
+    bindings: HashMap<Value, Slot>,
+    /// The signals that wake up the instance when they change.
+    sensitivity: HashSet<usize>,
+    /// The state specific to entities or processes.
+    state: InstanceState,
+}
+
+/// The state of an entity or process instance.
+enum InstanceState {
+    Entity {
+        /// The previous trigger values of each register.
+        triggers: HashMap<Inst, Vec<Data>>,
+    },
+    Process {
+        /// The block where execution resumes.
+        block: Block,
+        /// The block execution came from, for `phi` instructions.
+        prev_block: Option<Block>,
+        /// The values computed so far.
+        values: HashMap<Value, Slot>,
+        /// The contents of the process' variables.
+        vars: Vec<Data>,
+        /// Incremented on every resumption to invalidate pending wake events.
+        generation: usize,
+        /// Whether the process has halted.
+        halted: bool,
+    },
+}
+
+/// The values and side effects of executing instructions.
+struct Env {
+    /// The values computed so far.
+    values: HashMap<Value, Slot>,
+    /// The contents of variables.
+    vars: Vec<Data>,
+    /// The signals that have been probed.
+    probed: HashSet<usize>,
+}
+
+/// A scope of signals in the VCD output.
+struct Scope {
+    name: String,
+    vars: Vec<(String, Ref)>,
+    children: Vec<Scope>,
+}
+
+impl Scope {
+    fn new(name: String) -> Self {
+        Scope {
+            name,
+            vars: vec![],
+            children: vec![],
+        }
+    }
+}
+
+/// The simulator state.
+struct Simulator<'a> {
+    sess: &'a Session,
+    module: &'a Module,
+    /// The names of instances, keyed by location hint.
+    instance_names: &'a HashMap<usize, String>,
+    signals: Vec<Signal>,
+    instances: Vec<Instance>,
+    queue: BTreeMap<TimeValue, Vec<Event>>,
+    now: TimeValue,
+}
+
+impl<'a> Simulator<'a> {
+    /// Report an error.
+    fn error<T>(&self, msg: impl Into<String>) -> Result<T, ()> {
+        self.sess.emit(DiagBuilder2::error(msg.into()));
+        Err(())
+    }
+
+    /// Create a new signal with an initial value.
+    fn add_signal(&mut self, value: Data) -> Ref {
+        self.signals.push(Signal { value, alias: None });
+        vec![Piece {
+            target: self.signals.len() - 1,
+            path: vec![],
+            slice: None,
+        }]
+    }
+
+    /// Determine the signal a signal has been connected to.
+    fn resolve(&self, mut id: usize) -> usize {
+        while let Some(alias) = self.signals[id].alias {
+            id = alias;
+        }
+        id
+    }
+
+    /// Elaborate a unit with the signals connected to its arguments.
+    fn instantiate(&mut self, unit: Unit, args: Vec<Ref>, scope: &mut Scope) -> Result<(), ()> {
+        let mut bindings = HashMap::new();
+        for (arg, r) in unit.args().zip(args) {
+            bindings.insert(arg, Slot::Signal(r));
+        }
+
+        // Processes only need their arguments to get started.
+        if unit.is_process() {
+            let entry = unit.entry();
+            self.instances.push(Instance {
+                unit: unit.id(),
+                bindings: bindings.clone(),
+                sensitivity: HashSet::new(),
+                state: InstanceState::Process {
+                    block: entry,
+                    prev_block: None,
+                    values: bindings,
+                    vars: vec![],
+                    generation: 0,
+                    halted: false,
+                },
+            });
+            return Ok(());
+        }
+        if !unit.is_entity() {
+            return self.error(format!("cannot simulate function {}", unit.name()));
+        }
+
+        // Create the signals and instances of the entity. Other instructions
+        // are evaluated along the way, since they may compute the initial
+        // values of signals.
+        let mut env = Env {
+            values: bindings.clone(),
+            vars: vec![],
+            probed: HashSet::new(),
+        };
+        for inst in unit.all_insts() {
+            let data = &unit[inst];
+            match data.opcode() {
+                Opcode::Sig => {
+                    let init = self.data(&env, data.args()[0])?.clone();
+                    let r = self.add_signal(init);
+                    let value = unit.inst_result(inst);
+                    if let Some(name) = unit.get_name(value) {
+                        scope.vars.push((name.to_string(), r.clone()));
+                    }
+                    env.values.insert(value, Slot::Signal(r.clone()));
+                    bindings.insert(value, Slot::Signal(r));
+                }
+                Opcode::Con => {
+                    let a = self.signal(&env, data.args()[0])?;
+                    let b = self.signal(&env, data.args()[1])?;
+                    match (a.as_slice(), b.as_slice()) {
+                        ([a], [b]) if a.path.is_empty() && a.slice.is_none() && *a == *b => (),
+                        ([a], [b])
+                            if a.path.is_empty()
+                                && a.slice.is_none()
+                                && b.path.is_empty()
+                                && b.slice.is_none() =>
+                        {
+                            let a = self.resolve(a.target);
+                            let b = self.resolve(b.target);
+                            if a != b {
+                                self.signals[b].alias = Some(a);
+                            }
+                        }
+                        _ => return self.error("cannot simulate `con` of parts of signals"),
+                    }
+                }
+                Opcode::Inst => {
+                    let ext = data.get_ext_unit().unwrap();
+                    let name = &unit[ext].name;
+                    let target = match self.module.units().find(|u| u.name() == name) {
+                        Some(target) => target,
+                        None => {
+                            return self
+                                .error(format!("cannot simulate {} without a definition", name))
+                        }
+                    };
+                    let mut args = vec![];
+                    for &arg in data.args() {
+                        args.push(self.signal(&env, arg)?);
+                    }
+                    if target.is_entity() {
+                        let name = match unit
+                            .location_hint(inst)
+                            .and_then(|hint| self.instance_names.get(&hint))
+                        {
+                            Some(name) => name.clone(),
+                            None => scope_name(target.name()),
+                        };
+                        let name = unique_scope_name(scope, name);
+                        let mut child = Scope::new(name);
+                        for (arg, r) in target.args().zip(args.iter()) {
+                            if let Some(name) = target.get_name(arg) {
+                                child.vars.push((name.to_string(), r.clone()));
+                            }
+                        }
+                        self.instantiate(target, args, &mut child)?;
+                        scope.children.push(child);
+                    } else {
+                        self.instantiate(target, args, scope)?;
+                    }
+                }
+                Opcode::Drv | Opcode::DrvCond | Opcode::Reg | Opcode::Halt => (),
+                _ => {
+                    let value = self.eval(unit, inst, &mut env)?;
+                    if let Some(value) = value {
+                        env.values.insert(unit.inst_result(inst), value);
+                    }
+                }
+            }
+        }
+        self.instances.push(Instance {
+            unit: unit.id(),
+            bindings,
+            sensitivity: HashSet::new(),
+            state: InstanceState::Entity {
+                triggers: HashMap::new(),
+            },
+        });
+        Ok(())
+    }
+
+    /// Run the simulation.
+    fn run(&mut self, until: Option<&TimeValue>, vcd: &mut Vcd) -> Result<(), ()> {
+        for index in 0..self.instances.len() {
+            self.execute(index)?;
+        }
+        let mut steps = 0;
+        while let Some(next) = self.queue.keys().next().cloned() {
+            if until.map(|until| next.time > until.time).unwrap_or(false) {
+                break;
+            }
+            if next.time != self.now.time {
+                vcd.dump(self);
+                steps = 0;
+            }
+            steps += 1;
+            if steps > MAX_STEPS {
+                return self.error(format!(
+                    "simulation does not settle at {}",
+                    format_time(&self.now)
+                ));
+            }
+            self.now = next;
+            let events = self.queue.remove(&self.now).unwrap();
+
+            // Apply the drives and determine which processes to resume.
+            let mut changed = HashSet::new();
+            let mut woken = vec![];
+            for event in events {
+                match event {
+                    Event::Drive(piece, value) => {
+                        let target = self.resolve(piece.target);
+                        let store = &mut self.signals[target].value;
+                        if read_piece(store, &piece) != value {
+                            write_piece(store, &piece, value);
+                            changed.insert(target);
+                        }
+                    }
+                    Event::Wake(index, gen) => match self.instances[index].state {
+                        InstanceState::Process { generation, .. } if generation == gen => {
+                            woken.push(index)
+                        }
+                        _ => (),
+                    },
+                }
+            }
+            for (index, instance) in self.instances.iter().enumerate() {
+                let halted = match instance.state {
+                    InstanceState::Process { halted, .. } => halted,
+                    _ => false,
+                };
+                if !halted && instance.sensitivity.iter().any(|s| changed.contains(s)) {
+                    woken.push(index);
+                }
+            }
+            woken.sort();
+            woken.dedup();
+            for index in woken {
+                self.execute(index)?;
+            }
+        }
+        vcd.dump(self);
+        Ok(())
+    }
+
+    /// Execute an entity or process instance.
+    fn execute(&mut self, index: usize) -> Result<(), ()> {
+        let unit = self.module.unit(self.instances[index].unit);
+        if unit.is_entity() {
+            self.execute_entity(index, unit)
+        } else {
+            self.execute_process(index, unit)
+        }
+    }
+
+    /// Evaluate all instructions of an entity.
+    fn execute_entity(&mut self, index: usize, unit: Unit) -> Result<(), ()> {
+        let mut env = Env {
+            values: self.instances[index].bindings.clone(),
+            vars: vec![],
+            probed: HashSet::new(),
+        };
+        for inst in unit.all_insts() {
+            let data = &unit[inst];
+            match data.opcode() {
+                Opcode::Sig | Opcode::Con | Opcode::Inst | Opcode::Halt => (),
+                Opcode::Reg => {
+                    let target = self.signal(&env, data.args()[0])?;
+                    let mut values = vec![];
+                    for trigger in data.triggers() {
+                        values.push(self.data(&env, trigger.trigger)?.clone());
+                    }
+                    let previous = match self.instances[index].state {
+                        InstanceState::Entity { ref mut triggers } => {
+                            triggers.insert(inst, values.clone())
+                        }
+                        _ => unreachable!(),
+                    };
+                    for (i, trigger) in data.triggers().enumerate() {
+                        let now = &values[i];
+                        let before = previous.as_ref().map(|p| &p[i]);
+                        let fire = match trigger.mode {
+                            RegMode::Low => now.is_zero(),
+                            RegMode::High => !now.is_zero(),
+                            RegMode::Rise => {
+                                before.map(Data::is_zero) == Some(true) && !now.is_zero()
+                            }
+                            RegMode::Fall => {
+                                before.map(Data::is_zero) == Some(false) && now.is_zero()
+                            }
+                            RegMode::Both => before.map(|b| b != now) == Some(true),
+                        };
+                        let gated = match trigger.gate {
+                            Some(gate) => self.data(&env, gate)?.is_zero(),
+                            None => false,
+                        };
+                        if fire && !gated {
+                            let value = self.data(&env, trigger.data)?.clone();
+                            let delay = TimeValue::new(Zero::zero(), 1, 0);
+                            self.drive(&target, value, &delay);
+                            break;
+                        }
+                    }
+                }
+                _ => self.execute_inst(unit, inst, &mut env)?,
+            }
+        }
+        let sensitivity = env
+            .probed
+            .into_iter()
+            .map(|s| self.resolve(s))
+            .collect::<Vec<_>>();
+        self.instances[index].sensitivity.extend(sensitivity);
+        Ok(())
+    }
+
+    /// Resume a process until it suspends or halts.
+    fn execute_process(&mut self, index: usize, unit: Unit) -> Result<(), ()> {
+        let (mut block, mut prev_block, values, vars) = match self.instances[index].state {
+            InstanceState::Process {
+                block,
+                prev_block,
+                ref mut values,
+                ref mut vars,
+                ref mut generation,
+                ..
+            } => {
+                *generation += 1;
+                (
+                    block,
+                    prev_block,
+                    std::mem::take(values),
+                    std::mem::take(vars),
+                )
+            }
+            _ => unreachable!(),
+        };
+        let mut env = Env {
+            values,
+            vars,
+            probed: HashSet::new(),
+        };
+        let mut count = 0;
+        let mut halted = false;
+        let mut sensitivity = HashSet::new();
+        'outer: loop {
+            for inst in unit.insts(block) {
+                count += 1;
+                if count > MAX_PROCESS_INSTS {
+                    return self.error(format!(
+                        "process {} does not suspend at {}",
+                        unit.name(),
+                        format_time(&self.now)
+                    ));
+                }
+                let data = &unit[inst];
+                let next = match data.opcode() {
+                    Opcode::Br => data.blocks()[0],
+                    Opcode::BrCond => {
+                        if self.data(&env, data.args()[0])?.is_zero() {
+                            data.blocks()[0]
+                        } else {
+                            data.blocks()[1]
+                        }
+                    }
+                    Opcode::Wait | Opcode::WaitTime => {
+                        let mut signals = data.args();
+                        if data.opcode() == Opcode::WaitTime {
+                            let delay = match self.data(&env, signals[0])? {
+                                Data::Time(t) => t.clone(),
+                                _ => {
+                                    return self
+                                        .error("cannot simulate `wait` for a non-time value")
+                                }
+                            };
+                            let generation = match self.instances[index].state {
+                                InstanceState::Process { generation, .. } => generation,
+                                _ => unreachable!(),
+                            };
+                            let time = advance(&self.now, &delay);
+                            self.queue
+                                .entry(time)
+                                .or_default()
+                                .push(Event::Wake(index, generation));
+                            signals = &signals[1..];
+                        }
+                        for &signal in signals {
+                            for piece in self.signal(&env, signal)? {
+                                sensitivity.insert(self.resolve(piece.target));
+                            }
+                        }
+                        prev_block = Some(block);
+                        block = data.blocks()[0];
+                        break 'outer;
+                    }
+                    Opcode::Halt => {
+                        halted = true;
+                        break 'outer;
+                    }
+                    _ => {
+                        self.execute_phi_or_inst(unit, inst, prev_block, &mut env)?;
+                        continue;
+                    }
+                };
+                prev_block = Some(block);
+                block = next;
+                continue 'outer;
+            }
+            return self.error(format!("block without terminator in {}", unit.name()));
+        }
+        let instance = &mut self.instances[index];
+        instance.sensitivity = sensitivity;
+        instance.state = match std::mem::replace(
+            &mut instance.state,
+            InstanceState::Entity {
+                triggers: HashMap::new(),
+            },
+        ) {
+            InstanceState::Process { generation, .. } => InstanceState::Process {
+                block,
+                prev_block,
+                values: env.values,
+                vars: env.vars,
+                generation,
+                halted,
+            },
+            _ => unreachable!(),
+        };
+        Ok(())
+    }
+
+    /// Execute an instruction in a process, including `phi` instructions.
+    fn execute_phi_or_inst(
+        &mut self,
+        unit: Unit,
+        inst: Inst,
+        prev_block: Option<Block>,
+        env: &mut Env,
+    ) -> Result<(), ()> {
+        let data = &unit[inst];
+        if data.opcode() != Opcode::Phi {
+            return self.execute_inst(unit, inst, env);
+        }
+        let index = data.blocks().iter().position(|&bb| Some(bb) == prev_block);
+        let value = match index {
+            Some(index) => match env.values.get(&data.args()[index]) {
+                Some(value) => value.clone(),
+                None => return self.error(format!("use of undefined value in {}", unit.name())),
+            },
+            None => return self.error(format!("`phi` without matching block in {}", unit.name())),
+        };
+        env.values.insert(unit.inst_result(inst), value);
+        Ok(())
+    }
+
+    /// Execute an instruction with side effects, or evaluate its value.
+    fn execute_inst(&mut self, unit: Unit, inst: Inst, env: &mut Env) -> Result<(), ()> {
+        let data = &unit[inst];
+        match data.opcode() {
+            Opcode::Drv | Opcode::DrvCond => {
+                if data.opcode() == Opcode::DrvCond && self.data(env, data.args()[3])?.is_zero() {
+                    return Ok(());
+                }
+                let target = self.signal(env, data.args()[0])?;
+                let value = self.data(env, data.args()[1])?.clone();
+                let delay = match self.data(env, data.args()[2])? {
+                    Data::Time(t) => t.clone(),
+                    _ => return self.error("cannot simulate `drv` with a non-time delay"),
+                };
+                self.drive(&target, value, &delay);
+            }
+            Opcode::Call if self.is_assertion_failed(unit, inst) => {
+                let mut diag = DiagBuilder2::error("assertion failed");
+                if let Some(loc) = unit.location_hint(inst).and_then(Location::from_hint) {
+                    diag = diag.span(loc);
+                }
+                self.sess
+                    .emit(diag.add_note(format!("at time {}", format_time(&self.now))));
+            }
+            Opcode::St => {
+                let target = self.pointer(env, data.args()[0])?;
+                let value = self.data(env, data.args()[1])?.clone();
+                let mut offset = 0;
+                for piece in target {
+                    let len = piece_len(&env.vars[piece.target], &piece);
+                    let part = if len == length(&value) {
+                        value.clone()
+                    } else {
+                        slice(&value, offset, len)
+                    };
+                    write_piece(&mut env.vars[piece.target], &piece, part);
+                    offset += len;
+                }
+            }
+            _ => {
+                if let Some(value) = self.eval(unit, inst, env)? {
+                    env.values.insert(unit.inst_result(inst), value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether an instruction reports a failed assertion.
+    fn is_assertion_failed(&self, unit: Unit, inst: Inst) -> bool {
+        match unit[inst].get_ext_unit() {
+            Some(ext) => unit[ext].name == UnitName::global(ASSERTION_FAILED),
+            None => false,
+        }
+    }
+
+    /// Schedule a drive of a value onto a signal.
+    fn drive(&mut self, target: &Ref, value: Data, delay: &TimeValue) {
+        let time = advance(&self.now, delay);
+        let mut offset = 0;
+        let mut events = vec![];
+        for piece in target {
+            let store = &self.signals[self.resolve(piece.target)].value;
+            let len = piece_len(store, piece);
+            let part = if target.len() == 1 {
+                value.clone()
+            } else {
+                slice(&value, offset, len)
+            };
+            events.push(Event::Drive(piece.clone(), part));
+            offset += len;
+        }
+        self.queue.entry(time).or_default().extend(events);
+    }
+
+    /// Evaluate an instruction without side effects on signals.
+    fn eval(&mut self, unit: Unit, inst: Inst, env: &mut Env) -> Result<Option<Slot>, ()> {
+        let data = &unit[inst];
+        let args = data.args();
+        let value = match data.opcode() {
+            Opcode::ConstInt => Data::Int(data.get_const_int().unwrap().clone()),
+            Opcode::ConstTime => Data::Time(data.get_const_time().unwrap().clone()),
+            Opcode::Alias => return self.slot(env, args[0]).map(Some),
+            Opcode::ArrayUniform => Data::Array(ArrayValue::new_uniform(
+                data.imms()[0],
+                self.data(env, args[0])?.clone(),
+            )),
+            Opcode::Array | Opcode::Struct => {
+                let mut values = vec![];
+                for &arg in args {
+                    values.push(self.data(env, arg)?.clone());
+                }
+                if data.opcode() == Opcode::Array {
+                    Data::Array(ArrayValue::new(values))
+                } else {
+                    Data::Struct(StructValue::new(values))
+                }
+            }
+            Opcode::Not | Opcode::Neg => {
+                let arg = self.int(env, args[0])?;
+                Data::Int(IntValue::unary_op(data.opcode(), arg))
+            }
+            Opcode::Add
+            | Opcode::Sub
+            | Opcode::And
+            | Opcode::Or
+            | Opcode::Xor
+            | Opcode::Smul
+            | Opcode::Sdiv
+            | Opcode::Smod
+            | Opcode::Srem
+            | Opcode::Umul
+            | Opcode::Udiv
+            | Opcode::Umod
+            | Opcode::Urem => {
+                let lhs = self.int(env, args[0])?;
+                let rhs = self.int(env, args[1])?;
+                if rhs.is_zero() && is_division(data.opcode()) {
+                    Data::Int(IntValue::zero(lhs.width))
+                } else {
+                    Data::Int(IntValue::binary_op(data.opcode(), lhs, rhs))
+                }
+            }
+            Opcode::Eq | Opcode::Neq => {
+                let lhs = self.data(env, args[0])?;
+                let rhs = self.data(env, args[1])?;
+                let equal = lhs == rhs;
+                Data::Int(IntValue::from_usize(
+                    1,
+                    (equal == (data.opcode() == Opcode::Eq)) as usize,
+                ))
+            }
+            Opcode::Slt
+            | Opcode::Sgt
+            | Opcode::Sle
+            | Opcode::Sge
+            | Opcode::Ult
+            | Opcode::Ugt
+            | Opcode::Ule
+            | Opcode::Uge => {
+                let lhs = self.int(env, args[0])?;
+                let rhs = self.int(env, args[1])?;
+                Data::Int(IntValue::compare_op(data.opcode(), lhs, rhs))
+            }
+            Opcode::Shl | Opcode::Shr => {
+                let amount = self
+                    .int(env, args[2])?
+                    .value
+                    .to_usize()
+                    .unwrap_or(usize::MAX);
+                let left = data.opcode() == Opcode::Shl;
+                match (self.slot(env, args[0])?, self.slot(env, args[1])?) {
+                    (Slot::Data(base), Slot::Data(hidden)) => {
+                        let (width, len) = (length(&base), length(&hidden));
+                        let amount = amount.min(len);
+                        if left {
+                            slice(&concat(hidden, base), len - amount, width)
+                        } else {
+                            slice(&concat(base, hidden), amount, width)
+                        }
+                    }
+                    (Slot::Signal(base), Slot::Signal(hidden)) => {
+                        let r = self.shift_ref(&base, &hidden, amount, left, None);
+                        return Ok(Some(Slot::Signal(r)));
+                    }
+                    (Slot::Pointer(base), Slot::Pointer(hidden)) => {
+                        let r = self.shift_ref(&base, &hidden, amount, left, Some(&env.vars));
+                        return Ok(Some(Slot::Pointer(r)));
+                    }
+                    _ => return self.error("cannot simulate shift of mixed values"),
+                }
+            }
+            Opcode::Mux => {
+                let choices = match self.data(env, args[0])? {
+                    Data::Array(a) => a,
+                    _ => return self.error("cannot simulate `mux` of a non-array value"),
+                };
+                let sel = self
+                    .int(env, args[1])?
+                    .value
+                    .to_usize()
+                    .unwrap_or(usize::MAX);
+                choices.0[sel.min(choices.0.len() - 1)].clone()
+            }
+            Opcode::InsField | Opcode::InsSlice => {
+                let mut target = self.data(env, args[0])?.clone();
+                let value = self.data(env, args[1])?.clone();
+                let imms = data.imms();
+                if data.opcode() == Opcode::InsField {
+                    *child_mut(&mut target, imms[0]) = value;
+                } else {
+                    insert_slice(&mut target, imms[0], imms[1], &value);
+                }
+                target
+            }
+            Opcode::ExtField | Opcode::ExtSlice => {
+                let imms = data.imms();
+                let field = data.opcode() == Opcode::ExtField;
+                match self.slot(env, args[0])? {
+                    Slot::Data(value) if field => child(&value, imms[0]).clone(),
+                    Slot::Data(value) => slice(&value, imms[0], imms[1]),
+                    Slot::Signal(r) => {
+                        let r = self.project(&r, imms, field, None);
+                        return Ok(Some(Slot::Signal(r)));
+                    }
+                    Slot::Pointer(r) => {
+                        let r = self.project(&r, imms, field, Some(&env.vars));
+                        return Ok(Some(Slot::Pointer(r)));
+                    }
+                }
+            }
+            Opcode::Prb => {
+                let r = self.signal(env, args[0])?;
+                env.probed.extend(r.iter().map(|piece| piece.target));
+                self.read(&r, None)
+            }
+            Opcode::Var => {
+                let init = self.data(env, args[0])?.clone();
+                env.vars.push(init);
+                return Ok(Some(Slot::Pointer(vec![Piece {
+                    target: env.vars.len() - 1,
+                    path: vec![],
+                    slice: None,
+                }])));
+            }
+            Opcode::Ld => {
+                let r = self.pointer(env, args[0])?;
+                self.read(&r, Some(&env.vars))
+            }
+            op => {
+                return self.error(format!(
+                    "cannot simulate `{}` instruction in {}",
+                    op,
+                    unit.name()
+                ))
+            }
+        };
+        Ok(Some(Slot::Data(value)))
+    }
+
+    /// Look up the value of an instruction argument.
+    fn slot(&self, env: &Env, value: Value) -> Result<Slot, ()> {
+        match env.values.get(&value) {
+            Some(slot) => Ok(slot.clone()),
+            None => self.error(format!("use of undefined value {}", value)),
+        }
+    }
+
+    /// Look up an instruction argument that is a plain value.
+    fn data<'e>(&self, env: &'e Env, value: Value) -> Result<&'e Data, ()> {
+        match env.values.get(&value) {
+            Some(Slot::Data(data)) => Ok(data),
+            _ => self.error(format!("use of {} as a value", value)),
+        }
+    }
+
+    /// Look up an instruction argument that is an integer.
+    fn int<'e>(&self, env: &'e Env, value: Value) -> Result<&'e IntValue, ()> {
+        match self.data(env, value)? {
+            Data::Int(v) => Ok(v),
+            _ => self.error(format!("use of {} as an integer", value)),
+        }
+    }
+
+    /// Look up an instruction argument that is a signal.
+    fn signal(&self, env: &Env, value: Value) -> Result<Ref, ()> {
+        match env.values.get(&value) {
+            Some(Slot::Signal(r)) => Ok(r.clone()),
+            _ => self.error(format!("use of {} as a signal", value)),
+        }
+    }
+
+    /// Look up an instruction argument that is a pointer.
+    fn pointer(&self, env: &Env, value: Value) -> Result<Ref, ()> {
+        match env.values.get(&value) {
+            Some(Slot::Pointer(r)) => Ok(r.clone()),
+            _ => self.error(format!("use of {} as a pointer", value)),
+        }
+    }
+
+    /// Get the value a piece of a signal or variable is stored in.
+    fn store<'s>(&'s self, piece: &Piece, vars: Option<&'s Vec<Data>>) -> &'s Data {
+        match vars {
+            Some(vars) => &vars[piece.target],
+            None => &self.signals[self.resolve(piece.target)].value,
+        }
+    }
+
+    /// Read the current value of a signal or pointer.
+    fn read(&self, r: &Ref, vars: Option<&Vec<Data>>) -> Data {
+        let mut parts = r
+            .iter()
+            .map(|piece| read_piece(self.store(piece, vars), piece));
+        let first = parts.next().expect("empty signal");
+        parts.fold(first, concat)
+    }
+
+    /// Select a field or slice of a signal or pointer.
+    fn project(&self, r: &Ref, imms: &[usize], field: bool, vars: Option<&Vec<Data>>) -> Ref {
+        let (off, len) = if field {
+            (imms[0], 1)
+        } else {
+            (imms[0], imms[1])
+        };
+        let mut result = vec![];
+        let mut base = 0;
+        for piece in r {
+            let n = piece_len(self.store(piece, vars), piece);
+            let start = off.max(base);
+            let end = (off + len).min(base + n);
+            if start < end {
+                let mut piece = piece.clone();
+                let (poff, _) = piece.slice.unwrap_or((0, n));
+                if field {
+                    piece.slice = None;
+                    piece.path.push(poff + start - base);
+                } else {
+                    piece.slice = Some((poff + start - base, end - start));
+                }
+                result.push(piece);
+            }
+            base += n;
+        }
+        result
+    }
+
+    /// Shift a signal or pointer, shifting in parts of a hidden one.
+    fn shift_ref(
+        &self,
+        base: &Ref,
+        hidden: &Ref,
+        amount: usize,
+        left: bool,
+        vars: Option<&Vec<Data>>,
+    ) -> Ref {
+        let width: usize = base
+            .iter()
+            .map(|piece| piece_len(self.store(piece, vars), piece))
+            .sum();
+        let len: usize = hidden
+            .iter()
+            .map(|piece| piece_len(self.store(piece, vars), piece))
+            .sum();
+        let amount = amount.min(len);
+        if left {
+            let cat: Ref = hidden.iter().chain(base.iter()).cloned().collect();
+            self.project(&cat, &[len - amount, width], false, vars)
+        } else {
+            let cat: Ref = base.iter().chain(hidden.iter()).cloned().collect();
+            self.project(&cat, &[amount, width], false, vars)
+        }
+    }
+}
+
+/// Compute the point in time a delay after another.
+fn advance(now: &TimeValue, delay: &TimeValue) -> TimeValue {
+    if !delay.time.is_zero() {
+        TimeValue::new(&now.time + &delay.time, delay.delta, delay.epsilon)
+    } else if delay.delta > 0 {
+        TimeValue::new(now.time.clone(), now.delta + delay.delta, delay.epsilon)
+    } else {
+        TimeValue::new(
+            now.time.clone(),
+            now.delta,
+            now.epsilon + delay.epsilon.max(1),
+        )
+    }
+}
+
+/// Check whether an opcode divides by its second operand.
+fn is_division(op: Opcode) -> bool {
+    match op {
+        Opcode::Sdiv | Opcode::Smod | Opcode::Srem | Opcode::Udiv | Opcode::Umod | Opcode::Urem => {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Get the number of bits, elements, or fields in a value.
+fn length(value: &Data) -> usize {
+    match value {
+        Data::Int(v) => v.width,
+        Data::Array(v) => v.0.len(),
+        Data::Struct(v) => v.0.len(),
+        Data::Time(_) | Data::Void => 1,
+    }
+}
+
+/// Get a field or element of a value.
+fn child(value: &Data, index: usize) -> &Data {
+    match value {
+        Data::Array(v) => &v.0[index],
+        Data::Struct(v) => &v.0[index],
+        _ => panic!("field of scalar value {}", value),
+    }
+}
+
+/// Get a mutable field or element of a value.
+fn child_mut(value: &mut Data, index: usize) -> &mut Data {
+    match value {
+        Data::Array(v) => &mut v.0[index],
+        Data::Struct(v) => &mut v.0[index],
+        _ => panic!("field of scalar value"),
+    }
+}
+
+/// Extract a range of bits or elements from a value.
+fn slice(value: &Data, off: usize, len: usize) -> Data {
+    match value {
+        Data::Int(v) => Data::Int(v.extract_slice(off, len)),
+        Data::Array(v) => Data::Array(v.extract_slice(off, len)),
+        _ => panic!("slice of value {}", value),
+    }
+}
+
+/// Insert a range of bits or elements into a value.
+fn insert_slice(target: &mut Data, off: usize, len: usize, value: &Data) {
+    match (target, value) {
+        (Data::Int(t), Data::Int(v)) => t.insert_slice(off, len, v),
+        (Data::Array(t), Data::Array(v)) => t.insert_slice(off, len, v),
+        _ => panic!("slice of incompatible values"),
+    }
+}
+
+/// Concatenate two values, with `lo` in the lower bits or elements.
+fn concat(lo: Data, hi: Data) -> Data {
+    match (lo, hi) {
+        (Data::Int(lo), Data::Int(hi)) => Data::Int(IntValue::from_unsigned(
+            lo.width + hi.width,
+            lo.value | (hi.value << lo.width),
+        )),
+        (Data::Array(mut lo), Data::Array(hi)) => {
+            lo.0.extend(hi.0);
+            Data::Array(lo)
+        }
+        _ => panic!("concatenation of incompatible values"),
+    }
+}
+
+/// Get the number of bits or elements a piece covers in its storage.
+fn piece_len(store: &Data, piece: &Piece) -> usize {
+    match piece.slice {
+        Some((_, len)) => len,
+        None => length(piece.path.iter().fold(store, |v, &i| child(v, i))),
+    }
+}
+
+/// Read the value of a piece from its storage.
+fn read_piece(store: &Data, piece: &Piece) -> Data {
+    let value = piece.path.iter().fold(store, |v, &i| child(v, i));
+    match piece.slice {
+        Some((off, len)) => slice(value, off, len),
+        None => value.clone(),
+    }
+}
+
+/// Write the value of a piece to its storage.
+fn write_piece(store: &mut Data, piece: &Piece, value: Data) {
+    let target = piece.path.iter().fold(store, |v, &i| child_mut(v, i));
+    match piece.slice {
+        Some((off, len)) => insert_slice(target, off, len, &value),
+        None => *target = value,
+    }
+}
+
+/// Format a point in time for diagnostics.
+fn format_time(time: &TimeValue) -> String {
+    format!("{}", time)
+}
+
+/// Determine the name of a VCD scope for a unit.
+fn scope_name(name: &UnitName) -> String {
+    match name {
+        UnitName::Global(n) | UnitName::Local(n) => n.clone(),
+        UnitName::Anonymous(i) => format!("unit{}", i),
+    }
+}
+
+/// Make a VCD scope name unique among its siblings.
+fn unique_scope_name(parent: &Scope, name: String) -> String {
+    let taken = |n: &str| parent.children.iter().any(|c| c.name == n);
+    if !taken(&name) {
+        return name;
+    }
+    (1..)
+        .map(|i| format!("{}_{}", name, i))
+        .find(|n| !taken(n))
+        .unwrap()
+}
+
+/// A VCD waveform being recorded.
+struct Vcd {
+    /// The text emitted so far.
+    output: String,
+    /// The signals dumped, with their identifier code and last dumped value.
+    vars: Vec<(String, Ref, Option<Data>)>,
+}
+
+impl Vcd {
+    /// Emit the VCD header for a hierarchy of scopes.
+    fn new(sim: &Simulator, scope: &Scope) -> Self {
+        let mut vcd = Vcd {
+            output: String::new(),
+            vars: vec![],
+        };
+        writeln!(vcd.output, "$timescale 1ps $end").unwrap();
+        let mut codes = HashMap::new();
+        vcd.declare_scope(sim, scope, &mut codes);
+        writeln!(vcd.output, "$enddefinitions $end").unwrap();
+        vcd
+    }
+
+    /// Declare the signals in a scope and its children.
+    fn declare_scope(&mut self, sim: &Simulator, scope: &Scope, codes: &mut HashMap<Ref, String>) {
+        writeln!(self.output, "$scope module {} $end", scope.name).unwrap();
+        for (name, r) in &scope.vars {
+            self.declare_var(sim, name.clone(), r.clone(), codes);
+        }
+        for child in &scope.children {
+            self.declare_scope(sim, child, codes);
+        }
+        writeln!(self.output, "$upscope $end").unwrap();
+    }
+
+    /// Declare a signal, or its elements if it is an array or struct.
+    fn declare_var(
+        &mut self,
+        sim: &Simulator,
+        name: String,
+        r: Ref,
+        codes: &mut HashMap<Ref, String>,
+    ) {
+        match sim.read(&r, None) {
+            Data::Int(v) => {
+                let code = match codes.get(&r) {
+                    Some(code) => code.clone(),
+                    None => {
+                        let code = identifier_code(self.vars.len());
+                        self.vars.push((code.clone(), r.clone(), None));
+                        codes.insert(r, code.clone());
+                        code
+                    }
+                };
+                writeln!(self.output, "$var wire {} {} {} $end", v.width, code, name).unwrap();
+            }
+            Data::Array(_) | Data::Struct(_) => {
+                for i in 0..length(&sim.read(&r, None)) {
+                    let element = sim.project(&r, &[i], true, None);
+                    self.declare_var(sim, format!("{}[{}]", name, i), element, codes);
+                }
+            }
+            Data::Time(_) | Data::Void => (),
+        }
+    }
+
+    /// Record the signals that changed since the last dump.
+    fn dump(&mut self, sim: &Simulator) {
+        let mut changes = String::new();
+        for (code, r, last) in &mut self.vars {
+            let value = sim.read(r, None);
+            if last.as_ref() == Some(&value) {
+                continue;
+            }
+            if let Data::Int(v) = &value {
+                if v.width == 1 {
+                    writeln!(changes, "{}{}", v.value, code).unwrap();
+                } else {
+                    writeln!(changes, "b{:b} {}", v.value, code).unwrap();
+                }
+            }
+            *last = Some(value);
+        }
+        if !changes.is_empty() {
+            let ps = &sim.now.time * BigRational::from_integer(num::pow(BigInt::from(10), 12));
+            writeln!(self.output, "#{}", ps.round().to_integer()).unwrap();
+            self.output.push_str(&changes);
+        }
+    }
+}
+
+/// Generate the VCD identifier code for the n-th signal.
+fn identifier_code(mut index: usize) -> String {
+    let mut code = String::new();
+    loop {
+        code.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            break;
+        }
+        index -= 1;
+    }
+    code
+}
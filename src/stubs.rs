@@ -0,0 +1,129 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Blackbox stubs.
+//!
+//! Derives a skeleton of every module and entity in a set of parsed files,
+//! which declares the same parameters and ports but has an empty body. Such
+//! stubs stand in for the actual design in simulation, or are passed to
+//! synthesis as blackboxes. Modules are marked with the `(* blackbox *)`
+//! attribute, and entities receive an empty architecture named `stub`.
+//!
+//! The declarations are copied from the source text, such that types and
+//! default values appear exactly as written.
+
+use crate::score::Ast;
+use crate::svlog::ast as sv;
+use crate::vhdl::syntax::ast as vhdl;
+use std::fmt::Write;
+
+/// Write the stubs of the modules and entities in a list of parsed files.
+///
+/// The stubs are in the language of the file they are derived from, in the
+/// order the modules and entities are defined. `extern module` declarations
+/// and modules nested in other modules are skipped.
+pub fn write_stubs(asts: &[Ast]) -> String {
+    let mut output = String::new();
+    for ast in asts {
+        match ast {
+            Ast::Svlog(file) => {
+                for item in &file.items {
+                    match item.data {
+                        sv::ItemData::ModuleDecl(ref m) if !m.external && !m.foreign => {
+                            write_module(&mut output, m)
+                        }
+                        _ => (),
+                    }
+                }
+            }
+            Ast::Vhdl(units) => {
+                for unit in units {
+                    if let vhdl::DesignUnitData::EntityDecl(ref e) = unit.data {
+                        write_entity(&mut output, &unit.ctx, e);
+                    }
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Write the stub of a SystemVerilog module.
+///
+/// Besides the header, the stub keeps the parameter, port, type, and import
+/// declarations in the body of the module, which non-ANSI ports refer to.
+fn write_module(output: &mut String, module: &sv::Module) {
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output.push_str("(* blackbox *)\n");
+    write!(output, "module {}", module.name.value).unwrap();
+    for import in &module.imports {
+        write!(output, " {}", import.span.extract()).unwrap();
+    }
+    if !module.params.is_empty() {
+        output.push_str(" #(\n");
+        write_list(output, module.params.iter().map(|p| p.span.extract()));
+        output.push(')');
+    }
+    if !module.ports.is_empty() {
+        output.push_str(" (\n");
+        write_list(output, module.ports.iter().map(|p| p.span.extract()));
+        output.push(')');
+    }
+    output.push_str(";\n");
+    for item in &module.items {
+        match item.data {
+            sv::ItemData::ParamDecl(_)
+            | sv::ItemData::PortDecl(_)
+            | sv::ItemData::Typedef(_)
+            | sv::ItemData::ImportDecl(_) => {
+                writeln!(output, "    {}", item.span.extract()).unwrap();
+            }
+            _ => (),
+        }
+    }
+    output.push_str("endmodule\n");
+}
+
+/// Write a comma-separated list of declarations, one per line.
+fn write_list(output: &mut String, items: impl Iterator<Item = String>) {
+    let items: Vec<_> = items.collect();
+    for (i, item) in items.iter().enumerate() {
+        let sep = if i + 1 < items.len() { "," } else { "" };
+        writeln!(output, "    {}{}", item, sep).unwrap();
+    }
+}
+
+/// Write the stub of a VHDL entity and an empty architecture.
+///
+/// The context clause of the entity is repeated, since the types of the
+/// generics and ports may refer to the libraries and packages it makes
+/// visible.
+fn write_entity(output: &mut String, ctx: &[vhdl::CtxItem], entity: &vhdl::EntityDecl) {
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    for item in ctx {
+        let span = match item {
+            vhdl::CtxItem::LibClause(x) => x.span,
+            vhdl::CtxItem::UseClause(x) => x.span,
+            vhdl::CtxItem::CtxRef(x) => x.span,
+        };
+        writeln!(output, "{}", span.extract()).unwrap();
+    }
+    if !ctx.is_empty() {
+        output.push('\n');
+    }
+    let name = entity.name.value;
+    writeln!(output, "entity {} is", name).unwrap();
+    for decl in &entity.decls {
+        if let vhdl::DeclItem::PortgenClause(span, ..) = decl {
+            writeln!(output, "    {}", span.extract()).unwrap();
+        }
+    }
+    writeln!(output, "end entity {};", name).unwrap();
+    output.push('\n');
+    writeln!(output, "architecture stub of {} is", name).unwrap();
+    output.push_str("begin\n");
+    output.push_str("end architecture stub;\n");
+}
@@ -103,6 +103,10 @@ pub enum AstNode<'ast> {
     SubroutineDecl(&'ast ast::SubroutineDecl<'ast>),
     /// An interface.
     Interface(&'ast ast::Interface<'ast>),
+    /// A concurrent assertion, which acts as a procedure.
+    ConcurrentAssertion(&'ast ast::Assertion<'ast>),
+    /// The check performed by a concurrent assertion.
+    AssertionCheck(&'ast ast::Assertion<'ast>),
 }
 
 impl<'a> AstNode<'a> {
@@ -226,6 +230,7 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::Import(x) => x.span(),
             AstNode::SubroutineDecl(x) => x.span(),
             AstNode::Interface(x) => x.span(),
+            AstNode::ConcurrentAssertion(x) | AstNode::AssertionCheck(x) => x.span,
         }
     }
 
@@ -257,6 +262,7 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::Import(x) => x.human_span(),
             AstNode::SubroutineDecl(x) => x.human_span(),
             AstNode::Interface(x) => x.human_span(),
+            AstNode::ConcurrentAssertion(x) | AstNode::AssertionCheck(x) => x.span,
         }
     }
 }
@@ -291,6 +297,8 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::Import(x) => "import",
             AstNode::SubroutineDecl(x) => "subroutine declaration",
             AstNode::Interface(x) => "interface",
+            AstNode::ConcurrentAssertion(x) => "concurrent assertion",
+            AstNode::AssertionCheck(x) => "assertion",
         }
     }
 
@@ -322,6 +330,8 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::Import(x) => x.to_definite_string(),
             AstNode::SubroutineDecl(x) => x.to_definite_string(),
             AstNode::Interface(x) => x.to_definite_string(),
+            AstNode::ConcurrentAssertion(_) => "concurrent assertion".to_string(),
+            AstNode::AssertionCheck(_) => "assertion".to_string(),
         }
     }
 }
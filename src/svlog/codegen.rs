@@ -3,8 +3,10 @@
 //! This module implements LLHD code generation.
 
 use crate::{
+    common::FourState,
     crate_prelude::*,
     hir::{AccessedNode, HirNode},
+    inference::{self, ProcInference},
     port_list::PortList,
     resolver::InstTarget,
     ty::UnpackedType,
@@ -19,6 +21,12 @@ use std::{
     rc::Rc,
 };
 
+/// The name of the function which is called when an assertion fails.
+///
+/// The function is only declared, and the call carries the location of the
+/// failed assertion as a location hint.
+pub const ASSERTION_FAILED: &str = "moore.assertion_failed";
+
 /// A code generator.
 ///
 /// Use this struct to emit LLHD code for nodes in a [`Context`].
@@ -45,12 +53,29 @@ impl<'gcx, C> CodeGenerator<'gcx, C> {
     pub fn finalize(self) -> llhd::ir::Module {
         self.into
     }
+
+    /// Get the names of the module instances emitted so far.
+    ///
+    /// LLHD has no names for instances. Instead, the `inst` instructions of
+    /// module instances carry the location of the instance name as their
+    /// location hint, regardless of whether debug info has been requested,
+    /// and this table maps these hints to the names.
+    pub fn instance_names(&self) -> &HashMap<usize, String> {
+        &self.tables.instance_names
+    }
 }
 
 #[derive(Default)]
 struct Tables<'gcx> {
     module_defs: HashMap<NodeEnvId, Result<Rc<EmittedModule<'gcx>>>>,
     module_signatures: HashMap<NodeEnvId, (llhd::ir::UnitName, llhd::ir::Signature)>,
+    module_specs: HashMap<ModuleSpec<'gcx>, Result<Rc<EmittedModule<'gcx>>>>,
+    unit_names: HashSet<String>,
+    checked_procs: HashSet<NodeId>,
+    memory_writes: HashSet<(NodeId, ParamEnv)>,
+    two_state_warnings: HashSet<Span>,
+    assertion_failed_declared: bool,
+    instance_names: HashMap<usize, String>,
     interned_types: HashMap<&'gcx UnpackedType<'gcx>, Result<llhd::Type>>,
 }
 
@@ -83,6 +108,18 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         };
         info!("Emit module `{}` with {:?}", hir.name, env);
 
+        // Resolve the parameters of the module and of the interfaces connected
+        // to its ports in this environment. Different environments that assign
+        // the same values to all parameters share a single specialization of
+        // the module.
+        let spec = self.module_spec(id, hir, env);
+        if let Some(x) = spec.as_ref().and_then(|s| self.tables.module_specs.get(s)) {
+            debug!("Reusing specialization of `{}` for {:?}", hir.name, env);
+            let x = x.clone();
+            self.tables.module_defs.insert(id.env(env), x.clone());
+            return x;
+        }
+
         // Emit detailed port information if requested.
         if self.sess().has_verbosity(Verbosity::PORTS) {
             emit_port_details(self.cx, hir, env);
@@ -93,18 +130,52 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
 
         // Pick an entity name.
         let mut entity_name: String = hir.name.value.into();
-        if env != self.default_param_env() {
-            entity_name.push_str(&format!(".param{}", env.0));
+        match spec {
+            Some(ref spec) if env != self.default_param_env() && !self.sess().opts.keep_names => {
+                for &(name, param) in &spec.params {
+                    entity_name.push_str(&format!(".{}_{}", name, mangle_param(param)));
+                }
+                for (port, params) in &spec.intfs {
+                    for &(name, param) in params {
+                        entity_name.push_str(&format!(
+                            ".{}_{}_{}",
+                            port,
+                            name,
+                            mangle_param(param)
+                        ));
+                    }
+                }
+            }
+            _ => (),
         }
+        let entity_name = self.unique_unit_name(entity_name);
         let name = llhd::ir::UnitName::Global(entity_name.clone());
 
+        // Modules without a definition become external unit declarations.
+        // Modules derived from VHDL entities are defined by the VHDL frontend.
+        if hir.ast.external {
+            let result = if hir.ast.foreign || self.sess().opts.allow_blackbox {
+                self.into.declare(name.clone(), ports.sig.clone());
+                Ok(Rc::new(EmittedModule { name, ports }))
+            } else {
+                self.emit(
+                    DiagBuilder2::error(format!("`{}` has no definition", hir.name))
+                        .span(hir.name.span)
+                        .add_note("Use `--allow-blackbox` to emit it as an external unit"),
+                );
+                Err(())
+            };
+            self.tables.module_defs.insert(id.env(env), result.clone());
+            return result;
+        }
+
         // Create entity.
         let mut ent =
             llhd::ir::UnitData::new(llhd::ir::UnitKind::Entity, name.clone(), ports.sig.clone());
         let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut ent);
         self.tables
             .module_signatures
-            .insert(id.env(env), (name, ports.sig.clone()));
+            .insert(id.env(env), (name.clone(), ports.sig.clone()));
         let mut values = HashMap::new();
         let mut gen = UnitGenerator {
             gen: self,
@@ -114,6 +185,9 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            plane: Plane::Value,
+            next_located: 0,
+            emitting_default: false,
         };
 
         // Assign proper port names and collect ports into a lookup table.
@@ -139,44 +213,151 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         }
 
         // Emit the actual contents of the entity.
+        gen.emit_location(hir.span);
         gen.emit_module_block(id, env, &hir.block, &entity_name)?;
 
         // Assign default values to undriven output ports.
         for port in ports.outputs.iter() {
             let value = gen.values[&port.accnode];
-            let driven = gen
-                .builder
-                .all_insts()
-                .any(|inst| match gen.builder[inst].opcode() {
-                    llhd::ir::Opcode::Drv => gen.builder[inst].args()[0] == value,
-                    llhd::ir::Opcode::Inst => gen.builder[inst].output_args().contains(&value),
+            let driven = gen.builder.all_insts().any(|inst| {
+                let unit = &gen.builder;
+                match unit[inst].opcode() {
+                    llhd::ir::Opcode::Drv | llhd::ir::Opcode::Reg => {
+                        projection_root(unit, unit[inst].args()[0]) == value
+                    }
+                    llhd::ir::Opcode::Inst => unit[inst]
+                        .output_args()
+                        .iter()
+                        .any(|&arg| projection_root(unit, arg) == value),
                     _ => false,
-                });
+                }
+            });
             if driven {
                 continue;
             }
-            let default_value = gen.emit_const(
+            let default_value = gen.with_plane(Plane::Both, |gen| {
                 if let Some(default) = port.default {
-                    gen.constant_value_of(default, env)
+                    let value = gen.constant_value_of(default, env);
+                    gen.emit_const(value, env, port.port.span())
                 } else {
-                    gen.type_default_value(port.ty)
-                },
-                env,
-                port.port.span(),
-            )?;
+                    let value = gen.type_default_value(port.ty);
+                    gen.emit_default_const(value, env, port.port.span())
+                }
+            })?;
             let zero_time = llhd::value::TimeValue::new(num::zero(), 0, 0);
             let zero_time = gen.builder.ins().const_time(zero_time);
             gen.builder
                 .ins()
                 .drv(gen.values[&port.accnode], default_value, zero_time);
         }
+        gen.emit_location(hir.span);
 
-        let unit = self.into.add_unit(ent);
-        let result = Ok(Rc::new(EmittedModule { unit, ports }));
+        self.into.add_unit(ent);
+        let result = Ok(Rc::new(EmittedModule { name, ports }));
         self.tables.module_defs.insert(id.env(env), result.clone());
+        if let Some(spec) = spec {
+            self.tables.module_specs.insert(spec, result.clone());
+        }
         result
     }
 
+    /// Determine the specialization of a module in an environment.
+    ///
+    /// Returns `None` for the default environment of a module with parameters
+    /// that have no default, which cannot be resolved without an override.
+    fn module_spec(
+        &mut self,
+        id: NodeId,
+        hir: &'gcx hir::Module<'gcx>,
+        env: ParamEnv,
+    ) -> Option<ModuleSpec<'gcx>> {
+        let ids: Vec<_> = hir
+            .params
+            .iter()
+            .chain(hir.block.params.iter())
+            .cloned()
+            .collect();
+        if env == self.default_param_env()
+            && ids.iter().any(|&id| match self.hir_of(id) {
+                Ok(HirNode::ValueParam(p)) => !p.local && p.default.is_none(),
+                Ok(HirNode::TypeParam(p)) => !p.local && p.default.is_none(),
+                _ => false,
+            })
+        {
+            return None;
+        }
+        let params = self.resolve_params(ids, env);
+
+        // Interfaces connected to the ports carry their own parametrization.
+        let mut intfs = vec![];
+        for port in &hir.ports_new.int {
+            if self.param_env_data(env).find_interface(port.id).is_none() {
+                continue;
+            }
+            let ty = self.type_of_int_port(Ref(port), env);
+            let intf = match ty.resolve_full().core.get_interface() {
+                Some(x) => x,
+                None => continue,
+            };
+            let intf_hir = match self.hir_of(intf.ast.id()) {
+                Ok(HirNode::Interface(x)) => x,
+                _ => continue,
+            };
+            let ids: Vec<_> = intf_hir
+                .params
+                .iter()
+                .flat_map(|p| match &p.kind {
+                    ast::ParamKind::Type(x) => x.iter().map(|d| d.id()).collect::<Vec<_>>(),
+                    ast::ParamKind::Value(x) => x.iter().map(|d| d.id()).collect::<Vec<_>>(),
+                })
+                .chain(intf_hir.block.params.iter().cloned())
+                .collect();
+            intfs.push((port.name.value, self.resolve_params(ids, intf.env)));
+        }
+
+        Some(ModuleSpec {
+            module: id,
+            params,
+            intfs,
+        })
+    }
+
+    /// Resolve the overridable value and type parameters among a list of
+    /// parameter declarations in an environment.
+    fn resolve_params(
+        &mut self,
+        ids: impl IntoIterator<Item = NodeId>,
+        env: ParamEnv,
+    ) -> Vec<(Name, ParamSpec<'gcx>)> {
+        let mut params = vec![];
+        for id in ids {
+            match self.hir_of(id) {
+                Ok(HirNode::ValueParam(p)) if !p.local => params.push((
+                    p.name.value,
+                    ParamSpec::Value(self.constant_value_of(id, env)),
+                )),
+                Ok(HirNode::TypeParam(p)) if !p.local => {
+                    let ty = self.map_to_type_or_error(Ref(self.ast_for_id(id)), env);
+                    params.push((p.name.value, ParamSpec::Type(ty.resolve_full())))
+                }
+                _ => (),
+            }
+        }
+        params
+    }
+
+    /// Pick a unit name that has not been used yet.
+    fn unique_unit_name(&mut self, name: String) -> String {
+        let mut unique = name.clone();
+        let mut index = 0;
+        while self.tables.unit_names.contains(&unique) {
+            index += 1;
+            unique = format!("{}.{}", name, index);
+        }
+        self.tables.unit_names.insert(unique.clone());
+        unique
+    }
+
     fn determine_module_ports(
         &mut self,
         ports: &'gcx [port_list::IntPort<'gcx>],
@@ -243,6 +424,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 }
             } else {
                 trace!("    Regular port");
+                self.check_fixed_size(ty, port.span)?;
                 let llty = llhd::signal_ty(self.emit_type(ty)?);
                 let name = port.name.to_string();
                 let mp = ModulePort {
@@ -311,12 +493,37 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         Ok(result)
     }
 
+    /// Analyze the hardware inferred from a procedure.
+    ///
+    /// Diagnostics are only emitted once for all parametrizations of the
+    /// procedure.
+    fn infer_procedure(&mut self, id: NodeId, env: ParamEnv) -> Result<Option<ProcInference>> {
+        let hir = match self.hir_of(id)? {
+            HirNode::Proc(x) => x,
+            _ => unreachable!(),
+        };
+        if !self.tables.checked_procs.insert(id) {
+            return inference::infer_procedure(self.cx, hir, env);
+        }
+        let inf = inference::check_procedure(self.cx, hir, env)?;
+        if let Some(ref inf) = inf {
+            if self.sess().has_verbosity(Verbosity::PROCS) {
+                inference::print_procedure(self.cx, hir, inf);
+            }
+        }
+        Ok(inf)
+    }
+
     /// Emit the code for a procedure.
+    ///
+    /// The `memories` have been written by memory write ports emitted outside
+    /// of the procedure, and are only read by the procedure itself.
     fn emit_procedure(
         &mut self,
         id: NodeId,
         env: ParamEnv,
         name_prefix: &str,
+        memories: &[AccessedNode],
     ) -> Result<EmittedProcedure> {
         let hir = match self.hir_of(id)? {
             HirNode::Proc(x) => x,
@@ -324,7 +531,10 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         };
 
         // Find the accessed nodes.
-        let acc = self.accessed_nodes(hir.stmt, env)?;
+        let mut acc = (*self.accessed_nodes(hir.stmt, env)?).clone();
+        for memory in memories {
+            acc.written.remove(memory);
+        }
         trace!("Process accesses {:#?}", acc);
         let mut sig = llhd::ir::Signature::new();
         let mut inputs = vec![];
@@ -361,20 +571,19 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         trace!("Process Env: {:?}", self.param_env_data(env));
 
         // Create process and entry block.
-        let proc_name = format!(
-            "{}.{}.{}.{}",
-            name_prefix,
-            match hir.kind {
-                ast::ProcedureKind::Initial => "initial",
-                ast::ProcedureKind::Always => "always",
-                ast::ProcedureKind::AlwaysComb => "always_comb",
-                ast::ProcedureKind::AlwaysLatch => "always_latch",
-                ast::ProcedureKind::AlwaysFf => "always_ff",
-                ast::ProcedureKind::Final => "final",
-            },
-            id.as_usize(),
-            env.0,
-        );
+        let kind_name = match hir.kind {
+            ast::ProcedureKind::Initial => "initial",
+            ast::ProcedureKind::Always => "always",
+            ast::ProcedureKind::AlwaysComb => "always_comb",
+            ast::ProcedureKind::AlwaysLatch => "always_latch",
+            ast::ProcedureKind::AlwaysFf => "always_ff",
+            ast::ProcedureKind::Final => "final",
+        };
+        let proc_name = if self.sess().opts.keep_names {
+            self.unique_unit_name(format!("{}.{}", name_prefix, kind_name))
+        } else {
+            format!("{}.{}.{}.{}", name_prefix, kind_name, id.as_usize(), env.0)
+        };
         let mut prok = llhd::ir::UnitData::new(
             llhd::ir::UnitKind::Process,
             llhd::ir::UnitName::Local(proc_name),
@@ -436,6 +645,9 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            plane: Plane::Value,
+            next_located: 0,
+            emitting_default: false,
         };
         let entry_blk = pg.add_nameless_block();
         pg.builder.append_to(entry_blk);
@@ -443,9 +655,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         // Determine which values are both read and written. These require
         // shadow variables to emulate the expected behaviour under blocking
         // assignments.
-        let input_set: HashSet<_> = acc.read.iter().cloned().collect();
-        let output_set: HashSet<_> = acc.written.iter().cloned().collect();
-        for &id in input_set.intersection(&output_set) {
+        for &id in acc.read.iter().filter(|id| acc.written.contains(id)) {
             let init = pg.builder.ins().prb(pg.values[&id.into()]);
             let shadow = pg.builder.ins().var(init);
             if let Some(name) = pg
@@ -494,6 +704,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         };
 
         // Emit the main statement.
+        pg.emit_location(hir.span);
         pg.emit_stmt(hir.stmt, env)?;
 
         // Emit epilogue.
@@ -511,6 +722,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 pg.builder.ins().halt();
             }
         }
+        pg.emit_location(hir.span);
 
         Ok(EmittedProcedure {
             unit: self.into.add_unit(prok),
@@ -519,8 +731,40 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         })
     }
 
-    /// Map a type to an LLHD type (interned).
+    /// Report queues, dynamic arrays, and associative arrays, which have no
+    /// fixed size and therefore cannot be mapped to LLHD.
+    fn check_fixed_size(&self, ty: &'gcx UnpackedType<'gcx>, span: Span) -> Result<()> {
+        let ty = ty.resolve_full();
+        if ty.dims.iter().any(|dim| dim.get_size().is_none()) {
+            self.emit(
+                DiagBuilder2::error(format!("unsupported: variable-size array type `{}`", ty))
+                    .span(span),
+            );
+            return Err(());
+        }
+        Ok(())
+    }
+
+    /// Map a type to an LLHD type.
+    ///
+    /// Four-valued types are mapped to a struct of their value and unknown
+    /// bits with `--four-state=paired`. See [`Plane`] for details.
     fn emit_type(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
+        let llty = self.emit_two_state_type(ty)?;
+        if self.is_paired(ty) {
+            Ok(llhd::struct_ty(vec![llty.clone(), llty]))
+        } else {
+            Ok(llty)
+        }
+    }
+
+    /// Check whether a type is emitted as a pair of value and unknown bits.
+    fn is_paired(&self, ty: &'gcx UnpackedType<'gcx>) -> bool {
+        self.sess().opts.four_state == FourState::Paired && ty.domain() == ty::Domain::FourValued
+    }
+
+    /// Map a type to an LLHD type, ignoring its `x` and `z` bits (interned).
+    fn emit_two_state_type(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
         if let Some(x) = self.tables.interned_types.get(&ty) {
             x.clone()
         } else {
@@ -530,7 +774,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         }
     }
 
-    /// Map a type to an LLHD type.
+    /// Map a type to an LLHD type, ignoring its `x` and `z` bits.
     fn emit_type_uninterned(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
         if ty.is_error() {
             return Err(());
@@ -549,14 +793,14 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 None => panic!("cannot map unsized array `{}` to LLHD", ty),
             };
             let inner = ty.pop_dim(self.cx).unwrap();
-            return Ok(llhd::array_ty(size, self.emit_type(inner)?));
+            return Ok(llhd::array_ty(size, self.emit_two_state_type(inner)?));
         }
 
         // Handle structs.
         if let Some(strukt) = ty.get_struct() {
             let mut types = vec![];
             for member in &strukt.members {
-                types.push(self.emit_type(member.ty)?);
+                types.push(self.emit_two_state_type(member.ty)?);
             }
             return Ok(llhd::struct_ty(types));
         }
@@ -568,7 +812,9 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 ty::PackedCore::Error => Ok(llhd::void_ty()),
                 ty::PackedCore::Void => Ok(llhd::void_ty()),
                 ty::PackedCore::IntAtom(ty::IntAtomType::Time) => Ok(llhd::time_ty()),
-                ty::PackedCore::Enum(ref enm) => self.emit_type(enm.base.to_unpacked(self.cx)),
+                ty::PackedCore::Enum(ref enm) => {
+                    self.emit_two_state_type(enm.base.to_unpacked(self.cx))
+                }
                 _ => unreachable!("emitting `{}` should have been handled above", packed),
             };
         }
@@ -577,85 +823,93 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         error!("Cannot map type {:#?}", ty);
         panic!("cannot map `{}` to LLHD", ty);
     }
+}
 
-    /// Execute the initialization step of a generate loop.
-    fn execute_genvar_init(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        match hir {
-            HirNode::GenvarDecl(_) => Ok(env),
-            HirNode::Stmt(stmt) => match stmt.kind {
-                hir::StmtKind::Assign {
-                    lhs,
-                    rhs,
-                    kind: hir::AssignKind::Block(ast::AssignOp::Identity),
-                } => {
-                    let target_id = self.resolve_node(lhs, env)?;
-                    let init_value = self.constant_value_of(rhs, env);
-                    let mut env_data = self.param_env_data(env).clone();
-                    env_data.set_value(target_id, init_value);
-                    Ok(self.intern_param_env(env_data))
-                }
-                _ => unreachable!(),
-            },
+/// Execute the initialization step of a generate loop.
+pub(crate) fn execute_genvar_init<'a>(
+    cx: &impl Context<'a>,
+    id: NodeId,
+    env: ParamEnv,
+) -> Result<ParamEnv> {
+    let hir = cx.hir_of(id)?;
+    match hir {
+        HirNode::GenvarDecl(_) => Ok(env),
+        HirNode::Stmt(stmt) => match stmt.kind {
+            hir::StmtKind::Assign {
+                lhs,
+                rhs,
+                kind: hir::AssignKind::Block(ast::AssignOp::Identity),
+            } => {
+                let target_id = cx.resolve_node(lhs, env)?;
+                let init_value = cx.constant_value_of(rhs, env);
+                let mut env_data = cx.param_env_data(env).clone();
+                env_data.set_value(target_id, init_value);
+                Ok(cx.intern_param_env(env_data))
+            }
             _ => unreachable!(),
-        }
+        },
+        _ => unreachable!(),
     }
+}
 
-    /// Execute the iteration step of a generate loop.
-    fn execute_genvar_step(&mut self, id: NodeId, env: ParamEnv) -> Result<ParamEnv> {
-        let hir = self.hir_of(id)?;
-        let mut env_data = self.param_env_data(env).clone();
-        let next = match hir {
-            HirNode::Expr(expr) => match expr.kind {
-                hir::ExprKind::Unary(op, target_id) => {
-                    let target_id = self.resolve_node(target_id, env)?;
-                    let current_value = self.constant_value_of(target_id, env);
-                    let next_value = match current_value.kind {
-                        ValueKind::Int(ref v, ..) => match op {
-                            hir::UnaryOp::PostInc | hir::UnaryOp::PreInc => Some(v + 1),
-                            hir::UnaryOp::PostDec | hir::UnaryOp::PreDec => Some(v - 1),
-                            _ => None,
-                        }
-                        .map(|v| value::make_int(current_value.ty, v)),
-                        _ => unreachable!(),
-                    };
-                    next_value.map(|v| (target_id, self.intern_value(v)))
-                }
-                hir::ExprKind::Assign { .. } => {
-                    let mir = self.mir_rvalue(id, env);
-                    match mir.kind {
-                        mir::RvalueKind::Error => return Err(()),
-                        mir::RvalueKind::Assignment { lvalue, rvalue, .. } => {
-                            let target_id = match lvalue.kind {
-                                mir::LvalueKind::Error => return Err(()),
-                                mir::LvalueKind::Genvar(id) => id,
-                                _ => unreachable!(),
-                            };
-                            let next_value = self.const_mir_rvalue(Ref(rvalue));
-                            Some((target_id, next_value))
-                        }
-                        _ => unreachable!(),
+/// Execute the iteration step of a generate loop.
+pub(crate) fn execute_genvar_step<'a>(
+    cx: &impl Context<'a>,
+    id: NodeId,
+    env: ParamEnv,
+) -> Result<ParamEnv> {
+    let hir = cx.hir_of(id)?;
+    let mut env_data = cx.param_env_data(env).clone();
+    let next = match hir {
+        HirNode::Expr(expr) => match expr.kind {
+            hir::ExprKind::Unary(op, target_id) => {
+                let target_id = cx.resolve_node(target_id, env)?;
+                let current_value = cx.constant_value_of(target_id, env);
+                let next_value = match current_value.kind {
+                    ValueKind::Int(ref v, ..) => match op {
+                        hir::UnaryOp::PostInc | hir::UnaryOp::PreInc => Some(v + 1),
+                        hir::UnaryOp::PostDec | hir::UnaryOp::PreDec => Some(v - 1),
+                        _ => None,
                     }
+                    .map(|v| value::make_int(current_value.ty, v)),
+                    _ => unreachable!(),
+                };
+                next_value.map(|v| (target_id, cx.intern_value(v)))
+            }
+            hir::ExprKind::Assign { .. } => {
+                let mir = cx.mir_rvalue(id, env);
+                match mir.kind {
+                    mir::RvalueKind::Error => return Err(()),
+                    mir::RvalueKind::Assignment { lvalue, rvalue, .. } => {
+                        let target_id = match lvalue.kind {
+                            mir::LvalueKind::Error => return Err(()),
+                            mir::LvalueKind::Genvar(id) => id,
+                            _ => unreachable!(),
+                        };
+                        let next_value = cx.const_mir_rvalue(Ref(rvalue));
+                        Some((target_id, next_value))
+                    }
+                    _ => unreachable!(),
                 }
-                _ => None,
-            },
-            _ => None,
-        };
-        match next {
-            Some((target_id, next_value)) => {
-                env_data.set_value(target_id, next_value);
-                return Ok(self.intern_param_env(env_data));
-            }
-            None => {
-                self.emit(
-                    DiagBuilder2::error(format!(
-                        "{} is not a valid genvar iteration step",
-                        hir.desc_full()
-                    ))
-                    .span(hir.human_span()),
-                );
-                Err(())
             }
+            _ => None,
+        },
+        _ => None,
+    };
+    match next {
+        Some((target_id, next_value)) => {
+            env_data.set_value(target_id, next_value);
+            return Ok(cx.intern_param_env(env_data));
+        }
+        None => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} is not a valid genvar iteration step",
+                    hir.desc_full()
+                ))
+                .span(hir.human_span()),
+            );
+            Err(())
         }
     }
 }
@@ -671,12 +925,20 @@ struct UnitGenerator<'a, 'gcx, C> {
     /// The constant values emitted into the unit.
     interned_consts: HashMap<Value<'gcx>, Result<llhd::ir::Value>>,
     /// The MIR lvalues emitted into the unit.
-    interned_lvalues: HashMap<NodeId, Result<(llhd::ir::Value, Option<llhd::ir::Value>)>>,
+    interned_lvalues: HashMap<(NodeId, Plane), Result<(llhd::ir::Value, Option<llhd::ir::Value>)>>,
     /// The MIR rvalues emitted into the unit.
-    interned_rvalues: HashMap<(NodeId, Mode), Result<(llhd::ir::Value, Mode)>>,
+    interned_rvalues: HashMap<(NodeId, Mode, Plane), Result<(llhd::ir::Value, Mode)>>,
     /// The shadow variables introduced to handle signals which are both read
     /// and written in a process.
     shadows: HashMap<AccessedNode, llhd::ir::Value>,
+    /// The part of four-valued values currently being emitted.
+    plane: Plane,
+    /// The index of the first instruction which `emit_location` has not
+    /// visited yet.
+    next_located: usize,
+    /// Whether constants with unknown bits are being emitted for the implicit
+    /// default value of a type, such that no warning is due in two-state mode.
+    emitting_default: bool,
 }
 
 impl<'a, 'gcx, C> Deref for UnitGenerator<'a, 'gcx, C> {
@@ -718,6 +980,47 @@ where
         self.values.insert(src, value);
     }
 
+    /// Emit code for a specific part of four-valued values.
+    fn with_plane<R>(&mut self, plane: Plane, f: impl FnOnce(&mut Self) -> R) -> R {
+        let outer = std::mem::replace(&mut self.plane, plane);
+        let result = f(self);
+        self.plane = outer;
+        result
+    }
+
+    /// Determine the parts of a value that are assigned separately.
+    fn assign_planes(&self, ty: &'gcx UnpackedType<'gcx>) -> &'static [Plane] {
+        if self.is_paired(ty) {
+            &[Plane::Value, Plane::Unknown]
+        } else {
+            &[Plane::Value]
+        }
+    }
+
+    /// Map a type to the LLHD type of the part of its values being emitted.
+    fn plane_type(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
+        match self.plane {
+            Plane::Both => self.emit_type(ty),
+            _ => self.emit_two_state_type(ty),
+        }
+    }
+
+    /// Select the part being emitted from a stored value, signal, or pointer.
+    fn emit_plane_of(
+        &mut self,
+        value: llhd::ir::Value,
+        ty: &'gcx UnpackedType<'gcx>,
+    ) -> llhd::ir::Value {
+        if !self.is_paired(ty) {
+            return value;
+        }
+        match self.plane {
+            Plane::Both => value,
+            Plane::Value => self.builder.ins().ext_field(value, 0),
+            Plane::Unknown => self.builder.ins().ext_field(value, 1),
+        }
+    }
+
     /// Clear the cached MIR lvalues and rvalues. This should be called before
     /// or after emitting an expression, and at least for every statement.
     /// Otherwise MIR codegen might reuse values that have become out-of-date
@@ -727,6 +1030,34 @@ where
         self.interned_rvalues.clear();
     }
 
+    /// Attach a source location to the instructions emitted so far.
+    ///
+    /// Only instructions which do not carry a location yet are annotated, such
+    /// that nested constructs keep their more precise location. Does nothing
+    /// unless debug info has been requested.
+    ///
+    /// LLHD numbers instructions in the order they are created, and code
+    /// generation never removes any, such that the instructions emitted since
+    /// the previous call are the ones from `next_located` up to the first
+    /// index which is not in the layout.
+    fn emit_location(&mut self, span: Span) {
+        use llhd::table::TableKey;
+        if !self.sess().opts.debug_info {
+            return;
+        }
+        let hint = span.begin().to_hint();
+        loop {
+            let inst = llhd::ir::Inst::new(self.next_located);
+            if !self.builder.is_inst_inserted(inst) {
+                break;
+            }
+            if self.builder.location_hint(inst).is_none() {
+                self.builder.set_location_hint(inst, hint);
+            }
+            self.next_located += 1;
+        }
+    }
+
     /// Emit the code for the contents of a module.
     fn emit_module_block(
         &mut self,
@@ -745,6 +1076,7 @@ where
             let value = self.emit_varnet_decl(decl_id, ty, env, hir.init)?;
             self.builder.set_name(value, hir.name.value.into());
             self.values.insert(decl_id.into(), value.into());
+            self.emit_location(hir.span);
         }
 
         // Emit interface instances.
@@ -815,6 +1147,7 @@ where
                     .ins()
                     .con(signal_lookup[&port.port.id], assigned);
             }
+            self.emit_location(inst.hir.span);
         }
 
         // Emit assignments.
@@ -835,20 +1168,26 @@ where
 
             // Check for sanity.
             for &assign in &simplified {
-                assert_type!(assign.rhs.ty, assign.lhs.ty, assign.rhs.span, self.cx);
                 if assign.is_error() {
                     return Err(());
                 }
+                assert_type!(assign.rhs.ty, assign.lhs.ty, assign.rhs.span, self.cx);
             }
 
             // Emit the assignments.
             let delay = llhd::value::TimeValue::new(num::zero(), 0, 1);
             let delay = self.builder.ins().const_time(delay);
             for &assign in &simplified {
-                let lhs = self.emit_mir_lvalue(assign.lhs)?;
-                let rhs = self.emit_mir_rvalue(assign.rhs)?;
-                self.builder.ins().drv(lhs.0, rhs, delay);
+                for &plane in self.assign_planes(assign.lhs.ty) {
+                    self.with_plane(plane, |gen| {
+                        let lhs = gen.emit_mir_lvalue(assign.lhs)?;
+                        let rhs = gen.emit_mir_rvalue(assign.rhs)?;
+                        gen.builder.ins().drv(lhs.0, rhs, delay);
+                        Ok(())
+                    })?;
+                }
             }
+            self.emit_location(hir.span);
         }
 
         // Emit module instantiations.
@@ -876,10 +1215,9 @@ where
             )?;
 
             // Instantiate the module.
-            let ext_unit = self.builder.add_extern(
-                self.into.unit(target.unit).name().clone(),
-                self.into.unit(target.unit).sig().clone(),
-            );
+            let ext_unit = self
+                .builder
+                .add_extern(target.name.clone(), target.ports.sig.clone());
             if !inst.hir.ast.dims.is_empty() {
                 bug_span!(
                     inst.hir.ast.span(),
@@ -887,8 +1225,13 @@ where
                     "instance arrays of modules not supported"
                 );
             }
-            self.builder.ins().inst(ext_unit, inputs, outputs);
-            // TODO: Annotate instance name once LLHD allows that.
+            let inst_id = self.builder.ins().inst(ext_unit, inputs, outputs);
+            let hint = inst.hir.name.span.begin().to_hint();
+            self.builder.set_location_hint(inst_id, hint);
+            self.tables
+                .instance_names
+                .insert(hint, inst.hir.name.value.to_string());
+            self.emit_location(inst.hir.span);
         }
 
         // Emit generate blocks.
@@ -921,20 +1264,29 @@ where
                 } => {
                     let mut local_env = env;
                     for &i in init {
-                        local_env = self.execute_genvar_init(i, local_env)?;
+                        local_env = execute_genvar_init(self.cx, i, local_env)?;
                     }
                     while self.constant_value_of(cond, local_env).is_true() {
                         self.emit_module_block(id, local_env, body, name_prefix)?;
-                        local_env = self.execute_genvar_step(step, local_env)?;
+                        local_env = execute_genvar_step(self.cx, step, local_env)?;
                     }
                 }
                 _ => return self.unimp_msg("code generation for", hir),
             }
         }
 
-        // Emit and instantiate procedures.
+        // Emit and instantiate procedures. Memory writes are emitted as
+        // registers directly in the entity, and are skipped in the procedure.
         for &proc_id in &hir.procs {
-            let prok = self.emit_procedure(proc_id, env, name_prefix)?;
+            let mut memories = vec![];
+            if let Some(inf) = self.infer_procedure(proc_id, env)? {
+                self.emit_memory_writes(&inf, env)?;
+                if inf.only_memories {
+                    continue;
+                }
+                memories.extend(inf.memories.iter().map(|w| w.memory));
+            }
+            let prok = self.emit_procedure(proc_id, env, name_prefix, &memories)?;
             let lookup_value = |&id: &AccessedNode| match self.values.get(&id) {
                 Some(v) => v.clone(),
                 None => {
@@ -956,11 +1308,114 @@ where
                 self.into.unit(prok.unit).sig().clone(),
             );
             self.builder.ins().inst(ext_unit, inputs, outputs);
+            self.emit_location(self.span(proc_id));
         }
 
         Ok(())
     }
 
+    /// Emit the memory write ports inferred from a procedure.
+    ///
+    /// Each write becomes a register on the addressed element of the array,
+    /// triggered by the clock of the procedure and gated by the conditions
+    /// under which the write happens.
+    fn emit_memory_writes(&mut self, inf: &ProcInference, env: ParamEnv) -> Result<()> {
+        let (clock, edge) = match inf.clock {
+            Some(clock) if !inf.memories.is_empty() => clock,
+            _ => return Ok(()),
+        };
+        let mode = match edge {
+            ast::EdgeIdent::Posedge => llhd::ir::RegMode::Rise,
+            ast::EdgeIdent::Negedge => llhd::ir::RegMode::Fall,
+            _ => llhd::ir::RegMode::Both,
+        };
+        let clock = self.emitted_value(clock);
+        let name = self
+            .builder
+            .get_name(clock)
+            .map(|name| format!("{}.prb", name));
+
+        // A paired clock triggers on its value bits.
+        let clock_bits = if self.llhd_type(clock).unwrap_signal().is_struct() {
+            self.builder.ins().ext_field(clock, 0)
+        } else {
+            clock
+        };
+        let trigger = self.builder.ins().prb(clock_bits);
+        if let Some(name) = name {
+            self.builder.set_name(trigger, name);
+        }
+        for write in &inf.memories {
+            self.tables.memory_writes.insert((write.assign, env));
+            let mut gate = None;
+            for &(cond, polarity) in &write.conds {
+                // An unknown condition takes the `else` branch.
+                let mut value = self.emit_rvalue_bool(cond, env)?;
+                if let Some(known) = self.emit_rvalue_known(cond, env)? {
+                    value = self.builder.ins().and(value, known);
+                }
+                if !polarity {
+                    value = self.builder.ins().not(value);
+                }
+                gate = Some(match gate {
+                    Some(gate) => self.builder.ins().and(gate, value),
+                    None => value,
+                });
+            }
+            let hir = match self.hir_of(write.assign)? {
+                HirNode::Stmt(x) => x,
+                _ => unreachable!(),
+            };
+            let (lhs, rhs, kind) = match hir.kind {
+                hir::StmtKind::Assign { lhs, rhs, kind } => (lhs, rhs, kind),
+                _ => unreachable!(),
+            };
+
+            // Writes to an unknown address have no effect.
+            if let HirNode::Expr(&hir::Expr {
+                kind: hir::ExprKind::Index(_, hir::IndexMode::One(index)),
+                ..
+            }) = self.hir_of(lhs)?
+            {
+                if let Some(known) = self.emit_rvalue_known(index, env)? {
+                    gate = Some(match gate {
+                        Some(gate) => self.builder.ins().and(gate, known),
+                        None => known,
+                    });
+                }
+            }
+            let assign_mir =
+                self.mir_assignment_from_procedural(write.assign, lhs, rhs, env, hir.span, kind);
+            let simplified = self.mir_simplify_assignment(Ref(assign_mir));
+            for &assign in &simplified {
+                if assign.is_error() {
+                    return Err(());
+                }
+                assert_type!(assign.rhs.ty, assign.lhs.ty, assign.rhs.span, self.cx);
+            }
+            for &assign in &simplified {
+                for &plane in self.assign_planes(assign.lhs.ty) {
+                    self.with_plane(plane, |gen| {
+                        let lhs = gen.emit_mir_lvalue(assign.lhs)?;
+                        let data = gen.emit_mir_rvalue(assign.rhs)?;
+                        gen.builder.ins().reg(
+                            lhs.0,
+                            vec![llhd::ir::RegTrigger {
+                                data,
+                                mode,
+                                trigger,
+                                gate,
+                            }],
+                        );
+                        Ok(())
+                    })?;
+                }
+            }
+            self.emit_location(hir.span);
+        }
+        Ok(())
+    }
+
     /// Emit code for the connections made in a port list.
     fn emit_port_connections(
         &mut self,
@@ -1011,77 +1466,81 @@ where
 
         // Connect to the actual internal ports emitted as the module's port
         // interface.
+        // Ports carry entire values, including the unknown bits of paired
+        // four-valued values.
         let mut map_port = |port: &ModulePort<'gcx>, lvalue: bool| {
-            trace!(
-                "Mapping port `{}` of type `{}` as {}",
-                port.name,
-                port.ty,
-                match lvalue {
-                    true => "lvalue",
-                    false => "rvalue",
-                }
-            );
-            if let Some(&mapping) = port_mapping_int.get(&port.port.id) {
-                // Emit the assigned node as rvalue or lvalue, depending on
-                // the port direction.
-                if lvalue {
-                    let mir = self.mir_lvalue(mapping.id(), mapping.env());
-                    if mir.is_error() {
-                        return Err(());
+            self.with_plane(Plane::Both, |gen| {
+                trace!(
+                    "Mapping port `{}` of type `{}` as {}",
+                    port.name,
+                    port.ty,
+                    match lvalue {
+                        true => "lvalue",
+                        false => "rvalue",
                     }
-                    let mir = match port.kind {
-                        ModulePortKind::Port => mir,
-                        ModulePortKind::IntfSignal { decl_id, env, .. } => {
-                            self.arena().alloc_mir_lvalue(mir::Lvalue {
-                                id: NodeId::alloc(),
-                                origin: mir.origin,
-                                env,
-                                span: mir.span,
-                                ty: port.ty,
-                                kind: mir::LvalueKind::IntfSignal(mir, decl_id),
-                            })
+                );
+                if let Some(&mapping) = port_mapping_int.get(&port.port.id) {
+                    // Emit the assigned node as rvalue or lvalue, depending on
+                    // the port direction.
+                    if lvalue {
+                        let mir = gen.mir_lvalue(mapping.id(), mapping.env());
+                        if mir.is_error() {
+                            return Err(());
                         }
-                    };
-                    self.emit_mir_lvalue(mir).map(|(x, _)| x)
-                } else {
-                    let mir = self.mir_rvalue(mapping.id(), mapping.env());
-                    if mir.is_error() {
-                        return Err(());
+                        let mir = match port.kind {
+                            ModulePortKind::Port => mir,
+                            ModulePortKind::IntfSignal { decl_id, env, .. } => {
+                                gen.arena().alloc_mir_lvalue(mir::Lvalue {
+                                    id: NodeId::alloc(),
+                                    origin: mir.origin,
+                                    env,
+                                    span: mir.span,
+                                    ty: port.ty,
+                                    kind: mir::LvalueKind::IntfSignal(mir, decl_id),
+                                })
+                            }
+                        };
+                        gen.emit_mir_lvalue(mir).map(|(x, _)| x)
+                    } else {
+                        let mir = gen.mir_rvalue(mapping.id(), mapping.env());
+                        if mir.is_error() {
+                            return Err(());
+                        }
+                        let mir = match port.kind {
+                            ModulePortKind::Port => mir,
+                            ModulePortKind::IntfSignal { decl_id, env, .. } => {
+                                gen.arena().alloc_mir_rvalue(mir::Rvalue {
+                                    id: NodeId::alloc(),
+                                    origin: mir.origin,
+                                    env,
+                                    span: mir.span,
+                                    ty: port.ty,
+                                    kind: mir::RvalueKind::IntfSignal(mir, decl_id),
+                                    konst: false,
+                                })
+                            }
+                        };
+                        gen.emit_mir_rvalue_mode(mir, Mode::Signal)
                     }
-                    let mir = match port.kind {
-                        ModulePortKind::Port => mir,
-                        ModulePortKind::IntfSignal { decl_id, env, .. } => {
-                            self.arena().alloc_mir_rvalue(mir::Rvalue {
-                                id: NodeId::alloc(),
-                                origin: mir.origin,
-                                env,
-                                span: mir.span,
-                                ty: port.ty,
-                                kind: mir::RvalueKind::IntfSignal(mir, decl_id),
-                                konst: false,
-                            })
+                } else {
+                    // Emit an auxiliary signal with the default value for this
+                    // port or type.
+                    let ty = gen.type_of_int_port(Ref(port.port), inst.inner_env);
+                    let value = match port.port.data.as_ref().and_then(|d| d.default) {
+                        Some(default) => {
+                            gen.emit_rvalue_mode(default, inst.inner_env, Mode::Signal)?
+                        }
+                        None => {
+                            let v = gen.type_default_value(ty);
+                            let v = gen.emit_default_const(v, inst.inner_env, port.port.span)?;
+                            gen.builder.ins().sig(v)
                         }
                     };
-                    self.emit_mir_rvalue_mode(mir, Mode::Signal)
+                    gen.builder
+                        .set_name(value, format!("{}.{}.default", inst.hir.name, port.name));
+                    Ok(value)
                 }
-            } else {
-                // Emit an auxiliary signal with the default value for this
-                // port or type.
-                let ty = self.type_of_int_port(Ref(port.port), inst.inner_env);
-                let value = match port.port.data.as_ref().and_then(|d| d.default) {
-                    Some(default) => {
-                        self.emit_rvalue_mode(default, inst.inner_env, Mode::Signal)?
-                    }
-                    None => {
-                        let v = self.type_default_value(ty);
-                        let v = self.emit_const(v, inst.inner_env, port.port.span)?;
-                        self.builder.ins().sig(v)
-                    }
-                };
-                self.builder
-                    .set_name(value, format!("{}.{}.default", inst.hir.name, port.name));
-                Ok(value)
-            }
+            })
         };
         let inputs = inputs
             .iter()
@@ -1101,6 +1560,27 @@ where
         env: ParamEnv,
         span: Span,
     ) -> Result<llhd::ir::Value> {
+        if self.plane == Plane::Both && self.is_paired(value.ty) {
+            let bits = self.with_plane(Plane::Value, |gen| gen.emit_const(value, env, span))?;
+            let unknown =
+                self.with_plane(Plane::Unknown, |gen| gen.emit_const(value, env, span))?;
+            return Ok(self.builder.ins().strukt(vec![bits, unknown]));
+        }
+        if self.plane == Plane::Unknown && !self.is_paired(value.ty) {
+            let ty = self.emit_two_state_type(value.ty)?;
+            return Ok(self.emit_zero_for_type(&ty));
+        }
+        if self.sess().opts.four_state == FourState::TwoState
+            && !self.emitting_default
+            && has_unknown_bits(value)
+            && self.tables.two_state_warnings.insert(span)
+        {
+            self.emit(
+                DiagBuilder2::warning("`x` and `z` bits lowered to 0")
+                    .span(span)
+                    .add_note("Use `--four-state=paired` to preserve unknown bits"),
+            );
+        }
         if let Some(x) = self.interned_consts.get(value) {
             x.clone()
         } else {
@@ -1110,6 +1590,22 @@ where
         }
     }
 
+    /// Map the default value of a type to an LLHD constant.
+    ///
+    /// Unlike `emit_const`, this does not warn about `x` bits lowered to 0 in
+    /// two-state mode, since they stem from the type rather than the source.
+    fn emit_default_const(
+        &mut self,
+        value: Value<'gcx>,
+        env: ParamEnv,
+        span: Span,
+    ) -> Result<llhd::ir::Value> {
+        let outer = std::mem::replace(&mut self.emitting_default, true);
+        let result = self.emit_const(value, env, span);
+        self.emitting_default = outer;
+        result
+    }
+
     /// Map a value to an LLHD constant.
     fn emit_const_uninterned(
         &mut self,
@@ -1121,9 +1617,13 @@ where
             return Err(());
         }
         match value.kind {
-            ValueKind::Int(ref k, ..) => {
+            ValueKind::Int(ref k, ref special_bits, _) => {
                 let size = value.ty.simple_bit_vector(self.cx, span).size;
-                Ok(self.builder.ins().const_int((size, k.clone())))
+                let k = match self.plane {
+                    Plane::Unknown => mask_to_int(special_bits),
+                    _ => k.clone(),
+                };
+                Ok(self.builder.ins().const_int((size, k)))
             }
             ValueKind::Time(ref k) => Ok(self
                 .builder
@@ -1188,6 +1688,25 @@ where
         }
     }
 
+    /// Emit a value of an LLHD type with all bits set.
+    fn emit_ones_for_type(&mut self, ty: &llhd::Type) -> llhd::ir::Value {
+        match **ty {
+            llhd::IntType(_) => {
+                let zero = self.emit_zero_for_type(ty);
+                self.builder.ins().not(zero)
+            }
+            llhd::ArrayType(l, ref ty) => {
+                let inner = self.emit_ones_for_type(ty);
+                self.builder.ins().array_uniform(l, inner)
+            }
+            llhd::StructType(ref tys) => {
+                let inner = tys.iter().map(|ty| self.emit_ones_for_type(ty)).collect();
+                self.builder.ins().strukt(inner)
+            }
+            _ => self.emit_zero_for_type(ty),
+        }
+    }
+
     /// Get the type of an LLHD value.
     fn llhd_type(&self, value: llhd::ir::Value) -> llhd::Type {
         self.builder.value_type(value)
@@ -1220,12 +1739,29 @@ where
         mir: &'gcx mir::Rvalue<'gcx>,
         mode: Mode,
     ) -> Result<llhd::ir::Value> {
-        let (value, actual_mode) = if let Some(x) = self.interned_rvalues.get(&(mir.id, mode)) {
-            x.clone()?
-        } else {
-            let x = self.emit_mir_rvalue_uninterned(mir, mode);
-            self.interned_rvalues.insert((mir.id, mode), x);
-            x?
+        let paired = self.is_paired(mir.ty);
+        let (value, actual_mode) = match self.plane {
+            // Stored values are emitted as is, or assembled from their parts.
+            Plane::Both if !paired => {
+                return self.with_plane(Plane::Value, |gen| gen.emit_mir_rvalue_mode(mir, mode));
+            }
+            Plane::Both => match mir.kind {
+                mir::RvalueKind::Var(..) | mir::RvalueKind::Port(..) => {
+                    self.emit_mir_rvalue_interned(mir, mode)?
+                }
+                _ => {
+                    let bits = self.with_plane(Plane::Value, |gen| gen.emit_mir_rvalue(mir))?;
+                    let unknown =
+                        self.with_plane(Plane::Unknown, |gen| gen.emit_mir_rvalue(mir))?;
+                    (self.builder.ins().strukt(vec![bits, unknown]), Mode::Value)
+                }
+            },
+            // Two-valued expressions have no unknown bits.
+            Plane::Unknown if !paired => {
+                let ty = self.emit_two_state_type(mir.ty)?;
+                (self.emit_zero_for_type(&ty), Mode::Value)
+            }
+            _ => self.emit_mir_rvalue_interned(mir, mode)?,
         };
 
         match (mode, actual_mode) {
@@ -1243,19 +1779,38 @@ where
         }
     }
 
+    /// Emit the code for an MIR rvalue (interned).
+    fn emit_mir_rvalue_interned(
+        &mut self,
+        mir: &'gcx mir::Rvalue<'gcx>,
+        mode: Mode,
+    ) -> Result<(llhd::ir::Value, Mode)> {
+        let key = (mir.id, mode, self.plane);
+        if let Some(x) = self.interned_rvalues.get(&key) {
+            x.clone()
+        } else {
+            let x = self.emit_mir_rvalue_uninterned(mir, mode);
+            self.interned_rvalues.insert(key, x);
+            x
+        }
+    }
+
     /// Emit the code for an MIR rvalue.
     ///
-    /// Wrapper around `emit_mir_rvalue_inner` that asserts the LLHD type of the
-    /// result matches expectations.
+    /// Wrapper around `emit_mir_rvalue_inner` and `emit_mir_rvalue_unknown`
+    /// that asserts the LLHD type of the result matches expectations.
     fn emit_mir_rvalue_uninterned(
         &mut self,
         mir: &'gcx mir::Rvalue<'gcx>,
         mode_hint: Mode,
     ) -> Result<(llhd::ir::Value, Mode)> {
-        let result = self.emit_mir_rvalue_inner(mir, mode_hint);
+        let result = match self.plane {
+            Plane::Unknown => self.emit_mir_rvalue_unknown(mir, mode_hint),
+            _ => self.emit_mir_rvalue_inner(mir, mode_hint),
+        };
         match result {
             Ok((result, actual_mode)) => {
-                let llty_exp = self.emit_type(mir.ty)?;
+                let llty_exp = self.plane_type(mir.ty)?;
                 let llty_exp = match actual_mode {
                     Mode::Value => llty_exp,
                     Mode::Signal => llhd::signal_ty(llty_exp),
@@ -1305,6 +1860,7 @@ where
                     .get(&id.into())
                     .cloned()
                     .unwrap_or_else(|| self.emitted_value(id));
+                let sig = self.emit_plane_of(sig, mir.ty);
                 if mode_hint == Mode::Signal && self.llhd_type(sig).is_signal() {
                     return Ok((sig, Mode::Signal));
                 } else {
@@ -1322,7 +1878,7 @@ where
             // Interface signals require special care, because they are emitted
             // in a transposed fashion.
             mir::RvalueKind::IntfSignal(value, signal) => {
-                return self.emit_rvalue_interface(value, signal, mode_hint);
+                return self.emit_rvalue_interface(value, signal, mir.ty, mode_hint);
             }
 
             mir::RvalueKind::CastValueDomain { value, .. } => {
@@ -1355,7 +1911,7 @@ where
 
             mir::RvalueKind::ZeroExtend(_, value) => {
                 let width = value.ty.simple_bit_vector(self.cx, value.span).size;
-                let llty = self.emit_type(mir.ty)?;
+                let llty = self.plane_type(mir.ty)?;
                 let result = self.emit_zero_for_type(&llty);
                 let value = self.emit_mir_rvalue(value)?;
                 let result = self.builder.ins().ins_slice(result, value, 0, width);
@@ -1365,7 +1921,7 @@ where
 
             mir::RvalueKind::SignExtend(_, value) => {
                 let width = value.ty.simple_bit_vector(self.cx, value.span).size;
-                let llty = self.emit_type(mir.ty)?;
+                let llty = self.plane_type(mir.ty)?;
                 let value = self.emit_mir_rvalue(value)?;
                 let sign = self.builder.ins().ext_slice(value, width - 1, 1);
                 let zeros = self.emit_zero_for_type(&llty);
@@ -1384,7 +1940,7 @@ where
                 Ok(self.builder.ins().array(llvalue))
             }
 
-            mir::RvalueKind::ConstructStruct(ref members) => {
+            mir::RvalueKind::ConstructStruct(members) => {
                 let members = members
                     .iter()
                     .map(|&v| self.emit_mir_rvalue(v))
@@ -1533,9 +2089,9 @@ where
                 })
             }
 
-            mir::RvalueKind::Concat(ref values) => {
+            mir::RvalueKind::Concat(values) => {
                 let mut offset = 0;
-                let llty = self.emit_type(mir.ty)?;
+                let llty = self.plane_type(mir.ty)?;
                 let mut result = self.emit_zero_for_type(&llty);
                 trace!(
                     "Concatenating {} values into `{}` (as `{}`)",
@@ -1562,7 +2118,7 @@ where
             mir::RvalueKind::Repeat(times, value) => {
                 let width = value.ty.simple_bit_vector(self.cx, value.span).size;
                 let value = self.emit_mir_rvalue(value)?;
-                let llty = self.emit_type(mir.ty)?;
+                let llty = self.plane_type(mir.ty)?;
                 let mut result = self.emit_zero_for_type(&llty);
                 for i in 0..times {
                     result = self
@@ -1655,6 +2211,189 @@ where
         value.map(|v| (v, Mode::Value))
     }
 
+    /// Emit the code for the unknown bits of an MIR rvalue.
+    ///
+    /// Operations which only move bits around are emitted by
+    /// `emit_mir_rvalue_inner`, which then operates on the unknown bits of its
+    /// operands.
+    fn emit_mir_rvalue_unknown(
+        &mut self,
+        mir: &'gcx mir::Rvalue<'gcx>,
+        mode_hint: Mode,
+    ) -> Result<(llhd::ir::Value, Mode)> {
+        if mir.is_const() {
+            return self.emit_mir_rvalue_inner(mir, mode_hint);
+        }
+
+        let value = match mir.kind {
+            // Casts which keep the bits unchanged.
+            mir::RvalueKind::CastValueDomain { value, .. }
+            | mir::RvalueKind::Transmute(value)
+            | mir::RvalueKind::CastSign(_, value) => {
+                if self.is_paired(value.ty) {
+                    return self.emit_mir_rvalue_unknown(value, mode_hint);
+                }
+                let ty = self.emit_two_state_type(mir.ty)?;
+                Ok(self.emit_zero_for_type(&ty))
+            }
+
+            mir::RvalueKind::Index {
+                value,
+                base,
+                length,
+            } => {
+                let target = self.emit_mir_rvalue(value)?;
+                let unknown = self.emit_rvalue_index(value.ty, target, base, length)?;
+                self.emit_unknown_if_any(unknown, &[base])
+            }
+
+            mir::RvalueKind::UnaryBitwise { arg, .. } => self.emit_mir_rvalue(arg),
+
+            mir::RvalueKind::BinaryBitwise { op, lhs, rhs } => {
+                let lhs_unknown = self.emit_mir_rvalue(lhs)?;
+                let rhs_unknown = self.emit_mir_rvalue(rhs)?;
+                let (lhs_bits, rhs_bits) = self.with_plane(Plane::Value, |gen| {
+                    Ok((gen.emit_mir_rvalue(lhs)?, gen.emit_mir_rvalue(rhs)?))
+                })?;
+                let unknown = self.builder.ins().or(lhs_unknown, rhs_unknown);
+                Ok(match op {
+                    // Bits which are 0 in either operand are known to be 0.
+                    mir::BinaryBitwiseOp::And => {
+                        let lhs_maybe_one = self.builder.ins().or(lhs_bits, lhs_unknown);
+                        let rhs_maybe_one = self.builder.ins().or(rhs_bits, rhs_unknown);
+                        let unknown = self.builder.ins().and(unknown, lhs_maybe_one);
+                        self.builder.ins().and(unknown, rhs_maybe_one)
+                    }
+                    // Bits which are 1 in either operand are known to be 1.
+                    mir::BinaryBitwiseOp::Or => {
+                        let lhs_known = self.builder.ins().not(lhs_unknown);
+                        let rhs_known = self.builder.ins().not(rhs_unknown);
+                        let lhs_one = self.builder.ins().and(lhs_bits, lhs_known);
+                        let rhs_one = self.builder.ins().and(rhs_bits, rhs_known);
+                        let one = self.builder.ins().or(lhs_one, rhs_one);
+                        let maybe_zero = self.builder.ins().not(one);
+                        self.builder.ins().and(unknown, maybe_zero)
+                    }
+                    mir::BinaryBitwiseOp::Xor => unknown,
+                })
+            }
+
+            mir::RvalueKind::Shift {
+                op,
+                arith,
+                value,
+                amount,
+            } => {
+                let unknown = self.emit_mir_rvalue(value)?;
+                let amount_bits =
+                    self.with_plane(Plane::Value, |gen| gen.emit_mir_rvalue(amount))?;
+                let unknown_ty = self.llhd_type(unknown);
+                let hidden = self.emit_zero_for_type(&unknown_ty);
+                let hidden = if arith && op == mir::ShiftOp::Right {
+                    let ones = self.builder.ins().not(hidden);
+                    let sign =
+                        self.builder
+                            .ins()
+                            .ext_slice(unknown, unknown_ty.unwrap_int() - 1, 1);
+                    let mux = self.builder.ins().array(vec![hidden, ones]);
+                    self.builder.ins().mux(mux, sign)
+                } else {
+                    hidden
+                };
+                let shifted = match op {
+                    mir::ShiftOp::Left => self.builder.ins().shl(unknown, hidden, amount_bits),
+                    mir::ShiftOp::Right => self.builder.ins().shr(unknown, hidden, amount_bits),
+                };
+                self.emit_unknown_if_any(shifted, &[amount])
+            }
+
+            // If the condition is unknown, the result is unknown wherever the
+            // two values may differ.
+            mir::RvalueKind::Ternary {
+                cond,
+                true_value,
+                false_value,
+            } => {
+                let cond_unknown = self.emit_mir_rvalue(cond)?;
+                let true_unknown = self.emit_mir_rvalue(true_value)?;
+                let false_unknown = self.emit_mir_rvalue(false_value)?;
+                let (cond_bits, true_bits, false_bits) = self.with_plane(Plane::Value, |gen| {
+                    Ok((
+                        gen.emit_mir_rvalue(cond)?,
+                        gen.emit_mir_rvalue(true_value)?,
+                        gen.emit_mir_rvalue(false_value)?,
+                    ))
+                })?;
+                let array = self.builder.ins().array(vec![false_unknown, true_unknown]);
+                let selected = self.builder.ins().mux(array, cond_bits);
+                let ty = self.llhd_type(selected);
+                let merged = if ty.is_int() {
+                    let differ = self.builder.ins().xor(true_bits, false_bits);
+                    let unknown = self.builder.ins().or(true_unknown, false_unknown);
+                    self.builder.ins().or(unknown, differ)
+                } else {
+                    self.emit_ones_for_type(&ty)
+                };
+                let array = self.builder.ins().array(vec![selected, merged]);
+                let zero = self.emit_zero_for_type(&self.llhd_type(cond_unknown));
+                let cond_unknown = self.builder.ins().neq(cond_unknown, zero);
+                Ok(self.builder.ins().mux(array, cond_unknown))
+            }
+
+            // The assignment itself is emitted together with the value bits.
+            mir::RvalueKind::Assignment { result, .. } => self.emit_mir_rvalue(result),
+
+            // All other operations are entirely unknown if any of their
+            // operands has an unknown bit.
+            mir::RvalueKind::CastToBool(arg)
+            | mir::RvalueKind::IntUnaryArith { arg, .. }
+            | mir::RvalueKind::Reduction { arg, .. } => {
+                let ty = self.emit_two_state_type(mir.ty)?;
+                let zero = self.emit_zero_for_type(&ty);
+                self.emit_unknown_if_any(zero, &[arg])
+            }
+            mir::RvalueKind::IntComp { lhs, rhs, .. }
+            | mir::RvalueKind::IntBinaryArith { lhs, rhs, .. } => {
+                let ty = self.emit_two_state_type(mir.ty)?;
+                let zero = self.emit_zero_for_type(&ty);
+                self.emit_unknown_if_any(zero, &[lhs, rhs])
+            }
+
+            _ => return self.emit_mir_rvalue_inner(mir, mode_hint),
+        };
+
+        value.map(|v| (v, Mode::Value))
+    }
+
+    /// Make all bits of the unknown bits of a value unknown if any operand
+    /// has an unknown bit.
+    fn emit_unknown_if_any(
+        &mut self,
+        unknown: llhd::ir::Value,
+        args: &[&'gcx mir::Rvalue<'gcx>],
+    ) -> Result<llhd::ir::Value> {
+        let mut any = None;
+        for &arg in args {
+            if !self.is_paired(arg.ty) {
+                continue;
+            }
+            let arg_unknown = self.with_plane(Plane::Unknown, |gen| gen.emit_mir_rvalue(arg))?;
+            let zero = self.emit_zero_for_type(&self.llhd_type(arg_unknown));
+            let arg_any = self.builder.ins().neq(arg_unknown, zero);
+            any = Some(match any {
+                Some(any) => self.builder.ins().or(any, arg_any),
+                None => arg_any,
+            });
+        }
+        let any = match any {
+            Some(any) => any,
+            None => return Ok(unknown),
+        };
+        let ones = self.emit_ones_for_type(&self.llhd_type(unknown));
+        let array = self.builder.ins().array(vec![unknown, ones]);
+        Ok(self.builder.ins().mux(array, any))
+    }
+
     fn emit_prb_or_var(&mut self, sig: llhd::ir::Value) -> llhd::ir::Value {
         match *self.llhd_type(sig) {
             llhd::SignalType(_) => {
@@ -1678,6 +2417,9 @@ where
     /// Emit the code for an rvalue converted to a boolean..
     fn emit_rvalue_bool(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<llhd::ir::Value> {
         let mir = self.mir_rvalue(expr_id, env);
+        if mir.is_error() {
+            return Err(());
+        }
         assert_span!(
             mir.ty
                 .get_simple_bit_vector()
@@ -1691,6 +2433,26 @@ where
         self.emit_mir_rvalue(mir)
     }
 
+    /// Emit a bool that is set if none of the bits of an rvalue are unknown.
+    ///
+    /// Returns `None` if the rvalue has no unknown bits.
+    fn emit_rvalue_known(
+        &mut self,
+        expr_id: NodeId,
+        env: ParamEnv,
+    ) -> Result<Option<llhd::ir::Value>> {
+        let mir = self.mir_rvalue(expr_id, env);
+        if mir.is_error() {
+            return Err(());
+        }
+        if !self.is_paired(mir.ty) {
+            return Ok(None);
+        }
+        let unknown = self.with_plane(Plane::Unknown, |gen| gen.emit_mir_rvalue(mir))?;
+        let zero = self.emit_zero_for_type(&self.llhd_type(unknown));
+        Ok(Some(self.builder.ins().eq(unknown, zero)))
+    }
+
     /// Emit the code for an MIR rvalue interface.
     ///
     /// This is a bit tricky, since we transpose interface arrays to array
@@ -1700,6 +2462,7 @@ where
         &mut self,
         mir: &mir::Rvalue<'gcx>,
         signal: NodeId,
+        ty: &'gcx UnpackedType<'gcx>,
         mode_hint: Mode,
     ) -> Result<(llhd::ir::Value, Mode)> {
         match mir.kind {
@@ -1710,6 +2473,7 @@ where
                     .get(&id)
                     .cloned()
                     .unwrap_or_else(|| self.emitted_value(id));
+                let sig = self.emit_plane_of(sig, ty);
                 debug!(
                     "{:?} emitted value is {:?} (type {})",
                     id,
@@ -1728,7 +2492,8 @@ where
                 base,
                 length,
             } => {
-                let (inner, actual_mode) = self.emit_rvalue_interface(value, signal, mode_hint)?;
+                let (inner, actual_mode) =
+                    self.emit_rvalue_interface(value, signal, ty, mode_hint)?;
                 self.emit_rvalue_index(value.ty, inner, base, length)
                     .map(|v| (v, actual_mode))
             }
@@ -1751,9 +2516,16 @@ where
         base: &'gcx mir::Rvalue<'gcx>,
         length: usize,
     ) -> Result<llhd::ir::Value> {
-        let base = self.emit_mir_rvalue(base)?;
-        let hidden = self.emit_zero_for_type(&self.llhd_type(value));
-        // TODO(fschuiki): make the above a constant of all `x`.
+        let base = self.with_plane(Plane::Value, |gen| gen.emit_mir_rvalue(base))?;
+        let ty_value = self.llhd_type(value);
+        let hidden = self.emit_zero_for_type(&ty_value);
+        // TODO(fschuiki): make the above a constant of all `x`. This is only
+        // possible for the unknown bits of paired four-valued values.
+        let hidden = if self.plane == Plane::Unknown && ty_value.is_int() {
+            self.builder.ins().not(hidden)
+        } else {
+            hidden
+        };
         let shifted = self.builder.ins().shr(value, hidden, base);
         if ty.coalesces_to_llhd_scalar() {
             let length = std::cmp::max(1, length);
@@ -1772,11 +2544,34 @@ where
         &mut self,
         mir: &mir::Lvalue<'gcx>,
     ) -> Result<(llhd::ir::Value, Option<llhd::ir::Value>)> {
-        if let Some(x) = self.interned_lvalues.get(&mir.id) {
+        if self.plane == Plane::Both {
+            if !self.is_paired(mir.ty) {
+                return self.with_plane(Plane::Value, |gen| gen.emit_mir_lvalue(mir));
+            }
+            match mir.kind {
+                mir::LvalueKind::Var(..) | mir::LvalueKind::Port(..) => (),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` cannot be connected to an output port",
+                            mir.span.extract()
+                        ))
+                        .span(mir.span)
+                        .add_note(
+                            "Only entire four-valued signals can be connected to output ports \
+                             with `--four-state=paired`",
+                        ),
+                    );
+                    return Err(());
+                }
+            }
+        }
+        let key = (mir.id, self.plane);
+        if let Some(x) = self.interned_lvalues.get(&key) {
             x.clone()
         } else {
             let x = self.emit_mir_lvalue_uninterned(mir);
-            self.interned_lvalues.insert(mir.id, x);
+            self.interned_lvalues.insert(key, x);
             x
         }
     }
@@ -1792,8 +2587,8 @@ where
         let result = self.emit_mir_lvalue_inner(mir);
         match result {
             Ok((sig, var)) => {
-                let llty_exp1 = llhd::signal_ty(self.emit_type(mir.ty)?);
-                let llty_exp2 = llhd::pointer_ty(self.emit_type(mir.ty)?);
+                let llty_exp1 = llhd::signal_ty(self.plane_type(mir.ty)?);
+                let llty_exp2 = llhd::pointer_ty(self.plane_type(mir.ty)?);
                 let llty_act = self.llhd_type(sig);
                 assert_span!(
                     llty_exp1 == llty_act || llty_exp2 == llty_act,
@@ -1806,7 +2601,7 @@ where
                     llty_act
                 );
                 if let Some(var) = var {
-                    let llty_exp = llhd::pointer_ty(self.emit_type(mir.ty)?);
+                    let llty_exp = llhd::pointer_ty(self.plane_type(mir.ty)?);
                     let llty_act = self.llhd_type(var);
                     assert_span!(
                         llty_exp == llty_act,
@@ -1839,14 +2634,20 @@ where
             // Variables and ports trivially return their declaration value.
             // This is either the `var` or `sig` instruction which introduced
             // them.
-            mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => Ok((
-                self.emitted_value(id).clone(),
-                self.shadows.get(&id.into()).cloned(),
-            )),
+            mir::LvalueKind::Var(id) | mir::LvalueKind::Port(id) => {
+                let value = self.emitted_value(id);
+                let shadow = self.shadows.get(&id.into()).cloned();
+                Ok((
+                    self.emit_plane_of(value, mir.ty),
+                    shadow.map(|shadow| self.emit_plane_of(shadow, mir.ty)),
+                ))
+            }
 
             // Interface signals require special care, because they are emitted
             // in a transposed fashion.
-            mir::LvalueKind::IntfSignal(value, signal) => self.emit_lvalue_interface(value, signal),
+            mir::LvalueKind::IntfSignal(value, signal) => {
+                self.emit_lvalue_interface(value, signal, mir.ty)
+            }
 
             // Member accesses simply look up their inner lvalue and extract the
             // signal or pointer to the respective subfield.
@@ -1902,13 +2703,16 @@ where
         &mut self,
         mir: &mir::Lvalue<'gcx>,
         signal: NodeId,
+        ty: &'gcx UnpackedType<'gcx>,
     ) -> Result<(llhd::ir::Value, Option<llhd::ir::Value>)> {
         match mir.kind {
             mir::LvalueKind::Intf(intf) => {
                 let id = AccessedNode::Intf(intf, signal);
+                let value = self.emitted_value(id);
+                let shadow = self.shadows.get(&id).cloned();
                 Ok((
-                    self.emitted_value(id).clone(),
-                    self.shadows.get(&id).cloned(),
+                    self.emit_plane_of(value, ty),
+                    shadow.map(|shadow| self.emit_plane_of(shadow, ty)),
                 ))
             }
 
@@ -1917,7 +2721,7 @@ where
                 base,
                 length,
             } => {
-                let inner = self.emit_lvalue_interface(value, signal)?;
+                let inner = self.emit_lvalue_interface(value, signal, ty)?;
                 self.emit_lvalue_index(value.ty, inner, base, length)
             }
 
@@ -1940,7 +2744,7 @@ where
         length: usize,
     ) -> Result<(llhd::ir::Value, Option<llhd::ir::Value>)> {
         let (target_real, target_shadow) = value;
-        let base = self.emit_mir_rvalue(base)?;
+        let base = self.with_plane(Plane::Value, |gen| gen.emit_mir_rvalue(base))?;
         let shifted_real = {
             let hidden = self.emit_zero_for_type(&self.llhd_type(target_real));
             self.builder.ins().shr(target_real, hidden, base)
@@ -1986,10 +2790,12 @@ where
     fn emit_stmt(&mut self, stmt_id: NodeId, env: ParamEnv) -> Result<()> {
         self.flush_mir();
         match self.hir_of(stmt_id)? {
-            HirNode::Stmt(x) => self.emit_stmt_regular(stmt_id, x, env),
-            HirNode::VarDecl(x) => self.emit_stmt_var_decl(stmt_id, x, env),
+            HirNode::Stmt(x) => self.emit_stmt_regular(stmt_id, x, env)?,
+            HirNode::VarDecl(x) => self.emit_stmt_var_decl(stmt_id, x, env)?,
             _ => unreachable!(),
         }
+        self.emit_location(self.span(stmt_id));
+        Ok(())
     }
 
     /// Emit the code for a statement, given its HIR.
@@ -2010,6 +2816,9 @@ where
                     self.emit_stmt(id, env)?;
                 }
             }
+            hir::StmtKind::Assign { .. } if self.tables.memory_writes.contains(&(stmt_id, env)) => {
+                // Already emitted as a memory write port.
+            }
             hir::StmtKind::Assign { lhs, rhs, kind } => {
                 // Map the assignment to an MIR node.
                 let assign_mir =
@@ -2034,26 +2843,20 @@ where
                 match kind {
                     hir::AssignKind::Block(_) => {
                         for &assign in &simplified {
-                            let lhs_lv = self.emit_mir_lvalue(assign.lhs)?;
-                            let rhs_rv = self.emit_mir_rvalue(assign.rhs)?;
-                            self.emit_blocking_assign_llhd(lhs_lv, rhs_rv)?;
+                            self.emit_mir_blocking_assign(assign.lhs, assign.rhs)?;
                         }
                     }
                     hir::AssignKind::Nonblock => {
                         let delay = llhd::value::TimeValue::new(num::zero(), 1, 0);
                         let delay_const = self.builder.ins().const_time(delay);
                         for &assign in &simplified {
-                            let lhs_lv = self.emit_mir_lvalue(assign.lhs)?;
-                            let rhs_rv = self.emit_mir_rvalue(assign.rhs)?;
-                            self.builder.ins().drv(lhs_lv.0, rhs_rv, delay_const);
+                            self.emit_mir_nonblocking_assign(assign.lhs, assign.rhs, delay_const)?;
                         }
                     }
                     hir::AssignKind::NonblockDelay(delay) => {
                         let delay = self.emit_rvalue(delay, env)?;
                         for &assign in &simplified {
-                            let lhs_lv = self.emit_mir_lvalue(assign.lhs)?;
-                            let rhs_rv = self.emit_mir_rvalue(assign.rhs)?;
-                            self.builder.ins().drv(lhs_lv.0, rhs_rv, delay);
+                            self.emit_mir_nonblocking_assign(assign.lhs, assign.rhs, delay)?;
                         }
                     }
                 }
@@ -2074,70 +2877,7 @@ where
                 control: hir::TimingControl::ExplicitEvent(expr_id),
                 stmt,
             } => {
-                let expr_hir = match self.hir_of(expr_id)? {
-                    HirNode::EventExpr(x) => x,
-                    _ => unreachable!(),
-                };
-                trace!("would now emit event checking code for {:#?}", expr_hir);
-
-                // Store initial values of the expressions the event is
-                // sensitive to.
-                let init_blk = self.add_named_block("init");
-                self.builder.ins().br(init_blk);
-                self.builder.append_to(init_blk);
-                let mut init_values = vec![];
-                for event in &expr_hir.events {
-                    init_values.push(self.emit_rvalue(event.expr, env)?);
-                }
-
-                // Wait for any of the inputs to those expressions to change.
-                let check_blk = self.add_named_block("check");
-                let mut trigger_on = vec![];
-                for event in &expr_hir.events {
-                    let acc = self.accessed_nodes(event.expr, env)?;
-                    for &id in &acc.read {
-                        trigger_on.push(self.emitted_value(id).clone());
-                    }
-                }
-                self.builder.ins().wait(check_blk, trigger_on);
-                self.builder.append_to(check_blk);
-                self.flush_mir(); // ensure we don't reuse earlier expr probe
-                self.emit_shadow_update();
-
-                // Check if any of the events happened and produce a single bit
-                // value that represents this.
-                let mut event_cond = None;
-                for (event, init_value) in expr_hir.events.iter().zip(init_values.into_iter()) {
-                    trace!(
-                        "would now emit check if {:?} changed according to {:#?}",
-                        init_value,
-                        event
-                    );
-                    let now_value = self.emit_rvalue(event.expr, env)?;
-                    let mut trigger = self.emit_event_trigger(event.edge, init_value, now_value)?;
-                    for &iff in &event.iff {
-                        let iff_value = self.emit_rvalue_bool(iff, env)?;
-                        trigger = self.builder.ins().and(trigger, iff_value);
-                        self.builder.set_name(trigger, "iff".to_string());
-                    }
-                    event_cond = Some(match event_cond {
-                        Some(chain) => {
-                            let value = self.builder.ins().or(chain, trigger);
-                            self.builder.set_name(value, "event_or".to_string());
-                            value
-                        }
-                        None => trigger,
-                    });
-                }
-
-                // If the event happened, branch to a new block which will
-                // contain the subsequent statements. Otherwise jump back up to
-                // the initial block.
-                if let Some(event_cond) = event_cond {
-                    let event_blk = self.add_named_block("event");
-                    self.builder.ins().br_cond(event_cond, init_blk, event_blk);
-                    self.builder.append_to(event_blk);
-                }
+                self.emit_event_wait(expr_id, env)?;
 
                 // Emit the actual statement.
                 self.emit_stmt(stmt, env)?;
@@ -2164,6 +2904,34 @@ where
             hir::StmtKind::Expr(expr_id) => {
                 self.emit_rvalue(expr_id, env)?;
             }
+            hir::StmtKind::Wait { cond, stmt } => {
+                let wait_blk = self.add_named_block("wait");
+                let check_blk = self.add_named_block("check");
+                let done_blk = self.add_named_block("wait_done");
+
+                // Skip the wait entirely if the condition already holds.
+                let cond_value = self.emit_rvalue_bool(cond, env)?;
+                self.builder.ins().br_cond(cond_value, wait_blk, done_blk);
+
+                // Otherwise wait for any of the inputs to the condition to
+                // change and check again.
+                self.builder.append_to(wait_blk);
+                let mut trigger_on = vec![];
+                let acc = self.accessed_nodes(cond, env)?;
+                for &id in &acc.read {
+                    trigger_on.push(self.emitted_value(id).clone());
+                }
+                self.builder.ins().wait(check_blk, trigger_on);
+                self.builder.append_to(check_blk);
+                self.flush_mir(); // ensure we don't reuse earlier expr probe
+                self.emit_shadow_update();
+                let cond_value = self.emit_rvalue_bool(cond, env)?;
+                self.builder.ins().br_cond(cond_value, wait_blk, done_blk);
+
+                // Emit the actual statement.
+                self.builder.append_to(done_blk);
+                self.emit_stmt(stmt, env)?;
+            }
             hir::StmtKind::If {
                 cond,
                 main_stmt,
@@ -2184,12 +2952,12 @@ where
                 self.builder.ins().br(final_blk);
                 self.builder.append_to(final_blk);
             }
-            hir::StmtKind::Loop { kind, body } => {
+            hir::StmtKind::Loop { ref kind, body } => {
                 let body_blk = self.add_named_block("loop_body");
                 let exit_blk = self.add_named_block("loop_exit");
 
                 // Emit the loop initialization.
-                let repeat_var = match kind {
+                let repeat_var = match *kind {
                     hir::LoopKind::Forever => None,
                     hir::LoopKind::Repeat(count) => {
                         let ty = self.type_of(count, env)?;
@@ -2209,18 +2977,19 @@ where
                 // Emit the loop prologue.
                 self.builder.ins().br(body_blk);
                 self.builder.append_to(body_blk);
-                let enter_cond = match kind {
+                let enter_cond = match *kind {
                     hir::LoopKind::Forever => None,
                     hir::LoopKind::Repeat(_) => {
                         let (repeat_var, ty) = repeat_var.clone().unwrap();
-                        let lty = self.emit_type(ty)?;
+                        let lty = self.emit_two_state_type(ty)?;
                         let value = self.builder.ins().ld(repeat_var);
                         let zero = self.emit_zero_for_type(&lty);
                         Some(self.builder.ins().neq(value, zero))
                     }
                     hir::LoopKind::While(cond) => Some(self.emit_rvalue_bool(cond, env)?),
                     hir::LoopKind::Do(_) => None,
-                    hir::LoopKind::For(_, cond, _) => Some(self.emit_rvalue_bool(cond, env)?),
+                    hir::LoopKind::For(_, Some(cond), _) => Some(self.emit_rvalue_bool(cond, env)?),
+                    hir::LoopKind::For(_, None, _) => None,
                 };
                 if let Some(enter_cond) = enter_cond {
                     let entry_blk = self.add_named_block("loop_continue");
@@ -2232,7 +3001,7 @@ where
                 self.emit_stmt(body, env)?;
 
                 // Emit the epilogue.
-                let continue_cond = match kind {
+                let continue_cond = match *kind {
                     hir::LoopKind::Forever => None,
                     hir::LoopKind::Repeat(_) => {
                         let (repeat_var, ty) = repeat_var.clone().unwrap();
@@ -2240,15 +3009,17 @@ where
                         let one = self
                             .builder
                             .ins()
-                            .const_int((ty.get_bit_size().unwrap(), 0));
+                            .const_int((ty.get_bit_size().unwrap(), 1));
                         let value = self.builder.ins().sub(value, one);
                         self.builder.ins().st(repeat_var, value);
                         None
                     }
                     hir::LoopKind::While(_) => None,
                     hir::LoopKind::Do(cond) => Some(self.emit_rvalue_bool(cond, env)?),
-                    hir::LoopKind::For(_, _, step) => {
-                        self.emit_rvalue(step, env)?;
+                    hir::LoopKind::For(_, _, ref step) => {
+                        for &step in step {
+                            self.emit_rvalue(step, env)?;
+                        }
                         None
                     }
                 };
@@ -2281,7 +3052,14 @@ where
                             ValueKind::Int(v, s, x) => (v, s, x),
                             _ => panic!("case constant evaluates to non-integer"),
                         };
-                        let way_expr = self.emit_const(way_const, env, self.span(way_expr))?;
+                        // The `x` and `z` bits of wildcard labels are masked
+                        // off below.
+                        let way_expr = match kind {
+                            ast::CaseKind::Normal => {
+                                self.emit_const(way_const, env, self.span(way_expr))?
+                            }
+                            _ => self.emit_const_uninterned(way_const, env, self.span(way_expr))?,
+                        };
                         let way_width = self.llhd_type(way_expr).unwrap_int();
 
                         // Generate the comparison mask based on the case kind.
@@ -2300,14 +3078,9 @@ where
                             }
                         };
                         let mask = mask.map(|bits| {
-                            let mut mask = BigInt::zero();
-                            for b in &bits {
-                                mask <<= 1;
-                                if b {
-                                    mask |= BigInt::one();
-                                }
-                            }
-                            self.builder.ins().const_int((way_width, mask))
+                            self.builder
+                                .ins()
+                                .const_int((way_width, mask_to_int(&bits)))
                         });
 
                         // Filter the comparison values through the mask.
@@ -2340,6 +3113,53 @@ where
                 self.builder.append_to(final_blk);
             }
 
+            hir::StmtKind::Assert {
+                clock,
+                disable,
+                antecedent,
+                expr,
+                pass_stmt,
+                fail_stmt,
+            } => {
+                if let Some(clock) = clock {
+                    self.emit_event_wait(clock, env)?;
+                }
+
+                // Skip the check if the assertion is disabled, or if the
+                // antecedent of an implication does not hold.
+                let pass_blk = self.add_named_block("assert_pass");
+                let fail_blk = self.add_named_block("assert_fail");
+                let exit_blk = self.add_named_block("assert_exit");
+                if let Some(disable) = disable {
+                    let disable = self.emit_rvalue_bool(disable, env)?;
+                    let enabled_blk = self.add_named_block("assert_enabled");
+                    self.builder.ins().br_cond(disable, enabled_blk, exit_blk);
+                    self.builder.append_to(enabled_blk);
+                }
+                if let Some(antecedent) = antecedent {
+                    let antecedent = self.emit_rvalue_bool(antecedent, env)?;
+                    let taken_blk = self.add_named_block("assert_check");
+                    self.builder.ins().br_cond(antecedent, exit_blk, taken_blk);
+                    self.builder.append_to(taken_blk);
+                }
+
+                // Check the condition and run the action blocks.
+                let cond = self.emit_rvalue_bool(expr, env)?;
+                self.builder.ins().br_cond(cond, fail_blk, pass_blk);
+                self.builder.append_to(pass_blk);
+                if let Some(pass_stmt) = pass_stmt {
+                    self.emit_stmt(pass_stmt, env)?;
+                }
+                self.builder.ins().br(exit_blk);
+                self.builder.append_to(fail_blk);
+                self.emit_assertion_failed(hir.span);
+                if let Some(fail_stmt) = fail_stmt {
+                    self.emit_stmt(fail_stmt, env)?;
+                }
+                self.builder.ins().br(exit_blk);
+                self.builder.append_to(exit_blk);
+            }
+
             _ => {
                 error!("{:#?}", hir);
                 return self.unimp_msg("code generation for", hir);
@@ -2348,6 +3168,92 @@ where
         Ok(())
     }
 
+    /// Emit a call which reports a failed assertion.
+    ///
+    /// The call is located at the assertion regardless of whether debug info
+    /// has been requested, such that the failure can be reported with a
+    /// source location.
+    fn emit_assertion_failed(&mut self, span: Span) {
+        let name = llhd::ir::UnitName::global(ASSERTION_FAILED);
+        let mut sig = llhd::ir::Signature::new();
+        sig.set_return_type(llhd::void_ty());
+        if !self.tables.assertion_failed_declared {
+            self.tables.assertion_failed_declared = true;
+            self.into.declare(name.clone(), sig.clone());
+        }
+        let ext = self.builder.add_extern(name, sig);
+        let result = self.builder.ins().call(ext, vec![]);
+        let inst = self.builder.value_inst(result);
+        self.builder.set_location_hint(inst, span.begin().to_hint());
+    }
+
+    /// Emit the code to wait until an event expression triggers.
+    fn emit_event_wait(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<()> {
+        let expr_hir = match self.hir_of(expr_id)? {
+            HirNode::EventExpr(x) => x,
+            _ => unreachable!(),
+        };
+        trace!("would now emit event checking code for {:#?}", expr_hir);
+
+        // Store initial values of the expressions the event is sensitive to.
+        let init_blk = self.add_named_block("init");
+        self.builder.ins().br(init_blk);
+        self.builder.append_to(init_blk);
+        let mut init_values = vec![];
+        for event in &expr_hir.events {
+            init_values.push(self.emit_rvalue(event.expr, env)?);
+        }
+
+        // Wait for any of the inputs to those expressions to change.
+        let check_blk = self.add_named_block("check");
+        let mut trigger_on = vec![];
+        for event in &expr_hir.events {
+            let acc = self.accessed_nodes(event.expr, env)?;
+            for &id in &acc.read {
+                trigger_on.push(self.emitted_value(id).clone());
+            }
+        }
+        self.builder.ins().wait(check_blk, trigger_on);
+        self.builder.append_to(check_blk);
+        self.flush_mir(); // ensure we don't reuse earlier expr probe
+        self.emit_shadow_update();
+
+        // Check if any of the events happened and produce a single bit
+        // value that represents this.
+        let mut event_cond = None;
+        for (event, init_value) in expr_hir.events.iter().zip(init_values.into_iter()) {
+            trace!(
+                "would now emit check if {:?} changed according to {:#?}",
+                init_value,
+                event
+            );
+            let now_value = self.emit_rvalue(event.expr, env)?;
+            let mut trigger = self.emit_event_trigger(event.edge, init_value, now_value)?;
+            for &iff in &event.iff {
+                let iff_value = self.emit_rvalue_bool(iff, env)?;
+                trigger = self.builder.ins().and(trigger, iff_value);
+                self.builder.set_name(trigger, "iff".to_string());
+            }
+            event_cond = Some(match event_cond {
+                Some(chain) => {
+                    let value = self.builder.ins().or(chain, trigger);
+                    self.builder.set_name(value, "event_or".to_string());
+                    value
+                }
+                None => trigger,
+            });
+        }
+
+        // If the event happened, branch to a new block which will contain the
+        // subsequent statements. Otherwise jump back up to the initial block.
+        if let Some(event_cond) = event_cond {
+            let event_blk = self.add_named_block("event");
+            self.builder.ins().br_cond(event_cond, init_blk, event_blk);
+            self.builder.append_to(event_blk);
+        }
+        Ok(())
+    }
+
     /// Emit the code for a variable declaration statement, given its HIR.
     fn emit_stmt_var_decl(
         &mut self,
@@ -2363,9 +3269,10 @@ where
                 .unwrap()),
             env,
         );
+        self.check_fixed_size(ty, hir.name.span)?;
         let ty = self.emit_type(ty)?;
         let init = match hir.init {
-            Some(expr) => self.emit_rvalue(expr, env)?,
+            Some(expr) => self.with_plane(Plane::Both, |gen| gen.emit_rvalue(expr, env))?,
             None => self.emit_zero_for_type(&ty),
         };
         let value = self.builder.ins().var(init);
@@ -2433,9 +3340,32 @@ where
         lvalue: &'gcx mir::Lvalue<'gcx>,
         rvalue: &'gcx mir::Rvalue<'gcx>,
     ) -> Result<()> {
-        let lv = self.emit_mir_lvalue(lvalue)?;
-        let rv = self.emit_mir_rvalue(rvalue)?;
-        self.emit_blocking_assign_llhd(lv, rv)
+        for &plane in self.assign_planes(lvalue.ty) {
+            self.with_plane(plane, |gen| {
+                let lv = gen.emit_mir_lvalue(lvalue)?;
+                let rv = gen.emit_mir_rvalue(rvalue)?;
+                gen.emit_blocking_assign_llhd(lv, rv)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Emit a nonblocking assignment to a signal.
+    fn emit_mir_nonblocking_assign(
+        &mut self,
+        lvalue: &'gcx mir::Lvalue<'gcx>,
+        rvalue: &'gcx mir::Rvalue<'gcx>,
+        delay: llhd::ir::Value,
+    ) -> Result<()> {
+        for &plane in self.assign_planes(lvalue.ty) {
+            self.with_plane(plane, |gen| {
+                let lv = gen.emit_mir_lvalue(lvalue)?;
+                let rv = gen.emit_mir_rvalue(rvalue)?;
+                gen.builder.ins().drv(lv.0, rv, delay);
+                Ok(())
+            })?;
+        }
+        Ok(())
     }
 
     /// Emit a blocking assignment to a variable or signal.
@@ -2486,34 +3416,63 @@ where
         env: ParamEnv,
         default: Option<NodeId>,
     ) -> Result<llhd::ir::Value> {
+        self.check_fixed_size(ty, self.span(decl_id))?;
+
         // Check if this is a variable or a net declaration.
-        let is_var = match self.hir_of(decl_id)? {
-            HirNode::VarDecl(x) => x.kind.is_var(),
-            HirNode::IntPort(x) => x.kind.is_var(),
+        let kind = match self.hir_of(decl_id)? {
+            HirNode::VarDecl(x) => x.kind,
+            HirNode::IntPort(x) => x.kind,
             x => unreachable!("emit_varnet_decl on HIR {:?}", x),
         };
+        let is_var = kind.is_var();
 
         // Differentiate between variable and net declarations, which have
         // slightly different semantics regarding their initial value.
         if is_var {
             // For variables we require that the initial value is a
             // constant.
-            let init = self.emit_const(
-                match default {
-                    Some(expr) => self.constant_value_of(expr, env),
-                    None => self.type_default_value(ty),
-                },
-                env,
-                self.span(default.unwrap_or(decl_id)),
-            )?;
+            let span = self.span(default.unwrap_or(decl_id));
+            let init = self.with_plane(Plane::Both, |gen| match default {
+                Some(expr) => {
+                    let init = gen.constant_value_of(expr, env);
+                    gen.emit_const(init, env, span)
+                }
+                None => {
+                    let init = gen.type_default_value(ty);
+                    gen.emit_default_const(init, env, span)
+                }
+            })?;
             Ok(self.builder.ins().sig(init))
         } else {
             // For nets we simply emit the initial value as a signal, then
-            // short-circuit it with the net declaration.
-            let zero = self.emit_const(self.type_default_value(ty), env, self.span(decl_id))?;
+            // short-circuit it with the net declaration. Nets that are tied
+            // or pulled high start out as all ones.
+            let pulled_up = match kind {
+                ast::VarKind::Net {
+                    ty: ast::NetType::Supply1,
+                    ..
+                }
+                | ast::VarKind::Net {
+                    ty: ast::NetType::Tri1,
+                    ..
+                } => ty.get_packed().map(|p| p.is_integer_vec()).unwrap_or(false),
+                _ => false,
+            };
+            let zero = match ty.get_bit_size() {
+                Some(w) if pulled_up => self.intern_value(crate::value::make_int(
+                    ty,
+                    (BigInt::one() << w) - BigInt::one(),
+                )),
+                _ => self.type_default_value(ty),
+            };
+            let span = self.span(decl_id);
+            let zero =
+                self.with_plane(Plane::Both, |gen| gen.emit_default_const(zero, env, span))?;
             let net = self.builder.ins().sig(zero);
             if let Some(default) = default {
-                let init = self.emit_rvalue_mode(default, env, Mode::Signal)?;
+                let init = self.with_plane(Plane::Both, |gen| {
+                    gen.emit_rvalue_mode(default, env, Mode::Signal)
+                })?;
                 self.builder.ins().con(net, init);
             }
             Ok(net)
@@ -2533,6 +3492,28 @@ enum Mode {
     Signal,
 }
 
+/// A part of a four-valued value.
+///
+/// With `--four-state=paired`, values of four-valued types are stored as a
+/// struct of two bit patterns: the value bits, and the unknown bits which are
+/// set wherever the value is `x` or `z`. Expressions are emitted separately
+/// for each of the two parts, and only combined into the struct where a value
+/// is stored or passed to another unit. Bitwise operators and the conditional
+/// operator propagate unknown bits precisely. All other operators produce an
+/// entirely unknown result if any bit of their operands is unknown. Control
+/// flow only considers the value bits of its conditions.
+///
+/// Otherwise all values are emitted as the `Value` part.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Plane {
+    /// The entire value, as it is stored.
+    Both,
+    /// The value bits.
+    Value,
+    /// The unknown bits.
+    Unknown,
+}
+
 // /// Different binary ops that may be emitted.
 // #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 // enum BinaryOp {
@@ -2543,6 +3524,48 @@ enum Mode {
 //     Xor,
 // }
 
+/// Determine the signal that a projection of a signal, such as a field or a
+/// slice, is derived from.
+fn projection_root(unit: &llhd::ir::Unit, mut value: llhd::ir::Value) -> llhd::ir::Value {
+    while let Some(inst) = unit.get_value_inst(value) {
+        match unit[inst].opcode() {
+            llhd::ir::Opcode::ExtField
+            | llhd::ir::Opcode::ExtSlice
+            | llhd::ir::Opcode::Shl
+            | llhd::ir::Opcode::Shr
+                if unit.value_type(value).is_signal() =>
+            {
+                value = unit[inst].args()[0]
+            }
+            _ => break,
+        }
+    }
+    value
+}
+
+/// Check whether a constant value has any `x` or `z` bits.
+fn has_unknown_bits(value: Value) -> bool {
+    match value.kind {
+        ValueKind::Int(_, ref special_bits, _) => special_bits.any(),
+        ValueKind::StructOrArray(ref values) => values.iter().any(|&v| has_unknown_bits(v)),
+        _ => false,
+    }
+}
+
+/// Convert a bit mask of a constant value to an integer.
+///
+/// The first bit in the mask is the most significant one.
+fn mask_to_int(bits: &bit_vec::BitVec) -> BigInt {
+    let mut mask = BigInt::zero();
+    for b in bits {
+        mask <<= 1;
+        if b {
+            mask |= BigInt::one();
+        }
+    }
+    mask
+}
+
 /// Emit a detailed description of a module's ports.
 ///
 /// Called when the PORTS verbosity flag is set.
@@ -2597,12 +3620,66 @@ fn emit_port_details<'gcx>(cx: &impl Context<'gcx>, hir: &hir::Module<'gcx>, env
 
 /// Result of emitting a module.
 pub struct EmittedModule<'a> {
-    /// The emitted LLHD unit.
-    unit: llhd::ir::UnitId,
+    /// The name of the emitted LLHD unit or declaration.
+    name: llhd::ir::UnitName,
     /// The module's ports.
     ports: ModuleIntf<'a>,
 }
 
+impl EmittedModule<'_> {
+    /// Get the name of the emitted LLHD unit or declaration.
+    pub fn name(&self) -> &llhd::ir::UnitName {
+        &self.name
+    }
+}
+
+/// A specialization of a module for a set of resolved parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ModuleSpec<'a> {
+    /// The module being specialized.
+    module: NodeId,
+    /// The resolved value and type parameters.
+    params: Vec<(Name, ParamSpec<'a>)>,
+    /// The resolved parameters of the interfaces connected to the module's
+    /// ports, by port name.
+    intfs: Vec<(Name, Vec<(Name, ParamSpec<'a>)>)>,
+}
+
+/// A resolved module parameter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum ParamSpec<'a> {
+    /// The value assigned to a value parameter.
+    Value(Value<'a>),
+    /// The type assigned to a type parameter.
+    Type(&'a UnpackedType<'a>),
+}
+
+/// Mangle a resolved parameter into a string suitable for a unit name.
+fn mangle_param(param: ParamSpec) -> String {
+    let raw = match param {
+        ParamSpec::Value(v) => match v.kind {
+            ValueKind::Int(ref v, ..) if *v < BigInt::zero() => format!("n{}", -v),
+            ref k => format!("{}", k),
+        },
+        ParamSpec::Type(t) => format!("{}", t),
+    };
+    let mut mangled = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_alphanumeric() {
+            mangled.push(c);
+        } else if !mangled.is_empty() && !mangled.ends_with('_') {
+            mangled.push('_');
+        }
+    }
+    while mangled.ends_with('_') {
+        mangled.pop();
+    }
+    if mangled.is_empty() {
+        mangled.push('x');
+    }
+    mangled
+}
+
 /// Result of emitting a procedure.
 pub struct EmittedProcedure {
     /// The emitted LLHD unit.
@@ -24,7 +24,10 @@ use crate::crate_prelude::*;
 use crate::{
     ast::{self, Visitor},
     ast_map::{AstMap, AstNode},
-    common::{arenas::Alloc, arenas::TypedArena, Session},
+    common::{
+        arenas::{Alloc, ArenaStats, DroplessArena, TypedArena},
+        Session,
+    },
     hir::{self, HirNode},
     port_list::PortList,
     resolver::Scope,
@@ -92,6 +95,9 @@ impl<'gcx> GlobalContext<'gcx> {
         root.link(None, &mut index);
         debug!("Linked {} nodes", index);
 
+        // Make the span of every AST node available by its ID.
+        ast::collect_spans(root, &mut self.node_id_to_span.borrow_mut());
+
         // Ensure there are no naming conflicts in the scopes.
         debug!("Materializing scopes");
         crate::resolver::materialize_scope(self, root);
@@ -110,7 +116,10 @@ impl<'gcx> GlobalContext<'gcx> {
                 match &item.data {
                     ast::ItemData::ModuleDecl(ref n) => {
                         let id = self.map_ast(AstNode::Module(n));
-                        self.modules.borrow_mut().insert(n.name.value, id);
+                        let mut modules = self.modules.borrow_mut();
+                        if !n.external || !modules.contains_key(&n.name.value) {
+                            modules.insert(n.name.value, id);
+                        }
                     }
                     ast::ItemData::PackageDecl(ref n) => {
                         let id = self.map_ast(AstNode::Package(n));
@@ -251,7 +260,7 @@ impl<'gcx> ty::HasTypeStorage<'gcx> for GlobalContext<'gcx> {
 /// something during the compilation procedure.
 #[derive(Default)]
 pub struct GlobalArenas<'t> {
-    ids: TypedArena<NodeId>,
+    lists: DroplessArena,
     pub ast: ast::Arena<'t>,
     hir: hir::Arena<'t>,
     param_envs: TypedArena<ParamEnvData<'t>>,
@@ -272,9 +281,9 @@ pub struct GlobalArenas<'t> {
 }
 
 impl<'t> GlobalArenas<'t> {
-    /// Allocate a list of node IDs.
-    pub fn alloc_ids(&'t self, ids: impl IntoIterator<Item = NodeId>) -> &'t [NodeId] {
-        self.ids.alloc_extend(ids)
+    /// Allocate a list of node IDs, spans, or other small values.
+    pub fn alloc_list<T: Copy>(&'t self, values: impl IntoIterator<Item = T>) -> &'t [T] {
+        self.lists.alloc_from_iter(values)
     }
 
     /// Allocate an HIR node into the global context.
@@ -330,6 +339,38 @@ impl<'t> GlobalArenas<'t> {
     pub fn alloc_ast_expr(&'t self, ast: ast::Expr<'t>) -> &'t ast::Expr {
         self.ast_exprs.alloc(ast)
     }
+
+    /// Report the number of values and bytes allocated in each arena, grouped
+    /// by the representation they belong to.
+    pub fn stats(&self) -> Vec<(&'static str, Vec<ArenaStats>)> {
+        vec![
+            ("svlog.ast", self.ast.stats()),
+            ("svlog.hir", self.hir.stats()),
+            (
+                "svlog.mir",
+                vec![
+                    ArenaStats::of("lvalues", &self.mir_lvalue),
+                    ArenaStats::of("rvalues", &self.mir_rvalue),
+                    ArenaStats::of("assignments", &self.mir_assignment),
+                ],
+            ),
+            ("svlog.ty", self.type_storage.stats()),
+            (
+                "svlog",
+                vec![
+                    self.lists.stats("lists"),
+                    ArenaStats::of("param_envs", &self.param_envs),
+                    ArenaStats::of("ribs", &self.ribs),
+                    ArenaStats::of("port_lists", &self.port_lists),
+                    ArenaStats::of("scopes", &self.scopes),
+                    ArenaStats::of("values", &self.values),
+                    ArenaStats::of("ast_roots", &self.ast_roots),
+                    ArenaStats::of("ast_types", &self.ast_types),
+                    ArenaStats::of("ast_exprs", &self.ast_exprs),
+                ],
+            ),
+        ]
+    }
 }
 
 /// Allow AST nodes to be allocated into `GlobalArenas`.
@@ -717,3 +758,43 @@ impl<'a, 'b> ast::Visitor<'a> for AstMapRegistrator<'a, 'b> {
         self.cx.canonicalize_ports(node);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::name::get_name_table, parser, QueryTag};
+
+    #[test]
+    fn invalidate_transitively() {
+        let sess = Session::new();
+        let ast_arena = ast::Arena::default();
+        let (file, _, _source) = parser::parse_str("module foo; endmodule", &ast_arena);
+        let file = file.unwrap();
+        let arena = GlobalArenas::default();
+        let gcx = GlobalContext::new(&sess, &arena);
+        gcx.storage().track_dependencies.set(true);
+        gcx.add_files(std::iter::once(&file));
+        let module = gcx
+            .find_module(get_name_table().intern("foo", true))
+            .unwrap();
+
+        // Find a query which the module's HIR depends on.
+        gcx.hir_of(module).unwrap();
+        let hir_of = QueryTag::HirOf(HirOfQueryKey(module));
+        let dependency = gcx
+            .storage()
+            .dependents
+            .borrow()
+            .iter()
+            .find(|(_, dependents)| dependents.contains(&hir_of))
+            .map(|(tag, _)| tag.clone())
+            .unwrap();
+
+        // Invalidating the dependency discards the module's HIR as well, which
+        // is recomputed on the next call.
+        assert!(gcx.invalidate(&dependency) >= 2);
+        assert!(!gcx.storage().cached_tags().contains(&hir_of));
+        gcx.hir_of(module).unwrap();
+        assert!(gcx.storage().cached_tags().contains(&hir_of));
+    }
+}
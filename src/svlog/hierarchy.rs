@@ -0,0 +1,284 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! The elaborated instance hierarchy of a design.
+//!
+//! Walks the instances below a top module with the parameter values of each
+//! instance resolved, unrolling generate loops and picking the taken branch of
+//! generate conditions. The resulting tree can be serialized for tools which
+//! need the structure of a design but not its behavior.
+
+use crate::{
+    ast_map::AstNode,
+    codegen::{execute_genvar_init, execute_genvar_step},
+    crate_prelude::*,
+    hir::HirNode,
+    port_list::PortList,
+    resolver::InstTarget,
+    value::Value,
+    ParamEnv, PortMapping,
+};
+use moore_common::source::Source;
+use serde::Serialize;
+
+/// An instance in the elaborated hierarchy.
+#[derive(Debug, Serialize)]
+pub struct HierInst {
+    /// The hierarchical path of the instance, e.g. `top.g[1].i_sub`. Unnamed
+    /// generate blocks do not contribute to the path.
+    pub path: String,
+    /// The name of the instance, or of the module for a top module.
+    pub name: String,
+    /// The kind of the instantiated unit: `"module"`, `"interface"`, or
+    /// `"entity"` for a VHDL entity.
+    pub kind: &'static str,
+    /// The name of the instantiated module, interface, or entity.
+    pub target: String,
+    /// The file the instance is declared in.
+    pub file: Source,
+    /// The line the instance is declared on.
+    pub line: usize,
+    /// The values of the parameters which are not local.
+    pub params: Vec<HierParam>,
+    /// The ports of the instantiated unit and what they are connected to.
+    pub ports: Vec<HierPort>,
+    /// The instances within the instantiated unit.
+    pub children: Vec<HierInst>,
+}
+
+/// A resolved parameter of an instance.
+#[derive(Debug, Serialize)]
+pub struct HierParam {
+    pub name: String,
+    /// The value of the parameter, or the type for type parameters.
+    pub value: String,
+}
+
+/// A port of an instance.
+#[derive(Debug, Serialize)]
+pub struct HierPort {
+    /// The name of the port, or `None` for unnamed port expressions.
+    pub name: Option<String>,
+    /// The direction of the port, or `None` if the port is made up of several
+    /// internal ports.
+    pub dir: Option<String>,
+    /// The source text of the connected expression, or `None` if the port is
+    /// unconnected.
+    pub connection: Option<String>,
+}
+
+/// Determine the elaborated hierarchy below a top module.
+pub fn hierarchy<'a>(cx: &impl Context<'a>, top: NodeId) -> Result<HierInst> {
+    let module = match cx.hir_of(top)? {
+        HirNode::Module(x) => x,
+        _ => panic!("expected {:?} to be a module", top),
+    };
+    let env = cx.default_param_env();
+    let name = module.name.value.to_string();
+    let mut children = vec![];
+    if !module.ast.external {
+        add_block(cx, &module.block, env, &name, &mut children)?;
+    }
+    Ok(HierInst {
+        path: name.clone(),
+        name: name.clone(),
+        kind: "module",
+        target: name,
+        file: module.name.span.source,
+        line: module.name.span.begin().human_line(),
+        params: params(cx, module.params.iter().chain(&module.block.params), env),
+        ports: ports(cx, module.ports_new, None),
+        children,
+    })
+}
+
+/// Add the instances in a module block, including its generate blocks.
+fn add_block<'a>(
+    cx: &impl Context<'a>,
+    block: &'a hir::ModuleBlock,
+    env: ParamEnv,
+    prefix: &str,
+    into: &mut Vec<HierInst>,
+) -> Result<()> {
+    for &inst_id in &block.insts {
+        let inst = match cx.hir_of(inst_id)? {
+            HirNode::Inst(x) => x,
+            _ => unreachable!(),
+        };
+        let details = cx.inst_details(Ref(inst), env)?;
+        let path = format!("{}.{}", prefix, inst.name.value);
+        let inner_env = details.inner_env;
+        let mut children = vec![];
+        let (kind, target, params, port_list) = match details.target.kind {
+            InstTarget::Module(node) => {
+                let module = cx.hir_of_module(node)?;
+                if !module.ast.external {
+                    add_block(cx, &module.block, inner_env, &path, &mut children)?;
+                }
+                let kind = if module.ast.foreign {
+                    "entity"
+                } else {
+                    "module"
+                };
+                let params = params(
+                    cx,
+                    module.params.iter().chain(&module.block.params),
+                    inner_env,
+                );
+                (kind, module.name.value, params, module.ports_new)
+            }
+            InstTarget::Interface(node) => {
+                let intf = cx.hir_of_interface(node)?;
+                add_block(cx, &intf.block, inner_env, &path, &mut children)?;
+                let param_ids: Vec<_> = intf
+                    .ast
+                    .params
+                    .iter()
+                    .flat_map(|p| match p.kind {
+                        ast::ParamKind::Type(ref x) => x.iter().map(|d| d.id()).collect(),
+                        ast::ParamKind::Value(ref x) => {
+                            x.iter().map(|d| d.id()).collect::<Vec<_>>()
+                        }
+                    })
+                    .chain(intf.block.params.iter().cloned())
+                    .collect();
+                let params = params(cx, &param_ids, inner_env);
+                (
+                    "interface",
+                    intf.ast.name.value,
+                    params,
+                    intf.ports as &PortList,
+                )
+            }
+        };
+        into.push(HierInst {
+            path,
+            name: inst.name.value.to_string(),
+            kind,
+            target: target.to_string(),
+            file: inst.name.span.source,
+            line: inst.name.span.begin().human_line(),
+            params,
+            ports: ports(cx, port_list, Some(&details.ports)),
+            children,
+        });
+    }
+
+    for &gen_id in &block.gens {
+        let gen = match cx.hir_of(gen_id)? {
+            HirNode::Gen(x) => x,
+            _ => unreachable!(),
+        };
+        match gen.kind {
+            hir::GenKind::If {
+                cond,
+                ref main_body,
+                ref else_body,
+            } => {
+                let gen_ast = match cx.ast_of(gen_id)? {
+                    AstNode::GenIf(x) => x,
+                    _ => unreachable!(),
+                };
+                if cx.constant_value_of(cond, env).is_false() {
+                    if let (Some(body), Some(else_ast)) = (else_body, &gen_ast.else_block) {
+                        let prefix = block_prefix(prefix, else_ast.label, None);
+                        add_block(cx, body, env, &prefix, into)?;
+                    }
+                } else {
+                    let prefix = block_prefix(prefix, gen_ast.main_block.label, None);
+                    add_block(cx, main_body, env, &prefix, into)?;
+                }
+            }
+            hir::GenKind::For {
+                ref init,
+                cond,
+                step,
+                ref body,
+            } => {
+                let gen_ast = match cx.ast_of(gen_id)? {
+                    AstNode::GenFor(x) => x,
+                    _ => unreachable!(),
+                };
+                let mut genvar = None;
+                let mut local_env = env;
+                for &i in init {
+                    match cx.hir_of(i)? {
+                        HirNode::GenvarDecl(decl) => genvar = Some(decl.id),
+                        HirNode::Stmt(hir::Stmt {
+                            kind: hir::StmtKind::Assign { lhs, .. },
+                            ..
+                        }) => genvar = Some(cx.resolve_node(*lhs, env)?),
+                        _ => (),
+                    }
+                    local_env = execute_genvar_init(cx, i, local_env)?;
+                }
+                while cx.constant_value_of(cond, local_env).is_true() {
+                    let index = genvar.map(|id| cx.constant_value_of(id, local_env));
+                    let prefix = block_prefix(prefix, gen_ast.block.label, index);
+                    add_block(cx, body, local_env, &prefix, into)?;
+                    local_env = execute_genvar_step(cx, step, local_env)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Determine the path prefix of the instances in a generate block.
+fn block_prefix(prefix: &str, label: Option<Spanned<Name>>, index: Option<Value>) -> String {
+    match (label, index) {
+        (Some(label), Some(index)) => format!("{}.{}[{}]", prefix, label.value, index),
+        (Some(label), None) => format!("{}.{}", prefix, label.value),
+        (None, _) => prefix.to_string(),
+    }
+}
+
+/// Resolve the parameters of an instantiated unit which are not local.
+fn params<'a, 'b>(
+    cx: &impl Context<'a>,
+    ids: impl IntoIterator<Item = &'b NodeId>,
+    env: ParamEnv,
+) -> Vec<HierParam> {
+    let mut params = vec![];
+    for &id in ids {
+        let (name, value) = match cx.hir_of(id) {
+            Ok(HirNode::ValueParam(p)) if !p.local => {
+                (p.name.value, cx.constant_value_of(id, env).to_string())
+            }
+            Ok(HirNode::TypeParam(p)) if !p.local => {
+                let ty = cx.map_to_type_or_error(Ref(cx.ast_for_id(id)), env);
+                (p.name.value, ty.resolve_full().to_string())
+            }
+            _ => continue,
+        };
+        params.push(HierParam {
+            name: name.to_string(),
+            value,
+        });
+    }
+    params
+}
+
+/// List the external ports of an instantiated unit and their connections.
+fn ports<'a>(
+    cx: &impl Context<'a>,
+    list: &PortList,
+    mapping: Option<&PortMapping>,
+) -> Vec<HierPort> {
+    list.ext_pos
+        .iter()
+        .map(|port| {
+            let dir = match port.exprs.as_slice() {
+                [expr] if expr.selects.is_empty() => Some(list.int[expr.port].dir.to_string()),
+                _ => None,
+            };
+            let connection = mapping
+                .and_then(|m| m.find(port.id))
+                .map(|id| cx.span(id.id()).extract());
+            HierPort {
+                name: port.name.map(|n| n.value.to_string()),
+                dir,
+                connection,
+            }
+        })
+        .collect()
+}
@@ -63,8 +63,8 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
             }
             let hir = hir::InstTarget {
                 ast,
-                pos_params,
-                named_params,
+                pos_params: cx.arena().alloc_list(pos_params),
+                named_params: cx.arena().alloc_list(named_params),
             };
             Ok(HirNode::InstTarget(cx.arena().alloc_hir(hir)))
         }
@@ -91,6 +91,9 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                             ast::PortConnMode::Connected(ref expr) => {
                                 Some(cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
                             }
+                            ast::PortConnMode::Event(ref expr) => {
+                                return unsupported_event_conn(cx, inst, expr);
+                            }
                         };
                         named_ports.push((port.span, name, value_id));
                     }
@@ -108,13 +111,16 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                         let value_id = cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
                         pos_ports.push((port.span, Some(value_id)));
                     }
+                    ast::PortConnData::PositionalEvent(ref expr) => {
+                        return unsupported_event_conn(cx, inst, expr);
+                    }
                 }
             }
             let hir = hir::Inst {
                 ast: inst,
                 target: target_id,
-                named_ports,
-                pos_ports,
+                named_ports: cx.arena().alloc_list(named_ports),
+                pos_ports: cx.arena().alloc_list(pos_ports),
                 has_wildcard_port,
             };
             Ok(HirNode::Inst(cx.arena().alloc_hir(hir)))
@@ -124,7 +130,7 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                 id: node_id,
                 name: decl.name,
                 span: Span::union(param.span, decl.span),
-                local: param.local,
+                local: param.local || is_implicitly_local(param),
                 default: decl
                     .ty
                     .as_ref()
@@ -137,7 +143,7 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                 id: node_id,
                 name: decl.name,
                 span: Span::union(param.span, decl.span),
-                local: param.local,
+                local: param.local || is_implicitly_local(param),
                 ty: cx.map_ast_with_parent(AstNode::Type(&decl.ty), node_id),
                 default: decl
                     .expr
@@ -187,6 +193,24 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
             };
             Ok(HirNode::Proc(cx.arena().alloc_hir(hir)))
         }
+        AstNode::ConcurrentAssertion(assertion) => {
+            let hir = hir::Proc {
+                id: node_id,
+                span: assertion.span,
+                kind: ast::ProcedureKind::Always,
+                stmt: cx.map_ast_with_parent(AstNode::AssertionCheck(assertion), node_id),
+            };
+            Ok(HirNode::Proc(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::AssertionCheck(assertion) => {
+            let hir = hir::Stmt {
+                id: node_id,
+                label: assertion.label.map(|(name, span)| Spanned::new(name, span)),
+                span: assertion.span,
+                kind: lower_assertion(cx, node_id, assertion).unwrap_or(hir::StmtKind::Null),
+            };
+            Ok(HirNode::Stmt(cx.arena().alloc_hir(hir)))
+        }
         AstNode::Stmt(stmt) => {
             let kind = match stmt.kind {
                 ast::NullStmt => hir::StmtKind::Null,
@@ -203,10 +227,38 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                             .collect(),
                     )
                 }
+                ast::BlockingAssignStmt {
+                    control: Some(ref control),
+                    ..
+                } => {
+                    let span = match *control {
+                        ast::TimingControl::Delay(ref dc) => dc.span,
+                        ast::TimingControl::Event(ref ec) => ec.span,
+                        ast::TimingControl::Cycle(..) | ast::TimingControl::Repeat(..) => {
+                            stmt.span()
+                        }
+                    };
+                    cx.emit(
+                        DiagBuilder2::error("unsupported: intra-assignment timing control")
+                            .span(span),
+                    );
+                    return Err(());
+                }
+                ast::NonblockingAssignStmt {
+                    event: Some(ref ec),
+                    ..
+                } => {
+                    cx.emit(
+                        DiagBuilder2::error("unsupported: intra-assignment timing control")
+                            .span(ec.span),
+                    );
+                    return Err(());
+                }
                 ast::BlockingAssignStmt {
                     ref lhs,
                     ref rhs,
                     op,
+                    control: None,
                 } => hir::StmtKind::Assign {
                     lhs: cx.map_ast_with_parent(AstNode::Expr(lhs), node_id),
                     rhs: cx.map_ast_with_parent(AstNode::Expr(rhs), node_id),
@@ -252,6 +304,27 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                         .as_ref()
                         .map(|else_stmt| cx.map_ast_with_parent(AstNode::Stmt(else_stmt), node_id)),
                 },
+                ast::WaitExprStmt(ref cond, ref inner_stmt) => hir::StmtKind::Wait {
+                    cond: cx.map_ast_with_parent(AstNode::Expr(cond), node_id),
+                    stmt: cx.map_ast_with_parent(AstNode::Stmt(inner_stmt), node_id),
+                },
+                // Fork blocks are not supported, so a process never has any
+                // child processes to wait for or disable.
+                ast::WaitForkStmt | ast::DisableForkStmt => hir::StmtKind::Null,
+                ast::RandcaseStmt(..) => {
+                    cx.emit(
+                        DiagBuilder2::error("unsupported: `randcase` statement")
+                            .span(stmt.human_span()),
+                    );
+                    return Err(());
+                }
+                ast::DisableStmt(_) => {
+                    cx.emit(
+                        DiagBuilder2::error("unsupported: `disable` statement")
+                            .span(stmt.human_span()),
+                    );
+                    return Err(());
+                }
                 ast::ExprStmt(ref expr) => {
                     hir::StmtKind::Expr(cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
                 }
@@ -277,8 +350,13 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                 },
                 ast::ForStmt(ref init, ref cond, ref step, ref body) => {
                     let init = cx.map_ast_with_parent(AstNode::Stmt(init), node_id);
-                    let cond = cx.map_ast_with_parent(AstNode::Expr(cond), init);
-                    let step = cx.map_ast_with_parent(AstNode::Expr(step), init);
+                    let cond = cond
+                        .as_ref()
+                        .map(|cond| cx.map_ast_with_parent(AstNode::Expr(cond), init));
+                    let step = step
+                        .iter()
+                        .map(|step| cx.map_ast_with_parent(AstNode::Expr(step), init))
+                        .collect();
                     hir::StmtKind::Loop {
                         kind: hir::LoopKind::For(init, cond, step),
                         body: cx.map_ast_with_parent(AstNode::Stmt(body), init),
@@ -331,8 +409,12 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                             ast::CaseItem::Expr(ref exprs, ref stmt) => ways.push((
                                 exprs
                                     .iter()
-                                    .map(|expr| {
-                                        cx.map_ast_with_parent(AstNode::Expr(expr), node_id)
+                                    .map(|range| match range {
+                                        ast::ValueRange::Single(expr) => {
+                                            cx.map_ast_with_parent(AstNode::Expr(expr), node_id)
+                                        }
+                                        // The parser only accepts ranges in `case inside`.
+                                        ast::ValueRange::Range { .. } => unreachable!(),
                                     })
                                     .collect(),
                                 cx.map_ast_with_parent(AstNode::Stmt(stmt), node_id),
@@ -346,13 +428,18 @@ pub(crate) fn hir_of<'a>(cx: &impl Context<'a>, node_id: NodeId) -> Result<HirNo
                         kind,
                     }
                 }
-                ast::AssertionStmt { .. } => {
-                    cx.emit(
-                        DiagBuilder2::warning("unsupported: immediate assertion; ignored")
+                ast::AssertionStmt(ref assertion) => match assertion.data {
+                    ast::AssertionData::Concurrent(..) => {
+                        cx.emit(
+                            DiagBuilder2::warning(
+                                "unsupported: concurrent assertion in procedure; ignored",
+                            )
                             .span(stmt.human_span()),
-                    );
-                    hir::StmtKind::Null
-                }
+                        );
+                        hir::StmtKind::Null
+                    }
+                    _ => lower_assertion(cx, node_id, assertion).unwrap_or(hir::StmtKind::Null),
+                },
                 _ => {
                     error!("{:#?}", stmt);
                     bug_span!(
@@ -539,7 +626,7 @@ pub(crate) fn hir_of_module<'a>(
     let hir = hir::Module {
         ast,
         ports_new,
-        params: cx.arena().alloc_ids(params),
+        params: cx.arena().alloc_list(params),
         last_rib: block.last_rib,
         block,
     };
@@ -565,8 +652,16 @@ pub(crate) fn hir_of_interface<'a>(
     // Lower the interface's ports.
     let ports = cx.canonicalize_ports(ast);
 
+    // Allocate the imports in the interface header.
+    let mut next_rib = ast.id();
+    for import in &ast.imports {
+        for item in &import.items {
+            next_rib = cx.map_ast_with_parent(AstNode::Import(item), next_rib);
+        }
+    }
+
     // Lower the interface body.
-    let block = lower_module_block(cx, ast.id(), &ast.items, true, true)?;
+    let block = lower_module_block(cx, next_rib, &ast.items, true, true)?;
 
     // Create the HIR node.
     let hir = hir::Interface { ast, ports, block };
@@ -597,7 +692,24 @@ fn lower_module_block<'gcx>(
     let mut gens = Vec::new();
     let mut params = Vec::new();
     let mut assigns = Vec::new();
-    for item in items {
+
+    // Generate regions merely group items, which belong to the enclosing
+    // scope.
+    fn flatten<'a>(
+        items: impl IntoIterator<Item = &'a ast::Item<'a>>,
+        into: &mut Vec<&'a ast::Item<'a>>,
+    ) {
+        for item in items {
+            match item.data {
+                ast::ItemData::GenerateRegion(_, ref items) => flatten(items, into),
+                _ => into.push(item),
+            }
+        }
+    }
+    let mut flat_items = vec![];
+    flatten(items, &mut flat_items);
+
+    for item in flat_items {
         match item.data {
             ast::ItemData::Dummy => (),
             ast::ItemData::ModuleDecl(ref decl) => {
@@ -619,13 +731,24 @@ fn lower_module_block<'gcx>(
                         .span(decl.span),
                 );
             }
-            ast::ItemData::ProgramDecl(ref _decl) => {
-                // let id = cx.map_ast_with_parent(AstNode::Program(decl), next_rib);
-                // next_rib = id;
-                // procs.push(id);
-                cx.emit(DiagBuilder2::warning(
-                    "unsupported: program declaration; ignored",
-                ));
+            ast::ItemData::ProgramDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: program declaration; ignored")
+                        .span(decl.span),
+                );
+            }
+            ast::ItemData::CheckerDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: checker declaration; ignored")
+                        .span(decl.span),
+                );
+            }
+            ast::ItemData::UdpDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::error("primitive declaration in module")
+                        .span(decl.span)
+                        .add_note("Primitives can only be declared outside of modules"),
+                );
             }
             ast::ItemData::Inst(ref inst) => {
                 let target_id = cx.map_ast_with_parent(AstNode::InstTarget(inst), next_rib);
@@ -679,12 +802,51 @@ fn lower_module_block<'gcx>(
                     assigns.push(id);
                 }
             }
+            ast::ItemData::Bind(ref bind) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: bind directive; ignored").span(bind.span),
+                );
+            }
+            ast::ItemData::ClockingBlock(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: clocking block; ignored").span(decl.span),
+                );
+            }
+            ast::ItemData::DefaultClocking(..) => (),
+            ast::ItemData::CovergroupDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: covergroup declaration; ignored")
+                        .span(decl.span),
+                );
+            }
+            ast::ItemData::LetDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: let declaration; ignored").span(decl.span),
+                );
+            }
+            ast::ItemData::NetAlias(ref alias) => {
+                cx.emit(DiagBuilder2::error("unsupported: net alias").span(alias.span));
+            }
+            ast::ItemData::Defparam(ref defparam) => {
+                cx.emit(
+                    DiagBuilder2::error("unsupported: defparam statement")
+                        .span(defparam.span)
+                        .add_note("Override the parameters in the instantiation instead"),
+                );
+            }
             ast::ItemData::ImportDecl(ref decl) => {
                 for item in &decl.items {
                     let id = cx.map_ast_with_parent(AstNode::Import(item), next_rib);
                     next_rib = id;
                 }
             }
+            ast::ItemData::ExportDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::error("misplaced export declaration")
+                        .span(decl.span)
+                        .add_note("Export declarations can only appear in a package"),
+                );
+            }
             ast::ItemData::PortDecl(ref decl) => {
                 if !allow_ports {
                     cx.emit(
@@ -710,19 +872,34 @@ fn lower_module_block<'gcx>(
                         .span(decl.span),
                 );
             }
+            ast::ItemData::InterfaceClassDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: interface class declaration; ignored")
+                        .span(decl.span),
+                );
+            }
             ast::ItemData::SubroutineDecl(ref decl) => {
                 let id = cx.map_ast_with_parent(AstNode::SubroutineDecl(decl), next_rib);
                 next_rib = id;
             }
-            ast::ItemData::Assertion(ref assert) => {
-                cx.emit(
-                    DiagBuilder2::warning("unsupported: concurrent assertion; ignored")
+            ast::ItemData::Assertion(ref assert) => match assert.data {
+                ast::AssertionData::Concurrent(..) => {
+                    let id = cx.map_ast_with_parent(AstNode::ConcurrentAssertion(assert), next_rib);
+                    procs.push(id);
+                }
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::warning(
+                            "unsupported: deferred assertion outside procedure; ignored",
+                        )
                         .span(assert.span),
-                );
-            }
+                    );
+                }
+            },
 
             // The remaining items don't need an HIR representation.
             ast::ItemData::DpiDecl(..)
+            | ast::ItemData::ExternSubroutine(..)
             | ast::ItemData::GenvarDecl(..)
             | ast::ItemData::GenerateRegion(..) => (),
         }
@@ -829,7 +1006,7 @@ fn lower_type<'gcx>(
             }
         }
         ast::ChandleType
-        | ast::VirtIntfType(..)
+        | ast::VirtIntfType { .. }
         | ast::EventType
         | ast::MailboxType
         | ast::ImplicitSignedType
@@ -955,8 +1132,38 @@ fn lower_expr_inner<'gcx>(
                 }
             };
 
+            // Identify the special bits (x and z) in the input, starting at the
+            // least significant bit. A decimal literal is either a number or a
+            // single x or z digit which covers all bits.
+            // TODO(fschuiki): Decimal literals are not handled properly.
+            let is_special = |c| match c {
+                'x' | 'X' | 'z' | 'Z' | '?' => true,
+                _ => false,
+            };
+            let is_x = |c| match c {
+                'x' | 'X' => true,
+                _ => false,
+            };
+            let digit_bits = match base {
+                'h' => 4,
+                'o' => 3,
+                'b' => 1,
+                _ => 0,
+            };
+            let digits: Vec<char> = value_str.chars().filter(|&c| c != '_').collect();
+            let mut bits: Vec<char> = digits
+                .iter()
+                .rev()
+                .flat_map(|&c| std::iter::repeat(c).take(digit_bits))
+                .collect();
+
             // Parse the size and verify the number fits.
-            let size_needed = parsed.bits() as usize;
+            let size_needed = std::cmp::max(
+                parsed.bits() as usize,
+                bits.iter()
+                    .rposition(|&c| is_special(c))
+                    .map_or(0, |i| i + 1),
+            );
             let size = match maybe_size {
                 Some(size) => match size.as_str().parse() {
                     Ok(s) => s,
@@ -983,30 +1190,17 @@ fn lower_expr_inner<'gcx>(
                 );
             }
 
-            // Identify the special bits (x and z) in the input.
-            // TODO(fschuiki): Decimal literals are not handled properly.
-            let bit_iter = value_str.chars().flat_map(|c| {
-                std::iter::repeat(c).take(match base {
-                    'h' => 4,
-                    'o' => 3,
-                    'b' => 1,
-                    _ => 0,
-                })
-            });
-            let special_bits: BitVec = bit_iter
-                .clone()
-                .map(|c| match c {
-                    'x' | 'X' | 'z' | 'Z' | '?' => true,
-                    _ => false,
-                })
-                .collect();
-            let x_bits: BitVec = bit_iter
-                .clone()
-                .map(|c| match c {
-                    'x' | 'X' => true,
-                    _ => false,
-                })
-                .collect();
+            // A leftmost x or z digit extends to the full width of the
+            // literal, as per IEEE 1800-2017 section 5.7.1.
+            let fill = match digits.first() {
+                Some(&c) if is_special(c) && (digit_bits > 0 || digits.len() == 1) => c,
+                _ => '0',
+            };
+            bits.resize(size, fill);
+
+            // Store the special bits starting at the most significant bit.
+            let special_bits: BitVec = bits.iter().rev().map(|&c| is_special(c)).collect();
+            let x_bits: BitVec = bits.iter().rev().map(|&c| is_x(c)).collect();
 
             // Assemble the HIR node.
             hir::ExprKind::IntConst {
@@ -1028,6 +1222,12 @@ fn lower_expr_inner<'gcx>(
                 TimeUnit::NanoSecond => 3,
                 TimeUnit::PicoSecond => 4,
                 TimeUnit::FemtoSecond => 5,
+                TimeUnit::Step => {
+                    cx.emit(
+                        DiagBuilder2::error("unsupported: `1step` time literal").span(expr.span),
+                    );
+                    return Err(());
+                }
             };
             for _ in 0..magnitude {
                 value = value / num::BigInt::from(1000);
@@ -1115,6 +1315,12 @@ fn lower_expr_inner<'gcx>(
                 Op::LogicShR => hir::BinaryOp::LogicShR,
                 Op::ArithShL => hir::BinaryOp::ArithShL,
                 Op::ArithShR => hir::BinaryOp::ArithShR,
+                Op::TripleAnd => {
+                    cx.emit(
+                        DiagBuilder2::error("unsupported: `&&&` in conditions").span(expr.span()),
+                    );
+                    return Err(());
+                }
                 _ => {
                     cx.emit(
                         DiagBuilder2::error(format!("`{}` is not a valid binary operator", op,))
@@ -1225,9 +1431,8 @@ fn lower_expr_inner<'gcx>(
                     cx.resolve_upwards_or_error(name, cx.parent_node_id(node_id).unwrap())?;
                 hir::ExprKind::FunctionCall(
                     target,
-                    args.iter()
-                        .map(|arg| lower_call_arg(cx, arg, node_id))
-                        .collect(),
+                    cx.arena()
+                        .alloc_list(args.iter().map(|arg| lower_call_arg(cx, arg, node_id))),
                 )
             }
             _ => {
@@ -1286,7 +1491,7 @@ fn lower_expr_inner<'gcx>(
                             }
                         });
                     }
-                    hir::ExprKind::PositionalPattern(mapping)
+                    hir::ExprKind::PositionalPattern(cx.arena().alloc_list(mapping))
                 }
                 ast::PatternFieldData::Repeat(ref count, ref exprs) => {
                     for field in &fields[1..] {
@@ -1301,10 +1506,11 @@ fn lower_expr_inner<'gcx>(
                     }
                     hir::ExprKind::RepeatPattern(
                         cx.map_ast_with_parent(AstNode::Expr(count), node_id),
-                        exprs
-                            .iter()
-                            .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
-                            .collect(),
+                        cx.arena().alloc_list(
+                            exprs
+                                .iter()
+                                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+                        ),
                     )
                 }
                 ast::PatternFieldData::Type(..)
@@ -1346,7 +1552,7 @@ fn lower_expr_inner<'gcx>(
                             }
                         });
                     }
-                    hir::ExprKind::NamedPattern(mapping)
+                    hir::ExprKind::NamedPattern(cx.arena().alloc_list(mapping))
                 }
             }
         }
@@ -1357,10 +1563,11 @@ fn lower_expr_inner<'gcx>(
             repeat
                 .as_ref()
                 .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
-            exprs
-                .iter()
-                .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
-                .collect(),
+            cx.arena().alloc_list(
+                exprs
+                    .iter()
+                    .map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+            ),
         ),
         ast::CastExpr(ref ty, ref expr) => {
             // Catch the corner case where a size cast looks like a type cast.
@@ -1405,26 +1612,32 @@ fn lower_expr_inner<'gcx>(
             cx.map_ast_with_parent(AstNode::Expr(size_expr), node_id),
             cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
         ),
+        ast::MatchesExpr(..) => {
+            cx.emit(DiagBuilder2::error("unsupported: pattern matching").span(expr.span()));
+            return Err(());
+        }
+        ast::RandomizeWithExpr { .. } => {
+            cx.emit(
+                DiagBuilder2::error("unsupported: inline constraints on `randomize`")
+                    .span(expr.span()),
+            );
+            return Err(());
+        }
         ast::InsideExpr(ref expr, ref ranges) => hir::ExprKind::Inside(
             cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
-            ranges
-                .iter()
-                .map(|vr| match vr {
-                    ast::ValueRange::Single(expr) => Spanned::new(
-                        hir::InsideRange::Single(
-                            cx.map_ast_with_parent(AstNode::Expr(expr), node_id),
-                        ),
-                        expr.span,
-                    ),
-                    ast::ValueRange::Range { lo, hi, span } => Spanned::new(
-                        hir::InsideRange::Range(
-                            cx.map_ast_with_parent(AstNode::Expr(lo), node_id),
-                            cx.map_ast_with_parent(AstNode::Expr(hi), node_id),
-                        ),
-                        *span,
+            cx.arena().alloc_list(ranges.iter().map(|vr| match vr {
+                ast::ValueRange::Single(expr) => Spanned::new(
+                    hir::InsideRange::Single(cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+                    expr.span,
+                ),
+                ast::ValueRange::Range { lo, hi, span } => Spanned::new(
+                    hir::InsideRange::Range(
+                        cx.map_ast_with_parent(AstNode::Expr(lo), node_id),
+                        cx.map_ast_with_parent(AstNode::Expr(hi), node_id),
                     ),
-                })
-                .collect(),
+                    *span,
+                ),
+            })),
         ),
         ast::BitsExpr { ref arg, .. } => hir::ExprKind::Builtin(hir::BuiltinCall::Bits(arg)),
         ast::AssignExpr {
@@ -1482,6 +1695,58 @@ fn parse_fixed_point_number<'gcx>(
     }
 }
 
+/// Lower an assertion to an assertion statement.
+///
+/// Emits a warning and returns `None` if the assertion is not supported.
+fn lower_assertion<'gcx>(
+    cx: &impl Context<'gcx>,
+    node_id: NodeId,
+    assertion: &'gcx ast::Assertion<'gcx>,
+) -> Option<hir::StmtKind> {
+    let unsupported = |what: &str| {
+        cx.emit(
+            DiagBuilder2::warning(format!("unsupported: {}; ignored", what)).span(assertion.span),
+        );
+        None
+    };
+    let (prop, expr, action) = match assertion.data {
+        ast::AssertionData::Immediate(ref blocking)
+        | ast::AssertionData::Deferred(_, ref blocking) => match *blocking {
+            ast::BlockingAssertion::Assert(ref expr, ref action)
+            | ast::BlockingAssertion::Assume(ref expr, ref action) => (None, expr, action),
+            ast::BlockingAssertion::Cover(..) => return unsupported("cover statement"),
+        },
+        ast::AssertionData::Concurrent(ref concurrent) => match *concurrent {
+            ast::ConcurrentAssertion::AssertProperty(ref prop, ref action)
+            | ast::ConcurrentAssertion::AssumeProperty(ref prop, ref action) => match prop.simple {
+                Some(ref simple) if simple.clock.is_some() => (Some(simple), &simple.expr, action),
+                Some(_) => return unsupported("concurrent assertion without clock"),
+                None => return unsupported("complex property"),
+            },
+            _ => return unsupported("concurrent assertion"),
+        },
+    };
+    let map_expr = |expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
+    let map_stmt = |stmt| cx.map_ast_with_parent(AstNode::Stmt(stmt), node_id);
+    let (pass_stmt, fail_stmt) = match *action {
+        ast::AssertionActionBlock::Positive(ref stmt) => (Some(map_stmt(stmt)), None),
+        ast::AssertionActionBlock::Negative(ref stmt) => (None, Some(map_stmt(stmt))),
+        ast::AssertionActionBlock::Both(ref pass, ref fail) => {
+            (Some(map_stmt(pass)), Some(map_stmt(fail)))
+        }
+    };
+    Some(hir::StmtKind::Assert {
+        clock: prop
+            .and_then(|p| p.clock.as_ref())
+            .map(|clock| cx.map_ast_with_parent(AstNode::EventExpr(clock), node_id)),
+        disable: prop.and_then(|p| p.disable.as_ref()).map(map_expr),
+        antecedent: prop.and_then(|p| p.antecedent.as_ref()).map(map_expr),
+        expr: map_expr(expr),
+        pass_stmt,
+        fail_stmt,
+    })
+}
+
 fn lower_event_expr<'gcx>(
     cx: &impl Context<'gcx>,
     expr: &'gcx ast::EventExpr<'gcx>,
@@ -1581,6 +1846,48 @@ fn alloc_param_decl<'gcx>(
     next_rib
 }
 
+/// Report an event expression connected to a port of a module or interface.
+///
+/// Checkers are the only instances that accept events, and these are
+/// reported as unsupported when their target is resolved.
+fn unsupported_event_conn<'gcx, T>(
+    cx: &impl Context<'gcx>,
+    inst: &'gcx ast::InstName<'gcx>,
+    expr: &ast::EventExpr<'gcx>,
+) -> Result<T> {
+    if let Some(ast::AllNode::Inst(target)) = inst.get_parent().map(|p| p.as_all()) {
+        cx.resolve_inst_target(target)?;
+    }
+    cx.emit(
+        DiagBuilder2::error("event expression cannot be connected to a port")
+            .span(expr.span())
+            .add_note("Only checker instances accept events as port connections"),
+    );
+    Err(())
+}
+
+/// Check whether a `parameter` declaration acts as a `localparam`.
+///
+/// This is the case for parameters declared in the body of a module,
+/// interface, or program that has a parameter port list (IEEE 1800-2017
+/// §6.20.1), even if that list is empty.
+fn is_implicitly_local<'gcx>(param: &'gcx ast::ParamDecl<'gcx>) -> bool {
+    let mut node: &dyn ast::AnyNode = param;
+    while let Some(parent) = node.get_parent() {
+        let (param_list, header) = match parent.as_all() {
+            ast::AllNode::Module(x) => (x.param_list, &x.params),
+            ast::AllNode::Interface(x) => (x.param_list, &x.params),
+            ast::AllNode::Program(x) => (x.param_list, &x.params),
+            _ => {
+                node = parent;
+                continue;
+            }
+        };
+        return param_list && header.iter().all(|p| p.id() != param.id());
+    }
+    false
+}
+
 /// Allocate node IDs for a variable declaration.
 fn alloc_var_decl<'gcx>(
     cx: &impl Context<'gcx>,
@@ -1658,6 +1965,12 @@ fn lower_package<'gcx>(
                 next_rib = cx.map_ast_with_parent(AstNode::Typedef(def), next_rib);
                 names.push((def.name, next_rib));
             }
+            ast::ItemData::ImportDecl(ref decl) => {
+                for item in &decl.items {
+                    next_rib = cx.map_ast_with_parent(AstNode::Import(item), next_rib);
+                }
+            }
+            ast::ItemData::ExportDecl(..) => (),
             ast::ItemData::SubroutineDecl(ref decl) => {
                 warn!("ignoring unsupported subroutine `{}`", decl.prototype.name)
             }
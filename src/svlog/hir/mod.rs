@@ -118,6 +118,21 @@ where
         self.cx
     }
 
+    fn visit_stmt(&mut self, stmt: &'gcx Stmt) {
+        // Compound assignments such as `a += b` also read their left-hand side.
+        if let StmtKind::Assign {
+            lhs,
+            kind: AssignKind::Block(op),
+            ..
+        } = stmt.kind
+        {
+            if op != ast::AssignOp::Identity {
+                self.visit_node_with_id(lhs, false);
+            }
+        }
+        walk_stmt(self, stmt)
+    }
+
     fn visit_expr(&mut self, expr: &'gcx Expr, lvalue: bool) {
         if lvalue {
             self.cx.mir_lvalue(expr.id, self.env).walk(self);
@@ -216,9 +216,9 @@ pub struct InstTarget<'a> {
     /// The underlying AST node.
     pub ast: &'a ast::Inst<'a>,
     /// The positional parameters.
-    pub pos_params: Vec<PosParam>,
+    pub pos_params: &'a [PosParam],
     /// The named parameters.
-    pub named_params: Vec<NamedParam>,
+    pub named_params: &'a [NamedParam],
 }
 
 impl<'a> Deref for InstTarget<'a> {
@@ -266,9 +266,9 @@ pub struct Inst<'a> {
     /// The target of the instantiation.
     pub target: NodeId,
     /// The positional port connections.
-    pub pos_ports: Vec<PosParam>,
+    pub pos_ports: &'a [PosParam],
     /// The named port connections.
-    pub named_ports: Vec<NamedParam>,
+    pub named_ports: &'a [NamedParam],
     /// If the instantiation has a wildcard port connection `.*`.
     pub has_wildcard_port: bool,
 }
@@ -658,13 +658,13 @@ pub enum ExprKind<'a> {
     /// A scope expression such as `foo::bar`.
     Scope(NodeId, Spanned<Name>),
     /// A positional pattern such as `'{a, b, c}`.
-    PositionalPattern(Vec<NodeId>),
+    PositionalPattern(&'a [NodeId]),
     /// A named pattern such as `'{logic: a, foo: b, 31: c, default: d}`.
-    NamedPattern(Vec<(PatternMapping, NodeId)>),
+    NamedPattern(&'a [(PatternMapping, NodeId)]),
     /// A repeat pattern such as `'{32{a, b, c}}`.
-    RepeatPattern(NodeId, Vec<NodeId>),
+    RepeatPattern(NodeId, &'a [NodeId]),
     /// A concatenation such as `{a,b}` or `{4{a,b}}`.
-    Concat(Option<NodeId>, Vec<NodeId>),
+    Concat(Option<NodeId>, &'a [NodeId]),
     /// A cast `(ty, expr)` such as `foo'(bar)`.
     Cast(NodeId, NodeId),
     /// A sign cast such as `unsigned'(foo)`.
@@ -672,9 +672,9 @@ pub enum ExprKind<'a> {
     /// A size cast `(size_expr, expr)` such as `42'(foo)`.
     CastSize(NodeId, NodeId),
     /// An inside expression such as `a inside {b, c}`.
-    Inside(NodeId, Vec<Spanned<InsideRange>>),
+    Inside(NodeId, &'a [Spanned<InsideRange>]),
     /// A function call such as `foo(a, b, c)`.
-    FunctionCall(NodeId, Vec<CallArg>),
+    FunctionCall(NodeId, &'a [CallArg]),
     /// An assignment.
     Assign {
         op: ast::AssignOp,
@@ -966,6 +966,8 @@ impl HasDesc for Stmt {
             StmtKind::Null => "null statement",
             StmtKind::Block(_) => "block",
             StmtKind::Assign { .. } => "assign statement",
+            StmtKind::Assert { .. } => "assertion",
+            StmtKind::Wait { .. } => "wait statement",
             _ => "statement",
         }
     }
@@ -991,6 +993,12 @@ pub enum StmtKind {
     },
     /// An expression statement.
     Expr(NodeId),
+    /// A wait statement.
+    ///
+    /// ```text
+    /// wait (<cond>) <stmt>
+    /// ```
+    Wait { cond: NodeId, stmt: NodeId },
     /// An if statement.
     ///
     /// ```text
@@ -1017,6 +1025,24 @@ pub enum StmtKind {
         default: Option<NodeId>,
         kind: ast::CaseKind,
     },
+    /// An assertion.
+    ///
+    /// ```text
+    /// assert (<expr>) [<pass_stmt>] [else <fail_stmt>]
+    /// assert property (@(<clock>) disable iff (<disable>) <antecedent> |-> <expr>)
+    /// ```
+    ///
+    /// Concurrent assertions carry the clocking event they are checked on.
+    /// The check is skipped if `disable` or `antecedent` are given and do
+    /// not hold.
+    Assert {
+        clock: Option<NodeId>,
+        disable: Option<NodeId>,
+        antecedent: Option<NodeId>,
+        expr: NodeId,
+        pass_stmt: Option<NodeId>,
+        fail_stmt: Option<NodeId>,
+    },
 }
 
 /// The different forms an assignment can take.
@@ -1031,7 +1057,7 @@ pub enum AssignKind {
 }
 
 /// The different forms a loop can take.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LoopKind {
     /// A `forever` loop.
     Forever,
@@ -1041,8 +1067,8 @@ pub enum LoopKind {
     While(NodeId),
     //// A `do .. while (<cond>)` loop.
     Do(NodeId),
-    //// A `for (<init>; <cond>; <step>)` loop.
-    For(NodeId, NodeId, NodeId),
+    //// A `for (<init>; [<cond>]; {<step>})` loop.
+    For(NodeId, Option<NodeId>, Vec<NodeId>),
 }
 
 /// The different forms of timing control that can be applied to a statement.
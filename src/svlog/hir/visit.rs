@@ -166,6 +166,10 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
             visitor.visit_node_with_id(stmt, false);
         }
         StmtKind::Expr(expr) => visitor.visit_node_with_id(expr, false),
+        StmtKind::Wait { cond, stmt } => {
+            visitor.visit_node_with_id(cond, false);
+            visitor.visit_node_with_id(stmt, false);
+        }
         StmtKind::If {
             cond,
             main_stmt,
@@ -177,16 +181,20 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
                 visitor.visit_node_with_id(else_stmt, false);
             }
         }
-        StmtKind::Loop { kind, body } => {
-            match kind {
+        StmtKind::Loop { ref kind, body } => {
+            match *kind {
                 LoopKind::Forever => (),
                 LoopKind::Repeat(id) | LoopKind::While(id) | LoopKind::Do(id) => {
                     visitor.visit_node_with_id(id, false);
                 }
-                LoopKind::For(init, cond, step) => {
+                LoopKind::For(init, ref cond, ref step) => {
                     visitor.visit_node_with_id(init, false);
-                    visitor.visit_node_with_id(cond, false);
-                    visitor.visit_node_with_id(step, false);
+                    if let Some(cond) = *cond {
+                        visitor.visit_node_with_id(cond, false);
+                    }
+                    for &step in step {
+                        visitor.visit_node_with_id(step, false);
+                    }
                 }
             }
             visitor.visit_node_with_id(body, false);
@@ -213,6 +221,31 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
                 visitor.visit_node_with_id(default, false);
             }
         }
+        StmtKind::Assert {
+            clock,
+            disable,
+            antecedent,
+            expr,
+            pass_stmt,
+            fail_stmt,
+        } => {
+            if let Some(clock) = clock {
+                visitor.visit_node_with_id(clock, false);
+            }
+            if let Some(disable) = disable {
+                visitor.visit_node_with_id(disable, false);
+            }
+            if let Some(antecedent) = antecedent {
+                visitor.visit_node_with_id(antecedent, false);
+            }
+            visitor.visit_node_with_id(expr, false);
+            if let Some(pass_stmt) = pass_stmt {
+                visitor.visit_node_with_id(pass_stmt, false);
+            }
+            if let Some(fail_stmt) = fail_stmt {
+                visitor.visit_node_with_id(fail_stmt, false);
+            }
+        }
     }
 }
 
@@ -277,12 +310,12 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
         ExprKind::Scope(expr, _) => {
             visitor.visit_node_with_id(expr, false);
         }
-        ExprKind::PositionalPattern(ref exprs) => {
+        ExprKind::PositionalPattern(exprs) => {
             for &expr in exprs {
                 visitor.visit_node_with_id(expr, lvalue);
             }
         }
-        ExprKind::NamedPattern(ref mappings) => {
+        ExprKind::NamedPattern(mappings) => {
             for &(key, value) in mappings {
                 match key {
                     PatternMapping::Type(ty) => visitor.visit_node_with_id(ty, false),
@@ -292,13 +325,13 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
                 visitor.visit_node_with_id(value, lvalue);
             }
         }
-        ExprKind::RepeatPattern(count, ref exprs) => {
+        ExprKind::RepeatPattern(count, exprs) => {
             visitor.visit_node_with_id(count, lvalue);
             for &expr in exprs {
                 visitor.visit_node_with_id(expr, lvalue);
             }
         }
-        ExprKind::Concat(repeat, ref exprs) => {
+        ExprKind::Concat(repeat, exprs) => {
             if let Some(repeat) = repeat {
                 visitor.visit_node_with_id(repeat, false);
             }
@@ -317,7 +350,7 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
             visitor.visit_node_with_id(size_expr, false);
             visitor.visit_node_with_id(expr, false);
         }
-        ExprKind::Inside(expr, ref ranges) => {
+        ExprKind::Inside(expr, ranges) => {
             visitor.visit_node_with_id(expr, false);
             for r in ranges {
                 match r.value {
@@ -329,7 +362,7 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
                 }
             }
         }
-        ExprKind::FunctionCall(_, ref args) => {
+        ExprKind::FunctionCall(_, args) => {
             for &arg in args {
                 if let Some(expr) = arg.expr {
                     visitor.visit_node_with_id(expr, false);
@@ -0,0 +1,510 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Register and latch inference for procedures.
+//!
+//! This module analyzes the structure of `always`, `always_comb`,
+//! `always_latch`, and `always_ff` procedures before they are lowered. It
+//! determines the clock and asynchronous resets of sequential procedures, and
+//! the variables of combinational procedures which are not assigned on every
+//! path through the procedure and therefore hold their value like a latch.
+//! Any mismatch between the kind of procedure and the hardware it describes is
+//! reported as a warning.
+//!
+//! Writes to individual elements of unpacked arrays in clocked procedures are
+//! recognized as memory write ports. LLHD has no dedicated memory construct, so
+//! the code generator keeps such an array as a single array-typed signal and
+//! lowers each write port to a register on the addressed element, rather than
+//! emitting the write as part of the procedure.
+
+use crate::{
+    crate_prelude::*,
+    hir::{self, AccessedNode, HirNode},
+    Context, ParamEnv,
+};
+use std::collections::BTreeSet;
+
+/// The hardware inferred from a procedure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcInference {
+    /// The kind of the procedure.
+    pub kind: ast::ProcedureKind,
+    /// The clock and its edge, if the procedure is clocked.
+    pub clock: Option<(AccessedNode, ast::EdgeIdent)>,
+    /// The asynchronous resets and their edges.
+    pub resets: Vec<(AccessedNode, ast::EdgeIdent)>,
+    /// The signals the procedure is sensitive to, if it has an explicit event
+    /// list without edges.
+    pub sensitivity: Option<BTreeSet<AccessedNode>>,
+    /// The variables which are not assigned on every path through the
+    /// procedure.
+    pub latches: BTreeSet<AccessedNode>,
+    /// The memory write ports of a clocked procedure.
+    pub memories: Vec<MemoryWrite>,
+    /// Whether the procedure does nothing but write to memories.
+    pub only_memories: bool,
+}
+
+/// A memory write port inferred from a clocked procedure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryWrite {
+    /// The written array.
+    pub memory: AccessedNode,
+    /// The nonblocking assignment to an element of the array.
+    pub assign: NodeId,
+    /// The conditions under which the assignment is executed, together with
+    /// the value they must have.
+    pub conds: Vec<(NodeId, bool)>,
+}
+
+/// Analyze the hardware inferred from a procedure.
+///
+/// Returns `None` if the procedure does not describe hardware, for example an
+/// `initial` procedure or an `always` procedure with delays.
+pub fn infer_procedure<'a>(
+    cx: &impl Context<'a>,
+    hir: &hir::Proc,
+    env: ParamEnv,
+) -> Result<Option<ProcInference>> {
+    match hir.kind {
+        ast::ProcedureKind::Always
+        | ast::ProcedureKind::AlwaysComb
+        | ast::ProcedureKind::AlwaysLatch
+        | ast::ProcedureKind::AlwaysFf => (),
+        _ => return Ok(None),
+    }
+    let mut inf = ProcInference {
+        kind: hir.kind,
+        clock: None,
+        resets: vec![],
+        sensitivity: None,
+        latches: Default::default(),
+        memories: vec![],
+        only_memories: false,
+    };
+
+    // Find the event control at the top of the procedure.
+    let (events, body) = match top_level_stmt(cx, hir.stmt)?.kind {
+        hir::StmtKind::Timed {
+            control: hir::TimingControl::ExplicitEvent(expr_id),
+            stmt,
+        } => match cx.hir_of(expr_id)? {
+            HirNode::EventExpr(x) => (Some(x), stmt),
+            _ => unreachable!(),
+        },
+        hir::StmtKind::Timed {
+            control: hir::TimingControl::ImplicitEvent,
+            stmt,
+        } => (None, stmt),
+        hir::StmtKind::Timed { .. } => return Ok(None),
+        _ if hir.kind == ast::ProcedureKind::Always => return Ok(None),
+        _ => (None, hir.stmt),
+    };
+
+    // Separate the edge-sensitive from the level-sensitive events.
+    let mut edges = vec![];
+    let mut levels = BTreeSet::new();
+    for event in events.iter().flat_map(|e| e.events.iter()) {
+        let acc = cx.accessed_nodes(event.expr, env)?;
+        match event.edge {
+            ast::EdgeIdent::Posedge | ast::EdgeIdent::Negedge | ast::EdgeIdent::Edge => {
+                edges.extend(acc.read.iter().map(|&id| (id, event.edge)))
+            }
+            ast::EdgeIdent::Implicit => levels.extend(acc.read.iter().cloned()),
+        }
+    }
+
+    // Procedures triggered by edges are clocked. The edge-sensitive signals
+    // which are checked at the top of the procedure are asynchronous resets,
+    // and the remaining one is the clock.
+    if !edges.is_empty() {
+        let checked = checked_signals(cx, body, env)?;
+        let (resets, clocks): (Vec<_>, Vec<_>) =
+            edges.into_iter().partition(|(id, _)| checked.contains(id));
+        inf.resets = resets;
+        if let [clock] = clocks[..] {
+            inf.clock = Some(clock);
+        }
+        if inf.clock.is_some() && inf.resets.is_empty() {
+            let (memories, only_memories) = memory_writes(cx, body, env)?;
+            inf.memories = memories;
+            inf.only_memories = only_memories;
+        }
+        return Ok(Some(inf));
+    }
+    if events.is_some() {
+        inf.sensitivity = Some(levels);
+    }
+
+    // Combinational procedures infer a latch for every variable which is not
+    // assigned on every path.
+    let acc = cx.accessed_nodes(body, env)?;
+    let assigned = assigned_nodes(cx, body, env)?;
+    inf.latches = acc.written.difference(&assigned).cloned().collect();
+    Ok(Some(inf))
+}
+
+/// Analyze a procedure and report unintended hardware.
+///
+/// Emits warnings for `always_ff` procedures without a unique clock,
+/// combinational procedures which infer latches, `always_latch` procedures
+/// which infer no latch, and incomplete sensitivity lists.
+pub fn check_procedure<'a>(
+    cx: &impl Context<'a>,
+    hir: &hir::Proc,
+    env: ParamEnv,
+) -> Result<Option<ProcInference>> {
+    let inf = match infer_procedure(cx, hir, env)? {
+        Some(inf) => inf,
+        None => return Ok(None),
+    };
+    let names = |ids: &mut dyn Iterator<Item = &AccessedNode>| {
+        ids.map(|&id| format!("`{}`", node_name(cx, id)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let is_clocked = inf.clock.is_some() || !inf.resets.is_empty();
+
+    // Check that sequential procedures have a clock.
+    if hir.kind == ast::ProcedureKind::AlwaysFf && inf.clock.is_none() {
+        let d = DiagBuilder2::warning("`always_ff` procedure does not have a unique clock")
+            .span(hir.span);
+        cx.emit(if is_clocked {
+            d.add_note(format!(
+                "None of the edge-sensitive signals {} is used as clock; signals checked at \
+                 the top of the procedure are considered asynchronous resets",
+                names(&mut inf.resets.iter().map(|(id, _)| id))
+            ))
+        } else {
+            d.add_note("Use an event control such as `@(posedge clk)` to infer registers")
+        });
+    }
+    if is_clocked {
+        return Ok(Some(inf));
+    }
+
+    // Check that combinational procedures are sensitive to all their inputs.
+    if let Some(ref sensitivity) = inf.sensitivity {
+        let acc = cx.accessed_nodes(hir.stmt, env)?;
+        let missing: Vec<_> = acc
+            .read
+            .iter()
+            .filter(|id| !sensitivity.contains(id))
+            .collect();
+        if !missing.is_empty() {
+            cx.emit(
+                DiagBuilder2::warning("incomplete sensitivity list")
+                    .span(hir.span)
+                    .add_note(format!(
+                        "The procedure reads {}, which {} not in the event list",
+                        names(&mut missing.iter().cloned()),
+                        if missing.len() == 1 { "is" } else { "are" }
+                    ))
+                    .add_note("Use `always_comb` or `@*` to be sensitive to all inputs"),
+            );
+        }
+    }
+
+    // Check that latches are only inferred where intended.
+    if hir.kind == ast::ProcedureKind::AlwaysLatch {
+        if inf.latches.is_empty() {
+            cx.emit(
+                DiagBuilder2::warning("`always_latch` procedure does not infer a latch")
+                    .span(hir.span)
+                    .add_note("All variables are assigned on every path through the procedure")
+                    .add_note("Use `always_comb` to describe combinational logic"),
+            );
+        }
+    } else if !inf.latches.is_empty() {
+        cx.emit(
+            DiagBuilder2::warning(format!(
+                "latch inferred for {}",
+                names(&mut inf.latches.iter())
+            ))
+            .span(hir.span)
+            .add_note("Not assigned on every path through the procedure")
+            .add_note("Assign a default value at the beginning of the procedure, or use `always_latch` if a latch is intended"),
+        );
+    }
+    Ok(Some(inf))
+}
+
+/// Print the hardware inferred from a procedure.
+pub fn print_procedure<'a>(cx: &impl Context<'a>, hir: &hir::Proc, inf: &ProcInference) {
+    let (line, _, _) = hir.span.begin().human();
+    let kind = match inf.kind {
+        ast::ProcedureKind::Always => "always",
+        ast::ProcedureKind::AlwaysComb => "always_comb",
+        ast::ProcedureKind::AlwaysLatch => "always_latch",
+        ast::ProcedureKind::AlwaysFf => "always_ff",
+        _ => unreachable!(),
+    };
+    println!("Procedure `{}` on line {}:", kind, line);
+    let edge = |edge| match edge {
+        ast::EdgeIdent::Posedge => "posedge",
+        ast::EdgeIdent::Negedge => "negedge",
+        _ => "edge",
+    };
+    if let Some((id, e)) = inf.clock {
+        println!("  clock: {} {}", edge(e), node_name(cx, id));
+    }
+    for &(id, e) in &inf.resets {
+        println!("  async reset: {} {}", edge(e), node_name(cx, id));
+    }
+    for &id in &inf.latches {
+        println!("  latch: {}", node_name(cx, id));
+    }
+    for write in &inf.memories {
+        println!("  memory write: {}", node_name(cx, write.memory));
+    }
+}
+
+/// Find the memory write ports in the body of a clocked procedure.
+///
+/// Only nonblocking assignments to an element of an unpacked array, which are
+/// nested in `if` statements at most, are considered. They must not depend on
+/// any variable which the procedure assigns with a blocking assignment, and
+/// the procedure must not write to the array in any other way. Also returns
+/// whether the procedure consists of nothing but these memory writes.
+fn memory_writes<'a>(
+    cx: &impl Context<'a>,
+    body: NodeId,
+    env: ParamEnv,
+) -> Result<(Vec<MemoryWrite>, bool)> {
+    let mut finder = MemoryWriteFinder {
+        cx,
+        env,
+        candidates: vec![],
+        blocking: Default::default(),
+        others: Default::default(),
+    };
+    finder.visit(body, &mut vec![])?;
+    let MemoryWriteFinder {
+        candidates,
+        blocking,
+        others,
+        ..
+    } = finder;
+
+    // Discard the writes which depend on blocking assignments, and the arrays
+    // which are also written in other ways.
+    let mut writes = vec![];
+    let mut discarded = others;
+    for (write, reads) in candidates {
+        if reads.iter().any(|id| blocking.contains(id)) {
+            discarded.insert(write.memory);
+        } else {
+            writes.push(write);
+        }
+    }
+    writes.retain(|w| !discarded.contains(&w.memory));
+    let only_memories = !writes.is_empty() && discarded.is_empty();
+    Ok((writes, only_memories))
+}
+
+/// A helper to traverse a procedure body in search of memory writes.
+struct MemoryWriteFinder<'c, C> {
+    cx: &'c C,
+    env: ParamEnv,
+    /// The memory writes found, together with the nodes they read.
+    candidates: Vec<(MemoryWrite, BTreeSet<AccessedNode>)>,
+    /// The nodes written by blocking assignments.
+    blocking: BTreeSet<AccessedNode>,
+    /// The nodes written by statements other than memory writes.
+    others: BTreeSet<AccessedNode>,
+}
+
+impl<'a, 'c, C: Context<'a>> MemoryWriteFinder<'c, C> {
+    fn visit(&mut self, id: NodeId, conds: &mut Vec<(NodeId, bool)>) -> Result<()> {
+        let cx = self.cx;
+        let env = self.env;
+        let stmt = match cx.hir_of(id)? {
+            HirNode::Stmt(x) => x,
+            _ => return Ok(()),
+        };
+        match stmt.kind {
+            hir::StmtKind::Null => (),
+            hir::StmtKind::Block(ref stmts) => {
+                for &stmt in stmts {
+                    self.visit(stmt, conds)?;
+                }
+            }
+            hir::StmtKind::If {
+                cond,
+                main_stmt,
+                else_stmt,
+            } => {
+                conds.push((cond, true));
+                self.visit(main_stmt, conds)?;
+                conds.pop();
+                if let Some(else_stmt) = else_stmt {
+                    conds.push((cond, false));
+                    self.visit(else_stmt, conds)?;
+                    conds.pop();
+                }
+            }
+            hir::StmtKind::Assign {
+                lhs,
+                kind: hir::AssignKind::Nonblock,
+                ..
+            } if self.is_element_write(id, lhs)? => {
+                let acc = cx.accessed_nodes(id, env)?;
+                let mut reads = acc.read.clone();
+                for &(cond, _) in conds.iter() {
+                    reads.extend(cx.accessed_nodes(cond, env)?.read.iter().cloned());
+                }
+                let write = MemoryWrite {
+                    memory: *acc.written.iter().next().unwrap(),
+                    assign: id,
+                    conds: conds.clone(),
+                };
+                self.candidates.push((write, reads));
+            }
+            hir::StmtKind::Assign {
+                kind: hir::AssignKind::Nonblock,
+                ..
+            } => {
+                let acc = cx.accessed_nodes(id, env)?;
+                self.others.extend(acc.written.iter().cloned());
+            }
+            _ => {
+                let acc = cx.accessed_nodes(id, env)?;
+                self.others.extend(acc.written.iter().cloned());
+                self.blocking.extend(acc.written.iter().cloned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check whether an assignment writes to an element of an unpacked array.
+    fn is_element_write(&self, assign: NodeId, lhs: NodeId) -> Result<bool> {
+        let cx = self.cx;
+        let target = match cx.hir_of(lhs)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Index(target, hir::IndexMode::One(..)),
+                ..
+            }) => *target,
+            _ => return Ok(false),
+        };
+        match cx.hir_of(target)? {
+            HirNode::Expr(hir::Expr {
+                kind: hir::ExprKind::Ident(..),
+                ..
+            }) => (),
+            _ => return Ok(false),
+        }
+        let memory = cx.resolve_node(target, self.env)?;
+        let acc = cx.accessed_nodes(assign, self.env)?;
+        if acc.written.iter().ne(Some(&AccessedNode::Regular(memory))) {
+            return Ok(false);
+        }
+        Ok(!cx.type_of(memory, self.env)?.resolve_full().dims.is_empty())
+    }
+}
+
+/// Skip over blocks which contain only a single statement.
+fn top_level_stmt<'a>(cx: &impl Context<'a>, id: NodeId) -> Result<&'a hir::Stmt> {
+    let mut stmt = match cx.hir_of(id)? {
+        HirNode::Stmt(x) => x,
+        _ => unreachable!(),
+    };
+    while let hir::StmtKind::Block(ref stmts) = stmt.kind {
+        match (stmts.len(), stmts.first().map(|&id| cx.hir_of(id))) {
+            (1, Some(Ok(HirNode::Stmt(x)))) => stmt = x,
+            _ => break,
+        }
+    }
+    Ok(stmt)
+}
+
+/// Determine the signals checked by the `if` statements at the top of a
+/// procedure body, including any `else if` branches.
+fn checked_signals<'a>(
+    cx: &impl Context<'a>,
+    id: NodeId,
+    env: ParamEnv,
+) -> Result<BTreeSet<AccessedNode>> {
+    let mut checked = BTreeSet::new();
+    let mut next = Some(id);
+    while let Some(id) = next {
+        next = None;
+        if let hir::StmtKind::If {
+            cond, else_stmt, ..
+        } = top_level_stmt(cx, id)?.kind
+        {
+            checked.extend(cx.accessed_nodes(cond, env)?.read.iter().cloned());
+            next = else_stmt;
+        }
+    }
+    Ok(checked)
+}
+
+/// Determine the nodes which are assigned on every path through a statement.
+///
+/// Loops are assumed to execute at least once, and case statements without a
+/// default are assumed to not cover every value.
+fn assigned_nodes<'a>(
+    cx: &impl Context<'a>,
+    id: NodeId,
+    env: ParamEnv,
+) -> Result<BTreeSet<AccessedNode>> {
+    let stmt = match cx.hir_of(id)? {
+        HirNode::Stmt(x) => x,
+        _ => return Ok(Default::default()),
+    };
+    let intersect = |a: BTreeSet<AccessedNode>, b: BTreeSet<AccessedNode>| {
+        a.intersection(&b).cloned().collect::<BTreeSet<_>>()
+    };
+    Ok(match stmt.kind {
+        hir::StmtKind::Null | hir::StmtKind::Assert { .. } => Default::default(),
+        hir::StmtKind::Block(ref stmts) | hir::StmtKind::InlineGroup { ref stmts, .. } => {
+            let mut assigned = BTreeSet::new();
+            for &stmt in stmts {
+                assigned.extend(assigned_nodes(cx, stmt, env)?);
+            }
+            assigned
+        }
+        hir::StmtKind::Timed { stmt, .. } | hir::StmtKind::Wait { stmt, .. } => {
+            assigned_nodes(cx, stmt, env)?
+        }
+        hir::StmtKind::If {
+            main_stmt,
+            else_stmt,
+            ..
+        } => match else_stmt {
+            Some(else_stmt) => intersect(
+                assigned_nodes(cx, main_stmt, env)?,
+                assigned_nodes(cx, else_stmt, env)?,
+            ),
+            None => Default::default(),
+        },
+        hir::StmtKind::Case {
+            ref ways, default, ..
+        } => match default {
+            Some(default) => {
+                let mut assigned = assigned_nodes(cx, default, env)?;
+                for &(_, stmt) in ways {
+                    assigned = intersect(assigned, assigned_nodes(cx, stmt, env)?);
+                }
+                assigned
+            }
+            None => Default::default(),
+        },
+        hir::StmtKind::Assign { .. } | hir::StmtKind::Expr(..) | hir::StmtKind::Loop { .. } => {
+            cx.accessed_nodes(id, env)?.written.clone()
+        }
+    })
+}
+
+/// Determine the name of an accessed node for diagnostics.
+fn node_name<'a>(cx: &impl Context<'a>, id: AccessedNode) -> String {
+    let name = |id| match cx.hir_of(id) {
+        Ok(HirNode::VarDecl(x)) => x.name.value.to_string(),
+        Ok(HirNode::IntPort(x)) => x.name.value.to_string(),
+        Ok(HirNode::Inst(x)) => x.name.value.to_string(),
+        _ => "<unknown>".to_string(),
+    };
+    match id {
+        AccessedNode::Regular(id) => name(id),
+        AccessedNode::Intf(inst, id) => format!("{}.{}", name(inst), name(id)),
+    }
+}
@@ -10,7 +10,7 @@ use crate::{
     resolver::InstTarget,
     Context, ParamEnv, ParamEnvData, ParamEnvSource, PortMapping,
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 /// Instantiation details
 ///
@@ -214,3 +214,69 @@ impl<'a, 'gcx> hir::Visitor<'gcx> for InstVerbosityVisitor<'a, 'gcx> {
         .visit_node_with_id(details.target.kind.as_any().id(), false);
     }
 }
+
+/// A visitor that resolves all instantiations in a design hierarchy.
+///
+/// Walks the instantiation tree below a node and computes the details of each
+/// instantiation along the way, which reports any instance whose target or
+/// ports cannot be resolved. Use [`failed`] to check whether any instance was
+/// unresolved.
+///
+/// [`failed`]: InstHierarchyVisitor::failed
+pub struct InstHierarchyVisitor<'a, 'gcx> {
+    cx: &'a GlobalContext<'gcx>,
+    env: ParamEnv,
+    visited: HashSet<(NodeId, ParamEnv)>,
+    failed: bool,
+}
+
+impl<'a, 'gcx> InstHierarchyVisitor<'a, 'gcx> {
+    /// Create a new visitor that resolves instantiations.
+    pub fn new(cx: &'a GlobalContext<'gcx>) -> Self {
+        Self {
+            cx,
+            env: cx.default_param_env(),
+            visited: Default::default(),
+            failed: false,
+        }
+    }
+
+    /// Check whether any instantiation could not be resolved.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+}
+
+impl<'a, 'gcx> hir::Visitor<'gcx> for InstHierarchyVisitor<'a, 'gcx> {
+    type Context = GlobalContext<'gcx>;
+
+    fn context(&self) -> &Self::Context {
+        self.cx
+    }
+
+    fn visit_module(&mut self, hir: &'gcx hir::Module) {
+        // Only the instantiations are of interest here.
+        for &id in &hir.block.insts {
+            self.visit_node_with_id(id, false);
+        }
+    }
+
+    fn visit_inst(&mut self, hir: &'gcx hir::Inst<'gcx>) {
+        let details = match self.cx.inst_details(Ref(hir), self.env) {
+            Ok(x) => x,
+            Err(()) => {
+                self.failed = true;
+                return;
+            }
+        };
+
+        // Visit each instantiated node only once per parameter environment.
+        let id = details.target.kind.as_any().id();
+        if !self.visited.insert((id, details.inner_env)) {
+            return;
+        }
+        let outer_env = std::mem::replace(&mut self.env, details.inner_env);
+        self.visit_node_with_id(id, false);
+        self.env = outer_env;
+    }
+}
@@ -108,8 +108,11 @@ macro_rules! assert_type {
 mod ast_map;
 mod codegen;
 mod context;
+pub mod hierarchy;
 pub mod hir;
+pub mod inference;
 mod inst_details;
+pub mod lint;
 pub mod mir;
 mod param_env;
 #[warn(missing_docs)]
@@ -132,9 +135,9 @@ pub use moore_common::{
 pub type Result<T> = std::result::Result<T, ()>;
 
 pub use crate::{
-    codegen::CodeGenerator,
+    codegen::{CodeGenerator, ASSERTION_FAILED},
     context::*,
-    inst_details::{InstDetails, InstTargetDetails, InstVerbosityVisitor},
+    inst_details::{InstDetails, InstHierarchyVisitor, InstTargetDetails, InstVerbosityVisitor},
     param_env::{
         IntoNodeEnvId, NodeEnvId, ParamEnv, ParamEnvBinding, ParamEnvData, ParamEnvSource,
     },
@@ -0,0 +1,693 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Lints on the SystemVerilog AST.
+//!
+//! Lints point out code which is legal but likely a mistake, such as a net
+//! which is implicitly declared by a misspelled name. Each lint is a `Rule`
+//! with a name and a diagnostic code, registered with a `Linter` that walks the
+//! modules of the parsed files. Rules are off by default, and are enabled or
+//! disabled by their name or code, like the `-W` option of the compiler.
+
+use crate::ast;
+use crate::crate_prelude::*;
+use std::collections::HashSet;
+
+/// How the findings of a rule are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// The rule is not checked.
+    Allow,
+    /// Findings are reported as warnings.
+    Warn,
+    /// Findings are reported as errors.
+    Deny,
+}
+
+/// A lint rule.
+pub trait Rule {
+    /// The name by which the rule is enabled, e.g. `unused-signal`.
+    fn name(&self) -> &'static str;
+
+    /// The diagnostic code of the rule, e.g. `L0004`.
+    fn code(&self) -> &'static str;
+
+    /// A one-line description of what the rule checks.
+    fn description(&self) -> &'static str;
+
+    /// Check a module and report findings to the context.
+    fn check_module<'a>(&self, module: &'a ast::Module<'a>, cx: &mut LintContext);
+}
+
+/// The information available to a rule while it checks a module.
+pub struct LintContext<'c> {
+    /// The names declared in the module, at any depth, and in the files.
+    pub declared: &'c HashSet<Name>,
+    /// Whether the module or its files import all names of a package, such
+    /// that undeclared names may be defined there.
+    pub wildcard_import: bool,
+    findings: Vec<DiagBuilder2>,
+}
+
+impl LintContext<'_> {
+    /// Report a finding of the rule being checked.
+    ///
+    /// The severity of the diagnostic is set by the linter according to the
+    /// level of the rule.
+    pub fn report(&mut self, diag: DiagBuilder2) {
+        self.findings.push(diag);
+    }
+}
+
+/// A set of rules and their levels.
+pub struct Linter {
+    rules: Vec<(Box<dyn Rule>, Level)>,
+    /// Whether to report warnings as errors.
+    warnings_as_errors: bool,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        let mut linter = Linter {
+            rules: vec![],
+            warnings_as_errors: false,
+        };
+        linter.register(Box::new(ImplicitNet), Level::Allow);
+        linter.register(Box::new(BlockingInFf), Level::Allow);
+        linter.register(Box::new(UniqueCaseDefault), Level::Allow);
+        linter.register(Box::new(UnusedSignal), Level::Allow);
+        linter
+    }
+}
+
+impl Linter {
+    /// Create a linter with the built-in rules, all of which are disabled.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a rule to the linter.
+    pub fn register(&mut self, rule: Box<dyn Rule>, level: Level) {
+        self.rules.push((rule, level));
+    }
+
+    /// The registered rules and their levels.
+    pub fn rules(&self) -> impl Iterator<Item = (&dyn Rule, Level)> {
+        self.rules
+            .iter()
+            .map(|(rule, level)| (rule.as_ref(), *level))
+    }
+
+    /// Set the level of the rules with a name or code, or of all rules if
+    /// `name` is `all`.
+    ///
+    /// Returns an error if no rule has the given name or code.
+    pub fn set_level(&mut self, name: &str, level: Level) -> Result<()> {
+        let mut found = false;
+        for (rule, lvl) in &mut self.rules {
+            if name == "all" || rule.name() == name || rule.code() == name {
+                *lvl = level;
+                found = true;
+            }
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Apply a `-W` flag.
+    ///
+    /// The flag is one of the following:
+    /// - `NAME` enables a rule, where `NAME` is its name, code, or `all`
+    /// - `no-NAME` disables a rule
+    /// - `error=NAME` reports the findings of a rule as errors
+    /// - `error` reports the findings of all enabled rules as errors
+    pub fn configure(&mut self, flag: &str) -> Result<()> {
+        if flag == "error" {
+            self.warnings_as_errors = true;
+            Ok(())
+        } else if let Some(name) = strip_prefix(flag, "no-") {
+            self.set_level(name, Level::Allow)
+        } else if let Some(name) = strip_prefix(flag, "error=") {
+            self.set_level(name, Level::Deny)
+        } else {
+            self.set_level(flag, Level::Warn)
+        }
+    }
+
+    /// Check whether any rule is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.rules.iter().any(|&(_, level)| level != Level::Allow)
+    }
+
+    /// Check the modules in a list of files.
+    ///
+    /// Returns the findings of the enabled rules, in the order of the modules
+    /// and rules. The first finding of each rule notes how to disable it.
+    pub fn check<'a>(&self, files: &[&'a ast::SourceFile<'a>]) -> Vec<DiagBuilder2> {
+        // Collect the names declared outside of modules, which are visible
+        // in all modules.
+        let mut unit = Declarations::default();
+        for file in files {
+            for item in &file.items {
+                match item.data {
+                    ast::ItemData::ModuleDecl(ref x) => {
+                        unit.names.insert(x.name.value);
+                    }
+                    ast::ItemData::InterfaceDecl(ref x) => {
+                        unit.names.insert(x.name.value);
+                    }
+                    ast::ItemData::PackageDecl(ref x) => {
+                        unit.names.insert(x.name.value);
+                    }
+                    _ => ast::AcceptVisitor::accept(item, &mut unit),
+                }
+            }
+        }
+
+        let mut diags = vec![];
+        let mut reported = HashSet::new();
+        for file in files {
+            for item in &file.items {
+                let module = match item.data {
+                    ast::ItemData::ModuleDecl(ref x) => x,
+                    _ => continue,
+                };
+                let mut decls = Declarations {
+                    names: unit.names.clone(),
+                    wildcard_import: unit.wildcard_import,
+                };
+                ast::AcceptVisitor::accept(module, &mut decls);
+                for (rule, level) in &self.rules {
+                    let severity = match level {
+                        Level::Allow => continue,
+                        Level::Warn if !self.warnings_as_errors => Severity::Warning,
+                        _ => Severity::Error,
+                    };
+                    let mut cx = LintContext {
+                        declared: &decls.names,
+                        wildcard_import: decls.wildcard_import,
+                        findings: vec![],
+                    };
+                    rule.check_module(module, &mut cx);
+                    for mut diag in cx.findings {
+                        diag.severity = severity;
+                        diag.message = format!("{} [{}]", diag.message, rule.code());
+                        if reported.insert(rule.name()) {
+                            diag = diag.add_note(format!(
+                                "Reported by `-W {}`; use `-W no-{}` to disable",
+                                rule.name(),
+                                rule.name()
+                            ));
+                        }
+                        diags.push(diag);
+                    }
+                }
+            }
+        }
+        diags
+    }
+}
+
+/// Strip a prefix from a string.
+fn strip_prefix<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.starts_with(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Collects the names declared in a subtree of the AST, ignoring scopes.
+#[derive(Default)]
+struct Declarations {
+    names: HashSet<Name>,
+    wildcard_import: bool,
+}
+
+impl<'a> ast::Visitor<'a> for Declarations {
+    fn pre_visit_port(&mut self, node: &'a ast::Port<'a>) -> bool {
+        match node.data {
+            ast::PortData::Intf { name, .. }
+            | ast::PortData::Explicit { name, .. }
+            | ast::PortData::Named { name, .. } => {
+                self.names.insert(name.value);
+            }
+            ast::PortData::Implicit(..) => (),
+        }
+        true
+    }
+
+    fn pre_visit_var_decl_name(&mut self, node: &'a ast::VarDeclName<'a>) -> bool {
+        self.names.insert(node.name);
+        true
+    }
+
+    fn pre_visit_param_value_decl(&mut self, node: &'a ast::ParamValueDecl<'a>) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_param_type_decl(&mut self, node: &'a ast::ParamTypeDecl<'a>) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_genvar_decl(&mut self, node: &'a ast::GenvarDecl<'a>) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_typedef(&mut self, node: &'a ast::Typedef<'a>) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_enum_name(&mut self, node: &'a ast::EnumName<'a>) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_inst_name(&mut self, node: &'a ast::InstName<'a>) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_subroutine_decl(&mut self, node: &'a ast::SubroutineDecl<'a>) -> bool {
+        self.names.insert(node.prototype.name.value);
+        true
+    }
+
+    fn pre_visit_subroutine_port_name(&mut self, node: &'a ast::SubroutinePortName<'a>) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_foreach_index(&mut self, node: &'a ast::ForeachIndex) -> bool {
+        self.names.insert(node.name.value);
+        true
+    }
+
+    fn pre_visit_generate_block(&mut self, node: &'a ast::GenerateBlock<'a>) -> bool {
+        if let Some(label) = node.label {
+            self.names.insert(label.value);
+        }
+        true
+    }
+
+    fn pre_visit_stmt(&mut self, node: &'a ast::Stmt<'a>) -> bool {
+        if let Some(label) = node.label {
+            self.names.insert(label);
+        }
+        true
+    }
+
+    fn pre_visit_import_item(&mut self, node: &'a ast::ImportItem) -> bool {
+        match node.name {
+            Some(name) => {
+                self.names.insert(name.value);
+            }
+            None => self.wildcard_import = true,
+        }
+        true
+    }
+}
+
+/// Collect the identifiers assigned by an lvalue, e.g. `a` and `b` in
+/// `{a, b[1]}`.
+fn assigned_idents<'a>(expr: &'a ast::Expr<'a>, into: &mut Vec<&'a ast::Expr<'a>>) {
+    match expr.data {
+        ast::IdentExpr(..) => into.push(expr),
+        ast::IndexExpr { ref indexee, .. } => assigned_idents(indexee, into),
+        ast::MemberExpr { ref expr, .. } => assigned_idents(expr, into),
+        ast::ConcatExpr { ref exprs, .. } => {
+            for expr in exprs {
+                assigned_idents(expr, into);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// The name of an identifier expression.
+fn ident_name(expr: &ast::Expr) -> Option<Spanned<Name>> {
+    match expr.data {
+        ast::IdentExpr(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Flags nets which are implicitly declared by a continuous assignment or
+/// port connection to an undeclared name.
+pub struct ImplicitNet;
+
+impl Rule for ImplicitNet {
+    fn name(&self) -> &'static str {
+        "implicit-net"
+    }
+
+    fn code(&self) -> &'static str {
+        "L0001"
+    }
+
+    fn description(&self) -> &'static str {
+        "net implicitly declared by an assignment or port connection"
+    }
+
+    fn check_module<'a>(&self, module: &'a ast::Module<'a>, cx: &mut LintContext) {
+        if cx.wildcard_import {
+            return;
+        }
+        let mut visitor = ImplicitNetVisitor {
+            idents: vec![],
+            seen: HashSet::new(),
+        };
+        ast::AcceptVisitor::accept(module, &mut visitor);
+        for name in visitor.idents {
+            if cx.declared.contains(&name.value) || !visitor.seen.insert(name.value) {
+                continue;
+            }
+            cx.report(
+                DiagBuilder2::warning(format!("implicit declaration of net `{}`", name.value))
+                    .span(name.span)
+                    .add_note(format!(
+                        "`{}` is not declared; declare it explicitly, e.g. as `wire {}`",
+                        name.value, name.value
+                    )),
+            );
+        }
+    }
+}
+
+struct ImplicitNetVisitor {
+    /// The identifiers which may implicitly declare a net.
+    idents: Vec<Spanned<Name>>,
+    /// The names already reported.
+    seen: HashSet<Name>,
+}
+
+impl<'a> ast::Visitor<'a> for ImplicitNetVisitor {
+    fn pre_visit_cont_assign(&mut self, node: &'a ast::ContAssign<'a>) -> bool {
+        for (lhs, _) in &node.assignments {
+            let mut idents = vec![];
+            assigned_idents(lhs, &mut idents);
+            self.idents.extend(idents.into_iter().flat_map(ident_name));
+        }
+        true
+    }
+
+    fn pre_visit_port_conn(&mut self, node: &'a ast::PortConn<'a>) -> bool {
+        match node.data {
+            ast::PortConnData::Positional(ref expr)
+            | ast::PortConnData::Named(_, ast::PortConnMode::Connected(ref expr)) => {
+                self.idents.extend(ident_name(expr));
+            }
+            _ => (),
+        }
+        true
+    }
+}
+
+/// Flags blocking assignments in `always_ff` procedures.
+pub struct BlockingInFf;
+
+impl Rule for BlockingInFf {
+    fn name(&self) -> &'static str {
+        "blocking-in-ff"
+    }
+
+    fn code(&self) -> &'static str {
+        "L0002"
+    }
+
+    fn description(&self) -> &'static str {
+        "blocking assignment in an `always_ff` procedure"
+    }
+
+    fn check_module<'a>(&self, module: &'a ast::Module<'a>, cx: &mut LintContext) {
+        let mut visitor = BlockingInFfVisitor {
+            in_ff: false,
+            locals: HashSet::new(),
+            loop_inits: HashSet::new(),
+            findings: vec![],
+        };
+        ast::AcceptVisitor::accept(module, &mut visitor);
+        for diag in visitor.findings {
+            cx.report(diag);
+        }
+    }
+}
+
+struct BlockingInFfVisitor {
+    in_ff: bool,
+    /// The variables declared within the current procedure.
+    locals: HashSet<Name>,
+    /// The initializations of `for` loops, which assign the loop variable.
+    loop_inits: HashSet<NodeId>,
+    findings: Vec<DiagBuilder2>,
+}
+
+impl<'a> ast::Visitor<'a> for BlockingInFfVisitor {
+    fn pre_visit_procedure(&mut self, node: &'a ast::Procedure<'a>) -> bool {
+        self.in_ff = node.kind == ast::ProcedureKind::AlwaysFf;
+        self.locals.clear();
+        self.in_ff
+    }
+
+    fn post_visit_procedure(&mut self, _: &'a ast::Procedure<'a>) {
+        self.in_ff = false;
+    }
+
+    fn pre_visit_var_decl_name(&mut self, node: &'a ast::VarDeclName<'a>) -> bool {
+        if self.in_ff {
+            self.locals.insert(node.name);
+        }
+        true
+    }
+
+    fn pre_visit_stmt(&mut self, node: &'a ast::Stmt<'a>) -> bool {
+        if !self.in_ff {
+            return true;
+        }
+        match node.kind {
+            ast::ForStmt(ref init, ..) => {
+                self.loop_inits.insert(init.id());
+            }
+            ast::BlockingAssignStmt { ref lhs, .. } if !self.loop_inits.contains(&node.id()) => {
+                let mut idents = vec![];
+                assigned_idents(lhs, &mut idents);
+                let names: Vec<_> = idents
+                    .into_iter()
+                    .flat_map(ident_name)
+                    .filter(|name| !self.locals.contains(&name.value))
+                    .collect();
+                if let Some(name) = names.first() {
+                    self.findings.push(
+                        DiagBuilder2::warning("blocking assignment in `always_ff`")
+                            .span(node.span)
+                            .add_note(format!(
+                                "Use a nonblocking assignment `<=` to assign `{}`",
+                                name.value
+                            )),
+                    );
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+}
+
+/// Flags `unique` and `priority` case statements without a `default` item.
+pub struct UniqueCaseDefault;
+
+impl Rule for UniqueCaseDefault {
+    fn name(&self) -> &'static str {
+        "unique-case-default"
+    }
+
+    fn code(&self) -> &'static str {
+        "L0003"
+    }
+
+    fn description(&self) -> &'static str {
+        "`unique` or `priority` case without a `default` item"
+    }
+
+    fn check_module<'a>(&self, module: &'a ast::Module<'a>, cx: &mut LintContext) {
+        let mut visitor = UniqueCaseDefaultVisitor { findings: vec![] };
+        ast::AcceptVisitor::accept(module, &mut visitor);
+        for diag in visitor.findings {
+            cx.report(diag);
+        }
+    }
+}
+
+struct UniqueCaseDefaultVisitor {
+    findings: Vec<DiagBuilder2>,
+}
+
+impl<'a> ast::Visitor<'a> for UniqueCaseDefaultVisitor {
+    fn pre_visit_stmt(&mut self, node: &'a ast::Stmt<'a>) -> bool {
+        if let ast::CaseStmt {
+            up: Some(up),
+            kind,
+            ref items,
+            ..
+        } = node.kind
+        {
+            let up = match up {
+                ast::UniquePriority::Unique => "unique",
+                ast::UniquePriority::Priority => "priority",
+                ast::UniquePriority::Unique0 => return true,
+            };
+            let kind = match kind {
+                ast::CaseKind::Normal => "case",
+                ast::CaseKind::DontCareZ => "casez",
+                ast::CaseKind::DontCareXZ => "casex",
+            };
+            let has_default = items
+                .iter()
+                .any(|item| matches!(item, ast::CaseItem::Default(..)));
+            if !has_default {
+                self.findings.push(
+                    DiagBuilder2::warning(format!("`{} {}` without `default`", up, kind))
+                        .span(node.span)
+                        .add_note("Add a `default` item for values which match no item"),
+                );
+            }
+        }
+        true
+    }
+}
+
+/// Flags nets and variables of a module which are never read.
+pub struct UnusedSignal;
+
+impl Rule for UnusedSignal {
+    fn name(&self) -> &'static str {
+        "unused-signal"
+    }
+
+    fn code(&self) -> &'static str {
+        "L0004"
+    }
+
+    fn description(&self) -> &'static str {
+        "net or variable which is never read"
+    }
+
+    fn check_module<'a>(&self, module: &'a ast::Module<'a>, cx: &mut LintContext) {
+        let mut signals = vec![];
+        module_signals(&module.items, &mut signals);
+        let mut visitor = UnusedSignalVisitor::default();
+        ast::AcceptVisitor::accept(module, &mut visitor);
+        if visitor.auto_connected {
+            return;
+        }
+        for signal in signals {
+            if visitor.reads.contains(&signal.name) {
+                continue;
+            }
+            let msg = if visitor.writes.contains(&signal.name) {
+                format!("`{}` is never read", signal.name)
+            } else {
+                format!("`{}` is never used", signal.name)
+            };
+            cx.report(DiagBuilder2::warning(msg).span(signal.name_span));
+        }
+    }
+}
+
+/// Collect the nets and variables declared among the items of a module,
+/// including generate blocks.
+fn module_signals<'a>(items: &'a [ast::Item<'a>], into: &mut Vec<&'a ast::VarDeclName<'a>>) {
+    for item in items {
+        match item.data {
+            ast::ItemData::NetDecl(ref x) => into.extend(&x.names),
+            ast::ItemData::VarDecl(ref x) => into.extend(&x.names),
+            ast::ItemData::GenerateRegion(_, ref items) => module_signals(items, into),
+            ast::ItemData::GenerateFor(ref x) => module_signals(&x.block.items, into),
+            ast::ItemData::GenerateIf(ref x) => {
+                module_signals(&x.main_block.items, into);
+                if let Some(ref block) = x.else_block {
+                    module_signals(&block.items, into);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[derive(Default)]
+struct UnusedSignalVisitor {
+    reads: HashSet<Name>,
+    writes: HashSet<Name>,
+    /// The identifiers which are assigned.
+    targets: HashSet<NodeId>,
+    /// Whether the module has `.*` port connections.
+    auto_connected: bool,
+}
+
+impl UnusedSignalVisitor {
+    fn assign<'a>(&mut self, lhs: &'a ast::Expr<'a>) {
+        let mut idents = vec![];
+        assigned_idents(lhs, &mut idents);
+        self.targets.extend(idents.into_iter().map(|x| x.id()));
+    }
+}
+
+impl<'a> ast::Visitor<'a> for UnusedSignalVisitor {
+    fn pre_visit_cont_assign(&mut self, node: &'a ast::ContAssign<'a>) -> bool {
+        for (lhs, _) in &node.assignments {
+            self.assign(lhs);
+        }
+        true
+    }
+
+    fn pre_visit_stmt(&mut self, node: &'a ast::Stmt<'a>) -> bool {
+        match node.kind {
+            ast::BlockingAssignStmt {
+                ref lhs,
+                op: ast::AssignOp::Identity,
+                ..
+            }
+            | ast::NonblockingAssignStmt { ref lhs, .. } => self.assign(lhs),
+            _ => (),
+        }
+        true
+    }
+
+    fn pre_visit_expr(&mut self, node: &'a ast::Expr<'a>) -> bool {
+        match node.data {
+            ast::IdentExpr(name) if self.targets.contains(&node.id()) => {
+                self.writes.insert(name.value);
+            }
+            ast::IdentExpr(name) => {
+                self.reads.insert(name.value);
+            }
+            ast::AssignExpr {
+                op: ast::AssignOp::Identity,
+                ref lhs,
+                ..
+            } => self.assign(lhs),
+            ast::BitsExpr { name, .. } => {
+                self.reads.insert(name.value);
+            }
+            _ => (),
+        }
+        true
+    }
+
+    fn pre_visit_port_conn(&mut self, node: &'a ast::PortConn<'a>) -> bool {
+        match node.data {
+            ast::PortConnData::Auto => self.auto_connected = true,
+            ast::PortConnData::Named(name, ast::PortConnMode::Auto) => {
+                self.reads.insert(name.value);
+            }
+            _ => (),
+        }
+        true
+    }
+}
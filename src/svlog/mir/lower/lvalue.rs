@@ -199,6 +199,30 @@ fn lower_expr_inner<'a>(
                 if def.node.as_all().is_modport_name() {
                     return Ok(builder.build(ty, value.kind.clone()));
                 } else {
+                    // Signals which the modport lists as inputs cannot be
+                    // driven through it.
+                    if let Some(modport) = intf.modport {
+                        for port in &modport.ports {
+                            let ast::ModportPortData::Simple { dir, ref port } = port.data;
+                            let input = port
+                                .iter()
+                                .find(|p| p.expr.is_none() && p.name.value == name.value);
+                            if let (ast::PortDir::Input, Some(input)) = (dir.value, input) {
+                                cx.emit(
+                                    DiagBuilder2::error(format!(
+                                        "`{}` is an input of modport `{}` and cannot be \
+                                         assigned to",
+                                        name.value, modport.name.value
+                                    ))
+                                    .span(builder.span)
+                                    .add_note("Declared as input here:")
+                                    .span(input.name.span),
+                                );
+                                return Err(());
+                            }
+                        }
+                    }
+
                     return Ok(builder.build(ty, LvalueKind::IntfSignal(value, def.node.id())));
                 }
             } else {
@@ -207,7 +231,7 @@ fn lower_expr_inner<'a>(
             }
         }
 
-        hir::ExprKind::Concat(repeat, ref exprs) => {
+        hir::ExprKind::Concat(repeat, exprs) => {
             // Compute the SBVT for each expression and lower it to MIR,
             // implicitly casting to the SBVT.
             let exprs = exprs
@@ -238,7 +262,12 @@ fn lower_expr_inner<'a>(
             // Assemble the concatenation.
             let concat = builder.build(
                 concat_ty,
-                LvalueKind::Concat(exprs.into_iter().map(|(_, v)| v).collect()),
+                LvalueKind::Concat(
+                    builder
+                        .cx
+                        .arena()
+                        .alloc_list(exprs.into_iter().map(|(_, v)| v)),
+                ),
             );
 
             // If a repetition is present, apply that.
@@ -306,6 +335,19 @@ fn lower_cast<'a>(
             CastOp::PickModport => {
                 value = builder.build(to, value.kind.clone());
             }
+            CastOp::Range(..) => {
+                builder.cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` of type `{}` cannot be assigned a value of type `{}`",
+                        value.span.extract(),
+                        value.ty,
+                        to
+                    ))
+                    .span(value.span)
+                    .add_note("Assignment targets are not truncated or extended; adjust the size"),
+                );
+                return builder.error();
+            }
             _ => {
                 bug_span!(
                     value.span,
@@ -383,7 +425,10 @@ fn pack_struct<'a>(
     }
 
     // Concatenate the fields.
-    builder.build(to, LvalueKind::Concat(packed_fields))
+    builder.build(
+        to,
+        LvalueKind::Concat(builder.cx.arena().alloc_list(packed_fields)),
+    )
 }
 
 /// Pack an array as a simple bit vector.
@@ -447,5 +492,8 @@ fn pack_array<'a>(
     }
 
     // Concatenate the elements.
-    builder.build(to, LvalueKind::Concat(packed_elements))
+    builder.build(
+        to,
+        LvalueKind::Concat(builder.cx.arena().alloc_list(packed_elements)),
+    )
 }
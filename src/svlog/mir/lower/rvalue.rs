@@ -12,6 +12,7 @@ use crate::{
     value::{self, ValueData, ValueKind},
     ParamEnv,
 };
+use bit_vec::BitVec;
 use num::{BigInt, One, Signed, ToPrimitive, Zero};
 use std::{cmp::max, collections::HashMap};
 
@@ -152,10 +153,10 @@ fn lower_expr_inner<'gcx>(
             special_bits.clone(),
             x_bits.clone(),
         ))),
-        hir::ExprKind::UnsizedConst('0') => Ok(builder.constant(value::make_int(ty, num::zero()))),
-        hir::ExprKind::UnsizedConst('1') => Ok(builder.constant(value::make_int(ty, num::one()))),
-        hir::ExprKind::UnsizedConst('x') => Ok(builder.constant(value::make_int(ty, num::one()))),
-        hir::ExprKind::UnsizedConst('z') => Ok(builder.constant(value::make_int(ty, num::one()))),
+        hir::ExprKind::UnsizedConst(c @ '0')
+        | hir::ExprKind::UnsizedConst(c @ '1')
+        | hir::ExprKind::UnsizedConst(c @ 'x')
+        | hir::ExprKind::UnsizedConst(c @ 'z') => Ok(builder.constant(unsized_value(ty, c))),
         hir::ExprKind::UnsizedConst(c) => {
             bug_span!(span, cx, "unsized const with weird '{}' char", c)
         }
@@ -220,10 +221,30 @@ fn lower_expr_inner<'gcx>(
                 }
             }
         }
-        hir::ExprKind::Builtin(hir::BuiltinCall::CountOnes(_))
-        | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot(_))
-        | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot0(_)) => {
-            bug_span!(span, cx, "unsupported system function {:?}", hir.kind)
+        hir::ExprKind::Builtin(hir::BuiltinCall::CountOnes(arg)) => {
+            Ok(lower_count_ones(builder, arg))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::OneHot(arg))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::OneHot0(arg)) => {
+            let count = lower_count_ones(builder, arg);
+            if count.is_error() {
+                return Ok(count);
+            }
+            let op = match hir.kind {
+                hir::ExprKind::Builtin(hir::BuiltinCall::OneHot(_)) => IntCompOp::Eq,
+                _ => IntCompOp::Leq,
+            };
+            let one = builder.constant(value::make_int(count.ty, One::one()));
+            Ok(builder.build(
+                ty,
+                RvalueKind::IntComp {
+                    op,
+                    sign: ty::Sign::Signed,
+                    domain: ty::Domain::TwoValued,
+                    lhs: count,
+                    rhs: one,
+                },
+            ))
         }
         hir::ExprKind::Builtin(hir::BuiltinCall::IsUnknown(_)) => {
             // Since we currently don't emit logic types, this is always zero.
@@ -339,7 +360,7 @@ fn lower_expr_inner<'gcx>(
         | hir::ExprKind::RepeatPattern(..)
         | hir::ExprKind::NamedPattern(..) => Ok(lower_pattern(&builder, hir, ty)),
 
-        hir::ExprKind::Concat(repeat, ref exprs) => {
+        hir::ExprKind::Concat(repeat, exprs) => {
             // Compute the SBVT for each expression and lower it to MIR,
             // implicitly casting to the SBVT.
             let exprs = exprs
@@ -364,7 +385,12 @@ fn lower_expr_inner<'gcx>(
             // Assemble the concatenation.
             let concat = builder.build(
                 concat_ty,
-                RvalueKind::Concat(exprs.into_iter().map(|(_, v)| v).collect()),
+                RvalueKind::Concat(
+                    builder
+                        .cx
+                        .arena()
+                        .alloc_list(exprs.into_iter().map(|(_, v)| v)),
+                ),
             );
 
             // If a repetition is present, apply that.
@@ -438,7 +464,7 @@ fn lower_expr_inner<'gcx>(
         | hir::ExprKind::Builtin(hir::BuiltinCall::Signed(expr))
         | hir::ExprKind::Builtin(hir::BuiltinCall::Unsigned(expr)) => Ok(cx.mir_rvalue(expr, env)),
 
-        hir::ExprKind::Inside(expr, ref ranges) => {
+        hir::ExprKind::Inside(expr, ranges) => {
             // By default nothing matches.
             let mut check = builder.build(
                 ty,
@@ -679,6 +705,12 @@ fn lower_cast<'gcx>(
             CastOp::Range(range, signed) => {
                 assert_span!(value.ty.is_simple_bit_vector(), value.span, builder.cx);
                 assert_span!(to.is_simple_bit_vector(), value.span, builder.cx);
+                // Unbased unsized literals such as `'1` or `'x` fill all bits
+                // of the type they are cast to.
+                if let Some(digit) = unsized_digit(builder, value) {
+                    value = builder.constant(unsized_value(to, digit));
+                    continue;
+                }
                 let kind = if value.ty.simple_bit_vector(builder.cx, value.span).size < range.size {
                     match signed {
                         true => RvalueKind::SignExtend(range.size, value),
@@ -742,6 +774,44 @@ fn lower_cast<'gcx>(
     value
 }
 
+/// Create the value of an unbased unsized literal with all bits set to `digit`.
+fn unsized_value<'a>(ty: &'a UnpackedType<'a>, digit: char) -> ValueData<'a> {
+    let width = ty.get_bit_size().unwrap();
+    let special = |x| {
+        value::make_int_special(
+            ty,
+            BigInt::zero(),
+            BitVec::from_elem(width, true),
+            BitVec::from_elem(width, x),
+        )
+    };
+    match digit {
+        '0' => value::make_int(ty, BigInt::zero()),
+        '1' => value::make_int(ty, (BigInt::one() << width) - 1),
+        'x' => special(true),
+        _ => special(false),
+    }
+}
+
+/// Determine the digit of an unbased unsized literal that has not been cast
+/// yet.
+fn unsized_digit<'gcx>(
+    builder: &Builder<'_, impl Context<'gcx>>,
+    value: &'gcx Rvalue<'gcx>,
+) -> Option<char> {
+    match value.kind {
+        RvalueKind::Const(_) => (),
+        _ => return None,
+    }
+    match builder.cx.hir_of(value.origin) {
+        Ok(HirNode::Expr(hir::Expr {
+            kind: hir::ExprKind::UnsizedConst(digit),
+            ..
+        })) => Some(*digit),
+        _ => None,
+    }
+}
+
 /// Generate the nodes necessary to pack a value to its corresponding simple bit
 /// vector type.
 fn pack_simple_bit_vector<'gcx>(
@@ -788,7 +858,10 @@ fn pack_struct<'a>(
     }
 
     // Concatenate the fields.
-    builder.build(to, RvalueKind::Concat(packed_fields))
+    builder.build(
+        to,
+        RvalueKind::Concat(builder.cx.arena().alloc_list(packed_fields)),
+    )
 }
 
 /// Pack an array as a simple bit vector.
@@ -842,7 +915,10 @@ fn pack_array<'a>(
     }
 
     // Concatenate the elements.
-    builder.build(to, RvalueKind::Concat(packed_elements))
+    builder.build(
+        to,
+        RvalueKind::Concat(builder.cx.arena().alloc_list(packed_elements)),
+    )
 }
 
 /// Generate the nodes necessary to unpack a value from its corresponding simple
@@ -899,7 +975,10 @@ fn unpack_struct<'a>(
     }
 
     // Construct the struct.
-    builder.build(to, RvalueKind::ConstructStruct(unpacked_fields))
+    builder.build(
+        to,
+        RvalueKind::ConstructStruct(builder.cx.arena().alloc_list(unpacked_fields)),
+    )
 }
 
 /// Unpack an array from a simple bit vector.
@@ -984,7 +1063,10 @@ fn lower_pattern<'a>(
         if values.len() == 1 {
             values[0]
         } else {
-            builder.build(ty, RvalueKind::Concat(values))
+            builder.build(
+                ty,
+                RvalueKind::Concat(builder.cx.arena().alloc_list(values)),
+            )
         }
     } else if ty.outermost_dim().is_some() {
         builder.build(
@@ -993,7 +1075,10 @@ fn lower_pattern<'a>(
             RvalueKind::ConstructArray(values.into_iter().enumerate().collect()),
         )
     } else if ty.get_struct().is_some() {
-        builder.build(ty, RvalueKind::ConstructStruct(values))
+        builder.build(
+            ty,
+            RvalueKind::ConstructStruct(builder.cx.arena().alloc_list(values)),
+        )
     } else {
         bug_span!(
             builder.span,
@@ -1502,6 +1587,64 @@ fn lower_binary_logic<'a>(
     builder.build(result_ty, RvalueKind::BinaryBitwise { op, lhs, rhs })
 }
 
+/// Count the bits of a value which are set to one, as in `$countones(x)`.
+///
+/// The result is of type `int`. Bits which are x or z are not counted.
+fn lower_count_ones<'a>(
+    builder: &Builder<'_, impl Context<'a>>,
+    arg: &'a ast::Expr<'a>,
+) -> &'a Rvalue<'a> {
+    let cx = builder.cx;
+    let value = pack_simple_bit_vector(builder, cx.mir_rvalue(arg.id(), builder.env));
+    if value.is_error() {
+        return value;
+    }
+    let sbvt = value.ty.simple_bit_vector(cx, value.span);
+    let value = if sbvt.domain == ty::Domain::FourValued {
+        builder.build(
+            sbvt.change_domain(ty::Domain::TwoValued).to_unpacked(cx),
+            RvalueKind::CastValueDomain {
+                from: ty::Domain::FourValued,
+                to: ty::Domain::TwoValued,
+                value,
+            },
+        )
+    } else {
+        value
+    };
+
+    // Zero-extend each bit to an `int` and sum them up.
+    let int_ty = ty::PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx);
+    let bit_ty = ty::PackedType::make(cx, ty::IntVecType::Bit).to_unpacked(cx);
+    let mut count = builder.constant(value::make_int(int_ty, Zero::zero()));
+    for i in 0..sbvt.size {
+        let bit = if sbvt.size == 1 {
+            value
+        } else {
+            builder.build(
+                bit_ty,
+                RvalueKind::Index {
+                    value,
+                    base: builder.constant_u32(i as u32),
+                    length: 0,
+                },
+            )
+        };
+        let bit = builder.build(int_ty, RvalueKind::ZeroExtend(32, bit));
+        count = builder.build(
+            int_ty,
+            RvalueKind::IntBinaryArith {
+                op: IntBinaryArithOp::Add,
+                sign: ty::Sign::Signed,
+                domain: ty::Domain::TwoValued,
+                lhs: count,
+                rhs: bit,
+            },
+        );
+    }
+    count
+}
+
 /// Map a reduction operator to MIR.
 fn lower_reduction<'a>(
     builder: &Builder<'_, impl Context<'a>>,
@@ -67,12 +67,12 @@ impl<'a> Print for Lvalue<'a> {
         write!(inner, "Lvalue ")?;
         match self.kind {
             LvalueKind::Transmute(v) => write!(inner, "Transmute({})", ctx.print(outer, v))?,
-            LvalueKind::DestructArray(ref args) => write!(
+            LvalueKind::DestructArray(args) => write!(
                 inner,
                 "DestructArray({})",
                 ctx.print_comma_separated(outer, args),
             )?,
-            LvalueKind::DestructStruct(ref args) => write!(
+            LvalueKind::DestructStruct(args) => write!(
                 inner,
                 "DestructStruct({})",
                 ctx.print_comma_separated(outer, args),
@@ -109,7 +109,7 @@ impl<'a> Print for Lvalue<'a> {
             LvalueKind::Member { value, field } => {
                 write!(inner, "{}.{}", ctx.print(outer, value), field)?
             }
-            LvalueKind::Concat(ref args) => {
+            LvalueKind::Concat(args) => {
                 write!(inner, "Concat({})", ctx.print_comma_separated(outer, args))?
             }
             LvalueKind::Repeat(num, arg) => {
@@ -131,9 +131,9 @@ pub enum LvalueKind<'a> {
     /// `bit [31:0]` to `int`, or vice versa.
     Transmute(&'a Lvalue<'a>),
     /// Destructor for an array.
-    DestructArray(Vec<&'a Lvalue<'a>>),
+    DestructArray(&'a [&'a Lvalue<'a>]),
     /// Destructor for a struct.
-    DestructStruct(Vec<&'a Lvalue<'a>>),
+    DestructStruct(&'a [&'a Lvalue<'a>]),
     /// A reference to a genvar declaration.
     Genvar(NodeId),
     /// A reference to a variable declaration.
@@ -158,7 +158,7 @@ pub enum LvalueKind<'a> {
     /// is yet another packed bit vector. The lowest index corresponds to the
     /// left-most item in the concatenation, which is at the MSB end of the
     /// final packed bit vector.
-    Concat(Vec<&'a Lvalue<'a>>),
+    Concat(&'a [&'a Lvalue<'a>]),
     /// Repeat a value multiple times.
     ///
     /// The value is cast to and treated as a packed bit vector, and the result
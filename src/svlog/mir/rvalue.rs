@@ -104,7 +104,7 @@ impl<'a> Print for Rvalue<'a> {
                 "ConstructArray({})",
                 ctx.print_comma_separated(outer, args.iter().map(|(_idx, v)| v)),
             )?,
-            RvalueKind::ConstructStruct(ref args) => write!(
+            RvalueKind::ConstructStruct(args) => write!(
                 inner,
                 "ConstructStruct({})",
                 ctx.print_comma_separated(outer, args),
@@ -163,7 +163,7 @@ impl<'a> Print for Rvalue<'a> {
                 sign,
                 domain
             )?,
-            RvalueKind::Concat(ref args) => {
+            RvalueKind::Concat(args) => {
                 write!(inner, "Concat({})", ctx.print_comma_separated(outer, args))?
             }
             RvalueKind::Repeat(num, arg) => {
@@ -292,7 +292,7 @@ pub enum RvalueKind<'a> {
     /// Constructor for an array.
     ConstructArray(HashMap<usize, &'a Rvalue<'a>>),
     /// Constructor for a struct.
-    ConstructStruct(Vec<&'a Rvalue<'a>>),
+    ConstructStruct(&'a [&'a Rvalue<'a>]),
     /// A constant value.
     Const(value::Value<'a>),
     /// A unary bitwise operator.
@@ -346,7 +346,7 @@ pub enum RvalueKind<'a> {
     /// is yet another packed bit vector. The lowest index corresponds to the
     /// left-most item in the concatenation, which is at the MSB end of the
     /// final packed bit vector.
-    Concat(Vec<&'a Rvalue<'a>>),
+    Concat(&'a [&'a Rvalue<'a>]),
     /// Repeat a value multiple times.
     ///
     /// The value is cast to and treated as a packed bit vector, and the result
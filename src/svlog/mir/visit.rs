@@ -32,12 +32,20 @@ impl<'a> WalkVisitor<'a> for ty::Sign {}
 impl<'a> WalkVisitor<'a> for ty::Domain {}
 impl<'a> WalkVisitor<'a> for value::Value<'_> {}
 
-impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for &'_ T {
+impl<'a, T: WalkVisitor<'a> + ?Sized> WalkVisitor<'a> for &'_ T {
     fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
         (*self).walk(visitor);
     }
 }
 
+impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for [T] {
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
+        for x in self {
+            x.walk(visitor);
+        }
+    }
+}
+
 impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for Vec<T> {
     fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
         for x in self {
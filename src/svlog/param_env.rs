@@ -5,7 +5,7 @@
 use crate::{
     ast_map::AstNode,
     crate_prelude::*,
-    hir::{NamedParam, PosParam},
+    hir::{HirNode, NamedParam, PosParam},
     ty::UnpackedType,
     value::Value,
 };
@@ -119,6 +119,11 @@ impl<'t> ParamEnvData<'t> {
             .next()
     }
 
+    /// Get the interface parametrizations.
+    pub fn interfaces(&self) -> &[(NodeId, NodeEnvId)] {
+        &self.intfs
+    }
+
     /// Assign a value to a node.
     pub fn set_value(&mut self, node_id: NodeId, value: Value<'t>) {
         self.values.retain(|&(n, _)| n != node_id);
@@ -215,6 +220,21 @@ fn param_env_from_instance<'a>(
     pos: &[PosParam],
     named: &[NamedParam],
 ) -> Result<ParamEnv> {
+    // Local parameters cannot be overridden. Leave them out of positional
+    // assignments and report attempts to assign them by name.
+    let (locals, params): (Vec<NodeId>, Vec<NodeId>) =
+        params.into_iter().partition(|&id| match cx.hir_of(id) {
+            Ok(HirNode::ValueParam(p)) => p.local,
+            Ok(HirNode::TypeParam(p)) => p.local,
+            _ => false,
+        });
+    let param_name = |id| match cx.ast_of(id) {
+        Ok(AstNode::TypeParam(_, p)) => Some(p.name.value),
+        Ok(AstNode::ValueParam(_, p)) => Some(p.name.value),
+        Ok(_) => unreachable!(),
+        Err(()) => None,
+    };
+
     // Associate the positional and named assignments with the actual
     // parameters of the module.
     let param_iter = pos
@@ -233,18 +253,23 @@ fn param_env_from_instance<'a>(
         .chain(named.iter().map(|&(_span, name, assign_id)| {
             let names: Vec<_> = params
                 .iter()
-                .flat_map(|&id| match cx.ast_of(id) {
-                    Ok(AstNode::TypeParam(_, p)) => Some((p.name.value, id)),
-                    Ok(AstNode::ValueParam(_, p)) => Some((p.name.value, id)),
-                    Ok(_) => unreachable!(),
-                    Err(()) => None,
-                })
+                .flat_map(|&id| param_name(id).map(|n| (n, id)))
                 .collect();
             match names
                 .iter()
                 .find(|&(param_name, _)| *param_name == name.value)
             {
                 Some(&(_, param_id)) => Ok((param_id, (assign_id, env))),
+                None if locals.iter().any(|&id| param_name(id) == Some(name.value)) => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "cannot override local parameter `{}` of {}",
+                            name, node
+                        ))
+                        .span(name.span),
+                    );
+                    Err(())
+                }
                 None => {
                     cx.emit(
                         DiagBuilder2::error(format!("no parameter `{}` in {}", name, node,))
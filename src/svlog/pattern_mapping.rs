@@ -15,8 +15,9 @@ pub struct PatternMapping<'a> {
     pub hir: &'a hir::Expr<'a>,
     /// The type the pattern maps to.
     pub ty: &'a ty::UnpackedType<'a>,
-    /// The mapped expression for each field. The fields are in type order.
-    /// Multiple fields may be assigned the same expression.
+    /// The mapped expression for each field. The fields are in type order,
+    /// which for types that coalesce to a packed bit vector is from the MSB to
+    /// the LSB. Multiple fields may be assigned the same expression.
     pub fields: Vec<(PatternField<'a>, &'a hir::Expr<'a>)>,
 }
 
@@ -62,10 +63,10 @@ pub(crate) fn map_pattern<'a>(
 
     // Then handle the different pattern styles.
     let fields = match expr.kind {
-        hir::ExprKind::PositionalPattern(ref mapping) => {
+        hir::ExprKind::PositionalPattern(mapping) => {
             map_positional_pattern(cx, mapping, 1, ty, expr.span)?
         }
-        hir::ExprKind::RepeatPattern(count, ref mapping) => {
+        hir::ExprKind::RepeatPattern(count, mapping) => {
             let const_count = cx.constant_int_value_of(count, env)?;
             let const_count = match const_count.to_usize() {
                 Some(c) => c,
@@ -82,7 +83,7 @@ pub(crate) fn map_pattern<'a>(
             };
             map_positional_pattern(cx, mapping, const_count, ty, expr.span)?
         }
-        hir::ExprKind::NamedPattern(ref mapping) => {
+        hir::ExprKind::NamedPattern(mapping) => {
             if let Some(dim) = ty.outermost_dim() {
                 map_named_array_pattern(cx, mapping, ty, dim, expr.span, env)?
             } else if let Some(strukt) = ty.get_struct() {
@@ -149,6 +150,7 @@ fn map_named_array_pattern<'a>(
     // Map things.
     let mut failed = false;
     let mut default: Option<&hir::Expr> = None;
+    let mut type_default: Option<&hir::Expr> = None;
     let mut values = HashMap::<usize, (PatternField, &hir::Expr)>::new();
 
     for &(map, to) in mapping {
@@ -161,10 +163,19 @@ fn map_named_array_pattern<'a>(
         };
         match map {
             hir::PatternMapping::Type(type_id) => {
-                cx.emit(
-                    DiagBuilder2::error("types cannot index into an array").span(cx.span(type_id)),
+                let ty = cx.packed_type_from_ast(
+                    Ref(cx.ast_for_id(type_id).as_all().get_type().unwrap()),
+                    env,
+                    None,
                 );
-                continue;
+                if ty.is_error() {
+                    failed = true;
+                    continue;
+                }
+                // Only a type key matching the element type applies.
+                if ty.resolve_full() == elem_ty.resolve_full() {
+                    type_default = Some(to);
+                }
             }
             hir::PatternMapping::Member(member_id) => {
                 // Determine the index for the mapping.
@@ -236,8 +247,8 @@ fn map_named_array_pattern<'a>(
 
     // In case the list of indices provided by the user is incomplete, use the
     // default to fill in the other elements.
-    let values: Vec<_> = if values.len() != length {
-        let default = if let Some(default) = default {
+    let mut values: Vec<_> = if values.len() != length {
+        let default = if let Some(default) = type_default.or(default) {
             default
         } else {
             cx.emit(
@@ -259,6 +270,12 @@ fn map_named_array_pattern<'a>(
         (0..length).map(|i| values[&i]).collect()
     };
 
+    // Packed bit vectors list their fields from the left bound of the range,
+    // which is the highest index for descending ranges such as `[3:0]`.
+    if ty.coalesces_to_llhd_scalar() && dim.get_range().map(|r| r.dir) == Some(ty::RangeDir::Down) {
+        values.reverse();
+    }
+
     if failed {
         Err(())
     } else {
@@ -464,20 +481,24 @@ fn map_positional_pattern<'a>(
             sbv.size,
             values
                 .into_iter()
-                .rev()
                 .map(|v| (PatternField::Bit(bit), v))
                 .collect(),
         )
     } else if let Some(dim) = ty.outermost_dim() {
         let elem_ty = ty.pop_dim(cx).unwrap();
         match dim.get_size() {
-            Some(size) => (
-                size,
-                values
+            Some(size) => {
+                // The first value maps to the left bound of the range, which
+                // is the highest index for descending ranges such as `[1:0]`.
+                let mut values: Vec<_> = values
                     .into_iter()
                     .map(|v| (PatternField::Array(elem_ty), v))
-                    .collect(),
-            ),
+                    .collect();
+                if dim.get_range().map(|r| r.dir) == Some(ty::RangeDir::Down) {
+                    values.reverse();
+                }
+                (size, values)
+            }
             None => {
                 cx.emit(
                     DiagBuilder2::error(format!(
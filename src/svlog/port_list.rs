@@ -190,6 +190,15 @@ impl<'a> PortedNode<'a> for ast::Interface<'a> {
     }
 }
 
+impl<'a> PortedNode<'a> for ast::Program<'a> {
+    fn ports(&self) -> &[ast::Port<'a>] {
+        &self.ports
+    }
+    fn items(&self) -> &[ast::Item<'a>] {
+        &self.items
+    }
+}
+
 // Compare and hash `PortedNode` by reference for use in the query system.
 impl<'a> Eq for &'a dyn PortedNode<'a> {}
 impl<'a> PartialEq for &'a dyn PortedNode<'a> {
@@ -220,6 +229,7 @@ impl<'a> AsPortedNode<'a> for ast::AllNode<'a> {
             // This should reflect the impl trait list above!
             ast::AllNode::Module(x) => Some(x),
             ast::AllNode::Interface(x) => Some(x),
+            ast::AllNode::Program(x) => Some(x),
             _ => None,
         }
     }
@@ -336,6 +346,19 @@ pub(crate) fn canonicalize_ports<'a>(
             _ => (),
         }
 
+        // Only input and output ports may have a default value.
+        if let (Some(expr), ast::PortDir::Inout) | (Some(expr), ast::PortDir::Ref) =
+            (port.default, port.dir)
+        {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "{} port `{}` cannot have a default value",
+                    port.dir, port.name
+                ))
+                .span(expr.span),
+            );
+        }
+
         // Hook things up in the hierarchy.
         cx.set_ast(port_id, AstNode::Port(port.span));
         cx.set_parent(port_id, next_rib);
@@ -460,22 +483,27 @@ fn lower_node_ports_ansi<'a>(
                 name,
                 dims: unpacked_dims,
                 expr,
+                ..
             } => {
-                // If no direction has been provided, use the one carried over
-                // from the previous port.
-                let dir = dir.unwrap_or(carry_dir);
-
-                // If no port kind has been provided, use the one carried over
-                // from the previous port.
-                let kind = kind.or(carry_kind);
-
-                // If no port type has been provided, use the one carried over
-                //  from the previous port.
-                let (ty, sign, packed_dims, ty_span) = if ty.kind.data == ast::ImplicitType
+                // If the direction, port kind, and type are all omitted, they
+                // are carried over from the previous port. Otherwise only an
+                // omitted direction is, and an omitted kind or type takes the
+                // default (IEEE 1800-2017 section 23.2.2.3).
+                let implicit_ty = ty.kind.data == ast::ImplicitType
                     && ty.sign == ast::TypeSign::None
-                    && ty.dims.is_empty()
-                {
+                    && ty.dims.is_empty();
+                let inherit = dir.is_none() && kind.is_none() && implicit_ty;
+                let dir = dir.unwrap_or(carry_dir);
+                let kind = if inherit { carry_kind } else { *kind };
+                let (ty, sign, packed_dims, ty_span) = if inherit {
                     (carry_ty, carry_sign, carry_packed_dims, None)
+                } else if implicit_ty {
+                    (
+                        Cow::Owned(ast::TypeKind::new(ty.span.begin().into(), ast::LogicType)),
+                        ast::TypeSign::None,
+                        &[][..],
+                        None,
+                    )
                 } else {
                     (
                         Cow::Borrowed(&ty.kind),
@@ -525,7 +553,9 @@ fn lower_node_ports_ansi<'a>(
             }
 
             // [direction] "." ident "(" [expr] ")"
-            ast::PortData::Explicit { dir, name, expr } => {
+            ast::PortData::Explicit {
+                dir, name, expr, ..
+            } => {
                 // If no direction has been provided, use the one carried
                 // over from the previous port.
                 let dir = dir.unwrap_or(carry_dir);
@@ -882,6 +912,7 @@ fn lower_node_ports_nonansi<'a>(
                 dir: None,
                 name,
                 ref expr,
+                ..
             } => {
                 if let Some(expr) = expr {
                     let pe = lower_port_expr(cx, expr, node.id());
@@ -913,6 +944,7 @@ fn lower_node_ports_nonansi<'a>(
                 name,
                 ref dims,
                 expr: None,
+                ..
             } if packed_dims.is_empty() => {
                 // Now we have to deal with the problem that a port like
                 // `foo[7:0]` is interpreted as a named type by the parser, but
@@ -136,21 +136,19 @@ pub(crate) fn port_mapping<'a>(
         }
     });
 
-    // Build a vector of ports.
-    let ports: Result<Vec<_>> = pos_iter
-        .chain(named_iter)
-        .filter_map(|err| match err {
-            Ok((port, Some(assign_id))) => Some(Ok((Ref(port), assign_id.env(outer_env)))),
-            Ok(_) => None,
-            Err(()) => Some(Err(())),
-        })
+    // Build a vector of ports. Explicitly unconnected ports are kept track of
+    // such that a wildcard connection does not connect them.
+    let assigned: Result<Vec<_>> = pos_iter.chain(named_iter).collect();
+    let assigned = assigned?;
+    let handled: HashSet<_> = assigned.iter().map(|&(p, _)| Ref(p)).collect();
+    let mut ports: Vec<_> = assigned
+        .into_iter()
+        .filter_map(|(port, assign_id)| Some((Ref(port), assign_id?.env(outer_env))))
         .collect();
-    let mut ports = ports?;
 
     // If there was a wildcard connection present, resolve any ports not yet
     // connected.
     if has_wildcard_port {
-        let handled: HashSet<_> = ports.iter().map(|(p, _)| *p).collect();
         let unhandled = port_list
             .ext_pos
             .iter()
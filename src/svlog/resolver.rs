@@ -450,10 +450,12 @@ impl<'a> ScopedNode<'a> for ast::Root<'a> {}
 impl<'a> ScopedNode<'a> for ast::SourceFile<'a> {}
 impl<'a> ScopedNode<'a> for ast::Module<'a> {}
 impl<'a> ScopedNode<'a> for ast::Interface<'a> {}
+impl<'a> ScopedNode<'a> for ast::Program<'a> {}
 impl<'a> ScopedNode<'a> for ast::Package<'a> {}
 impl<'a> ScopedNode<'a> for ast::Stmt<'a> {}
 impl<'a> ScopedNode<'a> for ast::Procedure<'a> {}
 impl<'a> ScopedNode<'a> for ast::ClassDecl<'a> {}
+impl<'a> ScopedNode<'a> for ast::InterfaceClassDecl<'a> {}
 impl<'a> ScopedNode<'a> for ast::SubroutineDecl<'a> {}
 impl<'a> ScopedNode<'a> for ast::GenerateFor<'a> {}
 impl<'a> ScopedNode<'a> for ast::GenerateIf<'a> {}
@@ -462,14 +464,17 @@ impl<'a> ScopedNode<'a> for ast::GenerateBlock<'a> {}
 
 // Compare and hash scoped nodes by reference for use in the query system.
 impl<'a> Eq for &'a dyn ScopedNode<'a> {}
+// The node ID is compared as well, since a node and its first field may
+// share the same address.
 impl<'a> PartialEq for &'a dyn ScopedNode<'a> {
     fn eq(&self, other: &Self) -> bool {
-        std::ptr::eq(self.as_ptr(), other.as_ptr())
+        std::ptr::eq(self.as_ptr(), other.as_ptr()) && self.id() == other.id()
     }
 }
 impl<'a> Hash for &'a dyn ScopedNode<'a> {
     fn hash<H: Hasher>(&self, h: &mut H) {
-        std::ptr::hash(self.as_ptr(), h)
+        std::ptr::hash(self.as_ptr(), h);
+        self.id().hash(h);
     }
 }
 
@@ -492,6 +497,7 @@ impl<'a> AsScopedNode<'a> for ast::AllNode<'a> {
             ast::AllNode::SourceFile(x) => Some(x),
             ast::AllNode::Module(x) => Some(x),
             ast::AllNode::Interface(x) => Some(x),
+            ast::AllNode::Program(x) => Some(x),
             ast::AllNode::Package(x) => Some(x),
             ast::AllNode::Stmt(x) => match x.kind {
                 ast::SequentialBlock(..)
@@ -508,6 +514,7 @@ impl<'a> AsScopedNode<'a> for ast::AllNode<'a> {
             },
             ast::AllNode::Procedure(x) => Some(x),
             ast::AllNode::ClassDecl(x) => Some(x),
+            ast::AllNode::InterfaceClassDecl(x) => Some(x),
             ast::AllNode::SubroutineDecl(x) => Some(x),
             ast::AllNode::GenerateFor(x) => Some(x),
             ast::AllNode::GenerateIf(x) => Some(x),
@@ -537,6 +544,7 @@ pub(crate) fn generated_scope<'a>(
             parent,
             defs: Default::default(),
             wildcard_imports: Default::default(),
+            exports: Default::default(),
             subscopes: Default::default(),
         },
     );
@@ -563,12 +571,18 @@ pub(crate) fn generated_scope<'a>(
     node.accept(&mut gen);
 
     // If this is the AST root, pull up `GLOBAL` definitions from the subscopes.
+    // Each source file is a separate compilation unit, whose other definitions
+    // remain local to the file, unless all files form a single compilation
+    // unit (IEEE 1800-2017 §3.12.1).
     if node.as_all().is_root() {
         trace!("Pulling up global defs from subscopes");
+        let single_unit = cx.sess().opts.single_unit;
         for node in gen.scope.subscopes.clone() {
             let scope = cx.generated_scope(node);
             for &def in scope.defs.values() {
-                gen.add_def(def);
+                if single_unit || def.vis.contains(DefVis::GLOBAL) {
+                    gen.add_def(def);
+                }
             }
         }
     }
@@ -589,6 +603,8 @@ pub struct Scope<'a> {
     pub defs: HashMap<Name, Def<'a>>,
     /// The wildcard imports in this scope.
     pub wildcard_imports: Vec<&'a ast::ImportItem<'a>>,
+    /// The exports in this scope.
+    pub exports: Vec<&'a ast::ExportItem<'a>>,
     /// The subscopes.
     pub subscopes: Vec<&'a dyn ScopedNode<'a>>,
 }
@@ -739,6 +755,12 @@ impl<'a, 'c, C: Context<'a>> ScopeGenerator<'a, 'c, C> {
         self.scope.wildcard_imports.push(node);
     }
 
+    /// Register an export.
+    pub fn add_export(&mut self, node: &'a ast::ExportItem<'a>) {
+        trace!(" - Adding export {:?}", node);
+        self.scope.exports.push(node);
+    }
+
     /// Register a definition.
     pub fn add_def(&mut self, mut def: Def<'a>) {
         trace!(" - Adding definition {:?}", def);
@@ -770,6 +792,16 @@ impl<'a, 'c, C: Context<'a>> ScopeGenerator<'a, 'c, C> {
                 }
             }
 
+            // Similarly, an extern module declaration never replaces the
+            // actual definition of the module.
+            if let DefNode::Ast(node) = def.node {
+                if let ast::AllNode::Module(ast) = node.as_all() {
+                    if ast.external {
+                        return;
+                    }
+                }
+            }
+
             if !def.may_override {
                 let d = DiagBuilder2::error(format!("`{}` is defined multiple times", def.name))
                     .span(def.name.span)
@@ -823,6 +855,66 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         false
     }
 
+    fn pre_visit_program(&mut self, node: &'a ast::Program<'a>) -> bool {
+        self.add_subscope(node);
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::GLOBAL,
+            may_override: true,
+            ordered: false,
+        });
+        false
+    }
+
+    fn pre_visit_bind(&mut self, _: &'a ast::Bind<'a>) -> bool {
+        // The names in a bind directive refer to the scope it binds into,
+        // not the one it appears in.
+        false
+    }
+
+    fn pre_visit_covergroup(&mut self, _: &'a ast::Covergroup<'a>) -> bool {
+        // Coverpoints, bins, and the arguments of a covergroup live in a
+        // scope of their own, which elaboration does not model yet.
+        false
+    }
+
+    fn pre_visit_let_decl(&mut self, _: &'a ast::LetDecl<'a>) -> bool {
+        // The arguments of a let declaration live in a scope of their own,
+        // which elaboration does not model yet.
+        false
+    }
+
+    fn pre_visit_checker(&mut self, node: &'a ast::Checker<'a>) -> bool {
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::GLOBAL,
+            may_override: true,
+            ordered: false,
+        });
+        // The ports and items of a checker live in a scope of their own,
+        // which elaboration does not model yet.
+        false
+    }
+
+    fn pre_visit_constraint(&mut self, _: &'a ast::Constraint<'a>) -> bool {
+        // The loop variables of `foreach` constraints would need scopes of
+        // their own, which elaboration does not model yet.
+        false
+    }
+
+    fn pre_visit_udp_decl(&mut self, node: &'a ast::UdpDecl<'a>) -> bool {
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::GLOBAL,
+            may_override: true,
+            ordered: false,
+        });
+        false
+    }
+
     fn pre_visit_modport_name(&mut self, node: &'a ast::ModportName<'a>) -> bool {
         self.add_def(Def {
             node: DefNode::Ast(node),
@@ -861,6 +953,11 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         true
     }
 
+    fn pre_visit_export_item(&mut self, node: &'a ast::ExportItem<'a>) -> bool {
+        self.add_export(node);
+        true
+    }
+
     fn pre_visit_var_decl_name(&mut self, node: &'a ast::VarDeclName<'a>) -> bool {
         // Don't register definitions for struct members.
         if node
@@ -952,6 +1049,18 @@ impl<'a, C: Context<'a>> ast::Visitor<'a> for ScopeGenerator<'a, '_, C> {
         false
     }
 
+    fn pre_visit_interface_class_decl(&mut self, node: &'a ast::InterfaceClassDecl<'a>) -> bool {
+        self.add_subscope(node);
+        self.add_def(Def {
+            node: DefNode::Ast(node),
+            name: node.name,
+            vis: DefVis::LOCAL | DefVis::NAMESPACE,
+            may_override: false,
+            ordered: true,
+        });
+        false
+    }
+
     fn pre_visit_subroutine_decl(&mut self, node: &'a ast::SubroutineDecl<'a>) -> bool {
         self.add_subscope(node);
         self.add_def(Def {
@@ -1202,16 +1311,48 @@ pub(crate) fn resolve_local_or_error<'a>(
             Ok(def)
         }
         None => {
-            cx.emit(DiagBuilder2::error(format!("`{}` not found", name.value)).span(name.span));
+            let mut d = DiagBuilder2::error(format!("`{}` not found", name.value)).span(name.span);
+            if let Some(def) = find_in_other_unit(cx, name.value, at) {
+                d = d
+                    .add_note(format!(
+                        "`{}` is declared in another compilation unit:",
+                        name.value
+                    ))
+                    .span(def.name.span)
+                    .add_note(
+                        "Move it into a package, or use `--single-unit` to compile all files \
+                         as one compilation unit",
+                    );
+            }
+            cx.emit(d);
             Err(())
         }
     }
 }
 
+/// Find a definition which is local to the compilation unit of a different
+/// source file.
+fn find_in_other_unit<'a>(
+    cx: &impl Context<'a>,
+    name: Name,
+    at: ScopeLocation<'a>,
+) -> Option<&'a Def<'a>> {
+    let mut scope = cx.generated_scope(at.scope);
+    while let Some(parent) = scope.parent {
+        scope = cx.generated_scope(parent);
+    }
+    scope
+        .subscopes
+        .iter()
+        .flat_map(|&node| cx.generated_scope(node).defs.get(&name))
+        .next()
+}
+
 /// Resolve a name in a scope as a namespace lookup.
 ///
 /// This checks if the scope contains a definition with visibility
-/// `NAMESPACE`. Returns `None` if no such name exists.
+/// `NAMESPACE`, or if the scope imports and exports the name from another
+/// package. Returns `None` if no such name exists.
 #[moore_derive::query]
 pub(crate) fn resolve_namespace<'a>(
     cx: &impl Context<'a>,
@@ -1220,13 +1361,51 @@ pub(crate) fn resolve_namespace<'a>(
 ) -> Option<&'a Def<'a>> {
     debug!("Resolving `{}` in namespace {:?}", name, inside);
     let scope = cx.generated_scope(inside);
-    match scope.defs.get(&name) {
-        Some(def) if def.vis.contains(DefVis::NAMESPACE) => {
+    let local = scope.defs.get(&name);
+    if let Some(def) = local {
+        if def.vis.contains(DefVis::NAMESPACE) {
             debug!(" - Found {:?}", def);
-            Some(def)
+            return Some(def);
         }
-        _ => None,
     }
+
+    // Check if an export makes an imported name visible. Only names imported
+    // into the scope can be exported (IEEE 1800-2017 §26.6).
+    for &export in &scope.exports {
+        if export.name.map(|n| n.value != name).unwrap_or(false) {
+            continue;
+        }
+        let exports_pkg = |pkg: Spanned<Name>| export.pkg.map_or(true, |p| p.value == pkg.value);
+
+        // Follow an explicit import of the name, like `import foo::A`.
+        let import = local.and_then(|def| match def.node {
+            DefNode::Ast(node) => node.as_all().get_import_item(),
+            _ => None,
+        });
+        if let Some(import) = import {
+            if exports_pkg(import.pkg) {
+                trace!(" - Following {:?} exported by {:?}", import, export);
+                let inside = cx.resolve_imported_scope(import).ok()?;
+                return cx.resolve_namespace(name, inside);
+            }
+        }
+
+        // Try the wildcard imports, like `import foo::*`.
+        for &import in &scope.wildcard_imports {
+            if !exports_pkg(import.pkg) {
+                continue;
+            }
+            let inside = match cx.resolve_imported_scope(import) {
+                Ok(x) => x,
+                Err(()) => continue,
+            };
+            if let Some(def) = cx.resolve_namespace(name, inside) {
+                debug!(" - Found {:?} exported by {:?}", def, export);
+                return Some(def);
+            }
+        }
+    }
+    None
 }
 
 /// Resolve a name in a scope as a namespace lookup or emit an error.
@@ -1426,6 +1605,26 @@ where
                     .is_err();
                 false
             }
+            // Names in inline constraints refer to the randomized object, as
+            // do the arguments of `obj.randomize(...)`. The arguments of
+            // `randomize(...)` and `std::randomize(...)` are local names.
+            ast::RandomizeWithExpr { ref call, .. } => {
+                let (callee, args) = match call.data {
+                    ast::CallExpr(ref callee, ref args) => (callee.as_ref(), Some(args)),
+                    _ => (call.as_ref(), None),
+                };
+                match callee.data {
+                    ast::MemberExpr { ref expr, .. } => {
+                        ast::AcceptVisitor::accept(expr.as_ref(), self)
+                    }
+                    _ => {
+                        for arg in args.into_iter().flatten() {
+                            ast::AcceptVisitor::accept(arg, self);
+                        }
+                    }
+                }
+                false
+            }
             _ => true,
         }
     }
@@ -1439,9 +1638,41 @@ where
                     .is_err();
                 false
             }
+            ast::VirtIntfType { name, .. } => {
+                self.failed |= self
+                    .cx
+                    .resolve_local_or_error(name, self.cx.scope_location(node), false)
+                    .is_err();
+                true
+            }
             _ => true,
         }
     }
+
+    fn pre_visit_bind(&mut self, _: &'a ast::Bind<'a>) -> bool {
+        // Names in a bind directive refer to the target scope.
+        false
+    }
+
+    fn pre_visit_covergroup(&mut self, _: &'a ast::Covergroup<'a>) -> bool {
+        // Covergroups are ignored during elaboration.
+        false
+    }
+
+    fn pre_visit_let_decl(&mut self, _: &'a ast::LetDecl<'a>) -> bool {
+        // Let declarations are ignored during elaboration.
+        false
+    }
+
+    fn pre_visit_checker(&mut self, _: &'a ast::Checker<'a>) -> bool {
+        // Checkers are ignored during elaboration.
+        false
+    }
+
+    fn pre_visit_constraint(&mut self, _: &'a ast::Constraint<'a>) -> bool {
+        // Constraints are ignored during elaboration.
+        false
+    }
 }
 
 /// Any AST node that can be instantiated.
@@ -1471,6 +1702,14 @@ pub(crate) fn resolve_inst_target<'a>(
 ) -> Result<InstTarget<'a>> {
     // Resolve the name of the instantiated module.
     let loc = cx.scope_location(inst);
+    if cx.resolve_local(inst.target.value, loc, false)?.is_none() {
+        cx.emit(
+            DiagBuilder2::error(format!("`{}` not found", inst.target))
+                .span(inst.target.span)
+                .add_note("Declare the module as `extern module` to instantiate it as a black box"),
+        );
+        return Err(());
+    }
     let def = cx.resolve_local_or_error(inst.target, loc, false)?;
     trace!("Resolved instance `{}` to {:?}", inst.target, def);
 
@@ -1479,6 +1718,20 @@ pub(crate) fn resolve_inst_target<'a>(
         DefNode::Ast(ast) => match ast.as_all() {
             ast::AllNode::Module(x) => Some(InstTarget::Module(x)),
             ast::AllNode::Interface(x) => Some(InstTarget::Interface(x)),
+            ast::AllNode::UdpDecl(x) => {
+                cx.emit(
+                    DiagBuilder2::error(format!("unsupported: instance of {}", x))
+                        .span(inst.target.span),
+                );
+                return Err(());
+            }
+            ast::AllNode::Checker(x) => {
+                cx.emit(
+                    DiagBuilder2::error(format!("unsupported: instance of {}", x))
+                        .span(inst.target.span),
+                );
+                return Err(());
+            }
             _ => None,
         },
         _ => None,
@@ -12,8 +12,10 @@ use moore_common::{
     util::{HasDesc, HasSpan},
 };
 use moore_derive::AnyNodeData;
+use serde::Serialize;
 use std::{
     cell::Cell,
+    collections::HashMap,
     hash::{Hash, Hasher},
 };
 
@@ -56,14 +58,17 @@ pub trait AnyNode<'a>: BasicNode<'a> + AnyNodeData + std::fmt::Display + Send +
 
 // Compare and hash nodes by reference for use in the query system.
 impl<'a> Eq for &'a dyn AnyNode<'a> {}
+// The node ID is compared as well, since a node and its first field may
+// share the same address.
 impl<'a> PartialEq for &'a dyn AnyNode<'a> {
     fn eq(&self, other: &Self) -> bool {
-        std::ptr::eq(self.as_ptr(), other.as_ptr())
+        std::ptr::eq(self.as_ptr(), other.as_ptr()) && self.id() == other.id()
     }
 }
 impl<'a> Hash for &'a dyn AnyNode<'a> {
     fn hash<H: Hasher>(&self, h: &mut H) {
-        std::ptr::hash(self.as_ptr(), h)
+        std::ptr::hash(self.as_ptr(), h);
+        self.id().hash(h);
     }
 }
 
@@ -178,6 +183,16 @@ pub trait ForEachChild<'a> {
     fn for_each_child(&'a self, each: &mut dyn FnMut(&'a dyn AnyNode<'a>));
 }
 
+/// Record the span of a node and all nodes below it, keyed by node ID.
+///
+/// Every AST node is assigned a unique ID when it is constructed. The parser
+/// constructs the nodes of a file in order on a single thread, such that the
+/// IDs are the same every time the same files are parsed in the same order.
+pub fn collect_spans<'a>(node: &'a dyn AnyNode<'a>, spans: &mut HashMap<NodeId, Span>) {
+    spans.insert(node.id(), node.span());
+    node.for_each_child(&mut |child| collect_spans(child, spans));
+}
+
 /// A node which can pass itself as `AnyNode` to a callback.
 pub trait ForEachNode<'a> {
     /// Apply a function to this node.
@@ -376,6 +391,24 @@ where
     }
 }
 
+// Serialize the node's type name, ID, span, and data. The parent and order are
+// implied by the structure of the serialized tree.
+impl<'a, T> Serialize for Node<'a, T>
+where
+    Self: BasicNode<'a>,
+    T: Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct(self.type_name(), 4)?;
+        s.serialize_field("node", self.type_name())?;
+        s.serialize_field("id", &self.id)?;
+        s.serialize_field("span", &self.span)?;
+        s.serialize_field("data", &self.data)?;
+        s.end()
+    }
+}
+
 // Compare and hash nodes by reference for use in the query system.
 impl<'a, T> Eq for Node<'a, T> {}
 impl<'a, T> PartialEq for Node<'a, T> {
@@ -416,6 +449,15 @@ where
     }
 }
 
+impl<'a, T> AcceptVisitorMut<'a> for Node<'a, T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.data.accept_mut(visitor)
+    }
+}
+
 impl<'a, T> std::ops::Deref for Node<'a, T> {
     type Target = T;
 
@@ -559,6 +601,129 @@ impl<'a> WalkVisitor<'a> for usize {
     fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {}
 }
 
+/// A node that accepts `VisitorMut`s.
+pub trait AcceptVisitorMut<'a> {
+    /// Walk a mutable visitor over the contents of `self`.
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>);
+}
+
+/// Nodes behind shared references have already been allocated and cannot be
+/// modified. They are skipped by mutable visitors.
+impl<'a, T> AcceptVisitorMut<'a> for &'_ T {
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a, T> AcceptVisitorMut<'a> for Vec<T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        for c in self {
+            c.accept_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> AcceptVisitorMut<'a> for Option<T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        if let Some(c) = self {
+            c.accept_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> AcceptVisitorMut<'a> for Spanned<T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.value.accept_mut(visitor);
+    }
+}
+
+/// A node that walks a `VisitorMut` over itself.
+pub trait WalkVisitorMut<'a> {
+    /// Walk a mutable visitor over `self`.
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>);
+}
+
+/// Nodes behind shared references have already been allocated and cannot be
+/// modified. They are skipped by mutable visitors.
+impl<'a, T> WalkVisitorMut<'a> for &'_ T {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Vec<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        for c in self {
+            c.walk_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Option<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        if let Some(c) = self {
+            c.walk_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Spanned<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.value.walk_mut(visitor);
+    }
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Box<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.as_mut().walk_mut(visitor);
+    }
+}
+
+impl<'a> WalkVisitorMut<'a> for Span {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Name {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Identifier {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Lit {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Op {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for bool {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for usize {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
 macro_rules! tuple_impls {
     ($($idx:tt => $args:ident),*) => {
         impl<'a $(, $args: AcceptVisitor<'a>)*> AcceptVisitor<'a> for ($($args),*) {
@@ -578,6 +743,18 @@ macro_rules! tuple_impls {
                 $(self.$idx.for_each_node(each);)*
             }
         }
+
+        impl<'a $(, $args: AcceptVisitorMut<'a>)*> AcceptVisitorMut<'a> for ($($args),*) {
+            fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+                $(self.$idx.accept_mut(visitor);)*
+            }
+        }
+
+        impl<'a $(, $args: WalkVisitorMut<'a>)*> WalkVisitorMut<'a> for ($($args),*) {
+            fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+                $(self.$idx.walk_mut(visitor);)*
+            }
+        }
     };
 }
 
@@ -603,7 +780,7 @@ pub type ModportDecl<'a> = Modport<'a>;
 /// All things being compiled.
 #[moore_derive::node]
 #[indefinite("root")]
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Root<'a> {
     pub files: Vec<&'a SourceFile<'a>>,
 }
@@ -611,7 +788,7 @@ pub struct Root<'a> {
 /// An entire source file.
 #[moore_derive::node]
 #[indefinite("source file")]
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SourceFile<'a> {
     pub timeunits: Timeunit,
     pub items: Vec<Item<'a>>,
@@ -628,7 +805,7 @@ pub struct SourceFile<'a> {
 /// - generates
 #[moore_derive::node]
 #[indefinite("item")]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub enum Item<'a> {
     #[indefinite("dummy item")]
     Dummy,
@@ -636,8 +813,12 @@ pub enum Item<'a> {
     InterfaceDecl(#[forward] Interface<'a>),
     PackageDecl(#[forward] Package<'a>),
     ClassDecl(#[forward] ClassDecl<'a>),
-    ProgramDecl(()),
+    InterfaceClassDecl(#[forward] InterfaceClassDecl<'a>),
+    ProgramDecl(#[forward] Program<'a>),
+    CheckerDecl(#[forward] Checker<'a>),
+    UdpDecl(#[forward] UdpDecl<'a>),
     ImportDecl(#[forward] ImportDecl<'a>),
+    ExportDecl(#[forward] ExportDecl<'a>),
     DpiDecl(#[forward] DpiDecl<'a>),
     ParamDecl(#[forward] ParamDecl<'a>),
     ModportDecl(#[forward] Modport<'a>),
@@ -645,13 +826,23 @@ pub enum Item<'a> {
     PortDecl(#[forward] PortDecl<'a>),
     Procedure(#[forward] Procedure<'a>),
     SubroutineDecl(#[forward] SubroutineDecl<'a>),
+    ExternSubroutine(#[forward] SubroutinePrototype<'a>),
     ContAssign(#[forward] ContAssign<'a>),
+    Defparam(#[forward] Defparam<'a>),
+    NetAlias(#[forward] NetAlias<'a>),
+    Bind(#[forward] Bind<'a>),
+    ClockingBlock(#[forward] ClockingBlock<'a>),
+    /// A `default clocking name;` declaration.
+    DefaultClocking(Spanned<Name>),
+    CovergroupDecl(#[forward] Covergroup<'a>),
+    LetDecl(#[forward] LetDecl<'a>),
     GenvarDecl(Vec<GenvarDecl<'a>>),
     GenerateRegion(Span, Vec<Item<'a>>),
     GenerateFor(#[forward] GenerateFor<'a>),
     GenerateIf(#[forward] GenerateIf<'a>),
     GenerateCase(#[forward] GenerateCase<'a>),
     Assertion(Assertion<'a>),
+    #[indefinite("net declaration")]
     NetDecl(NetDecl<'a>),
     VarDecl(#[forward] VarDecl<'a>),
     Inst(Inst<'a>),
@@ -661,47 +852,228 @@ pub enum Item<'a> {
 #[moore_derive::node]
 #[indefinite("module")]
 #[definite("module `{}`", name)]
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Module<'a> {
     pub lifetime: Lifetime, // default static
+    /// Whether this is an `extern module` declaration without a body.
+    pub external: bool,
+    /// Whether this declaration was derived from a VHDL entity, which provides
+    /// the actual definition.
+    pub foreign: bool,
     #[name]
     pub name: Spanned<Name>,
     pub imports: Vec<ImportDecl<'a>>,
     pub params: Vec<ParamDecl<'a>>,
+    /// Whether a parameter port list `#(...)` is present, which may be empty.
+    pub param_list: bool,
     pub ports: Vec<Port<'a>>,
     pub items: Vec<Item<'a>>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
 }
 
 /// An interface.
 #[moore_derive::node]
 #[indefinite("interface")]
 #[definite("interface `{}`", name)]
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Interface<'a> {
     pub lifetime: Lifetime, // default static
     #[name]
     pub name: Spanned<Name>,
+    pub imports: Vec<ImportDecl<'a>>,
+    pub params: Vec<ParamDecl<'a>>,
+    /// Whether a parameter port list `#(...)` is present, which may be empty.
+    pub param_list: bool,
+    pub ports: Vec<Port<'a>>,
+    pub items: Vec<Item<'a>>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
+}
+
+/// A program.
+#[moore_derive::node]
+#[indefinite("program")]
+#[definite("program `{}`", name)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Program<'a> {
+    pub lifetime: Lifetime, // default static
+    #[name]
+    pub name: Spanned<Name>,
+    pub imports: Vec<ImportDecl<'a>>,
     pub params: Vec<ParamDecl<'a>>,
+    /// Whether a parameter port list `#(...)` is present, which may be empty.
+    pub param_list: bool,
     pub ports: Vec<Port<'a>>,
     pub items: Vec<Item<'a>>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
+}
+
+/// A checker.
+///
+/// ```text
+/// "checker" ident ["(" [checker_port {"," checker_port}] ")"] ";"
+///     {item} "endchecker" [":" ident]
+/// ```
+#[moore_derive::node]
+#[indefinite("checker")]
+#[definite("checker `{}`", name)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Checker<'a> {
+    #[name]
+    pub name: Spanned<Name>,
+    pub ports: Vec<CheckerPort<'a>>,
+    pub items: Vec<Item<'a>>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
+}
+
+/// A port of a checker.
+///
+/// ```text
+/// checker_port ::= ["input" | "output"] checker_port_type ident {dimension} ["=" prop_expr]
+/// ```
+#[moore_derive::node]
+#[indefinite("checker port")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CheckerPort<'a> {
+    /// The direction of the port, or `None` if it is inherited from the
+    /// previous port.
+    pub dir: Option<PortDir>,
+    pub ty: CheckerPortType<'a>,
+    pub name: Spanned<Name>,
+    pub dims: Vec<TypeDim<'a>>,
+    /// The default actual argument, which may be a sequence or property.
+    pub default: Option<PropExpr<'a>>,
+}
+
+/// The type of a checker port.
+///
+/// ```text
+/// checker_port_type ::= "untyped" | "sequence" | "property" | data_type_or_implicit
+/// ```
+#[moore_derive::visit]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum CheckerPortType<'a> {
+    Untyped,
+    Sequence,
+    Property,
+    Data(Type<'a>),
+}
+
+/// A user-defined primitive.
+///
+/// For example `primitive mux (out, a, b, s); ... endprimitive`.
+#[moore_derive::node]
+#[indefinite("primitive")]
+#[definite("primitive `{}`", name)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UdpDecl<'a> {
+    #[name]
+    pub name: Spanned<Name>,
+    /// The port names, in the order they appear in the port list.
+    pub ports: Vec<Spanned<Name>>,
+    pub decls: Vec<UdpPortDecl<'a>>,
+    /// The optional `initial` statement of a sequential primitive.
+    pub initial: Option<(Spanned<Name>, Expr<'a>)>,
+    pub table: Vec<UdpTableEntry>,
+}
+
+/// A port declaration of a user-defined primitive.
+#[moore_derive::visit]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UdpPortDecl<'a> {
+    pub span: Span,
+    pub kind: UdpPortKind,
+    pub names: Vec<Spanned<Name>>,
+    /// The initial value of an `output reg`.
+    pub init: Option<Expr<'a>>,
+}
+
+/// The kind of a user-defined primitive port declaration.
+#[moore_derive::visit]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum UdpPortKind {
+    /// An `input`.
+    Input,
+    /// An `output`.
+    Output,
+    /// An `output reg`.
+    OutputReg,
+    /// A `reg` that turns a previously declared output into a state.
+    Reg,
+}
+
+/// A row in the table of a user-defined primitive.
+///
+/// For example the `0 (01) : ? : 1;` in a sequential primitive.
+#[moore_derive::visit]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UdpTableEntry {
+    pub span: Span,
+    pub inputs: Vec<UdpSymbol>,
+    /// The current state, for sequential primitives.
+    pub state: Option<UdpSymbol>,
+    pub output: UdpSymbol,
+}
+
+/// A symbol in the table of a user-defined primitive.
+#[moore_derive::visit]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum UdpSymbol {
+    /// A level, such as `0` or `?`.
+    Level(UdpLevel),
+    /// A transition between two levels, such as `(01)`.
+    Edge(UdpLevel, UdpLevel),
+    /// `r`, the same as `(01)`.
+    Rise,
+    /// `f`, the same as `(10)`.
+    Fall,
+    /// `p`, a potential positive edge.
+    Pos,
+    /// `n`, a potential negative edge.
+    Neg,
+    /// `*`, the same as `(??)`.
+    AnyEdge,
+    /// `-`, an output that does not change.
+    NoChange,
+}
+
+/// A level symbol in the table of a user-defined primitive.
+#[moore_derive::visit]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum UdpLevel {
+    /// `0`
+    Zero,
+    /// `1`
+    One,
+    /// `x`
+    X,
+    /// `?`, any of `0`, `1`, and `x`.
+    Any,
+    /// `b`, either `0` or `1`.
+    ZeroOrOne,
 }
 
 /// A package.
 #[moore_derive::node]
 #[indefinite("package")]
 #[definite("package `{}`", name)]
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Package<'a> {
     pub lifetime: Lifetime,
     #[name]
     pub name: Spanned<Name>,
     pub timeunits: Timeunit,
     pub items: Vec<Item<'a>>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
 }
 
 /// Lifetime specifier for variables, tasks, and functions. Defaults to static.
 #[moore_derive::visit]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub enum Lifetime {
     Static,
     Automatic,
@@ -714,7 +1086,7 @@ pub enum Lifetime {
 /// "timeprecision" time_literal ";"
 /// ```
 #[moore_derive::visit]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub struct Timeunit {
     pub unit: Option<Spanned<Lit>>,
     pub prec: Option<Spanned<Lit>>,
@@ -723,7 +1095,7 @@ pub struct Timeunit {
 /// A type.
 #[moore_derive::node]
 #[indefinite("type")]
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
 pub struct Type<'a> {
     pub kind: TypeKind<'a>,
     pub sign: TypeSign,
@@ -740,14 +1112,19 @@ impl<'a> Type<'a> {
 /// A type without sign and packed dimensions.
 #[moore_derive::node]
 #[indefinite("type")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum TypeKind<'a> {
     ImplicitType,
     VoidType,
     NamedType(Spanned<Name>),
     StringType,
     ChandleType,
-    VirtIntfType(Name),
+    /// A virtual interface, such as `virtual interface bus #(8).master`.
+    VirtIntfType {
+        name: Spanned<Name>,
+        params: Vec<ParamAssignment<'a>>,
+        modport: Option<Spanned<Name>>,
+    },
     EventType,
     MailboxType,
     ImplicitSignedType,
@@ -807,7 +1184,7 @@ impl<'a> TypeKind<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum TypeSign {
     None,
     Signed,
@@ -815,7 +1192,7 @@ pub enum TypeSign {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum TypeDim<'a> {
     Expr(Expr<'a>),
     Range(Expr<'a>, Expr<'a>),
@@ -849,7 +1226,7 @@ impl HasDesc for TypeDim<'_> {
 /// For example `enum { FOO = 42 }`.
 #[moore_derive::node]
 #[indefinite("enum definition")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Enum<'a> {
     pub base_type: Option<Box<Type<'a>>>,
     pub variants: Vec<EnumName<'a>>,
@@ -860,11 +1237,11 @@ pub struct Enum<'a> {
 /// For example the `FOO = 42` in `enum { FOO = 42 }`.
 #[moore_derive::node]
 #[indefinite("enum variant")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct EnumName<'a> {
     #[name]
     pub name: Spanned<Name>,
-    pub range: Option<Expr<'a>>,
+    pub range: Option<TypeDim<'a>>,
     pub value: Option<Expr<'a>>,
 }
 
@@ -873,7 +1250,7 @@ pub struct EnumName<'a> {
 /// For example `struct packed { byte foo; }`.
 #[moore_derive::node]
 #[indefinite("struct definition")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Struct<'a> {
     pub kind: StructKind,
     pub packed: bool,
@@ -882,7 +1259,7 @@ pub struct Struct<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize)]
 pub enum StructKind {
     /// A `struct`.
     Struct,
@@ -907,7 +1284,7 @@ impl std::fmt::Display for StructKind {
 /// For example the `byte foo;` in `struct packed { byte foo; }`.
 #[moore_derive::node]
 #[indefinite("struct member")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct StructMember<'a> {
     pub rand_qualifier: Option<RandomQualifier>,
     pub ty: Box<Type<'a>>,
@@ -917,7 +1294,7 @@ pub struct StructMember<'a> {
 /// A module or interface port as declared in the port list.
 #[moore_derive::node]
 #[indefinite("port")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum Port<'a> {
     Intf {
         modport: Option<Spanned<Name>>,
@@ -925,12 +1302,16 @@ pub enum Port<'a> {
         name: Spanned<Name>,
         dims: Vec<TypeDim<'a>>,
         expr: Option<Expr<'a>>,
+        /// The doc comment preceding the port.
+        doc: Option<Spanned<Name>>,
     },
     Explicit {
         dir: Option<PortDir>,
         #[name]
         name: Spanned<Name>,
         expr: Option<Expr<'a>>,
+        /// The doc comment preceding the port.
+        doc: Option<Spanned<Name>>,
     },
     Named {
         dir: Option<PortDir>,
@@ -940,6 +1321,8 @@ pub enum Port<'a> {
         name: Spanned<Name>,
         dims: Vec<TypeDim<'a>>,
         expr: Option<Expr<'a>>,
+        /// The doc comment preceding the port.
+        doc: Option<Spanned<Name>>,
     },
     #[indefinite("implicit port")]
     Implicit(Expr<'a>),
@@ -968,17 +1351,19 @@ impl HasDesc for Port<'_> {
 /// A port declaration in an item body.
 #[moore_derive::node]
 #[indefinite("port declaration")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PortDecl<'a> {
     pub dir: PortDir,
     pub kind: Option<VarKind>,
     pub ty: Type<'a>,
     pub names: Vec<VarDeclName<'a>>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
 }
 
 /// Whether a declaration is a variable or a net.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum VarKind {
     /// A variable declaration.
     Var,
@@ -1026,7 +1411,7 @@ impl std::fmt::Display for VarKind {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize)]
 pub enum PortDir {
     Input,
     Output,
@@ -1046,7 +1431,7 @@ impl std::fmt::Display for PortDir {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum NetType {
     Supply0,
     Supply1,
@@ -1084,14 +1469,14 @@ impl std::fmt::Display for NetType {
 /// A procedure such as `always*`, `initial`, or `final`.
 #[moore_derive::node]
 #[indefinite("procedure")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Procedure<'a> {
     pub kind: ProcedureKind,
     pub stmt: Stmt<'a>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum ProcedureKind {
     Initial,
     Always,
@@ -1104,7 +1489,7 @@ pub enum ProcedureKind {
 /// A statement.
 #[moore_derive::node]
 #[indefinite("statement")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Stmt<'a> {
     pub label: Option<Name>,
     pub kind: StmtKind<'a>,
@@ -1112,7 +1497,7 @@ pub struct Stmt<'a> {
 
 /// The different kinds of statement.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum StmtKind<'a> {
     NullStmt,
     SequentialBlock(Vec<Stmt<'a>>),
@@ -1127,12 +1512,15 @@ pub enum StmtKind<'a> {
         lhs: Expr<'a>,
         rhs: Expr<'a>,
         op: AssignOp,
+        control: Option<TimingControl<'a>>,
     },
     NonblockingAssignStmt {
         lhs: Expr<'a>,
         rhs: Expr<'a>,
         delay: Option<DelayControl<'a>>,
-        event: Option<()>,
+        event: Option<EventControl<'a>>,
+        /// The number of times `event` has to occur.
+        repeat: Option<Expr<'a>>,
     },
     TimedStmt(TimingControl<'a>, Box<Stmt<'a>>),
     CaseStmt {
@@ -1142,11 +1530,19 @@ pub enum StmtKind<'a> {
         mode: CaseMode,
         items: Vec<CaseItem<'a>>,
     },
+    /// A `randcase ... endcase` statement.
+    RandcaseStmt(Vec<RandcaseItem<'a>>),
     ForeverStmt(Box<Stmt<'a>>),
     RepeatStmt(Expr<'a>, Box<Stmt<'a>>),
     WhileStmt(Expr<'a>, Box<Stmt<'a>>),
     DoStmt(Box<Stmt<'a>>, Expr<'a>),
-    ForStmt(Box<Stmt<'a>>, Expr<'a>, Expr<'a>, Box<Stmt<'a>>),
+    /// A `for (<init>; [<cond>]; {<step>}) <body>` loop.
+    ForStmt(
+        Box<Stmt<'a>>,
+        Option<Expr<'a>>,
+        Vec<Expr<'a>>,
+        Box<Stmt<'a>>,
+    ),
     ForeachStmt(Expr<'a>, Vec<ForeachIndex<'a>>, Box<Stmt<'a>>),
     ExprStmt(Expr<'a>),
     VarDeclStmt(VarDecl<'a>),
@@ -1175,7 +1571,7 @@ impl<'a> Stmt<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum JoinKind {
     All,
     Any,
@@ -1183,7 +1579,7 @@ pub enum JoinKind {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum UniquePriority {
     Unique,
     Unique0,
@@ -1191,7 +1587,7 @@ pub enum UniquePriority {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum CaseKind {
     Normal,
     DontCareZ,
@@ -1199,7 +1595,7 @@ pub enum CaseKind {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Serialize)]
 pub enum CaseMode {
     Normal,
     Inside,
@@ -1207,47 +1603,79 @@ pub enum CaseMode {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum CaseItem<'a> {
     Default(Box<Stmt<'a>>),
-    Expr(Vec<Expr<'a>>, Box<Stmt<'a>>),
+    /// The values matched by the item, which may be ranges in a `case inside`,
+    /// and the statement to execute.
+    Expr(Vec<ValueRange<'a>>, Box<Stmt<'a>>),
 }
 
+/// A weighted item of a `randcase` statement.
+///
+/// ```text
+/// randcase_item ::= expression ":" statement_or_null
+/// ```
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RandcaseItem<'a> {
+    pub weight: Expr<'a>,
+    pub stmt: Box<Stmt<'a>>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DelayControl<'a> {
     pub span: Span,
     pub expr: Expr<'a>,
 }
 
+/// A delay with separate rise, fall, and turn-off values.
+///
+/// ```text
+/// "#" delay_value
+/// "#" "(" mintypmax_expression ["," mintypmax_expression ["," mintypmax_expression]] ")"
+/// ```
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Delay3<'a> {
+    pub span: Span,
+    pub rise: Expr<'a>,
+    pub fall: Option<Expr<'a>>,
+    pub turnoff: Option<Expr<'a>>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct EventControl<'a> {
     pub span: Span,
     pub data: EventControlData<'a>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum EventControlData<'a> {
     Implicit,
     Expr(EventExpr<'a>),
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CycleDelay {}
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum TimingControl<'a> {
     Delay(DelayControl<'a>),
     Event(EventControl<'a>),
     Cycle(CycleDelay),
+    /// An event control which has to occur a number of times, as in
+    /// `a = repeat (2) @(posedge clk) b`.
+    Repeat(Expr<'a>, EventControl<'a>),
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum AssignOp {
     Identity,
     Add,
@@ -1269,10 +1697,12 @@ pub enum AssignOp {
 /// For example `logic x, y, z`.
 #[moore_derive::node]
 #[indefinite("variable declaration")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct VarDecl<'a> {
     pub konst: bool,
     pub var: bool,
+    /// Whether this is a free `rand` variable of a checker.
+    pub rand: bool,
     pub lifetime: Option<Lifetime>,
     pub ty: Type<'a>,
     pub names: Vec<VarDeclName<'a>>,
@@ -1283,7 +1713,7 @@ pub struct VarDecl<'a> {
 /// For example the `x` in `logic x, y, z`.
 #[moore_derive::node]
 #[indefinite("variable")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct VarDeclName<'a> {
     pub name: Name,
     pub name_span: Span,
@@ -1294,7 +1724,7 @@ pub struct VarDeclName<'a> {
 /// A generate variable declaration.
 #[moore_derive::node]
 #[indefinite("genvar")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GenvarDecl<'a> {
     #[name]
     pub name: Spanned<Name>,
@@ -1304,7 +1734,7 @@ pub struct GenvarDecl<'a> {
 /// A foreach-loop index variable.
 #[moore_derive::node]
 #[indefinite("index variable")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ForeachIndex {
     /// The name of the index.
     #[name]
@@ -1316,7 +1746,7 @@ pub struct ForeachIndex {
 /// An expression.
 #[moore_derive::node]
 #[indefinite("expression")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum Expr<'a> {
     DummyExpr,
     LiteralExpr(Lit),
@@ -1325,6 +1755,7 @@ pub enum Expr<'a> {
     /// A system identifier, like `$foo`.
     SysIdentExpr(Spanned<Name>),
     ThisExpr,
+    SuperExpr,
     DollarExpr,
     NullExpr,
     ScopeExpr(Box<Expr<'a>>, Spanned<Name>),
@@ -1383,6 +1814,8 @@ pub enum Expr<'a> {
     },
     PatternExpr(Vec<PatternField<'a>>),
     InsideExpr(Box<Expr<'a>>, Vec<ValueRange<'a>>),
+    /// An expression matched against a pattern, as in `x matches 4'b1???`.
+    MatchesExpr(Box<Expr<'a>>, Box<Expr<'a>>),
     CastExpr(Type<'a>, Box<Expr<'a>>),
     CastSizeExpr(Box<Expr<'a>>, Box<Expr<'a>>),
     CastSignExpr(Spanned<TypeSign>, Box<Expr<'a>>),
@@ -1391,6 +1824,15 @@ pub enum Expr<'a> {
         name: Spanned<Name>,
         arg: TypeOrExpr<'a>,
     },
+    /// A `randomize` call with inline constraints, as in
+    /// `obj.randomize() with { x < 4; }`.
+    RandomizeWithExpr {
+        call: Box<Expr<'a>>,
+        /// The names in `with (a, b)` that restrict name lookup to the
+        /// randomized object.
+        names: Option<Vec<Spanned<Name>>>,
+        constraints: Vec<ConstraintItem<'a>>,
+    },
 }
 
 /// An ambiguous node that can either be a type or and expression.
@@ -1399,7 +1841,7 @@ pub enum Expr<'a> {
 /// resolution.
 #[moore_derive::arena]
 #[moore_derive::visit]
-#[derive(AnyNodeData, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(AnyNodeData, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[forward]
 pub enum TypeOrExpr<'a> {
     Type(&'a Type<'a>),
@@ -1490,7 +1932,7 @@ impl<'a> std::fmt::Display for TypeOrExpr<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ValueRange<'a> {
     Single(Expr<'a>),
     Range {
@@ -1501,21 +1943,21 @@ pub enum ValueRange<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum RangeMode {
     Absolute,
     RelativeDown,
     RelativeUp,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct Identifier {
     pub span: Span,
     pub name: Name,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct CallArg<'a> {
     pub span: Span,
     pub name_span: Span,
@@ -1524,21 +1966,21 @@ pub struct CallArg<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum StreamConcatSlice<'a> {
     Expr(Box<Expr<'a>>),
     Type(Type<'a>),
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct StreamExpr<'a> {
     pub expr: Box<Expr<'a>>,
     pub range: Option<Box<Expr<'a>>>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum EventExpr<'a> {
     Edge {
         span: Span,
@@ -1558,7 +2000,7 @@ pub enum EventExpr<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum EdgeIdent {
     Implicit,
     Edge,
@@ -1586,19 +2028,34 @@ impl HasDesc for EventExpr<'_> {
 #[moore_derive::node]
 #[indefinite("class declaration")]
 #[definite("class `{}`", name)]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ClassDecl<'a> {
     pub virt: bool,
     pub lifetime: Lifetime, // default static
     pub name: Spanned<Name>,
     pub params: Vec<ParamDecl<'a>>,
     pub extends: Option<(Type<'a>, Vec<CallArg<'a>>)>,
-    pub impls: Vec<Spanned<Name>>,
+    pub impls: Vec<Type<'a>>,
+    pub items: Vec<ClassItem<'a>>,
+}
+
+/// An interface class declaration.
+///
+/// Interface classes only contain pure virtual methods, type definitions, and
+/// parameters, and may extend any number of other interface classes.
+#[moore_derive::node]
+#[indefinite("interface class declaration")]
+#[definite("interface class `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InterfaceClassDecl<'a> {
+    pub name: Spanned<Name>,
+    pub params: Vec<ParamDecl<'a>>,
+    pub extends: Vec<Type<'a>>,
     pub items: Vec<ClassItem<'a>>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ClassItem<'a> {
     pub span: Span,
     pub qualifiers: Vec<(ClassItemQualifier, Span)>,
@@ -1606,7 +2063,7 @@ pub struct ClassItem<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum ClassItemQualifier {
     Static,
     Protected,
@@ -1619,21 +2076,22 @@ pub enum ClassItemQualifier {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ClassItemData<'a> {
-    Property,
+    Property(VarDecl<'a>),
     Typedef(Typedef<'a>),
     SubroutineDecl(SubroutineDecl<'a>),
     ExternSubroutine(SubroutinePrototype<'a>),
     Constraint(Constraint<'a>),
-    ClassDecl,
-    CovergroupDecl,
+    ClassDecl(ClassDecl<'a>),
+    InterfaceClassDecl(InterfaceClassDecl<'a>),
+    CovergroupDecl(Covergroup<'a>),
     ParamDecl(ParamDecl<'a>),
     Null,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum RandomQualifier {
     Rand,
     Randc,
@@ -1644,7 +2102,7 @@ pub enum RandomQualifier {
 /// For example `typedef int my_type_t`.
 #[moore_derive::node]
 #[indefinite("typedef")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Typedef<'a> {
     #[name]
     pub name: Spanned<Name>,
@@ -1653,7 +2111,7 @@ pub struct Typedef<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Constraint<'a> {
     pub span: Span,
     pub kind: ConstraintKind,
@@ -1664,7 +2122,7 @@ pub struct Constraint<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum ConstraintKind {
     Decl,
     Proto,
@@ -1673,24 +2131,286 @@ pub enum ConstraintKind {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ConstraintItem<'a> {
     pub span: Span,
     pub data: ConstraintItemData<'a>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ConstraintItemData<'a> {
-    If,
-    Foreach,
+    /// An `if (cond) ... [else ...]` constraint.
+    If(
+        Expr<'a>,
+        Box<ConstraintItem<'a>>,
+        Option<Box<ConstraintItem<'a>>>,
+    ),
+    /// A `foreach (array[i, j]) ...` constraint.
+    Foreach(Expr<'a>, Vec<ForeachIndex<'a>>, Box<ConstraintItem<'a>>),
+    /// An implication, as in `mode == 0 -> len < 4`.
+    Implication(Expr<'a>, Box<ConstraintItem<'a>>),
+    /// A set of constraints in braces.
+    Set(Vec<ConstraintItem<'a>>),
+    /// An expression, as in `[soft] x < 4`.
+    Expr { soft: bool, expr: Expr<'a> },
+    /// A distribution, as in `[soft] x dist { 0 := 1, [1:7] :/ 3 }`.
+    Dist {
+        soft: bool,
+        expr: Expr<'a>,
+        items: Vec<DistItem<'a>>,
+    },
+    /// A uniqueness constraint, as in `unique { a, b, c }`.
+    Unique(Vec<ValueRange<'a>>),
+    /// A `disable soft x` constraint.
+    DisableSoft(Expr<'a>),
+    /// A `solve a, b before c` ordering.
+    Solve(Vec<Expr<'a>>, Vec<Expr<'a>>),
+}
+
+/// An item in the list of a `dist` constraint.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DistItem<'a> {
+    pub span: Span,
+    pub range: ValueRange<'a>,
+    /// The optional weight, as in `:= 2` or `:/ 2`.
+    pub weight: Option<(DistWeight, Expr<'a>)>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DistWeight {
+    /// `:=`, which assigns the weight to each value in the range.
+    PerValue,
+    /// `:/`, which divides the weight among the values in the range.
+    PerRange,
+}
+
+/// A covergroup declaration.
+///
+/// ```text
+/// "covergroup" ident ["(" tf_port_list ")"] [coverage_event] ";"
+///     {coverage_spec_or_option}
+/// "endgroup" [":" ident]
+/// ```
+#[moore_derive::node]
+#[indefinite("covergroup declaration")]
+#[definite("covergroup `{}`", name)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Covergroup<'a> {
+    #[name]
+    pub name: Spanned<Name>,
+    pub args: Vec<SubroutinePort<'a>>,
+    /// The event that triggers sampling of the covergroup.
+    pub event: Option<CoverageEvent<'a>>,
+    pub items: Vec<CoverageItem<'a>>,
+}
+
+/// The sampling event of a covergroup.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum CoverageEvent<'a> {
+    /// A clocking event, as in `@(posedge clk)`.
+    Clocking(EventExpr<'a>),
+    /// An explicit sample method, as in `with function sample(int x)`.
+    Sample(Vec<SubroutinePort<'a>>),
+    /// The beginning or end of a subroutine, as in `@@(begin f or end g)`.
+    Block(Vec<BlockEvent<'a>>),
+}
+
+/// One of the events in a `@@(...)` covergroup sampling event.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockEvent<'a> {
+    pub span: Span,
+    /// Whether the event triggers at the `begin` rather than the `end`.
+    pub begin: bool,
+    pub target: Expr<'a>,
+}
+
+/// A coverpoint, cross, or option in a covergroup.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverageItem<'a> {
+    pub span: Span,
+    pub data: CoverageItemData<'a>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum CoverageItemData<'a> {
+    Option(CoverageOption<'a>),
+    Coverpoint(Coverpoint<'a>),
+    Cross(CoverCross<'a>),
+}
+
+/// A coverage option, as in `option.weight = 2` or `type_option.goal = 90`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverageOption<'a> {
+    pub span: Span,
+    /// Whether this is a `type_option` rather than an instance `option`.
+    pub type_option: bool,
+    pub name: Spanned<Name>,
+    pub value: Expr<'a>,
+}
+
+/// A coverpoint.
+///
+/// ```text
+/// [[data_type_or_implicit] ident ":"] "coverpoint" expr ["iff" "(" expr ")"]
+///     ("{" {bins_or_options ";"} "}" | ";")
+/// ```
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Coverpoint<'a> {
+    pub span: Span,
+    pub ty: Option<Type<'a>>,
+    pub name: Option<Spanned<Name>>,
+    pub expr: Expr<'a>,
+    pub iff: Option<Expr<'a>>,
+    pub bins: Vec<CoverageBinsItem<'a>>,
+}
+
+/// A cross coverage point.
+///
+/// ```text
+/// [ident ":"] "cross" ident "," ident {"," ident} ["iff" "(" expr ")"]
+///     ("{" {cross_body_item} "}" | ";")
+/// ```
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverCross<'a> {
+    pub span: Span,
+    pub name: Option<Spanned<Name>>,
+    pub targets: Vec<Spanned<Name>>,
+    pub iff: Option<Expr<'a>>,
+    pub bins: Vec<CoverageBinsItem<'a>>,
+}
+
+/// An item in the body of a coverpoint or cross.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum CoverageBinsItem<'a> {
+    Option(CoverageOption<'a>),
+    Bins(CoverageBins<'a>),
+    /// A function declared in the body of a cross.
+    Function(SubroutineDecl<'a>),
+}
+
+/// A `bins`, `illegal_bins`, or `ignore_bins` declaration.
+///
+/// For example `wildcard bins b[4] = {[0:15]} iff (en)`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoverageBins<'a> {
+    pub span: Span,
+    pub kind: BinsKind,
+    pub wildcard: bool,
+    pub name: Spanned<Name>,
+    /// Whether the bins form an array, as in `bins b[]` or `bins b[4]`.
+    pub array: bool,
+    /// The number of bins in the array, as in `bins b[4]`.
+    pub count: Option<Expr<'a>>,
+    pub data: CoverageBinsData<'a>,
+    /// The filter of a `with` clause.
+    pub with: Option<Expr<'a>>,
+    pub iff: Option<Expr<'a>>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BinsKind {
+    Bins,
+    IllegalBins,
+    IgnoreBins,
+}
+
+/// The values covered by a bins declaration.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum CoverageBinsData<'a> {
+    /// A list of values and ranges, as in `{1, [4:7]}`.
+    Ranges(Vec<ValueRange<'a>>),
+    /// A list of transitions, as in `(0 => 1), (1 => 0)`.
+    Transitions(Vec<TransSet<'a>>),
+    /// A set expression or coverpoint name, as in `cp with (item > 2)`.
+    Expr(Expr<'a>),
+    /// A selection of cross products, as in `binsof(a) intersect {0}`.
+    Select(SelectExpr<'a>),
+    /// The `default` bin.
+    Default,
+    /// The `default sequence` bin.
+    DefaultSequence,
+}
+
+/// A sequence of transitions, as in `(0 => 1 => 2[*3])`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransSet<'a> {
+    pub span: Span,
+    pub steps: Vec<TransRangeList<'a>>,
+}
+
+/// A step in a sequence of transitions, as in `1, [4:5]` or `3 [-> 2:4]`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransRangeList<'a> {
+    pub span: Span,
+    pub ranges: Vec<ValueRange<'a>>,
+    pub repeat: Option<TransRepeat<'a>>,
+}
+
+/// The repetition of a transition step, as in `[* 3]` or `[= 2:4]`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransRepeat<'a> {
+    pub span: Span,
+    pub kind: TransRepeatKind,
+    pub lo: Expr<'a>,
+    pub hi: Option<Expr<'a>>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum TransRepeatKind {
+    /// `[* ...]`
+    Consecutive,
+    /// `[-> ...]`
+    Goto,
+    /// `[= ...]`
+    NonConsecutive,
+}
+
+/// A selection of cross products in the bins of a cross.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SelectExpr<'a> {
+    /// A `binsof(x)` or `binsof(x) intersect {...}`.
+    Binsof {
+        span: Span,
+        target: Expr<'a>,
+        intersect: Option<Vec<ValueRange<'a>>>,
+    },
+    Not(Span, Box<SelectExpr<'a>>),
+    And(Span, Box<SelectExpr<'a>>, Box<SelectExpr<'a>>),
+    Or(Span, Box<SelectExpr<'a>>, Box<SelectExpr<'a>>),
+    /// A `with` filter, optionally followed by `matches`.
+    With {
+        span: Span,
+        expr: Box<SelectExpr<'a>>,
+        cond: Expr<'a>,
+        matches: Option<Expr<'a>>,
+    },
+    /// A cross identifier or set expression.
     Expr(Expr<'a>),
 }
 
 /// A function or task declaration.
 #[moore_derive::node]
 #[indefinite("subroutine declaration")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SubroutineDecl<'a> {
     pub prototype: SubroutinePrototype<'a>,
     pub items: Vec<SubroutineItem<'a>>,
@@ -1699,10 +2419,13 @@ pub struct SubroutineDecl<'a> {
 /// A function or task prototype.
 #[moore_derive::node]
 #[indefinite("subroutine prototype")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SubroutinePrototype<'a> {
     pub kind: SubroutineKind,
     pub lifetime: Option<Lifetime>,
+    /// The interface or class scope of a subroutine declared outside of it,
+    /// as in `function int cls::name()`.
+    pub scope: Vec<Spanned<Name>>,
     #[name]
     pub name: Spanned<Name>,
     pub args: Vec<SubroutinePort<'a>>,
@@ -1710,7 +2433,7 @@ pub struct SubroutinePrototype<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 pub enum SubroutineKind {
     Func,
     Task,
@@ -1719,7 +2442,7 @@ pub enum SubroutineKind {
 /// A function or task port.
 #[moore_derive::node]
 #[indefinite("subroutine port")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SubroutinePort<'a> {
     pub dir: Option<SubroutinePortDir>,
     pub var: bool,
@@ -1728,7 +2451,7 @@ pub struct SubroutinePort<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SubroutinePortName<'a> {
     pub name: Spanned<Name>,
     pub dims: Vec<TypeDim<'a>>,
@@ -1736,14 +2459,14 @@ pub struct SubroutinePortName<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum SubroutineItem<'a> {
     PortDecl(SubroutinePortDecl<'a>),
     Stmt(Stmt<'a>),
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SubroutinePortDecl<'a> {
     pub span: Span,
     pub dir: SubroutinePortDir,
@@ -1753,7 +2476,7 @@ pub struct SubroutinePortDecl<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum SubroutinePortDir {
     Input,
     Output,
@@ -1767,18 +2490,18 @@ pub enum SubroutinePortDir {
 /// For example `wire x, y, z`.
 #[moore_derive::node]
 #[indefinite("net declaration")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct NetDecl<'a> {
     pub net_type: NetType,
     pub strength: Option<NetStrength>,
     pub kind: NetKind,
     pub ty: Type<'a>,
-    pub delay: Option<DelayControl<'a>>,
+    pub delay: Option<Delay3<'a>>,
     pub names: Vec<VarDeclName<'a>>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum NetKind {
     Vectored,
     Scalared,
@@ -1786,14 +2509,14 @@ pub enum NetKind {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum NetStrength {
     Drive(DriveStrength, DriveStrength),
     Charge(ChargeStrength),
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum DriveStrength {
     Supply0,
     Strong0,
@@ -1808,7 +2531,7 @@ pub enum DriveStrength {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum ChargeStrength {
     Small,
     Medium,
@@ -1818,7 +2541,7 @@ pub enum ChargeStrength {
 /// A field in a `'{...}` pattern.
 #[moore_derive::node]
 #[indefinite("pattern field")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum PatternField<'a> {
     Default(Box<Expr<'a>>),
     Member(Box<Expr<'a>>, Box<Expr<'a>>),
@@ -1832,7 +2555,7 @@ pub enum PatternField<'a> {
 /// For example `import a::b, c::*`.
 #[moore_derive::node]
 #[indefinite("import declaration")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ImportDecl<'a> {
     pub items: Vec<ImportItem<'a>>,
 }
@@ -1842,14 +2565,35 @@ pub struct ImportDecl<'a> {
 /// For example the `a::b` in `import a::b, c::*`.
 #[moore_derive::node]
 #[indefinite("import")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ImportItem {
     pub pkg: Spanned<Name>,
     pub name: Option<Spanned<Name>>, // None means `import pkg::*`
 }
 
+/// An export declaration.
+///
+/// For example `export a::b, c::*` or `export *::*`.
+#[moore_derive::node]
+#[indefinite("export declaration")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportDecl<'a> {
+    pub items: Vec<ExportItem<'a>>,
+}
+
+/// A single export.
+///
+/// For example the `a::b` in `export a::b, c::*`.
+#[moore_derive::node]
+#[indefinite("export")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportItem {
+    pub pkg: Option<Spanned<Name>>,  // None means `export *::*`
+    pub name: Option<Spanned<Name>>, // None means `export pkg::*`
+}
+
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Assertion<'a> {
     pub span: Span,
     pub label: Option<(Name, Span)>,
@@ -1857,7 +2601,7 @@ pub struct Assertion<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum AssertionData<'a> {
     Immediate(BlockingAssertion<'a>),
     Deferred(AssertionDeferred, BlockingAssertion<'a>),
@@ -1865,7 +2609,7 @@ pub enum AssertionData<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum AssertionDeferred {
     /// `assert #0`
     Observed,
@@ -1874,7 +2618,7 @@ pub enum AssertionDeferred {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum BlockingAssertion<'a> {
     Assert(Expr<'a>, AssertionActionBlock<'a>),
     Assume(Expr<'a>, AssertionActionBlock<'a>),
@@ -1882,18 +2626,18 @@ pub enum BlockingAssertion<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ConcurrentAssertion<'a> {
-    AssertProperty(PropSpec, AssertionActionBlock<'a>),
-    AssumeProperty(PropSpec, AssertionActionBlock<'a>),
-    CoverProperty(PropSpec, Stmt<'a>),
+    AssertProperty(PropSpec<'a>, AssertionActionBlock<'a>),
+    AssumeProperty(PropSpec<'a>, AssertionActionBlock<'a>),
+    CoverProperty(PropSpec<'a>, Stmt<'a>),
     CoverSequence,
-    ExpectProperty(PropSpec, AssertionActionBlock<'a>),
-    RestrictProperty(PropSpec),
+    ExpectProperty(PropSpec<'a>, AssertionActionBlock<'a>),
+    RestrictProperty(PropSpec<'a>),
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum AssertionActionBlock<'a> {
     Positive(Stmt<'a>),
     Negative(Stmt<'a>),
@@ -1901,14 +2645,14 @@ pub enum AssertionActionBlock<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SeqExpr<'a> {
     pub span: Span,
     pub data: SeqExprData<'a>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum SeqExprData<'a> {
     Expr(Expr<'a>, Option<SeqRep<'a>>),
     BinOp(SeqBinOp, Box<SeqExpr<'a>>, Box<SeqExpr<'a>>),
@@ -1917,7 +2661,7 @@ pub enum SeqExprData<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum SeqRep<'a> {
     Consec(Expr<'a>),    // [* expr]
     ConsecStar,          // [*]
@@ -1927,7 +2671,7 @@ pub enum SeqRep<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum SeqBinOp {
     Or,
     And,
@@ -1935,19 +2679,37 @@ pub enum SeqBinOp {
     Within,
 }
 
+/// A property specification.
+///
+/// Only properties of the form `@(<clock>) disable iff (<disable>) <expr>` and
+/// `... <antecedent> |-> <expr>` are parsed in full. All other properties are
+/// skipped and have no `simple` form.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct PropSpec;
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PropSpec<'a> {
+    pub span: Span,
+    pub simple: Option<SimplePropSpec<'a>>,
+}
 
+/// A property specification consisting of a single boolean check.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimplePropSpec<'a> {
+    pub clock: Option<EventExpr<'a>>,
+    pub disable: Option<Expr<'a>>,
+    pub antecedent: Option<Expr<'a>>,
+    pub expr: Expr<'a>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PropExpr<'a> {
     pub span: Span,
     pub data: PropExprData<'a>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum PropExprData<'a> {
     SeqOp(PropSeqOp, SeqExpr<'a>),
     SeqBinOp(PropSeqBinOp, PropSeqOp, SeqExpr<'a>, Box<PropExpr<'a>>),
@@ -1957,7 +2719,7 @@ pub enum PropExprData<'a> {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum PropSeqOp {
     None,
     Weak,
@@ -1965,7 +2727,7 @@ pub enum PropSeqOp {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum PropSeqBinOp {
     ImplOverlap,
     ImplNonoverlap,
@@ -1974,7 +2736,7 @@ pub enum PropSeqBinOp {
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum PropBinOp {
     Or,
     And,
@@ -1996,7 +2758,7 @@ pub enum PropBinOp {
 #[moore_derive::node]
 #[indefinite("instantiation")]
 #[definite("`{}` instantiation", target)]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Inst<'a> {
     /// The name of the module to instantiate.
     #[name]
@@ -2007,12 +2769,30 @@ pub struct Inst<'a> {
     pub names: Vec<InstName<'a>>,
 }
 
+/// A `bind` directive.
+///
+/// ```text
+/// "bind" target [":" target_inst {"," target_inst}] instantiation
+/// ```
+#[moore_derive::node]
+#[indefinite("bind directive")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bind<'a> {
+    /// The module or interface, or the hierarchical instance, to bind into.
+    pub target: Expr<'a>,
+    /// The instances of the target module or interface to bind into, if
+    /// only some of them are affected.
+    pub target_insts: Vec<Expr<'a>>,
+    /// The instantiation to add to the target.
+    pub inst: Inst<'a>,
+}
+
 /// A single module instance.
 ///
 /// For example the `u0()` in `foo u0(), u1();`.
 #[moore_derive::node]
 #[indefinite("instance")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct InstName<'a> {
     /// The name of the instance.
     #[name]
@@ -2041,7 +2821,7 @@ impl<'a> InstName<'a> {
 /// For example `modport in (...), out (...);`.
 #[moore_derive::node]
 #[indefinite("modport")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Modport<'a> {
     /// The names of the modports.
     pub names: Vec<&'a ModportName<'a>>,
@@ -2052,7 +2832,7 @@ pub struct Modport<'a> {
 /// For example the `in (...)` in `modport in (...), out (...);`.
 #[moore_derive::node]
 #[indefinite("modport")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ModportName<'a> {
     /// The name of the modport.
     #[name]
@@ -2066,7 +2846,7 @@ pub struct ModportName<'a> {
 /// For example `input a, .b(expr)`, or `import ...`, or `clocking foo`.
 #[moore_derive::node]
 #[indefinite("modport port")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ModportPort<'a> {
     /// A simple port, for example `input a, .b(expr)`.
     Simple {
@@ -2080,7 +2860,7 @@ pub enum ModportPort<'a> {
 /// For example the `a` or `.b(expr)` in `input a, .b(expr)`.
 #[moore_derive::node]
 #[indefinite("simple modport port")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ModportSimplePort<'a> {
     /// The name of the port.
     #[name]
@@ -2099,14 +2879,16 @@ pub struct ModportSimplePort<'a> {
 /// ```
 #[moore_derive::node]
 #[indefinite("parameter")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ParamDecl<'a> {
     pub local: bool,
     pub kind: ParamKind<'a>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum ParamKind<'a> {
     Type(Vec<ParamTypeDecl<'a>>),
     Value(Vec<ParamValueDecl<'a>>),
@@ -2119,7 +2901,7 @@ pub enum ParamKind<'a> {
 /// ```
 #[moore_derive::node]
 #[indefinite("type parameter")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ParamTypeDecl<'a> {
     #[name]
     pub name: Spanned<Name>,
@@ -2133,7 +2915,7 @@ pub struct ParamTypeDecl<'a> {
 /// ```
 #[moore_derive::node]
 #[indefinite("value parameter")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ParamValueDecl<'a> {
     pub ty: Type<'a>,
     #[name]
@@ -2146,22 +2928,134 @@ pub struct ParamValueDecl<'a> {
 ///
 /// ```text
 /// "assign" [drive_strength] [delay3] list_of_assignments ";"
-/// "assign" [delay_control] list_of_assignments ";"
 /// ```
 #[moore_derive::node]
 #[indefinite("continuous assignment")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ContAssign<'a> {
     pub strength: Option<(DriveStrength, DriveStrength)>,
-    pub delay: Option<Expr<'a>>,
-    pub delay_control: Option<DelayControl<'a>>,
+    pub delay: Option<Delay3<'a>>,
     pub assignments: Vec<(Expr<'a>, Expr<'a>)>,
 }
 
+/// A net alias statement.
+///
+/// ```text
+/// "alias" net_lvalue "=" net_lvalue {"=" net_lvalue} ";"
+/// ```
+#[moore_derive::node]
+#[indefinite("net alias")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetAlias<'a> {
+    pub nets: Vec<Expr<'a>>,
+}
+
+/// A `defparam` statement.
+///
+/// ```text
+/// "defparam" hierarchical_parameter_identifier "=" expression {"," ...} ";"
+/// ```
+#[moore_derive::node]
+#[indefinite("defparam statement")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Defparam<'a> {
+    pub assignments: Vec<(Expr<'a>, Expr<'a>)>,
+}
+
+/// A clocking block.
+///
+/// ```text
+/// ["default" | "global"] "clocking" [ident] clocking_event ";"
+///     {clocking_item}
+/// "endclocking" [":" ident]
+/// ```
+#[moore_derive::node]
+#[indefinite("clocking block")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClockingBlock<'a> {
+    pub default: bool,
+    pub global: bool,
+    pub name: Option<Spanned<Name>>,
+    pub event: EventExpr<'a>,
+    pub items: Vec<ClockingItem<'a>>,
+}
+
+/// An item in a clocking block.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClockingItem<'a> {
+    pub span: Span,
+    pub data: ClockingItemData<'a>,
+}
+
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ClockingItemData<'a> {
+    /// The default skews, as in `default input #1step output #2`.
+    DefaultSkew {
+        input: Option<ClockingSkew<'a>>,
+        output: Option<ClockingSkew<'a>>,
+    },
+    /// A list of clocking signals, as in `input #1 a, b = top.c`.
+    Signals {
+        dir: ClockingDir<'a>,
+        signals: Vec<(Spanned<Name>, Option<Expr<'a>>)>,
+    },
+}
+
+/// The direction and skews of clocking signals.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ClockingDir<'a> {
+    Input(Option<ClockingSkew<'a>>),
+    Output(Option<ClockingSkew<'a>>),
+    InputOutput(Option<ClockingSkew<'a>>, Option<ClockingSkew<'a>>),
+    Inout,
+}
+
+/// A clocking skew, as in `#2`, `negedge`, or `posedge #1`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClockingSkew<'a> {
+    pub span: Span,
+    pub edge: EdgeIdent,
+    pub delay: Option<DelayControl<'a>>,
+}
+
+/// A let declaration.
+///
+/// ```text
+/// "let" ident ["(" [let_port {"," let_port}] ")"] "=" expr ";"
+/// ```
+#[moore_derive::node]
+#[indefinite("let declaration")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LetDecl<'a> {
+    pub name: Spanned<Name>,
+    pub ports: Vec<LetPort<'a>>,
+    pub expr: Expr<'a>,
+}
+
+/// A formal argument of a let declaration.
+///
+/// ```text
+/// let_port ::= ["untyped" | data_type] ident {dimension} ["=" expr]
+/// ```
+#[moore_derive::node]
+#[indefinite("let argument")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LetPort<'a> {
+    /// The type of the argument, or `None` if it is untyped.
+    pub ty: Option<Type<'a>>,
+    pub name: Spanned<Name>,
+    pub dims: Vec<TypeDim<'a>>,
+    pub default: Option<Expr<'a>>,
+}
+
 /// A `for` generate statement.
 #[moore_derive::node]
 #[indefinite("for-generate statement")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GenerateFor<'a> {
     pub init: Stmt<'a>,
     pub cond: Expr<'a>,
@@ -2172,7 +3066,7 @@ pub struct GenerateFor<'a> {
 /// An `if` generate statement.
 #[moore_derive::node]
 #[indefinite("if-generate statement")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GenerateIf<'a> {
     pub cond: Expr<'a>,
     pub main_block: GenerateBlock<'a>,
@@ -2182,7 +3076,7 @@ pub struct GenerateIf<'a> {
 /// A `case` generate statement.
 #[moore_derive::node]
 #[indefinite("case-generate statement")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GenerateCase {
     // TODO
 }
@@ -2192,14 +3086,14 @@ pub struct GenerateCase {
 /// May contains hierarchy items or more generate constructs.
 #[moore_derive::node]
 #[indefinite("generate block")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct GenerateBlock<'a> {
     pub label: Option<Spanned<Name>>,
     pub items: Vec<Item<'a>>,
 }
 
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ParamAssignment<'a> {
     pub span: Span,
     pub name: Option<Identifier>,
@@ -2215,12 +3109,14 @@ pub struct ParamAssignment<'a> {
 ///     .name,
 ///     .name(),
 ///     .name(expr),
+///     .name(posedge clk),
 ///     expr,
+///     posedge clk,
 /// );
 /// ```
 #[moore_derive::node]
 #[indefinite("port connection")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum PortConn<'a> {
     /// The `.*` case,
     Auto,
@@ -2228,11 +3124,13 @@ pub enum PortConn<'a> {
     Named(#[name] Spanned<Name>, PortConnMode<'a>),
     /// The `expr` case,
     Positional(Expr<'a>),
+    /// The `posedge clk` case, which only checker instances accept.
+    PositionalEvent(EventExpr<'a>),
 }
 
 /// How a named port connection is made.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum PortConnMode<'a> {
     /// The `.name` case.
     Auto,
@@ -2240,12 +3138,14 @@ pub enum PortConnMode<'a> {
     Unconnected,
     /// The `.name(expr)` case.
     Connected(Expr<'a>),
+    /// The `.name(posedge clk)` case, which only checker instances accept.
+    Event(EventExpr<'a>),
 }
 
 /// A DPI declaration such as `import "DPI-C"` or `export "DPI-C"`.
 #[moore_derive::node]
 #[indefinite("DPI declaration")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum DpiDecl<'a> {
     /// An `import`.
     Import {
@@ -2267,7 +3167,7 @@ pub enum DpiDecl<'a> {
 
 /// A DPI function/task property.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum DpiProperty {
     /// `context`
     Context,
@@ -2299,7 +3199,7 @@ pub enum DpiProperty {
 /// ```
 #[moore_derive::node]
 #[indefinite("data type")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum DataType<'a> {
     /// An integer type, like `bit`, `logic signed`, `reg signed [42:0]`, `int`,
     /// or `int unsigned`.
@@ -2346,7 +3246,7 @@ pub enum DataType<'a> {
 
 /// An integer type.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum IntType {
     /// A `bit`.
     Bit,
@@ -2370,7 +3270,7 @@ pub enum IntType {
 
 /// A real type.
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum RealType {
     /// A `shortreal`.
     ShortReal,
@@ -2389,7 +3289,7 @@ pub enum RealType {
 /// ```
 #[moore_derive::node]
 #[indefinite("implicit data type")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ImplicitDataType<'a> {
     pub signing: Option<TypeSign>,
     pub packed_dims: Vec<PackedDim<'a>>,
@@ -2404,7 +3304,7 @@ pub struct ImplicitDataType<'a> {
 ///     implicit_data_type
 /// ```
 #[moore_derive::visit]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum DataTypeOrImplicit<'a> {
     /// An explicit data type.
     Explicit(DataType<'a>),
@@ -2423,7 +3323,7 @@ pub enum DataTypeOrImplicit<'a> {
 /// ```
 #[moore_derive::node]
 #[indefinite("variable dimension")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum VarDim<'a> {
     /// An unsized dimension, like `[]`.
     Unsized,
@@ -2444,7 +3344,7 @@ pub enum VarDim<'a> {
 /// ```
 #[moore_derive::node]
 #[indefinite("packed dimension")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum PackedDim<'a> {
     /// Such as `[41:0]`.
     Range(Expr<'a>, Expr<'a>),
@@ -2461,7 +3361,7 @@ pub enum PackedDim<'a> {
 /// ```
 #[moore_derive::node]
 #[indefinite("unpacked dimension")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum UnpackedDim<'a> {
     /// Such as `[41:0]`.
     Range(Expr<'a>, Expr<'a>),
@@ -2482,7 +3382,7 @@ pub enum UnpackedDim<'a> {
 /// ```
 #[moore_derive::node]
 #[indefinite("type name segment")]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum PathSegment<'a> {
     /// A `$unit`.
     Unit,
@@ -2503,5 +3403,16 @@ moore_derive::derive_visitor!(
     /// Called for every node after visiting its children.
     fn post_visit_node(&mut self, node: &'a dyn AnyNode<'a>) {}
 );
+moore_derive::derive_visitor_mut!(
+    /// Called for every node before visiting its children.
+    ///
+    /// Return `false` from this function to not visit the node's children.
+    fn pre_visit_node(&mut self, node: &mut dyn AnyNode<'a>) -> bool {
+        true
+    }
+
+    /// Called for every node after visiting its children.
+    fn post_visit_node(&mut self, node: &mut dyn AnyNode<'a>) {}
+);
 moore_derive::derive_all_node!();
 moore_derive::derive_arena!();
@@ -0,0 +1,547 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! A concrete syntax tree for SystemVerilog source text.
+//!
+//! Unlike the AST, the concrete syntax tree retains every character of the
+//! input: the tokens seen by the parser, whitespace, comments, and the text
+//! consumed by the preprocessor, such as compiler directives and macro uses.
+//! Printing the tree reproduces the input exactly, which allows formatters and
+//! refactoring tools to operate losslessly.
+//!
+//! The tree is constructed on top of the AST. Each AST node whose span lies in
+//! the parsed file and nests properly within its parent becomes a node of the
+//! tree, which owns the tokens it covers. Nodes which do not, for example the
+//! ones produced by a macro expansion, are left out and their tokens are kept
+//! by the enclosing node. Construction never fails: regions of the input that
+//! the parser skipped during error recovery, or that do not belong to any
+//! item, are wrapped in error nodes.
+//!
+//! # Example
+//! ```
+//! use moore_svlog_syntax::cst::parse_str;
+//! let (cst, _) = parse_str("module foo; assign a = ; endmodule\n");
+//! assert_eq!(cst.to_string(), "module foo; assign a = ; endmodule\n");
+//! assert!(cst.has_errors());
+//! ```
+
+use crate::ast::{self, AnyNode};
+use crate::cat::{Cat, CatTokenKind};
+use crate::lexer::{Lexer, TokenAndSpan, TokenSource};
+use crate::parser::parse_with_recovery;
+use crate::preproc::Preprocessor;
+use crate::token::{Kw, Token};
+use moore_common::errors::*;
+use moore_common::source::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// A node in the concrete syntax tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstNode {
+    /// What kind of node this is.
+    pub kind: CstNodeKind,
+    /// The span covered by the node's tokens.
+    pub span: Span,
+    /// The nodes and tokens below this node, in source order.
+    pub children: Vec<CstElement>,
+}
+
+/// The different kinds of nodes in the concrete syntax tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CstNodeKind {
+    /// The root of the tree, covering the entire file.
+    Root,
+    /// A node of the AST, identified by its type name.
+    Node(&'static str),
+    /// A region of the input that could not be parsed.
+    Error,
+}
+
+/// An element of the concrete syntax tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CstElement {
+    Node(CstNode),
+    Token(CstToken),
+}
+
+/// A token in the concrete syntax tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CstToken {
+    /// What kind of token this is.
+    pub kind: CstTokenKind,
+    /// The span of the token's text.
+    pub span: Span,
+}
+
+/// The different kinds of tokens in the concrete syntax tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CstTokenKind {
+    /// A token seen by the parser.
+    Token(Token),
+    /// A sequence of whitespace.
+    Whitespace,
+    /// A line break.
+    Newline,
+    /// A line or block comment.
+    Comment,
+    /// Text consumed by the preprocessor, such as a compiler directive or a
+    /// macro use.
+    Preproc,
+}
+
+impl CstNode {
+    fn new(kind: CstNodeKind, span: Span, children: Vec<CstElement>) -> CstNode {
+        let span = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => Span::union(first.span(), last.span()),
+            _ => span,
+        };
+        CstNode {
+            kind,
+            span,
+            children,
+        }
+    }
+
+    /// Check whether this node or any node below it is an error node.
+    pub fn has_errors(&self) -> bool {
+        self.kind == CstNodeKind::Error
+            || self.children.iter().any(|child| match child {
+                CstElement::Node(node) => node.has_errors(),
+                CstElement::Token(_) => false,
+            })
+    }
+
+    /// Apply a function to every token below this node, in source order.
+    pub fn for_each_token(&self, each: &mut dyn FnMut(&CstToken)) {
+        for child in &self.children {
+            match child {
+                CstElement::Node(node) => node.for_each_token(each),
+                CstElement::Token(token) => each(token),
+            }
+        }
+    }
+}
+
+impl fmt::Display for CstNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let content = self.span.source.get_content();
+        let mut result = Ok(());
+        self.for_each_token(&mut |token| {
+            if result.is_ok() {
+                let text = content.extract(token.span.begin as usize, token.span.end as usize);
+                result = write!(f, "{}", text);
+            }
+        });
+        result
+    }
+}
+
+impl CstElement {
+    /// The span covered by this element.
+    pub fn span(&self) -> Span {
+        match self {
+            CstElement::Node(node) => node.span,
+            CstElement::Token(token) => token.span,
+        }
+    }
+}
+
+impl CstToken {
+    /// Check whether this token is whitespace or a comment.
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            CstTokenKind::Whitespace | CstTokenKind::Newline | CstTokenKind::Comment
+        )
+    }
+
+    /// The text of this token.
+    pub fn text(&self) -> String {
+        self.span.extract()
+    }
+}
+
+/// Parse a source file into a concrete syntax tree.
+///
+/// The file is preprocessed with the given include paths and macro
+/// definitions, as for the AST. Returns the tree together with the diagnostics
+/// of the preprocessor, lexer, and parser, instead of printing them.
+pub fn parse(
+    source: Source,
+    include_paths: &[&Path],
+    macro_defs: &[(&str, Option<&str>)],
+) -> (CstNode, Vec<DiagBuilder2>) {
+    let arena = ast::Arena::default();
+    let ((root, skipped, lexed), captured) = capture_diags(|| {
+        let pp = Preprocessor::new(source, include_paths, macro_defs);
+        let mut input = Recorder {
+            input: Lexer::new(pp),
+            tokens: vec![],
+        };
+        let (root, skipped) = parse_with_recovery(&mut input, &arena);
+        (root, skipped, input.tokens)
+    });
+
+    let tokens = collect_tokens(source, &lexed);
+    let end = source.get_content().bytes().len();
+    let mut shapes = collect_shapes(&root, source, 0, end);
+    shapes.extend(collect_timeunits(&root, &tokens));
+    shapes.sort_by_key(|s| s.begin);
+    let errors = Errors {
+        skipped: skipped
+            .into_iter()
+            .filter(|sp| sp.source == source)
+            .map(|sp| sp.begin as usize)
+            .collect(),
+        // If parsing was aborted, the rest of the file was never seen.
+        unparsed: match lexed.last() {
+            Some(&(Token::Eof, _)) => end,
+            _ => lexed
+                .iter()
+                .filter(|(_, sp)| sp.source == source)
+                .map(|(_, sp)| sp.end as usize)
+                .max()
+                .unwrap_or(0),
+        },
+    };
+    let children = build(&tokens, &errors, &mut 0, end, &shapes, true);
+    let root = CstNode {
+        kind: CstNodeKind::Root,
+        span: Span::new(source, 0, end),
+        children,
+    };
+    (root, captured.diags)
+}
+
+/// Parse SystemVerilog source text into a concrete syntax tree.
+///
+/// The text is added to the source manager as an anonymous file, against
+/// which the spans in the tree and diagnostics are resolved.
+pub fn parse_str(input: &str) -> (CstNode, Vec<DiagBuilder2>) {
+    let source = get_source_manager().add_anonymous(input);
+    parse(source, &[], &[])
+}
+
+/// A token source which records the tokens passed to the parser.
+struct Recorder<I> {
+    input: I,
+    tokens: Vec<TokenAndSpan>,
+}
+
+impl<I: TokenSource> TokenSource for Recorder<I> {
+    fn next_token(&mut self) -> DiagResult2<TokenAndSpan> {
+        let token = self.input.next_token()?;
+        self.tokens.push(token);
+        Ok(token)
+    }
+}
+
+/// Split a source file into tokens.
+///
+/// The text covered by a token the parser has seen becomes a single token of
+/// that kind. Tokens which come from another file, or which are out of order
+/// because they were produced by a macro expansion, are ignored. The remaining
+/// text becomes whitespace, comments, or preprocessor text.
+fn collect_tokens(source: Source, lexed: &[TokenAndSpan]) -> Vec<CstToken> {
+    let mut seen = vec![];
+    let mut last = 0;
+    for &(tkn, sp) in lexed {
+        if tkn != Token::Eof
+            && sp.source == source
+            && sp.begin as usize >= last
+            && sp.end as usize > sp.begin as usize
+        {
+            seen.push((tkn, sp.begin as usize, sp.end as usize));
+            last = sp.end as usize;
+        }
+    }
+    let mut seen = seen.into_iter().peekable();
+
+    let content = source.get_content();
+    let mut cat = Cat::new(content.iter()).peekable();
+    let mut tokens: Vec<CstToken> = vec![];
+    while let Some(ct) = cat.next() {
+        while seen
+            .peek()
+            .map(|&(_, begin, _)| begin < ct.1)
+            .unwrap_or(false)
+        {
+            seen.next();
+        }
+        let (kind, end) = match seen.peek() {
+            Some(&(tkn, begin, end)) if begin == ct.1 => {
+                seen.next();
+                let mut last = ct.2;
+                while let Some(ct) = cat.peek().filter(|ct| ct.1 < end) {
+                    last = ct.2;
+                    cat.next();
+                }
+                (CstTokenKind::Token(tkn), last)
+            }
+            _ => match ct.0 {
+                CatTokenKind::Whitespace => (CstTokenKind::Whitespace, ct.2),
+                CatTokenKind::Newline => (CstTokenKind::Newline, ct.2),
+                CatTokenKind::Comment => (CstTokenKind::Comment, ct.2),
+                _ => {
+                    // Merge adjacent preprocessor text into one token.
+                    if let Some(prev) = tokens.last_mut() {
+                        if prev.kind == CstTokenKind::Preproc && prev.span.end as usize == ct.1 {
+                            prev.span.end = ct.2 as u32;
+                            continue;
+                        }
+                    }
+                    (CstTokenKind::Preproc, ct.2)
+                }
+            },
+        };
+        tokens.push(CstToken {
+            kind,
+            span: Span::new(source, ct.1, end),
+        });
+    }
+    tokens
+}
+
+/// The regions of a file the parser could not make sense of.
+struct Errors {
+    /// The offsets of the tokens skipped during error recovery.
+    skipped: HashSet<usize>,
+    /// The offset after which the parser did not see the input.
+    unparsed: usize,
+}
+
+impl Errors {
+    fn contains(&self, token: &CstToken) -> bool {
+        let begin = token.span.begin as usize;
+        begin >= self.unparsed || self.skipped.contains(&begin)
+    }
+}
+
+/// The extent of a node of the tree, before tokens are assigned to it.
+struct Shape {
+    name: &'static str,
+    begin: usize,
+    end: usize,
+    children: Vec<Shape>,
+}
+
+/// Collect the children of an AST node which lie within the given range and
+/// do not overlap each other.
+fn collect_shapes<'a>(
+    node: &'a dyn AnyNode<'a>,
+    source: Source,
+    begin: usize,
+    end: usize,
+) -> Vec<Shape> {
+    let mut nodes = vec![];
+    node.for_each_child(&mut |child| {
+        let sp = child.span();
+        let (b, e) = (sp.begin as usize, sp.end as usize);
+        if sp.source == source && begin <= b && e <= end && b < e {
+            nodes.push(child);
+        }
+    });
+    nodes.sort_by_key(|n| n.span().begin);
+    let mut shapes = vec![];
+    let mut last = begin;
+    for node in nodes {
+        let sp = node.span();
+        let (b, e) = (sp.begin as usize, sp.end as usize);
+        if b < last {
+            continue;
+        }
+        last = e;
+        shapes.push(Shape {
+            name: node.type_name(),
+            begin: b,
+            end: e,
+            children: collect_shapes(node, source, b, e),
+        });
+    }
+    shapes
+}
+
+/// Collect the `timeunit` and `timeprecision` declarations of a file.
+///
+/// These are not AST nodes, but are recorded in the source file node directly.
+/// Only the spans of their literals are known, so locate the keyword before
+/// and the semicolon after the literal among the tokens.
+fn collect_timeunits(root: &ast::SourceFile, tokens: &[CstToken]) -> Vec<Shape> {
+    let lits = root.timeunits.unit.iter().chain(root.timeunits.prec.iter());
+    let mut shapes = vec![];
+    for lit in lits {
+        let index = match tokens.iter().position(|t| t.span == lit.span) {
+            Some(i) => i,
+            None => continue,
+        };
+        let kw = tokens[..index].iter().rev().find(|t| {
+            matches!(
+                t.kind,
+                CstTokenKind::Token(Token::Keyword(Kw::Timeunit))
+                    | CstTokenKind::Token(Token::Keyword(Kw::Timeprecision))
+            )
+        });
+        let semi = tokens[index..]
+            .iter()
+            .find(|t| t.kind == CstTokenKind::Token(Token::Semicolon));
+        if let (Some(kw), Some(semi)) = (kw, semi) {
+            if shapes
+                .iter()
+                .all(|s: &Shape| s.begin != kw.span.begin as usize)
+            {
+                shapes.push(Shape {
+                    name: "Timeunit",
+                    begin: kw.span.begin as usize,
+                    end: semi.span.end as usize,
+                    children: vec![],
+                });
+            }
+        }
+    }
+    shapes
+}
+
+/// Assign the tokens up to `end` to the given shapes, returning the resulting
+/// tree elements.
+fn build(
+    tokens: &[CstToken],
+    errors: &Errors,
+    index: &mut usize,
+    end: usize,
+    mut shapes: &[Shape],
+    root: bool,
+) -> Vec<CstElement> {
+    let mut elements = vec![];
+    while let Some(token) = tokens.get(*index) {
+        let begin = token.span.begin as usize;
+        if begin >= end {
+            break;
+        }
+        while shapes.first().map(|s| s.end <= begin).unwrap_or(false) {
+            shapes = &shapes[1..];
+        }
+        match shapes.first() {
+            Some(shape) if shape.begin <= begin => {
+                shapes = &shapes[1..];
+                let children = build(tokens, errors, index, shape.end, &shape.children, false);
+                elements.push(CstElement::Node(CstNode::new(
+                    CstNodeKind::Node(shape.name),
+                    token.span,
+                    children,
+                )));
+            }
+            _ => {
+                elements.push(CstElement::Token(*token));
+                *index += 1;
+            }
+        }
+    }
+    wrap_errors(elements, errors, root)
+}
+
+/// Wrap runs of erroneous tokens in error nodes.
+///
+/// A token is erroneous if the parser skipped it during error recovery or never
+/// saw it, or if it appears at the top level of the file outside of any item.
+/// Trivia between erroneous tokens becomes part of the error node.
+fn wrap_errors(elements: Vec<CstElement>, errors: &Errors, root: bool) -> Vec<CstElement> {
+    let mut result = vec![];
+    let mut error = vec![];
+    let mut trivia = vec![];
+    for element in elements {
+        let token = match element {
+            CstElement::Token(token) => token,
+            node => {
+                flush_error(&mut result, &mut error);
+                result.append(&mut trivia);
+                result.push(node);
+                continue;
+            }
+        };
+        let preproc = token.kind == CstTokenKind::Preproc;
+        if !token.is_trivia() && (errors.contains(&token) || (root && !preproc)) {
+            if error.is_empty() {
+                result.append(&mut trivia);
+            } else {
+                error.append(&mut trivia);
+            }
+            error.push(element);
+        } else if token.is_trivia() || (root && preproc) {
+            trivia.push(element);
+        } else {
+            flush_error(&mut result, &mut error);
+            result.append(&mut trivia);
+            result.push(element);
+        }
+    }
+    flush_error(&mut result, &mut error);
+    result.append(&mut trivia);
+    result
+}
+
+fn flush_error(result: &mut Vec<CstElement>, error: &mut Vec<CstElement>) {
+    if !error.is_empty() {
+        let span = error[0].span();
+        let children = std::mem::take(error);
+        result.push(CstElement::Node(CstNode::new(
+            CstNodeKind::Error,
+            span,
+            children,
+        )));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_lossless(input: &str) -> CstNode {
+        let (cst, _) = parse_str(input);
+        assert_eq!(cst.to_string(), input);
+        cst
+    }
+
+    fn find(node: &CstNode, kind: CstNodeKind) -> Option<&CstNode> {
+        if node.kind == kind {
+            return Some(node);
+        }
+        node.children.iter().find_map(|child| match child {
+            CstElement::Node(node) => find(node, kind),
+            CstElement::Token(_) => None,
+        })
+    }
+
+    #[test]
+    fn valid() {
+        let cst = check_lossless(
+            "// header\n`define WIDTH 8\nmodule foo (input [`WIDTH-1:0] a);\n  /* body */\n  assign b = a; // tail\nendmodule\n",
+        );
+        assert!(!cst.has_errors());
+        let module = find(&cst, CstNodeKind::Node("Module")).unwrap();
+        assert!(module.to_string().starts_with("module foo"));
+        let assign = find(&cst, CstNodeKind::Node("ContAssign")).unwrap();
+        assert_eq!(assign.to_string(), "assign b = a;");
+    }
+
+    #[test]
+    fn timeunits() {
+        let cst = check_lossless("timeunit 1ns;\ntimeprecision 1ps;\nmodule foo; endmodule\n");
+        assert!(!cst.has_errors());
+        assert!(find(&cst, CstNodeKind::Node("Timeunit")).is_some());
+    }
+
+    #[test]
+    fn broken() {
+        let cst = check_lossless("module foo;\n  always begin a = ; end\nendmodule\n");
+        assert!(cst.has_errors());
+        let cst = check_lossless("module foo; endmodule\n42 garbage;\nmodule bar; endmodule\n");
+        let error = find(&cst, CstNodeKind::Error).unwrap();
+        assert_eq!(error.to_string(), "42 garbage;");
+        let cst = check_lossless("module foo; endmodule\n) garbage\nmodule bar; endmodule\n");
+        let error = find(&cst, CstNodeKind::Error).unwrap();
+        assert_eq!(error.to_string(), ") garbage\nmodule bar; endmodule");
+        check_lossless("module foo; \"unterminated\n");
+        check_lossless("");
+    }
+}
@@ -0,0 +1,1061 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! A formatter for SystemVerilog source text.
+//!
+//! Re-prints source text with consistent indentation, spacing, and line
+//! breaks. The formatter operates on the tokens of the categorizing lexer,
+//! which retains comments, and does not preprocess its input, such that
+//! comments, macro uses, and compiler directives are kept as written. Only
+//! whitespace is changed; the tokens of the input are emitted in order.
+//!
+//! Line breaks in the input are kept, and additional breaks are inserted after
+//! statements, around `begin`/`end` and similar keywords, and where a line
+//! exceeds the maximum width. Lines are indented by the blocks, brackets, and
+//! statements they are nested in.
+//!
+//! # Example
+//! ```
+//! use moore_svlog_syntax::format::{format, FormatOptions};
+//! let output = format("module foo;assign a=b;endmodule\n", &FormatOptions::default());
+//! assert_eq!(output, "module foo;\n    assign a = b;\nendmodule\n");
+//! ```
+
+use crate::cat::{Cat, CatToken, CatTokenKind};
+use crate::token::find_keyword;
+
+/// Options that control how source text is formatted.
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// The number of columns by which each level is indented.
+    pub indent_width: usize,
+    /// Indent with tabs rather than spaces.
+    pub use_tabs: bool,
+    /// The width beyond which lines are broken, or 0 to never break lines.
+    pub max_width: usize,
+    /// The maximum number of consecutive blank lines to keep.
+    pub max_blank_lines: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent_width: 4,
+            use_tabs: false,
+            max_width: 100,
+            max_blank_lines: 1,
+        }
+    }
+}
+
+/// Format SystemVerilog source text.
+pub fn format(input: &str, opts: &FormatOptions) -> String {
+    let words = scan(input);
+    let mut layout = Layout::default();
+    for (i, &word) in words.iter().enumerate() {
+        let next = words[i + 1..]
+            .iter()
+            .find(|w| w.kind != Kind::Newline && !w.is_comment());
+        layout.push(word, next);
+    }
+    layout.finish_line();
+    render(&layout.lines, opts)
+}
+
+/// The compiler directives which occupy the remainder of their line.
+static DIRECTIVES: &[&str] = &[
+    "begin_keywords",
+    "celldefine",
+    "default_nettype",
+    "define",
+    "else",
+    "elsif",
+    "end_keywords",
+    "endcelldefine",
+    "endif",
+    "ifdef",
+    "ifndef",
+    "include",
+    "line",
+    "nounconnected_drive",
+    "pragma",
+    "resetall",
+    "timescale",
+    "unconnected_drive",
+    "undef",
+    "undefineall",
+];
+
+/// The operators made up of multiple symbols, longest first.
+static OPERATORS: &[&str] = &[
+    "<<<=", ">>>=", "===", "!==", "==?", "!=?", "<<<", ">>>", "<<=", ">>=", "|->", "|=>", "<->",
+    "->>", "&&&", "#-#", "#=#", "==", "!=", "<=", ">=", "&&", "||", "**", "<<", ">>", "->", "::",
+    "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "++", "--", "~&", "~|", "~^", "^~", "+:", "-:",
+    ".*", "##", ":=", ":/", "@@",
+];
+
+/// The operators which may be unary.
+static UNARY_OPERATORS: &[&str] = &[
+    "+", "-", "!", "~", "&", "|", "^", "~&", "~|", "~^", "^~", "++", "--", "*",
+];
+
+/// The keywords which are followed by a space before an opening parenthesis.
+static CONTROL_KEYWORDS: &[&str] = &[
+    "assert", "assume", "case", "casex", "casez", "cover", "expect", "for", "foreach", "if", "iff",
+    "randcase", "repeat", "restrict", "return", "wait", "while", "with",
+];
+
+/// The keywords which open a block up to the next semicolon.
+static IMMEDIATE_OPENERS: &[&str] = &[
+    "begin", "case", "casex", "casez", "fork", "generate", "randcase", "specify", "table",
+];
+
+/// The keywords which open a block once their header ends with a semicolon.
+static DEFERRED_OPENERS: &[&str] = &[
+    "checker",
+    "class",
+    "clocking",
+    "config",
+    "covergroup",
+    "function",
+    "interface",
+    "macromodule",
+    "module",
+    "package",
+    "primitive",
+    "program",
+    "property",
+    "sequence",
+    "task",
+];
+
+/// The keywords which close a block.
+static CLOSERS: &[&str] = &[
+    "end",
+    "endcase",
+    "endchecker",
+    "endclass",
+    "endclocking",
+    "endconfig",
+    "endfunction",
+    "endgenerate",
+    "endgroup",
+    "endinterface",
+    "endmodule",
+    "endpackage",
+    "endprimitive",
+    "endprogram",
+    "endproperty",
+    "endsequence",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "join",
+    "join_any",
+    "join_none",
+];
+
+/// The keywords which start a statement whose body follows on the next line.
+static HEADERS: &[&str] = &[
+    "always",
+    "always_comb",
+    "always_ff",
+    "always_latch",
+    "do",
+    "else",
+    "final",
+    "for",
+    "foreach",
+    "forever",
+    "if",
+    "initial",
+    "repeat",
+    "while",
+];
+
+/// The kinds of words the formatter operates on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Ident,
+    EscapedIdent,
+    Keyword,
+    Str,
+    Symbol,
+    LineComment,
+    BlockComment,
+    Attribute,
+    Directive,
+    Newline,
+}
+
+/// A word of the input, such as an identifier, operator, or comment.
+#[derive(Debug, Clone, Copy)]
+struct Word<'a> {
+    kind: Kind,
+    text: &'a str,
+    /// Whether the word is preceded by whitespace in the input.
+    space: bool,
+}
+
+impl<'a> Word<'a> {
+    fn is_comment(&self) -> bool {
+        self.kind == Kind::LineComment || self.kind == Kind::BlockComment
+    }
+
+    fn is(&self, text: &str) -> bool {
+        self.kind != Kind::Str && !self.is_comment() && self.text == text
+    }
+
+    fn is_any(&self, texts: &[&str]) -> bool {
+        self.kind == Kind::Keyword && texts.contains(&self.text)
+    }
+
+    fn is_open(&self) -> bool {
+        self.kind == Kind::Symbol && (self.text == "(" || self.text == "[" || self.text == "{")
+    }
+
+    fn is_close(&self) -> bool {
+        self.kind == Kind::Symbol && (self.text == ")" || self.text == "]" || self.text == "}")
+    }
+}
+
+/// Divide the input into words.
+fn scan<'a>(input: &'a str) -> Vec<Word<'a>> {
+    use CatTokenKind::*;
+    let tokens: Vec<CatToken> = Cat::new(Box::new(input.char_indices())).collect();
+    let kind_at = |i: usize| tokens.get(i).map(|t| t.0).unwrap_or(Eof);
+    let based = |i: usize| {
+        if kind_at(i) != Symbol('\'') || kind_at(i + 1) != Text {
+            return None;
+        }
+        if !is_base(token_text(input, &tokens[i + 1])) {
+            return None;
+        }
+        let mut j = i + 2;
+        while let Text | Digits | Symbol('?') = kind_at(j) {
+            j += 1;
+        }
+        Some(j)
+    };
+    let mut words = vec![];
+    let mut space = false;
+    let mut line_start = true;
+    let mut i = 0;
+    while i < tokens.len() {
+        let CatToken(kind, begin, _) = tokens[i];
+        let (kind, end) = match kind {
+            Newline => {
+                words.push(Word {
+                    kind: Kind::Newline,
+                    text: "\n",
+                    space: false,
+                });
+                space = false;
+                line_start = true;
+                i += 1;
+                continue;
+            }
+            Whitespace | Eof => {
+                space = true;
+                i += 1;
+                continue;
+            }
+            Comment if input[begin..].starts_with("//") => (Kind::LineComment, i + 1),
+            Comment => (Kind::BlockComment, i + 1),
+
+            // Directives extend to the end of the line, and for macro
+            // definitions across escaped line breaks.
+            Symbol('`')
+                if line_start
+                    && kind_at(i + 1) == Text
+                    && DIRECTIVES.contains(&token_text(input, &tokens[i + 1])) =>
+            {
+                let define = token_text(input, &tokens[i + 1]) == "define";
+                let mut j = i + 2;
+                loop {
+                    while j < tokens.len() && tokens[j].0 != Newline {
+                        j += 1;
+                    }
+                    if define
+                        && j < tokens.len()
+                        && input[begin..tokens[j].1].trim_end().ends_with('\\')
+                    {
+                        j += 1;
+                        continue;
+                    }
+                    break;
+                }
+                (Kind::Directive, j)
+            }
+
+            // Macro uses, string literals, and escaped identifiers.
+            Symbol('`') => {
+                let mut j = i + 1;
+                while let Text | Digits | Symbol('$') = kind_at(j) {
+                    j += 1;
+                }
+                (Kind::Ident, j)
+            }
+            Symbol('"') => {
+                let mut j = i + 1;
+                loop {
+                    match kind_at(j) {
+                        Eof | Newline => break,
+                        Symbol('\\') => j += 2,
+                        Symbol('"') => {
+                            j += 1;
+                            break;
+                        }
+                        _ => j += 1,
+                    }
+                }
+                (Kind::Str, j.min(tokens.len()))
+            }
+            Symbol('\\') => {
+                let mut j = i + 1;
+                while !matches!(kind_at(j), Eof | Newline | Whitespace) {
+                    j += 1;
+                }
+                (Kind::EscapedIdent, j)
+            }
+
+            // Attributes are kept as written.
+            Symbol('(') if kind_at(i + 1) == Symbol('*') && kind_at(i + 2) != Symbol(')') => {
+                let mut j = i + 2;
+                while j < tokens.len()
+                    && !(kind_at(j) == Symbol('*') && kind_at(j + 1) == Symbol(')'))
+                {
+                    j += 1;
+                }
+                (Kind::Attribute, (j + 2).min(tokens.len()))
+            }
+
+            // Identifiers, keywords, and numbers, including real numbers with
+            // a fraction and exponent.
+            Text | Digits | Symbol('$') => {
+                let mut j = i + 1;
+                loop {
+                    match (kind_at(j), kind_at(j + 1)) {
+                        (Text, _) | (Digits, _) | (Symbol('$'), _) => j += 1,
+                        (Symbol('\''), Text) if is_number(&input[begin..tokens[j].1]) => {
+                            match based(j) {
+                                Some(end) => j = end,
+                                None => break,
+                            }
+                        }
+                        (Symbol('.'), Digits) if is_number(&input[begin..tokens[j].1]) => j += 2,
+                        (Symbol('+'), Digits) | (Symbol('-'), Digits)
+                            if is_number(&input[begin..tokens[j].1])
+                                && input[begin..tokens[j].1].ends_with(&['e', 'E'][..]) =>
+                        {
+                            j += 2
+                        }
+                        _ => break,
+                    }
+                }
+                let text = &input[begin..tokens[j - 1].2];
+                if find_keyword(text).is_some() {
+                    (Kind::Keyword, j)
+                } else {
+                    (Kind::Ident, j)
+                }
+            }
+
+            // Unsized based numbers, which may contain `?` digits.
+            Symbol('\'') if based(i).is_some() => (Kind::Ident, based(i).unwrap()),
+
+            // Operators.
+            Symbol(_) => {
+                let mut j = i + 1;
+                while j < tokens.len() && j < i + 4 && matches!(tokens[j].0, Symbol(_)) {
+                    j += 1;
+                }
+                let end = OPERATORS
+                    .iter()
+                    .filter_map(|op| {
+                        let n = op.len();
+                        if j - i >= n && &input[begin..tokens[i + n - 1].2] == *op {
+                            Some(i + n)
+                        } else {
+                            None
+                        }
+                    })
+                    .next()
+                    .unwrap_or(i + 1);
+                (Kind::Symbol, end)
+            }
+        };
+        let mut text = &input[begin..tokens[end - 1].2];
+        if kind == Kind::Directive {
+            text = text.trim_end();
+        }
+        words.push(Word { kind, text, space });
+        space = false;
+        line_start = false;
+        i = end;
+    }
+    words
+}
+
+fn token_text<'a>(input: &'a str, token: &CatToken) -> &'a str {
+    &input[token.1..token.2]
+}
+
+/// Check whether a word is a number, possibly with a fraction and exponent.
+fn is_number(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_digit())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '_' || c == 'e' || c == 'E')
+}
+
+/// Check whether a word is the base of a based number, e.g. `sb0101` in
+/// `4'sb0101`.
+fn is_base(text: &str) -> bool {
+    let mut chars = text.chars().peekable();
+    if let Some('s') | Some('S') = chars.peek() {
+        chars.next();
+    }
+    match chars.next() {
+        Some(c) if "bodhBODH".contains(c) => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_hexdigit() || "xXzZ_".contains(c))
+}
+
+/// A word placed on a line.
+#[derive(Debug, Clone, Copy)]
+struct Placed<'a> {
+    word: Word<'a>,
+    /// Whether the word is preceded by a space.
+    space: bool,
+    /// Whether the word is a unary or prefix operator.
+    unary: bool,
+    /// The indentation of the line if it were broken before the word.
+    wrap_indent: usize,
+}
+
+/// A line of output.
+#[derive(Debug)]
+struct Line<'a> {
+    indent: usize,
+    words: Vec<Placed<'a>>,
+    /// The number of blank lines before this line in the input.
+    blank_before: usize,
+}
+
+/// Which words may follow on the same line after a forced line break.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Break {
+    None,
+    /// After a statement; only comments may follow.
+    Stmt,
+    /// After `begin` and similar; a label may follow.
+    Begin,
+    BeginLabel,
+    /// After `end` and similar; a label or `else` may follow.
+    End,
+    EndLabel,
+}
+
+/// Assigns words to lines and determines their indentation and spacing.
+#[derive(Debug)]
+struct Layout<'a> {
+    lines: Vec<Line<'a>>,
+    current: Vec<Placed<'a>>,
+    indent: usize,
+    /// The number of line breaks since the last line.
+    newlines: usize,
+    /// The indentation of the lines which open the enclosing blocks.
+    blocks: Vec<usize>,
+    /// The indentation of the lines with block keywords awaiting a semicolon.
+    pending: Vec<usize>,
+    /// The open brackets.
+    brackets: Vec<&'a str>,
+    /// The number of `?` awaiting a `:`.
+    ternary: usize,
+    /// The words of the current statement.
+    stmt: Vec<Word<'a>>,
+    /// The indentation of the line the current statement starts on.
+    stmt_indent: usize,
+    /// The indentation of a statement which follows a header on a new line.
+    header_indent: Option<usize>,
+    /// The indentation and block depth of the `if` statements awaiting an
+    /// `else`.
+    ifs: Vec<(usize, usize)>,
+    brk: Break,
+    /// Whether a block has been opened or closed on the current line.
+    block_changed: bool,
+}
+
+impl<'a> Default for Layout<'a> {
+    fn default() -> Self {
+        Layout {
+            lines: vec![],
+            current: vec![],
+            indent: 0,
+            newlines: 0,
+            blocks: vec![],
+            pending: vec![],
+            brackets: vec![],
+            ternary: 0,
+            stmt: vec![],
+            stmt_indent: 0,
+            header_indent: None,
+            ifs: vec![],
+            brk: Break::None,
+            block_changed: false,
+        }
+    }
+}
+
+impl<'a> Layout<'a> {
+    /// The indentation of the statements in the innermost block.
+    fn base(&self) -> usize {
+        self.blocks.last().map(|&x| x + 1).unwrap_or(0)
+    }
+
+    fn push(&mut self, word: Word<'a>, next: Option<&Word<'a>>) {
+        match word.kind {
+            Kind::Newline => {
+                self.finish_line();
+                self.newlines += 1;
+                return;
+            }
+            Kind::Directive => {
+                self.finish_line();
+                self.indent = self.base() + self.brackets.len();
+                self.current.push(Placed {
+                    word,
+                    space: false,
+                    unary: false,
+                    wrap_indent: self.indent,
+                });
+                self.finish_line();
+                return;
+            }
+            _ => (),
+        }
+
+        // Break the line before closing keywords, and after statements and
+        // block keywords.
+        if !self.current.is_empty() {
+            let keep = match self.brk {
+                _ if word.is_any(CLOSERS) => false,
+                Break::None => true,
+                Break::Stmt => word.kind == Kind::LineComment,
+                Break::Begin => word.kind == Kind::LineComment || word.is(":"),
+                Break::BeginLabel | Break::EndLabel => word.kind == Kind::Ident,
+                Break::End => {
+                    word.kind == Kind::LineComment
+                        || word.is_any(&["else", "while"])
+                        || [":", ";", ",", ")"].iter().any(|&x| word.is(x))
+                }
+            };
+            if !keep {
+                self.finish_line();
+            }
+        }
+        self.brk = match self.brk {
+            Break::Begin if word.is(":") => Break::BeginLabel,
+            Break::End if word.is(":") => Break::EndLabel,
+            Break::BeginLabel | Break::EndLabel => Break::Stmt,
+            _ => Break::None,
+        };
+
+        // Close blocks and brackets before the indentation of the line is
+        // determined.
+        let closed_block = if word.is_any(CLOSERS) {
+            self.close_block()
+        } else {
+            None
+        };
+        if word.is_close() {
+            self.brackets.pop();
+        }
+
+        // Determine the indentation of a new line.
+        let line_start = self.current.is_empty();
+        let stmt_start = self.stmt.is_empty() && !word.is_comment();
+        if line_start {
+            self.indent = if let Some(indent) = closed_block {
+                indent
+            } else if !self.brackets.is_empty() || word.is_close() {
+                self.stmt_indent + self.brackets.len()
+            } else if word.is_any(&["else"]) {
+                let depth = self.blocks.len();
+                match self.ifs.iter().rposition(|&(_, d)| d == depth) {
+                    Some(index) => self.ifs[index].0,
+                    None => self.base(),
+                }
+            } else if !self.stmt.is_empty() {
+                self.stmt_indent + 1
+            } else {
+                self.header_indent.unwrap_or_else(|| self.base())
+            };
+        }
+        if stmt_start && !word.is_any(CLOSERS) {
+            self.stmt_indent = if line_start {
+                self.indent
+            } else {
+                self.current_stmt_indent()
+            };
+            self.header_indent = None;
+        }
+
+        // Place the word.
+        let (space, unary) = match self.current.last() {
+            Some(prev) => (self.space_between(prev, &word), self.is_unary(prev, &word)),
+            None => (false, self.is_unary_at_start(&word)),
+        };
+        let wrap_indent = self.stmt_indent + self.brackets.len().max(1);
+        self.current.push(Placed {
+            word,
+            space,
+            unary,
+            wrap_indent,
+        });
+        if word.is_comment() {
+            if word.kind == Kind::LineComment {
+                self.brk = Break::Stmt;
+            }
+            return;
+        }
+
+        // Track the statement, blocks, and brackets.
+        let depth = self.brackets.len();
+        if word.is_open() {
+            self.brackets.push(word.text);
+        }
+        if word.is("?") {
+            self.ternary += 1;
+        }
+        if closed_block.is_some() {
+            self.end_stmt();
+            self.brk = Break::End;
+            return;
+        }
+        let prev_stmt = self.stmt.clone();
+        let prev_kw = prev_stmt.last().filter(|w| w.kind == Kind::Keyword);
+        let prev_is = |text: &str| prev_kw.map(|w| w.text == text).unwrap_or(false);
+        let in_stmt = |text: &str| prev_stmt.iter().any(|w| w.is(text));
+        self.stmt.push(word);
+        if word.is_any(&["if"]) {
+            let indent = self.stmt_indent;
+            self.ifs.push((indent, self.blocks.len()));
+        }
+        if word.is_any(&["else"]) {
+            let depth = self.blocks.len();
+            if let Some(index) = self.ifs.iter().rposition(|&(_, d)| d == depth) {
+                self.ifs.remove(index);
+            }
+        }
+
+        if depth == 0 && word.is(";") {
+            self.ternary = 0;
+            self.end_stmt();
+            for indent in std::mem::take(&mut self.pending) {
+                self.blocks.push(indent);
+                self.block_changed = true;
+            }
+            self.brk = Break::Stmt;
+        } else if depth == 0 && word.is(":") {
+            if self.ternary > 0 {
+                self.ternary -= 1;
+            } else if self.brk == Break::None {
+                // A label, e.g. of a case item, after which a statement
+                // follows.
+                self.header_indent = Some(self.stmt_indent + 1);
+                self.stmt.clear();
+            }
+        } else if word.is(":") && self.ternary > 0 {
+            self.ternary -= 1;
+        } else if word.is_any(IMMEDIATE_OPENERS)
+            && !(word.is_any(&["fork"]) && (prev_is("wait") || prev_is("disable")))
+        {
+            let indent = self.current_stmt_indent();
+            self.blocks.push(indent);
+            self.block_changed = true;
+            if !word.is_any(&["case", "casex", "casez", "randcase"]) {
+                self.end_stmt();
+                self.brk = Break::Begin;
+            }
+        } else if depth == 0 && word.is_any(DEFERRED_OPENERS) {
+            let is_decl = match word.text {
+                "interface" => {
+                    !prev_is("virtual") && !next.map(|w| w.is_any(&["class"])).unwrap_or(false)
+                }
+                "class" => !in_stmt("typedef"),
+                "property" | "sequence" => prev_stmt.is_empty(),
+                "clocking" => !next.map(|w| w.is(";")).unwrap_or(false),
+                _ => true,
+            } && !["extern", "pure", "import", "export", "typedef"]
+                .iter()
+                .any(|&x| in_stmt(x));
+            if is_decl {
+                self.pending.push(self.stmt_indent);
+            }
+        }
+    }
+
+    /// The indentation of the current statement, or of the current line if
+    /// no statement is in progress.
+    fn current_stmt_indent(&self) -> usize {
+        if self.stmt.len() > 1 {
+            self.stmt_indent
+        } else {
+            self.indent
+        }
+    }
+
+    fn close_block(&mut self) -> Option<usize> {
+        let indent = self.blocks.pop();
+        let depth = self.blocks.len();
+        self.ifs.retain(|&(_, d)| d <= depth);
+        self.block_changed = true;
+        Some(indent.unwrap_or(0))
+    }
+
+    fn end_stmt(&mut self) {
+        self.stmt.clear();
+        self.header_indent = None;
+    }
+
+    /// Finish the current line, if any.
+    fn finish_line(&mut self) {
+        if self.current.is_empty() {
+            return;
+        }
+        let words = std::mem::take(&mut self.current);
+        let last = words
+            .iter()
+            .rev()
+            .find(|p| !p.word.is_comment())
+            .map(|p| p.word);
+        self.lines.push(Line {
+            indent: self.indent,
+            words,
+            blank_before: self.newlines.saturating_sub(1),
+        });
+        self.newlines = 0;
+        self.brk = Break::None;
+        let block_changed = std::mem::replace(&mut self.block_changed, false);
+        let last = match last {
+            Some(last) => last,
+            None => return,
+        };
+
+        // Statements which end at a closing brace, or which consist of a
+        // macro use, end with the line.
+        if !self.brackets.is_empty() || self.stmt.is_empty() {
+            return;
+        }
+        if last.is("}") || (self.stmt[0].text.starts_with('`') && !block_changed) {
+            self.end_stmt();
+            return;
+        }
+
+        // The body of a statement like `if` or `always` follows on the next
+        // line.
+        if self.stmt[0].is_any(HEADERS) && !last.is(";") {
+            self.header_indent = Some(self.stmt_indent + 1);
+            self.stmt.clear();
+        }
+    }
+
+    /// Determine whether a space separates two words.
+    fn space_between(&self, prev: &Placed<'a>, next: &Word<'a>) -> bool {
+        let p = prev.word;
+        if p.kind == Kind::EscapedIdent || p.is_comment() || next.is_comment() {
+            return true;
+        }
+        if p.kind == Kind::Attribute || next.kind == Kind::Attribute {
+            return true;
+        }
+        // Casts, sized numbers, and unsized literals keep their spacing.
+        if p.is("'")
+            || (next.is("'") && (matches!(p.kind, Kind::Ident | Kind::Keyword) || p.is_close()))
+        {
+            return next.space;
+        }
+        if next.is_close() || next.is(",") || next.is(";") {
+            return false;
+        }
+        if p.is(",") || p.is(";") {
+            return true;
+        }
+        if p.is_open() || p.is("#") || p.is("##") || p.is("@") || p.is(".") || p.is("::") {
+            return false;
+        }
+        if prev.unary {
+            return false;
+        }
+        if next.is(".") || next.is("::") {
+            return false;
+        }
+        if next.is("(") || next.is("[") || next.is("{") {
+            return match p.kind {
+                Kind::Keyword if p.is_any(CONTROL_KEYWORDS) || !next.is("(") => true,
+                Kind::Keyword | Kind::Ident => next.space,
+                _ => p.is_close() && !next.is("[") || p.kind == Kind::Symbol,
+            };
+        }
+        if next.is("++") || next.is("--") {
+            return !(p.kind == Kind::Ident || p.is_close());
+        }
+        if next.is(":") {
+            return self.ternary > 0;
+        }
+        if p.is(":") {
+            return self.brackets.last() != Some(&"[") || self.ternary > 0;
+        }
+        let in_index = self.brackets.last() == Some(&"[");
+        if in_index && (p.kind == Kind::Symbol || next.kind == Kind::Symbol) {
+            return p.is("+:") || p.is("-:") || next.is("+:") || next.is("-:");
+        }
+        true
+    }
+
+    /// Determine whether an operator is unary, given the preceding word.
+    fn is_unary(&self, prev: &Placed<'a>, next: &Word<'a>) -> bool {
+        if next.kind != Kind::Symbol || !UNARY_OPERATORS.contains(&next.text) {
+            return false;
+        }
+        let p = prev.word;
+        if p.is("@") && next.is("*") {
+            return false;
+        }
+        match p.kind {
+            Kind::Keyword => true,
+            Kind::Symbol => {
+                !p.is_close() && !((p.is("++") || p.is("--")) && !prev.unary) && !p.is("'")
+            }
+            _ => false,
+        }
+    }
+
+    fn is_unary_at_start(&self, next: &Word<'a>) -> bool {
+        next.kind == Kind::Symbol && UNARY_OPERATORS.contains(&next.text)
+    }
+}
+
+/// Render lines as text.
+fn render(lines: &[Line], opts: &FormatOptions) -> String {
+    let indent_str = |level: usize| {
+        if opts.use_tabs {
+            "\t".repeat(level)
+        } else {
+            " ".repeat(level * opts.indent_width)
+        }
+    };
+    let indent_cols = |level: usize| level * opts.indent_width;
+    let mut output = String::new();
+    for line in lines {
+        if !output.is_empty() {
+            for _ in 0..line.blank_before.min(opts.max_blank_lines) {
+                output.push('\n');
+            }
+        }
+
+        // Divide the line into chunks after which it may be broken.
+        let mut chunks: Vec<&[Placed]> = vec![];
+        let mut start = 0;
+        for (i, placed) in line.words.iter().enumerate() {
+            let w = placed.word;
+            if i + 1 < line.words.len()
+                && !line.words[i + 1].word.is_comment()
+                && (w.is(",") || w.is("&&") || w.is("||"))
+            {
+                chunks.push(&line.words[start..=i]);
+                start = i + 1;
+            }
+        }
+        chunks.push(&line.words[start..]);
+        // Trailing comments may extend beyond the maximum width.
+        let width = |chunk: &[Placed], first: bool| -> usize {
+            chunk
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| p.word.kind != Kind::LineComment)
+                .map(|(i, p)| {
+                    let text = p.word.text.lines().next().unwrap_or("");
+                    text.chars().count() + (p.space && !(first && i == 0)) as usize
+                })
+                .sum()
+        };
+
+        // Place the chunks greedily.
+        output.push_str(&indent_str(line.indent));
+        let mut column = indent_cols(line.indent);
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let mut first = i == 0;
+            if !first
+                && opts.max_width > 0
+                && column + width(chunk, false) > opts.max_width
+                && column > indent_cols(line.indent)
+            {
+                output.push('\n');
+                output.push_str(&indent_str(chunk[0].wrap_indent));
+                column = indent_cols(chunk[0].wrap_indent);
+                first = true;
+            }
+            for (j, placed) in chunk.iter().enumerate() {
+                if placed.space && !(first && j == 0) {
+                    output.push(' ');
+                }
+                output.push_str(placed.word.text);
+            }
+            column += width(chunk, first);
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(input: &str) -> String {
+        format(input, &FormatOptions::default())
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(fmt(""), "");
+        assert_eq!(fmt("\n\n"), "");
+    }
+
+    #[test]
+    fn module() {
+        assert_eq!(
+            fmt("module foo(input logic a,output logic b);\nassign b=~a;\nendmodule"),
+            "module foo(input logic a, output logic b);\n    assign b = ~a;\nendmodule\n"
+        );
+    }
+
+    #[test]
+    fn ports() {
+        assert_eq!(
+            fmt("module foo #(\nparameter W=8\n) (\ninput [W-1:0] a\n);\nendmodule\n"),
+            "module foo #(\n    parameter W = 8\n) (\n    input [W-1:0] a\n);\nendmodule\n"
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(
+            fmt("always_ff @(posedge clk) begin if (rst) q <= 0; else begin q <= d; end end"),
+            "always_ff @(posedge clk) begin\n    if (rst) q <= 0;\n    else begin\n        \
+             q <= d;\n    end\nend\n"
+        );
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(
+            fmt("initial begin:foo\nx=1;\nend:foo\n"),
+            "initial begin: foo\n    x = 1;\nend: foo\n"
+        );
+    }
+
+    #[test]
+    fn hanging() {
+        assert_eq!(
+            fmt("always_ff @(posedge clk)\nif (rst)\nq <= 0;\nelse\nq <= d;\nassign a = b;\n"),
+            "always_ff @(posedge clk)\n    if (rst)\n        q <= 0;\n    else\n        q <= d;\n\
+             assign a = b;\n"
+        );
+    }
+
+    #[test]
+    fn case() {
+        assert_eq!(
+            fmt("case (x)\n0: y = 1;\ndefault:\ny = 0;\nendcase\n"),
+            "case (x)\n    0: y = 1;\n    default:\n        y = 0;\nendcase\n"
+        );
+    }
+
+    #[test]
+    fn continuation() {
+        assert_eq!(
+            fmt("assign x = a +\nb;\nassign y = c ? d : e;\n"),
+            "assign x = a +\n    b;\nassign y = c ? d : e;\n"
+        );
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            fmt("module foo;   // top\n\n\n\n  /* block\n   comment */\nlogic a;endmodule\n"),
+            "module foo; // top\n\n    /* block\n   comment */\n    logic a;\nendmodule\n"
+        );
+    }
+
+    #[test]
+    fn directives() {
+        assert_eq!(
+            fmt(
+                "`timescale 1ns/1ps\nmodule foo;\n`ifdef A\nlogic a;\n`endif\n`FOO(x)\nendmodule\n"
+            ),
+            "`timescale 1ns/1ps\nmodule foo;\n    `ifdef A\n    logic a;\n    `endif\n    \
+             `FOO(x)\nendmodule\n"
+        );
+        assert_eq!(
+            fmt("`define A(x) \\\n  x + 1\nmodule foo;endmodule\n"),
+            "`define A(x) \\\n  x + 1\nmodule foo;\nendmodule\n"
+        );
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(
+            fmt("assign a=8'hFF+'0+int'(b)+1.5e-3+c[i+:4]+\"a  b\";\n"),
+            "assign a = 8'hFF + '0 + int'(b) + 1.5e-3 + c[i +: 4] + \"a  b\";\n"
+        );
+        assert_eq!(
+            fmt("assign b=x==4'b1??0||x=='b?;\n"),
+            "assign b = x == 4'b1??0 || x == 'b?;\n"
+        );
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(
+            fmt("assign a=-b&&!c||d<=e;\ninitial i++;\nalways @* x=y;\nimport pkg::*;\n"),
+            "assign a = -b && !c || d <= e;\ninitial i++;\nalways @* x = y;\nimport pkg::*;\n"
+        );
+    }
+
+    #[test]
+    fn prototypes() {
+        assert_eq!(
+            fmt("class C;\nextern function void f();\ntypedef class D;\nvirtual function void g();\nendfunction\nendclass\n"),
+            "class C;\n    extern function void f();\n    typedef class D;\n    \
+             virtual function void g();\n    endfunction\nendclass\n"
+        );
+    }
+
+    #[test]
+    fn wrap() {
+        let opts = FormatOptions {
+            max_width: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            format("foo i_foo (.a(a), .b(b), .c(c));\n", &opts),
+            "foo i_foo (.a(a),\n    .b(b), .c(c));\n"
+        );
+    }
+
+    #[test]
+    fn tabs() {
+        let opts = FormatOptions {
+            use_tabs: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format("module foo;\nlogic a;\nendmodule\n", &opts),
+            "module foo;\n\tlogic a;\nendmodule\n"
+        );
+    }
+}
@@ -17,6 +17,8 @@ pub type TokenAndSpan = (Token, Span);
 pub struct Lexer<'a> {
     input: Preprocessor<'a>,
     peek: [CatTokenAndSpan; 4],
+    /// The keywords in effect for each token in `peek`.
+    keywords: [Standard; 4],
 }
 
 impl<'a> Lexer<'a> {
@@ -24,6 +26,7 @@ impl<'a> Lexer<'a> {
         Lexer {
             input: input,
             peek: [(CatTokenKind::Eof, INVALID_SPAN); 4],
+            keywords: [Standard::default(); 4],
         }
     }
 
@@ -36,6 +39,12 @@ impl<'a> Lexer<'a> {
             Some(Ok(x)) => x,
             None => (CatTokenKind::Eof, self.peek[2].1),
         };
+        self.keywords = [
+            self.keywords[1],
+            self.keywords[2],
+            self.keywords[3],
+            self.input.keywords(),
+        ];
 
         Ok(())
     }
@@ -100,6 +109,7 @@ impl<'a> Lexer<'a> {
 
                     // Logic
                     ('<', '-', '>') => Some(Operator(Op::LogicEquiv)),
+                    ('&', '&', '&') => Some(Operator(Op::TripleAnd)),
 
                     // Shift
                     ('<', '<', '<') => Some(Operator(Op::ArithShL)),
@@ -235,8 +245,11 @@ impl<'a> Lexer<'a> {
                 // IEEE 1800-2009 5.6 Identifiers
                 // IEEE 1800-2009 5.6.2 Keywords
                 (CatTokenKind::Text, _) | (CatTokenKind::Symbol('_'), _) => {
+                    // Keywords of later revisions of the standard are
+                    // regular identifiers.
+                    let keywords = self.keywords[0];
                     let (m, msp) = self.match_ident()?;
-                    return match find_keyword(&m) {
+                    return match find_keyword(&m).filter(|kw| kw.standard() <= keywords) {
                         Some(Kw::Begin) => Ok((OpenDelim(Bgend), msp)),
                         Some(Kw::End) => Ok((CloseDelim(Bgend), msp)),
                         Some(kw) => Ok((Keyword(kw), msp)),
@@ -385,6 +398,10 @@ impl<'a> Lexer<'a> {
                                 )
                                 .span(sp))
                             }
+                            (CatTokenKind::Eof, _) => {
+                                return Err(DiagBuilder2::fatal("String literal is not terminated")
+                                    .span(span))
+                            }
                             (_, sp) => {
                                 span.expand(sp);
                                 s.push_str(&sp.extract());
@@ -641,6 +658,7 @@ impl<'a> Lexer<'a> {
                 "ns" => Some(TimeUnit::NanoSecond),
                 "ps" => Some(TimeUnit::PicoSecond),
                 "fs" => Some(TimeUnit::FemtoSecond),
+                "step" => Some(TimeUnit::Step),
                 _ => None,
             }
         } else {
@@ -660,20 +678,62 @@ impl<'a> Iterator for Lexer<'a> {
     }
 }
 
+/// A source of tokens for the parser.
+pub trait TokenSource {
+    /// Get the next token, or an `Eof` token at the end of the input.
+    fn next_token(&mut self) -> DiagResult2<TokenAndSpan>;
+}
+
+impl<T: TokenSource + ?Sized> TokenSource for &mut T {
+    fn next_token(&mut self) -> DiagResult2<TokenAndSpan> {
+        (**self).next_token()
+    }
+}
+
+impl<'a> TokenSource for Lexer<'a> {
+    fn next_token(&mut self) -> DiagResult2<TokenAndSpan> {
+        Lexer::next_token(self)
+    }
+}
+
+/// The tokens of a source file, lexed ahead of parsing.
+///
+/// Unlike a `Lexer`, the tokens may be sent to another thread. This allows
+/// files to be preprocessed and lexed concurrently, and parsed afterwards.
+pub struct Tokens {
+    tokens: std::vec::IntoIter<DiagResult2<TokenAndSpan>>,
+    eof: Span,
+}
+
+impl Tokens {
+    /// Lex the entire input of a lexer.
+    pub fn new(mut lexer: Lexer) -> Tokens {
+        let mut tokens = vec![];
+        let eof = loop {
+            match lexer.next_token() {
+                Ok((Eof, sp)) => break sp,
+                x => tokens.push(x),
+            }
+        };
+        Tokens {
+            tokens: tokens.into_iter(),
+            eof,
+        }
+    }
+}
+
+impl TokenSource for Tokens {
+    fn next_token(&mut self) -> DiagResult2<TokenAndSpan> {
+        self.tokens.next().unwrap_or(Ok((Eof, self.eof)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn check(input: &str, expected: &[Token]) {
-        use std::cell::Cell;
-        thread_local!(static INDEX: Cell<usize> = Cell::new(0));
-        let sm = get_source_manager();
-        let idx = INDEX.with(|i| {
-            let v = i.get();
-            i.set(v + 1);
-            v
-        });
-        let source = sm.add(&format!("test_{}.sv", idx), input);
+        let source = get_source_manager().add_anonymous(input);
         let pp = Preprocessor::new(source, &[], &[]);
         let lexer = Lexer::new(pp);
         let actual: Vec<_> = lexer.map(|x| x.unwrap().0).collect();
@@ -834,7 +894,7 @@ mod tests {
     #[test]
     fn time_literal() {
         check(
-            "42s 14.3ms 16.32us 9ns 0.1ps 8123fs",
+            "42s 14.3ms 16.32us 9ns 0.1ps 8123fs 1step",
             &[
                 Literal(Time(name("42"), None, TimeUnit::Second)),
                 Literal(Time(name("14"), Some(name("3")), TimeUnit::MilliSecond)),
@@ -842,6 +902,7 @@ mod tests {
                 Literal(Time(name("9"), None, TimeUnit::NanoSecond)),
                 Literal(Time(name("0"), Some(name("1")), TimeUnit::PicoSecond)),
                 Literal(Time(name("8123"), None, TimeUnit::FemtoSecond)),
+                Literal(Time(name("1"), None, TimeUnit::Step)),
             ],
         );
     }
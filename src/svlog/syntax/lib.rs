@@ -8,6 +8,8 @@ extern crate log;
 
 pub mod ast;
 pub mod cat;
+pub mod cst;
+pub mod format;
 pub mod lexer;
 pub mod parser;
 pub mod preproc;
@@ -8,11 +8,12 @@
 
 use crate::ast;
 use crate::ast::*;
-use crate::lexer::{Lexer, TokenAndSpan};
+use crate::lexer::{Lexer, TokenAndSpan, TokenSource};
+use crate::preproc::Preprocessor;
 use crate::token::*;
 use moore_common::{arenas::Alloc, errors::*, name::*, source::*, util::HasSpan};
 use std;
-use std::collections::VecDeque;
+use std::collections::HashSet;
 
 // The problem with data_declaration and data_type_or_implicit:
 //
@@ -42,6 +43,45 @@ trait AbstractParser<'n> {
     fn add_diag(&mut self, diag: DiagBuilder2);
     fn severity(&self) -> Severity;
 
+    /// How deeply the constructs currently being parsed are nested. Shared
+    /// with the speculative parsers branched off of this one.
+    fn nesting(&mut self) -> &mut Nesting;
+
+    /// The absolute position of the next token in the input.
+    fn position(&self) -> usize;
+
+    /// The outcomes of speculative parses. Shared with the speculative parsers
+    /// branched off of this one.
+    fn memo(&mut self) -> &mut Memo;
+
+    /// Whether the input is parsed as Verilog, where the constructs
+    /// introduced by SystemVerilog are errors.
+    fn verilog(&self) -> bool;
+
+    /// Report a construct introduced by SystemVerilog if the input is parsed
+    /// as Verilog.
+    fn check_verilog(&mut self, what: &str, span: Span) {
+        if self.verilog() {
+            self.add_diag(
+                DiagBuilder2::error(format!(
+                    "{} requires {} or later",
+                    what,
+                    Standard::Ieee1800_2005
+                ))
+                .span(span)
+                .add_note(format!(
+                    "The input is parsed as {} due to `--verilog`",
+                    Standard::Ieee1364_2005
+                )),
+            );
+        }
+    }
+
+    /// Look ahead at the kind of a token, without its span.
+    fn peek_kind(&mut self, offset: usize) -> Token {
+        self.peek(offset).0
+    }
+
     fn try_eat_ident(&mut self) -> Option<(Name, Span)> {
         match self.peek(0) {
             (Ident(name), span) => {
@@ -92,7 +132,7 @@ trait AbstractParser<'n> {
     }
 
     fn is_ident(&mut self) -> bool {
-        match self.peek(0).0 {
+        match self.peek_kind(0) {
             Ident(_) | EscIdent(_) => true,
             _ => false,
         }
@@ -229,35 +269,129 @@ trait AbstractParser<'n> {
     }
 }
 
-struct Parser<'a, 'n> {
-    input: Lexer<'a>,
-    queue: VecDeque<TokenAndSpan>,
+/// The tokens a parser has looked ahead at.
+///
+/// A ring buffer whose capacity is a power of two, such that positions wrap
+/// around by masking. The parser itself rarely looks more than a few tokens
+/// ahead, which the initial capacity covers. Speculative parsing through a
+/// `BranchParser` may look further ahead, in which case the capacity doubles.
+struct TokenRing {
+    buf: Box<[TokenAndSpan]>,
+    head: usize,
+    len: usize,
+}
+
+impl TokenRing {
+    /// The initial capacity of the ring.
+    const INITIAL_CAPACITY: usize = 16;
+
+    fn new() -> Self {
+        TokenRing {
+            buf: vec![(Eof, INVALID_SPAN); Self::INITIAL_CAPACITY].into_boxed_slice(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// The number of tokens in the ring.
+    #[inline(always)]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Get the token at `offset` from the front. The offset must be less than
+    /// `len()`.
+    #[inline(always)]
+    fn get(&self, offset: usize) -> TokenAndSpan {
+        debug_assert!(offset < self.len);
+        self.buf[(self.head + offset) & (self.buf.len() - 1)]
+    }
+
+    /// Get the last token in the ring.
+    fn back(&self) -> Option<TokenAndSpan> {
+        if self.len > 0 {
+            Some(self.get(self.len - 1))
+        } else {
+            None
+        }
+    }
+
+    fn push_back(&mut self, token: TokenAndSpan) {
+        if self.len == self.buf.len() {
+            self.grow();
+        }
+        let mask = self.buf.len() - 1;
+        self.buf[(self.head + self.len) & mask] = token;
+        self.len += 1;
+    }
+
+    fn pop_front(&mut self) -> Option<TokenAndSpan> {
+        if self.len == 0 {
+            return None;
+        }
+        let token = self.buf[self.head];
+        self.head = (self.head + 1) & (self.buf.len() - 1);
+        self.len -= 1;
+        Some(token)
+    }
+
+    /// Double the capacity, moving the tokens to the front of the new buffer.
+    fn grow(&mut self) {
+        let mut buf = vec![(Eof, INVALID_SPAN); self.buf.len() * 2];
+        for (i, slot) in buf.iter_mut().take(self.len).enumerate() {
+            *slot = self.get(i);
+        }
+        self.buf = buf.into_boxed_slice();
+        self.head = 0;
+    }
+}
+
+struct Parser<'n, I> {
+    input: I,
+    queue: TokenRing,
     diagnostics: Vec<DiagBuilder2>,
     last_span: Span,
     severity: Severity,
     consumed: usize,
     arena: &'n ast::Arena<'n>,
+    /// The keywords of later standards which appeared as identifiers.
+    demoted_keywords: HashSet<Name>,
+    nesting: Nesting,
+    memo: Memo,
+    /// The spans of the tokens skipped during error recovery.
+    skipped: Vec<Span>,
+    /// Whether the input is parsed as Verilog.
+    verilog: bool,
 }
 
-impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
+impl<'n, I: TokenSource> AbstractParser<'n> for Parser<'n, I> {
     fn arena(&self) -> &'n ast::Arena<'n> {
         self.arena
     }
 
     fn peek(&mut self, offset: usize) -> TokenAndSpan {
-        self.ensure_queue_filled(offset);
+        if offset >= self.queue.len() {
+            self.ensure_queue_filled(offset);
+            if offset >= self.queue.len() {
+                return self
+                    .queue
+                    .back()
+                    .expect("At least an Eof token should be in the queue");
+            }
+        }
+        self.queue.get(offset)
+    }
+
+    fn peek_kind(&mut self, offset: usize) -> Token {
         if offset < self.queue.len() {
-            self.queue[offset]
+            self.queue.get(offset).0
         } else {
-            *self
-                .queue
-                .back()
-                .expect("At least an Eof token should be in the queue")
+            self.peek(offset).0
         }
     }
 
     fn bump(&mut self) {
-        if self.queue.is_empty() {
+        if self.queue.len() == 0 {
             self.ensure_queue_filled(1);
         }
         if let Some((_, sp)) = self.queue.pop_front() {
@@ -267,7 +401,9 @@ impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
     }
 
     fn skip(&mut self) {
-        self.bump()
+        let span = self.peek(0).1;
+        self.bump();
+        self.skipped.push(span);
     }
 
     fn consumed(&self) -> usize {
@@ -279,8 +415,7 @@ impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
     }
 
     fn add_diag(&mut self, diag: DiagBuilder2) {
-        eprintln!("");
-        eprintln!("{}", diag);
+        print_diag(&diag, format_args!("\n{}", diag));
 
         // Emit a backtrace for this diagnostic.
         if diag.get_severity() >= Severity::Warning {
@@ -301,35 +436,158 @@ impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
     fn severity(&self) -> Severity {
         self.severity
     }
+
+    fn nesting(&mut self) -> &mut Nesting {
+        &mut self.nesting
+    }
+
+    fn position(&self) -> usize {
+        self.consumed
+    }
+
+    fn memo(&mut self) -> &mut Memo {
+        &mut self.memo
+    }
+
+    fn verilog(&self) -> bool {
+        self.verilog
+    }
 }
 
-impl<'a, 'n> Parser<'a, 'n> {
-    fn new(input: Lexer<'a>, arena: &'n ast::Arena<'n>) -> Self {
+impl<'n, I: TokenSource> Parser<'n, I> {
+    fn new(input: I, arena: &'n ast::Arena<'n>) -> Self {
         Parser {
             input: input,
-            queue: VecDeque::new(),
+            queue: TokenRing::new(),
             diagnostics: Vec::new(),
             last_span: INVALID_SPAN,
             severity: Severity::Note,
             consumed: 0,
             arena,
+            demoted_keywords: HashSet::new(),
+            nesting: Nesting::default(),
+            memo: Memo::default(),
+            skipped: Vec::new(),
+            verilog: false,
+        }
+    }
+
+    /// Warn about an identifier which is a keyword in a later revision of the
+    /// standard than the one the input is processed as.
+    fn check_demoted_keyword(&mut self, name: Name, span: Span) {
+        // Verilog input is expected to use these as identifiers.
+        if self.verilog {
+            return;
+        }
+        let kw = match find_keyword(&*name.as_str()) {
+            Some(kw) => kw,
+            None => return,
+        };
+        if self.demoted_keywords.insert(name) {
+            self.add_diag(
+                DiagBuilder2::warning(format!(
+                    "`{}` is a keyword in {} and later",
+                    name,
+                    kw.standard()
+                ))
+                .span(span)
+                .add_note("It is treated as an identifier under the selected standard")
+                .add_note("Use `--std` to select a later standard"),
+            );
         }
     }
 
     fn ensure_queue_filled(&mut self, min_tokens: usize) {
-        if let Some(&(Eof, _)) = self.queue.back() {
+        if let Some((Eof, _)) = self.queue.back() {
             return;
         }
         while self.queue.len() <= min_tokens {
             match self.input.next_token() {
                 Ok((Eof, sp)) => self.queue.push_back((Eof, sp)),
-                Ok(tkn) => self.queue.push_back(tkn),
-                Err(x) => self.add_diag(x),
+                Ok((Ident(name), sp)) => {
+                    self.check_demoted_keyword(name, sp);
+                    self.queue.push_back((Ident(name), sp));
+                }
+                Ok((tkn, sp)) => {
+                    if tkn.standard() > Standard::Ieee1364_2005 {
+                        let what = match tkn {
+                            Literal(UnbasedUnsized(c)) => format!("literal `'{}`", c),
+                            Literal(_) => "time literal".to_string(),
+                            _ => format!("`{}`", tkn),
+                        };
+                        self.check_verilog(&what, sp);
+                    }
+                    self.queue.push_back((tkn, sp));
+                }
+                Err(x) => {
+                    // The lexer does not necessarily advance past the input
+                    // that caused a fatal error, so end the token stream.
+                    let fatal = x.get_severity() >= Severity::Fatal;
+                    let span = x
+                        .get_segments()
+                        .iter()
+                        .filter_map(|s| match *s {
+                            DiagSegment::Span(sp) => Some(sp.begin().into()),
+                            _ => None,
+                        })
+                        .next()
+                        .unwrap_or(self.last_span);
+                    self.add_diag(x);
+                    if fatal {
+                        self.queue.push_back((Eof, span));
+                        return;
+                    }
+                }
             }
         }
     }
 }
 
+/// The maximum number of constructs that may be nested within each other.
+/// Deeper nesting is rejected instead of overflowing the stack.
+const MAX_NESTING: usize = 256;
+
+/// How deeply the constructs currently being parsed are nested.
+#[derive(Default)]
+struct Nesting {
+    depth: usize,
+    /// Where the maximum nesting depth was exceeded.
+    exceeded: Option<Span>,
+}
+
+/// The outcomes of speculative parses, which are remembered such that nested
+/// constructs are not parsed over and over again.
+#[derive(Default)]
+struct Memo {
+    /// The token positions at which no cast or typed pattern expression
+    /// starts.
+    no_cast: HashSet<usize>,
+}
+
+/// Calls the `inner` function as a construct nested within the current one.
+/// Wrapped around the functions through which the parser recurses.
+///
+/// Once the maximum nesting depth is exceeded, this fails without calling
+/// `inner`, even in other speculative branches. This unwinds the parser to
+/// `parse_source_text`, which reports the error and stops.
+fn nested<'n, R, F>(p: &mut dyn AbstractParser<'n>, inner: F) -> ReportedResult<R>
+where
+    F: FnOnce(&mut dyn AbstractParser<'n>) -> ReportedResult<R>,
+{
+    if p.nesting().exceeded.is_some() {
+        return Err(());
+    }
+    if p.nesting().depth >= MAX_NESTING {
+        let span = p.peek(0).1;
+        p.nesting().exceeded = Some(span);
+        return Err(());
+    }
+    p.nesting().depth += 1;
+    let result = inner(p);
+    p.nesting().depth -= 1;
+    result
+}
+
 /// Parses the opening delimiter, calls the `inner` function, and parses the
 /// closing delimiter. Properly recovers to and including the closing
 /// delimiter if the `inner` function throws an error.
@@ -342,7 +600,7 @@ where
     F: FnMut(&mut dyn AbstractParser<'n>) -> ReportedResult<R>,
 {
     p.require_reported(OpenDelim(delim))?;
-    match inner(p) {
+    match nested(p, |p| inner(p)) {
         Ok(r) => match p.require_reported(CloseDelim(delim)) {
             Ok(_) => Ok(r),
             Err(e) => {
@@ -369,7 +627,7 @@ fn try_flanked<'n, R, F>(
 where
     F: FnMut(&mut dyn AbstractParser<'n>) -> ReportedResult<R>,
 {
-    if p.peek(0).0 == OpenDelim(delim) {
+    if p.peek_kind(0) == OpenDelim(delim) {
         flanked(p, delim, inner).map(|r| Some(r))
     } else {
         Ok(None)
@@ -389,7 +647,7 @@ where
     T: Predicate,
 {
     let mut v = Vec::new();
-    while !p.is_fatal() && p.peek(0).0 != Eof && !term.matches(p) {
+    while !p.is_fatal() && p.peek_kind(0) != Eof && !term.matches(p) {
         // Parse the item.
         match item(p) {
             Ok(x) => v.push(x),
@@ -453,7 +711,7 @@ where
     F: FnMut(&mut dyn AbstractParser<'n>) -> ReportedResult<R>,
 {
     let mut v = Vec::new();
-    while p.peek(0).0 != term && p.peek(0).0 != Eof {
+    while p.peek_kind(0) != term && p.peek_kind(0) != Eof {
         match item(p) {
             Ok(x) => v.push(x),
             Err(_) => {
@@ -583,7 +841,7 @@ trait Predicate {
 
 impl Predicate for Token {
     fn matches(&mut self, p: &mut dyn AbstractParser<'_>) -> bool {
-        p.peek(0).0 == *self
+        p.peek_kind(0) == *self
     }
 
     fn recover(&mut self, p: &mut dyn AbstractParser<'_>, consume: bool) {
@@ -622,8 +880,55 @@ impl<
     }
 }
 
-pub fn parse<'n>(input: Lexer, arena: &'n ast::Arena<'n>) -> Result<ast::SourceFile<'n>, ()> {
+pub fn parse<'n>(
+    input: impl TokenSource,
+    arena: &'n ast::Arena<'n>,
+) -> Result<ast::SourceFile<'n>, ()> {
+    let mut p = Parser::new(input, arena);
+    let root = parse_source_text(&mut p);
+    if p.is_error() {
+        Err(())
+    } else {
+        Ok(root)
+    }
+}
+
+/// Parse SystemVerilog source text.
+///
+/// Unlike `parse`, this returns the diagnostics of the preprocessor, lexer, and
+/// parser instead of printing them. The text is added to the source manager as
+/// an anonymous file, against which the spans in the AST and diagnostics are
+/// resolved. The file is released once the returned `AnonymousSource` is
+/// dropped, after which the spans must no longer be used. Meant for tools and
+/// fuzz targets which feed arbitrary text to the parser.
+pub fn parse_str<'n>(
+    input: &str,
+    arena: &'n ast::Arena<'n>,
+) -> (
+    Option<ast::SourceFile<'n>>,
+    Vec<DiagBuilder2>,
+    AnonymousSource,
+) {
+    let source = AnonymousSource::new(input);
+    let (result, captured) = capture_diags(|| {
+        let pp = Preprocessor::new(source.source(), &[], &[]);
+        parse(Lexer::new(pp), arena)
+    });
+    (result.ok(), captured.diags, source)
+}
+
+/// Parse source text as Verilog, as per IEEE 1364-2005.
+///
+/// The constructs introduced by SystemVerilog, such as its operators,
+/// literals, and implicit port connections, are reported as errors. The
+/// keywords in effect are determined by the preprocessor, which should be
+/// configured for the same standard.
+pub fn parse_verilog<'n>(
+    input: impl TokenSource,
+    arena: &'n ast::Arena<'n>,
+) -> Result<ast::SourceFile<'n>, ()> {
     let mut p = Parser::new(input, arena);
+    p.verilog = true;
     let root = parse_source_text(&mut p);
     if p.is_error() {
         Err(())
@@ -632,6 +937,20 @@ pub fn parse<'n>(input: Lexer, arena: &'n ast::Arena<'n>) -> Result<ast::SourceF
     }
 }
 
+/// Parse source text, recovering from errors where possible.
+///
+/// Returns the AST of everything that could be parsed, even if errors were
+/// reported, together with the spans of the tokens skipped during error
+/// recovery. Used to construct concrete syntax trees.
+pub(crate) fn parse_with_recovery<'n>(
+    input: impl TokenSource,
+    arena: &'n ast::Arena<'n>,
+) -> (ast::SourceFile<'n>, Vec<Span>) {
+    let mut p = Parser::new(input, arena);
+    let root = parse_source_text(&mut p);
+    (root, p.skipped)
+}
+
 fn parse_source_text<'n>(p: &mut dyn AbstractParser<'n>) -> ast::SourceFile<'n> {
     let mut span = p.peek(0).1;
     let mut root = ast::SourceFileData {
@@ -649,11 +968,21 @@ fn parse_source_text<'n>(p: &mut dyn AbstractParser<'n>) -> ast::SourceFile<'n>
     }
 
     // Parse the descriptions in the source text.
-    while !p.is_fatal() && p.peek(0).0 != Eof {
+    while !p.is_fatal() && p.peek_kind(0) != Eof {
         match parse_item(p) {
             Ok(item) => root.items.push(item),
             Err(()) => (), // parse_item handles recovery, so no need to do anything here
         }
+        if let Some(span) = p.nesting().exceeded {
+            p.add_diag(
+                DiagBuilder2::fatal(format!(
+                    "constructs nested too deeply; more than {} levels",
+                    MAX_NESTING
+                ))
+                .span(span),
+            );
+            break;
+        }
     }
 
     span.expand(p.last_span());
@@ -663,7 +992,7 @@ fn parse_source_text<'n>(p: &mut dyn AbstractParser<'n>) -> ast::SourceFile<'n>
 fn parse_time_units<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Timeunit> {
     let mut unit = None;
     let mut prec = None;
-    while p.peek(0).0 == Keyword(Kw::Timeunit) || p.peek(0).0 == Keyword(Kw::Timeprecision) {
+    while p.peek_kind(0) == Keyword(Kw::Timeunit) || p.peek_kind(0) == Keyword(Kw::Timeprecision) {
         recovered(p, Semicolon, |p| {
             if p.try_eat(Keyword(Kw::Timeunit)) {
                 unit = Some(parse_time_literal(p)?);
@@ -714,49 +1043,12 @@ fn parse_interface_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<In
     let mut span = p.peek(0).1;
     p.require_reported(Keyword(Kw::Interface))?;
     let result = recovered(p, Keyword(Kw::Endinterface), |p| {
-        // Eat the optional lifetime.
-        let lifetime = match as_lifetime(p.peek(0).0) {
-            Some(l) => {
-                p.bump();
-                l
-            }
-            None => Lifetime::Static,
-        };
-
-        // Eat the interface name.
-        let (name, name_sp) = p.eat_ident("interface name")?;
-
-        // TODO: Parse package import declarations.
-
-        // Eat the parameter port list.
-        let param_ports = if p.try_eat(Hashtag) {
-            parse_parameter_port_list(p)?
-        } else {
-            Vec::new()
-        };
-
-        // Eat the optional list of ports.
-        let ports = if p.try_eat(OpenDelim(Paren)) {
-            parse_port_list(p)?
-        } else {
-            Vec::new()
-        };
-
-        // Eat the semicolon at the end of the header.
-        if !p.try_eat(Semicolon) {
-            let q = p.peek(0).1.end();
-            p.add_diag(
-                DiagBuilder2::error(format!(
-                    "Missing semicolon \";\" after header of interface \"{}\"",
-                    name
-                ))
-                .span(q),
-            );
-        }
+        let header = parse_design_header(p, "interface")?;
 
         // Eat the items in the interface.
         let mut items = Vec::new();
-        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endinterface) && p.peek(0).0 != Eof {
+        while !p.is_fatal() && p.peek_kind(0) != Keyword(Kw::Endinterface) && p.peek_kind(0) != Eof
+        {
             if p.try_eat(Semicolon) {
                 continue;
             }
@@ -767,11 +1059,14 @@ fn parse_interface_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<In
         Ok(Interface::new(
             span,
             InterfaceData {
-                lifetime: lifetime,
-                name: Spanned::new(name, name_sp),
-                params: param_ports,
-                ports: ports,
+                lifetime: header.lifetime,
+                name: header.name,
+                imports: header.imports,
+                params: header.params,
+                param_list: header.param_list,
+                ports: header.ports,
                 items: items,
+                doc: doc_comment(span),
             },
         ))
     });
@@ -782,6 +1077,84 @@ fn parse_interface_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<In
     result
 }
 
+/// The header of a module, interface, or program declaration.
+struct DesignHeader<'n> {
+    lifetime: Lifetime,
+    name: Spanned<Name>,
+    imports: Vec<ImportDecl<'n>>,
+    params: Vec<ParamDecl<'n>>,
+    param_list: bool,
+    ports: Vec<Port<'n>>,
+}
+
+/// Parse the header of a module, interface, or program declaration, assuming
+/// that the leading keyword has already been consumed.
+///
+/// ```text
+/// [lifetime] ident {import_decl} ["#" param_port_list] ["(" port_list ")"] ";"
+/// ```
+fn parse_design_header<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    kind: &str,
+) -> ReportedResult<DesignHeader<'n>> {
+    // Eat the optional lifetime.
+    let lifetime = match as_lifetime(p.peek_kind(0)) {
+        Some(l) => {
+            p.bump();
+            l
+        }
+        None => Lifetime::Static,
+    };
+
+    // Eat the name.
+    let (name, name_sp) = p.eat_ident(&format!("{} name", kind))?;
+
+    // Eat the optional package import declarations.
+    let mut imports = vec![];
+    while p.peek_kind(0) == Keyword(Kw::Import) {
+        imports.push(parse_import_decl(p)?);
+    }
+
+    // Eat the optional parameter port list.
+    let param_list = p.try_eat(Hashtag);
+    let params = if param_list {
+        parse_parameter_port_list(p)?
+    } else {
+        Vec::new()
+    };
+
+    // Eat the optional list of ports. Not having such a list requires the ports
+    // to be defined further down in the body.
+    let ports = if p.try_eat(OpenDelim(Paren)) {
+        parse_port_list(p)?
+    } else {
+        Vec::new()
+    };
+
+    // Eat the semicolon after the header.
+    if !p.try_eat(Semicolon) {
+        let q = p.peek(0).1.end();
+        p.add_diag(
+            DiagBuilder2::error(format!("Missing ; after header of {} \"{}\"", kind, name)).span(q),
+        );
+    }
+
+    Ok(DesignHeader {
+        lifetime,
+        name: Spanned::new(name, name_sp),
+        imports,
+        params,
+        param_list,
+        ports,
+    })
+}
+
+/// Find the `///` or `/** */` doc comment preceding a declaration.
+fn doc_comment(span: Span) -> Option<Spanned<Name>> {
+    span.doc_comment("///")
+        .map(|doc| doc.map(|text| get_name_table().intern(&text, true)))
+}
+
 fn parse_parameter_port_list<'n>(
     p: &mut dyn AbstractParser<'n>,
 ) -> ReportedResult<Vec<ParamDecl<'n>>> {
@@ -792,7 +1165,7 @@ fn parse_parameter_port_list<'n>(
             // Parse the optional `parameter` or `localparam` keyword. If none is
             // provided, the previous scope is assumed.
             let mut outer_span = p.peek(0).1;
-            match p.peek(0).0 {
+            match p.peek_kind(0) {
                 Keyword(Kw::Parameter) => {
                     p.bump();
                     local = false;
@@ -864,7 +1237,11 @@ fn parse_parameter_port_list<'n>(
             outer_span.expand(p.last_span());
             Ok(ast::ParamDecl::new(
                 outer_span,
-                ast::ParamDeclData { local, kind },
+                ast::ParamDeclData {
+                    local,
+                    kind,
+                    doc: doc_comment(outer_span),
+                },
             ))
         })
     })
@@ -874,54 +1251,23 @@ fn parse_parameter_port_list<'n>(
 /// already been consumed.
 fn parse_module_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Module<'n>> {
     let mut span = p.peek(0).1;
+    let external = p.try_eat(Keyword(Kw::Extern));
     p.require_reported(Keyword(Kw::Module))?;
-    let result = recovered(p, Keyword(Kw::Endmodule), |p| {
-        // Eat the optional lifetime.
-        let lifetime = match as_lifetime(p.peek(0).0) {
-            Some(l) => {
-                p.bump();
-                l
-            }
-            None => Lifetime::Static,
-        };
-
-        // Eat the module name.
-        let (name, name_sp) = p.eat_ident("module name")?;
-
-        // TODO: Parse package import declarations.
-        // Eat the optional package import declarations.
-        let mut imports = vec![];
-        while p.peek(0).0 == Keyword(Kw::Import) {
-            imports.push(parse_import_decl(p)?);
-        }
-
-        // Eat the optional parameter port list.
-        let params = if p.try_eat(Hashtag) {
-            parse_parameter_port_list(p)?
-        } else {
-            Vec::new()
-        };
-
-        // Eat the optional list of ports. Not having such a list requires the ports
-        // to be defined further down in the body of the module.
-        let ports = if p.try_eat(OpenDelim(Paren)) {
-            parse_port_list(p)?
-        } else {
-            Vec::new()
-        };
-
-        // Eat the semicolon after the header.
-        if !p.try_eat(Semicolon) {
-            let q = p.peek(0).1.end();
-            p.add_diag(
-                DiagBuilder2::error(format!("Missing ; after header of module \"{}\"", name))
-                    .span(q),
-            );
-        }
+    let term = if external {
+        Semicolon
+    } else {
+        Keyword(Kw::Endmodule)
+    };
+    let result = recovered(p, term, |p| {
+        let header = parse_design_header(p, "module")?;
 
-        // Parse the module items.
+        // Parse the module items. Extern modules only consist of the header.
         let mut items = Vec::new();
-        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endmodule) && p.peek(0).0 != Eof {
+        while !external
+            && !p.is_fatal()
+            && p.peek_kind(0) != Keyword(Kw::Endmodule)
+            && p.peek_kind(0) != Eof
+        {
             if p.try_eat(Semicolon) {
                 continue;
             }
@@ -932,18 +1278,29 @@ fn parse_module_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Modul
         Ok(Module::new(
             span,
             ModuleData {
-                lifetime,
-                name: Spanned::new(name, name_sp),
-                imports,
-                params,
-                ports,
+                lifetime: header.lifetime,
+                external,
+                foreign: false,
+                name: header.name,
+                imports: header.imports,
+                params: header.params,
+                param_list: header.param_list,
+                ports: header.ports,
                 items,
+                doc: doc_comment(span),
             },
         ))
     });
+    if external {
+        if result.is_err() {
+            p.try_eat(Semicolon);
+        }
+        return result;
+    }
     let sp = p.peek(0).1;
     p.require_reported(Keyword(Kw::Endmodule))?;
     if p.try_eat(Colon) {
+        p.check_verilog("label after `endmodule`", p.last_span());
         p.eat_ident("module name")?;
     }
     result
@@ -954,7 +1311,7 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
     p.require_reported(Keyword(Kw::Package))?;
     let result = recovered(p, Keyword(Kw::Endpackage), |p| {
         // Parse the optional lifetime.
-        let lifetime = match as_lifetime(p.peek(0).0) {
+        let lifetime = match as_lifetime(p.peek_kind(0)) {
             Some(x) => {
                 p.bump();
                 x
@@ -974,7 +1331,7 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
 
         // Parse the package items.
         let mut items = Vec::new();
-        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endpackage) && p.peek(0).0 != Eof {
+        while !p.is_fatal() && p.peek_kind(0) != Keyword(Kw::Endpackage) && p.peek_kind(0) != Eof {
             if p.try_eat(Semicolon) {
                 continue;
             }
@@ -989,6 +1346,7 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
                 name: Spanned::new(name, name_span),
                 timeunits: timeunits,
                 items: items,
+                doc: doc_comment(span),
             },
         ))
     });
@@ -999,47 +1357,464 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
     result
 }
 
-fn parse_program_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
+/// Parse a program declaration.
+///
+/// ```text
+/// "program" design_header {item} "endprogram" [":" ident]
+/// ```
+fn parse_program_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Program<'n>> {
+    let mut span = p.peek(0).1;
     p.require_reported(Keyword(Kw::Program))?;
     let result = recovered(p, Keyword(Kw::Endprogram), |p| {
-        let q = p.peek(0).1;
-        p.add_diag(DiagBuilder2::error("Don't know how to parse program declarations").span(q));
-        Err(())
+        let header = parse_design_header(p, "program")?;
+
+        // Eat the items in the program.
+        let mut items = Vec::new();
+        while !p.is_fatal() && p.peek_kind(0) != Keyword(Kw::Endprogram) && p.peek_kind(0) != Eof {
+            if p.try_eat(Semicolon) {
+                continue;
+            }
+            items.push(parse_item(p)?);
+        }
+
+        span.expand(p.last_span());
+        Ok(Program::new(
+            span,
+            ProgramData {
+                lifetime: header.lifetime,
+                name: header.name,
+                imports: header.imports,
+                params: header.params,
+                param_list: header.param_list,
+                ports: header.ports,
+                items,
+                doc: doc_comment(span),
+            },
+        ))
     });
     p.require_reported(Keyword(Kw::Endprogram))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("program name")?;
+    }
     result
 }
 
-fn parse_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Item<'n>> {
+/// Parse a checker declaration.
+///
+/// ```text
+/// "checker" ident ["(" [checker_port {"," checker_port}] ")"] ";"
+///     {item} "endchecker" [":" ident]
+/// ```
+fn parse_checker_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Checker<'n>> {
     let mut span = p.peek(0).1;
-    let item = parse_item_data(p)?;
-    span.expand(p.last_span());
-    Ok(Item::new(span, item))
-}
+    p.require_reported(Keyword(Kw::Checker))?;
+    let result = recovered(p, Keyword(Kw::Endchecker), |p| {
+        let name = parse_identifier_name(p, "checker name")?;
+        let ports = if p.peek_kind(0) == OpenDelim(Paren) {
+            flanked(p, Paren, |p| {
+                comma_list(p, CloseDelim(Paren), "checker port", parse_checker_port)
+            })?
+        } else {
+            vec![]
+        };
+        p.require_reported(Semicolon)?;
 
-fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemData<'n>> {
-    // Consume optional leading label.
-    if p.is_ident() && p.peek(1).0 == Colon {
-        p.bump();
-        p.bump();
-    }
+        // Eat the items in the checker.
+        let mut items = Vec::new();
+        while !p.is_fatal() && p.peek_kind(0) != Keyword(Kw::Endchecker) && p.peek_kind(0) != Eof {
+            if p.try_eat(Semicolon) {
+                continue;
+            }
 
-    // First attempt the simple cases where a keyword reliably identifies the
-    // following item.
-    let class_follows = p.peek(1).0 == Keyword(Kw::Class);
-    let strlit_follows = match p.peek(1).0 {
-        Literal(Lit::Str(..)) => true,
-        _ => false,
-    };
-    match p.peek(0).0 {
-        Keyword(Kw::Module) => return parse_module_decl(p).map(ItemData::ModuleDecl),
-        Keyword(Kw::Interface) | Keyword(Kw::Virtual) if class_follows => {
-            return parse_class_decl(p).map(ItemData::ClassDecl)
-        }
-        Keyword(Kw::Class) => return parse_class_decl(p).map(ItemData::ClassDecl),
+            // Variables may be declared as free `rand` variables.
+            if p.peek_kind(0) == Keyword(Kw::Rand) {
+                let mut span = p.peek(0).1;
+                p.bump();
+                let mut decl = parse_var_decl(p)?;
+                decl.rand = true;
+                span.expand(p.last_span());
+                decl.span = span;
+                items.push(Item::new(span, ItemData::VarDecl(decl)));
+                continue;
+            }
+
+            let item = parse_item(p)?;
+            match item.data {
+                ItemData::ModuleDecl(..)
+                | ItemData::InterfaceDecl(..)
+                | ItemData::PackageDecl(..)
+                | ItemData::ProgramDecl(..)
+                | ItemData::ClassDecl(..)
+                | ItemData::InterfaceClassDecl(..)
+                | ItemData::UdpDecl(..)
+                | ItemData::PortDecl(..)
+                | ItemData::ModportDecl(..)
+                | ItemData::NetDecl(..)
+                | ItemData::NetAlias(..) => {
+                    p.add_diag(
+                        DiagBuilder2::error(format!(
+                            "{} not allowed in checker",
+                            item.format_indefinite()
+                        ))
+                        .span(item.span),
+                    );
+                }
+                _ => items.push(item),
+            }
+        }
+
+        span.expand(p.last_span());
+        Ok(Checker::new(
+            span,
+            CheckerData {
+                name,
+                ports,
+                items,
+                doc: doc_comment(span),
+            },
+        ))
+    });
+    p.require_reported(Keyword(Kw::Endchecker))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("checker name")?;
+    }
+    result
+}
+
+/// Parse a user-defined primitive declaration.
+///
+/// ```text
+/// "primitive" ident "(" port_list ")" ";" {port_decl} [initial] table "endprimitive"
+/// ```
+fn parse_udp_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<UdpDecl<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Primitive))?;
+    let result = recovered(p, Keyword(Kw::Endprimitive), |p| {
+        let name = parse_identifier_name(p, "primitive name")?;
+
+        // Parse the port list, which either only names the ports, or declares
+        // them directly.
+        let ansi = match p.peek_kind(1) {
+            Keyword(Kw::Output) | Keyword(Kw::Input) => true,
+            _ => false,
+        };
+        let mut decls = vec![];
+        let ports = if ansi {
+            decls = flanked(p, Paren, |p| {
+                comma_list_nonempty(p, CloseDelim(Paren), "primitive port", |p| {
+                    parse_udp_port_decl(p, true)
+                })
+            })?;
+            decls
+                .iter()
+                .flat_map(|decl| decl.names.iter().cloned())
+                .collect()
+        } else {
+            flanked(p, Paren, |p| {
+                comma_list_nonempty(p, CloseDelim(Paren), "primitive port", |p| {
+                    parse_identifier_name(p, "port name")
+                })
+            })?
+        };
+        p.require_reported(Semicolon)?;
+
+        // Parse the port declarations.
+        if !ansi {
+            while let Keyword(Kw::Output) | Keyword(Kw::Input) | Keyword(Kw::Reg) = p.peek_kind(0) {
+                decls.push(parse_udp_port_decl(p, false)?);
+                p.require_reported(Semicolon)?;
+            }
+        }
+
+        // Parse the optional initial statement.
+        let initial = if p.try_eat(Keyword(Kw::Initial)) {
+            let name = parse_identifier_name(p, "output name")?;
+            p.require_reported(Operator(Op::Assign))?;
+            let value = parse_expr(p)?;
+            p.require_reported(Semicolon)?;
+            Some((name, value))
+        } else {
+            None
+        };
+
+        // Parse the table.
+        p.require_reported(Keyword(Kw::Table))?;
+        let table = repeat_until(p, Keyword(Kw::Endtable), parse_udp_table_entry)?;
+        p.require_reported(Keyword(Kw::Endtable))?;
+
+        // Make sure every entry has a symbol for each input.
+        let num_inputs = decls
+            .iter()
+            .filter(|decl| decl.kind == UdpPortKind::Input)
+            .map(|decl| decl.names.len())
+            .sum::<usize>();
+        for entry in &table {
+            if entry.inputs.len() != num_inputs {
+                p.add_diag(
+                    DiagBuilder2::error(format!(
+                        "expected {} input symbols in table entry of primitive `{}`, found {}",
+                        num_inputs,
+                        name,
+                        entry.inputs.len()
+                    ))
+                    .span(entry.span),
+                );
+                return Err(());
+            }
+        }
+
+        span.expand(p.last_span());
+        Ok(UdpDecl::new(
+            span,
+            UdpDeclData {
+                name,
+                ports,
+                decls,
+                initial,
+                table,
+            },
+        ))
+    });
+    p.require_reported(Keyword(Kw::Endprimitive))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("primitive name")?;
+    }
+    result
+}
+
+/// Parse a port declaration of a user-defined primitive.
+///
+/// In the port list of the primitive header (`ansi`), a list of input names
+/// ends at the next `input` keyword.
+fn parse_udp_port_decl<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    ansi: bool,
+) -> ReportedResult<UdpPortDecl<'n>> {
+    let mut span = p.peek(0).1;
+    let kind = match p.peek_kind(0) {
+        Keyword(Kw::Output) => {
+            p.bump();
+            if p.try_eat(Keyword(Kw::Reg)) {
+                UdpPortKind::OutputReg
+            } else {
+                UdpPortKind::Output
+            }
+        }
+        Keyword(Kw::Input) => {
+            p.bump();
+            UdpPortKind::Input
+        }
+        Keyword(Kw::Reg) if !ansi => {
+            p.bump();
+            UdpPortKind::Reg
+        }
+        tkn => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected primitive port declaration before `{}`",
+                    tkn
+                ))
+                .span(span),
+            );
+            return Err(());
+        }
+    };
+
+    // Parse the port names.
+    let mut names = vec![parse_identifier_name(p, "port name")?];
+    if kind == UdpPortKind::Input {
+        loop {
+            match (p.peek_kind(0), p.peek_kind(1)) {
+                (Comma, Ident(_)) | (Comma, EscIdent(_)) => {
+                    p.bump();
+                    names.push(parse_identifier_name(p, "port name")?);
+                }
+                _ => break,
+            }
+        }
+    }
+
+    // Parse the optional initial value of an `output reg`.
+    let init = if kind == UdpPortKind::OutputReg && p.try_eat(Operator(Op::Assign)) {
+        Some(parse_expr(p)?)
+    } else {
+        None
+    };
+
+    span.expand(p.last_span());
+    Ok(UdpPortDecl {
+        span,
+        kind,
+        names,
+        init,
+    })
+}
+
+/// Parse a row in the table of a user-defined primitive.
+///
+/// ```text
+/// {input_symbol} ":" output_symbol ";"
+/// {input_symbol} ":" state_symbol ":" output_symbol ";"
+/// ```
+///
+/// Symbols need not be separated by whitespace, so the lexer may have merged
+/// several of them into one token. We therefore look at the individual
+/// characters of each token.
+fn parse_udp_table_entry<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<UdpTableEntry> {
+    let mut span = p.peek(0).1;
+    let mut fields: Vec<Vec<UdpSymbol>> = vec![vec![]];
+    loop {
+        let (tkn, sp) = p.peek(0);
+        match tkn {
+            Semicolon => break,
+            Colon => {
+                p.bump();
+                fields.push(vec![]);
+            }
+            OpenDelim(Paren) => {
+                p.bump();
+                let mut levels = vec![];
+                while p.peek_kind(0) != CloseDelim(Paren) {
+                    let (tkn, sp) = p.peek(0);
+                    if tkn == Semicolon || tkn == Eof {
+                        break;
+                    }
+                    for c in sp.extract().chars() {
+                        levels.push(udp_level(p, c, sp)?);
+                    }
+                    p.bump();
+                }
+                p.require_reported(CloseDelim(Paren))?;
+                if levels.len() != 2 {
+                    p.add_diag(
+                        DiagBuilder2::error("expected two level symbols in edge")
+                            .span(Span::union(sp, p.last_span())),
+                    );
+                    return Err(());
+                }
+                fields
+                    .last_mut()
+                    .unwrap()
+                    .push(UdpSymbol::Edge(levels[0], levels[1]));
+            }
+            Eof => {
+                p.add_diag(DiagBuilder2::error("expected `;` after table entry").span(sp));
+                return Err(());
+            }
+            _ => {
+                p.bump();
+                for c in sp.extract().chars() {
+                    let sym = match c {
+                        'r' | 'R' => UdpSymbol::Rise,
+                        'f' | 'F' => UdpSymbol::Fall,
+                        'p' | 'P' => UdpSymbol::Pos,
+                        'n' | 'N' => UdpSymbol::Neg,
+                        '*' => UdpSymbol::AnyEdge,
+                        '-' => UdpSymbol::NoChange,
+                        c => UdpSymbol::Level(udp_level(p, c, sp)?),
+                    };
+                    fields.last_mut().unwrap().push(sym);
+                }
+            }
+        }
+    }
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+
+    // Split the symbols into inputs, state, and output.
+    let single = |p: &mut dyn AbstractParser<'n>, field: Vec<UdpSymbol>, what: &str| {
+        if field.len() == 1 {
+            Ok(field[0])
+        } else {
+            p.add_diag(
+                DiagBuilder2::error(format!("expected a single {} symbol in table entry", what))
+                    .span(span),
+            );
+            Err(())
+        }
+    };
+    let mut fields = fields.into_iter();
+    let inputs = fields.next().unwrap();
+    let (state, output) = match (fields.next(), fields.next(), fields.next()) {
+        (Some(output), None, None) => (None, single(p, output, "output")?),
+        (Some(state), Some(output), None) => (
+            Some(single(p, state, "state")?),
+            single(p, output, "output")?,
+        ),
+        _ => {
+            p.add_diag(
+                DiagBuilder2::error(
+                    "expected inputs, an optional state, and an output separated by `:`",
+                )
+                .span(span),
+            );
+            return Err(());
+        }
+    };
+    Ok(UdpTableEntry {
+        span,
+        inputs,
+        state,
+        output,
+    })
+}
+
+/// Map a character in a table entry to a level symbol.
+fn udp_level<'n>(p: &mut dyn AbstractParser<'n>, c: char, span: Span) -> ReportedResult<UdpLevel> {
+    match c {
+        '0' => Ok(UdpLevel::Zero),
+        '1' => Ok(UdpLevel::One),
+        'x' | 'X' => Ok(UdpLevel::X),
+        '?' => Ok(UdpLevel::Any),
+        'b' | 'B' => Ok(UdpLevel::ZeroOrOne),
+        c => {
+            p.add_diag(
+                DiagBuilder2::error(format!("`{}` is not a valid table symbol", c)).span(span),
+            );
+            Err(())
+        }
+    }
+}
+
+fn parse_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Item<'n>> {
+    let mut span = p.peek(0).1;
+    let item = nested(p, parse_item_data)?;
+    span.expand(p.last_span());
+    Ok(Item::new(span, item))
+}
+
+fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemData<'n>> {
+    // Consume optional leading label.
+    if p.is_ident() && p.peek_kind(1) == Colon {
+        p.bump();
+        p.bump();
+    }
+
+    // First attempt the simple cases where a keyword reliably identifies the
+    // following item.
+    let class_follows = p.peek_kind(1) == Keyword(Kw::Class);
+    let strlit_follows = match p.peek_kind(1) {
+        Literal(Lit::Str(..)) => true,
+        _ => false,
+    };
+    match p.peek_kind(0) {
+        Keyword(Kw::Module) => return parse_module_decl(p).map(ItemData::ModuleDecl),
+        Keyword(Kw::Extern) if p.peek_kind(1) == Keyword(Kw::Module) => {
+            return parse_module_decl(p).map(ItemData::ModuleDecl)
+        }
+        Keyword(Kw::Interface) if class_follows => {
+            return parse_interface_class_decl(p).map(ItemData::InterfaceClassDecl)
+        }
+        Keyword(Kw::Virtual) if class_follows => {
+            return parse_class_decl(p).map(ItemData::ClassDecl)
+        }
+        Keyword(Kw::Class) => return parse_class_decl(p).map(ItemData::ClassDecl),
         Keyword(Kw::Interface) => return parse_interface_decl(p).map(ItemData::InterfaceDecl),
         Keyword(Kw::Package) => return parse_package_decl(p).map(ItemData::PackageDecl),
         Keyword(Kw::Program) => return parse_program_decl(p).map(ItemData::ProgramDecl),
+        Keyword(Kw::Checker) => return parse_checker_decl(p).map(ItemData::CheckerDecl),
+        Keyword(Kw::Primitive) => return parse_udp_decl(p).map(ItemData::UdpDecl),
+        Keyword(Kw::Covergroup) => return parse_covergroup(p).map(ItemData::CovergroupDecl),
 
         Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
             let decl = parse_param_decl(p, false)?;
@@ -1049,7 +1824,8 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
         Keyword(Kw::Modport) => return parse_modport_decl(p).map(|x| ItemData::ModportDecl(x)),
         Keyword(Kw::Typedef) => return parse_typedef(p).map(|x| ItemData::Typedef(x)),
         Keyword(Kw::Import) if strlit_follows => return parse_dpi_decl(p).map(ItemData::DpiDecl),
-        Keyword(Kw::Export) => return parse_dpi_decl(p).map(ItemData::DpiDecl),
+        Keyword(Kw::Export) if strlit_follows => return parse_dpi_decl(p).map(ItemData::DpiDecl),
+        Keyword(Kw::Export) => return parse_export_decl(p).map(|x| ItemData::ExportDecl(x)),
         Keyword(Kw::Import) => return parse_import_decl(p).map(|x| ItemData::ImportDecl(x)),
 
         // Structured procedures as per IEEE 1800-2009 section 9.2
@@ -1074,6 +1850,11 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
         Keyword(Kw::Function) | Keyword(Kw::Task) => {
             return parse_subroutine_decl(p).map(|x| ItemData::SubroutineDecl(x));
         }
+        Keyword(Kw::Extern) => {
+            p.bump();
+            p.try_eat(Keyword(Kw::Forkjoin));
+            return parse_subroutine_prototype(p).map(|x| ItemData::ExternSubroutine(x));
+        }
 
         // Port declarations
         Keyword(Kw::Inout) | Keyword(Kw::Input) | Keyword(Kw::Output) | Keyword(Kw::Ref) => {
@@ -1085,6 +1866,26 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
             return parse_continuous_assign(p).map(|x| ItemData::ContAssign(x));
         }
 
+        // Bind directive
+        Keyword(Kw::Bind) => {
+            return parse_bind(p).map(|x| ItemData::Bind(x));
+        }
+
+        // Let declaration
+        Keyword(Kw::Let) => {
+            return parse_let_decl(p).map(ItemData::LetDecl);
+        }
+
+        // Net alias
+        Keyword(Kw::Alias) => {
+            return parse_net_alias(p).map(ItemData::NetAlias);
+        }
+
+        // Parameter override
+        Keyword(Kw::Defparam) => {
+            return parse_defparam(p).map(|x| ItemData::Defparam(x));
+        }
+
         // Genvar declaration
         Keyword(Kw::Genvar) => {
             p.bump();
@@ -1117,16 +1918,32 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
             return Ok(ItemData::Dummy);
         }
 
-        // Default clocking and disable declarations.
+        // Clocking blocks.
+        Keyword(Kw::Clocking) => {
+            return parse_clocking_block(p).map(ItemData::ClockingBlock);
+        }
+        Keyword(Kw::Global) if p.peek_kind(1) == Keyword(Kw::Clocking) => {
+            return parse_clocking_block(p).map(ItemData::ClockingBlock);
+        }
+        Keyword(Kw::Default) if p.peek_kind(1) == Keyword(Kw::Clocking) => {
+            // A `default clocking name;` refers to a clocking block declared
+            // elsewhere, whereas anything else declares a new one.
+            match (p.peek_kind(2), p.peek_kind(3)) {
+                (Ident(_), Semicolon) | (EscIdent(_), Semicolon) => {
+                    p.bump();
+                    p.bump();
+                    let name = parse_identifier_name(p, "clocking block name")?;
+                    p.require_reported(Semicolon)?;
+                    return Ok(ItemData::DefaultClocking(name));
+                }
+                _ => return parse_clocking_block(p).map(ItemData::ClockingBlock),
+            }
+        }
+
+        // Default disable declarations.
         Keyword(Kw::Default) => {
             p.bump();
             let mut span = p.last_span();
-            if p.try_eat(Keyword(Kw::Clocking)) {
-                let name = p.eat_ident("clocking identifier")?;
-                p.require_reported(Semicolon)?;
-                span.expand(p.last_span());
-                return Ok(ItemData::Dummy);
-            }
             if p.try_eat(Keyword(Kw::Disable)) {
                 p.require_reported(Keyword(Kw::Iff))?;
                 let expr = parse_expr(p)?;
@@ -1167,7 +1984,7 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
 
 fn parse_elab_system_task<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
     let mut span = p.peek(0).1;
-    let name = match p.peek(0).0 {
+    let name = match p.peek_kind(0) {
         SysIdent(name) => name,
         _ => unreachable!(),
     };
@@ -1177,120 +1994,25 @@ fn parse_elab_system_task<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<
     Ok(())
 }
 
-fn parse_localparam_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
-    p.require_reported(Keyword(Kw::Localparam))?;
-    // TODO: Parse data type or implicit type.
-
-    // Eat the list of parameter assignments.
-    loop {
-        // parameter_identifier { unpacked_dimension } [ = constant_param_expression ]
-        let (name, name_sp) = match p.eat_ident_or("parameter name") {
-            Ok(x) => x,
-            Err(e) => {
-                p.add_diag(e);
-                return Err(());
-            }
-        };
-
-        // TODO: Eat the unpacked dimensions.
-
-        // Eat the optional assignment.
-        if p.try_eat(Operator(Op::Assign)) {
-            match parse_expr(p) {
-                Ok(_) => (),
-                Err(_) => p.recover_balanced(&[Comma, Semicolon], false),
-            }
-        }
+/// Parse a modport declaration.
+///
+/// ```text
+/// modport_decl: "modport" modport_item {"," modport_item} ";"
+/// modport_item: ident "(" modport_ports_decl {"," modport_ports_decl} ")"
+/// modport_ports_decl:
+///   port_direction modport_simple_port {"," modport_simple_port} |
+///   ("import"|"export") modport_tf_port {"," modport_tf_port} |
+///   "clocking" ident
+/// modport_simple_port: ident | "." ident "(" [expr] ")"
+/// ```
+fn parse_modport_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::Modport<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Modport))?;
+    let names = comma_list_nonempty(p, Semicolon, "modport item", parse_modport_item)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
 
-        // Eat the trailing comma or semicolon.
-        match p.peek(0) {
-            (Comma, sp) => {
-                p.bump();
-
-                // A closing parenthesis indicates that the previous
-                // comma was superfluous. Report the issue but continue
-                // gracefully.
-                if p.peek(0).0 == Semicolon {
-                    // TODO: This should be an error in pedantic mode.
-                    p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
-                    break;
-                }
-            }
-            (Semicolon, _) => break,
-            (x, sp) => {
-                p.add_diag(
-                    DiagBuilder2::error(format!("expected , or ; after localparam, found {}", x))
-                        .span(sp),
-                );
-                return Err(());
-            }
-        }
-    }
-    p.require_reported(Semicolon)?;
-    Ok(())
-}
-
-fn parse_parameter_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
-    p.require_reported(Keyword(Kw::Parameter))?;
-
-    // Branch to try the explicit and implicit type version.
-    let mut pp = ParallelParser::new();
-    pp.add("explicit type", |p| {
-        let ty = parse_explicit_type(p)?;
-        Ok((ty, tail(p)?))
-    });
-    pp.add("implicit type", |p| {
-        let ty = parse_implicit_type(p)?;
-        Ok((ty, tail(p)?))
-    });
-    let (ty, ()) = pp.finish(p, "explicit or implicit type")?;
-
-    fn tail<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
-        let names = parse_parameter_names(p)?;
-        p.require_reported(Semicolon)?;
-        Ok(())
-    }
-
-    return Ok(());
-}
-
-fn parse_parameter_names<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<()>> {
-    let v = comma_list_nonempty(p, Semicolon, "parameter name", |p| {
-        // Consume the parameter name and optional dimensions.
-        let (name, name_sp) = p.eat_ident("parameter name")?;
-        let (dims, _) = parse_optional_dimensions(p)?;
-
-        // Parse the optional assignment.
-        let expr = if p.try_eat(Operator(Op::Assign)) {
-            Some(parse_expr(p)?)
-        } else {
-            None
-        };
-
-        Ok(())
-    })?;
-    Ok(v)
-}
-
-/// Parse a modport declaration.
-///
-/// ```text
-/// modport_decl: "modport" modport_item {"," modport_item} ";"
-/// modport_item: ident "(" modport_ports_decl {"," modport_ports_decl} ")"
-/// modport_ports_decl:
-///   port_direction modport_simple_port {"," modport_simple_port} |
-///   ("import"|"export") modport_tf_port {"," modport_tf_port} |
-///   "clocking" ident
-/// modport_simple_port: ident | "." ident "(" [expr] ")"
-/// ```
-fn parse_modport_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::Modport<'n>> {
-    let mut span = p.peek(0).1;
-    p.require_reported(Keyword(Kw::Modport))?;
-    let names = comma_list_nonempty(p, Semicolon, "modport item", parse_modport_item)?;
-    p.require_reported(Semicolon)?;
-    span.expand(p.last_span());
-
-    Ok(ast::Modport::new(span, ast::ModportData { names }))
+    Ok(ast::Modport::new(span, ast::ModportData { names }))
 
     // loop {
     //  parse_modport_item(p)?;
@@ -1364,7 +2086,7 @@ fn parse_modport_ports_decl<'n>(
 
     // Attempt to parse a simple port introduced by one of the port direction
     // keywords.
-    if let Some(dir) = as_port_direction(p.peek(0).0) {
+    if let Some(dir) = as_port_direction(p.peek_kind(0)) {
         let dir = Spanned::new(dir, p.peek(0).1);
         let mut port: Vec<&_> = vec![];
         p.bump();
@@ -1392,7 +2114,7 @@ fn parse_modport_ports_decl<'n>(
             // Otherwise, if the next item is a comma still, we continue
             // iteration. In all other cases, we assume the port declaration to
             // be done.
-            match (p.peek(0).0, p.peek(1).0) {
+            match (p.peek_kind(0), p.peek_kind(1)) {
                 (Comma, Keyword(_)) => break,
                 (Comma, _) => {
                     p.bump();
@@ -1478,7 +2200,7 @@ fn parse_explicit_type<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Typ
 }
 
 fn parse_type_suffix<'n>(p: &mut dyn AbstractParser<'n>, ty: Type<'n>) -> ReportedResult<Type<'n>> {
-    let tkn = p.peek(0).0;
+    let tkn = p.peek_kind(0);
     let sp = ty.span;
     match tkn {
         // Interfaces allow their internal modports and typedefs to be accessed
@@ -1553,7 +2275,7 @@ fn parse_type_signing_and_dimensions<'n>(
     let kind = TypeKind::new(span, kind);
 
     // Parse the optional sign information.
-    let sign = match p.peek(0).0 {
+    let sign = match p.peek_kind(0) {
         Keyword(Kw::Signed) => {
             p.bump();
             TypeSign::Signed
@@ -1675,8 +2397,22 @@ fn parse_type_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<TypeKin
         Keyword(Kw::Virtual) => {
             p.bump();
             p.try_eat(Keyword(Kw::Interface));
-            let (name, _) = p.eat_ident("virtual interface name")?;
-            Ok(ast::VirtIntfType(name))
+            let name = parse_identifier_name(p, "virtual interface name")?;
+            let params = if p.try_eat(Hashtag) {
+                parse_parameter_assignments(p)?
+            } else {
+                vec![]
+            };
+            let modport = if p.try_eat(Period) {
+                Some(parse_identifier_name(p, "modport name")?)
+            } else {
+                None
+            };
+            Ok(ast::VirtIntfType {
+                name,
+                params,
+                modport,
+            })
         }
 
         // type_reference ::= `type` `(` expression `)`
@@ -1702,7 +2438,7 @@ fn parse_enum_type<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<TypeKin
     p.bump();
 
     // Parse the optional enum base type.
-    let base = if p.peek(0).0 != OpenDelim(Brace) {
+    let base = if p.peek_kind(0) != OpenDelim(Brace) {
         Some(Box::new(parse_data_type(p)?))
     } else {
         None
@@ -1729,8 +2465,15 @@ fn parse_enum_name<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<EnumNam
     // Eat the name.
     let name = parse_identifier_name(p, "enum name")?;
 
-    // Parse the optional range.
-    let range = try_flanked(p, Brack, parse_expr)?;
+    // Parse the optional range, either `[N]` or `[N:M]`.
+    let range = try_flanked(p, Brack, |p| {
+        let lhs = parse_expr(p)?;
+        if p.try_eat(Colon) {
+            Ok(TypeDim::Range(lhs, parse_expr(p)?))
+        } else {
+            Ok(TypeDim::Expr(lhs))
+        }
+    })?;
 
     // Parse the optional value.
     let value = if p.try_eat(Operator(Op::Assign)) {
@@ -1747,7 +2490,7 @@ fn parse_struct_type<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<TypeK
     let mut span = p.peek(0).1;
 
     // Consume the "struct", "union", or "union tagged" keywords.
-    let kind = match (p.peek(0).0, p.peek(1).0) {
+    let kind = match (p.peek_kind(0), p.peek_kind(1)) {
         (Keyword(Kw::Struct), _) => {
             p.bump();
             StructKind::Struct
@@ -1777,7 +2520,7 @@ fn parse_struct_type<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<TypeK
         (false, TypeSign::None)
     };
 
-    if p.peek(0).0 == OpenDelim(Brace) {
+    if p.peek_kind(0) == OpenDelim(Brace) {
         // Parse the struct members.
         let members = flanked(p, Brace, |p| {
             repeat_until(p, CloseDelim(Brace), parse_struct_member)
@@ -1818,7 +2561,7 @@ fn parse_struct_member<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Str
     let mut span = p.peek(0).1;
 
     // Parse the optional random qualifier.
-    let rand_qualifier = match p.peek(0).0 {
+    let rand_qualifier = match p.peek_kind(0) {
         Keyword(Kw::Rand) => {
             p.bump();
             Some(RandomQualifier::Rand)
@@ -1850,7 +2593,7 @@ fn parse_struct_member<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Str
 }
 
 fn try_signing<'n>(p: &mut dyn AbstractParser<'n>) -> Option<TypeSign> {
-    match p.peek(0).0 {
+    match p.peek_kind(0) {
         Keyword(Kw::Signed) => {
             p.bump();
             Some(TypeSign::Signed)
@@ -1890,7 +2633,7 @@ fn try_dimension<'n>(
 ) -> ReportedResult<Option<(TypeDim<'n>, Span)>> {
     let mut span = Span::from(p.peek(0).1.begin());
     let dim = try_flanked(p, Brack, |p| {
-        Ok(match p.peek(0).0 {
+        Ok(match p.peek_kind(0) {
             // unsized_dimension ::= `[` `]`
             CloseDelim(Brack) => TypeDim::Unsized,
             // associative_dimension ::= `[` `*` `]`
@@ -1932,7 +2675,7 @@ fn try_dimension<'n>(
 fn parse_list_of_port_connections<'n>(
     p: &mut dyn AbstractParser<'n>,
 ) -> ReportedResult<Vec<PortConn<'n>>> {
-    comma_list(p, CloseDelim(Paren), "list of port connections", |p| {
+    let conns = comma_list(p, CloseDelim(Paren), "list of port connections", |p| {
         let mut span = p.peek(0).1;
 
         // A period introduces a named port connection. Otherwise this is an
@@ -1940,27 +2683,51 @@ fn parse_list_of_port_connections<'n>(
         let kind = if p.try_eat(Period) {
             if p.try_eat(Operator(Op::Mul)) {
                 // handle .* case
+                p.check_verilog("implicit port connection `.*`", span);
                 ast::PortConnData::Auto
             } else {
                 let name = parse_identifier_name(p, "port name")?;
                 // handle .name, .name(), and .name(expr) cases
                 let mode = try_flanked(p, Paren, |p| {
-                    Ok(if p.peek(0).0 != CloseDelim(Paren) {
-                        ast::PortConnMode::Connected(parse_expr(p)?)
-                    } else {
+                    Ok(if p.peek_kind(0) == CloseDelim(Paren) {
                         ast::PortConnMode::Unconnected
+                    } else if as_edge_ident(p.peek_kind(0)) != EdgeIdent::Implicit {
+                        ast::PortConnMode::Event(parse_port_event_expr(p)?)
+                    } else {
+                        ast::PortConnMode::Connected(parse_expr(p)?)
                     })
                 })?
-                .unwrap_or(ast::PortConnMode::Auto);
+                .unwrap_or_else(|| {
+                    p.check_verilog("implicit port connection", name.span);
+                    ast::PortConnMode::Auto
+                });
                 ast::PortConnData::Named(name, mode)
             }
+        } else if as_edge_ident(p.peek_kind(0)) != EdgeIdent::Implicit {
+            ast::PortConnData::PositionalEvent(parse_port_event_expr(p)?)
         } else {
             ast::PortConnData::Positional(parse_expr(p)?)
         };
 
         span.expand(p.last_span());
         Ok(ast::PortConn::new(span, kind))
-    })
+    })?;
+
+    // The wildcard connection `.*` may appear at most once.
+    let mut wildcards = conns
+        .iter()
+        .filter(|conn| conn.data == ast::PortConnData::Auto);
+    if let Some(first) = wildcards.next() {
+        for conn in wildcards {
+            p.add_diag(
+                DiagBuilder2::error("`.*` may appear only once in a list of port connections")
+                    .span(conn.span)
+                    .add_note("Previous `.*` was here:")
+                    .span(first.span),
+            );
+        }
+    }
+    Ok(conns)
 }
 
 /// Parse either an expression or a type. Prefers expressions over types.
@@ -2037,11 +2804,16 @@ fn parse_expr_prec<'n>(
     // type, followed by an apostrophe.
     // pattern_expr ::= type? pattern
     // cast ::= type `'` `(` expr `)`
-    {
+    //
+    // The type may contain expressions, such as the `[...]` in `x[y[0]]`,
+    // which try this branch again. Remember where it failed, such that nested
+    // expressions are not parsed an exponential number of times.
+    let position = p.position();
+    if !p.memo().no_cast.contains(&position) {
         let mut bp = BranchParser::new(p);
         let mut span = bp.peek(0).1;
         let ty = parse_explicit_type(&mut bp);
-        match (ty, bp.peek(0).0, bp.peek(1).0) {
+        match (ty, bp.peek_kind(0), bp.peek_kind(1)) {
             // type `'` `(` ...
             (Ok(ty), Apostrophe, OpenDelim(Paren)) => {
                 bp.commit();
@@ -2060,7 +2832,9 @@ fn parse_expr_prec<'n>(
                 let cast = Expr::new(span, CastExpr(ty, Box::new(expr)));
                 return parse_expr_suffix(p, cast, precedence);
             }
-            _ => (),
+            _ => {
+                p.memo().no_cast.insert(position);
+            }
         }
     }
 
@@ -2079,204 +2853,246 @@ fn parse_expr_prec<'n>(
     // Otherwise treat this as a normal expression.
     let q = p.peek(0).1;
     // p.add_diag(DiagBuilder2::note(format!("expr_suffix with precedence {:?}", precedence)).span(q));
-    let prefix = parse_expr_first(p, precedence)?;
+    let prefix = nested(p, |p| parse_expr_first(p, precedence))?;
     parse_expr_suffix(p, prefix, precedence)
 }
 
 fn parse_expr_suffix<'n>(
     p: &mut dyn AbstractParser<'n>,
-    prefix: Expr<'n>,
+    mut prefix: Expr<'n>,
     precedence: Precedence,
 ) -> ReportedResult<Expr<'n>> {
     // p.add_diag(DiagBuilder2::note(format!("expr_suffix with precedence {:?}", precedence)).span(prefix.span));
 
-    // Try to parse the index and call expressions.
-    let (tkn, sp) = p.peek(0);
-    match tkn {
-        // Index: "[" range_expression "]"
-        OpenDelim(Brack) if precedence <= Precedence::Postfix => {
-            p.bump();
-            let expr = match parse_range_expr(p) {
-                Ok(x) => x,
-                Err(e) => {
-                    p.recover_balanced(&[CloseDelim(Brack)], true);
-                    return Err(e);
-                }
-            };
-            p.require_reported(CloseDelim(Brack))?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                IndexExpr {
-                    indexee: Box::new(prefix),
-                    index: Box::new(expr),
-                },
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
+    // Each suffix becomes the prefix of the next one. Loop instead of
+    // recursing, such that long chains of operators do not exhaust the stack.
+    loop {
+        // Try to parse the index and call expressions.
+        let (tkn, sp) = p.peek(0);
+        match tkn {
+            // Index: "[" range_expression "]". A `[*`, `[->`, or `[=`
+            // starts a repetition instead.
+            OpenDelim(Brack)
+                if precedence <= Precedence::Postfix && !is_repetition_start(p.peek_kind(1)) =>
+            {
+                p.bump();
+                let expr = match parse_range_expr(p) {
+                    Ok(x) => x,
+                    Err(e) => {
+                        p.recover_balanced(&[CloseDelim(Brack)], true);
+                        return Err(e);
+                    }
+                };
+                p.require_reported(CloseDelim(Brack))?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    IndexExpr {
+                        indexee: Box::new(prefix),
+                        index: Box::new(expr),
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
 
-        // Call: "(" [list_of_arguments] ")"
-        OpenDelim(Paren) if precedence <= Precedence::Postfix => {
-            // Special treatment for system tasks with custom syntax. SV is a
-            // horrible language.
-            if let ast::SysIdentExpr(name) = prefix.data {
-                match try_builtin_system_task(p, name, prefix.span)? {
-                    Some(expr) => return parse_expr_suffix(p, expr, precedence),
-                    None => (),
+            // Call: "(" [list_of_arguments] ")"
+            OpenDelim(Paren) if precedence <= Precedence::Postfix => {
+                // Special treatment for system tasks with custom syntax. SV is a
+                // horrible language.
+                if let ast::SysIdentExpr(name) = prefix.data {
+                    match try_builtin_system_task(p, name, prefix.span)? {
+                        Some(expr) => {
+                            prefix = expr;
+                            continue;
+                        }
+                        None => (),
+                    }
                 }
+                let args = flanked(p, Paren, parse_call_args)?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    CallExpr(Box::new(prefix), args),
+                );
+                prefix = expr;
+                continue;
             }
-            let args = flanked(p, Paren, parse_call_args)?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                CallExpr(Box::new(prefix), args),
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
 
-        // expr "." ident
-        Period if precedence <= Precedence::Scope => {
-            p.bump();
-            let name = parse_identifier_name(p, "member name")?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                MemberExpr {
-                    expr: Box::new(prefix),
-                    name,
-                },
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
+            // Inline constraints of a randomize call:
+            // expr "with" ["(" [identifier_list] ")"] constraint_block
+            Keyword(Kw::With)
+                if precedence <= Precedence::Postfix && is_randomize_call(&prefix) =>
+            {
+                p.bump();
+                let names = try_flanked(p, Paren, |p| {
+                    comma_list(p, CloseDelim(Paren), "variable name", |p| {
+                        parse_identifier_name(p, "variable name")
+                    })
+                })?;
+                let constraints = flanked(p, Brace, |p| {
+                    repeat_until(p, CloseDelim(Brace), parse_constraint_item)
+                })?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    RandomizeWithExpr {
+                        call: Box::new(prefix),
+                        names,
+                        constraints,
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
 
-        // expr "::" ident
-        Namespace if precedence <= Precedence::Scope => {
-            p.bump();
-            let ident = parse_identifier_name(p, "scope name")?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                ScopeExpr(Box::new(prefix), ident),
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
+            // expr "." ident
+            // "super" "." "new"
+            Period if precedence <= Precedence::Scope => {
+                p.bump();
+                let name = match prefix.data {
+                    SuperExpr => parse_subroutine_name(p)?,
+                    _ => parse_identifier_name(p, "member name")?,
+                };
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    MemberExpr {
+                        expr: Box::new(prefix),
+                        name,
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
 
-        // expr "++"
-        Operator(Op::Inc) if precedence <= Precedence::Unary => {
-            p.bump();
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                UnaryExpr {
-                    op: Op::Inc,
-                    expr: Box::new(prefix),
-                    postfix: true,
-                },
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
+            // expr "::" ident
+            Namespace if precedence <= Precedence::Scope => {
+                p.bump();
+                let ident = parse_identifier_name(p, "scope name")?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    ScopeExpr(Box::new(prefix), ident),
+                );
+                prefix = expr;
+                continue;
+            }
 
-        // expr "--"
-        Operator(Op::Dec) if precedence <= Precedence::Unary => {
-            p.bump();
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                UnaryExpr {
-                    op: Op::Dec,
-                    expr: Box::new(prefix),
-                    postfix: true,
-                },
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
+            // expr "++"
+            Operator(Op::Inc) if precedence <= Precedence::Unary => {
+                p.bump();
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    UnaryExpr {
+                        op: Op::Inc,
+                        expr: Box::new(prefix),
+                        postfix: true,
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
 
-        // expr "?" expr ":" expr
-        Ternary if precedence < Precedence::Ternary => {
-            p.bump();
-            let true_expr = parse_expr_prec(p, Precedence::Ternary)?;
-            p.require_reported(Colon)?;
-            let false_expr = parse_expr_prec(p, Precedence::Ternary)?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                TernaryExpr {
-                    cond: Box::new(prefix),
-                    true_expr: Box::new(true_expr),
-                    false_expr: Box::new(false_expr),
-                },
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
-
-        // expr "inside" "{" open_range_list "}"
-        Keyword(Kw::Inside) if precedence <= Precedence::Relational => {
-            p.bump();
-            let set = flanked(p, Brace, |p| {
-                comma_list_nonempty(p, CloseDelim(Brace), "range", |p| {
-                    if p.peek(0).0 == OpenDelim(Brack) {
-                        p.require_reported(OpenDelim(Brack))?;
-                        let mut sp = p.last_span();
-                        let lo = parse_expr(p)?;
-                        p.require_reported(Colon)?;
-                        let hi = parse_expr(p)?;
-                        p.require_reported(CloseDelim(Brack))?;
-                        sp.expand(p.last_span());
-                        Ok(ValueRange::Range { lo, hi, span: sp })
-                    } else {
-                        Ok(ValueRange::Single(parse_expr(p)?))
-                    }
-                })
-            })?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                InsideExpr(Box::new(prefix), set),
-            );
-            return parse_expr_suffix(p, expr, precedence);
-        }
+            // expr "--"
+            Operator(Op::Dec) if precedence <= Precedence::Unary => {
+                p.bump();
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    UnaryExpr {
+                        op: Op::Dec,
+                        expr: Box::new(prefix),
+                        postfix: true,
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
 
-        // expr "'" "(" expr ")"
-        Apostrophe if precedence <= Precedence::Postfix => {
-            p.bump();
-            let inner = flanked(p, Paren, |p| parse_expr(p))?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                CastSizeExpr(Box::new(prefix), Box::new(inner)),
-            );
-            return parse_expr_suffix(p, expr, precedence);
+            // expr "?" expr ":" expr
+            //
+            // The operator is right-associative, such that `a ? b : c ? d : e`
+            // nests in the false branch. The true branch is delimited by the
+            // colon and may contain any expression, including another ternary.
+            Ternary if precedence < Precedence::Ternary => {
+                p.bump();
+                let true_expr = parse_expr_prec(p, Precedence::Min)?;
+                p.require_reported(Colon)?;
+                let false_expr = parse_expr_prec(p, Precedence::Implication)?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    TernaryExpr {
+                        cond: Box::new(prefix),
+                        true_expr: Box::new(true_expr),
+                        false_expr: Box::new(false_expr),
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
+
+            // expr "inside" "{" open_range_list "}"
+            Keyword(Kw::Inside) if precedence <= Precedence::Relational => {
+                p.bump();
+                let set = flanked(p, Brace, |p| {
+                    comma_list_nonempty(p, CloseDelim(Brace), "range", parse_value_range)
+                })?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    InsideExpr(Box::new(prefix), set),
+                );
+                prefix = expr;
+                continue;
+            }
+
+            // expr "'" "(" expr ")"
+            Apostrophe if precedence <= Precedence::Postfix => {
+                p.bump();
+                let inner = flanked(p, Paren, |p| parse_expr(p))?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    CastSizeExpr(Box::new(prefix), Box::new(inner)),
+                );
+                prefix = expr;
+                continue;
+            }
+            _ => (),
         }
-        _ => (),
-    }
 
-    // Try assign operators.
-    if let Some(op) = as_assign_operator(tkn) {
-        if precedence <= Precedence::Assignment {
-            p.bump();
-            let rhs = parse_expr_prec(p, Precedence::Assignment)?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                AssignExpr {
-                    op: op,
-                    lhs: Box::new(prefix),
-                    rhs: Box::new(rhs),
-                },
-            );
-            return parse_expr_suffix(p, expr, precedence);
+        // Try assign operators.
+        if let Some(op) = as_assign_operator(tkn) {
+            if precedence <= Precedence::Assignment {
+                p.bump();
+                let rhs = parse_expr_prec(p, Precedence::Assignment)?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    AssignExpr {
+                        op: op,
+                        lhs: Box::new(prefix),
+                        rhs: Box::new(rhs),
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
         }
-    }
 
-    // Try to parse binary operations.
-    if let Some(op) = as_binary_operator(tkn) {
-        let prec = op.get_precedence();
-        if precedence < prec {
-            p.bump();
-            let rhs = parse_expr_prec(p, prec)?;
-            let expr = Expr::new(
-                Span::union(prefix.span, p.last_span()),
-                BinaryExpr {
-                    op: op,
-                    lhs: Box::new(prefix),
-                    rhs: Box::new(rhs),
-                },
-            );
-            return parse_expr_suffix(p, expr, precedence);
+        // Try to parse binary operations.
+        if let Some(op) = as_binary_operator(tkn) {
+            let prec = op.get_precedence();
+            if precedence < prec {
+                p.bump();
+                let rhs = parse_expr_prec(p, prec)?;
+                let expr = Expr::new(
+                    Span::union(prefix.span, p.last_span()),
+                    BinaryExpr {
+                        op: op,
+                        lhs: Box::new(prefix),
+                        rhs: Box::new(rhs),
+                    },
+                );
+                prefix = expr;
+                continue;
+            }
         }
-    }
 
-    Ok(prefix)
+        return Ok(prefix);
+    }
 }
 
 fn parse_expr_first<'n>(
@@ -2323,7 +3139,7 @@ fn parse_expr_first<'n>(
     }
 
     // Try the unary operators next.
-    if let Some(op) = as_unary_operator(p.peek(0).0) {
+    if let Some(op) = as_unary_operator(p.peek_kind(0)) {
         p.bump();
         let expr = parse_expr_prec(p, Precedence::Unary)?;
         return Ok(Expr::new(
@@ -2365,6 +3181,12 @@ fn parse_primary_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Expr
             return Ok(Expr::new(sp, ThisExpr));
         }
 
+        // `super`
+        Keyword(Kw::Super) => {
+            p.bump();
+            return Ok(Expr::new(sp, SuperExpr));
+        }
+
         // `$`
         Dollar => {
             p.bump();
@@ -2500,7 +3322,7 @@ pub enum StreamDir {
 
 fn parse_concat_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ExprData<'n>> {
     // Streaming concatenations have a "<<" or ">>" following the opening "{".
-    let stream = match p.peek(0).0 {
+    let stream = match p.peek_kind(0) {
         Operator(Op::LogicShL) => Some(StreamDir::Out),
         Operator(Op::LogicShR) => Some(StreamDir::In),
         _ => None,
@@ -2512,7 +3334,7 @@ fn parse_concat_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ExprD
         // Parse the optional slice size. This can either be an expression or a
         // type. We prefer to parse things as expressions, and only if that does
         // not succeed do we switch to a type.
-        let slice_size = if p.peek(0).0 != OpenDelim(Brace) {
+        let slice_size = if p.peek_kind(0) != OpenDelim(Brace) {
             let mut pp = ParallelParser::new();
             pp.add_greedy("slice size expression", |p| {
                 let s = parse_expr(p).map(|e| StreamConcatSlice::Expr(Box::new(e)))?;
@@ -2581,7 +3403,7 @@ fn parse_concat_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ExprD
     let mut exprs = Vec::new();
     exprs.push(first_expr);
     while p.try_eat(Comma) {
-        if p.peek(0).0 == CloseDelim(Brace) {
+        if p.peek_kind(0) == CloseDelim(Brace) {
             let q = p.peek(0).1;
             p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(q));
             break;
@@ -2603,7 +3425,7 @@ fn parse_expr_list<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<Exp
         match p.peek(0) {
             (Comma, sp) => {
                 p.bump();
-                if p.peek(0).0 == CloseDelim(Brace) {
+                if p.peek_kind(0) == CloseDelim(Brace) {
                     p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
                     break;
                 }
@@ -2656,7 +3478,7 @@ fn parse_primary_parenthesis<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResu
 fn parse_range_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Expr<'n>> {
     let mut span = p.peek(0).1;
     let first_expr = parse_expr(p)?;
-    let mode = match p.peek(0).0 {
+    let mode = match p.peek_kind(0) {
         Colon => RangeMode::Absolute,
         AddColon => RangeMode::RelativeUp,
         SubColon => RangeMode::RelativeDown,
@@ -2743,6 +3565,32 @@ fn as_binary_operator(tkn: Token) -> Option<Op> {
     }
 }
 
+/// Check whether an expression is a call to `randomize`, as in `randomize(x)`,
+/// `obj.randomize()`, or `std::randomize(x)`, which may be followed by inline
+/// constraints.
+fn is_randomize_call(expr: &Expr) -> bool {
+    let callee = match expr.data {
+        CallExpr(ref callee, _) => callee.as_ref(),
+        _ => expr,
+    };
+    let name = match callee.data {
+        IdentExpr(name) => name,
+        MemberExpr { name, .. } => name,
+        ScopeExpr(_, name) => name,
+        _ => return false,
+    };
+    &*name.value.as_str() == "randomize"
+}
+
+/// Check whether a token following a `[` starts a repetition, as in `[*3]`,
+/// `[->2]`, or `[=1:4]`, rather than an index.
+fn is_repetition_start(tkn: Token) -> bool {
+    match tkn {
+        Operator(Op::Mul) | Operator(Op::LogicImpl) | Operator(Op::Assign) => true,
+        _ => false,
+    }
+}
+
 /// Convert a token to the corresponding AssignOp. Return `None` if the token
 /// does not map to an assignment operator.
 fn as_assign_operator(tkn: Token) -> Option<AssignOp> {
@@ -2785,7 +3633,7 @@ fn parse_port_list<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<Por
         match p.peek(0) {
             (Comma, sp) => {
                 p.bump();
-                if p.peek(0).0 == CloseDelim(Paren) {
+                if p.peek_kind(0) == CloseDelim(Paren) {
                     p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
                     break;
                 }
@@ -2814,14 +3662,14 @@ fn parse_port_list<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<Por
 //  // different kinds of port declarations.
 
 //  // Consume the optional port direction.
-//  let mut dir = as_port_direction(p.peek(0).0);
+//  let mut dir = as_port_direction(p.peek_kind(0));
 //  if dir.is_some() {
 //      p.bump();
 //  }
 
 //  // Consume the optional net type or var keyword, which determines the port
 //  // kind.
-//  let mut kind = match p.peek(0).0 {
+//  let mut kind = match p.peek_kind(0) {
 //      // Net Types
 //      Keyword(Kw::Supply0) => Some(PortKind::Net(NetType::Wire)),
 //      Keyword(Kw::Supply1) => Some(PortKind::Net(NetType::Wire)),
@@ -2987,6 +3835,7 @@ fn parse_interface_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<as
             name,
             dims,
             expr,
+            doc: doc_comment(span),
         },
     ))
 }
@@ -2999,7 +3848,7 @@ fn parse_explicit_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast
     let mut span = p.peek(0).1;
 
     // Consume the optional port direction.
-    let dir = as_port_direction(p.peek(0).0);
+    let dir = as_port_direction(p.peek_kind(0));
     if dir.is_some() {
         p.bump();
     }
@@ -3010,7 +3859,7 @@ fn parse_explicit_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast
 
     // Consume the port expression in parenthesis.
     let expr = flanked(p, Paren, |p| {
-        if p.peek(0).0 == CloseDelim(Paren) {
+        if p.peek_kind(0) == CloseDelim(Paren) {
             Ok(None)
         } else {
             Ok(Some(parse_expr(p)?))
@@ -3021,7 +3870,12 @@ fn parse_explicit_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast
     span.expand(p.last_span());
     Ok(ast::Port::new(
         span,
-        ast::PortData::Explicit { dir, name, expr },
+        ast::PortData::Explicit {
+            dir,
+            name,
+            expr,
+            doc: doc_comment(span),
+        },
     ))
 }
 
@@ -3033,7 +3887,7 @@ fn parse_named_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::P
     let mut span = p.peek(0).1;
 
     // Consume the optional port direction.
-    let dir = as_port_direction(p.peek(0).0);
+    let dir = as_port_direction(p.peek_kind(0));
     if dir.is_some() {
         p.bump();
     }
@@ -3041,7 +3895,7 @@ fn parse_named_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::P
     // Consume the optional port kind, which is either a specific net type, a
     // "var" keyword, or nothing at all.
     let kind = {
-        let tkn = p.peek(0).0;
+        let tkn = p.peek_kind(0);
         if let Some(ty) = as_net_type(tkn) {
             p.bump();
             Some(ast::VarKind::Net {
@@ -3098,6 +3952,7 @@ fn parse_named_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::P
             name,
             dims,
             expr,
+            doc: doc_comment(span),
         },
     ))
 }
@@ -3179,10 +4034,26 @@ fn parse_subroutine_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<S
     };
     let items = repeat_until(p, term, parse_subroutine_item)?;
 
-    // Consume the "endfunction" or "endtask" keywords.
+    // Consume the "endfunction" or "endtask" keywords, and the optional label
+    // after it, which must match the name of the subroutine.
     p.require_reported(term)?;
     if p.try_eat(Colon) {
-        p.eat_ident("function/task name")?;
+        p.check_verilog(&format!("label after `{}`", term), p.last_span());
+        let label = parse_subroutine_name(p)?;
+        if label.value != prototype.name.value {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "label `{}` does not match the name of {} `{}`",
+                    label.value,
+                    match prototype.kind {
+                        SubroutineKind::Func => "function",
+                        SubroutineKind::Task => "task",
+                    },
+                    prototype.name.value
+                ))
+                .span(label.span),
+            );
+        }
     }
     span.expand(p.last_span());
     Ok(SubroutineDecl::new(
@@ -3193,7 +4064,7 @@ fn parse_subroutine_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<S
 
 fn parse_subroutine_kind<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SubroutineKind> {
     let span = p.peek(0).1;
-    match p.peek(0).0 {
+    match p.peek_kind(0) {
         Keyword(Kw::Function) => {
             p.bump();
             Ok(SubroutineKind::Func)
@@ -3219,15 +4090,15 @@ fn parse_subroutine_prototype<'n>(
     let kind = parse_subroutine_kind(p)?;
 
     // Parse the optional lifetime specifier.
-    let lifetime = as_lifetime(p.peek(0).0);
+    let lifetime = as_lifetime(p.peek_kind(0));
     if lifetime.is_some() {
         p.bump();
     }
 
     // Parse the return type (if this is a function), the subroutine name, and
     // the optional argument list.
-    let (retty, (name, args)) = if kind == SubroutineKind::Func {
-        if p.peek(0).0 == Keyword(Kw::New) {
+    let (retty, (scope, name, args)) = if kind == SubroutineKind::Func {
+        if p.peek_kind(0) == Keyword(Kw::New) {
             (None, parse_subroutine_prototype_tail(p)?)
         } else {
             let mut pp = ParallelParser::new();
@@ -3251,6 +4122,7 @@ fn parse_subroutine_prototype<'n>(
         SubroutinePrototypeData {
             kind,
             lifetime,
+            scope,
             name,
             args,
             retty,
@@ -3260,94 +4132,130 @@ fn parse_subroutine_prototype<'n>(
 
 fn parse_subroutine_prototype_tail<'n>(
     p: &mut dyn AbstractParser<'n>,
-) -> ReportedResult<(Spanned<Name>, Vec<SubroutinePort<'n>>)> {
-    // Consume the subroutine name, or "new".
-    // TODO: Make this accept the full `[interface_identifier "." | class_scope] tf_identifier`.
-    let name = if p.try_eat(Keyword(Kw::New)) {
-        Spanned::new(get_name_table().intern("new", true), p.last_span())
+) -> ReportedResult<(Vec<Spanned<Name>>, Spanned<Name>, Vec<SubroutinePort<'n>>)> {
+    // Consume the subroutine name, or "new", and the optional interface or
+    // class scope that precedes it, as in `intf.name` or `pkg::cls::name`.
+    let mut scope = vec![];
+    let mut name = parse_subroutine_name(p)?;
+    if p.try_eat(Period) {
+        scope.push(name);
+        name = parse_identifier_name(p, "function or task name")?;
     } else {
-        parse_identifier_name(p, "function or task name")?
-    };
+        while p.try_eat(Namespace) {
+            scope.push(name);
+            name = parse_subroutine_name(p)?;
+        }
+    }
 
     // Consume the port list.
-    let args = try_flanked(p, Paren, |p| {
-        comma_list(p, CloseDelim(Paren), "subroutine port", |p| {
-            let mut span = p.peek(0).1;
+    let args = try_flanked(p, Paren, parse_subroutine_port_list)?.unwrap_or(Vec::new());
 
-            // Consume the optional port direction.
-            let dir = try_subroutine_port_dir(p);
+    // Wrap things up.
+    p.require_reported(Semicolon)?;
+    Ok((scope, name, args))
+}
 
-            // Consume the optional "var" keyword.
-            let var = p.try_eat(Keyword(Kw::Var));
+/// Parse the ports of a subroutine, up to but excluding the closing `)`.
+fn parse_subroutine_port_list<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Vec<SubroutinePort<'n>>> {
+    comma_list(
+        p,
+        CloseDelim(Paren),
+        "subroutine port",
+        parse_subroutine_port,
+    )
+}
 
-            // Branch to parse ports with explicit and implicit type.
-            let mut pp = ParallelParser::new();
-            pp.add("explicit type", |p| {
-                let ty = parse_explicit_type(p)?;
-                Ok((ty, tail(p)?))
-            });
-            pp.add("implicit type", |p| {
-                let ty = parse_implicit_type(p)?;
-                Ok((ty, tail(p)?))
-            });
-            let (ty, name) = pp.finish(p, "explicit or implicit type")?;
+/// Parse a single subroutine port.
+fn parse_subroutine_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SubroutinePort<'n>> {
+    let mut span = p.peek(0).1;
 
-            // The `tail` function handles everything that follows the data type. To
-            // ensure that the ports are parsed correctly, the function must fail if
-            // the port is not immediately followed by a "," or ")". Otherwise
-            // implicit and explicit types cannot be distinguished.
-            fn tail<'n>(
-                p: &mut dyn AbstractParser<'n>,
-            ) -> ReportedResult<Option<SubroutinePortName<'n>>> {
-                // Parse the optional port identifier.
-                let data = if let Some(name) = try_identifier_name(p)? {
-                    // Parse the optional dimensions.
-                    let (dims, _) = parse_optional_dimensions(p)?;
+    // Consume the optional port direction.
+    let dir = try_subroutine_port_dir(p);
 
-                    // Parse the optional initial assignment.
-                    let expr = if p.try_eat(Operator(Op::Assign)) {
-                        Some(parse_expr(p)?)
-                    } else {
-                        None
-                    };
+    // Consume the optional "var" keyword.
+    let var = p.try_eat(Keyword(Kw::Var));
 
-                    Some(SubroutinePortName {
-                        name: name,
-                        dims: dims,
-                        expr: expr,
-                    })
-                } else {
-                    None
-                };
+    // Branch to parse ports with explicit and implicit type. A lone
+    // identifier is the name of a port which inherits the direction
+    // and type of the preceding port, as in `input int a, b`, rather
+    // than the type of an unnamed port.
+    let lone_name = match (p.peek_kind(0), p.peek_kind(1)) {
+        (Ident(_), Comma) | (Ident(_), CloseDelim(Paren)) => true,
+        (EscIdent(_), Comma) | (EscIdent(_), CloseDelim(Paren)) => true,
+        _ => false,
+    };
+    let mut pp = ParallelParser::new();
+    if !lone_name {
+        pp.add("explicit type", |p| {
+            let ty = parse_explicit_type(p)?;
+            Ok((ty, tail(p)?))
+        });
+    }
+    pp.add("implicit type", |p| {
+        let ty = parse_implicit_type(p)?;
+        Ok((ty, tail(p)?))
+    });
+    let (ty, name) = pp.finish(p, "explicit or implicit type")?;
+
+    // The `tail` function handles everything that follows the data type. To
+    // ensure that the ports are parsed correctly, the function must fail if
+    // the port is not immediately followed by a "," or ")". Otherwise
+    // implicit and explicit types cannot be distinguished.
+    fn tail<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Option<SubroutinePortName<'n>>> {
+        // Parse the optional port identifier.
+        let data = if let Some(name) = try_identifier_name(p)? {
+            // Parse the optional dimensions.
+            let (dims, _) = parse_optional_dimensions(p)?;
+
+            // Parse the optional initial assignment.
+            let expr = if p.try_eat(Operator(Op::Assign)) {
+                Some(parse_expr(p)?)
+            } else {
+                None
+            };
 
-                // Ensure that we have consumed all tokens for this port.
-                match p.peek(0) {
-                    (Comma, _) | (CloseDelim(Paren), _) => Ok(data),
-                    (_, sp) => {
-                        p.add_diag(
-                            DiagBuilder2::error("expected , or ) after subroutine port").span(sp),
-                        );
-                        Err(())
-                    }
-                }
+            Some(SubroutinePortName {
+                name: name,
+                dims: dims,
+                expr: expr,
+            })
+        } else {
+            None
+        };
+
+        // Ensure that we have consumed all tokens for this port.
+        match p.peek(0) {
+            (Comma, _) | (CloseDelim(Paren), _) => Ok(data),
+            (_, sp) => {
+                p.add_diag(DiagBuilder2::error("expected , or ) after subroutine port").span(sp));
+                Err(())
             }
+        }
+    }
 
-            span.expand(p.last_span());
-            Ok(SubroutinePort::new(
-                span,
-                SubroutinePortData { dir, var, ty, name },
-            ))
-        })
-    })?
-    .unwrap_or(Vec::new());
+    span.expand(p.last_span());
+    Ok(SubroutinePort::new(
+        span,
+        SubroutinePortData { dir, var, ty, name },
+    ))
+}
 
-    // Wrap things up.
-    p.require_reported(Semicolon)?;
-    Ok((name, args))
+/// Parse the name of a function or task, or `new`.
+fn parse_subroutine_name<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Spanned<Name>> {
+    if p.try_eat(Keyword(Kw::New)) {
+        Ok(Spanned::new(
+            get_name_table().intern("new", true),
+            p.last_span(),
+        ))
+    } else {
+        parse_identifier_name(p, "function or task name")
+    }
 }
 
 fn try_subroutine_port_dir<'n>(p: &mut dyn AbstractParser<'n>) -> Option<SubroutinePortDir> {
-    match (p.peek(0).0, p.peek(1).0) {
+    match (p.peek_kind(0), p.peek_kind(1)) {
         (Keyword(Kw::Input), _) => {
             p.bump();
             Some(SubroutinePortDir::Input)
@@ -3432,7 +4340,7 @@ fn parse_stmt<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Stmt<'n>> {
     }
 
     // Consume the optional statement label.
-    let mut label = if p.is_ident() && p.peek(1).0 == Colon {
+    let mut label = if p.is_ident() && p.peek_kind(1) == Colon {
         let (n, _) = p.eat_ident("statement label")?;
         p.bump(); // eat the colon
         Some(n)
@@ -3441,7 +4349,7 @@ fn parse_stmt<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Stmt<'n>> {
     };
 
     // Parse the actual statement item.
-    let kind = parse_stmt_kind(p, &mut label)?;
+    let kind = nested(p, |p| parse_stmt_kind(p, &mut label))?;
     span.expand(p.last_span());
 
     Ok(Stmt::new(span, StmtData { label, kind }))
@@ -3513,6 +4421,7 @@ fn parse_stmt_kind<'n>(
         Keyword(Kw::If) | Keyword(Kw::Case) | Keyword(Kw::Casex) | Keyword(Kw::Casez) => {
             parse_if_or_case(p, None)?
         }
+        Keyword(Kw::Randcase) => parse_randcase(p)?,
 
         // Loops, as per IEEE 1800-2009 section 12.7.
         Keyword(Kw::Forever) => {
@@ -3547,9 +4456,13 @@ fn parse_stmt_kind<'n>(
             p.bump();
             let (init, cond, step) = flanked(p, Paren, |p| {
                 let init = Box::new(parse_stmt(p)?);
-                let cond = parse_expr(p)?;
+                let cond = if p.peek_kind(0) != Semicolon {
+                    Some(parse_expr(p)?)
+                } else {
+                    None
+                };
                 p.require_reported(Semicolon)?;
-                let step = parse_expr(p)?;
+                let step = comma_list(p, CloseDelim(Paren), "loop step", parse_expr)?;
                 Ok((init, cond, step))
             })?;
             let stmt = Box::new(parse_stmt(p)?);
@@ -3557,30 +4470,7 @@ fn parse_stmt_kind<'n>(
         }
         Keyword(Kw::Foreach) => {
             p.bump();
-            let (expr, vars) = flanked(p, Paren, |p| {
-                let expr = parse_expr_prec(p, Precedence::Scope)?;
-                let vars = flanked(p, Brack, |p| {
-                    Ok(comma_list(p, CloseDelim(Brack), "loop variables", |p| {
-                        Ok(if p.peek(0).0 != Comma {
-                            Some(parse_identifier_name(p, "loop variable name")?)
-                        } else {
-                            None
-                        })
-                    })?
-                    .into_iter()
-                    .enumerate()
-                    .flat_map(|(i, name)| {
-                        name.map(|name| {
-                            ast::ForeachIndex::new(
-                                name.span,
-                                ast::ForeachIndexData { index: i, name },
-                            )
-                        })
-                    })
-                    .collect())
-                })?;
-                Ok((expr, vars))
-            })?;
+            let (expr, vars) = parse_foreach_header(p)?;
             let stmt = Box::new(parse_stmt(p)?);
             ForeachStmt(expr, vars, stmt)
         }
@@ -3729,7 +4619,7 @@ fn parse_block<'n>(
     let terminator;
     'outer: loop {
         // Check if we have reached one of the terminators.
-        let tkn = p.peek(0).0;
+        let tkn = p.peek_kind(0);
         for term in terminators {
             if tkn == *term {
                 terminator = *term;
@@ -3743,7 +4633,7 @@ fn parse_block<'n>(
             Ok(x) => v.push(x),
             Err(()) => {
                 p.recover_balanced(terminators, false);
-                terminator = p.peek(0).0;
+                terminator = p.peek_kind(0);
                 p.bump();
                 break;
             }
@@ -3753,6 +4643,7 @@ fn parse_block<'n>(
     // Consume the optional block label after the terminator and verify that it
     // matches the label provided at the beginning of the block.
     if p.try_eat(Colon) {
+        p.check_verilog(&format!("label after `{}`", terminator), p.last_span());
         let (name, name_span) = p.eat_ident("block label")?;
         if let Some(before) = *label {
             if before != name {
@@ -3800,8 +4691,8 @@ fn parse_continuous_assign<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult
         }
     })?;
 
-    // Parse the optional delay control.
-    let delay_control = try_delay_control(p)?;
+    // Parse the optional delay.
+    let delay = try_delay3(p)?;
 
     // Parse the names and assignments.
     let assignments = comma_list_nonempty(p, Semicolon, "continuous assignment", parse_assignment)?;
@@ -3812,1008 +4703,2426 @@ fn parse_continuous_assign<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult
         span,
         ast::ContAssignData {
             strength,
-            delay: None,
-            delay_control,
+            delay,
             assignments,
         },
     ))
 }
 
-fn parse_if_or_case<'n>(
-    p: &mut dyn AbstractParser<'n>,
-    up: Option<UniquePriority>,
-) -> ReportedResult<StmtKind<'n>> {
-    let (tkn, span) = p.peek(0);
-    match tkn {
-        // Case statements
-        Keyword(Kw::Case) => {
-            p.bump();
-            parse_case(p, up, CaseKind::Normal)
-        }
-        Keyword(Kw::Casez) => {
-            p.bump();
-            parse_case(p, up, CaseKind::DontCareZ)
-        }
-        Keyword(Kw::Casex) => {
-            p.bump();
-            parse_case(p, up, CaseKind::DontCareXZ)
-        }
+/// Parse a clocking block.
+///
+/// ```text
+/// ["default" | "global"] "clocking" [ident] clocking_event ";"
+///     {clocking_item}
+/// "endclocking" [":" ident]
+/// ```
+fn parse_clocking_block<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClockingBlock<'n>> {
+    let mut span = p.peek(0).1;
+    let result = recovered(p, Keyword(Kw::Endclocking), |p| {
+        let default = p.try_eat(Keyword(Kw::Default));
+        let global = !default && p.try_eat(Keyword(Kw::Global));
+        p.require_reported(Keyword(Kw::Clocking))?;
+
+        // Parse the optional name and the clocking event.
+        let name = try_identifier_name(p)?;
+        p.require_reported(At)?;
+        let event = parse_event_expr(p, EventPrecedence::Max)?;
+        p.require_reported(Semicolon)?;
 
-        // If statement
-        Keyword(Kw::If) => {
-            p.bump();
-            parse_if(p, up)
-        }
+        // Parse the clocking items. Global clocking blocks have none.
+        let items = if global {
+            vec![]
+        } else {
+            repeat_until(p, Keyword(Kw::Endclocking), parse_clocking_item)?
+        };
+        Ok((default, global, name, event, items))
+    });
+    p.require_reported(Keyword(Kw::Endclocking))?;
+    let (default, global, name, event, items) = result?;
 
-        x => {
+    // Parse the optional name after "endclocking".
+    if p.try_eat(Colon) {
+        let n = parse_identifier_name(p, "clocking block name")?;
+        if Some(n.value) != name.map(|n| n.value) {
             p.add_diag(
-                DiagBuilder2::error(format!("expected case or if statement, got {:?}", x))
-                    .span(span),
+                DiagBuilder2::error(format!(
+                    "label `{}` does not match the name of the clocking block",
+                    n.value
+                ))
+                .span(n.span),
             );
-            Err(())
         }
     }
-}
 
-/// Parse a case statement as per IEEE 1800-2009 section 12.5.
-fn parse_case<'n>(
-    p: &mut dyn AbstractParser<'n>,
-    up: Option<UniquePriority>,
-    kind: CaseKind,
-) -> ReportedResult<StmtKind<'n>> {
-    let q = p.last_span();
+    span.expand(p.last_span());
+    Ok(ClockingBlock::new(
+        span,
+        ClockingBlockData {
+            default,
+            global,
+            name,
+            event,
+            items,
+        },
+    ))
+}
 
-    // Parse the case expression.
-    p.require_reported(OpenDelim(Paren))?;
-    let expr = match parse_expr(p) {
-        Ok(x) => x,
-        Err(()) => {
-            p.recover_balanced(&[CloseDelim(Paren)], true);
-            return Err(());
-        }
+/// Parse a let declaration as per IEEE 1800-2017 section 11.12.
+///
+/// ```text
+/// "let" ident ["(" [let_port {"," let_port}] ")"] "=" expr ";"
+/// ```
+fn parse_let_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<LetDecl<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Let))?;
+    let name = parse_identifier_name(p, "let name")?;
+    let ports = if p.peek_kind(0) == OpenDelim(Paren) {
+        flanked(p, Paren, |p| {
+            comma_list(p, CloseDelim(Paren), "let argument", parse_let_port)
+        })?
+    } else {
+        vec![]
     };
-    p.require_reported(CloseDelim(Paren))?;
+    p.require_reported(Operator(Op::Assign))?;
+    let expr = parse_expr(p)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(LetDecl::new(span, LetDeclData { name, ports, expr }))
+}
 
-    // The case expression may be followed by a "matches" or "inside" keyword
-    // which changes the kind of operation the statement performs.
-    let mode = match p.peek(0).0 {
-        Keyword(Kw::Inside) => {
-            p.bump();
-            CaseMode::Inside
-        }
-        Keyword(Kw::Matches) => {
-            p.bump();
-            CaseMode::Pattern
-        }
-        _ => CaseMode::Normal,
+/// Parse a formal argument of a let declaration.
+///
+/// ```text
+/// let_port ::= ["untyped" | data_type] ident {dimension} ["=" expr]
+/// ```
+fn parse_let_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<LetPort<'n>> {
+    let mut span = p.peek(0).1;
+    let tail =
+        |p: &mut dyn AbstractParser<'n>| parse_formal_arg_tail(p, "let argument", parse_expr);
+    let (ty, (name, dims, default)) = if p.try_eat(Keyword(Kw::Untyped)) {
+        (None, tail(p)?)
+    } else {
+        let mut pp = ParallelParser::new();
+        pp.add("typed argument", move |p| {
+            let ty = parse_explicit_type(p)?;
+            Ok((Some(ty), tail(p)?))
+        });
+        pp.add("untyped argument", move |p| Ok((None, tail(p)?)));
+        pp.finish(p, "let argument")?
     };
 
-    // Parse the case items.
-    let mut items = Vec::new();
-    while p.peek(0).0 != Keyword(Kw::Endcase) && p.peek(0).0 != Eof {
-        let mut span = p.peek(0).1;
-
-        // Handle the default case items.
-        if p.peek(0).0 == Keyword(Kw::Default) {
-            p.bump();
-            p.try_eat(Colon);
-            let stmt = Box::new(parse_stmt(p)?);
-            items.push(CaseItem::Default(stmt));
-        }
-        // Handle regular case items.
-        else {
-            let mut exprs = Vec::new();
-            loop {
-                if p.peek(0).0 == OpenDelim(Brack) {
-                    // TODO(fschuiki): Keep track of results
-                    // TODO(fschuiki): Error recovery
-                    p.require_reported(OpenDelim(Brack))?;
-                    parse_expr(p)?;
-                    p.require_reported(Colon)?;
-                    parse_expr(p)?;
-                    p.require_reported(CloseDelim(Brack))?;
-                } else {
-                    match parse_expr(p) {
-                        Ok(x) => exprs.push(x),
-                        Err(()) => {
-                            p.recover_balanced(&[Colon], false);
-                            break;
-                        }
-                    }
-                }
-
-                match p.peek(0) {
-                    (Comma, sp) => {
-                        p.bump();
-                        if p.try_eat(Colon) {
-                            p.add_diag(
-                                DiagBuilder2::warning("superfluous trailing comma").span(sp),
-                            );
-                            break;
-                        }
-                    }
-                    (Colon, _) => break,
-                    (_, sp) => {
-                        p.add_diag(
-                            DiagBuilder2::error("expected , or : after case expression").span(sp),
-                        );
-                        break;
-                    }
-                }
-            }
+    span.expand(p.last_span());
+    Ok(LetPort::new(
+        span,
+        LetPortData {
+            ty,
+            name,
+            dims,
+            default,
+        },
+    ))
+}
 
-            // Parse the statement.
-            p.require_reported(Colon)?;
-            let stmt = Box::new(parse_stmt(p)?);
-            items.push(CaseItem::Expr(exprs, stmt));
-        }
+/// Parse a port of a checker.
+///
+/// ```text
+/// checker_port ::= ["input" | "output"] checker_port_type ident {dimension} ["=" prop_expr]
+/// checker_port_type ::= "untyped" | "sequence" | "property" | data_type_or_implicit
+/// ```
+fn parse_checker_port<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<CheckerPort<'n>> {
+    let mut span = p.peek(0).1;
+    let dir = match p.peek_kind(0) {
+        Keyword(Kw::Input) => Some(PortDir::Input),
+        Keyword(Kw::Output) => Some(PortDir::Output),
+        _ => None,
+    };
+    if dir.is_some() {
+        p.bump();
     }
 
-    p.require_reported(Keyword(Kw::Endcase))?;
+    let tail =
+        |p: &mut dyn AbstractParser<'n>| parse_formal_arg_tail(p, "checker port", parse_propexpr);
+    let fixed_ty = match p.peek_kind(0) {
+        Keyword(Kw::Untyped) => Some(CheckerPortType::Untyped),
+        Keyword(Kw::Sequence) => Some(CheckerPortType::Sequence),
+        Keyword(Kw::Property) => Some(CheckerPortType::Property),
+        _ => None,
+    };
+    let (ty, (name, dims, default)) = if let Some(ty) = fixed_ty {
+        p.bump();
+        (ty, tail(p)?)
+    } else {
+        let mut pp = ParallelParser::new();
+        pp.add("explicit type", move |p| {
+            let ty = parse_explicit_type(p)?;
+            Ok((CheckerPortType::Data(ty), tail(p)?))
+        });
+        pp.add("implicit type", move |p| {
+            let ty = parse_implicit_type(p)?;
+            Ok((CheckerPortType::Data(ty), tail(p)?))
+        });
+        pp.finish(p, "checker port")?
+    };
 
-    Ok(CaseStmt {
-        up: up,
-        kind: kind,
-        expr: expr,
-        mode: mode,
-        items: items,
-    })
+    span.expand(p.last_span());
+    Ok(CheckerPort::new(
+        span,
+        CheckerPortData {
+            dir,
+            ty,
+            name,
+            dims,
+            default,
+        },
+    ))
 }
 
-fn parse_if<'n>(
+/// Parse everything that follows the type of a formal argument.
+///
+/// Fails if the argument is not followed by a "," or ")", such that typed and
+/// untyped arguments can be distinguished in a `ParallelParser`.
+///
+/// ```text
+/// ident {dimension} ["=" default]
+/// ```
+fn parse_formal_arg_tail<'n, D>(
     p: &mut dyn AbstractParser<'n>,
-    up: Option<UniquePriority>,
-) -> ReportedResult<StmtKind<'n>> {
-    // Parse the condition expression surrounded by parenthesis.
-    p.require_reported(OpenDelim(Paren))?;
-    let cond = match parse_expr(p) {
-        Ok(x) => x,
-        Err(()) => {
-            p.recover_balanced(&[CloseDelim(Paren)], true);
-            return Err(());
-        }
-    };
-    p.require_reported(CloseDelim(Paren))?;
-
-    // Parse the main statement.
-    let main_stmt = Box::new(parse_stmt(p)?);
-
-    // Parse the optional "else" branch.
-    let else_stmt = if p.peek(0).0 == Keyword(Kw::Else) {
-        p.bump();
-        Some(Box::new(parse_stmt(p)?))
+    desc: &str,
+    parse_default: fn(&mut dyn AbstractParser<'n>) -> ReportedResult<D>,
+) -> ReportedResult<(Spanned<Name>, Vec<TypeDim<'n>>, Option<D>)> {
+    let name = parse_identifier_name(p, "argument name")?;
+    let (dims, _) = parse_optional_dimensions(p)?;
+    let default = if p.try_eat(Operator(Op::Assign)) {
+        Some(parse_default(p)?)
     } else {
         None
     };
-
-    Ok(IfStmt {
-        up: up,
-        cond: cond,
-        main_stmt: main_stmt,
-        else_stmt: else_stmt,
-    })
+    match p.peek(0) {
+        (Comma, _) | (CloseDelim(Paren), _) => Ok((name, dims, default)),
+        (_, sp) => {
+            p.add_diag(DiagBuilder2::error(format!("expected , or ) after {}", desc)).span(sp));
+            Err(())
+        }
+    }
 }
 
-fn try_delay_control<'n>(
-    p: &mut dyn AbstractParser<'n>,
-) -> ReportedResult<Option<DelayControl<'n>>> {
-    // Try to consume the hashtag which introduces the delay control.
-    if !p.try_eat(Hashtag) {
-        return Ok(None);
+/// Parse an item in a clocking block.
+///
+/// ```text
+/// clocking_item ::= "default" default_skew ";"
+///     | clocking_direction clocking_decl_assign {"," clocking_decl_assign} ";"
+/// default_skew ::= "input" skew | "output" skew | "input" skew "output" skew
+/// clocking_direction ::= "input" [skew] | "output" [skew]
+///     | "input" [skew] "output" [skew] | "inout"
+/// clocking_decl_assign ::= ident ["=" expr]
+/// ```
+fn parse_clocking_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClockingItem<'n>> {
+    let mut span = p.peek(0).1;
+
+    // Parse the default skews.
+    if p.try_eat(Keyword(Kw::Default)) {
+        let input = if p.try_eat(Keyword(Kw::Input)) {
+            Some(parse_clocking_skew(p)?)
+        } else {
+            None
+        };
+        let output = if input.is_none() || p.peek_kind(0) == Keyword(Kw::Output) {
+            p.require_reported(Keyword(Kw::Output))?;
+            Some(parse_clocking_skew(p)?)
+        } else {
+            None
+        };
+        p.require_reported(Semicolon)?;
+        span.expand(p.last_span());
+        return Ok(ClockingItem {
+            span,
+            data: ClockingItemData::DefaultSkew { input, output },
+        });
     }
-    let mut span = p.last_span();
 
-    // Parse the delay value. This may either be a literal delay value,
-    // or a min-typ-max expression in parenthesis.
-    let (tkn, sp) = p.peek(0);
-    let expr = match tkn {
-        // Expression
-        OpenDelim(Paren) => {
+    // Parse the direction and optional skews of the clocking signals.
+    let dir = match p.peek(0) {
+        (Keyword(Kw::Inout), _) => {
             p.bump();
-            let e = parse_expr_prec(p, Precedence::MinTypMax)?;
-            p.require_reported(CloseDelim(Paren))?;
-            e
+            ClockingDir::Inout
         }
-
-        // Literals
-        Literal(Number(..)) | Literal(Time(..)) | Ident(..) => {
-            parse_expr_first(p, Precedence::Max)?
+        (Keyword(Kw::Input), _) => {
+            p.bump();
+            let input = try_clocking_skew(p)?;
+            if p.try_eat(Keyword(Kw::Output)) {
+                ClockingDir::InputOutput(input, try_clocking_skew(p)?)
+            } else {
+                ClockingDir::Input(input)
+            }
         }
-
-        // TODO: Parse "1step" keyword
-        _ => {
-            p.add_diag(DiagBuilder2::error("expected delay value or expression after #").span(sp));
+        (Keyword(Kw::Output), _) => {
+            p.bump();
+            ClockingDir::Output(try_clocking_skew(p)?)
+        }
+        (tkn, sp) => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected `default`, `input`, `output`, or `inout` in clocking block, found \
+                     `{}` instead",
+                    tkn
+                ))
+                .span(sp),
+            );
             return Err(());
         }
     };
-    span.expand(p.last_span());
 
-    Ok(Some(DelayControl {
-        span: span,
-        expr: expr,
-    }))
+    // Parse the signals, each with an optional hierarchical expression.
+    let signals = comma_list_nonempty(p, Semicolon, "clocking signal", |p| {
+        let name = parse_identifier_name(p, "signal name")?;
+        let expr = if p.try_eat(Operator(Op::Assign)) {
+            Some(parse_expr(p)?)
+        } else {
+            None
+        };
+        Ok((name, expr))
+    })?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(ClockingItem {
+        span,
+        data: ClockingItemData::Signals { dir, signals },
+    })
 }
 
-/// Try to parse an event control as described in IEEE 1800-2009 section 9.4.2.
-fn try_event_control<'n>(
-    p: &mut dyn AbstractParser<'n>,
-) -> ReportedResult<Option<EventControl<'n>>> {
-    if !p.try_eat(At) {
-        return Ok(None);
+/// Parse a clocking skew, as in `#1step`, `negedge`, or `posedge #2`.
+fn parse_clocking_skew<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClockingSkew<'n>> {
+    match try_clocking_skew(p)? {
+        Some(skew) => Ok(skew),
+        None => {
+            let (tkn, sp) = p.peek(0);
+            p.add_diag(
+                DiagBuilder2::error(format!("expected clocking skew, found `{}` instead", tkn))
+                    .span(sp),
+            );
+            Err(())
+        }
     }
-    let mut span = p.last_span();
+}
 
-    // @*
-    if p.peek(0).0 == Operator(Op::Mul) {
+fn try_clocking_skew<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Option<ClockingSkew<'n>>> {
+    let mut span = p.peek(0).1;
+    let edge = as_edge_ident(p.peek_kind(0));
+    if edge != EdgeIdent::Implicit {
         p.bump();
-        span.expand(p.last_span());
-        return Ok(Some(EventControl {
-            span: span,
-            data: EventControlData::Implicit,
-        }));
     }
-
-    // @(*)
-    if p.peek(0).0 == OpenDelim(Paren)
-        && p.peek(1).0 == Operator(Op::Mul)
-        && p.peek(2).0 == CloseDelim(Paren)
-    {
-        p.bump();
-        p.bump();
-        p.bump();
-        span.expand(p.last_span());
-        return Ok(Some(EventControl {
-            span: span,
-            data: EventControlData::Implicit,
-        }));
+    let delay = try_delay_control(p)?;
+    if edge == EdgeIdent::Implicit && delay.is_none() {
+        return Ok(None);
     }
-
-    let expr = parse_event_expr(p, EventPrecedence::Max)?;
     span.expand(p.last_span());
-
-    Ok(Some(EventControl {
-        span: span,
-        data: EventControlData::Expr(expr),
-    }))
+    Ok(Some(ClockingSkew { span, edge, delay }))
 }
 
-fn try_cycle_delay<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Option<CycleDelay>> {
-    if !p.try_eat(DoubleHashtag) {
-        return Ok(None);
+/// Parse a net alias statement as per IEEE 1800-2017 section 10.11.
+///
+/// ```text
+/// "alias" net_lvalue "=" net_lvalue {"=" net_lvalue} ";"
+/// ```
+fn parse_net_alias<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<NetAlias<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Alias))?;
+    let mut nets = vec![parse_expr_prec(p, Precedence::Postfix)?];
+    p.require_reported(Operator(Op::Assign))?;
+    nets.push(parse_expr_prec(p, Precedence::Postfix)?);
+    while p.try_eat(Operator(Op::Assign)) {
+        nets.push(parse_expr_prec(p, Precedence::Postfix)?);
     }
-
-    let q = p.last_span();
-    p.add_diag(DiagBuilder2::error("Don't know how to parse cycle delay").span(q));
-    Err(())
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(NetAlias::new(span, NetAliasData { nets }))
 }
 
-fn parse_assignment<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<(Expr<'n>, Expr<'n>)> {
-    let lhs = parse_expr_prec(p, Precedence::Postfix)?;
-    p.require_reported(Operator(Op::Assign))?;
-    let rhs = parse_expr_prec(p, Precedence::Assignment)?;
-    Ok((lhs, rhs))
+fn parse_defparam<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Defparam<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Defparam))?;
+    let assignments = comma_list_nonempty(p, Semicolon, "parameter assignment", parse_assignment)?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(Defparam::new(span, DefparamData { assignments }))
 }
 
-fn parse_assign_stmt<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<StmtKind<'n>> {
-    // Parse the leading expression.
-    let expr = parse_expr_prec(p, Precedence::Postfix)?;
-    let (tkn, sp) = p.peek(0);
-
-    // Handle blocking assignments (IEEE 1800-2009 section 10.4.1), where the
-    // expression is followed by an assignment operator.
-    if let Some(op) = as_assign_operator(tkn) {
-        p.bump();
-        let rhs = parse_expr(p)?;
-        p.require_reported(Semicolon)?;
-        return Ok(BlockingAssignStmt {
-            lhs: expr,
-            rhs: rhs,
-            op: op,
-        });
-    }
-
-    // Handle non-blocking assignments (IEEE 1800-2009 section 10.4.2).
-    if tkn == Operator(Op::Leq) {
-        p.bump();
-
-        // Parse the optional delay and event control.
-        let delay_control = try_delay_control(p)?;
-        let event_control = /*try_event_control(p)?*/ None;
+fn parse_if_or_case<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    up: Option<UniquePriority>,
+) -> ReportedResult<StmtKind<'n>> {
+    let (tkn, span) = p.peek(0);
+    match tkn {
+        // Case statements
+        Keyword(Kw::Case) => {
+            p.bump();
+            parse_case(p, up, CaseKind::Normal)
+        }
+        Keyword(Kw::Casez) => {
+            p.bump();
+            parse_case(p, up, CaseKind::DontCareZ)
+        }
+        Keyword(Kw::Casex) => {
+            p.bump();
+            parse_case(p, up, CaseKind::DontCareXZ)
+        }
 
-        // Parse the right-hand side of the assignment.
-        let rhs = parse_expr(p)?;
-        p.require_reported(Semicolon)?;
+        // If statement
+        Keyword(Kw::If) => {
+            p.bump();
+            parse_if(p, up)
+        }
 
-        return Ok(NonblockingAssignStmt {
-            lhs: expr,
-            rhs: rhs,
-            delay: delay_control,
-            event: event_control,
-        });
+        x => {
+            p.add_diag(
+                DiagBuilder2::error(format!("expected case or if statement, got {:?}", x))
+                    .span(span),
+            );
+            Err(())
+        }
     }
-
-    p.add_diag(DiagBuilder2::error("expected blocking or non-blocking assign statement").span(sp));
-    Err(())
 }
 
-fn parse_expr_stmt<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<StmtKind<'n>> {
-    let expr = parse_expr_prec(p, Precedence::Unary)?;
-    p.require_reported(Semicolon)?;
-    Ok(ExprStmt(expr))
+/// Parse the array and loop variables of a `foreach` statement or constraint.
+///
+/// ```text
+/// "(" array_identifier "[" [ident] {"," [ident]} "]" ")"
+/// ```
+fn parse_foreach_header<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<(Expr<'n>, Vec<ForeachIndex<'n>>)> {
+    flanked(p, Paren, |p| {
+        let expr = parse_expr_prec(p, Precedence::Scope)?;
+        let vars = flanked(p, Brack, |p| {
+            Ok(comma_list(p, CloseDelim(Brack), "loop variables", |p| {
+                Ok(if p.peek_kind(0) != Comma {
+                    Some(parse_identifier_name(p, "loop variable name")?)
+                } else {
+                    None
+                })
+            })?
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, name)| {
+                name.map(|name| {
+                    ast::ForeachIndex::new(name.span, ast::ForeachIndexData { index: i, name })
+                })
+            })
+            .collect())
+        })?;
+        Ok((expr, vars))
+    })
 }
 
-fn parse_event_expr<'n>(
-    p: &mut dyn AbstractParser<'n>,
-    precedence: EventPrecedence,
-) -> ReportedResult<EventExpr<'n>> {
-    let mut span = p.peek(0).1;
+/// Parse a value range, as in the set of an `inside` expression or the items
+/// of a `case inside` statement.
+///
+/// ```text
+/// value_range ::= expression | "[" expression ":" expression "]"
+/// ```
+fn parse_value_range<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ValueRange<'n>> {
+    if p.peek_kind(0) == OpenDelim(Brack) {
+        p.require_reported(OpenDelim(Brack))?;
+        let mut sp = p.last_span();
+        let lo = parse_expr(p)?;
+        p.require_reported(Colon)?;
+        let hi = parse_expr(p)?;
+        p.require_reported(CloseDelim(Brack))?;
+        sp.expand(p.last_span());
+        Ok(ValueRange::Range { lo, hi, span: sp })
+    } else {
+        Ok(ValueRange::Single(parse_expr(p)?))
+    }
+}
 
-    // Try parsing an event expression in parentheses.
-    if p.try_eat(OpenDelim(Paren)) {
-        return match parse_event_expr(p, EventPrecedence::Min) {
-            Ok(x) => {
-                p.require_reported(CloseDelim(Paren))?;
-                parse_event_expr_suffix(p, x, precedence)
-            }
-            Err(()) => {
-                p.recover_balanced(&[CloseDelim(Paren)], true);
-                Err(())
-            }
-        };
-    }
-
-    // Consume the optional edge identifier.
-    let edge = as_edge_ident(p.peek(0).0);
-    if edge != EdgeIdent::Implicit {
-        p.bump();
+/// Parse a randcase statement as per IEEE 1800-2009 section 18.16.
+///
+/// ```text
+/// "randcase" randcase_item {randcase_item} "endcase"
+/// ```
+fn parse_randcase<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<StmtKind<'n>> {
+    p.require_reported(Keyword(Kw::Randcase))?;
+    let q = p.last_span();
+    let items = repeat_until(p, Keyword(Kw::Endcase), |p| {
+        let weight = parse_expr(p)?;
+        p.require_reported(Colon)?;
+        let stmt = Box::new(parse_stmt(p)?);
+        Ok(RandcaseItem { weight, stmt })
+    })?;
+    p.require_reported(Keyword(Kw::Endcase))?;
+    if items.is_empty() {
+        p.add_diag(
+            DiagBuilder2::error("randcase statement must have at least one item")
+                .span(Span::union(q, p.last_span())),
+        );
     }
+    Ok(RandcaseStmt(items))
+}
 
-    // Parse the value.
-    let value = parse_expr(p)?;
-    span.expand(p.last_span());
+/// Parse a case statement as per IEEE 1800-2009 section 12.5.
+fn parse_case<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    up: Option<UniquePriority>,
+    kind: CaseKind,
+) -> ReportedResult<StmtKind<'n>> {
+    let q = p.last_span();
 
-    let expr = EventExpr::Edge {
-        span: span,
-        edge: edge,
-        value: value,
+    // Parse the case expression.
+    p.require_reported(OpenDelim(Paren))?;
+    let expr = match parse_expr(p) {
+        Ok(x) => x,
+        Err(()) => {
+            p.recover_balanced(&[CloseDelim(Paren)], true);
+            return Err(());
+        }
     };
-    parse_event_expr_suffix(p, expr, precedence)
-
-    // p.add_diag(DiagBuilder2::error("expected event expression").span(span));
-    // Err(())
-}
+    p.require_reported(CloseDelim(Paren))?;
 
-fn parse_event_expr_suffix<'n>(
-    p: &mut dyn AbstractParser<'n>,
-    expr: EventExpr<'n>,
-    precedence: EventPrecedence,
-) -> ReportedResult<EventExpr<'n>> {
-    match p.peek(0).0 {
-        // event_expr "iff" expr
-        Keyword(Kw::Iff) if precedence < EventPrecedence::Iff => {
+    // The case expression may be followed by a "matches" or "inside" keyword
+    // which changes the kind of operation the statement performs.
+    let mode = match p.peek_kind(0) {
+        Keyword(Kw::Inside) => {
             p.bump();
-            let cond = parse_expr(p)?;
-            Ok(EventExpr::Iff {
-                span: Span::union(expr.span(), cond.span),
-                expr: Box::new(expr),
-                cond: cond,
-            })
+            CaseMode::Inside
         }
-        // event_expr "or" event_expr
-        // event_expr "," event_expr
-        Keyword(Kw::Or) | Comma if precedence <= EventPrecedence::Or => {
+        Keyword(Kw::Matches) => {
             p.bump();
-            let rhs = parse_event_expr(p, EventPrecedence::Or)?;
-            Ok(EventExpr::Or {
-                span: Span::union(expr.span(), rhs.span()),
-                lhs: Box::new(expr),
-                rhs: Box::new(rhs),
-            })
+            CaseMode::Pattern
         }
-        _ => Ok(expr),
-    }
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum EventPrecedence {
-    Min,
-    Or,
-    Iff,
-    Max,
-}
-
-fn as_edge_ident(tkn: Token) -> EdgeIdent {
-    match tkn {
-        Keyword(Kw::Edge) => EdgeIdent::Edge,
-        Keyword(Kw::Posedge) => EdgeIdent::Posedge,
-        Keyword(Kw::Negedge) => EdgeIdent::Negedge,
-        _ => EdgeIdent::Implicit,
-    }
-}
+        _ => CaseMode::Normal,
+    };
 
-fn parse_call_args<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<CallArg<'n>>> {
-    let mut v = Vec::new();
-    if p.peek(0).0 == CloseDelim(Paren) {
-        return Ok(v);
-    }
-    loop {
-        match p.peek(0) {
-            (Comma, sp) => v.push(CallArg {
-                span: sp,
-                name_span: sp,
-                name: None,
-                expr: None,
-            }),
-            (Period, mut sp) => {
-                p.bump();
-                let (name, mut name_sp) = p.eat_ident("argument name")?;
-                name_sp.expand(sp);
-                let expr = flanked(p, Paren, |p| {
-                    Ok(if p.peek(0).0 == CloseDelim(Paren) {
-                        None
-                    } else {
-                        Some(parse_expr(p)?)
-                    })
-                })?;
-                sp.expand(p.last_span());
+    // Parse the case items.
+    let mut items = Vec::new();
+    while p.peek_kind(0) != Keyword(Kw::Endcase) && p.peek_kind(0) != Eof {
+        let mut span = p.peek(0).1;
 
-                v.push(CallArg {
-                    span: sp,
-                    name_span: name_sp,
-                    name: Some(name),
-                    expr: expr,
-                });
-            }
-            (_, mut sp) => {
-                let expr = parse_expr(p)?;
-                sp.expand(p.last_span());
-                v.push(CallArg {
-                    span: sp,
-                    name_span: sp,
-                    name: None,
-                    expr: Some(expr),
-                });
-            }
+        // Handle the default case items.
+        if p.peek_kind(0) == Keyword(Kw::Default) {
+            p.bump();
+            p.try_eat(Colon);
+            let stmt = Box::new(parse_stmt(p)?);
+            items.push(CaseItem::Default(stmt));
         }
+        // Handle regular case items.
+        else {
+            let mut exprs = Vec::new();
+            loop {
+                // The items of a `case matches` are patterns, optionally
+                // followed by further conditions joined with `&&&`.
+                let item = if mode == CaseMode::Pattern {
+                    parse_cond_predicate(p).map(ValueRange::Single)
+                } else {
+                    parse_value_range(p)
+                };
+                match item {
+                    // Only the items of a `case inside` may be ranges.
+                    Ok(ValueRange::Range { span, .. }) if mode != CaseMode::Inside => {
+                        p.add_diag(
+                            DiagBuilder2::error(
+                                "value ranges are only allowed in `case inside` items",
+                            )
+                            .span(span),
+                        );
+                    }
+                    Ok(x) => exprs.push(x),
+                    Err(()) => {
+                        p.recover_balanced(&[Colon], false);
+                        break;
+                    }
+                }
 
-        match p.peek(0) {
-            (Comma, sp) => {
-                p.bump();
-                if p.try_eat(CloseDelim(Paren)) {
-                    p.add_diag(DiagBuilder2::warning("superfluous trailing comma").span(sp));
-                    break;
+                match p.peek(0) {
+                    (Comma, sp) => {
+                        p.bump();
+                        if p.try_eat(Colon) {
+                            p.add_diag(
+                                DiagBuilder2::warning("superfluous trailing comma").span(sp),
+                            );
+                            break;
+                        }
+                    }
+                    (Colon, _) => break,
+                    (_, sp) => {
+                        p.add_diag(
+                            DiagBuilder2::error("expected , or : after case expression").span(sp),
+                        );
+                        break;
+                    }
                 }
             }
-            (CloseDelim(Paren), _) => break,
-            (_, sp) => {
-                p.add_diag(DiagBuilder2::error("expected , or ) after call argument").span(sp));
-                return Err(());
-            }
+
+            // Parse the statement.
+            p.require_reported(Colon)?;
+            let stmt = Box::new(parse_stmt(p)?);
+            items.push(CaseItem::Expr(exprs, stmt));
         }
     }
-    Ok(v)
+
+    p.require_reported(Keyword(Kw::Endcase))?;
+
+    Ok(CaseStmt {
+        up: up,
+        kind: kind,
+        expr: expr,
+        mode: mode,
+        items: items,
+    })
 }
 
-fn parse_variable_decl_assignment<'n>(
+fn parse_if<'n>(
     p: &mut dyn AbstractParser<'n>,
-) -> ReportedResult<VarDeclName<'n>> {
-    let mut span = p.peek(0).1;
-
-    // Parse the variable name.
-    let (name, name_span) = p.eat_ident("variable name")?;
+    up: Option<UniquePriority>,
+) -> ReportedResult<StmtKind<'n>> {
+    // Parse the condition expression surrounded by parenthesis.
+    p.require_reported(OpenDelim(Paren))?;
+    let cond = match parse_cond_predicate(p) {
+        Ok(x) => x,
+        Err(()) => {
+            p.recover_balanced(&[CloseDelim(Paren)], true);
+            return Err(());
+        }
+    };
+    p.require_reported(CloseDelim(Paren))?;
 
-    // Parse the optional dimensions.
-    let (dims, _) = parse_optional_dimensions(p)?;
+    // Parse the main statement.
+    let main_stmt = Box::new(parse_stmt(p)?);
 
-    // Parse the optional initial expression.
-    let init = if p.try_eat(Operator(Op::Assign)) {
-        Some(parse_expr(p)?)
+    // Parse the optional "else" branch.
+    let else_stmt = if p.peek_kind(0) == Keyword(Kw::Else) {
+        p.bump();
+        Some(Box::new(parse_stmt(p)?))
     } else {
         None
     };
-    span.expand(p.last_span());
 
-    Ok(VarDeclName::new(
-        span,
-        VarDeclNameData {
-            name: name,
-            name_span: name_span,
-            dims: dims,
-            init: init,
-        },
-    ))
+    Ok(IfStmt {
+        up: up,
+        cond: cond,
+        main_stmt: main_stmt,
+        else_stmt: else_stmt,
+    })
+}
+
+/// Parse a conditional predicate, as in the condition of an if statement or
+/// the items of a `case matches` statement. The `&&&` operator has the lowest
+/// precedence and may only appear here.
+///
+/// ```text
+/// cond_predicate ::= expression_or_cond_pattern { "&&&" expression_or_cond_pattern }
+/// expression_or_cond_pattern ::= expression | expression "matches" pattern
+/// ```
+fn parse_cond_predicate<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Expr<'n>> {
+    let mut expr = parse_expr_or_cond_pattern(p)?;
+    while p.try_eat(Operator(Op::TripleAnd)) {
+        let rhs = parse_expr_or_cond_pattern(p)?;
+        expr = Expr::new(
+            Span::union(expr.span, rhs.span),
+            BinaryExpr {
+                op: Op::TripleAnd,
+                lhs: Box::new(expr),
+                rhs: Box::new(rhs),
+            },
+        );
+    }
+    Ok(expr)
+}
+
+fn parse_expr_or_cond_pattern<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Expr<'n>> {
+    let expr = parse_expr(p)?;
+    if p.try_eat(Keyword(Kw::Matches)) {
+        let pattern = parse_expr(p)?;
+        Ok(Expr::new(
+            Span::union(expr.span, pattern.span),
+            MatchesExpr(Box::new(expr), Box::new(pattern)),
+        ))
+    } else {
+        Ok(expr)
+    }
+}
+
+fn try_delay_control<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Option<DelayControl<'n>>> {
+    // Try to consume the hashtag which introduces the delay control.
+    if !p.try_eat(Hashtag) {
+        return Ok(None);
+    }
+    let mut span = p.last_span();
+
+    // Parse the delay value. This may either be a literal delay value,
+    // or a min-typ-max expression in parenthesis.
+    let (tkn, sp) = p.peek(0);
+    let expr = match tkn {
+        // Expression
+        OpenDelim(Paren) => {
+            p.bump();
+            let e = parse_primary_parenthesis(p)?;
+            p.require_reported(CloseDelim(Paren))?;
+            e
+        }
+
+        // Literals
+        Literal(Number(..)) | Literal(Time(..)) | Ident(..) => {
+            parse_expr_first(p, Precedence::Max)?
+        }
+
+        // TODO: Parse "1step" keyword
+        _ => {
+            p.add_diag(DiagBuilder2::error("expected delay value or expression after #").span(sp));
+            return Err(());
+        }
+    };
+    span.expand(p.last_span());
+
+    Ok(Some(DelayControl {
+        span: span,
+        expr: expr,
+    }))
+}
+
+/// Try to parse a delay with up to three values, as it may appear on nets and
+/// continuous assignments.
+fn try_delay3<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Option<Delay3<'n>>> {
+    // Single delay values are handled like regular delay controls.
+    if p.peek_kind(0) != Hashtag || p.peek_kind(1) != OpenDelim(Paren) {
+        return Ok(try_delay_control(p)?.map(|dc| Delay3 {
+            span: dc.span,
+            rise: dc.expr,
+            fall: None,
+            turnoff: None,
+        }));
+    }
+    p.require_reported(Hashtag)?;
+    let mut span = p.last_span();
+
+    // Parse the rise, fall, and turn-off delays.
+    let exprs = flanked(p, Paren, |p| {
+        comma_list_nonempty(
+            p,
+            CloseDelim(Paren),
+            "delay value",
+            parse_primary_parenthesis,
+        )
+    })?;
+    if exprs.len() > 3 {
+        p.add_diag(
+            DiagBuilder2::error("expected at most three delay values (rise, fall, turn-off)")
+                .span(exprs[3].span),
+        );
+        return Err(());
+    }
+    span.expand(p.last_span());
+
+    let mut exprs = exprs.into_iter();
+    Ok(Some(Delay3 {
+        span,
+        rise: exprs.next().unwrap(),
+        fall: exprs.next(),
+        turnoff: exprs.next(),
+    }))
+}
+
+/// Try to parse an event control as described in IEEE 1800-2009 section 9.4.2.
+fn try_event_control<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Option<EventControl<'n>>> {
+    if !p.try_eat(At) {
+        return Ok(None);
+    }
+    let mut span = p.last_span();
+
+    // @*
+    if p.peek_kind(0) == Operator(Op::Mul) {
+        p.bump();
+        span.expand(p.last_span());
+        return Ok(Some(EventControl {
+            span: span,
+            data: EventControlData::Implicit,
+        }));
+    }
+
+    // @(*)
+    if p.peek_kind(0) == OpenDelim(Paren)
+        && p.peek_kind(1) == Operator(Op::Mul)
+        && p.peek_kind(2) == CloseDelim(Paren)
+    {
+        p.bump();
+        p.bump();
+        p.bump();
+        span.expand(p.last_span());
+        return Ok(Some(EventControl {
+            span: span,
+            data: EventControlData::Implicit,
+        }));
+    }
+
+    let expr = parse_event_expr(p, EventPrecedence::Max)?;
+    span.expand(p.last_span());
+
+    Ok(Some(EventControl {
+        span: span,
+        data: EventControlData::Expr(expr),
+    }))
+}
+
+fn try_cycle_delay<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Option<CycleDelay>> {
+    if !p.try_eat(DoubleHashtag) {
+        return Ok(None);
+    }
+
+    let q = p.last_span();
+    p.add_diag(DiagBuilder2::error("Don't know how to parse cycle delay").span(q));
+    Err(())
+}
+
+/// Try to parse an intra-assignment delay or event control, as in `a = #2 b`,
+/// `a = @(posedge clk) b`, or `a = repeat (2) @(posedge clk) b`.
+fn try_intra_timing_control<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Option<TimingControl<'n>>> {
+    if let Some(dc) = try_delay_control(p)? {
+        return Ok(Some(TimingControl::Delay(dc)));
+    }
+    if let Some(ec) = try_event_control(p)? {
+        return Ok(Some(TimingControl::Event(ec)));
+    }
+    if p.try_eat(Keyword(Kw::Repeat)) {
+        let count = flanked(p, Paren, parse_expr)?;
+        return match try_event_control(p)? {
+            Some(ec) => Ok(Some(TimingControl::Repeat(count, ec))),
+            None => {
+                let (tkn, sp) = p.peek(0);
+                p.add_diag(
+                    DiagBuilder2::error(format!("expected event control before `{}`", tkn))
+                        .span(sp),
+                );
+                Err(())
+            }
+        };
+    }
+    Ok(None)
+}
+
+fn parse_assignment<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<(Expr<'n>, Expr<'n>)> {
+    let lhs = parse_expr_prec(p, Precedence::Postfix)?;
+    p.require_reported(Operator(Op::Assign))?;
+    let rhs = parse_expr_prec(p, Precedence::Assignment)?;
+    Ok((lhs, rhs))
+}
+
+fn parse_assign_stmt<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<StmtKind<'n>> {
+    // Parse the leading expression.
+    let expr = parse_expr_prec(p, Precedence::Postfix)?;
+    let (tkn, sp) = p.peek(0);
+
+    // Handle blocking assignments (IEEE 1800-2009 section 10.4.1), where the
+    // expression is followed by an assignment operator.
+    if let Some(op) = as_assign_operator(tkn) {
+        p.bump();
+
+        // Parse the optional intra-assignment timing control, which is only
+        // allowed on simple assignments.
+        let control = if op == AssignOp::Identity {
+            try_intra_timing_control(p)?
+        } else {
+            None
+        };
+
+        let rhs = parse_expr(p)?;
+        p.require_reported(Semicolon)?;
+        return Ok(BlockingAssignStmt {
+            lhs: expr,
+            rhs: rhs,
+            op: op,
+            control: control,
+        });
+    }
+
+    // Handle non-blocking assignments (IEEE 1800-2009 section 10.4.2).
+    if tkn == Operator(Op::Leq) {
+        p.bump();
+
+        // Parse the optional delay and event control.
+        let (delay_control, event_control, repeat) = match try_intra_timing_control(p)? {
+            Some(TimingControl::Delay(dc)) => (Some(dc), None, None),
+            Some(TimingControl::Event(ec)) => (None, Some(ec), None),
+            Some(TimingControl::Repeat(count, ec)) => (None, Some(ec), Some(count)),
+            Some(TimingControl::Cycle(..)) | None => (None, None, None),
+        };
+
+        // Parse the right-hand side of the assignment.
+        let rhs = parse_expr(p)?;
+        p.require_reported(Semicolon)?;
+
+        return Ok(NonblockingAssignStmt {
+            lhs: expr,
+            rhs: rhs,
+            delay: delay_control,
+            event: event_control,
+            repeat,
+        });
+    }
+
+    p.add_diag(DiagBuilder2::error("expected blocking or non-blocking assign statement").span(sp));
+    Err(())
+}
+
+fn parse_expr_stmt<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<StmtKind<'n>> {
+    let mut expr = parse_expr_prec(p, Precedence::Unary)?;
+    p.require_reported(Semicolon)?;
+
+    // A system task may be called without parentheses, as in `$error;`.
+    if let SysIdentExpr(..) = expr.data {
+        expr = Expr::new(expr.span, CallExpr(Box::new(expr), vec![]));
+    }
+    Ok(ExprStmt(expr))
+}
+
+fn parse_event_expr<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    precedence: EventPrecedence,
+) -> ReportedResult<EventExpr<'n>> {
+    let mut span = p.peek(0).1;
+
+    // Try parsing an event expression in parentheses.
+    if p.try_eat(OpenDelim(Paren)) {
+        return match parse_event_expr(p, EventPrecedence::Min) {
+            Ok(x) => {
+                p.require_reported(CloseDelim(Paren))?;
+                parse_event_expr_suffix(p, x, precedence)
+            }
+            Err(()) => {
+                p.recover_balanced(&[CloseDelim(Paren)], true);
+                Err(())
+            }
+        };
+    }
+
+    // Consume the optional edge identifier.
+    let edge = as_edge_ident(p.peek_kind(0));
+    if edge != EdgeIdent::Implicit {
+        p.bump();
+    }
+
+    // Parse the value.
+    let value = parse_expr(p)?;
+    span.expand(p.last_span());
+
+    let expr = EventExpr::Edge {
+        span: span,
+        edge: edge,
+        value: value,
+    };
+    parse_event_expr_suffix(p, expr, precedence)
+
+    // p.add_diag(DiagBuilder2::error("expected event expression").span(span));
+    // Err(())
+}
+
+fn parse_event_expr_suffix<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    expr: EventExpr<'n>,
+    precedence: EventPrecedence,
+) -> ReportedResult<EventExpr<'n>> {
+    match p.peek_kind(0) {
+        // event_expr "iff" expr
+        Keyword(Kw::Iff) if precedence < EventPrecedence::Iff => {
+            p.bump();
+            let cond = parse_expr(p)?;
+            let expr = EventExpr::Iff {
+                span: Span::union(expr.span(), cond.span),
+                expr: Box::new(expr),
+                cond: cond,
+            };
+            parse_event_expr_suffix(p, expr, precedence)
+        }
+        // event_expr "or" event_expr
+        // event_expr "," event_expr
+        Keyword(Kw::Or) | Comma if precedence <= EventPrecedence::Or => {
+            p.bump();
+            let rhs = parse_event_expr(p, EventPrecedence::Or)?;
+            Ok(EventExpr::Or {
+                span: Span::union(expr.span(), rhs.span()),
+                lhs: Box::new(expr),
+                rhs: Box::new(rhs),
+            })
+        }
+        _ => Ok(expr),
+    }
+}
+
+/// Parse an event expression passed to a port of a checker instance.
+///
+/// The comma separates port connections here, so only `or` may combine
+/// events.
+///
+/// ```text
+/// edge_ident expr ["iff" expr] {"or" port_event_expr}
+/// ```
+fn parse_port_event_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<EventExpr<'n>> {
+    let mut expr = parse_event_expr(p, EventPrecedence::Iff)?;
+    if p.try_eat(Keyword(Kw::Iff)) {
+        let cond = parse_expr(p)?;
+        expr = EventExpr::Iff {
+            span: Span::union(expr.span(), cond.span),
+            expr: Box::new(expr),
+            cond: cond,
+        };
+    }
+    if p.try_eat(Keyword(Kw::Or)) {
+        let rhs = parse_port_event_expr(p)?;
+        expr = EventExpr::Or {
+            span: Span::union(expr.span(), rhs.span()),
+            lhs: Box::new(expr),
+            rhs: Box::new(rhs),
+        };
+    }
+    Ok(expr)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum EventPrecedence {
+    Min,
+    Or,
+    Iff,
+    Max,
+}
+
+fn as_edge_ident(tkn: Token) -> EdgeIdent {
+    match tkn {
+        Keyword(Kw::Edge) => EdgeIdent::Edge,
+        Keyword(Kw::Posedge) => EdgeIdent::Posedge,
+        Keyword(Kw::Negedge) => EdgeIdent::Negedge,
+        _ => EdgeIdent::Implicit,
+    }
+}
+
+fn parse_call_args<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Vec<CallArg<'n>>> {
+    let mut v: Vec<CallArg> = Vec::new();
+    if p.peek_kind(0) == CloseDelim(Paren) {
+        return Ok(v);
+    }
+    loop {
+        match p.peek(0) {
+            // An empty positional argument, as in `f(, b)` or `f(a, )`.
+            (Comma, sp) | (CloseDelim(Paren), sp) => v.push(CallArg {
+                span: sp,
+                name_span: sp,
+                name: None,
+                expr: None,
+            }),
+            (Period, mut sp) => {
+                p.bump();
+                let (name, mut name_sp) = p.eat_ident("argument name")?;
+                name_sp.expand(sp);
+                let expr = flanked(p, Paren, |p| {
+                    Ok(if p.peek_kind(0) == CloseDelim(Paren) {
+                        None
+                    } else {
+                        Some(parse_expr(p)?)
+                    })
+                })?;
+                sp.expand(p.last_span());
+
+                v.push(CallArg {
+                    span: sp,
+                    name_span: name_sp,
+                    name: Some(name),
+                    expr: expr,
+                });
+            }
+            (_, mut sp) => {
+                let expr = parse_expr(p)?;
+                sp.expand(p.last_span());
+                v.push(CallArg {
+                    span: sp,
+                    name_span: sp,
+                    name: None,
+                    expr: Some(expr),
+                });
+            }
+        }
+
+        // Positional arguments must precede all named arguments.
+        let arg = v.last().unwrap();
+        if arg.name.is_none() {
+            if let Some(named) = v.iter().find(|a| a.name.is_some()) {
+                p.add_diag(
+                    DiagBuilder2::error("positional argument after named argument")
+                        .span(arg.span)
+                        .add_note("Named argument given here:")
+                        .span(named.name_span),
+                );
+            }
+        }
+
+        match p.peek(0) {
+            (Comma, _) => p.bump(),
+            (CloseDelim(Paren), _) => break,
+            (_, sp) => {
+                p.add_diag(DiagBuilder2::error("expected , or ) after call argument").span(sp));
+                return Err(());
+            }
+        }
+    }
+    Ok(v)
+}
+
+fn parse_variable_decl_assignment<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<VarDeclName<'n>> {
+    let mut span = p.peek(0).1;
+
+    // Parse the variable name.
+    let (name, name_span) = p.eat_ident("variable name")?;
+
+    // Parse the optional dimensions.
+    let (dims, _) = parse_optional_dimensions(p)?;
+
+    // Parse the optional initial expression.
+    let init = if p.try_eat(Operator(Op::Assign)) {
+        Some(parse_expr(p)?)
+    } else {
+        None
+    };
+    span.expand(p.last_span());
+
+    Ok(VarDeclName::new(
+        span,
+        VarDeclNameData {
+            name: name,
+            name_span: name_span,
+            dims: dims,
+            init: init,
+        },
+    ))
+}
+
+fn parse_genvar_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenvarDecl<'n>> {
+    let mut span = p.peek(0).1;
+
+    // Parse the genvar name.
+    let name = parse_identifier_name(p, "genvar name")?;
+
+    // Parse the optional initial expression.
+    let init = if p.try_eat(Operator(Op::Assign)) {
+        Some(parse_expr(p)?)
+    } else {
+        None
+    };
+    span.expand(p.last_span());
+
+    Ok(GenvarDecl::new(span, GenvarDeclData { name, init }))
+}
+
+fn parse_generate_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Item<'n>> {
+    let mut span = p.peek(0).1;
+    let data = match p.peek_kind(0) {
+        Keyword(Kw::For) => ItemData::GenerateFor(nested(p, parse_generate_for)?),
+        Keyword(Kw::If) => ItemData::GenerateIf(nested(p, parse_generate_if)?),
+        Keyword(Kw::Case) => ItemData::GenerateCase(nested(p, parse_generate_case)?),
+        _ => return parse_item(p),
+    };
+    span.expand(p.last_span());
+    Ok(Item::new(span, data))
+}
+
+/// Parse a generate-for construct.
+/// ```text
+/// "for" "(" stmt expr ";" expr ")" generate_block
+/// ```
+fn parse_generate_for<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateFor<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::For))?;
+    let (init, cond, step) = flanked(p, Paren, |p| {
+        let init = parse_stmt(p)?;
+        let cond = parse_expr(p)?;
+        p.require_reported(Semicolon)?;
+        let step = parse_expr(p)?;
+        Ok((init, cond, step))
+    })?;
+    let block = parse_generate_block(p)?;
+    span.expand(p.last_span());
+    Ok(GenerateFor::new(
+        span,
+        GenerateForData {
+            init,
+            cond,
+            step,
+            block,
+        },
+    ))
+}
+
+fn parse_generate_if<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateIf<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::If))?;
+    let cond = flanked(p, Paren, parse_expr)?;
+    let main_block = parse_generate_block(p)?;
+    let else_block = if p.try_eat(Keyword(Kw::Else)) {
+        Some(parse_generate_block(p)?)
+    } else {
+        None
+    };
+    span.expand(p.last_span());
+    Ok(GenerateIf::new(
+        span,
+        GenerateIfData {
+            cond,
+            main_block,
+            else_block,
+        },
+    ))
+}
+
+fn parse_generate_case<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateCase<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Case))?;
+    p.recover_balanced(&[Keyword(Kw::Endcase)], true);
+    if p.try_eat(Colon) {
+        parse_identifier_name(p, "generate block label")?;
+    }
+    span.expand(p.last_span());
+    p.add_diag(DiagBuilder2::error("case-generate statements not supported").span(span));
+    Ok(GenerateCase::new(span, GenerateCaseData {}))
+}
+
+fn parse_generate_block<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateBlock<'n>> {
+    let mut span = p.peek(0).1;
+
+    // Parse the optional block label.
+    let mut label = if p.is_ident() && p.peek_kind(1) == Colon {
+        let n = parse_identifier_name(p, "generate block label")?;
+        p.require_reported(Colon)?;
+        Some(n)
+    } else {
+        None
+    };
+
+    // Consume the opening "begin" keyword if present. Otherwise simply parse
+    // a single generate item.
+    if !p.try_eat(OpenDelim(Bgend)) {
+        if label.is_some() {
+            let (t, q) = p.peek(0);
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected `begin` keyword after generate block label, found {} instead",
+                    t
+                ))
+                .span(q),
+            );
+            return Err(());
+        }
+        let item = parse_generate_item(p)?;
+        span.expand(p.last_span());
+        return Ok(GenerateBlock::new(
+            span,
+            GenerateBlockData {
+                label: label,
+                items: vec![item],
+            },
+        ));
+    }
+
+    // Consume the optional label after the "begin" keyword.
+    if p.try_eat(Colon) {
+        let n = parse_identifier_name(p, "generate block label")?;
+        if let Some(existing) = label {
+            if existing.value == n.value {
+                p.add_diag(
+                    DiagBuilder2::warning(format!("Generate block {} labelled twice", n))
+                        .span(n.span),
+                );
+            } else {
+                p.add_diag(
+                    DiagBuilder2::error(format!(
+                        "Generate block given conflicting labels {} and {}",
+                        existing, n
+                    ))
+                    .span(n.span),
+                );
+                return Err(());
+            }
+        } else {
+            label = Some(n);
+        }
+    }
+
+    let items = repeat_until(p, CloseDelim(Bgend), parse_generate_item)?;
+    p.require_reported(CloseDelim(Bgend))?;
+
+    // Consume the optional label after the "end" keyword.
+    if p.try_eat(Colon) {
+        p.check_verilog("label after `end`", p.last_span());
+        let n = parse_identifier_name(p, "generate block label")?;
+        if let Some(existing) = label {
+            if existing.value != n.value {
+                p.add_diag(
+                    DiagBuilder2::error(format!(
+                        "Label {} given after generate block does not match label {} given before \
+                         the block",
+                        n, existing
+                    ))
+                    .span(n.span),
+                );
+                return Err(());
+            }
+        } else {
+            p.add_diag(
+                DiagBuilder2::warning(format!(
+                    "Generate block has trailing label {}, but is missing leading label",
+                    n
+                ))
+                .span(n.span),
+            );
+        }
+    }
+
+    span.expand(p.last_span());
+    Ok(GenerateBlock::new(
+        span,
+        GenerateBlockData {
+            label: label,
+            items: items,
+        },
+    ))
+}
+
+fn parse_class_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassDecl<'n>> {
+    let mut span = p.peek(0).1;
+    let result = recovered(p, Keyword(Kw::Endclass), |p| {
+        // Eat the optional "virtual" keyword.
+        let virt = p.try_eat(Keyword(Kw::Virtual));
+
+        // Eat the "class" keyword.
+        p.require_reported(Keyword(Kw::Class))?;
+
+        // Eat the optional lifetime.
+        let lifetime = match as_lifetime(p.peek_kind(0)) {
+            Some(l) => {
+                p.bump();
+                l
+            }
+            None => Lifetime::Static,
+        };
+
+        // Parse the class name.
+        let name = parse_identifier_name(p, "class name")?;
+
+        // Parse the optional parameter port list.
+        let params = if p.try_eat(Hashtag) {
+            parse_parameter_port_list(p)?
+        } else {
+            Vec::new()
+        };
+
+        // Parse the optional inheritance clause.
+        let extends = if p.try_eat(Keyword(Kw::Extends)) {
+            let superclass = parse_data_type(p)?;
+            let args = try_flanked(p, Paren, parse_call_args)?.unwrap_or(Vec::new());
+            Some((superclass, args))
+        } else {
+            None
+        };
+
+        // Parse the optional implementation clause.
+        let impls = if p.try_eat(Keyword(Kw::Implements)) {
+            comma_list_nonempty(p, Semicolon, "interface class", parse_data_type)?
+        } else {
+            vec![]
+        };
+
+        p.require_reported(Semicolon)?;
+
+        // Parse the class items.
+        let items = repeat_until(p, Keyword(Kw::Endclass), parse_class_item)?;
+        Ok((virt, lifetime, name, params, extends, impls, items))
+    });
+    p.require_reported(Keyword(Kw::Endclass))?;
+
+    let (virt, lifetime, name, params, extends, impls, items) = result?;
+
+    // Parse the optional class name after "endclass".
+    if p.try_eat(Colon) {
+        let n = parse_identifier_name(p, "class name")?;
+        if n.value != name.value {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "Class name {} disagrees with name {} given before",
+                    n, name
+                ))
+                .span(n.span),
+            );
+            return Err(());
+        }
+    }
+
+    span.expand(p.last_span());
+    Ok(ClassDecl::new(
+        span,
+        ClassDeclData {
+            virt,
+            lifetime,
+            name,
+            params,
+            extends,
+            impls,
+            items,
+        },
+    ))
+}
+
+fn parse_class_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassItem<'n>> {
+    let mut span = p.peek(0).1;
+
+    // Easy path for null class items.
+    if p.try_eat(Semicolon) {
+        return Ok(ClassItem {
+            span,
+            qualifiers: vec![],
+            data: ClassItemData::Null,
+        });
+    }
+
+    // Parse nested class declarations.
+    if p.peek_kind(0) == Keyword(Kw::Interface) && p.peek_kind(1) == Keyword(Kw::Class) {
+        let decl = parse_interface_class_decl(p)?;
+        span.expand(p.last_span());
+        return Ok(ClassItem {
+            span,
+            qualifiers: vec![],
+            data: ClassItemData::InterfaceClassDecl(decl),
+        });
+    }
+    let nested_class = match p.peek_kind(0) {
+        Keyword(Kw::Class) => true,
+        Keyword(Kw::Virtual) => p.peek_kind(1) == Keyword(Kw::Class),
+        _ => false,
+    };
+    if nested_class {
+        let decl = parse_class_decl(p)?;
+        span.expand(p.last_span());
+        return Ok(ClassItem {
+            span,
+            qualifiers: vec![],
+            data: ClassItemData::ClassDecl(decl),
+        });
+    }
+
+    // Parse constraints, including their `extern` and `pure` prototypes.
+    let mut i = 0;
+    while let Keyword(Kw::Extern) | Keyword(Kw::Pure) | Keyword(Kw::Static) = p.peek_kind(i) {
+        i += 1;
+    }
+    if p.peek_kind(i) == Keyword(Kw::Constraint) {
+        let constraint = parse_constraint(p)?;
+        span.expand(p.last_span());
+        return Ok(ClassItem {
+            span,
+            qualifiers: vec![],
+            data: ClassItemData::Constraint(constraint),
+        });
+    }
+
+    // Parse covergroup declarations.
+    if p.peek_kind(0) == Keyword(Kw::Covergroup) {
+        let decl = parse_covergroup(p)?;
+        span.expand(p.last_span());
+        return Ok(ClassItem {
+            span,
+            qualifiers: vec![],
+            data: ClassItemData::CovergroupDecl(decl),
+        });
+    }
+
+    // Parse localparam and parameter declarations.
+    // TODO: Replace these by calls to parse_param_decl.
+    match p.peek_kind(0) {
+        Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
+            let decl = parse_param_decl(p, false)?;
+            span.expand(p.last_span());
+            p.require_reported(Semicolon)?;
+            return Ok(ClassItem {
+                span,
+                qualifiers: vec![],
+                data: ClassItemData::ParamDecl(decl),
+            });
+        }
+        // Parse "extern" task and function prototypes, with the optional
+        // method qualifiers.
+        Keyword(Kw::Extern) => {
+            p.bump();
+            let qualifiers = parse_class_item_qualifiers(p)?;
+            for &(q, sp) in &qualifiers {
+                match q {
+                    ClassItemQualifier::Static
+                    | ClassItemQualifier::Protected
+                    | ClassItemQualifier::Local
+                    | ClassItemQualifier::Virtual => (),
+                    _ => p.add_diag(
+                        DiagBuilder2::error(format!(
+                            "`{}` cannot qualify an extern method",
+                            sp.extract()
+                        ))
+                        .span(sp),
+                    ),
+                }
+            }
+            let proto = parse_subroutine_prototype(p)?;
+            span.expand(p.last_span());
+            return Ok(ClassItem {
+                span,
+                qualifiers,
+                data: ClassItemData::ExternSubroutine(proto),
+            });
+        }
+        // Parse type defs.
+        Keyword(Kw::Typedef) => {
+            let def = parse_typedef(p)?;
+            span.expand(p.last_span());
+            return Ok(ClassItem {
+                span,
+                qualifiers: vec![],
+                data: ClassItemData::Typedef(def),
+            });
+        }
+        _ => (),
+    }
+
+    // Parse the optional class item qualifiers.
+    let qualifiers = parse_class_item_qualifiers(p)?;
+    let konst = qualifiers
+        .iter()
+        .any(|&(q, _)| q == ClassItemQualifier::Const);
+
+    // Pure virtual methods only consist of a prototype.
+    if qualifiers
+        .iter()
+        .any(|&(q, _)| q == ClassItemQualifier::Pure)
+    {
+        let proto = parse_subroutine_prototype(p)?;
+        span.expand(p.last_span());
+        return Ok(ClassItem {
+            span,
+            qualifiers,
+            data: ClassItemData::ExternSubroutine(proto),
+        });
+    }
+
+    let data = {
+        let mut pp = ParallelParser::new();
+        pp.add("class property", move |p| {
+            let mut span = p.peek(0).1;
+            let ty = parse_data_type(p)?;
+            let names = comma_list_nonempty(
+                p,
+                Semicolon,
+                "data declaration",
+                parse_variable_decl_assignment,
+            )?;
+            p.require_reported(Semicolon)?;
+            span.expand(p.last_span());
+            Ok(ClassItemData::Property(VarDecl::new(
+                span,
+                VarDeclData {
+                    konst,
+                    var: false,
+                    rand: false,
+                    lifetime: None,
+                    ty,
+                    names,
+                },
+            )))
+        });
+        pp.add("class function or task", |p| {
+            parse_subroutine_decl(p).map(ClassItemData::SubroutineDecl)
+        });
+        pp.add("class constraint", |p| {
+            parse_constraint(p).map(ClassItemData::Constraint)
+        });
+        pp.finish(p, "class item")?
+    };
+    span.expand(p.last_span());
+
+    Ok(ClassItem {
+        span: span,
+        qualifiers: qualifiers,
+        data: data,
+    })
+}
+
+fn parse_interface_class_decl<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<InterfaceClassDecl<'n>> {
+    let mut span = p.peek(0).1;
+    let result = recovered(p, Keyword(Kw::Endclass), |p| {
+        // Eat the "interface class" keywords.
+        p.require_reported(Keyword(Kw::Interface))?;
+        p.require_reported(Keyword(Kw::Class))?;
+
+        // Parse the class name.
+        let name = parse_identifier_name(p, "interface class name")?;
+
+        // Parse the optional parameter port list.
+        let params = if p.try_eat(Hashtag) {
+            parse_parameter_port_list(p)?
+        } else {
+            Vec::new()
+        };
+
+        // Parse the optional list of extended interface classes.
+        let extends = if p.try_eat(Keyword(Kw::Extends)) {
+            comma_list_nonempty(p, Semicolon, "interface class", parse_data_type)?
+        } else {
+            vec![]
+        };
+
+        p.require_reported(Semicolon)?;
+
+        // Parse the class items.
+        let items = repeat_until(p, Keyword(Kw::Endclass), parse_interface_class_item)?;
+        Ok((name, params, extends, items))
+    });
+    p.require_reported(Keyword(Kw::Endclass))?;
+
+    let (name, params, extends, items) = result?;
+
+    // Parse the optional class name after "endclass".
+    if p.try_eat(Colon) {
+        let n = parse_identifier_name(p, "interface class name")?;
+        if n.value != name.value {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "Class name {} disagrees with name {} given before",
+                    n, name
+                ))
+                .span(n.span),
+            );
+            return Err(());
+        }
+    }
+
+    span.expand(p.last_span());
+    Ok(InterfaceClassDecl::new(
+        span,
+        InterfaceClassDeclData {
+            name,
+            params,
+            extends,
+            items,
+        },
+    ))
+}
+
+/// Parse an item of an interface class.
+///
+/// These are restricted to pure virtual methods, type definitions, and
+/// parameter declarations.
+fn parse_interface_class_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassItem<'n>> {
+    let mut span = p.peek(0).1;
+    let mut qualifiers = vec![];
+    let data = match p.peek_kind(0) {
+        Semicolon => {
+            p.bump();
+            ClassItemData::Null
+        }
+        Keyword(Kw::Typedef) => ClassItemData::Typedef(parse_typedef(p)?),
+        Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
+            let decl = parse_param_decl(p, false)?;
+            p.require_reported(Semicolon)?;
+            ClassItemData::ParamDecl(decl)
+        }
+        Keyword(Kw::Pure) => {
+            qualifiers.push((ClassItemQualifier::Pure, p.peek(0).1));
+            p.bump();
+            qualifiers.push((ClassItemQualifier::Virtual, p.peek(0).1));
+            p.require_reported(Keyword(Kw::Virtual))?;
+            let proto = parse_subroutine_prototype(p)?;
+            ClassItemData::ExternSubroutine(proto)
+        }
+        tkn => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected pure virtual method, typedef, or parameter before `{}`",
+                    tkn
+                ))
+                .span(span)
+                .add_note("Interface classes cannot have properties or method bodies"),
+            );
+            return Err(());
+        }
+    };
+    span.expand(p.last_span());
+    Ok(ClassItem {
+        span,
+        qualifiers,
+        data,
+    })
+}
+
+fn parse_class_item_qualifiers<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Vec<(ClassItemQualifier, Span)>> {
+    let mut v = Vec::new();
+    loop {
+        let (tkn, sp) = p.peek(0);
+        match tkn {
+            Keyword(Kw::Static) => v.push((ClassItemQualifier::Static, sp)),
+            Keyword(Kw::Protected) => v.push((ClassItemQualifier::Protected, sp)),
+            Keyword(Kw::Local) => v.push((ClassItemQualifier::Local, sp)),
+            Keyword(Kw::Rand) => v.push((ClassItemQualifier::Rand, sp)),
+            Keyword(Kw::Randc) => v.push((ClassItemQualifier::Randc, sp)),
+            Keyword(Kw::Pure) => v.push((ClassItemQualifier::Pure, sp)),
+            // A `virtual` that does not precede a method starts a virtual
+            // interface type.
+            Keyword(Kw::Virtual) => match p.peek_kind(1) {
+                Keyword(Kw::Function) | Keyword(Kw::Task) => {
+                    v.push((ClassItemQualifier::Virtual, sp))
+                }
+                _ => break,
+            },
+            Keyword(Kw::Const) => v.push((ClassItemQualifier::Const, sp)),
+            _ => break,
+        }
+        p.bump();
+    }
+    Ok(v)
+}
+
+fn parse_class_method<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassItem<'n>> {
+    debug!("{:?}: parsing class method", p.peek(0).1);
+    Err(())
 }
 
-fn parse_genvar_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenvarDecl<'n>> {
-    let mut span = p.peek(0).1;
+fn parse_class_property<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassItem<'n>> {
+    debug!("{:?}: parsing class property", p.peek(0).1);
+    p.try_eat(Keyword(Kw::Rand));
+    Err(())
+}
 
-    // Parse the genvar name.
-    let name = parse_identifier_name(p, "genvar name")?;
+fn parse_constraint<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Constraint<'n>> {
+    let mut span = p.peek(0).1;
 
-    // Parse the optional initial expression.
-    let init = if p.try_eat(Operator(Op::Assign)) {
-        Some(parse_expr(p)?)
-    } else {
-        None
+    // Parse the prototype qualifier.
+    let kind = match p.peek_kind(0) {
+        Keyword(Kw::Extern) => {
+            p.bump();
+            ConstraintKind::ExternProto
+        }
+        Keyword(Kw::Pure) => {
+            p.bump();
+            ConstraintKind::PureProto
+        }
+        _ => ConstraintKind::Decl,
     };
-    span.expand(p.last_span());
+    let kind_span = span;
 
-    Ok(GenvarDecl::new(span, GenvarDeclData { name, init }))
-}
+    // Parse the optional "static" keyword.
+    let statik = p.try_eat(Keyword(Kw::Static));
 
-fn parse_generate_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Item<'n>> {
-    let mut span = p.peek(0).1;
-    let data = match p.peek(0).0 {
-        Keyword(Kw::For) => ItemData::GenerateFor(parse_generate_for(p)?),
-        Keyword(Kw::If) => ItemData::GenerateIf(parse_generate_if(p)?),
-        Keyword(Kw::Case) => ItemData::GenerateCase(parse_generate_case(p)?),
-        _ => return parse_item(p),
-    };
-    span.expand(p.last_span());
-    Ok(Item::new(span, data))
-}
+    // Parse the "constraint" keyword.
+    p.require_reported(Keyword(Kw::Constraint))?;
 
-/// Parse a generate-for construct.
-/// ```text
-/// "for" "(" stmt expr ";" expr ")" generate_block
-/// ```
-fn parse_generate_for<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateFor<'n>> {
-    let mut span = p.peek(0).1;
-    p.require_reported(Keyword(Kw::For))?;
-    let (init, cond, step) = flanked(p, Paren, |p| {
-        let init = parse_stmt(p)?;
-        let cond = parse_expr(p)?;
-        p.require_reported(Semicolon)?;
-        let step = parse_expr(p)?;
-        Ok((init, cond, step))
-    })?;
-    let block = parse_generate_block(p)?;
-    span.expand(p.last_span());
-    Ok(GenerateFor::new(
-        span,
-        GenerateForData {
-            init,
-            cond,
-            step,
-            block,
-        },
-    ))
-}
+    // Parse the constraint name.
+    let (name, name_span) = p.eat_ident("constraint name")?;
 
-fn parse_generate_if<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateIf<'n>> {
-    let mut span = p.peek(0).1;
-    p.require_reported(Keyword(Kw::If))?;
-    let cond = flanked(p, Paren, parse_expr)?;
-    let main_block = parse_generate_block(p)?;
-    let else_block = if p.try_eat(Keyword(Kw::Else)) {
-        Some(parse_generate_block(p)?)
+    let items = if p.try_eat(Semicolon) {
+        let kind = match kind {
+            ConstraintKind::Decl => ConstraintKind::Proto,
+            x => x,
+        };
+        Vec::new()
     } else {
-        None
+        // Make sure that no "extern" or "pure" keyword was used, as these are
+        // only valid for prototypes.
+        if kind == ConstraintKind::ExternProto || kind == ConstraintKind::PureProto {
+            p.add_diag(
+                DiagBuilder2::error("Only constraint prototypes can be extern or pure")
+                    .span(kind_span),
+            );
+            return Err(());
+        }
+        flanked(p, Brace, |p| {
+            repeat_until(p, CloseDelim(Brace), parse_constraint_item)
+        })?
     };
     span.expand(p.last_span());
-    Ok(GenerateIf::new(
-        span,
-        GenerateIfData {
-            cond,
-            main_block,
-            else_block,
-        },
-    ))
+
+    Ok(Constraint {
+        span: span,
+        kind: kind,
+        statik: statik,
+        name: name,
+        name_span: name_span,
+        items: items,
+    })
 }
 
-fn parse_generate_case<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateCase<'n>> {
+fn parse_constraint_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ConstraintItem<'n>> {
     let mut span = p.peek(0).1;
-    p.require_reported(Keyword(Kw::Case))?;
-    p.recover_balanced(&[Keyword(Kw::Endcase)], true);
-    if p.try_eat(Colon) {
-        parse_identifier_name(p, "generate block label")?;
-    }
+    let data = parse_constraint_item_data(p)?;
     span.expand(p.last_span());
-    p.add_diag(DiagBuilder2::error("case-generate statements not supported").span(span));
-    Ok(GenerateCase::new(span, GenerateCaseData {}))
+    Ok(ConstraintItem {
+        span: span,
+        data: data,
+    })
 }
 
-fn parse_generate_block<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<GenerateBlock<'n>> {
-    let mut span = p.peek(0).1;
+fn parse_constraint_item_data<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<ConstraintItemData<'n>> {
+    // Handle the cases that start with a keyword or brace first.
+    match p.peek_kind(0) {
+        // "if" "(" expr ")" constraint_set ["else" constraint_set]
+        Keyword(Kw::If) => {
+            p.bump();
+            let cond = flanked(p, Paren, parse_expr)?;
+            let main = Box::new(parse_constraint_item(p)?);
+            let other = if p.try_eat(Keyword(Kw::Else)) {
+                Some(Box::new(parse_constraint_item(p)?))
+            } else {
+                None
+            };
+            return Ok(ConstraintItemData::If(cond, main, other));
+        }
 
-    // Parse the optional block label.
-    let mut label = if p.is_ident() && p.peek(1).0 == Colon {
-        let n = parse_identifier_name(p, "generate block label")?;
-        p.require_reported(Colon)?;
-        Some(n)
-    } else {
-        None
-    };
+        // "foreach" "(" array "[" loop_variables "]" ")" constraint_set
+        Keyword(Kw::Foreach) => {
+            p.bump();
+            let (expr, vars) = parse_foreach_header(p)?;
+            let body = Box::new(parse_constraint_item(p)?);
+            return Ok(ConstraintItemData::Foreach(expr, vars, body));
+        }
 
-    // Consume the opening "begin" keyword if present. Otherwise simply parse
-    // a single generate item.
-    if !p.try_eat(OpenDelim(Bgend)) {
-        if label.is_some() {
-            let (t, q) = p.peek(0);
-            p.add_diag(
-                DiagBuilder2::error(format!(
-                    "expected `begin` keyword after generate block label, found {} instead",
-                    t
-                ))
-                .span(q),
-            );
-            return Err(());
+        // "{" {constraint_expression} "}"
+        OpenDelim(Brace) => {
+            let items = flanked(p, Brace, |p| {
+                repeat_until(p, CloseDelim(Brace), parse_constraint_item)
+            })?;
+            return Ok(ConstraintItemData::Set(items));
         }
-        let item = parse_generate_item(p)?;
-        span.expand(p.last_span());
-        return Ok(GenerateBlock::new(
-            span,
-            GenerateBlockData {
-                label: label,
-                items: vec![item],
-            },
-        ));
-    }
 
-    // Consume the optional label after the "begin" keyword.
-    if p.try_eat(Colon) {
-        let n = parse_identifier_name(p, "generate block label")?;
-        if let Some(existing) = label {
-            if existing.value == n.value {
-                p.add_diag(
-                    DiagBuilder2::warning(format!("Generate block {} labelled twice", n))
-                        .span(n.span),
-                );
-            } else {
-                p.add_diag(
-                    DiagBuilder2::error(format!(
-                        "Generate block given conflicting labels {} and {}",
-                        existing, n
-                    ))
-                    .span(n.span),
-                );
-                return Err(());
-            }
-        } else {
-            label = Some(n);
+        // "solve" expr {"," expr} "before" expr {"," expr} ";"
+        Keyword(Kw::Solve) => {
+            p.bump();
+            let first = comma_list_nonempty(p, Keyword(Kw::Before), "variable", parse_expr)?;
+            p.require_reported(Keyword(Kw::Before))?;
+            let then = comma_list_nonempty(p, Semicolon, "variable", parse_expr)?;
+            p.require_reported(Semicolon)?;
+            return Ok(ConstraintItemData::Solve(first, then));
         }
-    }
 
-    let items = repeat_until(p, CloseDelim(Bgend), parse_generate_item)?;
-    p.require_reported(CloseDelim(Bgend))?;
+        // "disable" "soft" expr ";"
+        Keyword(Kw::Disable) => {
+            p.bump();
+            p.require_reported(Keyword(Kw::Soft))?;
+            let expr = parse_expr(p)?;
+            p.require_reported(Semicolon)?;
+            return Ok(ConstraintItemData::DisableSoft(expr));
+        }
 
-    // Consume the optional label after the "end" keyword.
-    if p.try_eat(Colon) {
-        let n = parse_identifier_name(p, "generate block label")?;
-        if let Some(existing) = label {
-            if existing.value != n.value {
-                p.add_diag(
-                    DiagBuilder2::error(format!(
-                        "Label {} given after generate block does not match label {} given before \
-                         the block",
-                        n, existing
-                    ))
-                    .span(n.span),
-                );
-                return Err(());
-            }
-        } else {
-            p.add_diag(
-                DiagBuilder2::warning(format!(
-                    "Generate block has trailing label {}, but is missing leading label",
-                    n
-                ))
-                .span(n.span),
-            );
+        // "unique" "{" value_range {"," value_range} "}" ";"
+        Keyword(Kw::Unique) => {
+            p.bump();
+            let ranges = flanked(p, Brace, |p| {
+                comma_list_nonempty(p, CloseDelim(Brace), "value range", parse_value_range)
+            })?;
+            p.require_reported(Semicolon)?;
+            return Ok(ConstraintItemData::Unique(ranges));
         }
+        _ => (),
     }
 
-    span.expand(p.last_span());
-    Ok(GenerateBlock::new(
-        span,
-        GenerateBlockData {
-            label: label,
-            items: items,
-        },
-    ))
-}
-
-fn parse_class_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassDecl<'n>> {
-    let mut span = p.peek(0).1;
-    let result = recovered(p, Keyword(Kw::Endclass), |p| {
-        // Eat the optional "virtual" or "interface" keyword.
-        let virt = p.try_eat(Keyword(Kw::Virtual));
-        let intf = p.try_eat(Keyword(Kw::Interface));
-
-        // Eat the "class" keyword.
-        p.require_reported(Keyword(Kw::Class))?;
+    // If we arrive here, the item starts with an expression. Parse it above
+    // implication precedence, such that the right-hand side of an implication
+    // can be a constraint set.
+    let soft = p.try_eat(Keyword(Kw::Soft));
+    let expr = parse_expr_prec(p, Precedence::Ternary)?;
 
-        // Eat the optional lifetime.
-        let lifetime = match as_lifetime(p.peek(0).0) {
-            Some(l) => {
-                p.bump();
-                l
-            }
-            None => Lifetime::Static,
-        };
+    // expr "->" constraint_set
+    if !soft && p.try_eat(Operator(Op::LogicImpl)) {
+        let body = Box::new(parse_constraint_item(p)?);
+        return Ok(ConstraintItemData::Implication(expr, body));
+    }
 
-        // Parse the class name.
-        let name = parse_identifier_name(p, "class name")?;
+    // expr "dist" "{" dist_item {"," dist_item} "}" ";"
+    if p.try_eat(Keyword(Kw::Dist)) {
+        let items = flanked(p, Brace, |p| {
+            comma_list_nonempty(p, CloseDelim(Brace), "distribution item", parse_dist_item)
+        })?;
+        p.require_reported(Semicolon)?;
+        return Ok(ConstraintItemData::Dist { soft, expr, items });
+    }
 
-        // Parse the optional parameter port list.
-        let params = if p.try_eat(Hashtag) {
-            parse_parameter_port_list(p)?
-        } else {
-            Vec::new()
-        };
+    p.require_reported(Semicolon)?;
+    Ok(ConstraintItemData::Expr { soft, expr })
+}
 
-        // Parse the optional inheritance clause.
-        let extends = if p.try_eat(Keyword(Kw::Extends)) {
-            let superclass = parse_data_type(p)?;
-            let args = try_flanked(p, Paren, parse_call_args)?.unwrap_or(Vec::new());
-            Some((superclass, args))
-        } else {
-            None
-        };
+/// Parse an item of a `dist` constraint.
+///
+/// ```text
+/// dist_item ::= value_range [(":=" | ":/") expr]
+/// ```
+fn parse_dist_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<DistItem<'n>> {
+    let mut span = p.peek(0).1;
+    let range = parse_value_range(p)?;
 
-        // Parse the optional implementation clause.
-        let impls = if p.try_eat(Keyword(Kw::Implements)) {
-            comma_list_nonempty(p, Semicolon, "interface class", |p| {
-                parse_identifier_name(p, "class name")
-            })?
-        } else {
-            vec![]
-        };
+    // The `:=` and `:/` are lexed as a colon followed by an operator.
+    let weight = match (p.peek_kind(0), p.peek_kind(1)) {
+        (Colon, Operator(Op::Assign)) => Some(DistWeight::PerValue),
+        (Colon, Operator(Op::Div)) => Some(DistWeight::PerRange),
+        _ => None,
+    };
+    let weight = match weight {
+        Some(kind) => {
+            p.bump();
+            p.bump();
+            Some((kind, parse_expr(p)?))
+        }
+        None => None,
+    };
+    span.expand(p.last_span());
+    Ok(DistItem {
+        span,
+        range,
+        weight,
+    })
+}
 
+/// Parse a covergroup declaration.
+///
+/// ```text
+/// "covergroup" ident ["(" tf_port_list ")"] [coverage_event] ";"
+///     {coverage_spec_or_option}
+/// "endgroup" [":" ident]
+/// ```
+fn parse_covergroup<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Covergroup<'n>> {
+    let mut span = p.peek(0).1;
+    let result = recovered(p, Keyword(Kw::Endgroup), |p| {
+        p.require_reported(Keyword(Kw::Covergroup))?;
+        let name = parse_identifier_name(p, "covergroup name")?;
+
+        // Parse the optional arguments passed to `new`, and the optional
+        // sampling event.
+        let args = try_flanked(p, Paren, parse_subroutine_port_list)?.unwrap_or(Vec::new());
+        let event = try_coverage_event(p)?;
         p.require_reported(Semicolon)?;
 
-        // Parse the class items.
-        let items = repeat_until(p, Keyword(Kw::Endclass), |p| parse_class_item(p, intf))?;
-        Ok((virt, lifetime, name, params, extends, impls, items))
+        // Parse the coverpoints, crosses, and options.
+        let items = repeat_until(p, Keyword(Kw::Endgroup), parse_coverage_item)?;
+        Ok((name, args, event, items))
     });
-    p.require_reported(Keyword(Kw::Endclass))?;
+    p.require_reported(Keyword(Kw::Endgroup))?;
+    let (name, args, event, items) = result?;
 
-    let (virt, lifetime, name, params, extends, impls, items) = result?;
-
-    // Parse the optional class name after "endclass".
+    // Parse the optional covergroup name after "endgroup".
     if p.try_eat(Colon) {
-        let n = parse_identifier_name(p, "class name")?;
+        let n = parse_identifier_name(p, "covergroup name")?;
         if n.value != name.value {
             p.add_diag(
                 DiagBuilder2::error(format!(
-                    "Class name {} disagrees with name {} given before",
-                    n, name
+                    "label `{}` does not match the name of covergroup `{}`",
+                    n.value, name.value
                 ))
                 .span(n.span),
             );
-            return Err(());
         }
     }
 
     span.expand(p.last_span());
-    Ok(ClassDecl::new(
+    Ok(Covergroup::new(
         span,
-        ClassDeclData {
-            virt,
-            lifetime,
+        CovergroupData {
             name,
-            params,
-            extends,
-            impls,
+            args,
+            event,
             items,
         },
     ))
 }
 
-fn parse_class_item<'n>(
+/// Try to parse the sampling event of a covergroup.
+///
+/// ```text
+/// coverage_event ::= clocking_event
+///     | "with" "function" "sample" "(" [tf_port_list] ")"
+///     | "@@" "(" block_event_expression ")"
+/// ```
+fn try_coverage_event<'n>(
     p: &mut dyn AbstractParser<'n>,
-    intf: bool,
-) -> ReportedResult<ClassItem<'n>> {
+) -> ReportedResult<Option<CoverageEvent<'n>>> {
+    match p.peek_kind(0) {
+        At if p.peek_kind(1) == At => {
+            p.bump();
+            p.bump();
+            let events = flanked(p, Paren, |p| {
+                let mut events = vec![];
+                loop {
+                    let mut span = p.peek(0).1;
+                    let begin = match p.peek_kind(0) {
+                        OpenDelim(Bgend) => true,
+                        CloseDelim(Bgend) => false,
+                        _ => {
+                            p.add_diag(
+                                DiagBuilder2::error("expected `begin` or `end` in block event")
+                                    .span(span),
+                            );
+                            return Err(());
+                        }
+                    };
+                    p.bump();
+                    let target = parse_expr_prec(p, Precedence::Postfix)?;
+                    span.expand(p.last_span());
+                    events.push(BlockEvent {
+                        span,
+                        begin,
+                        target,
+                    });
+                    if !p.try_eat(Keyword(Kw::Or)) {
+                        break;
+                    }
+                }
+                Ok(events)
+            })?;
+            Ok(Some(CoverageEvent::Block(events)))
+        }
+        At => {
+            p.bump();
+            let expr = parse_event_expr(p, EventPrecedence::Max)?;
+            Ok(Some(CoverageEvent::Clocking(expr)))
+        }
+        Keyword(Kw::With) => {
+            p.bump();
+            p.require_reported(Keyword(Kw::Function))?;
+            let name = parse_identifier_name(p, "`sample`")?;
+            if &*name.value.as_str() != "sample" {
+                p.add_diag(
+                    DiagBuilder2::error(format!(
+                        "expected `sample` after `with function`, found `{}`",
+                        name.value
+                    ))
+                    .span(name.span),
+                );
+                return Err(());
+            }
+            let args = flanked(p, Paren, parse_subroutine_port_list)?;
+            Ok(Some(CoverageEvent::Sample(args)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Parse a coverpoint, cross, or option in a covergroup.
+fn parse_coverage_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<CoverageItem<'n>> {
     let mut span = p.peek(0).1;
 
-    // Easy path for null class items.
-    if p.try_eat(Semicolon) {
-        return Ok(ClassItem {
+    // Handle coverage options.
+    if let Some(option) = try_coverage_option(p)? {
+        p.require_reported(Semicolon)?;
+        span.expand(p.last_span());
+        return Ok(CoverageItem {
             span,
-            qualifiers: vec![],
-            data: ClassItemData::Null,
+            data: CoverageItemData::Option(option),
         });
     }
 
-    // Parse localparam and parameter declarations.
-    // TODO: Replace these by calls to parse_param_decl.
-    match p.peek(0).0 {
-        Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
-            let decl = parse_param_decl(p, false)?;
-            span.expand(p.last_span());
-            p.require_reported(Semicolon)?;
-            return Ok(ClassItem {
-                span,
-                qualifiers: vec![],
-                data: ClassItemData::ParamDecl(decl),
-            });
+    // Parse the optional data type and label.
+    let (ty, name) = match (p.peek_kind(0), p.peek_kind(1)) {
+        (Keyword(Kw::Coverpoint), _) | (Keyword(Kw::Cross), _) => (None, None),
+        (Ident(_), Colon) | (EscIdent(_), Colon) => {
+            let name = parse_identifier_name(p, "coverpoint or cross name")?;
+            p.require_reported(Colon)?;
+            (None, Some(name))
         }
-        // Parse "extern" task and function prototypes.
-        Keyword(Kw::Extern) => {
+        _ => {
+            let ty = parse_data_type(p)?;
+            let name = parse_identifier_name(p, "coverpoint name")?;
+            p.require_reported(Colon)?;
+            (Some(ty), Some(name))
+        }
+    };
+
+    let data = match p.peek_kind(0) {
+        Keyword(Kw::Coverpoint) => {
             p.bump();
-            let proto = parse_subroutine_prototype(p)?;
+            let expr = parse_expr(p)?;
+            let iff = try_coverage_iff(p)?;
+            let bins = parse_coverage_bins_list(p, false)?;
             span.expand(p.last_span());
-            return Ok(ClassItem {
+            CoverageItemData::Coverpoint(Coverpoint {
                 span,
-                qualifiers: vec![],
-                data: ClassItemData::ExternSubroutine(proto),
-            });
+                ty,
+                name,
+                expr,
+                iff,
+                bins,
+            })
         }
-        // Parse type defs.
-        Keyword(Kw::Typedef) => {
-            let def = parse_typedef(p)?;
+        Keyword(Kw::Cross) if ty.is_none() => {
+            p.bump();
+            let mut targets = vec![];
+            loop {
+                targets.push(parse_identifier_name(p, "coverpoint name")?);
+                if !p.try_eat(Comma) {
+                    break;
+                }
+            }
+            let iff = try_coverage_iff(p)?;
+            let bins = parse_coverage_bins_list(p, true)?;
             span.expand(p.last_span());
-            return Ok(ClassItem {
+            CoverageItemData::Cross(CoverCross {
                 span,
-                qualifiers: vec![],
-                data: ClassItemData::Typedef(def),
-            });
+                name,
+                targets,
+                iff,
+                bins,
+            })
         }
-        _ => (),
-    }
-
-    // Parse the optional class item qualifiers.
-    let qualifiers = parse_class_item_qualifiers(p)?;
-
-    let data = {
-        let mut pp = ParallelParser::new();
-        pp.add("class property", |p| {
-            let ty = parse_data_type(p)?;
-            let names = comma_list_nonempty(
-                p,
-                Semicolon,
-                "data declaration",
-                parse_variable_decl_assignment,
-            )?;
-            p.require_reported(Semicolon)?;
-            Ok(ClassItemData::Property)
-        });
-        if intf {
-            pp.add("class function or task prototype", |p| {
-                parse_subroutine_prototype(p).map(ClassItemData::ExternSubroutine)
-            });
-        } else {
-            pp.add("class function or task", |p| {
-                parse_subroutine_decl(p).map(ClassItemData::SubroutineDecl)
-            });
+        _ => {
+            let (tkn, sp) = p.peek(0);
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected `coverpoint`, `cross`, or coverage option, found `{}` instead",
+                    tkn
+                ))
+                .span(sp),
+            );
+            return Err(());
         }
-        pp.add("class constraint", |p| {
-            parse_constraint(p).map(ClassItemData::Constraint)
-        });
-        pp.finish(p, "class item")?
     };
     span.expand(p.last_span());
+    Ok(CoverageItem { span, data })
+}
 
-    Ok(ClassItem {
-        span: span,
-        qualifiers: qualifiers,
-        data: data,
-    })
+/// Try to parse a coverage option, as in `option.weight = 2`.
+fn try_coverage_option<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Option<CoverageOption<'n>>> {
+    let mut span = p.peek(0).1;
+    let type_option = match (p.peek_kind(0), p.peek_kind(1)) {
+        (Ident(n), Period) if &*n.as_str() == "option" => false,
+        (Ident(n), Period) if &*n.as_str() == "type_option" => true,
+        _ => return Ok(None),
+    };
+    p.bump();
+    p.bump();
+    let name = parse_identifier_name(p, "option name")?;
+    p.require_reported(Operator(Op::Assign))?;
+    let value = parse_expr(p)?;
+    span.expand(p.last_span());
+    Ok(Some(CoverageOption {
+        span,
+        type_option,
+        name,
+        value,
+    }))
 }
 
-fn parse_class_item_qualifiers<'n>(
+/// Try to parse the `iff (expr)` guard of a coverpoint, cross, or bins.
+fn try_coverage_iff<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Option<Expr<'n>>> {
+    if p.try_eat(Keyword(Kw::Iff)) {
+        Ok(Some(flanked(p, Paren, parse_expr)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Parse the body of a coverpoint or cross, which is either a `;` or a list of
+/// bins and options in braces.
+fn parse_coverage_bins_list<'n>(
     p: &mut dyn AbstractParser<'n>,
-) -> ReportedResult<Vec<(ClassItemQualifier, Span)>> {
-    let mut v = Vec::new();
-    loop {
-        let (tkn, sp) = p.peek(0);
-        match tkn {
-            Keyword(Kw::Static) => v.push((ClassItemQualifier::Static, sp)),
-            Keyword(Kw::Protected) => v.push((ClassItemQualifier::Protected, sp)),
-            Keyword(Kw::Local) => v.push((ClassItemQualifier::Local, sp)),
-            Keyword(Kw::Rand) => v.push((ClassItemQualifier::Rand, sp)),
-            Keyword(Kw::Randc) => v.push((ClassItemQualifier::Randc, sp)),
-            Keyword(Kw::Pure) => v.push((ClassItemQualifier::Pure, sp)),
-            Keyword(Kw::Virtual) => v.push((ClassItemQualifier::Virtual, sp)),
-            Keyword(Kw::Const) => v.push((ClassItemQualifier::Const, sp)),
-            _ => break,
-        }
-        p.bump();
+    cross: bool,
+) -> ReportedResult<Vec<CoverageBinsItem<'n>>> {
+    if p.try_eat(Semicolon) {
+        return Ok(vec![]);
     }
-    Ok(v)
+    flanked(p, Brace, |p| {
+        repeat_until(p, CloseDelim(Brace), |p| {
+            // Crosses may declare functions to be used in their bins.
+            if cross && p.peek_kind(0) == Keyword(Kw::Function) {
+                return parse_subroutine_decl(p).map(CoverageBinsItem::Function);
+            }
+            let item = match try_coverage_option(p)? {
+                Some(option) => CoverageBinsItem::Option(option),
+                None => CoverageBinsItem::Bins(parse_coverage_bins(p, cross)?),
+            };
+            p.require_reported(Semicolon)?;
+            Ok(item)
+        })
+    })
 }
 
-fn parse_class_method<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassItem<'n>> {
-    println!("Parsing class method");
-    Err(())
+/// Parse a `bins`, `illegal_bins`, or `ignore_bins` declaration.
+///
+/// The values of the bins of a cross are a select expression, whereas the
+/// values of the bins of a coverpoint are value ranges, transitions, or an
+/// arbitrary set expression.
+fn parse_coverage_bins<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    cross: bool,
+) -> ReportedResult<CoverageBins<'n>> {
+    let mut span = p.peek(0).1;
+    let wildcard = p.try_eat(Keyword(Kw::Wildcard));
+    let kind = match p.peek(0) {
+        (Keyword(Kw::Bins), _) => BinsKind::Bins,
+        (Keyword(Kw::IllegalBins), _) => BinsKind::IllegalBins,
+        (Keyword(Kw::IgnoreBins), _) => BinsKind::IgnoreBins,
+        (tkn, sp) => {
+            p.add_diag(
+                DiagBuilder2::error(format!(
+                    "expected `bins`, `illegal_bins`, `ignore_bins`, or coverage option, found \
+                     `{}` instead",
+                    tkn
+                ))
+                .span(sp),
+            );
+            return Err(());
+        }
+    };
+    p.bump();
+    let name = parse_identifier_name(p, "bin name")?;
+
+    // Parse the optional array brackets and number of bins.
+    let (array, count) = if p.try_eat(OpenDelim(Brack)) {
+        let count = if p.peek_kind(0) != CloseDelim(Brack) {
+            Some(parse_expr(p)?)
+        } else {
+            None
+        };
+        p.require_reported(CloseDelim(Brack))?;
+        (true, count)
+    } else {
+        (false, None)
+    };
+    p.require_reported(Operator(Op::Assign))?;
+
+    // Parse the values covered by the bins.
+    let data = match p.peek_kind(0) {
+        _ if cross => CoverageBinsData::Select(parse_select_expr(p)?),
+        Keyword(Kw::Default) => {
+            p.bump();
+            if p.try_eat(Keyword(Kw::Sequence)) {
+                CoverageBinsData::DefaultSequence
+            } else {
+                CoverageBinsData::Default
+            }
+        }
+        OpenDelim(Brace) => CoverageBinsData::Ranges(flanked(p, Brace, |p| {
+            comma_list_nonempty(p, CloseDelim(Brace), "value range", parse_value_range)
+        })?),
+        OpenDelim(Paren) => {
+            let mut sets = vec![parse_trans_set(p)?];
+            while p.try_eat(Comma) {
+                sets.push(parse_trans_set(p)?);
+            }
+            CoverageBinsData::Transitions(sets)
+        }
+        _ => CoverageBinsData::Expr(parse_expr(p)?),
+    };
+
+    // Parse the optional `with` filter and `iff` guard.
+    let with = if !cross && p.try_eat(Keyword(Kw::With)) {
+        Some(flanked(p, Paren, parse_expr)?)
+    } else {
+        None
+    };
+    let iff = try_coverage_iff(p)?;
+    span.expand(p.last_span());
+
+    Ok(CoverageBins {
+        span,
+        kind,
+        wildcard,
+        name,
+        array,
+        count,
+        data,
+        with,
+        iff,
+    })
 }
 
-fn parse_class_property<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ClassItem<'n>> {
-    println!("Parsing class property");
-    p.try_eat(Keyword(Kw::Rand));
-    Err(())
+/// Parse a sequence of transitions in the bins of a coverpoint.
+///
+/// ```text
+/// trans_set ::= "(" trans_range_list {"=>" trans_range_list} ")"
+/// trans_range_list ::= value_range {"," value_range} [repeat]
+/// repeat ::= "[" ("*" | "->" | "=") expr [":" expr] "]"
+/// ```
+fn parse_trans_set<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<TransSet<'n>> {
+    let mut span = p.peek(0).1;
+    let steps = flanked(p, Paren, |p| {
+        let mut steps = vec![];
+        loop {
+            steps.push(parse_trans_range_list(p)?);
+
+            // The `=>` is lexed as an assignment followed by a greater-than.
+            if p.peek_kind(0) == Operator(Op::Assign) && p.peek_kind(1) == Operator(Op::Gt) {
+                p.bump();
+                p.bump();
+            } else {
+                break;
+            }
+        }
+        Ok(steps)
+    })?;
+    span.expand(p.last_span());
+    Ok(TransSet { span, steps })
 }
 
-fn parse_constraint<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Constraint<'n>> {
+fn parse_trans_range_list<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<TransRangeList<'n>> {
     let mut span = p.peek(0).1;
 
-    // Parse the prototype qualifier.
-    let kind = match p.peek(0).0 {
-        Keyword(Kw::Extern) => {
-            p.bump();
-            ConstraintKind::ExternProto
-        }
-        Keyword(Kw::Pure) => {
+    // Parse the values. These must not consume the `=` of a following `=>`,
+    // which would otherwise be taken for an assignment.
+    let mut ranges = vec![];
+    loop {
+        ranges.push(if p.peek_kind(0) == OpenDelim(Brack) {
+            let mut sp = p.peek(0).1;
             p.bump();
-            ConstraintKind::PureProto
+            let lo = parse_expr(p)?;
+            p.require_reported(Colon)?;
+            let hi = parse_expr(p)?;
+            p.require_reported(CloseDelim(Brack))?;
+            sp.expand(p.last_span());
+            ValueRange::Range { lo, hi, span: sp }
+        } else {
+            ValueRange::Single(parse_expr_prec(p, Precedence::Ternary)?)
+        });
+        if !p.try_eat(Comma) {
+            break;
         }
-        _ => ConstraintKind::Decl,
-    };
-    let kind_span = span;
-
-    // Parse the optional "static" keyword.
-    let statik = p.try_eat(Keyword(Kw::Static));
-
-    // Parse the "constraint" keyword.
-    p.require_reported(Keyword(Kw::Constraint))?;
-
-    // Parse the constraint name.
-    let (name, name_span) = p.eat_ident("constraint name")?;
+    }
 
-    let items = if p.try_eat(Semicolon) {
-        let kind = match kind {
-            ConstraintKind::Decl => ConstraintKind::Proto,
-            x => x,
+    // Parse the optional repetition.
+    let repeat = if p.peek_kind(0) == OpenDelim(Brack) {
+        let mut sp = p.peek(0).1;
+        p.bump();
+        let kind = match p.peek(0) {
+            (Operator(Op::Mul), _) => TransRepeatKind::Consecutive,
+            (Operator(Op::LogicImpl), _) => TransRepeatKind::Goto,
+            (Operator(Op::Assign), _) => TransRepeatKind::NonConsecutive,
+            (tkn, sp) => {
+                p.add_diag(
+                    DiagBuilder2::error(format!(
+                        "expected `*`, `->`, or `=` in transition repetition, found `{}` instead",
+                        tkn
+                    ))
+                    .span(sp),
+                );
+                return Err(());
+            }
         };
-        Vec::new()
+        p.bump();
+        let lo = parse_expr(p)?;
+        let hi = if p.try_eat(Colon) {
+            Some(parse_expr(p)?)
+        } else {
+            None
+        };
+        p.require_reported(CloseDelim(Brack))?;
+        sp.expand(p.last_span());
+        Some(TransRepeat {
+            span: sp,
+            kind,
+            lo,
+            hi,
+        })
     } else {
-        // Make sure that no "extern" or "pure" keyword was used, as these are
-        // only valid for prototypes.
-        if kind == ConstraintKind::ExternProto || kind == ConstraintKind::PureProto {
-            p.add_diag(
-                DiagBuilder2::error("Only constraint prototypes can be extern or pure")
-                    .span(kind_span),
-            );
-            return Err(());
-        }
-        flanked(p, Brace, |p| {
-            repeat_until(p, CloseDelim(Brace), parse_constraint_item)
-        })?
+        None
     };
     span.expand(p.last_span());
 
-    Ok(Constraint {
-        span: span,
-        kind: kind,
-        statik: statik,
-        name: name,
-        name_span: name_span,
-        items: items,
+    Ok(TransRangeList {
+        span,
+        ranges,
+        repeat,
     })
 }
 
-fn parse_constraint_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ConstraintItem<'n>> {
+/// Parse a select expression in the bins of a cross.
+///
+/// ```text
+/// select_expr ::= select_condition
+///     | "!" select_condition
+///     | select_expr "&&" select_expr
+///     | select_expr "||" select_expr
+///     | "(" select_expr ")"
+///     | select_expr "with" "(" expr ")" ["matches" expr]
+///     | cross_identifier
+/// select_condition ::= "binsof" "(" bins_expr ")" ["intersect" "{" value_range_list "}"]
+/// ```
+fn parse_select_expr<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SelectExpr<'n>> {
     let mut span = p.peek(0).1;
-    let data = parse_constraint_item_data(p)?;
-    span.expand(p.last_span());
-    Ok(ConstraintItem {
-        span: span,
-        data: data,
-    })
+    let mut expr = parse_select_expr_and(p)?;
+    while p.try_eat(Operator(Op::LogicOr)) {
+        let rhs = parse_select_expr_and(p)?;
+        span.expand(p.last_span());
+        expr = SelectExpr::Or(span, Box::new(expr), Box::new(rhs));
+    }
+    if p.try_eat(Keyword(Kw::With)) {
+        let cond = flanked(p, Paren, parse_expr)?;
+        let matches = if p.try_eat(Keyword(Kw::Matches)) {
+            Some(parse_expr(p)?)
+        } else {
+            None
+        };
+        span.expand(p.last_span());
+        expr = SelectExpr::With {
+            span,
+            expr: Box::new(expr),
+            cond,
+            matches,
+        };
+    }
+    Ok(expr)
 }
 
-fn parse_constraint_item_data<'n>(
-    p: &mut dyn AbstractParser<'n>,
-) -> ReportedResult<ConstraintItemData<'n>> {
-    // Handle the trivial cases that start with a keyword first.
-    if p.try_eat(Keyword(Kw::If)) {
-        let q = p.last_span();
-        p.add_diag(DiagBuilder2::error("Don't know how to parse `if` constraint items").span(q));
-        return Err(());
+fn parse_select_expr_and<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SelectExpr<'n>> {
+    let mut span = p.peek(0).1;
+    let mut expr = parse_select_expr_primary(p)?;
+    while p.try_eat(Operator(Op::LogicAnd)) {
+        let rhs = parse_select_expr_primary(p)?;
+        span.expand(p.last_span());
+        expr = SelectExpr::And(span, Box::new(expr), Box::new(rhs));
     }
+    Ok(expr)
+}
 
-    if p.try_eat(Keyword(Kw::Foreach)) {
-        let q = p.last_span();
-        p.add_diag(
-            DiagBuilder2::error("Don't know how to parse `foreach` constraint items").span(q),
-        );
-        return Err(());
+fn parse_select_expr_primary<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SelectExpr<'n>> {
+    let mut span = p.peek(0).1;
+    match p.peek_kind(0) {
+        Operator(Op::LogicNot) => {
+            p.bump();
+            let expr = parse_select_expr_primary(p)?;
+            span.expand(p.last_span());
+            Ok(SelectExpr::Not(span, Box::new(expr)))
+        }
+        OpenDelim(Paren) => flanked(p, Paren, parse_select_expr),
+        Keyword(Kw::Binsof) => {
+            p.bump();
+            let target = flanked(p, Paren, parse_expr)?;
+            let intersect = if p.try_eat(Keyword(Kw::Intersect)) {
+                Some(flanked(p, Brace, |p| {
+                    comma_list_nonempty(p, CloseDelim(Brace), "value range", parse_value_range)
+                })?)
+            } else {
+                None
+            };
+            span.expand(p.last_span());
+            Ok(SelectExpr::Binsof {
+                span,
+                target,
+                intersect,
+            })
+        }
+        _ => Ok(SelectExpr::Expr(parse_expr_prec(p, Precedence::Unary)?)),
     }
-
-    // If we arrive here, the item starts with an expression.
-    let expr = parse_expr(p)?;
-    p.require_reported(Semicolon)?;
-    Ok(ConstraintItemData::Expr(expr))
 }
 
 struct ParallelParser<'a, 'n, R: Clone> {
@@ -4997,6 +7306,10 @@ impl<'tp, 'n> AbstractParser<'n> for BranchParser<'tp, 'n> {
         self.parser.peek(self.consumed + offset)
     }
 
+    fn peek_kind(&mut self, offset: usize) -> Token {
+        self.parser.peek_kind(self.consumed + offset)
+    }
+
     fn bump(&mut self) {
         self.last_span = self.parser.peek(self.consumed).1;
         self.consumed += 1;
@@ -5025,6 +7338,22 @@ impl<'tp, 'n> AbstractParser<'n> for BranchParser<'tp, 'n> {
     fn severity(&self) -> Severity {
         self.severity
     }
+
+    fn nesting(&mut self) -> &mut Nesting {
+        self.parser.nesting()
+    }
+
+    fn position(&self) -> usize {
+        self.parser.position() + self.consumed
+    }
+
+    fn memo(&mut self) -> &mut Memo {
+        self.parser.memo()
+    }
+
+    fn verilog(&self) -> bool {
+        self.parser.verilog()
+    }
 }
 
 fn parse_typedef<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Typedef<'n>> {
@@ -5032,12 +7361,20 @@ fn parse_typedef<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Typedef<'
     p.require_reported(Keyword(Kw::Typedef))?;
 
     // We might be a declaration of the format "typedef x;", in which case we
-    // just store what we know and continue. Also handle "typedef enum x;" syntax
-    // here to avoid the enum parsing code, which has to deal with type specifers.
+    // just store what we know and continue. Also handle "typedef enum x;",
+    // "typedef class x;", and the like here to avoid the enum, struct, and
+    // class parsing code, which has to deal with type specifers.
     {
         let mut bp = BranchParser::new(p);
-        if bp.peek(0).0 == Keyword(Kw::Enum) {
-            bp.bump();
+        match bp.peek_kind(0) {
+            Keyword(Kw::Enum) | Keyword(Kw::Struct) | Keyword(Kw::Union) | Keyword(Kw::Class) => {
+                bp.bump()
+            }
+            Keyword(Kw::Interface) if bp.peek_kind(1) == Keyword(Kw::Class) => {
+                bp.bump();
+                bp.bump();
+            }
+            _ => (),
         }
         let name = parse_identifier_name(&mut bp, "type name");
         let semi = bp.require_reported(Semicolon);
@@ -5082,7 +7419,7 @@ fn parse_port_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PortDec
     let mut span = p.peek(0).1;
 
     // Consume the port direction.
-    let dir = match as_port_direction(p.peek(0).0) {
+    let dir = match as_port_direction(p.peek_kind(0)) {
         Some(x) => {
             p.bump();
             x
@@ -5097,7 +7434,7 @@ fn parse_port_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PortDec
     };
 
     // Consume the optional net type or "var" keyword.
-    let kind = if let Some(ty) = as_net_type(p.peek(0).0) {
+    let kind = if let Some(ty) = as_net_type(p.peek_kind(0)) {
         p.bump();
         Some(VarKind::Net {
             ty,
@@ -5141,6 +7478,7 @@ fn parse_port_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PortDec
             kind,
             ty,
             names,
+            doc: doc_comment(span),
         },
     ))
 }
@@ -5167,7 +7505,7 @@ fn parse_net_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<NetDecl<
     let mut span = p.peek(0).1;
 
     // Consume the net type.
-    let net_type = match as_net_type(p.peek(0).0) {
+    let net_type = match as_net_type(p.peek_kind(0)) {
         Some(x) => {
             p.bump();
             x
@@ -5183,7 +7521,7 @@ fn parse_net_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<NetDecl<
     let strength = try_flanked(p, Paren, parse_net_strength)?;
 
     // Consume the optional "vectored" or "scalared" keywords.
-    let kind = match p.peek(0).0 {
+    let kind = match p.peek_kind(0) {
         Keyword(Kw::Vectored) => {
             p.bump();
             NetKind::Vectored
@@ -5210,9 +7548,9 @@ fn parse_net_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<NetDecl<
     // This function handles parsing of everything after the type.
     fn tail<'n>(
         p: &mut dyn AbstractParser<'n>,
-    ) -> ReportedResult<(Option<DelayControl<'n>>, Vec<VarDeclName<'n>>)> {
+    ) -> ReportedResult<(Option<Delay3<'n>>, Vec<VarDeclName<'n>>)> {
         // Parse the optional delay.
-        let delay = try_delay_control(p)?;
+        let delay = try_delay3(p)?;
 
         // Parse the names and assignments.
         let names = comma_list_nonempty(
@@ -5242,10 +7580,10 @@ fn parse_net_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<NetDecl<
 fn try_drive_strength<'n>(
     p: &mut dyn AbstractParser<'n>,
 ) -> ReportedResult<Option<(DriveStrength, DriveStrength)>> {
-    if let Some(a) = as_drive_strength(p.peek(0).0) {
+    if let Some(a) = as_drive_strength(p.peek_kind(0)) {
         p.bump();
         p.require_reported(Comma)?;
-        if let Some(b) = as_drive_strength(p.peek(0).0) {
+        if let Some(b) = as_drive_strength(p.peek_kind(0)) {
             p.bump();
             Ok(Some((a, b)))
         } else {
@@ -5261,7 +7599,7 @@ fn try_drive_strength<'n>(
 fn parse_net_strength<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<NetStrength> {
     if let Some((a, b)) = try_drive_strength(p)? {
         Ok(NetStrength::Drive(a, b))
-    } else if let Some(s) = as_charge_strength(p.peek(0).0) {
+    } else if let Some(s) = as_charge_strength(p.peek_kind(0)) {
         p.bump();
         Ok(NetStrength::Charge(s))
     } else {
@@ -5410,6 +7748,51 @@ fn parse_import_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Impor
     Ok(ImportDecl::new(span, ImportDeclData { items }))
 }
 
+/// Parse an export declaration.
+/// ```text
+/// "export" "*" "::" "*" ";"
+/// "export" package_ident "::" "*" ";"
+/// "export" package_ident "::" ident ";"
+/// ```
+fn parse_export_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ExportDecl<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Export))?;
+    let items = comma_list_nonempty(p, Semicolon, "export item", |p| {
+        // "*" "::" "*"
+        // package_ident "::" ident
+        // package_ident "::" "*"
+        let mut span = p.peek(0).1;
+        let pkg = if p.try_eat(Operator(Op::Mul)) {
+            None
+        } else {
+            Some(parse_identifier_name(p, "package name")?)
+        };
+        p.require_reported(Namespace)?;
+        let (tkn, sp) = p.peek(0);
+        let name = match tkn {
+            Operator(Op::Mul) => None,
+            Ident(n) | EscIdent(n) if pkg.is_some() => Some(Spanned::new(n, sp)),
+            _ => {
+                p.add_diag(
+                    DiagBuilder2::error(if pkg.is_some() {
+                        "expected identifier or `*` after `::` in export declaration"
+                    } else {
+                        "expected `*` after `*::` in export declaration"
+                    })
+                    .span(sp),
+                );
+                return Err(());
+            }
+        };
+        p.bump();
+        span.expand(p.last_span());
+        Ok(ExportItem::new(span, ExportItemData { pkg, name }))
+    })?;
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(ExportDecl::new(span, ExportDeclData { items }))
+}
+
 fn parse_assertion<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Assertion<'n>> {
     let mut span = p.peek(0).1;
 
@@ -5417,10 +7800,11 @@ fn parse_assertion<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Asserti
     // "#0", or "final" follows. This decides what kind of assertion we're
     // parsing.
     let null = get_name_table().intern("0", false);
-    let is_property = p.peek(1).0 == Keyword(Kw::Property);
-    let is_sequence = p.peek(1).0 == Keyword(Kw::Sequence);
-    let is_deferred_observed = p.peek(1).0 == Hashtag && p.peek(2).0 == Literal(Number(null, None));
-    let is_deferred_final = p.peek(1).0 == Keyword(Kw::Final);
+    let is_property = p.peek_kind(1) == Keyword(Kw::Property);
+    let is_sequence = p.peek_kind(1) == Keyword(Kw::Sequence);
+    let is_deferred_observed =
+        p.peek_kind(1) == Hashtag && p.peek_kind(2) == Literal(Number(null, None));
+    let is_deferred_final = p.peek_kind(1) == Keyword(Kw::Final);
     let is_deferred = is_deferred_observed || is_deferred_final;
     let deferred_mode = match is_deferred_final {
         true => AssertionDeferred::Final,
@@ -5429,7 +7813,7 @@ fn parse_assertion<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Asserti
 
     // Handle the different combinations of keywords and lookaheads from above.
 
-    let data = match p.peek(0).0 {
+    let data = match p.peek_kind(0) {
         // Concurrent Assertions
         // ---------------------
 
@@ -5578,31 +7962,78 @@ fn parse_assertion_action_block<'n>(
     }
 }
 
-fn parse_property_spec<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PropSpec> {
+fn parse_property_spec<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PropSpec<'n>> {
     let mut span = p.peek(0).1;
 
-    // TODO: Actually parse this stuff, rather than just chicken out.
-    p.recover_balanced(&[CloseDelim(Paren)], false);
-    return Ok(PropSpec);
+    // Try to parse the property as a simple boolean check. If that fails, or
+    // the property continues after the check, skip over it.
+    // TODO: Actually parse the full property expression syntax.
+    let simple = {
+        let mut bp = BranchParser::new(p);
+        match parse_simple_property_spec(&mut bp) {
+            Ok(x) if bp.peek_kind(0) == CloseDelim(Paren) && bp.severity() < Severity::Error => {
+                bp.commit();
+                Some(x)
+            }
+            _ => None,
+        }
+    };
+    if simple.is_none() {
+        p.recover_balanced(&[CloseDelim(Paren)], false);
+    }
+    span.expand(p.last_span());
+    Ok(PropSpec { span, simple })
+}
+
+fn parse_simple_property_spec<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<SimplePropSpec<'n>> {
+    // Parse the optional event expression.
+    let clock = if p.try_eat(At) {
+        Some(parse_event_expr(p, EventPrecedence::Max)?)
+    } else {
+        None
+    };
+
+    // Parse the optional "disable iff" clause.
+    let disable = if p.try_eat(Keyword(Kw::Disable)) {
+        p.require_reported(Keyword(Kw::Iff))?;
+        Some(flanked(p, Paren, parse_expr)?)
+    } else {
+        None
+    };
 
-    // // Parse the optional event expression.
-    // let event = if p.try_eat(At) {
-    //     Some(parse_event_expr(p, EventPrecedence::Min)?)
-    // } else {
-    //     None
-    // };
+    // Parse the check itself.
+    let (antecedent, expr) = parse_simple_property_check(p)?;
+    Ok(SimplePropSpec {
+        clock,
+        disable,
+        antecedent,
+        expr,
+    })
+}
 
-    // // Parse the optional "disable iff" clause.
-    // let disable = if p.try_eat(Keyword(Kw::Disable)) {
-    //     p.require_reported(Keyword(Kw::Iff))?;
-    //     Some(flanked(p, Paren, parse_expr)?)
-    // } else {
-    //     None
-    // };
+fn parse_simple_property_check<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<(Option<Expr<'n>>, Expr<'n>)> {
+    // Handle a parenthesized check, as in `(a |-> b)`.
+    if p.peek_kind(0) == OpenDelim(Paren) {
+        let mut bp = BranchParser::new(p);
+        if let Ok(x) = flanked(&mut bp, Paren, parse_simple_property_check) {
+            if bp.peek_kind(0) == CloseDelim(Paren) && bp.severity() < Severity::Error {
+                bp.commit();
+                return Ok(x);
+            }
+        }
+    }
 
-    // // Parse the property expression.
-    // let prop = parse_propexpr(p)?;
-    // Ok(PropSpec)
+    // Parse the expression and an optional overlapping implication.
+    let expr = parse_expr(p)?;
+    if p.try_eat(Operator(Op::SeqImplOl)) {
+        Ok((Some(expr), parse_expr(p)?))
+    } else {
+        Ok((None, expr))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -5652,7 +8083,7 @@ fn parse_propexpr_prec<'n>(
         parse_propexpr_seq(p, precedence)
     });
     pp.add_greedy("property expression", move |p| {
-        parse_propexpr_nonseq(p, precedence)
+        nested(p, |p| parse_propexpr_nonseq(p, precedence))
     });
     let data = pp.finish(p, "sequence or primary property expression")?;
 
@@ -5669,7 +8100,7 @@ fn parse_propexpr_nonseq<'n>(
     precedence: PropSeqPrecedence,
 ) -> ReportedResult<PropExprData<'n>> {
     // Handle the trivial case of expressions introduced by a symbol or keyword.
-    match p.peek(0).0 {
+    match p.peek_kind(0) {
         // Parenthesized property expression.
         OpenDelim(Paren) => return flanked(p, Paren, parse_propexpr).map(|pe| pe.data),
 
@@ -5701,7 +8132,7 @@ fn parse_propexpr_seq<'n>(
     precedence: PropSeqPrecedence,
 ) -> ReportedResult<PropExprData<'n>> {
     // Consume a strong, weak, or regular sequence operator.
-    let (seqop, seqexpr) = match p.peek(0).0 {
+    let (seqop, seqexpr) = match p.peek_kind(0) {
         Keyword(Kw::Strong) => {
             p.bump();
             (PropSeqOp::Strong, flanked(p, Paren, parse_seqexpr)?)
@@ -5716,7 +8147,7 @@ fn parse_propexpr_seq<'n>(
     // Handle the operators that have a sequence expression on their left hand
     // side.
     if precedence <= PropSeqPrecedence::ImplFollow {
-        if let Some(op) = match p.peek(0).0 {
+        if let Some(op) = match p.peek_kind(0) {
             Operator(Op::SeqImplOl) => Some(PropSeqBinOp::ImplOverlap),
             Operator(Op::SeqImplNol) => Some(PropSeqBinOp::ImplNonoverlap),
             Operator(Op::SeqFollowOl) => Some(PropSeqBinOp::FollowOverlap),
@@ -5740,7 +8171,7 @@ fn parse_propexpr_suffix<'n>(
 ) -> ReportedResult<PropExpr<'n>> {
     // Handle the binary operators that have a property expression on both their
     // left and right hand side.
-    if let Some((op, prec, rassoc)) = match p.peek(0).0 {
+    if let Some((op, prec, rassoc)) = match p.peek_kind(0) {
         Keyword(Kw::Or) => Some((PropBinOp::Or, PropSeqPrecedence::Or, false)),
         Keyword(Kw::And) => Some((PropBinOp::And, PropSeqPrecedence::And, false)),
         Keyword(Kw::Until) => Some((PropBinOp::Until, PropSeqPrecedence::Until, true)),
@@ -5835,12 +8266,12 @@ fn parse_seqexpr_suffix<'n>(
 }
 
 fn parse_seqrep<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SeqRep<'n>> {
-    match p.peek(0).0 {
+    match p.peek_kind(0) {
         // [*]
         // [* expr]
         Operator(Op::Mul) => {
             p.bump();
-            if p.peek(0).0 == CloseDelim(Brack) {
+            if p.peek_kind(0) == CloseDelim(Brack) {
                 Ok(SeqRep::ConsecStar)
             } else {
                 Ok(SeqRep::Consec(parse_expr(p)?))
@@ -5917,6 +8348,37 @@ fn parse_inst<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::Inst<'n
     ))
 }
 
+fn parse_bind<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::Bind<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Bind))?;
+
+    // Consume the target scope or instance.
+    let target = parse_expr_prec(p, Precedence::Postfix)?;
+
+    // Consume the optional list of target instances.
+    let mut target_insts = vec![];
+    if p.try_eat(Colon) {
+        loop {
+            target_insts.push(parse_expr_prec(p, Precedence::Postfix)?);
+            if !p.try_eat(Comma) {
+                break;
+            }
+        }
+    }
+
+    // Consume the instantiation.
+    let inst = parse_inst(p)?;
+    span.expand(p.last_span());
+    Ok(ast::Bind::new(
+        span,
+        ast::BindData {
+            target,
+            target_insts,
+            inst,
+        },
+    ))
+}
+
 fn parse_var_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::VarDecl<'n>> {
     let mut span = p.peek(0).1;
 
@@ -5927,7 +8389,7 @@ fn parse_var_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::Var
     let var = p.try_eat(Keyword(Kw::Var));
 
     // Parse the optional lifetime specifier.
-    let lifetime = as_lifetime(p.peek(0).0);
+    let lifetime = as_lifetime(p.peek_kind(0));
     if lifetime.is_some() {
         p.bump();
     }
@@ -5965,6 +8427,7 @@ fn parse_var_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ast::Var
         ast::VarDeclData {
             konst: konst,
             var: var,
+            rand: false,
             lifetime: lifetime,
             ty: ty,
             names: names,
@@ -6013,9 +8476,9 @@ fn parse_param_decl<'n>(
     // end of the list has been reached. The list terminates if after the "," a
     // "parameter", "localparam", "type", or explicit type follows.
     let predicate = FuncPredicate {
-        match_func: |p| match p.peek(0).0 {
+        match_func: |p| match p.peek_kind(0) {
             Semicolon | CloseDelim(Paren) => true,
-            Comma => match p.peek(1).0 {
+            Comma => match p.peek_kind(1) {
                 Keyword(Kw::Parameter) | Keyword(Kw::Localparam) => true,
                 _ => false,
             },
@@ -6093,7 +8556,11 @@ fn parse_param_decl<'n>(
     span.expand(p.last_span());
     Ok(ast::ParamDecl::new(
         span,
-        ast::ParamDeclData { local, kind },
+        ast::ParamDeclData {
+            local,
+            kind,
+            doc: doc_comment(span),
+        },
     ))
 }
 
@@ -6116,3 +8583,136 @@ fn try_builtin_system_task<'n>(
         _ => None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn token(i: usize) -> TokenAndSpan {
+        (
+            Literal(Lit::Str(get_name_table().intern(&i.to_string(), true))),
+            INVALID_SPAN,
+        )
+    }
+
+    #[test]
+    fn token_ring_wraps_and_grows() {
+        let mut ring = TokenRing::new();
+        let mut next = 0;
+        let mut front = 0;
+        // Interleave pushes and pops such that the ring wraps around, then
+        // push far beyond the initial capacity.
+        for round in 0..5 {
+            for _ in 0..(TokenRing::INITIAL_CAPACITY - 3) << round {
+                ring.push_back(token(next));
+                next += 1;
+            }
+            for i in 0..ring.len() {
+                assert_eq!(ring.get(i).0, token(front + i).0);
+            }
+            for _ in 0..10 {
+                assert_eq!(ring.pop_front().map(|t| t.0), Some(token(front).0));
+                front += 1;
+            }
+        }
+        assert_eq!(ring.len(), next - front);
+        assert_eq!(ring.back().map(|t| t.0), Some(token(next - 1).0));
+    }
+
+    #[test]
+    fn node_ids() {
+        let source = get_source_manager().add_anonymous("module foo; assign a = b + c; endmodule");
+        let arena = ast::Arena::default();
+        let pp = Preprocessor::new(source, &[], &[]);
+        let file = parse(Lexer::new(pp), &arena).unwrap();
+        let mut spans = HashMap::new();
+        ast::collect_spans(&file, &mut spans);
+        assert_eq!(spans[&file.id], file.span);
+        let module = match file.items[0].data {
+            ast::ItemData::ModuleDecl(ref m) => m,
+            _ => panic!("expected a module"),
+        };
+        assert_eq!(spans[&module.id], module.span);
+
+        // Each node must have an ID of its own.
+        fn count<'a>(node: &'a dyn ast::AnyNode<'a>) -> usize {
+            let mut n = 1;
+            node.for_each_child(&mut |child| n += count(child));
+            n
+        }
+        assert_eq!(spans.len(), count(&file));
+    }
+
+    #[test]
+    fn visitor_mut() {
+        struct Rename(Name, Name);
+        impl<'a> ast::VisitorMut<'a> for Rename {
+            fn pre_visit_expr(&mut self, node: &mut ast::Expr<'a>) -> bool {
+                if let ast::IdentExpr(ref mut name) = node.data {
+                    if name.value == self.0 {
+                        name.value = self.1;
+                    }
+                }
+                true
+            }
+        }
+
+        struct Collect(Vec<Name>);
+        impl<'a> ast::Visitor<'a> for Collect {
+            fn pre_visit_expr(&mut self, node: &'a ast::Expr<'a>) -> bool {
+                if let ast::IdentExpr(name) = node.data {
+                    self.0.push(name.value);
+                }
+                true
+            }
+        }
+
+        let source = get_source_manager().add_anonymous("module foo; assign a = b + c; endmodule");
+        let arena = ast::Arena::default();
+        let pp = Preprocessor::new(source, &[], &[]);
+        let mut file = parse(Lexer::new(pp), &arena).unwrap();
+        let name = |s| get_name_table().intern(s, true);
+        ast::WalkVisitorMut::walk_mut(&mut file, &mut Rename(name("b"), name("x")));
+        let mut names = Collect(vec![]);
+        ast::WalkVisitor::walk(&file, &mut names);
+        assert_eq!(names.0, vec![name("a"), name("x"), name("c")]);
+    }
+
+    #[test]
+    fn parse_str_nested_index() {
+        // Each level used to parse the levels below it twice, once as the
+        // dimension of a speculative cast type, and once as an expression.
+        let depth = 24;
+        let input = format!(
+            "module foo; assign a = {}0{}; endmodule",
+            "x[".repeat(depth),
+            "]".repeat(depth)
+        );
+        let arena = ast::Arena::default();
+        let (file, diags, _source) = parse_str(&input, &arena);
+        assert!(file.is_some());
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn parse_str_pathological() {
+        let inputs = [
+            "",
+            "p'ackage e",
+            "module foo; \"",
+            "module foo;\n`ifdef FOO\n",
+            "`define FOO(x) x `FOO(x)\n`FOO(42)",
+        ];
+        for input in &inputs {
+            let arena = ast::Arena::default();
+            let (_, diags, _source) = parse_str(input, &arena);
+            let output: Vec<_> = diags.iter().map(|d| d.to_string()).collect();
+            assert!(
+                input.is_empty() || !output.is_empty(),
+                "no diagnostics for {:?}",
+                input
+            );
+        }
+    }
+}
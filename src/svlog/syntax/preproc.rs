@@ -5,14 +5,24 @@
 //! resolution.
 
 use crate::cat::*;
+use crate::token::Standard;
 use moore_common::errors::{DiagBuilder2, DiagResult2};
 use moore_common::source::*;
-use std::{collections::HashMap, fmt, path::Path, rc::Rc};
+use std::path::PathBuf;
+use std::{cell::RefCell, collections::HashMap, fmt, path::Path, rc::Rc, sync::Arc};
 
 use once_cell::sync::Lazy;
 
 type TokenAndSpan = (CatTokenKind, Span);
 
+/// The maximum number of macros expanded in a row, without reading a token
+/// from an input file in between. Exceeded by recursive macros.
+const MAX_MACRO_EXPANSIONS: usize = 1 << 16;
+
+/// The maximum nesting depth of included files. Exceeded by recursive
+/// includes.
+const MAX_INCLUDE_DEPTH: usize = 200;
+
 pub struct Preprocessor<'a> {
     /// The stack of input files. Tokens are taken from the topmost stream until
     /// the end of input, at which point the stream is popped and the process
@@ -22,14 +32,17 @@ pub struct Preprocessor<'a> {
     /// Keeping these around ensures that all emitted tokens remain valid (and
     /// point to valid memory locations) at least until the preprocessor is
     /// dropped.
-    contents: Vec<Rc<dyn SourceContent>>,
+    contents: Vec<Arc<dyn SourceContent>>,
     /// The current token, or None if either the end of the stream has been
     /// encountered, or at the beginning when no token has been read yet.
     token: Option<TokenAndSpan>,
     /// The defined macros.
-    macro_defs: HashMap<String, Macro>,
+    macro_defs: MacroTable,
     /// The stack used to inject expanded macros into the token stream.
     macro_stack: Vec<TokenAndSpan>,
+    /// The number of macros expanded since the last token was read from an
+    /// input file.
+    expansions: usize,
     /// The paths that are searched for included files, besides the current
     /// file's directory.
     include_paths: &'a [&'a Path],
@@ -39,6 +52,23 @@ pub struct Preprocessor<'a> {
     defcond_stack: Vec<Defcond>,
     /// Currently enabled directives.
     dirs: Directives,
+    /// Whether the current token was read from an input file, as opposed to
+    /// a macro expansion.
+    from_stream: bool,
+    /// Whether to emit `` `line`` directives to keep the line numbers of the
+    /// output in sync with the input files.
+    line_markers: bool,
+    /// The input file, line, and include depth at which the output continues,
+    /// as of the last emitted token.
+    line_state: Option<(Source, usize, usize)>,
+    /// Whether the last emitted token was a line break.
+    at_line_start: bool,
+    /// A token to be emitted after a `` `line`` directive.
+    pending: Option<TokenAndSpan>,
+    /// The revision of the standard the input is processed as.
+    standard: Standard,
+    /// Where macro definitions, macro expansions, and includes are recorded.
+    xref: Option<Xref>,
 }
 
 impl<'a> Preprocessor<'a> {
@@ -47,35 +77,23 @@ impl<'a> Preprocessor<'a> {
         source: Source,
         include_paths: &'a [&'a Path],
         macro_defs: &'a [(&'a str, Option<&'a str>)],
+    ) -> Preprocessor<'a> {
+        Self::with_macros(source, include_paths, MacroTable::new(macro_defs))
+    }
+
+    /// Create a new preprocessor for the given source file, which defines and
+    /// expands macros in the given table.
+    ///
+    /// Use this to process multiple files as one compilation unit, where
+    /// macros defined in one file are visible in the files that follow it.
+    pub fn with_macros(
+        source: Source,
+        include_paths: &'a [&'a Path],
+        macro_defs: MacroTable,
     ) -> Preprocessor<'a> {
         let content = source.get_content();
         let content_unbound = unsafe { &*(content.as_ref() as *const dyn SourceContent) };
         let iter = content_unbound.iter();
-        let macro_defs = macro_defs
-            .into_iter()
-            .map(|(name, value)| {
-                let body = match value {
-                    Some(value) => {
-                        // Create dummy sources for each user defined macro.
-                        let src = get_source_manager().add_anonymous(*value);
-                        let span = Span::new(src, 0, value.len());
-                        Cat::new(Box::new(value.char_indices()))
-                            .map(|x| (x.0, span))
-                            .collect()
-                    }
-                    None => Vec::new(),
-                };
-                (
-                    name.to_string(),
-                    Macro {
-                        name: name.to_string(),
-                        span: INVALID_SPAN,
-                        args: Vec::new(),
-                        body: body,
-                    },
-                )
-            })
-            .collect();
         Preprocessor {
             stack: vec![Stream {
                 source: source,
@@ -85,15 +103,56 @@ impl<'a> Preprocessor<'a> {
             token: None,
             macro_defs,
             macro_stack: Vec::new(),
+            expansions: 0,
             include_paths: include_paths,
             defcond_stack: Vec::new(),
             dirs: Default::default(),
+            from_stream: false,
+            line_markers: false,
+            line_state: None,
+            at_line_start: true,
+            pending: None,
+            standard: Default::default(),
+            xref: None,
         }
     }
 
+    /// Emit `` `line`` directives at the beginning of the output, whenever
+    /// the input switches to or returns from an included file, and whenever
+    /// the output lines get out of sync with the input lines, e.g. after a
+    /// macro definition or an inactive `` `ifdef`` block.
+    pub fn with_line_markers(mut self) -> Self {
+        self.line_markers = true;
+        self
+    }
+
+    /// Process the input as a specific revision of the standard.
+    ///
+    /// Directives introduced in later revisions are reported as errors. The
+    /// revision also selects the keywords, unless overridden with
+    /// `` `begin_keywords``.
+    pub fn with_standard(mut self, standard: Standard) -> Self {
+        self.standard = standard;
+        self
+    }
+
+    /// Record the macro definitions, macro expansions, and includes in a
+    /// cross-reference.
+    pub fn with_xref(mut self, xref: Xref) -> Self {
+        self.xref = Some(xref);
+        self
+    }
+
+    /// Get the revision of the standard whose keywords are currently in
+    /// effect.
+    pub fn keywords(&self) -> Standard {
+        self.dirs.keywords.last().cloned().unwrap_or(self.standard)
+    }
+
     /// Advance to the next token in the input stream.
     fn bump(&mut self) {
         self.token = self.macro_stack.pop();
+        self.from_stream = self.token.is_none();
         if self.token.is_some() {
             return;
         }
@@ -108,6 +167,7 @@ impl<'a> Preprocessor<'a> {
             if self.token.is_none() {
                 self.stack.pop();
             } else {
+                self.expansions = 0;
                 break;
             }
         }
@@ -123,6 +183,17 @@ impl<'a> Preprocessor<'a> {
             .map(|x| *x)
             .unwrap_or(Directive::Unknown);
 
+        // Reject directives which the selected standard does not provide.
+        if dir.standard() > self.standard && !self.is_inactive() {
+            return Err(DiagBuilder2::error(format!(
+                "{} requires {} or later",
+                dir,
+                dir.standard()
+            ))
+            .span(span)
+            .add_note(format!("The input is processed as {}", self.standard)));
+        }
+
         match dir {
             Directive::Include => {
                 if self.is_inactive() {
@@ -189,17 +260,45 @@ impl<'a> Preprocessor<'a> {
                 // stream stack.
                 // TODO: Search only system location if `include <...> is used
                 let included_source = match self.open_include(&filename, &span.source.get_path()) {
-                    Some(src) => src,
-                    None => {
-                        // TODO: Add notes to the message indicating which files have been tried.
-                        return Err(DiagBuilder2::fatal(format!(
+                    Ok(src) => src,
+                    Err(searched) => {
+                        let mut d = DiagBuilder2::fatal(format!(
                             "cannot open included file \"{}\"",
                             filename
                         ))
-                        .span(Span::union(name_p, name_q)));
+                        .span(Span::union(name_p, name_q))
+                        .add_note("Searched the following directories:");
+                        for dir in searched {
+                            let dir = if dir.as_os_str().is_empty() {
+                                Path::new(".")
+                            } else {
+                                &dir
+                            };
+                            d = d.add_note(format!("    {}", dir.display()));
+                        }
+                        if self.include_paths.is_empty() {
+                            d = d.add_note(
+                                "Use `-I <dir>` or `+incdir+<dir>` to add directories to the \
+                                 include search path",
+                            );
+                        }
+                        return Err(d);
                     }
                 };
 
+                if self.stack.len() >= MAX_INCLUDE_DEPTH {
+                    self.bump(); // consume the closing symbol
+                    return Err(DiagBuilder2::fatal(format!(
+                        "included files nested too deeply; more than {} levels",
+                        MAX_INCLUDE_DEPTH
+                    ))
+                    .span(span)
+                    .add_note("The file may directly or indirectly include itself"));
+                }
+
+                if let Some(ref xref) = self.xref {
+                    xref.add_include(included_source, span);
+                }
                 let content = included_source.get_content();
                 let content_unbound = unsafe { &*(content.as_ref() as *const dyn SourceContent) };
                 let iter = content_unbound.iter();
@@ -225,8 +324,14 @@ impl<'a> Preprocessor<'a> {
                 }
 
                 let makro = self.handle_macro_definition(span)?;
+                if let Some(ref xref) = self.xref {
+                    xref.add_define(&makro.name, makro.span);
+                }
 
-                self.macro_defs.insert(makro.name.clone(), makro);
+                self.macro_defs
+                    .0
+                    .borrow_mut()
+                    .insert(makro.name.clone(), Rc::new(makro));
                 return Ok(());
             }
 
@@ -252,7 +357,7 @@ impl<'a> Preprocessor<'a> {
                 };
 
                 // Remove the macro definition.
-                self.macro_defs.remove(&name);
+                self.macro_defs.0.borrow_mut().remove(&name);
                 return Ok(());
             }
 
@@ -260,7 +365,7 @@ impl<'a> Preprocessor<'a> {
                 if self.is_inactive() {
                     return Ok(());
                 }
-                self.macro_defs.clear();
+                self.macro_defs.0.borrow_mut().clear();
             }
 
             Directive::Ifdef | Directive::Ifndef | Directive::Elsif => {
@@ -281,7 +386,7 @@ impl<'a> Preprocessor<'a> {
                         .span(span));
                     }
                 };
-                let exists = self.macro_defs.contains_key(&name);
+                let exists = self.macro_defs.0.borrow().contains_key(&name);
 
                 // Depending on the directive, modify the define conditional
                 // stack.
@@ -364,13 +469,28 @@ impl<'a> Preprocessor<'a> {
                 if self.is_inactive() {
                     return Ok(());
                 }
-                // TODO(fschuiki): This unsafe here is ugly. Why is this needed
-                // again?
-                if let Some(ref makro) = unsafe { &*(self as *const Preprocessor) }
-                    .macro_defs
-                    .get(dir_name)
-                {
+                let makro = self.macro_defs.0.borrow().get(dir_name).cloned();
+                if let Some(ref makro) = makro {
+                    self.expansions += 1;
+                    if self.expansions > MAX_MACRO_EXPANSIONS {
+                        // Abandon the expansion altogether, such that the
+                        // remaining input can be processed.
+                        self.macro_stack.clear();
+                        return Err(DiagBuilder2::fatal(format!(
+                            "expansion of `{}` does not terminate",
+                            dir_name
+                        ))
+                        .span(span)
+                        .add_note(format!(
+                            "More than {} macros were expanded in a row; the macro may directly \
+                             or indirectly expand to itself",
+                            MAX_MACRO_EXPANSIONS
+                        )));
+                    }
                     let args = self.handle_macro_expansion_args(makro, span)?;
+                    if let Some(ref xref) = self.xref {
+                        xref.add_expansion(&makro.name, makro.span, span);
+                    }
 
                     // Now we have a problem. All the tokens of the macro name
                     // have been parsed and we would like to continue by
@@ -540,7 +660,7 @@ impl<'a> Preprocessor<'a> {
                     };
 
                     // Parse the version.
-                    let spec = match KeywordsDirective::from_str(&spec) {
+                    let spec = match Standard::parse(&spec) {
                         Some(spec) => spec,
                         _ => {
                             return Err(DiagBuilder2::fatal(format!(
@@ -700,23 +820,56 @@ impl<'a> Preprocessor<'a> {
         );
     }
 
-    fn open_include(&mut self, filename: &str, current_file: &str) -> Option<Source> {
-        // println!("Resolving include '{}' from '{}'", filename, current_file);
-        let first = [Path::new(current_file)
+    /// Locate an included file.
+    ///
+    /// Searches the directory of the current file first, followed by the
+    /// include paths. Returns the directories searched if the file cannot be
+    /// found.
+    fn open_include(&mut self, filename: &str, current_file: &str) -> Result<Source, Vec<PathBuf>> {
+        let first = Path::new(current_file)
             .parent()
-            .expect("current file path must have a valid parent")];
-        let prefices = first.iter().chain(self.include_paths.iter());
+            .expect("current file path must have a valid parent");
+        let prefices = std::iter::once(first).chain(self.include_paths.iter().cloned());
         let sm = get_source_manager();
+        let mut searched: Vec<PathBuf> = vec![];
         for prefix in prefices {
+            if searched.iter().any(|p| p == prefix) {
+                continue;
+            }
             let mut buf = prefix.to_path_buf();
             buf.push(filename);
-            // println!("  trying {}", buf.to_str().unwrap());
-            let src = sm.open(buf.to_str().unwrap());
-            if src.is_some() {
-                return src;
+            if let Some(src) = sm.open(buf.to_str().unwrap()) {
+                return Ok(src);
             }
+            searched.push(prefix.to_path_buf());
         }
-        return None;
+        Err(searched)
+    }
+
+    /// Determine the `` `line`` directive to be emitted before a token.
+    ///
+    /// The `depth` is the number of input files on the stack at the time the
+    /// token was read.
+    fn line_marker_before(&mut self, token: TokenAndSpan, depth: usize) -> Option<TokenAndSpan> {
+        let (kind, span) = token;
+        let line = span.begin().human_line();
+        let marker = match self.line_state {
+            Some((source, expected, d)) if source == span.source && d == depth => {
+                if self.at_line_start && line != expected {
+                    Some(line_marker(span.source, line, 0, false))
+                } else {
+                    None
+                }
+            }
+            Some((_, _, d)) => {
+                let level = if depth > d { 1 } else { 2 };
+                Some(line_marker(span.source, line, level, !self.at_line_start))
+            }
+            None => Some(line_marker(span.source, line, 0, !self.at_line_start)),
+        };
+        let next_line = if kind == Newline { line + 1 } else { line };
+        self.line_state = Some((span.source, next_line, depth));
+        marker
     }
 
     /// Check whether we are inside a disabled define conditional. That is,
@@ -1091,6 +1244,9 @@ impl<'a> Iterator for Preprocessor<'a> {
     fn next(&mut self) -> Option<DiagResult2<TokenAndSpan>> {
         // In case this is the first call to next(), the token has not been
         // populated yet. In this case we need to artificially bump the lexer.
+        if let Some(tkn) = self.pending.take() {
+            return Some(Ok(tkn));
+        }
         if self.token.is_none() {
             self.bump();
         }
@@ -1138,13 +1294,28 @@ impl<'a> Iterator for Preprocessor<'a> {
                 _ => {
                     // All tokens other than preprocessor directives are
                     // emitted, unless we're currently inside a disabled define
-                    // conditional.
-                    if self.is_inactive() {
+                    // conditional. The end of the input is always reached,
+                    // even inside an unterminated conditional.
+                    if self.is_inactive() && self.token.is_some() {
                         self.bump();
                     } else {
-                        let tkn = self.token.map(|x| Ok(x));
+                        let tkn = self.token;
+                        let from_stream = self.from_stream;
+                        let depth = self.stack.len();
                         self.bump();
-                        return tkn;
+                        if let (true, Some(tkn)) = (self.line_markers, tkn) {
+                            let marker = if from_stream {
+                                self.line_marker_before(tkn, depth)
+                            } else {
+                                None
+                            };
+                            self.at_line_start = tkn.0 == Newline;
+                            if let Some(marker) = marker {
+                                self.pending = Some(tkn);
+                                return Some(Ok(marker));
+                            }
+                        }
+                        return tkn.map(|x| Ok(x));
                     }
                 }
             }
@@ -1157,6 +1328,24 @@ struct Stream<'a> {
     iter: Cat<'a>,
 }
 
+/// Create a `` `line`` directive as a single text token.
+///
+/// The `level` is 1 when entering an included file, 2 when returning from
+/// one, and 0 otherwise. If `newline` is set, the directive is preceded by a
+/// line break to place it on a line of its own.
+fn line_marker(source: Source, line: usize, level: usize, newline: bool) -> TokenAndSpan {
+    let text = format!(
+        "{}`line {} \"{}\" {}\n",
+        if newline { "\n" } else { "" },
+        line,
+        source.get_path(),
+        level
+    );
+    let len = text.len();
+    let src = get_source_manager().add_anonymous(text);
+    (Text, Span::new(src, 0, len))
+}
+
 /// The different compiler directives recognized by the preprocessor.
 #[derive(Debug, Clone, Copy)]
 enum Directive {
@@ -1184,6 +1373,20 @@ enum Directive {
     Unknown,
 }
 
+impl Directive {
+    /// Get the revision of the standard which introduced this directive.
+    fn standard(self) -> Standard {
+        match self {
+            Directive::Undefineall | Directive::CurrentFile | Directive::CurrentLine => {
+                Standard::Ieee1800_2009
+            }
+            Directive::BeginKeywords | Directive::EndKeywords => Standard::Ieee1364_2005,
+            Directive::Ifndef | Directive::Elsif | Directive::Line => Standard::Ieee1364_2001,
+            _ => Standard::Ieee1364_1995,
+        }
+    }
+}
+
 impl fmt::Display for Directive {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -1239,6 +1442,134 @@ static DIRECTIVES_TABLE: Lazy<HashMap<&'static str, Directive>> = Lazy::new(|| {
     table
 });
 
+/// A table of macro definitions.
+///
+/// Clones of a table refer to the same definitions, such that macros defined
+/// while preprocessing one file remain visible in the next.
+#[derive(Clone, Default)]
+pub struct MacroTable(Rc<RefCell<HashMap<String, Rc<Macro>>>>);
+
+impl MacroTable {
+    /// Create a table with a list of predefined macros, given as name and
+    /// optional value.
+    pub fn new(macro_defs: &[(&str, Option<&str>)]) -> Self {
+        let macro_defs = macro_defs
+            .iter()
+            .map(|(name, value)| {
+                let body = match value {
+                    Some(value) => {
+                        // Create dummy sources for each user defined macro.
+                        let src = get_source_manager().add_anonymous(*value);
+                        Cat::new(Box::new(value.char_indices()))
+                            .map(|x| (x.0, Span::new(src, x.1, x.2)))
+                            .collect()
+                    }
+                    None => Vec::new(),
+                };
+                (
+                    name.to_string(),
+                    Rc::new(Macro {
+                        name: name.to_string(),
+                        span: INVALID_SPAN,
+                        args: Vec::new(),
+                        body: body,
+                    }),
+                )
+            })
+            .collect();
+        MacroTable(Rc::new(RefCell::new(macro_defs)))
+    }
+}
+
+/// A cross-reference of macros and included files.
+///
+/// Maps each macro definition to the places where it is expanded, and each
+/// included file to the `` `include`` directives that include it. Clones of a
+/// cross-reference record into the same tables, such that it can cover
+/// several files which are preprocessed independently.
+#[derive(Clone, Default)]
+pub struct Xref(Rc<RefCell<XrefTables>>);
+
+#[derive(Default)]
+struct XrefTables {
+    /// The macro definitions, by name and the span of the name in the
+    /// `` `define`` directive.
+    macros: HashMap<(String, Span), Vec<Span>>,
+    /// The included files, and the directives that include them.
+    includes: HashMap<Source, Vec<Span>>,
+}
+
+impl Xref {
+    fn add_define(&self, name: &str, span: Span) {
+        self.0
+            .borrow_mut()
+            .macros
+            .entry((name.to_string(), span))
+            .or_default();
+    }
+
+    fn add_expansion(&self, name: &str, def: Span, span: Span) {
+        self.0
+            .borrow_mut()
+            .macros
+            .entry((name.to_string(), def))
+            .or_default()
+            .push(span);
+    }
+
+    fn add_include(&self, source: Source, span: Span) {
+        self.0
+            .borrow_mut()
+            .includes
+            .entry(source)
+            .or_default()
+            .push(span);
+    }
+}
+
+impl fmt::Display for Xref {
+    /// Print one paragraph per macro definition, sorted by name, listing its
+    /// expansion sites, and one per included file, sorted by path, listing
+    /// its includers.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let tables = self.0.borrow();
+        let location = |span: Span| {
+            if span.source == INVALID_SOURCE {
+                return String::from("<command line>");
+            }
+            let (line, col, _) = span.begin().human();
+            format!("{}:{}:{}", span.source.get_path(), line, col)
+        };
+        let mut macros: Vec<_> = tables.macros.iter().collect();
+        macros
+            .sort_by_key(|&((name, span), _)| (name.as_str(), span.source.get_path(), span.begin));
+        for ((name, def), uses) in macros {
+            writeln!(f, "`define {} at {}", name, location(*def))?;
+            let mut uses = uses.clone();
+            uses.sort_by_key(|span| (span.source.get_path(), span.begin));
+            uses.dedup();
+            if uses.is_empty() {
+                writeln!(f, "    never expanded")?;
+            }
+            for span in uses {
+                writeln!(f, "    expanded at {}", location(span))?;
+            }
+        }
+        let mut includes: Vec<_> = tables.includes.iter().collect();
+        includes.sort_by_key(|&(source, _)| source.get_path());
+        for (source, uses) in includes {
+            writeln!(f, "`include {}", source.get_path())?;
+            let mut uses = uses.clone();
+            uses.sort_by_key(|span| (span.source.get_path(), span.begin));
+            uses.dedup();
+            for span in uses {
+                writeln!(f, "    included at {}", location(span))?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct Macro {
     name: String,
@@ -1275,55 +1606,22 @@ enum Defcond {
 struct Directives {
     celldefine: bool,
     default_nettype: Option<TokenAndSpan>,
-    keywords: Vec<KeywordsDirective>,
+    keywords: Vec<Standard>,
     unconnected_drive: Option<UnconnectedDrive>,
 }
 
-#[allow(non_camel_case_types)]
-#[derive(Debug)]
-enum KeywordsDirective {
-    Ieee1800_2009,
-    Ieee1800_2005,
-    Ieee1364_2005,
-    Ieee1364_2001,
-    Ieee1364_2001_Noconfig,
-    Ieee1364_1995,
-}
-
 #[derive(Debug)]
 enum UnconnectedDrive {
     Pull0,
     Pull1,
 }
 
-impl KeywordsDirective {
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "1800-2009" => Some(Self::Ieee1800_2009),
-            "1800-2005" => Some(Self::Ieee1800_2005),
-            "1364-2005" => Some(Self::Ieee1364_2005),
-            "1364-2001" => Some(Self::Ieee1364_2001),
-            "1364-2001-noconfig" => Some(Self::Ieee1364_2001_Noconfig),
-            "1364-1995" => Some(Self::Ieee1364_1995),
-            _ => None,
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn preproc(input: &str) -> Preprocessor {
-        use std::cell::Cell;
-        thread_local!(static INDEX: Cell<usize> = Cell::new(0));
-        let sm = get_source_manager();
-        let idx = INDEX.with(|i| {
-            let v = i.get();
-            i.set(v + 1);
-            v
-        });
-        let source = sm.add(&format!("test_{}.sv", idx), input);
+        let source = get_source_manager().add_anonymous(input);
         Preprocessor::new(source, &[], &[])
     }
 
@@ -1336,9 +1634,9 @@ mod tests {
     #[test]
     fn include() {
         let sm = get_source_manager();
-        sm.add("other.sv", "bar\n");
-        sm.add("test.sv", "foo\n`include \"other.sv\"\nbaz");
-        let pp = Preprocessor::new(sm.open("test.sv").unwrap(), &[], &[]);
+        sm.add("include_other.sv", "bar\n");
+        sm.add("include.sv", "foo\n`include \"include_other.sv\"\nbaz");
+        let pp = Preprocessor::new(sm.open("include.sv").unwrap(), &[], &[]);
         let actual: Vec<_> = pp.map(|x| x.unwrap().0).collect();
         assert_eq!(actual, &[Text, Newline, Text, Newline, Newline, Text,]);
     }
@@ -1346,12 +1644,15 @@ mod tests {
     #[test]
     fn include_and_define() {
         let sm = get_source_manager();
-        sm.add("other.sv", "/* World */\n`define foo 42\nbar");
         sm.add(
-            "test.sv",
-            "// Hello\n`include \"other.sv\"\n`foo something\n",
+            "include_and_define_other.sv",
+            "/* World */\n`define foo 42\nbar",
         );
-        let pp = Preprocessor::new(sm.open("test.sv").unwrap(), &[], &[]);
+        sm.add(
+            "include_and_define.sv",
+            "// Hello\n`include \"include_and_define_other.sv\"\n`foo something\n",
+        );
+        let pp = Preprocessor::new(sm.open("include_and_define.sv").unwrap(), &[], &[]);
         let actual: String = pp
             .map(|x| {
                 let x = x.unwrap();
@@ -1362,11 +1663,33 @@ mod tests {
         assert_eq!(actual, "// Hello\n/* World */\nbar\n42 something\n");
     }
 
+    #[test]
+    fn xref() {
+        let sm = get_source_manager();
+        sm.add("xref_other.sv", "`define foo 42\n`define bar\n");
+        sm.add("xref.sv", "`include \"xref_other.sv\"\n`foo `foo\n");
+        let xref = Xref::default();
+        let pp = Preprocessor::new(sm.open("xref.sv").unwrap(), &[], &[]).with_xref(xref.clone());
+        for x in pp {
+            x.unwrap();
+        }
+        assert_eq!(
+            xref.to_string(),
+            "`define bar at xref_other.sv:2:9\n    never expanded\n\
+             `define foo at xref_other.sv:1:9\n    expanded at xref.sv:2:1\n    \
+             expanded at xref.sv:2:6\n\
+             `include xref_other.sv\n    included at xref.sv:1:1\n"
+        );
+    }
+
     #[test]
     #[should_panic(expected = "unknown compiler directive")]
     fn conditional_define() {
         let sm = get_source_manager();
-        let source = sm.add("test.sv", "`ifdef FOO\n`define BAR\n`endif\n`BAR");
+        let source = sm.add(
+            "conditional_define.sv",
+            "`ifdef FOO\n`define BAR\n`endif\n`BAR",
+        );
         let mut pp = Preprocessor::new(source, &[], &[]);
         while let Some(tkn) = pp.next() {
             tkn.unwrap();
@@ -8,10 +8,11 @@ pub use self::DelimToken::*;
 pub use self::Lit::*;
 pub use self::Token::*;
 use moore_common::name::Name;
+use serde::Serialize;
 use std::fmt::{Display, Formatter, Result};
 
 /// A primary token emitted by the lexer.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum Token {
     // Symbols
     Comma,
@@ -95,6 +96,18 @@ impl Token {
             Eof => "end of file",
         }
     }
+
+    /// Get the revision of the standard which introduced this token.
+    pub fn standard(self) -> Standard {
+        match self {
+            Keyword(kw) => kw.standard(),
+            Operator(op) => op.standard(),
+            AddColon | SubColon => Standard::Ieee1364_2001,
+            DoubleHashtag | Namespace | Apostrophe | Dollar => Standard::Ieee1800_2005,
+            Literal(UnbasedUnsized(_)) | Literal(Time(..)) => Standard::Ieee1800_2005,
+            _ => Standard::Ieee1364_1995,
+        }
+    }
 }
 
 impl Display for Token {
@@ -104,7 +117,7 @@ impl Display for Token {
 }
 
 /// A delimiter token such as parentheses or brackets.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum DelimToken {
     /// A round paranthesis `(` or `)`
     Paren,
@@ -117,7 +130,7 @@ pub enum DelimToken {
 }
 
 /// Abstract literals such as strings.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum Lit {
     Str(Name),
     BasedInteger(Option<Name>, bool, char, Name),
@@ -130,7 +143,7 @@ pub enum Lit {
 }
 
 /// The unit of a time literal.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum TimeUnit {
     Second,
     MilliSecond,
@@ -138,10 +151,12 @@ pub enum TimeUnit {
     NanoSecond,
     PicoSecond,
     FemtoSecond,
+    /// The `step` in `1step`, the smallest time step of the simulation.
+    Step,
 }
 
 /// Operator symbols.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum Op {
     // Assignment
     Assign,
@@ -188,6 +203,7 @@ pub enum Op {
     LogicOr,
     LogicImpl,
     LogicEquiv,
+    TripleAnd,
 
     // Bitwise
     BitNot,
@@ -213,6 +229,35 @@ pub enum Op {
 }
 
 impl Op {
+    /// Get the revision of the standard which introduced this operator.
+    pub fn standard(self) -> Standard {
+        match self {
+            Op::Pow | Op::ArithShL | Op::ArithShR => Standard::Ieee1364_2001,
+            Op::AssignAdd
+            | Op::AssignSub
+            | Op::AssignMul
+            | Op::AssignDiv
+            | Op::AssignMod
+            | Op::AssignBitAnd
+            | Op::AssignBitOr
+            | Op::AssignBitXor
+            | Op::AssignLogicShL
+            | Op::AssignLogicShR
+            | Op::AssignArithShL
+            | Op::AssignArithShR
+            | Op::Inc
+            | Op::Dec
+            | Op::WildcardEq
+            | Op::WildcardNeq
+            | Op::LogicEquiv
+            | Op::SeqImplOl
+            | Op::SeqImplNol
+            | Op::SeqFollowOl
+            | Op::SeqFollowNol => Standard::Ieee1800_2005,
+            _ => Standard::Ieee1364_1995,
+        }
+    }
+
     pub fn as_str(self) -> &'static str {
         match self {
             // Assignment
@@ -260,6 +305,7 @@ impl Op {
             Op::LogicOr => "||",
             Op::LogicImpl => "->",
             Op::LogicEquiv => "<->",
+            Op::TripleAnd => "&&&",
 
             // Bitwise
             Op::BitNot => "~",
@@ -324,6 +370,7 @@ impl Op {
             Op::LogicAnd => Precedence::LogicAnd,
             Op::LogicOr => Precedence::LogicOr,
             Op::LogicImpl | Op::LogicEquiv => Precedence::Implication,
+            Op::TripleAnd => Precedence::Min,
 
             // Bitwise
             Op::BitNot => Precedence::Unary,
@@ -350,7 +397,7 @@ impl Display for Op {
 
 /// Expression precedence. Note that a few kinds of expression are
 /// right-associative rather than the default left-associative.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum Precedence {
     Min,
     MinTypMax,
@@ -378,7 +425,7 @@ pub enum Precedence {
 macro_rules! declare_keywords {(
     $( ($konst: ident, $string: expr) )*
 ) => {
-    #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Debug, Hash)]
+    #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Debug, Hash, Serialize)]
     pub enum Kw {
         $($konst,)*
     }
@@ -660,3 +707,101 @@ declare_keywords! {
     (Xnor,                 "xnor")
     (Xor,                  "xor")
 }
+
+impl Kw {
+    /// Get the revision of the standard which introduced this keyword.
+    ///
+    /// As per IEEE 1800-2017 Table 22-1 to 22-7.
+    pub fn standard(self) -> Standard {
+        use Kw::*;
+        match self {
+            Always | And | Assign | Begin | Buf | Bufif0 | Bufif1 | Case | Casex | Casez | Cmos
+            | Deassign | Default | Defparam | Disable | Edge | Else | End | Endcase
+            | Endfunction | Endmodule | Endprimitive | Endspecify | Endtable | Endtask | Event
+            | For | Force | Forever | Fork | Function | Highz0 | Highz1 | If | Ifnone | Initial
+            | Inout | Input | Integer | Join | Large | Macromodule | Medium | Module | Nand
+            | Negedge | Nmos | Nor | Not | Notif0 | Notif1 | Or | Output | Parameter | Pmos
+            | Posedge | Primitive | Pull0 | Pull1 | Pulldown | Pullup | Rcmos | Real | Realtime
+            | Reg | Release | Repeat | Rnmos | Rpmos | Rtran | Rtranif0 | Rtranif1 | Scalared
+            | Small | Specify | Specparam | Strong0 | Strong1 | Supply0 | Supply1 | Table
+            | Task | Time | Tran | Tranif0 | Tranif1 | Tri | Tri0 | Tri1 | Triand | Trior
+            | Trireg | Vectored | Wait | Wand | Weak0 | Weak1 | While | Wire | Wor | Xnor | Xor => {
+                Standard::Ieee1364_1995
+            }
+            Automatic | Endgenerate | Generate | Genvar | Localparam | Noshowcancelled
+            | PulsestyleOndetect | PulsestyleOnevent | Showcancelled | Signed | Unsigned => {
+                Standard::Ieee1364_2001Noconfig
+            }
+            Cell | Config | Design | Endconfig | Incdir | Include | Instance | Liblist
+            | Library | Use => Standard::Ieee1364_2001,
+            Uwire => Standard::Ieee1364_2005,
+            AcceptOn | Checker | Endchecker | Eventually | Global | Implies | Let | Nexttime
+            | RejectOn | Restrict | SAlways | SEventually | SNexttime | SUntil | SUntilWith
+            | Strong | SyncAcceptOn | SyncRejectOn | Unique0 | Until | UntilWith | Untyped
+            | Weak => Standard::Ieee1800_2009,
+            Implements | Interconnect | Nettype | Soft => Standard::Ieee1800_2012,
+            _ => Standard::Ieee1800_2005,
+        }
+    }
+}
+
+/// A revision of the Verilog or SystemVerilog standard.
+///
+/// Revisions are ordered by their publication, such that a revision compares
+/// greater than the ones it extends.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Debug, Hash)]
+pub enum Standard {
+    Ieee1364_1995,
+    Ieee1364_2001Noconfig,
+    Ieee1364_2001,
+    Ieee1364_2005,
+    Ieee1800_2005,
+    Ieee1800_2009,
+    Ieee1800_2012,
+    Ieee1800_2017,
+}
+
+impl Standard {
+    /// Parse a version specifier as used in `begin_keywords`, e.g.
+    /// `"1800-2009"`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "1364-1995" => Some(Self::Ieee1364_1995),
+            "1364-2001-noconfig" => Some(Self::Ieee1364_2001Noconfig),
+            "1364-2001" => Some(Self::Ieee1364_2001),
+            "1364-2005" => Some(Self::Ieee1364_2005),
+            "1800-2005" => Some(Self::Ieee1800_2005),
+            "1800-2009" => Some(Self::Ieee1800_2009),
+            "1800-2012" => Some(Self::Ieee1800_2012),
+            "1800-2017" => Some(Self::Ieee1800_2017),
+            _ => None,
+        }
+    }
+
+    /// Get the version specifier of this revision.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ieee1364_1995 => "1364-1995",
+            Self::Ieee1364_2001Noconfig => "1364-2001-noconfig",
+            Self::Ieee1364_2001 => "1364-2001",
+            Self::Ieee1364_2005 => "1364-2005",
+            Self::Ieee1800_2005 => "1800-2005",
+            Self::Ieee1800_2009 => "1800-2009",
+            Self::Ieee1800_2012 => "1800-2012",
+            Self::Ieee1800_2017 => "1800-2017",
+        }
+    }
+}
+
+impl Default for Standard {
+    fn default() -> Self {
+        Self::Ieee1800_2017
+    }
+}
+
+impl Display for Standard {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "IEEE {}", self.as_str())
+    }
+}
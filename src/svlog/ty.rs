@@ -86,7 +86,10 @@
 //! which changes only their value domain.
 
 use crate::crate_prelude::*;
-use crate::{common::arenas::TypedArena, ParamEnv};
+use crate::{
+    common::arenas::{ArenaStats, TypedArena},
+    ParamEnv,
+};
 use once_cell::sync::Lazy;
 use std::{
     cell::RefCell,
@@ -2110,6 +2113,16 @@ pub struct TypeStorage<'a> {
     cached_unpacked: RefCell<HashSet<&'a Intern<UnpackedType<'a>>>>,
 }
 
+impl<'a> TypeStorage<'a> {
+    /// Report the number of types and bytes allocated.
+    pub fn stats(&self) -> Vec<ArenaStats> {
+        vec![
+            ArenaStats::of("packed_types", &self.packed),
+            ArenaStats::of("unpacked_types", &self.unpacked),
+        ]
+    }
+}
+
 /// An object that has type storage.
 pub trait HasTypeStorage<'a> {
     /// Get the type storage.
@@ -590,6 +590,20 @@ pub(crate) fn packed_type_from_ast<'a>(
 
         // Struct types
         ast::StructType(ref strukt) => {
+            // Unions would need their members to overlap in storage, which
+            // the struct representation below cannot express.
+            let union_desc = match strukt.kind {
+                ast::StructKind::Struct => None,
+                ast::StructKind::Union => Some("union"),
+                ast::StructKind::TaggedUnion => Some("tagged union"),
+            };
+            if let Some(desc) = union_desc {
+                cx.emit(
+                    DiagBuilder2::error(format!("unsupported: {} type", desc)).span(ast.span()),
+                );
+                return UnpackedType::make_error();
+            }
+
             // Assemble the struct type, with no members.
             let mut def = ty::StructType {
                 ast: strukt,
@@ -665,6 +679,21 @@ pub(crate) fn packed_type_from_ast<'a>(
             };
             let base_explicit = enm.base_type.is_some();
 
+            // Enumerator ranges such as `A[4]` declare a sequence of names,
+            // which we cannot represent yet.
+            for var in &enm.variants {
+                if let Some(ref range) = var.range {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "unsupported: enumerator range {}",
+                            range.desc_full()
+                        ))
+                        .span(var.span),
+                    );
+                    return UnpackedType::make_error();
+                }
+            }
+
             // Assemble the enum type.
             let def = ty::EnumType {
                 ast: enm,
@@ -818,7 +847,16 @@ pub(crate) fn packed_type_from_ast<'a>(
             }
         }
 
-        ast::VirtIntfType { .. } | ast::MailboxType | ast::SpecializedType(..) => {
+        ast::VirtIntfType { .. } => {
+            cx.emit(
+                DiagBuilder2::error("unsupported: virtual interface type")
+                    .span(ast.span())
+                    .add_note("Virtual interfaces are references that only exist in simulation"),
+            );
+            return UnpackedType::make_error();
+        }
+
+        ast::MailboxType | ast::SpecializedType(..) => {
             bug_span!(ast.span(), cx, "type {:#1?} not implemented", ast.kind)
         }
     };
@@ -1578,7 +1616,7 @@ fn self_determined_expr_type<'gcx>(
         // sum of the simple bit vector types of each argument.
         //
         // See §11.8.1 "Rules for expression types".
-        hir::ExprKind::Concat(repeat, ref exprs) => {
+        hir::ExprKind::Concat(repeat, exprs) => {
             let mut failed = false;
 
             // Determine the cumulative width of all fields.
@@ -2124,7 +2162,7 @@ pub(crate) fn operation_type<'a>(
 
         // The inside expression uses an operation type for its comparisons. It
         // is determined in the same way as for comparisons.
-        hir::ExprKind::Inside(lhs, ref ranges) => {
+        hir::ExprKind::Inside(lhs, ranges) => {
             let tlhs = cx.self_determined_type(lhs, env);
             let tranges = ranges.iter().flat_map(|r| {
                 let (a, b) = match r.value {
@@ -2515,7 +2553,7 @@ fn type_context_imposed_by_expr<'gcx>(
         hir::ExprKind::RepeatPattern(rep, ..) if onto != rep => {
             type_context_imposed_by_pattern(cx, onto, expr, env)
         }
-        hir::ExprKind::NamedPattern(ref nodes) => {
+        hir::ExprKind::NamedPattern(nodes) => {
             if nodes.iter().all(|&(n, _)| match n {
                 hir::PatternMapping::Type(n) => n != onto,
                 hir::PatternMapping::Member(n) => n != onto,
@@ -2615,16 +2653,29 @@ fn type_context_imposed_by_stmt<'gcx>(
         // If statements and do/while loops require a boolean condition.
         hir::StmtKind::If { cond, .. } if onto == cond => Some(TypeContext::Bool),
 
+        // Wait statements require a boolean condition.
+        hir::StmtKind::Wait { cond, .. } if onto == cond => Some(TypeContext::Bool),
+
+        // Assertions require boolean conditions.
+        hir::StmtKind::Assert {
+            disable,
+            antecedent,
+            expr,
+            ..
+        } if onto == expr || Some(onto) == disable || Some(onto) == antecedent => {
+            Some(TypeContext::Bool)
+        }
+
         // Do/while loops require a boolean condition.
-        hir::StmtKind::Loop { kind, .. } => {
-            match kind {
+        hir::StmtKind::Loop { ref kind, .. } => {
+            match *kind {
                 hir::LoopKind::Repeat(count) if onto == count => {
                     // TODO: Actually this should require a simple 2-value bit vector type
                     None
                 }
                 hir::LoopKind::Do(cond)
                 | hir::LoopKind::While(cond)
-                | hir::LoopKind::For(_, cond, _)
+                | hir::LoopKind::For(_, Some(cond), _)
                     if onto == cond =>
                 {
                     Some(TypeContext::Bool)
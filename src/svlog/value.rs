@@ -170,6 +170,25 @@ pub fn make_int_special<'a>(
     }
 }
 
+/// Create the default value of an integer type, which is all `x` for
+/// four-valued types and zero otherwise.
+///
+/// Panics if `ty` is not an integer type.
+fn make_int_default<'a>(ty: &'a UnpackedType<'a>, domain: ty::Domain) -> ValueData<'a> {
+    match domain {
+        ty::Domain::TwoValued => make_int(ty, Zero::zero()),
+        ty::Domain::FourValued => {
+            let w = ty.get_bit_size().unwrap();
+            make_int_special(
+                ty,
+                Zero::zero(),
+                BitVec::from_elem(w, true),
+                BitVec::from_elem(w, true),
+            )
+        }
+    }
+}
+
 /// Create a new time value.
 pub fn make_time<'a>(value: BigRational) -> ValueData<'a> {
     ValueData {
@@ -287,7 +306,27 @@ fn constant_value_of_inner<'a>(cx: &impl Context<'a>, node_id: NodeId, env: Para
                     Ok(x) => x,
                     _ => return cx.intern_value(make_error(UnpackedType::make_error())),
                 };
-                cx.intern_value(make_int(ty, var.index.into()))
+                if var.index == 0 {
+                    return cx.intern_value(make_int(ty, BigInt::zero()));
+                }
+
+                // Variants without an explicit value count up from the
+                // preceding variant.
+                let mut kind = match cx.hir_of(var.enum_id) {
+                    Ok(HirNode::Type(ty)) => &ty.kind,
+                    _ => bug_span!(var.span, cx, "enum variant outside of a type"),
+                };
+                while let hir::TypeKind::PackedArray(ref inner, ..) = *kind {
+                    kind = inner;
+                }
+                let prev = match *kind {
+                    hir::TypeKind::Enum(ref variants, _) => variants[var.index - 1].1,
+                    _ => bug_span!(var.span, cx, "enum variant outside of an enum type"),
+                };
+                match cx.constant_value_of(prev, env).kind {
+                    ValueKind::Int(ref v, ..) => cx.intern_value(make_int(ty, v + 1)),
+                    _ => cx.intern_value(make_error(ty)),
+                }
             }
         },
         _ => {
@@ -410,7 +449,7 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
                 .collect(),
         )),
 
-        mir::RvalueKind::ConstructStruct(ref values) => cx.intern_value(make_struct(
+        mir::RvalueKind::ConstructStruct(values) => cx.intern_value(make_struct(
             mir.ty,
             values
                 .iter()
@@ -522,7 +561,7 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
             }
         }
 
-        mir::RvalueKind::Concat(ref values) => {
+        mir::RvalueKind::Concat(values) => {
             let mut result = BigInt::zero();
             for &value in values {
                 result <<= value.ty.simple_bit_vector(cx, value.span).size;
@@ -911,13 +950,13 @@ pub(crate) fn type_default_value<'a>(cx: &impl Context<'a>, ty: &'a UnpackedType
         let packed = packed;
         match packed.core {
             ty::PackedCore::IntVec(_) if packed.dims.len() <= 1 => {
-                return cx.intern_value(make_int(ty, Zero::zero()));
+                return cx.intern_value(make_int_default(ty, packed.domain()));
             }
             ty::PackedCore::IntAtom(ty::IntAtomType::Time) if packed.dims.is_empty() => {
                 return cx.intern_value(make_time(Zero::zero()));
             }
             ty::PackedCore::IntAtom(_) if packed.dims.is_empty() => {
-                return cx.intern_value(make_int(ty, Zero::zero()));
+                return cx.intern_value(make_int_default(ty, packed.domain()));
             }
             _ => (),
         }
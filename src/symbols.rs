@@ -0,0 +1,680 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Symbols declared in source files.
+//!
+//! Collects the named declarations in a parsed file, such as modules,
+//! entities, subroutines, signals, and instances, together with their spans.
+//! Declarations which contain others, such as a module and its ports, form a
+//! tree. Editors use these symbols to outline a file and navigate to
+//! declarations.
+
+use crate::common::name::Name;
+use crate::common::source::{Span, Spanned};
+use crate::score::Ast;
+use crate::svlog::ast as sv;
+use crate::vhdl::syntax::ast as vhdl;
+use serde::Serialize;
+
+/// A named declaration.
+#[derive(Debug, Serialize)]
+pub struct Symbol {
+    /// The name of the declaration, as written in the source.
+    pub name: String,
+    /// The kind of declaration.
+    pub kind: SymbolKind,
+    /// The span of the entire declaration.
+    pub span: Span,
+    /// The span of the name within the declaration.
+    pub name_span: Span,
+    /// The symbols declared within this declaration.
+    pub children: Vec<Symbol>,
+}
+
+/// The kinds of declarations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Module,
+    Interface,
+    Package,
+    PackageBody,
+    Class,
+    Entity,
+    Architecture,
+    Configuration,
+    Context,
+    Component,
+    Subprogram,
+    Type,
+    Parameter,
+    Port,
+    Signal,
+    Variable,
+    Constant,
+    Variant,
+    Instance,
+    Block,
+}
+
+impl SymbolKind {
+    /// Get a human-readable name of the kind.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SymbolKind::Module => "module",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Package => "package",
+            SymbolKind::PackageBody => "package body",
+            SymbolKind::Class => "class",
+            SymbolKind::Entity => "entity",
+            SymbolKind::Architecture => "architecture",
+            SymbolKind::Configuration => "configuration",
+            SymbolKind::Context => "context",
+            SymbolKind::Component => "component",
+            SymbolKind::Subprogram => "subprogram",
+            SymbolKind::Type => "type",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Port => "port",
+            SymbolKind::Signal => "signal",
+            SymbolKind::Variable => "variable",
+            SymbolKind::Constant => "constant",
+            SymbolKind::Variant => "variant",
+            SymbolKind::Instance => "instance",
+            SymbolKind::Block => "block",
+        }
+    }
+}
+
+/// Collect the symbols declared in a parsed file, in the order they appear.
+pub fn collect<'a>(ast: &'a Ast<'a>) -> Vec<Symbol> {
+    match ast {
+        Ast::Svlog(file) => {
+            let mut collector = SvlogSymbols::default();
+            for item in &file.items {
+                sv::AcceptVisitor::accept(item, &mut collector);
+            }
+            collector.stack.pop().unwrap_or_default()
+        }
+        Ast::Vhdl(units) => units.iter().map(vhdl_unit).collect(),
+    }
+}
+
+/// Collects the symbols declared in a SystemVerilog file.
+struct SvlogSymbols {
+    /// The symbols collected for each enclosing declaration, and the file.
+    stack: Vec<Vec<Symbol>>,
+    /// The kind of the variables currently being declared.
+    var_kind: SymbolKind,
+}
+
+impl Default for SvlogSymbols {
+    fn default() -> Self {
+        SvlogSymbols {
+            stack: vec![vec![]],
+            var_kind: SymbolKind::Variable,
+        }
+    }
+}
+
+impl SvlogSymbols {
+    /// Add a symbol to the innermost enclosing declaration.
+    fn add(&mut self, kind: SymbolKind, name: impl ToString, span: Span, name_span: Span) {
+        self.stack.last_mut().unwrap().push(Symbol {
+            name: spelling(name, name_span),
+            kind,
+            span,
+            name_span,
+            children: vec![],
+        });
+    }
+
+    /// Add a symbol which contains further declarations.
+    fn enter(&mut self, kind: SymbolKind, name: impl ToString, span: Span, name_span: Span) {
+        self.add(kind, name, span, name_span);
+        self.stack.push(vec![]);
+    }
+
+    /// Finish the symbol added with `enter`.
+    fn leave(&mut self) {
+        let children = self.stack.pop().unwrap();
+        self.stack.last_mut().unwrap().last_mut().unwrap().children = children;
+    }
+}
+
+impl<'a> sv::Visitor<'a> for SvlogSymbols {
+    fn pre_visit_module(&mut self, node: &'a sv::Module<'a>) -> bool {
+        self.enter(
+            SymbolKind::Module,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn post_visit_module(&mut self, _: &'a sv::Module<'a>) {
+        self.leave();
+    }
+
+    fn pre_visit_interface(&mut self, node: &'a sv::Interface<'a>) -> bool {
+        self.enter(
+            SymbolKind::Interface,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn post_visit_interface(&mut self, _: &'a sv::Interface<'a>) {
+        self.leave();
+    }
+
+    fn pre_visit_package(&mut self, node: &'a sv::Package<'a>) -> bool {
+        self.enter(
+            SymbolKind::Package,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn post_visit_package(&mut self, _: &'a sv::Package<'a>) {
+        self.leave();
+    }
+
+    fn pre_visit_class_decl(&mut self, node: &'a sv::ClassDecl<'a>) -> bool {
+        self.enter(
+            SymbolKind::Class,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn post_visit_class_decl(&mut self, _: &'a sv::ClassDecl<'a>) {
+        self.leave();
+    }
+
+    fn pre_visit_interface_class_decl(&mut self, node: &'a sv::InterfaceClassDecl<'a>) -> bool {
+        self.enter(
+            SymbolKind::Class,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn post_visit_interface_class_decl(&mut self, _: &'a sv::InterfaceClassDecl<'a>) {
+        self.leave();
+    }
+
+    fn pre_visit_subroutine_decl(&mut self, node: &'a sv::SubroutineDecl<'a>) -> bool {
+        let name = node.prototype.name;
+        self.enter(SymbolKind::Subprogram, name.value, node.span, name.span);
+        true
+    }
+
+    fn post_visit_subroutine_decl(&mut self, _: &'a sv::SubroutineDecl<'a>) {
+        self.leave();
+    }
+
+    fn pre_visit_port(&mut self, node: &'a sv::Port<'a>) -> bool {
+        match node.data {
+            sv::PortData::Intf { name, .. }
+            | sv::PortData::Explicit { name, .. }
+            | sv::PortData::Named { name, .. } => {
+                self.add(SymbolKind::Port, name.value, node.span, name.span)
+            }
+            sv::PortData::Implicit(..) => (),
+        }
+        true
+    }
+
+    fn pre_visit_port_decl(&mut self, _: &'a sv::PortDecl<'a>) -> bool {
+        self.var_kind = SymbolKind::Port;
+        true
+    }
+
+    fn pre_visit_subroutine_port_decl(&mut self, _: &'a sv::SubroutinePortDecl<'a>) -> bool {
+        self.var_kind = SymbolKind::Port;
+        true
+    }
+
+    fn pre_visit_net_decl(&mut self, _: &'a sv::NetDecl<'a>) -> bool {
+        self.var_kind = SymbolKind::Signal;
+        true
+    }
+
+    fn pre_visit_var_decl(&mut self, node: &'a sv::VarDecl<'a>) -> bool {
+        self.var_kind = if node.konst {
+            SymbolKind::Constant
+        } else {
+            SymbolKind::Variable
+        };
+        true
+    }
+
+    fn pre_visit_var_decl_name(&mut self, node: &'a sv::VarDeclName<'a>) -> bool {
+        self.add(self.var_kind, node.name, node.span, node.name_span);
+        true
+    }
+
+    fn pre_visit_subroutine_port_name(&mut self, node: &'a sv::SubroutinePortName<'a>) -> bool {
+        self.add(
+            SymbolKind::Port,
+            node.name.value,
+            node.name.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn pre_visit_genvar_decl(&mut self, node: &'a sv::GenvarDecl<'a>) -> bool {
+        self.add(
+            SymbolKind::Variable,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn pre_visit_typedef(&mut self, node: &'a sv::Typedef<'a>) -> bool {
+        self.add(SymbolKind::Type, node.name.value, node.span, node.name.span);
+        true
+    }
+
+    fn pre_visit_enum_name(&mut self, node: &'a sv::EnumName<'a>) -> bool {
+        self.add(
+            SymbolKind::Variant,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn pre_visit_param_type_decl(&mut self, node: &'a sv::ParamTypeDecl<'a>) -> bool {
+        self.add(
+            SymbolKind::Parameter,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn pre_visit_param_value_decl(&mut self, node: &'a sv::ParamValueDecl<'a>) -> bool {
+        self.add(
+            SymbolKind::Parameter,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+
+    fn pre_visit_inst_name(&mut self, node: &'a sv::InstName<'a>) -> bool {
+        self.add(
+            SymbolKind::Instance,
+            node.name.value,
+            node.span,
+            node.name.span,
+        );
+        true
+    }
+}
+
+/// Collect the symbols of a VHDL design unit.
+fn vhdl_unit(unit: &vhdl::DesignUnit) -> Symbol {
+    let (kind, name, span, decls, stmts): (_, _, _, &[_], &[_]) = match unit.data {
+        vhdl::DesignUnitData::EntityDecl(ref x) => (
+            SymbolKind::Entity,
+            x.name,
+            x.span,
+            &x.decls,
+            x.stmts.as_deref().unwrap_or(&[]),
+        ),
+        vhdl::DesignUnitData::CfgDecl(ref x) => {
+            (SymbolKind::Configuration, x.name, x.span, &x.decls, &[])
+        }
+        vhdl::DesignUnitData::PkgDecl(ref x) => {
+            (SymbolKind::Package, x.name, x.span, &x.decls, &[])
+        }
+        vhdl::DesignUnitData::PkgInst(ref x) => (SymbolKind::Package, x.name, x.span, &[], &[]),
+        vhdl::DesignUnitData::CtxDecl(ref x) => (SymbolKind::Context, x.name, x.span, &[], &[]),
+        vhdl::DesignUnitData::PkgBody(ref x) => {
+            (SymbolKind::PackageBody, x.name, x.span, &x.decls, &[])
+        }
+        vhdl::DesignUnitData::ArchBody(ref x) => {
+            (SymbolKind::Architecture, x.name, x.span, &x.decls, &x.stmts)
+        }
+    };
+    let mut children = vec![];
+    vhdl_decls(decls, &mut children);
+    vhdl_stmts(stmts, &mut children);
+    Symbol {
+        name: spelling(name.value, name.span),
+        kind,
+        span,
+        name_span: name.span,
+        children,
+    }
+}
+
+/// Collect the symbols of VHDL declarations.
+fn vhdl_decls(decls: &[vhdl::DeclItem], into: &mut Vec<Symbol>) {
+    for decl in decls {
+        match *decl {
+            vhdl::DeclItem::PkgBody(ref x) => {
+                let mut children = vec![];
+                vhdl_decls(&x.decls, &mut children);
+                into.push(vhdl_symbol(
+                    SymbolKind::PackageBody,
+                    x.name,
+                    x.span,
+                    children,
+                ));
+            }
+            vhdl::DeclItem::PkgDecl(ref x) => {
+                let mut children = vec![];
+                vhdl_decls(&x.decls, &mut children);
+                into.push(vhdl_symbol(SymbolKind::Package, x.name, x.span, children));
+            }
+            vhdl::DeclItem::PkgInst(ref x) => {
+                into.push(vhdl_symbol(SymbolKind::Package, x.name, x.span, vec![]))
+            }
+            vhdl::DeclItem::TypeDecl(ref x) => vhdl_type(x, into),
+            vhdl::DeclItem::SubtypeDecl(ref x) => {
+                into.push(vhdl_symbol(SymbolKind::Type, x.name, x.span, vec![]))
+            }
+            vhdl::DeclItem::ObjDecl(ref x) => {
+                let kind = match x.kind {
+                    vhdl::ObjKind::Const => SymbolKind::Constant,
+                    vhdl::ObjKind::Signal => SymbolKind::Signal,
+                    _ => SymbolKind::Variable,
+                };
+                for name in &x.names {
+                    into.push(vhdl_ident(kind, name, x.span));
+                }
+            }
+            vhdl::DeclItem::AliasDecl(ref x) => {
+                if let Some(sym) = vhdl_primary(SymbolKind::Variable, &x.name, x.span) {
+                    into.push(sym);
+                }
+            }
+            vhdl::DeclItem::SubprogDecl(ref x) => {
+                let mut children = vec![];
+                if let Some(ref params) = x.spec.params {
+                    vhdl_intfs(params, SymbolKind::Port, &mut children);
+                }
+                if let vhdl::SubprogData::Body {
+                    ref decls,
+                    ref stmts,
+                } = x.data
+                {
+                    vhdl_decls(decls, &mut children);
+                    vhdl_stmts(stmts, &mut children);
+                }
+                if let Some(mut sym) = vhdl_primary(SymbolKind::Subprogram, &x.spec.name, x.span) {
+                    sym.children = children;
+                    into.push(sym);
+                }
+            }
+            vhdl::DeclItem::CompDecl(ref x) => {
+                let mut children = vec![];
+                if let Some(ref generics) = x.generics {
+                    vhdl_intfs(&generics.value, SymbolKind::Parameter, &mut children);
+                }
+                if let Some(ref ports) = x.ports {
+                    vhdl_intfs(&ports.value, SymbolKind::Port, &mut children);
+                }
+                into.push(vhdl_symbol(SymbolKind::Component, x.name, x.span, children));
+            }
+            vhdl::DeclItem::PortgenClause(_, kind, ref intfs) => {
+                let kind = match kind.value {
+                    vhdl::PortgenKind::Port => SymbolKind::Port,
+                    vhdl::PortgenKind::Generic => SymbolKind::Parameter,
+                };
+                vhdl_intfs(&intfs.value, kind, into);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Collect the symbols of a VHDL type declaration, including enum variants.
+fn vhdl_type(decl: &vhdl::TypeDecl, into: &mut Vec<Symbol>) {
+    let mut children = vec![];
+    if let Some(Spanned {
+        value: vhdl::EnumType(ref variants),
+        ..
+    }) = decl.data
+    {
+        for variant in &variants.value {
+            if let vhdl::NameExpr(ref name) = variant.expr.data {
+                if let Some(sym) = vhdl_primary(SymbolKind::Variant, &name.primary, variant.span) {
+                    children.push(sym);
+                }
+            }
+        }
+    }
+    into.push(vhdl_symbol(
+        SymbolKind::Type,
+        decl.name,
+        decl.span,
+        children,
+    ));
+}
+
+/// Collect the symbols of VHDL interface declarations, such as ports.
+fn vhdl_intfs(intfs: &[vhdl::IntfDecl], kind: SymbolKind, into: &mut Vec<Symbol>) {
+    for intf in intfs {
+        match *intf {
+            vhdl::IntfDecl::ObjDecl(ref x) => {
+                for name in &x.names {
+                    into.push(vhdl_ident(kind, name, x.span));
+                }
+            }
+            vhdl::IntfDecl::TypeDecl(ref x) => vhdl_type(x, into),
+            vhdl::IntfDecl::SubprogSpec(ref x) => {
+                if let Some(sym) = vhdl_primary(SymbolKind::Subprogram, &x.spec.name, x.span) {
+                    into.push(sym);
+                }
+            }
+            vhdl::IntfDecl::PkgInst(ref x) => {
+                into.push(vhdl_symbol(SymbolKind::Package, x.name, x.span, vec![]))
+            }
+        }
+    }
+}
+
+/// Collect the symbols of labeled VHDL statements.
+fn vhdl_stmts(stmts: &[vhdl::Stmt], into: &mut Vec<Symbol>) {
+    for stmt in stmts {
+        let mut children = vec![];
+        let kind = match stmt.data {
+            vhdl::ProcStmt {
+                ref decls,
+                ref stmts,
+                ..
+            }
+            | vhdl::BlockStmt {
+                ref decls,
+                ref stmts,
+                ..
+            } => {
+                vhdl_decls(decls, &mut children);
+                vhdl_stmts(stmts, &mut children);
+                SymbolKind::Block
+            }
+            vhdl::ForGenStmt { ref body, .. } => {
+                vhdl_decls(&body.decls, &mut children);
+                vhdl_stmts(&body.stmts, &mut children);
+                SymbolKind::Block
+            }
+            vhdl::IfGenStmt { ref conds, ref alt } => {
+                for body in conds.iter().map(|(_, body)| body).chain(alt) {
+                    vhdl_decls(&body.decls, &mut children);
+                    vhdl_stmts(&body.stmts, &mut children);
+                }
+                SymbolKind::Block
+            }
+            vhdl::CaseGenStmt { ref cases, .. } => {
+                for (_, body) in cases {
+                    vhdl_decls(&body.decls, &mut children);
+                    vhdl_stmts(&body.stmts, &mut children);
+                }
+                SymbolKind::Block
+            }
+            vhdl::InstOrCallStmt {
+                target,
+                ref generics,
+                ref ports,
+                ..
+            } if target.is_some() || generics.is_some() || ports.is_some() => SymbolKind::Instance,
+            _ => continue,
+        };
+        if let Some(label) = stmt.label {
+            into.push(vhdl_symbol(kind, label, stmt.span, children));
+        } else {
+            into.extend(children);
+        }
+    }
+}
+
+fn vhdl_symbol(kind: SymbolKind, name: Spanned<Name>, span: Span, children: Vec<Symbol>) -> Symbol {
+    Symbol {
+        name: spelling(name.value, name.span),
+        kind,
+        span,
+        name_span: name.span,
+        children,
+    }
+}
+
+fn vhdl_ident(kind: SymbolKind, name: &vhdl::Ident, span: Span) -> Symbol {
+    Symbol {
+        name: spelling(name.name, name.span),
+        kind,
+        span,
+        name_span: name.span,
+        children: vec![],
+    }
+}
+
+fn vhdl_primary(kind: SymbolKind, name: &vhdl::PrimaryName, span: Span) -> Option<Symbol> {
+    let name_str = match name.kind {
+        vhdl::PrimaryNameKind::Ident(x) => spelling(x, name.span),
+        vhdl::PrimaryNameKind::String(x) => format!("\"{}\"", x),
+        vhdl::PrimaryNameKind::Char(_) => return None,
+    };
+    Some(Symbol {
+        name: name_str,
+        kind,
+        span,
+        name_span: name.span,
+        children: vec![],
+    })
+}
+
+/// Determine the spelling of a name as written in the source.
+///
+/// The name table hands out the first spelling it has seen of a
+/// case-insensitive name, which may stem from a different file or even a
+/// case-sensitive name of the same letters.
+fn spelling(name: impl ToString, span: Span) -> String {
+    let name = name.to_string();
+    let written = span.extract();
+    if written != name && written.eq_ignore_ascii_case(&name) {
+        written
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::source::AnonymousSource;
+    use crate::svlog;
+
+    /// Render a tree of symbols as `kind name { children }`.
+    fn render(symbols: &[Symbol]) -> String {
+        symbols
+            .iter()
+            .map(|sym| {
+                let mut s = format!("{} {}", sym.kind.as_str(), sym.name);
+                if !sym.children.is_empty() {
+                    s.push_str(&format!(" {{ {} }}", render(&sym.children)));
+                }
+                s
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Collect the symbols of a SystemVerilog source text. The returned
+    /// source must be kept alive for as long as the spans are used.
+    fn svlog_symbols(text: &str) -> (Vec<Symbol>, AnonymousSource) {
+        let arena = svlog::ast::Arena::default();
+        let (file, _, source) = svlog::parser::parse_str(text, &arena);
+        (collect(&Ast::Svlog(file.unwrap())), source)
+    }
+
+    fn vhdl_symbols(text: &str) -> Vec<Symbol> {
+        let source = AnonymousSource::new(text);
+        let units = crate::vhdl::syntax::parse(source.source()).unwrap();
+        collect(&Ast::Vhdl(units))
+    }
+
+    #[test]
+    fn svlog_module() {
+        let (symbols, _source) = svlog_symbols(
+            "module foo #(parameter N = 1) (input logic a, output logic b);
+                 logic [N-1:0] x;
+                 function int f(int y); return y; endfunction
+                 bar u0 (.a(a));
+             endmodule",
+        );
+        assert_eq!(
+            render(&symbols),
+            "module foo { parameter N, port a, port b, variable x, \
+             subprogram f { port y }, instance u0 }"
+        );
+    }
+
+    #[test]
+    fn svlog_spans() {
+        let text = "package p; typedef int t; endpackage";
+        let (symbols, _source) = svlog_symbols(text);
+        let p = &symbols[0];
+        assert_eq!(p.kind, SymbolKind::Package);
+        assert_eq!(p.name_span.extract(), "p");
+        assert!(p.span.extract().starts_with("package p;"));
+        let t = &p.children[0];
+        assert_eq!(t.name_span.extract(), "t");
+        assert!(t.span.begin >= p.span.begin && t.span.end <= p.span.end);
+    }
+
+    #[test]
+    fn vhdl_entity() {
+        let symbols = vhdl_symbols(
+            "entity foo is
+                 port (a : in bit; b : out bit);
+             end entity;
+             architecture rtl of foo is
+                 signal s : bit;
+             begin
+                 b <= a;
+             end architecture;",
+        );
+        assert_eq!(
+            render(&symbols),
+            "entity foo { port a, port b }, architecture rtl { signal s }"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2016-2021 Fabian Schuiki
+
+//! Symbol indices for editors.
+//!
+//! Lists the declarations in a set of parsed files together with their
+//! location, either as a tags file in the extended format of ctags, or as a
+//! list which can be serialized to JSON.
+
+use crate::common::source::{Source, Span};
+use crate::score::Ast;
+use crate::symbols::{self, Symbol, SymbolKind};
+use serde::Serialize;
+use std::fmt::Write;
+
+/// A declaration in the index.
+#[derive(Debug, Serialize)]
+pub struct Tag {
+    /// The name of the declaration.
+    pub name: String,
+    /// The kind of declaration.
+    pub kind: SymbolKind,
+    /// The file the declaration is in.
+    pub file: Source,
+    /// The line of the name, starting at 1.
+    pub line: usize,
+    /// The column of the name, starting at 1.
+    pub column: usize,
+    /// The span of the entire declaration.
+    pub span: Span,
+    /// The declarations enclosing this one, separated by `.`, if any.
+    pub scope: Option<String>,
+    /// The kind of the innermost enclosing declaration, if any.
+    pub scope_kind: Option<SymbolKind>,
+}
+
+/// The declarations in a set of files, in the order they appear.
+#[derive(Debug, Default, Serialize)]
+pub struct Index {
+    pub tags: Vec<Tag>,
+}
+
+impl Index {
+    /// Determine the declarations in a list of parsed files.
+    pub fn new<'a>(asts: &'a [Ast<'a>]) -> Index {
+        let mut index = Index::default();
+        for ast in asts {
+            index.add(&symbols::collect(ast), None);
+        }
+        index
+    }
+
+    fn add(&mut self, symbols: &[Symbol], parent: Option<(&str, SymbolKind)>) {
+        for sym in symbols {
+            let (line, column, _) = sym.name_span.begin().human();
+            let scope = parent.map(|(name, _)| name.to_string());
+            let path = match scope {
+                Some(ref scope) => format!("{}.{}", scope, sym.name),
+                None => sym.name.clone(),
+            };
+            self.tags.push(Tag {
+                name: sym.name.clone(),
+                kind: sym.kind,
+                file: sym.name_span.source,
+                line,
+                column,
+                span: sym.span,
+                scope,
+                scope_kind: parent.map(|(_, kind)| kind),
+            });
+            self.add(&sym.children, Some((&path, sym.kind)));
+        }
+    }
+
+    /// Render the index as a tags file in the extended format of ctags.
+    ///
+    /// The tags are sorted by name, such that editors may binary search them.
+    /// Kinds and scopes are given as `kind:` and `module:top` fields.
+    pub fn to_ctags(&self) -> String {
+        let mut tags: Vec<_> = self.tags.iter().collect();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut output = String::new();
+        output.push_str("!_TAG_FILE_FORMAT\t2\t/extended format/\n");
+        output.push_str("!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n");
+        output.push_str("!_TAG_PROGRAM_NAME\tmoore\t//\n");
+        output.push_str(concat!(
+            "!_TAG_PROGRAM_VERSION\t",
+            env!("CARGO_PKG_VERSION"),
+            "\t//\n"
+        ));
+        for tag in tags {
+            write!(
+                output,
+                "{}\t{}\t{};\"\tkind:{}\tline:{}",
+                tag.name,
+                tag.file.get_path(),
+                tag.line,
+                kind_name(tag.kind),
+                tag.line
+            )
+            .unwrap();
+            if let (Some(scope), Some(kind)) = (&tag.scope, tag.scope_kind) {
+                write!(output, "\t{}:{}", kind_name(kind), scope).unwrap();
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// The name of a kind in a tags file, which may not contain spaces.
+fn kind_name(kind: SymbolKind) -> String {
+    kind.as_str().replace(' ', "_")
+}
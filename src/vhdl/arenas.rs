@@ -4,6 +4,7 @@
 
 #![deny(missing_docs)]
 
+pub use crate::common::arenas::ArenaStats;
 use std::borrow::Cow;
 
 /// Allocates values.
@@ -122,6 +123,10 @@ where
 }
 
 /// Generate a collection of arenas for different types.
+///
+/// The collection implements `AllocOwned` for all member types which are their
+/// own owned form, such that `maybe_alloc` and `force_alloc` may be used to
+/// allocate a `Cow`.
 #[macro_export]
 macro_rules! make_arenas {
     ($(#[$arena_attr:meta])* pub struct $arena_name:ident { $($name:ident: $type:ty,)* }) => {
@@ -150,6 +155,21 @@ macro_rules! make_arenas {
                     $($name: ::typed_arena::Arena::new(),)*
                 }
             }
+
+            /// Report the number of values and bytes allocated in each arena.
+            pub fn stats(&self) -> Vec<$crate::arenas::ArenaStats> {
+                vec![$($crate::arenas::ArenaStats::of(stringify!($name), &self.$name),)*]
+            }
+        }
+
+        impl<'a, $($lt,)* T> $crate::arenas::AllocOwned<'a, 'a, T> for $arena_name<$($lt),*>
+        where
+            T: ToOwned<Owned = T> + 'a,
+            Self: $crate::arenas::Alloc<'a, 'a, T>,
+        {
+            fn alloc_owned(&'a self, value: T) -> &'a T {
+                $crate::arenas::Alloc::alloc(self, value)
+            }
         }
 
         impl<$($lt),*> Default for $arena_name<$($lt),*> {
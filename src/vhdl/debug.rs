@@ -131,13 +131,13 @@ impl<'b, 'a, 't: 'a> Alloc<'b, 't, konst2::IntegerConst<'t>> for &'a TypeVisitor
     }
 }
 
-impl<'a, 'b, 't: 'a> AllocOwned<'b, 't, konst2::Const2<'t>> for &'a TypeVisitor<'t> {
+impl<'a, 'b, 't: 'a> AllocOwned<'b, 't, dyn konst2::Const2<'t> + 't> for &'a TypeVisitor<'t> {
     fn alloc_owned(&'b self, value: OwnedConst<'t>) -> &'t Const2<'t> {
         self.const_arena.alloc_owned(value)
     }
 }
 
-impl<'a, 'b, 't: 'a> AllocOwned<'b, 't, ty2::Type> for &'a TypeVisitor<'t> {
+impl<'a, 'b, 't: 'a> AllocOwned<'b, 't, dyn ty2::Type + 't> for &'a TypeVisitor<'t> {
     fn alloc_owned(&'b self, value: ty2::OwnedType<'t>) -> &'t ty2::Type {
         self.type_arena.alloc_owned(value)
     }
@@ -19,7 +19,7 @@ mod expr;
 mod lib;
 mod misc;
 mod node;
-mod obj_decl;
+pub mod obj_decl;
 mod pkg;
 mod prelude;
 mod slot;
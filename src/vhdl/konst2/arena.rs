@@ -13,7 +13,7 @@ make_arenas!(
     }
 );
 
-impl<'t> AllocOwned<'t, 't, Const2<'t>> for ConstArena<'t> {
+impl<'t> AllocOwned<'t, 't, dyn Const2<'t> + 't> for ConstArena<'t> {
     fn alloc_owned(&'t self, value: OwnedConst<'t>) -> &'t Const2<'t> {
         match value {
             OwnedConst::Integer(k) => self.alloc(k),
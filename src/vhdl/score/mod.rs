@@ -22,7 +22,7 @@ use llhd;
 use num::{BigInt, Signed};
 use typed_arena::Arena;
 
-use crate::arenas::Alloc;
+use crate::arenas::{Alloc, ArenaStats};
 use crate::builtin;
 pub use crate::builtin::*;
 use crate::codegen::Codegen;
@@ -1136,6 +1136,24 @@ impl Arenas {
             konst: Arena::new(),
         }
     }
+
+    /// Report the number of values and bytes allocated in each arena, grouped
+    /// by the representation they belong to.
+    pub fn stats(&self) -> Vec<(&'static str, Vec<ArenaStats>)> {
+        vec![
+            ("vhdl.hir", self.hir.stats()),
+            (
+                "vhdl",
+                vec![
+                    ArenaStats::of("defs", &self.defs),
+                    ArenaStats::of("archs", &self.archs),
+                    ArenaStats::of("scope", &self.scope),
+                    ArenaStats::of("ty", &self.ty),
+                    ArenaStats::of("konst", &self.konst),
+                ],
+            ),
+        ]
+    }
 }
 
 /// A table of the architectures in a library, and how they relate to the
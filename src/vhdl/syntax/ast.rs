@@ -10,6 +10,7 @@ use crate::lexer::token::Literal;
 use moore_common::name::Name;
 use moore_common::source::{Span, Spanned};
 use moore_common::util::{HasDesc, HasSpan};
+use serde::Serialize;
 
 pub use self::ExprData::*;
 pub use self::StmtData::*;
@@ -18,7 +19,7 @@ pub use self::TypeData::*;
 /// A positive, small ID assigned to each node in the AST. Used as a lightweight
 /// way to refer to individual nodes, e.g. during symbol table construction and
 /// name resolution.
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Debug, Serialize)]
 pub struct NodeId(u32);
 
 impl NodeId {
@@ -61,14 +62,14 @@ pub const DUMMY_NODE_ID: NodeId = NodeId(0);
 /// A design unit. Multiple design units make up a design file. Each unit
 /// consists of an optional context clause followed by a primary or secondary
 /// unit.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct DesignUnit {
     pub id: NodeId,
     pub ctx: Vec<CtxItem>,
     pub data: DesignUnitData,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum DesignUnitData {
     EntityDecl(EntityDecl),
     CfgDecl(CfgDecl),
@@ -80,7 +81,7 @@ pub enum DesignUnitData {
 }
 
 /// A context item, multiple of which make up a context clause.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum CtxItem {
     LibClause(Spanned<Vec<Ident>>),
     UseClause(Spanned<Vec<CompoundName>>),
@@ -88,7 +89,7 @@ pub enum CtxItem {
 }
 
 /// An identifier. Has a node ID such that it may be referenced later on.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 // #[deprecated(note="use `Spanned<Name>` instead of `Ident`")]
 pub struct Ident {
     pub id: NodeId,
@@ -128,7 +129,7 @@ impl Into<Spanned<Name>> for Ident {
 /// | attribute_name      | `NamePart::Attribute`           |
 /// | external_name       | not implemented                 |
 ///
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct CompoundName {
     pub id: NodeId,
     pub span: Span,
@@ -148,21 +149,21 @@ impl HasDesc for CompoundName {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct PrimaryName {
     pub id: NodeId,
     pub span: Span,
     pub kind: PrimaryNameKind,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum PrimaryNameKind {
     Ident(Name),
     Char(char),
     String(Name),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum NamePart {
     Select(PrimaryName),
     SelectAll(Span),
@@ -173,7 +174,7 @@ pub enum NamePart {
 }
 
 /// A context declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct CtxDecl {
     pub id: NodeId,
     pub span: Span,
@@ -182,17 +183,19 @@ pub struct CtxDecl {
 }
 
 /// An entity declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct EntityDecl {
     pub id: NodeId,
     pub span: Span,
     pub name: Spanned<Name>,
     pub decls: Vec<DeclItem>,
     pub stmts: Option<Vec<Stmt>>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
 }
 
 /// A configuration declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct CfgDecl {
     pub id: NodeId,
     pub span: Span,
@@ -202,7 +205,7 @@ pub struct CfgDecl {
 }
 
 /// An architecture body.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct ArchBody {
     pub id: NodeId,
     pub span: Span,
@@ -213,16 +216,18 @@ pub struct ArchBody {
 }
 
 /// A package declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct PkgDecl {
     pub id: NodeId,
     pub span: Span,
     pub name: Spanned<Name>,
     pub decls: Vec<DeclItem>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
 }
 
 /// A package body.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct PkgBody {
     pub id: NodeId,
     pub span: Span,
@@ -231,7 +236,7 @@ pub struct PkgBody {
 }
 
 /// A package instantiation declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct PkgInst {
     pub id: NodeId,
     pub span: Span,
@@ -241,7 +246,7 @@ pub struct PkgInst {
 }
 
 /// An interface declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum IntfDecl {
     TypeDecl(TypeDecl),
     SubprogSpec(IntfSubprogDecl),
@@ -279,7 +284,7 @@ impl HasDesc for IntfDecl {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct IntfSubprogDecl {
     pub id: NodeId,
     pub span: Span,
@@ -287,14 +292,14 @@ pub struct IntfSubprogDecl {
     pub default: Option<SubprogDefault>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum SubprogDefault {
     Any,
     Name(CompoundName),
 }
 
 /// An interface object declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct IntfObjDecl {
     pub kind: IntfObjKind,
     pub span: Span,
@@ -303,6 +308,8 @@ pub struct IntfObjDecl {
     pub ty: SubtypeInd,
     pub bus: bool,
     pub default: Option<Expr>,
+    /// The doc comment preceding the declaration.
+    pub doc: Option<Spanned<Name>>,
 }
 
 impl HasDesc for IntfObjDecl {
@@ -316,7 +323,7 @@ impl HasDesc for IntfObjDecl {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub enum IntfObjKind {
     Const,
     Signal,
@@ -324,7 +331,7 @@ pub enum IntfObjKind {
     File,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub enum IntfMode {
     In,
     Out,
@@ -334,7 +341,7 @@ pub enum IntfMode {
 }
 
 /// A declarative item.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum DeclItem {
     PkgBody(PkgBody),
     PkgInst(PkgInst),
@@ -452,13 +459,13 @@ impl HasDesc for DeclItem {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum PortgenKind {
     Port,
     Generic,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Subprog {
     pub id: NodeId,
     pub span: Span,
@@ -482,7 +489,7 @@ impl HasDesc for Subprog {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum SubprogData {
     Decl,
     Inst {
@@ -505,7 +512,7 @@ impl HasDesc for SubprogData {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct SubprogSpec {
     pub span: Span,
     pub name: PrimaryName,
@@ -517,19 +524,19 @@ pub struct SubprogSpec {
     pub retty: Option<CompoundName>,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum SubprogPurity {
     Pure,
     Impure,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum SubprogKind {
     Proc,
     Func,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct SubtypeInd {
     pub span: Span,
     pub res: Option<ResolInd>,
@@ -548,7 +555,7 @@ impl HasDesc for SubtypeInd {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct SubtypeDecl {
     pub id: NodeId,
     pub span: Span,
@@ -556,14 +563,14 @@ pub struct SubtypeDecl {
     pub subtype: SubtypeInd,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum ResolInd {
     Exprs(ParenElems),
     Name(CompoundName),
 }
 
 /// An alias declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct AliasDecl {
     pub id: NodeId,
     pub span: Span,
@@ -572,7 +579,7 @@ pub struct AliasDecl {
     pub target: CompoundName,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct ObjDecl {
     pub span: Span,
     pub kind: ObjKind,
@@ -582,7 +589,7 @@ pub struct ObjDecl {
     pub init: Option<Expr>,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum ObjKind {
     Const,
     Signal,
@@ -593,7 +600,7 @@ pub enum ObjKind {
 
 /// Additional mutually exclusive details that may be provided with an object
 /// declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum ObjDetail {
     Register,
     Bus,
@@ -627,7 +634,7 @@ impl HasDesc for ObjDecl {
 }
 
 /// A component declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct CompDecl {
     pub id: NodeId,
     pub span: Span,
@@ -636,7 +643,7 @@ pub struct CompDecl {
     pub ports: Option<Spanned<Vec<IntfDecl>>>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct DisconSpec {
     pub span: Span,
     pub target: DisconTarget,
@@ -644,14 +651,14 @@ pub struct DisconSpec {
     pub after: Expr,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum DisconTarget {
     Others,
     All,
     Signals(Vec<CompoundName>),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct BlockCompCfg {
     pub span: Span,
     pub spec: Spanned<BlockCompSpec>,
@@ -659,7 +666,7 @@ pub struct BlockCompCfg {
     pub decls: Vec<DeclItem>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum BlockCompSpec {
     CompOthers(CompoundName),
     CompAll(CompoundName),
@@ -667,7 +674,7 @@ pub enum BlockCompSpec {
     Block(CompoundName),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct BindingInd {
     pub span: Span,
     pub entity: Option<EntityAspect>,
@@ -675,14 +682,14 @@ pub struct BindingInd {
     pub ports: Option<ParenElems>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum EntityAspect {
     Entity(CompoundName),
     Cfg(CompoundName),
     Open,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct CfgSpec {
     pub span: Span,
     pub spec: Spanned<BlockCompSpec>,
@@ -690,7 +697,7 @@ pub struct CfgSpec {
     pub vunits: Vec<()>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct AttrDecl {
     pub id: NodeId,
     pub span: Span,
@@ -698,7 +705,7 @@ pub struct AttrDecl {
     pub data: AttrData,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum AttrData {
     Decl(CompoundName),
     Spec {
@@ -708,14 +715,14 @@ pub enum AttrData {
     },
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum AttrTarget {
     Others,
     All,
     List(Vec<(CompoundName, Option<Signature>)>),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum EntityClass {
     Arch,
     Comp,
@@ -738,7 +745,7 @@ pub enum EntityClass {
     Var,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct GroupDecl {
     pub id: NodeId,
     pub span: Span,
@@ -746,7 +753,7 @@ pub struct GroupDecl {
     pub data: GroupData,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum GroupData {
     /// A group declaration.
     Decl(CompoundName),
@@ -758,7 +765,7 @@ pub enum GroupData {
 /// A parenthesized expression element. A parenthesized expression contains
 /// elements of which each may either be a simple `<expr>`, or an association of
 /// the form `<choices> => <expr>`.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct ParenElem {
     pub span: Span,
     pub choices: Choices,
@@ -770,7 +777,7 @@ pub struct ParenElem {
 pub type ParenElems = Spanned<Vec<ParenElem>>;
 
 /// An expression.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Expr {
     pub span: Span,
     pub data: ExprData,
@@ -789,7 +796,7 @@ impl HasDesc for Expr {
 }
 
 /// The data associated with a specific expression.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum ExprData {
     NullExpr,
     OpenExpr,
@@ -818,7 +825,7 @@ impl HasDesc for ExprData {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum UnaryOp {
     Not,
     Abs,
@@ -828,7 +835,7 @@ pub enum UnaryOp {
     Condition,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum BinaryOp {
     Dir(Dir),
     Logical(LogicalOp),
@@ -845,7 +852,7 @@ pub enum BinaryOp {
     Pow,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum Dir {
     To,
     Downto,
@@ -860,13 +867,13 @@ impl std::fmt::Display for Dir {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum Sign {
     Pos,
     Neg,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum LogicalOp {
     And,
     Or,
@@ -889,7 +896,7 @@ impl fmt::Display for LogicalOp {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum RelationalOp {
     Eq,
     Neq,
@@ -912,7 +919,7 @@ impl fmt::Display for RelationalOp {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum ShiftOp {
     Sll,
     Srl,
@@ -937,7 +944,7 @@ impl fmt::Display for ShiftOp {
 
 /// A type declaration. If the `data` field is omitted, this is an incomplete
 /// declaration.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct TypeDecl {
     pub id: NodeId,
     pub span: Span,
@@ -964,7 +971,7 @@ impl HasDesc for TypeDecl {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum TypeData {
     EnumType(ParenElems),
     RangeType(Box<Expr>, Option<Vec<(Ident, Option<Box<Expr>>)>>),
@@ -989,7 +996,7 @@ impl HasDesc for TypeData {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Stmt {
     pub id: NodeId,
     pub span: Span,
@@ -1016,7 +1023,7 @@ impl HasDesc for Stmt {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum StmtData {
     WaitStmt {
         on: Option<Spanned<Vec<CompoundName>>>,
@@ -1131,26 +1138,26 @@ impl HasDesc for StmtData {
 }
 
 /// The body of an if, loop, or case statement.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct StmtBody {
     pub id: NodeId,
     pub stmts: Vec<Stmt>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum LoopScheme {
     While(Expr),
     For(Spanned<Name>, Expr),
     Loop,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum NexitMode {
     Next,
     Exit,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct GenBody {
     pub id: NodeId,
     pub label: Option<Spanned<Name>>,
@@ -1159,73 +1166,73 @@ pub struct GenBody {
     pub stmts: Vec<Stmt>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum Sensitivity {
     All,
     List(Vec<CompoundName>),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum AssignTarget {
     Name(CompoundName),
     Aggregate(ParenElems),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum InstTarget {
     Comp,
     Entity,
     Cfg,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum AssignKind {
     Signal,
     Var,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum AssignMode {
     Release(Option<Spanned<ForceMode>>),
     Force(Option<Spanned<ForceMode>>, Vec<CondWave>),
     Normal(Option<Spanned<DelayMech>>, Vec<CondWave>),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum SelectAssignMode {
     Force(Option<Spanned<ForceMode>>),
     Normal(Option<Spanned<DelayMech>>),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum ForceMode {
     In,
     Out,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum DelayMech {
     Transport,
     Inertial,
     InertialReject(Expr),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Wave {
     pub span: Span,
     pub elems: Option<Vec<(Expr, Option<Expr>)>>,
 }
 
 /// A conditional wave.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct CondWave(pub Wave, pub Option<Expr>);
 
 /// A selected wave. The second element of the tuple represents the choices for
 /// which this wave would be selected.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct SelectWave(pub Wave, pub Choices);
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct Signature {
     pub span: Span,
     pub args: Vec<CompoundName>,
@@ -52,7 +52,7 @@ where
                     if d == '\n' {
                         break;
                     } else {
-                        sp.end = offset + sz as usize;
+                        sp.end = (offset + sz as usize) as u32;
                         self.inner.next();
                     }
                 }
@@ -72,7 +72,7 @@ where
                     } else {
                         p0 = p1;
                         p1 = Some(d);
-                        sp.end = offset + sz as usize;
+                        sp.end = (offset + sz as usize) as u32;
                         self.inner.next();
                     }
                 }
@@ -87,7 +87,7 @@ where
                     if let Some((offset, '\'', sz, _)) = *self.inner.lookahead(1) {
                         self.inner.next();
                         self.inner.next();
-                        sp.end = offset + sz as usize;
+                        sp.end = (offset + sz as usize) as u32;
                         return Some(Spanned::new(Bundle::BitLiteral(c), sp));
                     }
                 }
@@ -98,7 +98,7 @@ where
         if c == '"' {
             let mut s = String::new();
             while let Some((offset, d, sz, _)) = self.inner.next() {
-                sp.end = offset + sz as usize;
+                sp.end = (offset + sz as usize) as u32;
                 if d == '"' {
                     if let Some((_, '"', _, _)) = *self.inner.lookahead(0) {
                         s.push('"');
@@ -127,7 +127,7 @@ where
             let mut s = String::new();
             s.push(c);
             while let Some((offset, d, sz, _)) = self.inner.next() {
-                sp.end = offset + sz as usize;
+                sp.end = (offset + sz as usize) as u32;
                 if d == '\\' {
                     s.push('\\');
                     if let Some((_, '\\', _, _)) = *self.inner.lookahead(0) {
@@ -157,7 +157,7 @@ where
                 while let &Some((offset, d, sz, c)) = self.inner.lookahead(0) {
                     if c == cat {
                         s.push(d);
-                        sp.end = offset + sz as usize;
+                        sp.end = (offset + sz as usize) as u32;
                         self.inner.next();
                     } else {
                         break;
@@ -178,7 +178,7 @@ where
             // spaces themselves.
             Category::Space => {
                 while let Some((offset, _, sz, Category::Space)) = *self.inner.lookahead(0) {
-                    sp.end = offset + sz as usize;
+                    sp.end = (offset + sz as usize) as u32;
                     self.inner.next();
                 }
                 Some(Spanned::new(Bundle::Space, sp))
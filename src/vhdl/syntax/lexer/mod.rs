@@ -22,6 +22,7 @@ use moore_common::source::*;
 /// backwards up the pipeline.
 pub struct Lexer<T: Grinder<Item = Option<u8>, Error = DiagBuilder2>> {
     inner: Tokenizer<Bundler<Categorizer<Utf8<T>>>>,
+    standard: Standard,
 }
 
 impl<T> Lexer<T>
@@ -30,11 +31,24 @@ where
 {
     /// Create a new lexer.
     pub fn new(bytes: T, src: Source) -> Lexer<T> {
+        Self::with_standard(bytes, src, Standard::default())
+    }
+
+    /// Create a new lexer for a revision of the standard.
+    pub fn with_standard(bytes: T, src: Source, standard: Standard) -> Lexer<T> {
         let chars = Utf8::new(bytes);
         let cat = Categorizer::new(chars);
         let bundles = Bundler::new(cat, src);
-        let tokens = Tokenizer::new(bundles);
-        Lexer { inner: tokens }
+        let tokens = Tokenizer::new(bundles, standard);
+        Lexer {
+            inner: tokens,
+            standard,
+        }
+    }
+
+    /// Get the revision of the standard the input is lexed as.
+    pub fn standard(&self) -> Standard {
+        self.standard
     }
 }
 
@@ -76,15 +90,7 @@ mod test {
     }
 
     fn check(input: &str, expected: &[Token]) {
-        use std::cell::Cell;
-        thread_local!(static INDEX: Cell<usize> = Cell::new(0));
-        let sm = get_source_manager();
-        let idx = INDEX.with(|i| {
-            let v = i.get();
-            i.set(v + 1);
-            v
-        });
-        let source = sm.add(&format!("test_{}.vhd", idx), input);
+        let source = get_source_manager().add_anonymous(input);
         let actual = lex(source);
         assert_eq!(actual.len(), expected.len());
         for (a, &e) in actual.into_iter().zip(expected.into_iter()) {
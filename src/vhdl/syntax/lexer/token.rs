@@ -3,6 +3,7 @@
 pub use self::DelimToken::*;
 pub use self::Token::*;
 use moore_common::name::*;
+use serde::Serialize;
 use std;
 use std::fmt::{Display, Formatter, Result};
 
@@ -110,6 +111,16 @@ impl Token {
             _ => false,
         }
     }
+
+    /// Get the revision of the standard which introduced this token.
+    pub fn standard(self) -> Standard {
+        match self {
+            Keyword(kw) => kw.standard(),
+            Condition | Lshift | Rshift | MatchEq | MatchNeq | MatchLt | MatchLeq | MatchGt
+            | MatchGeq | Qmark => Standard::Vhdl2008,
+            _ => Standard::Vhdl1993,
+        }
+    }
 }
 
 impl Display for Token {
@@ -123,7 +134,7 @@ impl Display for Token {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum Literal {
     Abstract(
         /// Base
@@ -164,7 +175,7 @@ pub enum DelimToken {
     Brack,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub struct Exponent(
     /// Sign
     pub ExponentSign,
@@ -172,13 +183,13 @@ pub struct Exponent(
     pub Name,
 );
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum ExponentSign {
     Positive,
     Negative,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
 pub enum BitStringBase {
     B,
     O,
@@ -348,3 +359,59 @@ declare_keywords! {
     (Xnor,               "xnor")
     (Xor,                "xor")
 }
+
+impl Kw {
+    /// Get the revision of the standard which introduced this keyword.
+    pub fn standard(self) -> Standard {
+        use Kw::*;
+        match self {
+            Protected => Standard::Vhdl2002,
+            Assume | AssumeGuarantee | Context | Cover | Default | Fairness | Force | Parameter
+            | Property | Release | Restrict | RestrictGuarantee | Sequence | Strong | Vmode
+            | Vprop | Vunit => Standard::Vhdl2008,
+            _ => Standard::Vhdl1993,
+        }
+    }
+}
+
+/// A revision of the VHDL standard.
+///
+/// Revisions are ordered by their publication, such that a revision compares
+/// greater than the ones it extends.
+#[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Debug, Hash)]
+pub enum Standard {
+    /// IEEE 1076-1993
+    Vhdl1993,
+    /// IEEE 1076-2002
+    Vhdl2002,
+    /// IEEE 1076-2008
+    Vhdl2008,
+}
+
+impl Standard {
+    /// Parse a revision given as the last digits of its year, e.g. `"93"`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "93" | "1993" => Some(Self::Vhdl1993),
+            "02" | "2002" => Some(Self::Vhdl2002),
+            "08" | "2008" => Some(Self::Vhdl2008),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Standard {
+    fn default() -> Self {
+        Self::Vhdl2008
+    }
+}
+
+impl Display for Standard {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Vhdl1993 => write!(f, "VHDL-93"),
+            Self::Vhdl2002 => write!(f, "VHDL-2002"),
+            Self::Vhdl2008 => write!(f, "VHDL-2008"),
+        }
+    }
+}
@@ -11,19 +11,22 @@ use moore_common::source::*;
 /// last stage of lexical analysis.
 pub struct Tokenizer<T: Grinder> {
     inner: Lookahead<T>,
+    standard: Standard,
 }
 
 impl<T: Grinder> Tokenizer<T>
 where
     T: Grinder<Item = Option<Spanned<Bundle>>, Error = DiagBuilder2>,
 {
-    /// Create a new bundler.
-    pub fn new<I>(inner: I) -> Tokenizer<T>
+    /// Create a new tokenizer which recognizes the keywords of a revision of
+    /// the standard.
+    pub fn new<I>(inner: I, standard: Standard) -> Tokenizer<T>
     where
         I: Into<Lookahead<T>>,
     {
         Tokenizer {
             inner: inner.into(),
+            standard,
         }
     }
 
@@ -379,9 +382,12 @@ where
                         }
                     }
 
-                    // See if this identifier is a keyword.
+                    // See if this identifier is a keyword. Keywords of
+                    // later revisions of the standard are identifiers.
                     Some(Spanned::new(
-                        if let Some(kw) = find_keyword(&s) {
+                        if let Some(kw) =
+                            find_keyword(&s).filter(|kw| kw.standard() <= self.standard)
+                        {
                             Keyword(kw)
                         } else {
                             Ident(get_name_table().intern(&s, false))
@@ -14,15 +14,26 @@ use moore_common::grind::{self, Grinder};
 use moore_common::source::*;
 
 pub fn parse(src: Source) -> Result<Vec<ast::DesignUnit>, ()> {
+    parse_with_standard(src, Default::default())
+}
+
+/// Parse a source file as a revision of the standard.
+///
+/// Keywords of later revisions are treated as identifiers, and tokens which
+/// the revision does not provide are reported as errors.
+pub fn parse_with_standard(
+    src: Source,
+    standard: lexer::token::Standard,
+) -> Result<Vec<ast::DesignUnit>, ()> {
     use self::parser::token_stream::TokenStream;
 
     // Get a grinder on the bytes of the source file.
     let content = src.get_content();
     let bytes = grind::from_iter(content.bytes().iter().map(|x| *x))
-        .vent(|err: DiagBuilder2| eprintln!("{}", err));
+        .vent(|err: DiagBuilder2| print_diag(&err, format_args!("{}", err)));
 
     // Perform lexical analysis on the bytes.
-    let tokens = lexer::Lexer::new(bytes, src);
+    let tokens = lexer::Lexer::with_standard(bytes, src, standard);
 
     // Parse the file.
     let mut parser = parser::basic::BasicParser::new(tokens);
@@ -3,13 +3,14 @@
 //! This module implements a basic parser that accepts tokens from the VHDL
 //! lexer and emits errors back to it.
 
-use crate::lexer::token::Token;
+use crate::lexer::token::{find_keyword, Token};
 use crate::lexer::Lexer;
 use crate::parser::TokenStream;
 use moore_common::errors::*;
 use moore_common::grind::Grinder;
+use moore_common::name::Name;
 use moore_common::source::*;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 
 pub struct BasicParser<T>
 where
@@ -20,6 +21,8 @@ where
     last_span: Span,
     severity: Severity,
     consumed: usize,
+    /// The keywords of later standards which appeared as identifiers.
+    demoted_keywords: HashSet<Name>,
 }
 
 impl<T> TokenStream<Token> for BasicParser<T>
@@ -80,6 +83,41 @@ where
             last_span: INVALID_SPAN,
             severity: Severity::Note,
             consumed: 0,
+            demoted_keywords: HashSet::new(),
+        }
+    }
+
+    /// Diagnose tokens which the standard the input is parsed as does not
+    /// provide.
+    fn check_standard(&mut self, token: Spanned<Token>) {
+        let standard = self.input.standard();
+        match token.value {
+            Token::Ident(name) if !name.is_case_sensitive() => {
+                let kw = match find_keyword(&*name.as_str()) {
+                    Some(kw) => kw,
+                    None => return,
+                };
+                if self.demoted_keywords.insert(name) {
+                    self.emit(
+                        DiagBuilder2::warning(format!(
+                            "`{}` is a keyword in {} and later",
+                            name,
+                            kw.standard()
+                        ))
+                        .span(token.span)
+                        .add_note(format!("It is treated as an identifier in {}", standard))
+                        .add_note("Use `--vhdl-std` to select a later standard"),
+                    );
+                }
+            }
+            tkn if tkn.standard() > standard => {
+                self.emit(
+                    DiagBuilder2::error(format!("{} requires {}", tkn, tkn.standard()))
+                        .span(token.span)
+                        .add_note(format!("The input is parsed as {}", standard)),
+                );
+            }
+            _ => (),
         }
     }
 
@@ -94,7 +132,10 @@ where
         }
         while self.queue.len() <= min_tokens {
             match self.input.next() {
-                Some(t) => self.queue.push_back(t),
+                Some(t) => {
+                    self.check_standard(t);
+                    self.queue.push_back(t);
+                }
                 None => {
                     self.queue
                         .push_back(Spanned::new(Token::Eof, self.last_span.end().into()));
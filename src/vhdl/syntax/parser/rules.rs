@@ -372,6 +372,12 @@ pub fn parse_name_suffix<P: Parser>(
     Ok(name)
 }
 
+/// Find the `--!` or `/** */` doc comment preceding a declaration.
+fn doc_comment(span: Span) -> Option<Spanned<Name>> {
+    span.doc_comment("--!")
+        .map(|doc| doc.map(|text| get_name_table().intern(&text, true)))
+}
+
 /// Parse the optional trailing name after an entity, configuration, etc., which
 /// must match the name at the beginning of the declaration.
 fn parse_optional_matching_ident<P, M1, M2, T>(p: &mut P, name: T, msg: M1, sec: M2)
@@ -473,6 +479,7 @@ pub fn parse_entity_decl<P: Parser>(p: &mut P) -> ReportedResult<ast::EntityDecl
         name: name,
         decls: decl_items,
         stmts: stmts,
+        doc: doc_comment(span),
     })
 }
 
@@ -758,6 +765,7 @@ pub fn parse_intf_decl<P: Parser>(
         ty: ty,
         bus: bus,
         default: def,
+        doc: doc_comment(span),
     }))
 }
 
@@ -1449,6 +1457,7 @@ pub fn parse_package_decl<P: Parser>(p: &mut P) -> ReportedResult<ast::PkgDecl>
         span: span,
         name: name,
         decls: decl_items,
+        doc: doc_comment(span),
     })
 }
 
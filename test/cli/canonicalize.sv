@@ -0,0 +1,16 @@
+// RUN: moore %s -e foo
+
+module foo (input logic [7:0] a, output logic [7:0] q);
+    logic [3:0][7:0] arr;
+    assign q = a + 8'd2 + 8'd3;
+    assign arr[1] = a;
+endmodule
+
+// CHECK: entity @foo (i8$ %a) -> (i8$ %q) {
+// CHECK:     %1 = [4 x i8 %0]
+// CHECK:     %arr = sig [4 x i8] %1
+// CHECK:     %3 = const i8 5
+// CHECK:     %4 = add i8 %a.prb, %3
+// CHECK:     %5 = extf i8$, [4 x i8]$ %arr, 1
+// CHECK:     drv i8$ %5, %a.prb, %2
+// CHECK: }
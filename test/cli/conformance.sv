@@ -0,0 +1,8 @@
+// RUN: moore conformance test/cli/conformance_tests
+
+// CHECK: unexpected pass of test/cli/conformance_tests/chapter-23/wrong_expectation.sv
+// CHECK-NEXT: section   tests  passed     rate
+// CHECK-NEXT: total         6       5    83.3%
+// CHECK-NEXT: 5             2       2   100.0%
+// CHECK-NEXT: 23            3       2    66.7%
+// CHECK-NEXT: misc          1       1   100.0%
@@ -0,0 +1,8 @@
+/*
+:name: ansi_ports
+:description: module with an ANSI port list
+:tags: 23.2.2.2
+*/
+module top (input logic a, output logic z);
+    assign z = a;
+endmodule
@@ -0,0 +1,10 @@
+/*
+:name: elab_only
+:description: a missing module is only detected during elaboration
+:should_fail_because: module foo does not exist
+:tags: 23.3
+:type: elaboration
+*/
+module top;
+    foo u0 ();
+endmodule
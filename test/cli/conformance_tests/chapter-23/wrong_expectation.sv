@@ -0,0 +1,8 @@
+/*
+:name: wrong_expectation
+:description: a valid module which is wrongly expected to be rejected
+:should_fail_because: the test itself is wrong
+:tags: 23.2.1
+*/
+module top;
+endmodule
@@ -0,0 +1,9 @@
+/*
+:name: integer_literals
+:description: sized and unsized integer literals
+:tags: 5.7.1
+*/
+module top;
+    logic [7:0] a = 8'hff;
+    int b = 42;
+endmodule
@@ -0,0 +1,9 @@
+// IGNORE
+// @exclude
+/*
+:name: missing_endmodule
+:description: a module missing its end
+:should_fail_because: the module is not closed by endmodule
+:tags: 5.4
+*/
+module top;
@@ -0,0 +1,19 @@
+// RUN: moore %s -e foo -g
+// Source locations are emitted as comments, which leave the body unchanged.
+
+module foo (input logic a, output logic b);
+    logic t;
+    assign t = ~a;
+    assign b = t;
+endmodule
+
+// CHECK: entity @foo (i1$ %a) -> (i1$ %b) {
+// CHECK-NEXT:     %0 = const i1 0
+// CHECK-NEXT:     %t = sig i1 %0
+// CHECK-NEXT:     %1 = const time 0s 1e
+// CHECK-NEXT:     %a.prb = prb i1$ %a
+// CHECK-NEXT:     %2 = not i1 %a.prb
+// CHECK-NEXT:     drv i1$ %t, %2, %1
+// CHECK-NEXT:     %t.prb = prb i1$ %t
+// CHECK-NEXT:     drv i1$ %b, %t.prb, %1
+// CHECK-NEXT: }
@@ -0,0 +1,11 @@
+// RUN: moore %s -e top -D MSB=7 -D EQ=a==b +define+LSB=0+ENABLE
+
+module top (input logic [`MSB:`LSB] a, b, output logic z);
+`ifdef ENABLE
+    assign z = `EQ;
+`else
+    assign z = 0;
+`endif
+endmodule
+
+// CHECK: entity @top (i8$ %a, i8$ %b) -> (i1$ %z) {
@@ -0,0 +1,7 @@
+// RUN: moore %s -D 1WIDTH=8
+// FAIL
+
+module top;
+endmodule
+
+// CHECK-ERR: fatal: invalid macro name `1WIDTH` in define `1WIDTH=8`
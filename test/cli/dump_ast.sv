@@ -0,0 +1,14 @@
+// RUN: moore %s --dump-ast=json
+
+module foo (input logic a, output logic b);
+    assign b = ~a;
+endmodule
+
+// CHECK: "node": "Module",
+// CHECK: "begin": 34,
+// CHECK-NEXT: "end": 96
+// CHECK: "value": "foo",
+// CHECK-NEXT: "span": {
+// CHECK: "begin": 41,
+// CHECK-NEXT: "end": 44
+// CHECK: "node": "ContAssign",
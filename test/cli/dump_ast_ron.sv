@@ -0,0 +1,10 @@
+// RUN: moore %s --dump-ast=ron
+
+package foo;
+    localparam int W = 8;
+endpackage
+
+// CHECK: data: PackageDecl((
+// CHECK: node: "Package",
+// CHECK: value: "foo",
+// CHECK: node: "ParamDecl",
@@ -0,0 +1,26 @@
+// RUN: moore %s --dump-deps
+
+package pkg;
+    localparam int W = 8;
+endpackage
+
+module top;
+    pkg::word_t x;
+    leaf #(pkg::W) i_leaf ();
+    ip_core i_core ();
+endmodule
+
+module leaf #(int N = 1);
+    import pkg::*;
+endmodule
+
+// CHECK: digraph deps {
+// CHECK-NEXT: n0 [label="package pkg"];
+// CHECK-NEXT: n1 [label="module top"];
+// CHECK-NEXT: n2 [label="module leaf"];
+// CHECK-NEXT: n1 -> n0 [label="import"];
+// CHECK-NEXT: n1 -> n2 [label="instance"];
+// CHECK-NEXT: n1 -> "ip_core" [label="instance"];
+// CHECK-NEXT: n2 -> n0 [label="import"];
+// CHECK-NEXT: "ip_core" [style=dashed];
+// CHECK-NEXT: }
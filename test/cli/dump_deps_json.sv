@@ -0,0 +1,18 @@
+// RUN: moore %s --dump-deps=json
+
+module top;
+    leaf i_leaf ();
+endmodule
+
+module leaf;
+endmodule
+
+// CHECK: "name": "top",
+// CHECK-NEXT: "kind": "module",
+// CHECK: "deps": [
+// CHECK-NEXT: {
+// CHECK-NEXT: "kind": "instance",
+// CHECK-NEXT: "target": "leaf"
+// CHECK: "name": "leaf",
+// CHECK-NEXT: "kind": "module",
+// CHECK: "deps": []
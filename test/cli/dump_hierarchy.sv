@@ -0,0 +1,46 @@
+// RUN: moore %s -e top --dump-hierarchy
+
+module leaf #(parameter int WIDTH = 8, localparam int L = 2) (
+    input logic clk,
+    input logic [WIDTH-1:0] a,
+    output logic [WIDTH-1:0] y
+);
+    assign y = a;
+endmodule
+
+module top (input logic clk, input logic [3:0] x, output logic [3:0] z);
+    for (genvar i = 0; i < 2; i++) begin : g
+        leaf #(.WIDTH(2 << i)) i_leaf (.clk, .a(x[i+:2]), .y());
+    end
+endmodule
+
+// CHECK: "path": "top",
+// CHECK-NEXT: "name": "top",
+// CHECK-NEXT: "kind": "module",
+// CHECK-NEXT: "target": "top",
+// CHECK: "params": [],
+// CHECK: "children": [
+// CHECK: "path": "top.g[0].i_leaf",
+// CHECK-NEXT: "name": "i_leaf",
+// CHECK-NEXT: "kind": "module",
+// CHECK-NEXT: "target": "leaf",
+// CHECK: "params": [
+// CHECK-NEXT: {
+// CHECK-NEXT: "name": "WIDTH",
+// CHECK-NEXT: "value": "2"
+// CHECK-NEXT: }
+// CHECK-NEXT: ],
+// CHECK-NEXT: "ports": [
+// CHECK-NEXT: {
+// CHECK-NEXT: "name": "clk",
+// CHECK-NEXT: "dir": "input",
+// CHECK-NEXT: "connection": "clk"
+// CHECK: "name": "a",
+// CHECK-NEXT: "dir": "input",
+// CHECK-NEXT: "connection": "x[i+:2]"
+// CHECK: "name": "y",
+// CHECK-NEXT: "dir": "output",
+// CHECK-NEXT: "connection": null
+// CHECK: "path": "top.g[1].i_leaf",
+// CHECK: "name": "WIDTH",
+// CHECK-NEXT: "value": "4"
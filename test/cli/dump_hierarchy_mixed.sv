@@ -0,0 +1,14 @@
+// RUN: moore mixed_lang.vhd %s -e top --dump-hierarchy
+
+module top (input logic clk);
+    if (1) begin : on
+        adder #(.WIDTH(4)) i_add (.a('0), .b('0), .y(), .en(clk));
+    end
+endmodule
+
+// CHECK: "path": "top.on.i_add",
+// CHECK-NEXT: "name": "i_add",
+// CHECK-NEXT: "kind": "entity",
+// CHECK-NEXT: "target": "adder",
+// CHECK: "name": "WIDTH",
+// CHECK-NEXT: "value": "4"
@@ -0,0 +1,19 @@
+// RUN: moore test/cli/dump_macro_xref.sv +incdir+test/cli/incdir --dump-macro-xref
+
+`include "width.svh"
+`define MAX(a, b) ((a) > (b) ? (a) : (b))
+`define UNUSED 1
+
+module top (input logic [`WIDTH-1:0] a, output logic [`WIDTH-1:0] z);
+    assign z = `MAX(a, 1);
+endmodule
+
+// CHECK: `define MAX at test/cli/dump_macro_xref.sv:4:9
+// CHECK-NEXT:     expanded at test/cli/dump_macro_xref.sv:8:16
+// CHECK-NEXT: `define UNUSED at test/cli/dump_macro_xref.sv:5:9
+// CHECK-NEXT:     never expanded
+// CHECK-NEXT: `define WIDTH at test/cli/incdir/width.svh:1:9
+// CHECK-NEXT:     expanded at test/cli/dump_macro_xref.sv:7:26
+// CHECK-NEXT:     expanded at test/cli/dump_macro_xref.sv:7:55
+// CHECK-NEXT: `include test/cli/incdir/width.svh
+// CHECK-NEXT:     included at test/cli/dump_macro_xref.sv:3:1
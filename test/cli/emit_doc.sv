@@ -0,0 +1,26 @@
+// RUN: moore %s --emit-doc=json
+
+/// A counter.
+module counter #(
+    /// The width of the counter.
+    parameter int W = 8
+) (
+    /// The clock.
+    input logic clk,
+    /** The count. */
+    output logic [W-1:0] q
+);
+endmodule
+
+// CHECK: "name": "counter",
+// CHECK-NEXT: "kind": "module",
+// CHECK: "doc": "A counter.",
+// CHECK: "name": "W",
+// CHECK-NEXT: "decl": "parameter int W = 8",
+// CHECK-NEXT: "doc": "The width of the counter."
+// CHECK: "name": "clk",
+// CHECK-NEXT: "decl": "input logic clk",
+// CHECK-NEXT: "doc": "The clock."
+// CHECK: "name": "q",
+// CHECK-NEXT: "decl": "output logic [W-1:0] q",
+// CHECK-NEXT: "doc": "The count."
@@ -0,0 +1,41 @@
+// RUN: moore mixed_lang.vhd %s --emit-stubs
+
+module top #(parameter int W = 8) (
+    input logic clk,
+    output logic [W-1:0] q
+);
+    always_ff @(posedge clk) q <= q + 1;
+endmodule
+
+module leaf (a, b);
+    parameter N = 4;
+    input [N-1:0] a;
+    output reg b;
+    assign b = |a;
+endmodule
+
+// CHECK: library ieee;
+// CHECK-NEXT: use ieee.std_logic_1164.all;
+// CHECK: entity adder is
+// CHECK-NEXT: generic (WIDTH : natural := 8);
+// CHECK: end entity adder;
+// CHECK: architecture stub of adder is
+// CHECK-NEXT: begin
+// CHECK-NEXT: end architecture stub;
+// CHECK: (* blackbox *)
+// CHECK-NEXT: module top #(
+// CHECK-NEXT: parameter int W = 8
+// CHECK-NEXT: ) (
+// CHECK-NEXT: input logic clk,
+// CHECK-NEXT: output logic [W-1:0] q
+// CHECK-NEXT: );
+// CHECK-NEXT: endmodule
+// CHECK: (* blackbox *)
+// CHECK-NEXT: module leaf (
+// CHECK-NEXT: a,
+// CHECK-NEXT: b
+// CHECK-NEXT: );
+// CHECK-NEXT: parameter N = 4;
+// CHECK-NEXT: input [N-1:0] a;
+// CHECK-NEXT: output reg b;
+// CHECK-NEXT: endmodule
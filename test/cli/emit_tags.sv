@@ -0,0 +1,12 @@
+// RUN: moore %s --emit-tags
+
+module top;
+    leaf i_leaf ();
+endmodule
+
+module leaf;
+endmodule
+
+// CHECK: !_TAG_FILE_FORMAT	2	/extended format/
+// CHECK-NEXT: !_TAG_FILE_SORTED	1	/0=unsorted, 1=sorted, 2=foldcase/
+// CHECK-NEXT: !_TAG_PROGRAM_NAME	moore
@@ -0,0 +1,36 @@
+// RUN: moore %s mixed_lang.vhd --emit-tags=json
+
+package pkg;
+    class C;
+    endclass
+endpackage
+
+module top (input logic clk);
+    logic q;
+endmodule
+
+// CHECK: "name": "pkg",
+// CHECK-NEXT: "kind": "package",
+// CHECK: "line": 3,
+// CHECK-NEXT: "column": 9,
+// CHECK: "scope": null,
+// CHECK: "name": "C",
+// CHECK-NEXT: "kind": "class",
+// CHECK: "line": 4,
+// CHECK-NEXT: "column": 11,
+// CHECK: "scope": "pkg",
+// CHECK-NEXT: "scope_kind": "package"
+// CHECK: "name": "top",
+// CHECK-NEXT: "kind": "module",
+// CHECK: "name": "clk",
+// CHECK-NEXT: "kind": "port",
+// CHECK: "scope": "top",
+// CHECK: "name": "q",
+// CHECK-NEXT: "kind": "variable",
+// CHECK: "line": 9,
+// CHECK: "name": "adder",
+// CHECK-NEXT: "kind": "entity",
+// CHECK: "name": "WIDTH",
+// CHECK-NEXT: "kind": "parameter",
+// CHECK: "scope": "adder",
+// CHECK-NEXT: "scope_kind": "entity"
@@ -0,0 +1,11 @@
+// RUN: moore -F file_list/sources.f %s -e top
+
+module top (input logic [3:0] a, output logic [1:0] z);
+    sub i_sub (.x(a), .y(z));
+endmodule
+
+// CHECK: entity @leaf (i2$ %x) -> (i2$ %y) {
+// CHECK: entity @sub (i4$ %x) -> (i2$ %y) {
+// CHECK:     inst @leaf (i2$ %2) -> (i2$ %y)
+// CHECK: entity @top (i4$ %a) -> (i2$ %z) {
+// CHECK:     inst @sub (i4$ %a) -> (i2$ %z)
@@ -0,0 +1,3 @@
+module leaf (input logic [1:0] x, output logic [1:0] y);
+    assign y = ~x;
+endmodule
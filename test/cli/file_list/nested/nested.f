@@ -0,0 +1,2 @@
+# Nested file list.
+leaf.sv
@@ -0,0 +1,5 @@
+// Sources of the file list test, with paths relative to this file.
++define+WIDTH=4+ENABLE
++incdir+include
+sub.svh  # needs the defines above
+-F nested/nested.f
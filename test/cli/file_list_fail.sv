@@ -0,0 +1,7 @@
+// RUN: moore -f file_list/missing.f %s -e top
+// FAIL
+
+module top;
+endmodule
+
+// CHECK-ERR: = note: No such file or directory (os error 2)
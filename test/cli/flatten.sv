@@ -0,0 +1,25 @@
+// RUN: moore %s -e foo -O0 --flatten=1
+
+module foo (input bit a, output bit b);
+    bit t;
+    bar i0(a, t);
+    bar i1(t, b);
+endmodule
+
+module bar (input bit x, output bit y);
+    baz i0(x, y);
+endmodule
+
+module baz (input bit x, output bit y);
+    assign y = x;
+endmodule
+
+// CHECK: entity @baz (i1$ %x) -> (i1$ %y) {
+// CHECK: }
+// CHECK:
+// CHECK: entity @foo (i1$ %a) -> (i1$ %b) {
+// CHECK:     %0 = const i1 0
+// CHECK:     %t = sig i1 %0
+// CHECK:     inst @baz (i1$ %a) -> (i1$ %t)
+// CHECK:     inst @baz (i1$ %t) -> (i1$ %b)
+// CHECK: }
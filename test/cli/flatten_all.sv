@@ -0,0 +1,20 @@
+// RUN: moore %s -e foo -O0 --flatten
+
+module foo (input bit a, output bit b);
+    bar i0(a, b);
+endmodule
+
+module bar (input bit x, output bit y);
+    baz i0(x, y);
+endmodule
+
+module baz (input bit x, output bit y);
+    assign y = ~x;
+endmodule
+
+// CHECK: entity @foo (i1$ %a) -> (i1$ %b) {
+// CHECK:     %0 = const time 0s 1e
+// CHECK:     %i0.i0.x.prb = prb i1$ %a
+// CHECK:     %1 = not i1 %i0.i0.x.prb
+// CHECK:     drv i1$ %b, %1, %0
+// CHECK: }
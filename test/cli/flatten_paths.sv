@@ -0,0 +1,26 @@
+// RUN: moore %s -e foo -O0 --flatten
+
+module foo (input bit a, output bit b);
+    bit t;
+    bar i0(a, t);
+    bar i1(t, b);
+endmodule
+
+module bar (input bit x, output bit y);
+    baz i0(x, y);
+endmodule
+
+module baz (input bit x, output bit y);
+    assign y = x;
+endmodule
+
+// CHECK: entity @foo (i1$ %a) -> (i1$ %b) {
+// CHECK:     %0 = const i1 0
+// CHECK:     %t = sig i1 %0
+// CHECK:     %1 = const time 0s 1e
+// CHECK:     %i0.i0.x.prb = prb i1$ %a
+// CHECK:     drv i1$ %t, %i0.i0.x.prb, %1
+// CHECK:     %2 = const time 0s 1e
+// CHECK:     %i1.i0.x.prb = prb i1$ %t
+// CHECK:     drv i1$ %b, %i1.i0.x.prb, %2
+// CHECK: }
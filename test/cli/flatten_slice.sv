@@ -0,0 +1,19 @@
+// RUN: moore %s -e foo --flatten
+
+module foo (input bit [3:0] a, output bit b);
+    bar i0(a, b);
+endmodule
+
+module bar (input bit [3:0] x, output bit y);
+    assign y = x[2];
+endmodule
+
+// CHECK: entity @foo (i4$ %a) -> (i1$ %b) {
+// CHECK:     %0 = const time 0s 1e
+// CHECK:     %i0.x.prb = prb i4$ %a
+// CHECK:     %1 = exts i2, i4 %i0.x.prb, 2, 2
+// CHECK:     %2 = const i4 0
+// CHECK:     %3 = inss i4 %2, i2 %1, 0, 2
+// CHECK:     %4 = exts i1, i4 %3, 0, 1
+// CHECK:     drv i1$ %b, %4, %0
+// CHECK: }
@@ -0,0 +1,14 @@
+// RUN: moore fmt %s
+
+module foo(input logic a,output logic[3:0]b);
+assign b=a?4'h1:'0;
+always_ff @(posedge a) begin if(a) x<=1; else x<=0; end
+endmodule
+
+// CHECK: module foo(input logic a, output logic [3:0] b);
+// CHECK-NEXT: assign b = a ? 4'h1 : '0;
+// CHECK-NEXT: always_ff @(posedge a) begin
+// CHECK-NEXT: if (a) x <= 1;
+// CHECK-NEXT: else x <= 0;
+// CHECK-NEXT: end
+// CHECK-NEXT: endmodule
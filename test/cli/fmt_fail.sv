@@ -0,0 +1,8 @@
+// RUN: moore fmt %s
+// FAIL
+
+module foo;
+    assign = 1;
+endmodule
+
+// CHECK: error: expected expression, found `=` instead
@@ -0,0 +1,10 @@
+// RUN: moore fmt --max-width 40 --indent 2 %s
+
+module foo;
+    bar i_bar (.clk(clk), .rst(rst), .data(data), .valid(valid));
+endmodule
+
+// CHECK: module foo;
+// CHECK-NEXT: bar i_bar (.clk(clk), .rst(rst),
+// CHECK-NEXT: .data(data), .valid(valid));
+// CHECK-NEXT: endmodule
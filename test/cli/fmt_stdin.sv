@@ -0,0 +1,7 @@
+// RUN: moore fmt - < %s
+
+module foo;assign x=1;endmodule
+
+// CHECK: module foo;
+// CHECK-NEXT: assign x = 1;
+// CHECK-NEXT: endmodule
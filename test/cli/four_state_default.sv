@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo
+
+// The implicit `x` default of four-valued variables is not written in the
+// source, and lowering it to 0 does not warrant a warning.
+module foo (input logic a, output logic y);
+    logic x;
+    struct packed { logic [3:0] f; } s;
+    assign y = a;
+endmodule
+
+// CHECK-NOT: warning: `x` and `z` bits lowered to 0
+// CHECK: entity @foo (i1$ %a) -> (i1$ %y) {
@@ -0,0 +1,79 @@
+// RUN: moore %s -e foo -e lits -e mem --four-state=paired
+
+module foo (input logic a, input logic [7:0] b, output logic [7:0] y);
+    assign y = a ? b : 'x;
+endmodule
+
+// CHECK: entity @foo ({i1, i1}$ %a, {i8, i8}$ %b) -> ({i8, i8}$ %y) {
+// CHECK-NEXT:     %0 = const time 0s 1e
+// CHECK-NEXT:     %1 = extf i8$, {i8, i8}$ %y, 0
+// CHECK-NEXT:     %2 = extf i1$, {i1, i1}$ %a, 0
+// CHECK-NEXT:     %3 = prb i1$ %2
+// CHECK-NEXT:     %4 = const i1 0
+// CHECK-NEXT:     %5 = neq i1 %3, %4
+// CHECK-NEXT:     %6 = extf i8$, {i8, i8}$ %b, 0
+// CHECK-NEXT:     %7 = prb i8$ %6
+// CHECK-NEXT:     %8 = const i8 0
+// CHECK-NEXT:     %9 = [i8 %8, %7]
+// CHECK-NEXT:     %10 = mux [2 x i8] %9, i1 %5
+// CHECK-NEXT:     drv i8$ %1, %10, %0
+// CHECK-NEXT:     %11 = extf i8$, {i8, i8}$ %y, 1
+// CHECK-NEXT:     %12 = extf i1$, {i1, i1}$ %a, 1
+// CHECK-NEXT:     %13 = prb i1$ %12
+// CHECK-NEXT:     %14 = neq i1 %13, %4
+// CHECK-NEXT:     %15 = const i1 1
+// CHECK-NEXT:     %16 = [i1 %4, %15]
+// CHECK-NEXT:     %17 = mux [2 x i1] %16, i1 %14
+// CHECK-NEXT:     %18 = extf i8$, {i8, i8}$ %b, 1
+// CHECK-NEXT:     %19 = prb i8$ %18
+// CHECK-NEXT:     %20 = const i8 255
+// CHECK-NEXT:     %21 = [i8 %20, %19]
+// CHECK-NEXT:     %22 = mux [2 x i8] %21, i1 %5
+// CHECK-NEXT:     %23 = [i8 %22, %20]
+// CHECK-NEXT:     %24 = neq i1 %17, %4
+// CHECK-NEXT:     %25 = mux [2 x i8] %23, i1 %24
+// CHECK-NEXT:     drv i8$ %11, %25, %0
+// CHECK-NEXT: }
+
+// A leftmost x or z digit extends to the full width of a literal, and
+// uninitialized variables start out as x.
+module lits (output logic [7:0] a, b, c, d);
+    logic [7:0] v;
+    assign a = 8'hx;
+    assign b = 8'bx;
+    assign c = 8'b0x;
+    assign d = v;
+endmodule
+
+// Memory writes trigger on the value bits of the clock, and are disabled by
+// an unknown enable or address.
+module mem (input logic clk, en, input logic [1:0] a, input logic [7:0] b);
+    logic [7:0] m [4];
+    always_ff @(posedge clk) if (en) m[a] <= b;
+endmodule
+
+// CHECK: entity @lits () -> ({i8, i8}$ %a, {i8, i8}$ %b, {i8, i8}$ %c, {i8, i8}$ %d) {
+// CHECK-NEXT:     %0 = const i8 0
+// CHECK-NEXT:     %1 = const i8 255
+// CHECK-NEXT:     %2 = {i8 %0, i8 %1}
+// CHECK-NEXT:     %v = sig {i8, i8} %2
+// CHECK:     %5 = extf i8$, {i8, i8}$ %a, 1
+// CHECK-NEXT:     drv i8$ %5, %1, %3
+// CHECK:     %7 = extf i8$, {i8, i8}$ %b, 1
+// CHECK-NEXT:     drv i8$ %7, %1, %3
+// CHECK:     %9 = extf i8$, {i8, i8}$ %c, 1
+// CHECK-NEXT:     %10 = const i8 1
+// CHECK-NEXT:     drv i8$ %9, %10, %3
+
+// CHECK: entity @mem ({i1, i1}$ %clk, {i1, i1}$ %en, {i2, i2}$ %a, {i8, i8}$ %b) -> () {
+// CHECK:     %5 = extf i1$, {i1, i1}$ %clk, 0
+// CHECK-NEXT:     %clk.prb = prb i1$ %5
+// CHECK:     %16 = eq i1 %15, %8
+// CHECK-NEXT:     %17 = and i1 %9, %16
+// CHECK-NEXT:     %18 = extf i2$, {i2, i2}$ %a, 1
+// CHECK-NEXT:     %19 = prb i2$ %18
+// CHECK-NEXT:     %20 = const i2 0
+// CHECK-NEXT:     %21 = eq i2 %19, %20
+// CHECK-NEXT:     %22 = and i1 %17, %21
+// CHECK:     reg i8$ %28, [%30, rise %clk.prb, if %22]
+// CHECK:     reg i8$ %34, [%36, rise %clk.prb, if %22]
@@ -0,0 +1,16 @@
+// RUN: moore %s -e foo
+
+module foo (input logic [7:0] a, output logic [7:0] y, output logic [7:0] z);
+    assign y = a & 8'b0000_xxxx;
+    assign z = '1;
+endmodule
+
+// CHECK: entity @foo (i8$ %a) -> (i8$ %y, i8$ %z) {
+// CHECK-NEXT:     %0 = const time 0s 1e
+// CHECK-NEXT:     %1 = const i8 0
+// CHECK-NEXT:     drv i8$ %y, %1, %0
+// CHECK-NEXT:     %2 = const i8 255
+// CHECK-NEXT:     drv i8$ %z, %2, %0
+// CHECK-NEXT: }
+// CHECK-ERR: warning: `x` and `z` bits lowered to 0
+// CHECK-ERR: = note: Use `--four-state=paired` to preserve unknown bits
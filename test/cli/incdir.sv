@@ -0,0 +1,10 @@
+// RUN: moore %s -e top +incdir+test/cli/incdir -I file_list/include
+
+`include "width.svh"
+`include "params.svh"
+
+module top (input logic [`WIDTH-1:0] a, output logic [`DEPTH-1:0] z);
+    assign z = a[`DEPTH-1:0];
+endmodule
+
+// CHECK: entity @top (i8$ %a) -> (i2$ %z) {
@@ -0,0 +1,8 @@
+// RUN: moore %s
+// FAIL
+
+`include "width.svh"
+
+// CHECK-ERR: fatal: cannot open included file "width.svh"
+// CHECK-ERR: = note: Searched the following directories:
+// CHECK-ERR: = note: Use `-I <dir>` or `+incdir+<dir>` to add directories to the include search path
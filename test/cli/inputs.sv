@@ -0,0 +1,8 @@
+// RUN: moore -E inputs
+
+// CHECK: module a;
+// CHECK-NEXT: endmodule
+// CHECK-NEXT: module b;
+// CHECK-NEXT: endmodule
+// CHECK-NEXT: module c;
+// CHECK-NEXT: endmodule
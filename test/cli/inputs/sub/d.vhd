@@ -0,0 +1,2 @@
+entity d is
+end;
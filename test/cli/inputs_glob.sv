@@ -0,0 +1,8 @@
+// RUN: moore -E test/cli/inputs/?.sv test/cli/inputs/sub/c.s[v]
+
+// CHECK: module a;
+// CHECK-NEXT: endmodule
+// CHECK-NEXT: module b;
+// CHECK-NEXT: endmodule
+// CHECK-NEXT: module c;
+// CHECK-NEXT: endmodule
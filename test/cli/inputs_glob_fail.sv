@@ -0,0 +1,4 @@
+// RUN: moore --syntax test/cli/inputs/?.vhd
+// FAIL
+
+// CHECK: fatal: no input files match `test/cli/inputs/?.vhd`
@@ -0,0 +1,10 @@
+// RUN: moore std/matching.vhd %s std/matching.vhd --vhdl-std=93 -j 2 --syntax
+// FAIL
+
+module foo;
+    assign = 1;
+endmodule
+
+// CHECK: error: `?=` requires VHDL-2008
+// CHECK: error: expected expression, found `=` instead
+// CHECK: error: `?=` requires VHDL-2008
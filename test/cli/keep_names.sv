@@ -0,0 +1,21 @@
+// RUN: moore %s -e top --keep-names
+
+module top (input logic clk, input logic [7:0] d, output logic [7:0] q1, output logic [7:0] q2);
+    dff #(8) a (clk, d, q1);
+    dff #(4) b (clk, d[3:0], q2[3:0]);
+    assign q2[7:4] = 0;
+endmodule
+
+module dff #(int W = 1) (input logic clk, input logic [W-1:0] d, output logic [W-1:0] q);
+    always_ff @(posedge clk) q <= d;
+endmodule
+
+// CHECK: proc %dff.always_ff (i1$ %clk, i8$ %d) -> (i8$ %q) {
+// CHECK: entity @dff (i1$ %clk, i8$ %d) -> (i8$ %q) {
+// CHECK-NEXT:     inst %dff.always_ff (i1$ %clk, i8$ %d) -> (i8$ %q)
+// CHECK: proc %dff.1.always_ff (i1$ %clk, i4$ %d) -> (i4$ %q) {
+// CHECK: entity @dff.1 (i1$ %clk, i4$ %d) -> (i4$ %q) {
+// CHECK-NEXT:     inst %dff.1.always_ff (i1$ %clk, i4$ %d) -> (i4$ %q)
+// CHECK: entity @top (i1$ %clk, i8$ %d) -> (i8$ %q1, i8$ %q2) {
+// CHECK:     inst @dff (i1$ %clk, i8$ %d) -> (i8$ %q1)
+// CHECK:     inst @dff.1 (i1$ %clk, i4$ %4) -> (i4$ %6)
@@ -0,0 +1,9 @@
+// RUN: moore %s --lib ip=test/cli/library/ip -e top
+
+module top (input ip_pkg::word_t a, output ip_pkg::word_t z);
+    ip_core i_core (.a, .z);
+endmodule
+
+// CHECK: entity @ip_core (i8$ %a) -> (i8$ %z) {
+// CHECK: entity @top (i8$ %a) -> (i8$ %z) {
+// CHECK:     inst @ip_core (i8$ %a) -> (i8$ %z)
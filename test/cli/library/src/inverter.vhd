@@ -0,0 +1,8 @@
+entity inverter is
+    port (a : in bit; z : out bit);
+end entity;
+
+architecture rtl of inverter is
+begin
+    z <= not a;
+end architecture;
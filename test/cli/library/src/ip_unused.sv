@@ -0,0 +1,3 @@
+module ip_unused;
+    missing i_missing ();
+endmodule
@@ -0,0 +1,8 @@
+// RUN: moore %s --lib =test/cli/library/ip
+// FAIL
+
+module top;
+endmodule
+
+// CHECK-ERR: fatal: invalid library mapping `=test/cli/library/ip`
+// CHECK-ERR: = note: Library mappings are of the form `NAME=DIR`
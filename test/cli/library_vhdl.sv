@@ -0,0 +1,9 @@
+// RUN: moore %s --lib vlib=test/cli/library/vlib -e top
+
+module top (input bit a, output bit z);
+    inverter i_inverter (.a, .z);
+endmodule
+
+// CHECK: entity @top (i1$ %a) -> (i1$ %z) {
+// CHECK:     inst @inverter (i1$ %a) -> (i1$ %z)
+// CHECK: declare @inverter (i1$) -> (i1$)
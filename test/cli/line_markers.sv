@@ -0,0 +1,18 @@
+// RUN: moore -E --line-markers test/cli/line_markers.sv
+
+module top;
+`include "line_markers/params.svh"
+`ifdef UNDEFINED
+    logic y;
+`endif
+    logic [`WIDTH-1:0] x;
+endmodule
+
+// CHECK: `line 1 "test/cli/line_markers.sv" 0
+// CHECK: module top;
+// CHECK-NEXT: `line 2 "test/cli/line_markers/params.svh" 1
+// CHECK-NEXT: localparam int Depth = 4;
+// CHECK-NEXT: `line 4 "test/cli/line_markers.sv" 2
+// CHECK: `line 7 "test/cli/line_markers.sv" 0
+// CHECK: logic [8-1:0] x;
+// CHECK-NEXT: endmodule
@@ -0,0 +1,7 @@
+// RUN: moore -W help
+
+// CHECK: Available lints:
+// CHECK-NEXT: L0001  implicit-net          net implicitly declared by an assignment or port connection
+// CHECK-NEXT: L0002  blocking-in-ff        blocking assignment in an `always_ff` procedure
+// CHECK-NEXT: L0003  unique-case-default   `unique` or `priority` case without a `default` item
+// CHECK-NEXT: L0004  unused-signal         net or variable which is never read
@@ -0,0 +1,9 @@
+// RUN: moore --syntax -W all -W no-unused-signal -W error %s
+// FAIL
+
+module foo (input logic clk, input logic a);
+    logic q;
+    always_ff @(posedge clk) q = a;
+endmodule
+
+// CHECK: error: blocking assignment in `always_ff` [L0002]
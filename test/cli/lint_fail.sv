@@ -0,0 +1,7 @@
+// RUN: moore --syntax -W bogus %s
+// FAIL
+
+module foo;
+endmodule
+
+// CHECK: fatal: unknown lint `bogus`
@@ -0,0 +1,10 @@
+// RUN: moore test/cli/make_deps.sv -I test/cli/incdir --syntax -o make_deps.llhd -MD -MF -
+
+`include "width.svh"
+
+module top (input logic [`WIDTH-1:0] a);
+endmodule
+
+// CHECK: make_deps.llhd: \
+// CHECK-NEXT: test/cli/make_deps.sv \
+// CHECK-NEXT: test/cli/incdir/width.svh
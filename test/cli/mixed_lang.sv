@@ -0,0 +1,10 @@
+// RUN: moore mixed_lang.vhd %s -e top
+
+module top (input logic [15:0] a, b, output logic [15:0] y);
+    ADDER #(.WIDTH(16)) i_adder (.a, .b, .y, .en(1'b1));
+endmodule
+
+// CHECK: entity @top (i16$ %a, i16$ %b) -> (i16$ %y) {
+// CHECK:     inst @ADDER.WIDTH_16 (i16$ %a, i16$ %b, i1$ %2) -> (i16$ %y)
+// CHECK: }
+// CHECK: declare @ADDER.WIDTH_16 (i16$, i16$, i1$) -> (i16$)
@@ -0,0 +1,19 @@
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity adder is
+    generic (WIDTH : natural := 8);
+    port (
+        a, b : in std_logic_vector(WIDTH-1 downto 0);
+        y : out std_logic_vector(WIDTH-1 downto 0);
+        en : in std_logic
+    );
+end entity;
+
+entity fifo is
+    port (
+        clk : in std_logic;
+        data : in std_logic_vector;
+        state : out state_t
+    );
+end entity;
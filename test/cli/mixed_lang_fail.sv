@@ -0,0 +1,11 @@
+// RUN: moore mixed_lang.vhd %s -e top
+// FAIL
+
+module top;
+    logic clk;
+    fifo i_fifo (.clk);
+endmodule
+
+// CHECK-ERR: error: unconstrained type `std_logic_vector` cannot be mapped to SystemVerilog
+// CHECK-ERR: = note: Needed to instantiate VHDL entity `fifo` here:
+// CHECK-ERR: error: type `state_t` cannot be mapped to SystemVerilog
@@ -0,0 +1,14 @@
+library ieee;
+use ieee.std_logic_1164.all;
+
+entity sub is
+    port (clk : in std_logic);
+end entity;
+
+architecture rtl of sub is
+    component top is
+        port (clk : in std_logic);
+    end component;
+begin
+    i_top : top port map (clk => clk);
+end architecture;
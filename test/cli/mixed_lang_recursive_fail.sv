@@ -0,0 +1,9 @@
+// RUN: moore mixed_lang_recursive.vhd %s -e top
+// FAIL
+
+module top (input logic clk);
+    sub i_sub (.clk);
+endmodule
+
+// CHECK-ERR: error: recursive instantiation of module `top`
+// CHECK-ERR: = note: module `top` instantiates entity `sub` here:
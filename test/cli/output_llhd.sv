@@ -4,7 +4,7 @@ module foo (output int x);
     initial x = 42;
 endmodule
 
-// CHECK: proc %foo.initial.15.0 () -> (i32$ %x) {
+// CHECK: proc %foo.initial.13.0 () -> (i32$ %x) {
 // CHECK: 0:
 // CHECK:     %1 = const i32 42
 // CHECK:     %2 = const time 0s 1e
@@ -13,5 +13,5 @@ endmodule
 // CHECK: }
 // CHECK:
 // CHECK: entity @foo () -> (i32$ %x) {
-// CHECK:     inst %foo.initial.15.0 () -> (i32$ %x)
+// CHECK:     inst %foo.initial.13.0 () -> (i32$ %x)
 // CHECK: }
@@ -4,7 +4,7 @@ module foo (output int x);
     initial x = 42;
 endmodule
 
-// CHECK: llhd.proc @foo.initial.15.0() -> (%x: !llhd.sig<i32> ) {
+// CHECK: llhd.proc @foo.initial.13.0() -> (%x: !llhd.sig<i32> ) {
 // CHECK:     br ^0
 // CHECK: ^0:
 // CHECK:     %1 = llhd.const 42 : i32
@@ -14,5 +14,5 @@ endmodule
 // CHECK: }
 // CHECK:
 // CHECK: llhd.entity @foo() -> (%x: !llhd.sig<i32> ) {
-// CHECK:     llhd.inst "inst" @foo.initial.15.0() -> (%x) : () -> (!llhd.sig<i32>)
+// CHECK:     llhd.inst "inst" @foo.initial.13.0() -> (%x) : () -> (!llhd.sig<i32>)
 // CHECK: }
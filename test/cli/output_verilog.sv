@@ -0,0 +1,37 @@
+// RUN: moore %s -e foo --format verilog
+
+module foo (input logic clk, input logic [7:0] a, output logic [7:0] q, output logic [7:0] z);
+    always_ff @(posedge clk) q <= a;
+    bar #(8) i0(a, z);
+endmodule
+
+module bar #(int W = 4) (input logic [W-1:0] x, output logic [W-1:0] y);
+    assign y = ~x;
+endmodule
+
+// CHECK: module \bar.W_8 (
+// CHECK:     input wire [7:0] x,
+// CHECK:     output wire [7:0] y
+// CHECK: );
+// CHECK:     wire [7:0] _0;
+// CHECK:     assign _0 = ~x;
+// CHECK:     assign y = _0;
+// CHECK: endmodule
+// CHECK:
+// CHECK: module \foo.always_ff.40.0 (
+// CHECK:     input wire clk,
+// CHECK:     input wire [7:0] a,
+// CHECK:     output reg [7:0] q
+// CHECK: );
+// CHECK:     always @(posedge clk) q <= a;
+// CHECK: endmodule
+// CHECK:
+// CHECK: module foo(
+// CHECK:     input wire clk,
+// CHECK:     input wire [7:0] a,
+// CHECK:     output wire [7:0] q,
+// CHECK:     output wire [7:0] z
+// CHECK: );
+// CHECK:     \bar.W_8  i0(a, z);
+// CHECK:     \foo.always_ff.40.0  inst(clk, a, q);
+// CHECK: endmodule
@@ -0,0 +1,28 @@
+// RUN: moore %s -e foo --format verilog
+
+module foo (input logic clk, input logic we, input logic [3:0] addr, input logic [7:0] d, output logic [7:0] q);
+    logic [7:0] mem [16];
+    always_ff @(posedge clk) if (we) mem[addr] <= d;
+    assign q = mem[addr];
+endmodule
+
+// CHECK: module foo(
+// CHECK:     input wire clk,
+// CHECK:     input wire we,
+// CHECK:     input wire [3:0] addr,
+// CHECK:     input wire [7:0] d,
+// CHECK:     output wire [7:0] q
+// CHECK: );
+// CHECK:     reg [127:0] mem = {16{8'd0}};
+// CHECK:     wire [127:0] _4;
+// CHECK:     wire [255:0] _0;
+// CHECK:     wire [127:0] _1;
+// CHECK:     wire [7:0] _2;
+// CHECK:     wire _3;
+// CHECK:     assign _0 = {{16{8'd0}}, mem} >> addr * 8;
+// CHECK:     assign _1 = _0[127:0];
+// CHECK:     assign _2 = _1[7:0];
+// CHECK:     assign _3 = we != 1'd0;
+// CHECK:     always @(posedge clk) if (_3) mem[addr * 8 +: 8] <= d;
+// CHECK:     assign q = _2;
+// CHECK: endmodule
@@ -0,0 +1,19 @@
+// RUN: moore %s -e foo --format verilog
+
+module foo (input logic \event , output logic \fork , output logic inst);
+    bar \wait (\event , \fork );
+    bar inst_(\event , inst);
+endmodule
+
+module bar (input logic x, output logic y);
+    assign y = x;
+endmodule
+
+// CHECK: module foo(
+// CHECK:     input wire event_,
+// CHECK:     output wire fork_,
+// CHECK:     output wire inst
+// CHECK: );
+// CHECK:     bar wait_(event_, fork_);
+// CHECK:     bar inst_(event_, inst);
+// CHECK: endmodule
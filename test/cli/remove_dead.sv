@@ -0,0 +1,23 @@
+// RUN: moore %s -e foo --remove-dead
+
+module foo (input logic a, output logic b);
+    logic unused, t, u;
+    assign unused = a;
+    bar i0(a, t);
+    bar i1(t, u);
+    bar i2(a, b);
+endmodule
+
+module bar (input logic x, output logic y);
+    assign y = ~x;
+endmodule
+
+// CHECK: entity @foo (i1$ %a) -> (i1$ %b) {
+// CHECK-NEXT:     inst @bar (i1$ %a) -> (i1$ %b)
+// CHECK-NEXT: }
+
+// CHECK-ERR: note: removed unused instance of `@bar` in `@foo`
+// CHECK-ERR: note: removed unused signal `%unused` in `@foo`
+// CHECK-ERR: note: removed unused signal `%u` in `@foo`
+// CHECK-ERR: note: removed unused instance of `@bar` in `@foo`
+// CHECK-ERR: note: removed unused signal `%t` in `@foo`
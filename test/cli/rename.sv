@@ -0,0 +1,16 @@
+// RUN: moore test/cli/rename.sv --rename leaf.z=y
+
+module leaf (input logic a, output logic \z );
+    assign \z = ~a;
+endmodule : leaf
+
+module top (input logic a, output logic z);
+    logic leaf_z;
+    leaf i_leaf0 (.a, .z);
+    leaf i_leaf1 (.a(a), .\z (leaf_z));
+endmodule
+
+// CHECK: test/cli/rename.sv:3:42: `\z` -> `y`
+// CHECK-NEXT: test/cli/rename.sv:4:12: `\z` -> `y`
+// CHECK-NEXT: test/cli/rename.sv:9:24: `z` -> `y(z)`
+// CHECK-NEXT: test/cli/rename.sv:10:27: `\z` -> `y`
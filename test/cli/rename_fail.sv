@@ -0,0 +1,7 @@
+// RUN: moore %s --rename top.a=b
+// FAIL
+
+module top (input logic a, output logic b);
+endmodule
+
+// CHECK-ERR: fatal: `b` is already declared in module `top`
@@ -0,0 +1,12 @@
+// RUN: moore test/cli/rename_module.sv --rename leaf=cell_a
+
+module leaf #(parameter int N = 1) (input logic [N-1:0] a);
+endmodule : leaf
+
+module top (input logic [7:0] leaf);
+    leaf #(8) i_leaf (.a(leaf));
+endmodule
+
+// CHECK: test/cli/rename_module.sv:3:8: `leaf` -> `cell_a`
+// CHECK-NEXT: test/cli/rename_module.sv:4:13: `leaf` -> `cell_a`
+// CHECK-NEXT: test/cli/rename_module.sv:7:5: `leaf` -> `cell_a`
@@ -0,0 +1,39 @@
+// RUN: moore sim %s -e tb
+
+module tb;
+    logic clk;
+    logic [3:0] cnt;
+    initial begin
+        clk = 0;
+        repeat (4) #5ns clk = ~clk;
+    end
+    counter c (.clk(clk), .q(cnt));
+endmodule
+
+module counter (input logic clk, output logic [3:0] q);
+    always_ff @(posedge clk) q <= q + 1;
+endmodule
+
+// CHECK: $timescale 1ps $end
+// CHECK-NEXT: $scope module tb $end
+// CHECK-NEXT: $var wire 1 ! clk $end
+// CHECK-NEXT: $var wire 4 " cnt $end
+// CHECK-NEXT: $scope module c $end
+// CHECK-NEXT: $var wire 1 ! clk $end
+// CHECK-NEXT: $var wire 4 " q $end
+// CHECK-NEXT: $upscope $end
+// CHECK-NEXT: $upscope $end
+// CHECK-NEXT: $enddefinitions $end
+// CHECK-NEXT: #0
+// CHECK-NEXT: 0!
+// CHECK-NEXT: b0 "
+// CHECK-NEXT: #5000
+// CHECK-NEXT: 1!
+// CHECK-NEXT: b1 "
+// CHECK-NEXT: #10000
+// CHECK-NEXT: 0!
+// CHECK-NEXT: #15000
+// CHECK-NEXT: 1!
+// CHECK-NEXT: b10 "
+// CHECK-NEXT: #20000
+// CHECK-NEXT: 0!
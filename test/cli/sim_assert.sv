@@ -0,0 +1,23 @@
+// RUN: moore sim %s -e tb --until 40ns
+// FAIL
+
+module tb;
+    bit clk;
+    bit [3:0] count;
+    always #5ns clk = ~clk;
+    always_ff @(posedge clk) count <= count + 1;
+    assert property (@(posedge clk) count != 2);
+    initial begin
+        #12ns;
+        assert (count == 1);
+        assert ($onehot(count));
+        assert (count == 2);
+    end
+endmodule
+
+// CHECK-ERR: error: assertion failed
+// CHECK-ERR: |         assert (count == 2);
+// CHECK-ERR: = note: at time 12ns
+// CHECK-ERR: error: assertion failed
+// CHECK-ERR: |     assert property (@(posedge clk) count != 2);
+// CHECK-ERR: = note: at time 25ns 1e
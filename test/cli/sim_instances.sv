@@ -0,0 +1,32 @@
+// RUN: moore sim %s -e tb
+
+module tb;
+    logic a, b, na, nb;
+    initial begin
+        a = 0;
+        b = 1;
+    end
+    inverter u0 (.x(a), .y(na));
+    inverter u1 (.x(b), .y(nb));
+endmodule
+
+module inverter (input logic x, output logic y);
+    assign y = ~x;
+endmodule
+
+// CHECK: $timescale 1ps $end
+// CHECK-NEXT: $scope module tb $end
+// CHECK-NEXT: $var wire 1 ! a $end
+// CHECK-NEXT: $var wire 1 " b $end
+// CHECK-NEXT: $var wire 1 # na $end
+// CHECK-NEXT: $var wire 1 $ nb $end
+// CHECK-NEXT: $scope module u0 $end
+// CHECK-NEXT: $var wire 1 ! x $end
+// CHECK-NEXT: $var wire 1 # y $end
+// CHECK-NEXT: $upscope $end
+// CHECK-NEXT: $scope module u1 $end
+// CHECK-NEXT: $var wire 1 " x $end
+// CHECK-NEXT: $var wire 1 $ y $end
+// CHECK-NEXT: $upscope $end
+// CHECK-NEXT: $upscope $end
+// CHECK-NEXT: $enddefinitions $end
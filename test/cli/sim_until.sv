@@ -0,0 +1,18 @@
+// RUN: moore sim %s -e tb --until 12ns
+
+module tb;
+    logic clk = 0;
+    always #5ns clk = ~clk;
+endmodule
+
+// CHECK: $timescale 1ps $end
+// CHECK-NEXT: $scope module tb $end
+// CHECK-NEXT: $var wire 1 ! clk $end
+// CHECK-NEXT: $upscope $end
+// CHECK-NEXT: $enddefinitions $end
+// CHECK-NEXT: #0
+// CHECK-NEXT: 0!
+// CHECK-NEXT: #5000
+// CHECK-NEXT: 1!
+// CHECK-NEXT: #10000
+// CHECK-NEXT: 0!
@@ -0,0 +1,7 @@
+// RUN: moore %s -e foo --stats
+
+module foo (input logic a, output logic z);
+    assign z = ~a;
+endmodule
+
+// CHECK: arena                                 count        bytes
@@ -0,0 +1,13 @@
+// RUN: moore %s --std=1364-2005 -e top
+
+module top (input clk, output reg logic);
+    reg bit;
+    always @(posedge clk) begin
+        bit <= !bit;
+        logic <= bit;
+    end
+endmodule
+
+// CHECK: entity @top (i1$ %clk) -> (i1$ %logic) {
+// CHECK: warning: `logic` is a keyword in IEEE 1800-2005 and later
+// CHECK: warning: `bit` is a keyword in IEEE 1800-2005 and later
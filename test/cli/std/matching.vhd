@@ -0,0 +1,8 @@
+entity matching is
+    port (a, b : in bit; y : out bit);
+end matching;
+
+architecture rtl of matching is
+begin
+    y <= a ?= b;
+end rtl;
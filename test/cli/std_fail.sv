@@ -0,0 +1,6 @@
+// RUN: moore %s --std=1364-2005 --syntax
+// FAIL
+
+`undefineall
+
+// CHECK: error: `undefineall requires IEEE 1800-2009 or later
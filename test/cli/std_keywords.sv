@@ -0,0 +1,14 @@
+// RUN: moore %s -e top
+
+`begin_keywords "1364-2001"
+module legacy (input clk, output reg logic);
+    always @(posedge clk) logic <= !logic;
+endmodule
+`end_keywords
+
+module top (input logic clk, output logic q);
+    legacy i_legacy (clk, q);
+endmodule
+
+// CHECK: entity @legacy (i1$ %clk) -> (i1$ %logic) {
+// CHECK: warning: `logic` is a keyword in IEEE 1800-2005 and later
@@ -0,0 +1,4 @@
+// RUN: moore std/matching.vhd --vhdl-std=93 --syntax
+// FAIL
+
+// CHECK: error: `?=` requires VHDL-2008
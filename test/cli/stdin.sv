@@ -0,0 +1,7 @@
+// RUN: moore - -e foo < %s
+
+module foo (input logic a, output logic b);
+    assign b = a;
+endmodule
+
+// CHECK: entity @foo (i1$ %a) -> (i1$ %b) {
@@ -0,0 +1,9 @@
+// RUN: moore --syntax - < %s
+// FAIL
+
+module foo;
+    assign = 1;
+endmodule
+
+// CHECK: error: expected expression, found `=` instead
+// CHECK-NEXT: --> <stdin>:5:12-13:
@@ -0,0 +1,23 @@
+// RUN: moore %s --top a --top b
+
+module a (input logic x, output logic y);
+    shared i_shared (.x, .y);
+endmodule
+
+module b (input logic x, output logic y);
+    shared i_shared (.x, .y);
+endmodule
+
+module shared (input logic x, output logic y);
+    assign y = x;
+endmodule
+
+module unused (input logic x, output logic y);
+    missing i_missing (.x, .y);
+endmodule
+
+// CHECK: entity @shared (i1$ %x) -> (i1$ %y) {
+// CHECK: entity @a (i1$ %x) -> (i1$ %y) {
+// CHECK:     inst @shared (i1$ %x) -> (i1$ %y)
+// CHECK: entity @b (i1$ %x) -> (i1$ %y) {
+// CHECK:     inst @shared (i1$ %x) -> (i1$ %y)
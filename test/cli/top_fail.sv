@@ -0,0 +1,14 @@
+// RUN: moore %s --top top
+// FAIL
+
+module top;
+    mid i_mid ();
+    first i_first ();
+endmodule
+
+module mid;
+    second i_second ();
+endmodule
+
+// CHECK-ERR: error: `second` not found
+// CHECK-ERR: error: `first` not found
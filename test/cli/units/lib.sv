@@ -0,0 +1,12 @@
+// Declarations shared by the compilation unit tests.
+
+`define WIDTH 8
+
+typedef logic [`WIDTH-1:0] byte_t;
+
+package pkg;
+    localparam int N = 4;
+endpackage
+
+module sub (input byte_t x, input logic [pkg::N-1:0] y);
+endmodule
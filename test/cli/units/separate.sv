@@ -0,0 +1,12 @@
+// RUN: moore lib.sv %s -e top
+// FAIL
+
+module top;
+    logic [pkg::N-1:0] a;
+    byte_t b;
+    sub i_sub (.x(b), .y(a));
+endmodule
+
+// CHECK-ERR: error: `byte_t` not found
+// CHECK-ERR: = note: `byte_t` is declared in another compilation unit:
+// CHECK-ERR: = note: Move it into a package, or use `--single-unit` to compile all files as one compilation unit
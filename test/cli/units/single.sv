@@ -0,0 +1,14 @@
+// RUN: moore --single-unit -O0 lib.sv %s -e top
+
+module top;
+    logic [pkg::N-1:0] a;
+    byte_t b;
+    logic [`WIDTH*2-1:0] c;
+    sub i_sub (.x(b), .y(a));
+endmodule
+
+// CHECK: entity @top () -> () {
+// CHECK:     %a = sig i4 %0
+// CHECK:     %b = sig i8 %1
+// CHECK:     %c = sig i16 %2
+// CHECK:     inst @sub (i8$ %b, i4$ %a) -> ()
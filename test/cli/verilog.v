@@ -0,0 +1,15 @@
+// RUN: moore %s --verilog -e top
+
+module top (input clk, input [3:0] d, output reg [3:0] logic);
+    reg bit;
+    wire [3:0] int;
+    assign int = d;
+    always @(posedge clk) begin : blk
+        bit <= !bit;
+        logic <= int;
+    end
+endmodule
+
+// CHECK: entity @top (i1$ %clk, i4$ %d) -> (i4$ %logic) {
+// CHECK: %bit = sig i1 %0
+// CHECK: %int = sig i4 %1
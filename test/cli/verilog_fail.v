@@ -0,0 +1,22 @@
+// RUN: moore %s --verilog --syntax
+// FAIL
+
+module sub (input a, output b);
+    assign b = a;
+endmodule
+
+module top (input clk, output reg [3:0] q);
+    wire a;
+    sub i_sub (.a, .b());
+    always @(posedge clk) begin : count
+        q <= '0;
+        q += 1;
+    end : count
+endmodule : top
+
+// CHECK-ERR: error: implicit port connection requires IEEE 1800-2005 or later
+// CHECK-ERR: = note: The input is parsed as IEEE 1364-2005 due to `--verilog`
+// CHECK-ERR: error: literal `'0` requires IEEE 1800-2005 or later
+// CHECK-ERR: error: `+=` requires IEEE 1800-2005 or later
+// CHECK-ERR: error: label after `end` requires IEEE 1800-2005 or later
+// CHECK-ERR: error: label after `endmodule` requires IEEE 1800-2005 or later
@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (inout wire a, inout wire b);
+    alias a = b;
+endmodule
+
+// CHECK-ERR: error: unsupported: net alias
@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo
+// FAIL
+
+checker handshake(logic req, logic ack, event clk);
+    assert property (@clk req |-> ack);
+endchecker
+
+module foo (input logic clk, req, ack);
+    handshake h0 (req, ack, posedge clk);
+endmodule
+
+// CHECK-ERR: error: unsupported: instance of checker `handshake`
@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module sub #(parameter P = 1);
+endmodule
+
+module foo;
+    sub s ();
+    defparam s.P = 5;
+endmodule
+
+// CHECK-ERR: error: unsupported: defparam statement
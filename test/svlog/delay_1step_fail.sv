@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    initial #1step;
+endmodule
+
+// CHECK-ERR: error: unsupported: `1step` time literal
@@ -0,0 +1,10 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    initial begin : blk
+        disable blk;
+    end
+endmodule
+
+// CHECK-ERR: error: unsupported: `disable` statement
@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    enum {A[2], B} e;
+endmodule
+
+// CHECK-ERR: error: unsupported: enumerator range `[2]`
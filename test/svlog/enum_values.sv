@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo -O0
+
+module foo;
+    typedef enum logic [1:0] {A, B = 2, C} state_t;
+    state_t s0 = A;
+    // CHECK: %0 = const i2 0
+    state_t s1 = C;
+    // CHECK: %1 = const i2 3
+    enum bit [3:0] {P = 4'h5, Q, R = 1, S} e0 = Q, e1 = S;
+    // CHECK: %2 = const i4 6
+    // CHECK: %3 = const i4 2
+endmodule
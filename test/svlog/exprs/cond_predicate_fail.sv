@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input logic a, b, output logic y);
+    always_comb if (a &&& b) y = 1; else y = 0;
+endmodule
+
+// CHECK-ERR: error: unsupported: `&&&` in conditions
@@ -9,6 +9,7 @@ module foo;
         } c;
     } p;
     int [3:0] q;
+    int r [3];
 
     initial begin
         p = '{c: '{y: 9001, x: 1337}, a: 1, b: 42};
@@ -21,5 +22,10 @@ module foo;
         // CHECK: %9 = const i32 2
         // CHECK: %10 = const i32 3
         // CHECK: %11 = const i32 4
+        r = '{1: 2, int: 7, default: 0};
+        // CHECK: %14 = const i32 7
+        // CHECK: %15 = const i32 2
+        // CHECK: %16 = const i32 7
+        // CHECK: %17 = [i32 %14, %15, %16]
     end
 endmodule
@@ -0,0 +1,32 @@
+// RUN: moore %s -e foo -O0
+// Positional patterns assign their first value to the left bound of the range.
+module foo;
+    int a [0:1] = '{1, 2};
+    int b [1:0] = '{1, 2};
+    int c [1:0] = '{1: 1, 0: 2};
+    logic [3:0] d = '{1, 1, 0, 0};
+    logic [3:0] e = '{0: 1, 1: 1, default: 0};
+    logic [0:3] f = '{1, 1, 0, 0};
+    logic [0:3] g = '{0: 1, 1: 1, default: 0};
+endmodule
+
+// CHECK: %0 = const i32 1
+// CHECK: %1 = const i32 2
+// CHECK: %2 = [i32 %0, %1]
+// CHECK: %a = sig [2 x i32] %2
+// CHECK: %3 = const i32 2
+// CHECK: %4 = const i32 1
+// CHECK: %5 = [i32 %3, %4]
+// CHECK: %b = sig [2 x i32] %5
+// CHECK: %6 = const i32 2
+// CHECK: %7 = const i32 1
+// CHECK: %8 = [i32 %6, %7]
+// CHECK: %c = sig [2 x i32] %8
+// CHECK: %9 = const i4 12
+// CHECK: %d = sig i4 %9
+// CHECK: %10 = const i4 3
+// CHECK: %e = sig i4 %10
+// CHECK: %11 = const i4 12
+// CHECK: %f = sig i4 %11
+// CHECK: %12 = const i4 12
+// CHECK: %g = sig i4 %12
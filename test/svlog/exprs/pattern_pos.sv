@@ -17,9 +17,9 @@ module foo;
         // CHECK: %3 = const i16 1337
         // CHECK: %4 = const i64 9001
         q = '{1, 2, 3, 4};
-        // CHECK: %8 = const i32 1
-        // CHECK: %9 = const i32 2
-        // CHECK: %10 = const i32 3
-        // CHECK: %11 = const i32 4
+        // CHECK: %8 = const i32 4
+        // CHECK: %9 = const i32 3
+        // CHECK: %10 = const i32 2
+        // CHECK: %11 = const i32 1
     end
 endmodule
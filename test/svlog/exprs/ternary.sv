@@ -0,0 +1,11 @@
+// RUN: moore %s -e foo -O0
+
+module foo #(parameter A = 1, parameter W = A ? 8 : 16);
+    int v0 = A ? 5 : 0 ? 6 : 7;
+    // CHECK: %0 = const i32 5
+    int v1 = A ? W > 8 ? 2 : 3 : 4;
+    // CHECK: %1 = const i32 3
+    int v2 = 0 ? 1 : A ? W : 2;
+    // CHECK: %2 = const i32 8
+    logic [A ? 7 : 3 : 0] v3;
+endmodule
@@ -0,0 +1,9 @@
+// RUN: moore %s -e foo
+
+module foo;
+    int x;
+    initial begin
+        for (int i = 0, j = 4; i < j; i++, j--) x = i;
+        for (;;) x = 0;
+    end
+endmodule
@@ -0,0 +1,27 @@
+// RUN: moore %s -e foo
+
+// Items in a generate region belong to the enclosing module.
+module foo (output logic [3:0] o, output logic p);
+    genvar i, j;
+    for (i = 0; i < 2; i++) begin : g1
+        assign o[i] = 1'b0;
+    end
+    generate
+        genvar k;
+        logic x;
+        for (k = 2; k < 4; k = k + 1) begin : g2
+            assign o[k] = 1'b1;
+        end
+        bar i_bar (.y(x));
+    endgenerate
+    assign p = x;
+endmodule
+
+module bar (output logic y);
+    assign y = 1;
+endmodule
+
+// CHECK: entity @foo () -> (i4$ %o, i1$ %p) {
+// CHECK: inst @bar () -> (i1$ %x)
+// CHECK: %4 = exts i1$, i4$ %o, 2, 1
+// CHECK: %6 = exts i1$, i4$ %o, 3, 1
@@ -0,0 +1,26 @@
+// RUN: moore %s -e foo -Vprocs
+
+module foo (input logic en, input logic [1:0] s, input logic [7:0] d, output logic [7:0] x, output logic [7:0] y, output logic [7:0] z);
+    always_comb begin
+        x = 0;
+        if (en) begin
+            x = d;
+            y = d;
+        end else begin
+            y = 0;
+            z = d;
+        end
+    end
+    always_comb
+        case (s)
+            0: z = d;
+            default: z = 0;
+        endcase
+endmodule
+
+// CHECK: Procedure `always_comb` on line 4:
+// CHECK-NEXT: latch: z
+// CHECK: Procedure `always_comb` on line 14:
+
+// CHECK-ERR: warning: latch inferred for `z`
+// CHECK-ERR: = note: Not assigned on every path through the procedure
@@ -0,0 +1,15 @@
+// RUN: moore %s -e foo -Vprocs
+
+module foo (input logic clk, input logic rst_n, input logic [7:0] d, output logic [7:0] q, output logic [7:0] p);
+    always_ff @(posedge clk or negedge rst_n)
+        if (!rst_n) q <= 0;
+        else q <= d;
+    always_ff @(negedge clk)
+        p <= d;
+endmodule
+
+// CHECK: Procedure `always_ff` on line 4:
+// CHECK-NEXT: clock: posedge clk
+// CHECK-NEXT: async reset: negedge rst_n
+// CHECK: Procedure `always_ff` on line 7:
+// CHECK-NEXT: clock: negedge clk
@@ -0,0 +1,11 @@
+// RUN: moore %s -e foo
+
+module foo (input logic a, input logic b, input logic [7:0] d, output logic [7:0] q);
+    always_ff @(posedge a or posedge b)
+        if (a) q <= 0;
+        else if (b) q <= 1;
+        else q <= d;
+endmodule
+
+// CHECK-ERR: warning: `always_ff` procedure does not have a unique clock
+// CHECK-ERR: = note: None of the edge-sensitive signals `a`, `b` is used as clock; signals checked at the top of the procedure are considered asynchronous resets
@@ -0,0 +1,11 @@
+// RUN: moore %s -e foo
+
+module foo (input logic en, input logic [7:0] d, output logic [7:0] q, output logic [7:0] r);
+    always_latch
+        if (en) q = d;
+    always_latch
+        r = d;
+endmodule
+
+// CHECK-ERR: warning: `always_latch` procedure does not infer a latch
+// CHECK-ERR: = note: All variables are assigned on every path through the procedure
@@ -0,0 +1,30 @@
+// RUN: moore %s -e foo -Vprocs
+
+module foo (input logic clk, input logic we, input logic [3:0] waddr, input logic [3:0] raddr, input logic [7:0] d, output logic [7:0] q);
+    logic [7:0] mem [16];
+    always_ff @(posedge clk)
+        if (we) mem[waddr] <= d;
+    always_ff @(posedge clk)
+        q <= mem[raddr];
+endmodule
+
+// CHECK: Procedure `always_ff` on line 5:
+// CHECK-NEXT: clock: posedge clk
+// CHECK-NEXT: memory write: mem
+// CHECK: Procedure `always_ff` on line 7:
+// CHECK-NEXT: clock: posedge clk
+
+// CHECK: entity @foo (i1$ %clk, i1$ %we, i4$ %waddr, i4$ %raddr, i8$ %d) -> (i8$ %q) {
+// CHECK:     %mem = sig [16 x i8] %1
+// CHECK-NEXT:     %clk.prb = prb i1$ %clk
+// CHECK-NEXT:     %we.prb = prb i1$ %we
+// CHECK-NEXT:     %2 = const i1 0
+// CHECK-NEXT:     %3 = neq i1 %we.prb, %2
+// CHECK-NEXT:     %waddr.prb = prb i4$ %waddr
+// CHECK-NEXT:     %4 = sig [16 x i8] %1
+// CHECK-NEXT:     %5 = shr [16 x i8]$ %mem, [16 x i8]$ %4, i4 %waddr.prb
+// CHECK-NEXT:     %6 = extf i8$, [16 x i8]$ %5, 0
+// CHECK-NEXT:     %d.prb = prb i8$ %d
+// CHECK-NEXT:     reg i8$ %6, [%d.prb, rise %clk.prb, if %3]
+// CHECK-NEXT:     inst %foo.always_ff.93.0 (i1$ %clk, i4$ %raddr, [16 x i8]$ %mem) -> (i8$ %q)
+// CHECK-NEXT: }
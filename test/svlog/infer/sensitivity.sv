@@ -0,0 +1,11 @@
+// RUN: moore %s -e foo
+
+module foo (input logic a, input logic b, input logic c, output logic x, output logic y);
+    always @(a or b)
+        x = a & b;
+    always @(a)
+        y = a | b | c;
+endmodule
+
+// CHECK-ERR: warning: incomplete sensitivity list
+// CHECK-ERR: = note: The procedure reads `b`, `c`, which are not in the event list
@@ -6,8 +6,8 @@ endmodule
 module B;
 endmodule
 
-// CHECK: entity @B.param1 () -> () {
+// CHECK: entity @B () -> () {
 // CHECK: }
 // CHECK: entity @A () -> () {
-// CHECK:     inst @B.param1 () -> ()
+// CHECK:     inst @B () -> ()
 // CHECK: }
@@ -0,0 +1,26 @@
+// RUN: moore %s -e A
+
+// Instances with the same parameter values share a specialization.
+
+module A;
+    logic [7:0] a, b;
+    logic [15:0] c;
+    B #(8) i0(a);
+    B #(.WIDTH(8)) i1(b);
+    B #(16) i2(c);
+endmodule
+
+module B #(int WIDTH = 4) (input logic [WIDTH-1:0] x);
+endmodule
+
+// CHECK: entity @B.WIDTH_8 (i8$ %x) -> () {
+// CHECK: }
+// CHECK:
+// CHECK: entity @B.WIDTH_16 (i16$ %x) -> () {
+// CHECK: }
+// CHECK:
+// CHECK: entity @A () -> () {
+// CHECK:     inst @B.WIDTH_8 (i8$ %a) -> ()
+// CHECK:     inst @B.WIDTH_8 (i8$ %b) -> ()
+// CHECK:     inst @B.WIDTH_16 (i16$ %c) -> ()
+// CHECK: }
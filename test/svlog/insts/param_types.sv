@@ -12,15 +12,15 @@ endmodule
 module A1 #(type T) (input T t);
 endmodule
 
-// CHECK: entity @A1.param1 (i32$ %t) -> () {
+// CHECK: entity @A1.T_int (i32$ %t) -> () {
 // CHECK: }
 // CHECK:
-// CHECK: entity @A1.param2 (i1$ %t) -> () {
+// CHECK: entity @A1.T_bit (i1$ %t) -> () {
 // CHECK: }
 // CHECK:
 // CHECK: entity @A0 () -> () {
-// CHECK:     inst @A1.param1 (i32$ %t1) -> ()
-// CHECK:     inst @A1.param2 (i1$ %t2) -> ()
+// CHECK:     inst @A1.T_int (i32$ %t1) -> ()
+// CHECK:     inst @A1.T_bit (i1$ %t2) -> ()
 // CHECK: }
 
 
@@ -46,17 +46,17 @@ endmodule
 module C1 #(type T, type R = T) (input T t, input R r);
 endmodule
 
-// CHECK: entity @C1.param3 (i1$ %t, i1$ %r) -> () {
+// CHECK: entity @C1.T_bit.R_bit (i1$ %t, i1$ %r) -> () {
 // CHECK: }
 // CHECK:
-// CHECK: entity @C1.param4 (i32$ %t, i32$ %r) -> () {
+// CHECK: entity @C1.T_int.R_int (i32$ %t, i32$ %r) -> () {
 // CHECK: }
 // CHECK:
-// CHECK: entity @C1.param5 (i32$ %t, i1$ %r) -> () {
+// CHECK: entity @C1.T_int.R_bit (i32$ %t, i1$ %r) -> () {
 // CHECK: }
 // CHECK:
 // CHECK: entity @C0 () -> () {
-// CHECK:     inst @C1.param3 (i1$ %x2, i1$ %x2) -> ()
-// CHECK:     inst @C1.param4 (i32$ %x1, i32$ %x1) -> ()
-// CHECK:     inst @C1.param5 (i32$ %x1, i1$ %x2) -> ()
+// CHECK:     inst @C1.T_bit.R_bit (i1$ %x2, i1$ %x2) -> ()
+// CHECK:     inst @C1.T_int.R_int (i32$ %x1, i32$ %x1) -> ()
+// CHECK:     inst @C1.T_int.R_bit (i32$ %x1, i1$ %x2) -> ()
 // CHECK: }
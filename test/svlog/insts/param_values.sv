@@ -10,20 +10,20 @@ endmodule
 module A1 #(int K = 0) (output int k = K);
 endmodule
 
-// CHECK: entity @A1.param1 () -> (i32$ %k) {
+// CHECK: entity @A1.K_0 () -> (i32$ %k) {
 // CHECK:     %0 = const i32 0
 // CHECK:     %1 = const time 0s
 // CHECK:     drv i32$ %k, %0, %1
 // CHECK: }
 // CHECK:
-// CHECK: entity @A1.param2 () -> (i32$ %k) {
-// CHECK:     %0 = const i32 0
+// CHECK: entity @A1.K_1 () -> (i32$ %k) {
+// CHECK:     %0 = const i32 1
 // CHECK:     %1 = const time 0s
 // CHECK:     drv i32$ %k, %0, %1
 // CHECK: }
 // CHECK:
-// CHECK: entity @A1.param3 () -> (i32$ %k) {
-// CHECK:     %0 = const i32 1
-// CHECK:     %1 = const time 0s
-// CHECK:     drv i32$ %k, %0, %1
+// CHECK: entity @A0 () -> () {
+// CHECK:     inst @A1.K_0 () -> (i32$ %x)
+// CHECK:     inst @A1.K_0 () -> (i32$ %x)
+// CHECK:     inst @A1.K_1 () -> (i32$ %x)
 // CHECK: }
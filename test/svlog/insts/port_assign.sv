@@ -12,7 +12,7 @@ endmodule
 // CHECK:     %0 = const i32 0
 // CHECK:     %a = sig i32 %0
 // CHECK:     %b = sig i32 %0
-// CHECK:     inst @X.param1 (i32$ %a) -> (i32$ %b)
+// CHECK:     inst @X (i32$ %a) -> (i32$ %b)
 // CHECK: }
 
 // module B1 ({x,y});
@@ -41,7 +41,7 @@ endmodule
 // CHECK:     %3 = sig i32 %0
 // CHECK:     %4 = const time 0s 1d
 // CHECK:     drv i32$ %3, %2, %4
-// CHECK:     inst @X.param1 (i32$ %3) -> (i32$ %b)
+// CHECK:     inst @X (i32$ %3) -> (i32$ %b)
 // CHECK: }
 
 module N1;
@@ -53,7 +53,7 @@ endmodule
 // CHECK:     %0 = const i32 42
 // CHECK:     %a = sig i32 %0
 // CHECK:     %b = sig i32 %0
-// CHECK:     inst @X.param1 (i32$ %a) -> (i32$ %b)
+// CHECK:     inst @X (i32$ %a) -> (i32$ %b)
 // CHECK: }
 
 module N2;
@@ -65,7 +65,7 @@ endmodule
 // CHECK:     %0 = const i32 42
 // CHECK:     %a = sig i32 %0
 // CHECK:     %b = sig i32 %0
-// CHECK:     inst @X.param1 (i32$ %a) -> (i32$ %b)
+// CHECK:     inst @X (i32$ %a) -> (i32$ %b)
 // CHECK: }
 
 module N3;
@@ -78,5 +78,5 @@ endmodule
 // CHECK:     %a = sig i32 %0
 // CHECK:     %1 = const i32 0
 // CHECK:     %foo.b.default = sig i32 %1
-// CHECK:     inst @X.param1 (i32$ %a) -> (i32$ %foo.b.default)
+// CHECK:     inst @X (i32$ %a) -> (i32$ %foo.b.default)
 // CHECK: }
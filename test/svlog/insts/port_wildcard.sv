@@ -0,0 +1,17 @@
+// RUN: moore %s -e top
+
+module sub (input logic a, b, output logic y);
+    assign y = a & b;
+endmodule
+
+module top (input logic a, b, output logic y);
+    sub s0 (.*);
+    sub s1 (.a, .*, .y());
+endmodule
+
+// CHECK: entity @top (i1$ %a, i1$ %b) -> (i1$ %y) {
+// CHECK:     inst @sub (i1$ %a, i1$ %b) -> (i1$ %y)
+// CHECK:     %0 = const i1 0
+// CHECK:     %s1.y.default = sig i1 %0
+// CHECK:     inst @sub (i1$ %a, i1$ %b) -> (i1$ %s1.y.default)
+// CHECK: }
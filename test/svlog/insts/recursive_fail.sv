@@ -0,0 +1,14 @@
+// RUN: moore %s -e a
+// FAIL
+
+module a (input logic x);
+    b ib (.x);
+endmodule
+
+module b (input logic x);
+    a ia (.x);
+endmodule
+
+// CHECK-ERR: error: recursive instantiation of module `a`
+// CHECK-ERR: = note: module `a` instantiates module `b` here:
+// CHECK-ERR: = note: Recursive instances must be inside a generate construct which ends the recursion
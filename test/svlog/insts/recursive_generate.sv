@@ -0,0 +1,13 @@
+// RUN: moore %s -e tree
+
+module tree #(parameter int N = 2) (input logic a, output logic y);
+    if (N > 1) begin : g
+        tree #(N-1) i_sub (.a, .y);
+    end else begin : h
+        assign y = a;
+    end
+endmodule
+
+// CHECK: entity @tree.N_1 (i1$ %a) -> (i1$ %y) {
+// CHECK: entity @tree (i1$ %a) -> (i1$ %y) {
+// CHECK:     inst @tree.N_1 (i1$ %a) -> (i1$ %y)
@@ -16,12 +16,12 @@ interface bar;
 	logic ready;
 endinterface
 
-// CHECK: entity @fee.param3 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) {
+// CHECK: entity @fee () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) {
 // CHECK:     drv i32$ %x.data, %1, %0
 // CHECK:     drv i1$ %x.valid, %3, %2
 // CHECK:     drv i1$ %x.ready, %5, %4
 // CHECK: }
 
 // CHECK: entity @foo () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) {
-// CHECK:     inst @fee.param3 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready)
+// CHECK:     inst @fee () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready)
 // CHECK: }
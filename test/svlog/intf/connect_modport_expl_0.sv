@@ -19,12 +19,12 @@ interface bar;
     modport out (output data, valid, input ready);
 endinterface
 
-// CHECK: entity @fee.param3 (i32$ %x.data, i1$ %x.valid, i1$ %y.ready) -> (i1$ %x.ready, i32$ %y.data, i1$ %y.valid) {
+// CHECK: entity @fee (i32$ %x.data, i1$ %x.valid, i1$ %y.ready) -> (i1$ %x.ready, i32$ %y.data, i1$ %y.valid) {
 // CHECK:     drv i32$ %y.data, %x.data.prb, %0
 // CHECK:     drv i1$ %y.valid, %x.valid.prb, %1
 // CHECK:     drv i1$ %x.ready, %y.ready.prb, %2
 // CHECK: }
 
 // CHECK: entity @foo (i32$ %x.data, i1$ %x.valid, i1$ %y.ready) -> (i1$ %x.ready, i32$ %y.data, i1$ %y.valid) {
-// CHECK:     inst @fee.param3 (i32$ %x.data, i1$ %x.valid, i1$ %y.ready) -> (i1$ %x.ready, i32$ %y.data, i1$ %y.valid)
+// CHECK:     inst @fee (i32$ %x.data, i1$ %x.valid, i1$ %y.ready) -> (i1$ %x.ready, i32$ %y.data, i1$ %y.valid)
 // CHECK: }
@@ -15,7 +15,7 @@ interface bar;
   logic ready;
 endinterface
 
-// CHECK: proc %foo.initial.61.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) {
+// CHECK: proc %foo.initial.49.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) {
 // CHECK:     drv i32$ %x.data, %1, %2
 // CHECK:     drv i1$ %x.valid, %3, %4
 // CHECK:     drv i1$ %x.ready, %5, %6
@@ -25,5 +25,5 @@ endinterface
 // CHECK:     %x.data = sig i32 %0
 // CHECK:     %x.valid = sig i1 %1
 // CHECK:     %x.ready = sig i1 %2
-// CHECK:     inst %foo.initial.61.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready)
+// CHECK:     inst %foo.initial.49.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready)
 // CHECK: }
@@ -37,7 +37,7 @@ interface bar #(
     logic ready;
 endinterface
 
-// CHECK: proc %foo.initial.229.0 () -> () {
+// CHECK: proc %foo.initial.217.0 () -> () {
 // CHECK:     %1 = const i32 32
 // CHECK:     %2 = const i32 1
 // CHECK:     %3 = const i32 19
@@ -14,7 +14,7 @@ interface bar;
   logic ready;
 endinterface
 
-// CHECK: proc %foo.initial.42.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z) {
+// CHECK: proc %foo.initial.40.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z) {
 // CHECK:     %1 = and i1 %x.valid.prb, %x.ready.prb
 // CHECK:     %3 = neq i32 %x.data.prb, %2
 // CHECK: }
@@ -24,5 +24,5 @@ endinterface
 // CHECK:     %x.data = sig i32 %1
 // CHECK:     %x.valid = sig i1 %2
 // CHECK:     %x.ready = sig i1 %3
-// CHECK:     inst %foo.initial.42.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z)
+// CHECK:     inst %foo.initial.40.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z)
 // CHECK: }
@@ -0,0 +1,14 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (bar.in x);
+    initial x.data = 42;
+endmodule
+
+interface bar;
+    logic [31:0] data;
+    logic ready;
+    modport in (input data, output ready);
+endinterface
+
+// CHECK-ERR: error: `data` is an input of modport `in` and cannot be assigned to
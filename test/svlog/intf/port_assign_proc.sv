@@ -20,12 +20,12 @@ interface bar;
     logic ready;
 endinterface
 
-// CHECK: proc %foo.initial.260.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready, [1 x [2 x i32]]$ %y.data, [1 x [2 x i1]]$ %y.valid, [1 x [2 x i1]]$ %y.ready) {
+// CHECK: proc %foo.initial.212.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready, [1 x [2 x i32]]$ %y.data, [1 x [2 x i1]]$ %y.valid, [1 x [2 x i1]]$ %y.ready) {
 // CHECK:     drv i32$ %x.data, %1, %2
 // CHECK:     drv i1$ %x.valid, %3, %4
 // CHECK:     drv i1$ %x.ready, %5, %6
 // CHECK: }
 
 // CHECK: entity @foo () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready, [1 x [2 x i32]]$ %y.data, [1 x [2 x i1]]$ %y.valid, [1 x [2 x i1]]$ %y.ready) {
-// CHECK:     inst %foo.initial.260.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready, [1 x [2 x i32]]$ %y.data, [1 x [2 x i1]]$ %y.valid, [1 x [2 x i1]]$ %y.ready)
+// CHECK:     inst %foo.initial.212.0 () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready, [1 x [2 x i32]]$ %y.data, [1 x [2 x i1]]$ %y.valid, [1 x [2 x i1]]$ %y.ready)
 // CHECK: }
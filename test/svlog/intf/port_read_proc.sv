@@ -13,12 +13,12 @@ interface bar;
   logic ready;
 endinterface
 
-// CHECK: proc %foo.initial.41.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z) {
+// CHECK: proc %foo.initial.39.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z) {
 // CHECK:     %1 = and i1 %x.valid.prb, %x.ready.prb
 // CHECK:     %3 = neq i32 %x.data.prb, %2
 // CHECK: }
 
 // CHECK: entity @foo () -> (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) {
 // CHECK:     %z = sig i1 %0
-// CHECK:     inst %foo.initial.41.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z)
+// CHECK:     inst %foo.initial.39.0 (i32$ %x.data, i1$ %x.valid, i1$ %x.ready) -> (i1$ %z)
 // CHECK: }
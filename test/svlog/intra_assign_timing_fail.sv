@@ -0,0 +1,9 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (input logic clk);
+    logic [3:0] a, b;
+    initial b = @(posedge clk) a;
+endmodule
+
+// CHECK-ERR: error: unsupported: intra-assignment timing control
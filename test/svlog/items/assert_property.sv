@@ -0,0 +1,29 @@
+// RUN: moore %s -e foo -O0
+
+module foo (input bit clk, input bit rst, input bit a, input bit b);
+    assert property (@(posedge clk) disable iff (rst) a |-> b);
+endmodule
+
+// CHECK: event:
+// CHECK-NEXT:     %rst.prb = prb i1$ %rst
+// CHECK-NEXT:     %4 = const i1 0
+// CHECK-NEXT:     %5 = neq i1 %rst.prb, %4
+// CHECK-NEXT:     br %5, %assert_enabled, %assert_exit
+// CHECK-NEXT: assert_pass:
+// CHECK-NEXT:     br %assert_exit
+// CHECK-NEXT: assert_fail:
+// CHECK-NEXT:     %6 = call void @moore.assertion_failed ()
+// CHECK-NEXT:     br %assert_exit
+// CHECK-NEXT: assert_exit:
+// CHECK-NEXT:     br %0
+// CHECK-NEXT: assert_enabled:
+// CHECK-NEXT:     %a.prb = prb i1$ %a
+// CHECK-NEXT:     %7 = const i1 0
+// CHECK-NEXT:     %8 = neq i1 %a.prb, %7
+// CHECK-NEXT:     br %8, %assert_exit, %assert_check
+// CHECK-NEXT: assert_check:
+// CHECK-NEXT:     %b.prb = prb i1$ %b
+// CHECK-NEXT:     %9 = const i1 0
+// CHECK-NEXT:     %10 = neq i1 %b.prb, %9
+// CHECK-NEXT:     br %10, %assert_fail, %assert_pass
+// CHECK-NEXT: }
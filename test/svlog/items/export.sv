@@ -0,0 +1,43 @@
+// RUN: moore %s -e foo -O0
+
+package pkgA;
+    localparam int A = 1;
+    localparam int A2 = 2;
+endpackage
+
+package pkgB;
+    localparam int B = 42;
+endpackage
+
+package pkgC;
+    import pkgA::*;
+    import pkgB::B;
+    export pkgA::A;
+    export *::*;
+    localparam int C = 1337;
+endpackage
+
+interface bar import pkgC::*; #(parameter int W = B);
+    logic [W-1:0] x;
+endinterface
+
+module foo;
+    import pkgC::*;
+
+    int a = A;
+    int a2 = pkgC::A2;
+    int b = B;
+    int c = C;
+    bar i_bar();
+endmodule
+
+// CHECK: %0 = const i32 1
+// CHECK: %a = sig i32 %0
+// CHECK: %1 = const i32 2
+// CHECK: %a2 = sig i32 %1
+// CHECK: %2 = const i32 42
+// CHECK: %b = sig i32 %2
+// CHECK: %3 = const i32 1337
+// CHECK: %c = sig i32 %3
+// CHECK: %4 = const i42 0
+// CHECK: %i_bar.x = sig i42 %4
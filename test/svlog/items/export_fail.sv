@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo
+// FAIL
+
+package pkgA;
+    localparam int A = 1;
+endpackage
+
+package pkgB;
+    export pkgA::*;
+endpackage
+
+module foo;
+    export pkgA::A;
+    int a = pkgB::A;
+endmodule
+
+// CHECK-ERR: error: misplaced export declaration
+// CHECK-ERR: error: `A` not found in package `pkgB`
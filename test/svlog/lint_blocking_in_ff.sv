@@ -0,0 +1,15 @@
+// RUN: moore --syntax -W blocking-in-ff %s
+
+module foo (input logic clk, input logic [3:0] a);
+    logic [3:0] q, r;
+    always_ff @(posedge clk) begin
+        automatic logic [3:0] t;
+        t = a;
+        q = t;
+        r <= q;
+        for (int i = 0; i < 4; i++) r[i] <= a[i];
+    end
+endmodule
+
+// CHECK: warning: blocking assignment in `always_ff` [L0002]
+// CHECK: = note: Use a nonblocking assignment `<=` to assign `q`
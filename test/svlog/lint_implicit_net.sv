@@ -0,0 +1,15 @@
+// RUN: moore --syntax -W implicit-net %s
+
+module foo (input logic a);
+    logic b;
+    assign b = a;
+    assign c = a;
+    bar i0 (.x(b));
+    bar i1 (.x(d));
+endmodule
+
+module bar (input logic x);
+endmodule
+
+// CHECK: warning: implicit declaration of net `c` [L0001]
+// CHECK: warning: implicit declaration of net `d` [L0001]
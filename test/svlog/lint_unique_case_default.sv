@@ -0,0 +1,19 @@
+// RUN: moore --syntax -W unique-case-default %s
+
+module foo (input logic [1:0] s, output logic z);
+    always_comb begin
+        unique case (s)
+            2'd0: z = 0;
+            default: z = 1;
+        endcase
+        priority casez (s)
+            2'b1?: z = 0;
+            2'b01: z = 1;
+        endcase
+        case (s)
+            2'd0: z = 0;
+        endcase
+    end
+endmodule
+
+// CHECK: warning: `priority casez` without `default` [L0003]
@@ -0,0 +1,14 @@
+// RUN: moore --syntax -W unused-signal %s
+
+module foo (input logic a, output logic z);
+    logic b, c, d;
+    assign b = a;
+    assign c = b;
+    assign z = $bits(c);
+    bar i0 (.x(a));
+endmodule
+
+module bar (input logic x);
+endmodule
+
+// CHECK: warning: `d` is never used [L0004]
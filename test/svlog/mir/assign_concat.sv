@@ -29,13 +29,8 @@ module foo;
         {d} = 8'd42;
         // CHECK: %13 = extf i4$, [2 x i4]$ %d, 0
         // CHECK: drv i4$ %13, %3, %2
-        // CHECK: %14 = const i32 1
-        // CHECK: %15 = const i4 0
-        // CHECK: %16 = [2 x i4 %15]
-        // CHECK: %17 = sig [2 x i4] %16
-        // CHECK: %18 = shr [2 x i4]$ %d, [2 x i4]$ %17, i32 %14
-        // CHECK: %19 = extf i4$, [2 x i4]$ %18, 0
-        // CHECK: %20 = const i4 2
-        // CHECK: drv i4$ %19, %20, %2
+        // CHECK: %14 = extf i4$, [2 x i4]$ %d, 1
+        // CHECK: %15 = const i4 2
+        // CHECK: drv i4$ %14, %15, %2
     end
 endmodule
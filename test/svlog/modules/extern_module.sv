@@ -0,0 +1,21 @@
+// RUN: moore %s -e top --allow-blackbox
+
+extern module bar (input logic x, output logic [7:0] y);
+extern module baz (input logic x, output logic y);
+
+module top (input logic a, output logic [7:0] y, output logic z);
+    bar i0 (.x(a), .y(y));
+    baz i1 (.x(a), .y(z));
+endmodule
+
+// The definition takes precedence over the extern declaration.
+module baz (input logic x, output logic y);
+    assign y = ~x;
+endmodule
+
+// CHECK: entity @baz (i1$ %x) -> (i1$ %y) {
+// CHECK: entity @top (i1$ %a) -> (i8$ %y, i1$ %z) {
+// CHECK-NEXT:     inst @bar (i1$ %a) -> (i8$ %y)
+// CHECK-NEXT:     inst @baz (i1$ %a) -> (i1$ %z)
+// CHECK-NEXT: }
+// CHECK: declare @bar (i1$) -> (i8$)
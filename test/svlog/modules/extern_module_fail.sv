@@ -0,0 +1,11 @@
+// RUN: moore %s -e top
+// FAIL
+
+extern module bar (input logic x, output logic y);
+
+module top (input logic a, output logic y);
+    bar i0 (.x(a), .y(y));
+endmodule
+
+// CHECK-ERR: error: `bar` has no definition
+// CHECK-ERR: = note: Use `--allow-blackbox` to emit it as an external unit
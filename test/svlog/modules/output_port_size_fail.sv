@@ -0,0 +1,11 @@
+// RUN: moore %s -e top
+// FAIL
+
+module sub (output logic [7:0] y);
+endmodule
+
+module top (output logic [15:0] y);
+    sub i_sub (.y);
+endmodule
+
+// CHECK-ERR: error: `y` of type `logic [15:0]` cannot be assigned a value of type `logic [7:0]`
@@ -0,0 +1,25 @@
+// RUN: moore %s --top bar --top foo
+
+// Instances that assign the same values to all parameters share a single
+// specialization, including the default parameters of a top-level module.
+
+module foo;
+    bar a();
+    bar #(4) b();
+    bar #(.N(4)) c();
+    bar #(8) d();
+endmodule
+
+module bar #(parameter int N = 4);
+endmodule
+
+// CHECK: entity @bar () -> () {
+// CHECK: }
+// CHECK: entity @bar.N_8 () -> () {
+// CHECK: }
+// CHECK: entity @foo () -> () {
+// CHECK:     inst @bar () -> ()
+// CHECK:     inst @bar () -> ()
+// CHECK:     inst @bar () -> ()
+// CHECK:     inst @bar.N_8 () -> ()
+// CHECK: }
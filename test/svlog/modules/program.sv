@@ -0,0 +1,27 @@
+// RUN: moore %s -e top
+
+package pkg;
+    localparam int W = 4;
+endpackage
+
+// Programs are parsed and name-checked, but not elaborated.
+program automatic test_prog import pkg::*; #(parameter int N = W) (
+    input logic clk,
+    output logic [N-1:0] q
+);
+    int count;
+    initial begin
+        count = 0;
+        repeat (N) @(posedge clk) count++;
+    end
+    final $display("done");
+endprogram : test_prog
+
+program empty_prog;
+endprogram
+
+module top;
+    logic clk;
+endmodule
+
+// CHECK: entity @top () -> () {
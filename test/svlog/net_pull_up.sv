@@ -0,0 +1,13 @@
+// RUN: moore %s -e foo -O0
+
+module foo;
+    supply1 vdd;
+    // CHECK: %0 = const i1 1
+    // CHECK-NEXT: %vdd = sig i1 %0
+    tri1 [3:0] t;
+    // CHECK: %1 = const i4 15
+    // CHECK-NEXT: %t = sig i4 %1
+    supply0 gnd;
+    // CHECK: %2 = const i1 0
+    // CHECK-NEXT: %gnd = sig i1 %2
+endmodule
@@ -0,0 +1,26 @@
+// RUN: moore %s -e top -O0
+
+module foo;
+    parameter int A = 1, B = A + 1;
+    parameter logic [3:0] C = 5;
+    parameter type T = bit;
+    T [A+B+C-1:0] x;
+endmodule
+
+module bar #(parameter N = 1);
+    parameter M = 2;
+    logic [N+M-1:0] y;
+endmodule
+
+module top;
+    foo #(.B(10), .T(logic)) f();
+    foo #(2, 3) g();
+    bar #(4) b();
+endmodule
+
+// CHECK: entity @foo.A_1.B_10.C_5.T_logic () -> () {
+// CHECK:     %x = sig i16 %0
+// CHECK: entity @foo.A_2.B_3.C_5.T_bit () -> () {
+// CHECK:     %x = sig i10 %0
+// CHECK: entity @bar.N_4 () -> () {
+// CHECK:     %y = sig i6 %0
@@ -0,0 +1,14 @@
+// RUN: moore %s -e top
+// FAIL
+
+module bar #(parameter N = 1, localparam K = N + 1);
+    parameter M = 2;
+endmodule
+
+module top;
+    bar #(.M(5)) b();
+    bar #(4, 5) c();
+endmodule
+
+// CHECK-ERR: error: cannot override local parameter `M` of module `bar`
+// CHECK-ERR: error: module `bar` only has 1 parameter(s)
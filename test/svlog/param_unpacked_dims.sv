@@ -0,0 +1,14 @@
+// RUN: moore %s -e foo -O0
+
+module foo #(parameter int A [2] = '{3, 4}, localparam int B [2:3] = '{5, 6});
+    parameter int C [3] = '{7, 8, 9};
+    localparam int D [2][3] = '{'{1, 2, 3}, '{4, 5, 6}};
+    logic [A[1]-1:0] v0;
+    // CHECK: %v0 = sig i4 %0
+    logic [B[2]-1:0] v1;
+    // CHECK: %v1 = sig i5 %1
+    logic [C[0]-1:0] v2;
+    // CHECK: %v2 = sig i7 %2
+    logic [D[1][2]-1:0] v3;
+    // CHECK: %v3 = sig i6 %3
+endmodule
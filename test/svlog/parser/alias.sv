@@ -0,0 +1,6 @@
+module foo (inout wire [7:0] a, inout wire [7:0] b);
+    wire [3:0] lo, hi;
+    alias a = b;
+    alias {hi, lo} = a;
+    alias lo = hi = a[3:0];
+endmodule
@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo (inout wire a);
+    alias a;
+endmodule
+
+// CHECK-ERR: error: expected `=`, but found `;` instead
@@ -0,0 +1,17 @@
+// RUN: moore %s -e top
+
+module checker_mod #(parameter W = 1) (input logic clk, input logic [W-1:0] sig);
+endmodule
+
+module dut (input logic clk, input logic [3:0] data);
+endmodule
+
+module top (input logic clk, input logic [3:0] d);
+    dut u0 (.clk, .data(d));
+    dut u1 (.clk, .data(d));
+    bind dut checker_mod #(.W(4)) c_local (.clk, .sig(data));
+endmodule
+
+bind dut checker_mod #(4) c0 (.clk(clk), .sig(data));
+bind dut : top.u0, top.u1 checker_mod #(4) c1 (.*);
+bind top.u0 checker_mod c2 (clk, data[0]), c3 (clk, data[1]);
@@ -0,0 +1,16 @@
+module foo;
+    function int f(int a = 1, int b = 2, int c = 3);
+        return a + b + c;
+    endfunction
+    int q;
+    initial begin
+        q = f();
+        q = f(1, 2, 3);
+        q = f(1, , 3);
+        q = f(, 2);
+        q = f(1, );
+        q = f(1, .c(3));
+        q = f(.a(1), .b(), .c(3));
+        $display("%d", q, );
+    end
+endmodule
@@ -0,0 +1,12 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo;
+    function int f(int a, int b);
+        return a + b;
+    endfunction
+    int q;
+    initial q = f(.a(1), 2);
+endmodule
+
+// CHECK-ERR: error: positional argument after named argument
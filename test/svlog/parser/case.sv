@@ -0,0 +1,24 @@
+module foo;
+    logic [3:0] x;
+    int y;
+    always_comb begin
+        unique case (x)
+            4'd0, 4'd1: y = 1;
+            default: y = 0;
+        endcase
+        priority casez (x)
+            4'b1???: y = 2;
+        endcase
+        unique0 casex (x)
+            4'b1x0z: y = 3;
+            default;
+        endcase
+        case (x) inside
+            [0:3], 7: y = 4;
+            [8:$]: y = 5;
+        endcase
+        case (x) matches
+            4'b1???: y = 6;
+        endcase
+    end
+endmodule
@@ -0,0 +1,13 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo;
+    logic [3:0] x;
+    int y;
+    always_comb
+        case (x)
+            [0:3]: y = 4;
+        endcase
+endmodule
+
+// CHECK-ERR: error: value ranges are only allowed in `case inside` items
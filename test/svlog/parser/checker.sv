@@ -0,0 +1,21 @@
+// RUN: moore %s -e foo
+
+checker request_ack(logic req, input [3:0] id, sequence ack = req, property p,
+                    untyped u, output bit seen = 0, int ids [2]);
+    default clocking @(posedge req); endclocking
+    logic last;
+    let valid(x) = x != 0;
+    always_ff @(posedge req) last <= id[0];
+    assert property (p);
+    cover property (ack);
+    initial seen = 1;
+endchecker : request_ack
+
+checker nothing;
+endchecker
+
+module foo;
+    checker inner(bit c);
+        assert property (c);
+    endchecker
+endmodule
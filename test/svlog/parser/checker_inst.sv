@@ -0,0 +1,12 @@
+// RUN: moore --syntax %s
+
+checker handshake(logic req, logic ack, event clk);
+    assert property (@clk req |-> ack);
+endchecker
+
+module foo (input logic clk, en, req, ack);
+    handshake h0 (req, ack, clk);
+    handshake h1 (req, ack, posedge clk);
+    handshake h2 (req, ack, negedge clk iff en);
+    handshake h3 (.req, .ack, .clk(posedge clk or negedge en));
+endmodule
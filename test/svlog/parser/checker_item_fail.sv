@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+checker foo(a);
+    wire w;
+endchecker
+
+// CHECK-ERR: error: net declaration not allowed in checker
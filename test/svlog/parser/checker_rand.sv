@@ -0,0 +1,7 @@
+// RUN: moore --syntax %s
+
+checker data_legal(logic clk, logic [7:0] data);
+    rand bit x;
+    rand const logic [2:0] idx;
+    assert property (@(posedge clk) data[idx] == x);
+endchecker
@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo;
+    rand bit x;
+endmodule
+
+// CHECK-ERR: error: expected hierarchy item, found `rand` instead
@@ -0,0 +1,41 @@
+// RUN: moore --syntax %s
+
+package pkg;
+    interface class ifc;
+        pure virtual function void run();
+    endclass
+
+    virtual class base #(type T = int, int N = 4) extends root implements ifc, other#(T);
+        typedef T item_t[$];
+        local int a;
+        protected rand bit [3:0] b;
+        static const int c = 1;
+        randc logic d;
+        extern function new(string name = "");
+        pure virtual function void run();
+        virtual task automatic main(input int x);
+            #1;
+        endtask : main
+        static function base create();
+            return null;
+        endfunction
+        constraint c_b { b < 10; }
+        class nested;
+            int x;
+        endclass
+    endclass : base
+
+    class derived extends base#(int, 8);
+        function new(string name = "");
+            super.new(name);
+        endfunction
+        virtual function void run();
+            this.a = 1;
+        endfunction
+    endclass
+
+    typedef class fwd;
+    typedef interface class fwd_ifc;
+    class fwd;
+    endclass
+endpackage
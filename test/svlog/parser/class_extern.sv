@@ -0,0 +1,9 @@
+// RUN: moore --syntax %s
+
+class C;
+    extern function new();
+    extern virtual function void f();
+    extern protected virtual task t(int x);
+    extern static function int g();
+    extern local function void h();
+endclass
@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+class C;
+    extern rand function void f();
+endclass
+
+// CHECK-ERR: error: `rand` cannot qualify an extern method
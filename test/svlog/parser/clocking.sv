@@ -0,0 +1,27 @@
+// RUN: moore %s -e foo
+
+module foo (input logic clk, input logic [3:0] a, output logic [3:0] b, inout logic c);
+    clocking cb @(posedge clk);
+        default input #1step output #2;
+        input a;
+        output b;
+        input #1 output negedge c;
+    endclocking : cb
+
+    clocking cb2 @clk;
+        default output posedge #1ns;
+        input posedge a, x = a[0];
+        output #0 b;
+        inout c;
+    endclocking
+
+    default clocking cb;
+endmodule
+
+module bar (input logic clk, input logic a);
+    default clocking @(posedge clk);
+        input a;
+    endclocking
+
+    global clocking gclk @(posedge clk); endclocking
+endmodule
@@ -0,0 +1,10 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo (input logic clk, input logic a);
+    clocking cb @(posedge clk);
+        logic b;
+    endclocking
+endmodule
+
+// CHECK-ERR: error: expected `default`, `input`, `output`, or `inout` in clocking block, found `logic` instead
@@ -0,0 +1,43 @@
+// RUN: moore --syntax %s
+
+class pkt;
+    rand bit [7:0] len;
+    rand bit [3:0] mode;
+    rand int data [4];
+    bit en;
+
+    constraint c_len { len inside {[0:7]}; }
+    constraint c_mode {
+        mode == 0 -> len < 4;
+        mode == 1 -> { len > 2; len < 6; }
+        if (en) {
+            soft mode != 3;
+        } else if (len == 0)
+            mode == 0;
+        else
+            mode inside {1, 2};
+        foreach (data[i])
+            data[i] < 10;
+        foreach (data[i]) {
+            if (i > 0) data[i] > data[i-1];
+        }
+        len dist { 0 := 1, [1:3] :/ 4, 5 };
+        soft mode dist { 0 := 9, [1:15] :/ 1 };
+        unique { data[0], data[1] };
+        solve mode before len;
+        disable soft mode;
+    }
+    constraint c_proto;
+    extern constraint c_ext;
+endclass
+
+module foo;
+    pkt p;
+    int x;
+    initial begin
+        void'(p.randomize() with { len < 5; mode == 1 -> len > 1; });
+        void'(p.randomize with { len == 3; });
+        void'(p.randomize(len) with (len) { len < x; });
+        void'(std::randomize(x) with { x inside {[0:9]}; });
+    end
+endmodule
@@ -0,0 +1,6 @@
+module foo (input logic b, c, output logic a, d, e, f, g);
+    assign a = b & c;
+    assign (strong0, weak1) #2 d = b | c, e = b ^ c;
+    assign #(1, 2) f = b;
+    assign (pull1, highz0) #(1:2:3, 4, 5) g = c;
+endmodule
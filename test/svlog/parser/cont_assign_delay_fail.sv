@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo (input logic b, output logic a);
+    assign #(1, 2, 3, 4) a = b;
+endmodule
+
+// CHECK-ERR: error: expected at most three delay values (rise, fall, turn-off)
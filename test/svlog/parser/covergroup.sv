@@ -0,0 +1,53 @@
+// RUN: moore %s -e foo
+
+module foo (input logic clk, input logic en, input logic [3:0] a, input logic [3:0] b);
+    covergroup cg_clk @(posedge clk);
+        option.per_instance = 1;
+        type_option.weight = 2;
+        coverpoint a;
+        cp_b: coverpoint b iff (en) {
+            option.auto_bin_max = 4;
+            bins zero = {0};
+            bins low[] = {[1:3], 5};
+            bins high[2] = {[8:$]} iff (en);
+            wildcard bins odd = {4'b???1};
+            bins div3[] = {[0:15]} with (item % 3 == 0);
+            illegal_bins bad = {7};
+            ignore_bins skip = {6};
+            bins others = default;
+        }
+        bit [4:0] cp_sum: coverpoint a + b;
+        cp_seq: coverpoint a {
+            bins rise = (0 => 1), (1 => 2 => 3);
+            bins burst = (4 [* 2:3] => 5);
+            bins hop = (1, 2 => [4:5] [-> 2]);
+            bins gap = (6 [= 2] => 7);
+            bins rest = default sequence;
+        }
+        axb: cross a, cp_b {
+            bins both = binsof(a) intersect {[0:3]} && binsof(cp_b.zero);
+            illegal_bins none = !binsof(a) intersect {15} || (binsof(cp_b.low));
+            ignore_bins filt = binsof(a) with (a > b);
+            function int pick();
+                return 1;
+            endfunction
+        }
+        cross cp_b, cp_sum;
+    endgroup : cg_clk
+
+    covergroup cg_args (int lo, int hi) with function sample (logic [3:0] x);
+        coverpoint x {
+            bins in_range = {[lo:hi]};
+        }
+    endgroup
+
+    covergroup cg_block @@(begin run or end stop);
+    endgroup
+endmodule
+
+class cls;
+    int x;
+    covergroup cg;
+        coverpoint x;
+    endgroup
+endclass
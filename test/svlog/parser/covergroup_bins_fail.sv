@@ -0,0 +1,13 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo (input logic [3:0] a);
+    covergroup cg;
+        coverpoint a {
+            bins b = {1};
+            wire w;
+        }
+    endgroup
+endmodule
+
+// CHECK-ERR: error: expected `bins`, `illegal_bins`, `ignore_bins`, or coverage option, found `wire` instead
@@ -0,0 +1,9 @@
+module sub #(parameter P = 1, parameter Q = 2);
+endmodule
+
+module foo;
+    sub s0 ();
+    sub s1 ();
+    defparam s0.P = 5;
+    defparam s1.P = 3, s1.Q = (4:5:6);
+endmodule
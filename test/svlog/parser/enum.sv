@@ -0,0 +1,6 @@
+module foo;
+    typedef enum logic [1:0] {A, B = 2, C} state_t;
+    typedef enum {X[4], Y[2:3] = 7, Z} ranged_t;
+    enum bit unsigned [3:0] {P = 4'h1, Q} e;
+    enum state_t {U, V} f;
+endmodule
@@ -0,0 +1,26 @@
+class C;
+    extern function int f(int a);
+    extern function new(int x = 0);
+endclass
+
+function int C::f(int a);
+    return a;
+endfunction : f
+
+function C::new(int x = 0);
+endfunction : new
+
+module foo;
+    function automatic logic [7:0] add(input logic [7:0] a, b = 8'd1, output int c);
+        c = 0;
+        return a + b;
+    endfunction : add
+
+    function signed [7:0] neg(logic [7:0] x);
+        return -x;
+    endfunction
+
+    function void f;
+        input int x;
+    endfunction : f
+endmodule
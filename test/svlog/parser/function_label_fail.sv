@@ -0,0 +1,9 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo;
+    function void f();
+    endfunction : g
+endmodule
+
+// CHECK-ERR: error: label `g` does not match the name of function `f`
@@ -0,0 +1,17 @@
+module foo;
+    int x, y;
+    initial begin
+        if (x) y = 1;
+        if (x) y = 1; else if (y) y = 2; else y = 3;
+        unique if (x == 0) y = 1; else if (x == 1) y = 2;
+        unique0 if (x) y = 1;
+        priority if (x) y = 1; else y = 0;
+        if (x) if (y) x = 1; else x = 2;
+        if (x) ; else y = 0;
+        if (x matches 1 &&& y > 0) y = 1;
+        case (x) matches
+            1 &&& y == 0: y = 1;
+            default: y = 0;
+        endcase
+    end
+endmodule
@@ -0,0 +1,35 @@
+// RUN: moore %s -e foo
+
+package pkg;
+    interface class getter #(type T = int);
+        pure virtual function T get();
+    endclass
+
+    interface class putter #(type T = int);
+        typedef T item_t;
+        localparam int N = 2;
+        pure virtual function void put(T value);
+    endclass
+
+    typedef interface class both;
+
+    interface class both #(type T = logic) extends getter#(T), putter#(T);
+        pure virtual task wait_ready();
+    endclass : both
+
+    class fifo implements both#(int);
+        int q[$];
+        virtual function int get();
+            return q.pop_front();
+        endfunction
+        virtual function void put(int value);
+            q.push_back(value);
+        endfunction
+        virtual task wait_ready();
+        endtask
+    endclass
+endpackage
+
+module foo;
+    import pkg::*;
+endmodule
@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+interface class bad;
+    int x;
+endclass
+
+// CHECK-ERR: error: expected pure virtual method, typedef, or parameter before `int`
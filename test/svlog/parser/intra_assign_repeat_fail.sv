@@ -0,0 +1,9 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo;
+    logic a, b;
+    initial b = repeat (2);
+endmodule
+
+// CHECK-ERR: error: expected event control before `;`
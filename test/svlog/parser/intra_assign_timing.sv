@@ -0,0 +1,17 @@
+// RUN: moore --syntax %s
+
+module foo;
+    logic clk;
+    logic [3:0] a, b;
+    initial begin
+        b = #2 a;
+        b = #(1:2:3) a;
+        b = @(posedge clk) a;
+        b = @clk a;
+        b <= #2 a;
+        b <= @(posedge clk iff a != 0) a;
+        b = repeat (2) @(posedge clk) a;
+        b <= repeat (a + 1) @clk a;
+        b += a;
+    end
+endmodule
@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo
+
+package pkg;
+    let two() = 2;
+endpackage
+
+interface bar;
+    let one = 1'b1;
+endinterface
+
+module foo;
+    typedef logic [3:0] nibble_t;
+    let max(x, y) = x > y ? x : y;
+    let at_least(x, untyped y = 1) = x >= y;
+    let sum(logic [3:0] x, int y = 2) = x + y;
+    let first(nibble_t x [2]) = x[0];
+    let always_true = 1;
+endmodule
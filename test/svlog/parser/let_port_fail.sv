@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo;
+    let f(x y z) = x;
+endmodule
+
+// CHECK-ERR: error: expected , or ) after let argument
@@ -0,0 +1,15 @@
+module foo;
+    int q, a[4][4];
+    initial begin
+        for (int i = 0, j = 1; i < 4; i++, j += 2) q = i;
+        for (q = 0; q < 4; q++) ;
+        for (;;) break;
+        for (int i = 0; ; ) break;
+        while (q < 3) q++;
+        do q--; while (q > 0);
+        repeat (3) q++;
+        foreach (a[i, j]) a[i][j] = 0;
+        foreach (a[, j]) q = j;
+        forever begin continue; end
+    end
+endmodule
@@ -0,0 +1,6 @@
+// RUN: moore %s -E
+// FAIL
+
+`define foo(x) x `foo(x)
+`foo(42)
+// CHECK: fatal: expansion of `foo` does not terminate
@@ -0,0 +1,12 @@
+module foo (input logic b);
+    wire w0 = b;
+    wire [3:0] w1, w2;
+    tri #(1, 2, 3) w3;
+    wand (strong0, pull1) #2 w4 = b;
+    trireg (small) w5;
+    trireg (large) #(0, 0, 50) w6;
+    wire logic signed [1:0] w7;
+    wor vectored [7:0] w8;
+    supply0 gnd;
+    uwire w9;
+endmodule
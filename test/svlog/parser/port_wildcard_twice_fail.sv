@@ -0,0 +1,11 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module sub (input logic a, output logic y);
+endmodule
+
+module top (input logic a, output logic y);
+    sub s0 (.*, .*);
+endmodule
+
+// CHECK-ERR: error: `.*` may appear only once in a list of port connections
@@ -0,0 +1,10 @@
+module foo;
+    int q[$];
+    int b[$:15];
+    typedef int qt[$:3];
+    qt x;
+    initial begin
+        int r[$];
+        byte s[$:7];
+    end
+endmodule
@@ -0,0 +1,13 @@
+module foo;
+    int x, w;
+    initial begin
+        randcase
+            3: x = 1;
+            w + 1: begin
+                x = 2;
+            end
+            0: ;
+        endcase
+        repeat (4) randcase 1: x++; 1: x--; endcase
+    end
+endmodule
@@ -0,0 +1,8 @@
+// RUN: moore --syntax %s
+// FAIL
+
+module foo;
+    initial randcase endcase
+endmodule
+
+// CHECK-ERR: error: randcase statement must have at least one item
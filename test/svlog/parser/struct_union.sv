@@ -0,0 +1,11 @@
+module foo (input struct packed { logic a; logic [1:0] b; } p);
+    typedef struct packed signed {
+        logic [3:0] a;
+        rand bit [1:0] b, c;
+    } s_t;
+    struct packed unsigned { logic a; } [3:0] arr;
+    struct { int a = 4; logic [1:0] b [2]; } us [2];
+    typedef union packed { logic [7:0] x; s_t y; } u_t;
+    typedef union tagged { void Invalid; int Valid; } t_t;
+    union tagged packed { void Invalid; logic [3:0] Valid; } tu;
+endmodule
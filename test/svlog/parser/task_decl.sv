@@ -0,0 +1,21 @@
+interface bus;
+    extern task read(input int addr, output int data);
+    extern forkjoin task reset();
+endinterface
+
+module foo;
+    task automatic write(input int addr = 0, data, output bit ok, inout int count);
+        ok = 1;
+        count++;
+    endtask : write
+
+    task static wait_cycles(int n);
+        repeat (n) #1;
+    endtask
+
+    task t;
+        input int a;
+        output int b;
+        b = a;
+    endtask : t
+endmodule
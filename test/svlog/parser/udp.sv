@@ -0,0 +1,49 @@
+primitive mux (out, a, b, s);
+    output out;
+    input a, b, s;
+    table
+    // a b s : out
+        0 ? 0 : 0;
+        1 ? 0 : 1;
+        ? 0 1 : 0;
+        ? 1 1 : 1;
+        0 0 x : 0;
+        1 1 x : 1;
+    endtable
+endprimitive
+
+primitive dff (q, clk, d);
+    output q;
+    reg q;
+    input clk, d;
+    initial q = 1'b0;
+    table
+        (01) 0 : ? : 0;
+        (01) 1 : ? : 1;
+        (0?) 1 : 1 : 1;
+        (?0) ? : ? : -;
+        ?   (??) : ? : -;
+        r 1 : ? : 1;
+        n ? : ? : -;
+        * b : ? : -;
+    endtable
+endprimitive : dff
+
+primitive latch (output reg q = 0, input en, d);
+    table
+        1 0 : ? : 0;
+        1 1 : ? : 1;
+        0 ? : ? : -;
+    endtable
+endprimitive
+
+primitive packed_syms (out, a, b);
+    output out;
+    input a, b;
+    table
+        00:0;
+        01 : 1;
+        1? : 1;
+        bx : x;
+    endtable
+endprimitive
@@ -0,0 +1,13 @@
+// RUN: moore --syntax %s
+// FAIL
+
+primitive and2 (out, a, b);
+    output out;
+    input a, b;
+    table
+        0 ? : 0;
+        1 1 1 : 1;
+    endtable
+endprimitive
+
+// CHECK-ERR: error: expected 2 input symbols in table entry of primitive `and2`, found 3
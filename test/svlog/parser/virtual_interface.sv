@@ -0,0 +1,16 @@
+interface bus #(parameter int N = 1);
+    logic [N-1:0] data;
+    modport master(output data);
+endinterface
+
+class driver;
+    virtual bus.master vif;
+    virtual interface bus #(8) wide_vif;
+    virtual bus #(.N(2)).master narrow_vif;
+    virtual function void drive();
+    endfunction
+endclass
+
+program test;
+    virtual bus vif;
+endprogram
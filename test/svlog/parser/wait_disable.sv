@@ -0,0 +1,10 @@
+module foo;
+    logic en;
+    initial begin : blk
+        wait (en) $display("en");
+        wait (en);
+        wait fork;
+        disable blk;
+        disable fork;
+    end
+endmodule
@@ -0,0 +1,11 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module bar (input logic clk);
+endmodule
+
+module foo (input logic clk);
+    bar b (posedge clk);
+endmodule
+
+// CHECK-ERR: error: event expression cannot be connected to a port
@@ -0,0 +1,17 @@
+// RUN: moore %s -e test -Vports
+module test (
+    input wire [1:0] a, b,      // `b` inherits everything from `a`
+    inout c,                    // a direction alone resets kind and type
+    input var d,
+    output signed [3:0] e, f,
+    output logic g
+);
+    // CHECK: Ports of `test`:
+    // CHECK: 0: input wire logic [1:0] a
+    // CHECK: 1: input wire logic [1:0] b
+    // CHECK: 2: inout wire logic c
+    // CHECK: 3: input var logic d
+    // CHECK: 4: output wire logic signed [3:0] e
+    // CHECK: 5: output wire logic signed [3:0] f
+    // CHECK: 6: output var logic g
+endmodule
@@ -0,0 +1,7 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo (inout logic z = 1, ref int r = 2);
+    // CHECK-ERR: error: inout port `z` cannot have a default value
+    // CHECK-ERR: error: ref port `r` cannot have a default value
+endmodule
@@ -0,0 +1,20 @@
+// RUN: moore %s -e top
+
+module foo (output int o, input int i = 3);
+    assign o = i;
+endmodule
+
+module top (output int x, y, z);
+    foo f0 (.o(x));
+    foo f1 (.o(y), .i());
+    foo f2 (.o(z), .i(4));
+endmodule
+
+// CHECK: entity @top () -> (i32$ %x, i32$ %y, i32$ %z) {
+// CHECK:     %0 = const i32 3
+// CHECK:     %f0.i.default = sig i32 %1
+// CHECK:     drv i32$ %f0.i.default, %0, %2
+// CHECK:     inst @foo (i32$ %f0.i.default) -> (i32$ %x)
+// CHECK:     %f1.i.default = sig i32 %1
+// CHECK:     drv i32$ %f1.i.default, %0, %2
+// CHECK:     inst @foo (i32$ %f1.i.default) -> (i32$ %y)
@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    int q[$:3];
+endmodule
+
+// CHECK-ERR: error: unsupported: variable-size array type `int $ [$:3]`
@@ -0,0 +1,12 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    int x;
+    initial randcase
+        1: x = 1;
+        2: x = 2;
+    endcase
+endmodule
+
+// CHECK-ERR: error: unsupported: `randcase` statement
@@ -0,0 +1,9 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    int x;
+    initial void'(std::randomize(x) with { x inside {[0:9]}; });
+endmodule
+
+// CHECK-ERR: error: unsupported: inline constraints on `randomize`
@@ -32,19 +32,16 @@ module bar (input logic b0, b1, b2, b3);
     // CHECK: %b0.prb = prb i1$ %b0
     // CHECK: drv i1$ %2, %b0.prb, %1
     assign x[8] = b1;
-    // CHECK: %3 = const i32 7
-    // CHECK: %5 = shr i8$ %x, i8$ %4, i32 %3
-    // CHECK: %6 = exts i1$, i8$ %5, 0, 1
+    // CHECK: %3 = exts i1$, i8$ %x, 7, 1
     // CHECK: %b1.prb = prb i1$ %b1
-    // CHECK: drv i1$ %6, %b1.prb, %1
+    // CHECK: drv i1$ %3, %b1.prb, %1
     assign z[2] = b2;
-    // CHECK: %7 = exts i1$, i8$ %z, 0, 1
+    // CHECK: %4 = exts i1$, i8$ %z, 0, 1
     // CHECK: %b2.prb = prb i1$ %b2
-    // CHECK: drv i1$ %7, %b2.prb, %1
+    // CHECK: drv i1$ %4, %b2.prb, %1
     assign z[9] = b3;
-    // CHECK: %9 = shr i8$ %z, i8$ %8, i32 %3
-    // CHECK: %10 = exts i1$, i8$ %9, 0, 1
+    // CHECK: %5 = exts i1$, i8$ %z, 7, 1
     // CHECK: %b3.prb = prb i1$ %b3
-    // CHECK: drv i1$ %10, %b3.prb, %1
+    // CHECK: drv i1$ %5, %b3.prb, %1
 endmodule
 // CHECK: }
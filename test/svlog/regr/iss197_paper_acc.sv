@@ -9,7 +9,7 @@ module acc (input clk, input [31:0] x, input en, output [31:0] q);
     end
 endmodule
 
-// CHECK: proc %acc.always_ff.45.0 (i1$ %clk, i32$ %d) -> (i32$ %q) {
+// CHECK: proc %acc.always_ff.43.0 (i1$ %clk, i32$ %d) -> (i32$ %q) {
 // CHECK: init:
 // CHECK:     %clk.prb = prb i1$ %clk
 // CHECK: check:
@@ -13,16 +13,16 @@ endmodule
 module C(input clk);
 endmodule
 
-// CHECK: entity @C.param1 (i1$ %clk) -> () {
+// CHECK: entity @C (i1$ %clk) -> () {
 // CHECK: }
 
 // CHECK: entity @A (i1$ %clk) -> () {
-// CHECK:     inst @C.param1 (i1$ %clk) -> ()
-// CHECK:     inst @C.param1 (i1$ %clk) -> ()
+// CHECK:     inst @C (i1$ %clk) -> ()
+// CHECK:     inst @C (i1$ %clk) -> ()
 // CHECK: }
 
 // CHECK: entity @B (i1$ %clk) -> () {
-// CHECK:     inst @C.param1 (i1$ %clk) -> ()
+// CHECK:     inst @C (i1$ %clk) -> ()
 // CHECK:     %clk.prb = prb i1$ %clk
 // CHECK:     %0 = const i1 1
 // CHECK:     %1 = xor i1 %clk.prb, %0
@@ -30,7 +30,7 @@ endmodule
 // CHECK:     %3 = sig i1 %2
 // CHECK:     %4 = const time 0s 1d
 // CHECK:     drv i1$ %3, %1, %4
-// CHECK:     inst @C.param1 (i1$ %3) -> ()
+// CHECK:     inst @C (i1$ %3) -> ()
 // CHECK: }
 
 interface I;
@@ -47,23 +47,17 @@ endmodule
 module E(I.in i);
 endmodule
 
-// CHECK: entity @E.param4 (i1$ %i.clk) -> () {
-// CHECK: }
-
-// CHECK: entity @E.param5 (i1$ %i.clk) -> () {
-// CHECK: }
-
-// CHECK: entity @E.param6 (i1$ %i.clk) -> () {
+// CHECK: entity @E (i1$ %i.clk) -> () {
 // CHECK: }
 
 // CHECK: entity @D (i1$ %i.clk, [2 x i1]$ %j.clk) -> () {
-// CHECK:     inst @E.param4 (i1$ %i.clk) -> ()
+// CHECK:     inst @E (i1$ %i.clk) -> ()
 // CHECK:     %0 = const i32 0
 // CHECK:     %4 = shr [2 x i1]$ %j.clk, [2 x i1]$ %3, i32 %0
 // CHECK:     %5 = extf i1$, [2 x i1]$ %4, 0
-// CHECK:     inst @E.param5 (i1$ %5) -> ()
+// CHECK:     inst @E (i1$ %5) -> ()
 // CHECK:     %6 = const i32 1
 // CHECK:     %10 = shr [2 x i1]$ %j.clk, [2 x i1]$ %9, i32 %6
 // CHECK:     %11 = extf i1$, [2 x i1]$ %10, 0
-// CHECK:     inst @E.param6 (i1$ %11) -> ()
+// CHECK:     inst @E (i1$ %11) -> ()
 // CHECK: }
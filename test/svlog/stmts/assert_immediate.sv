@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo
+
+module foo (input logic [3:0] a);
+    initial begin
+        assert (a != 0);
+        assert (a != 1) else $error("bad %d", a);
+        assert (a != 2) $display("ok"); else $error("bad");
+        assert (a != 3) else $error;
+        assert (a != 4) else ;
+        assert (a != 5) begin end else begin $fatal(1, "bad"); end
+        assume (a != 6) else $warning("bad");
+        cover (a == 7) $info("hit");
+        lbl: assert (a != 8);
+        if (a > 9) assert (a < 15); else assert (a == 0);
+        assert #0 (a != 10) else $error("bad");
+        assert final (a != 11) else $error("bad");
+    end
+endmodule
@@ -0,0 +1,20 @@
+// RUN: moore %s -e foo
+
+module foo;
+    int i;
+    logic [7:0] x;
+    initial begin
+        i += 1; i -= 1; i *= 2; i /= 2; i %= 3;
+        x &= 8'hf; x |= 1; x ^= 2;
+        x <<= 1; x >>= 1; x <<<= 1; x >>>= 1;
+        x[0] += 1;
+        i++; i--; ++i; --i;
+    end
+endmodule
+
+// CHECK: %x.shadow = var i8 %2
+// CHECK: %x.shadow.ld = ld i8* %x.shadow
+// CHECK: %13 = and i8 %x.shadow.ld, %12
+// CHECK: st i8* %x.shadow, %13
+// CHECK: %x.shadow.ld1 = ld i8* %x.shadow
+// CHECK: %15 = or i8 %x.shadow.ld1, %14
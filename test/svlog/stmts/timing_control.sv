@@ -0,0 +1,23 @@
+// RUN: moore %s -e foo
+
+module foo (input logic clk, input logic rst, input logic en);
+    logic [3:0] a, b;
+    always @(posedge clk) a <= b;
+    always @(posedge clk or negedge rst) a <= b;
+    always @(posedge clk, negedge rst) a <= b;
+    always @(edge clk iff en) a <= b;
+    always @(posedge clk iff en or negedge rst) a <= b;
+    always @((posedge clk) or (negedge rst iff en)) a <= b;
+    always @(a or b) b = a;
+    always @* b = a;
+    always @(*) b = a;
+    initial begin
+        #10;
+        #10 b = a;
+        #(2) b = a;
+        @(posedge clk);
+        @clk b = a;
+        repeat (2) @(posedge clk);
+        b <= #2 a;
+    end
+endmodule
@@ -0,0 +1,16 @@
+// RUN: moore %s -e foo
+
+module foo (input logic en);
+    int a;
+    initial begin
+        if (en) a = 2;
+        wait (en) a = 1;
+        wait fork;
+        disable fork;
+    end
+endmodule
+
+// CHECK: wait:
+// CHECK: wait %check, %en
+// CHECK: check:
+// CHECK: br %6, %wait, %wait_done
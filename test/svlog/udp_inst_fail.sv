@@ -0,0 +1,17 @@
+// RUN: moore %s -e foo
+// FAIL
+
+primitive inv (out, in);
+    output out;
+    input in;
+    table
+        0 : 1;
+        1 : 0;
+    endtable
+endprimitive
+
+module foo (input logic a, output logic z);
+    inv i0 (z, a);
+endmodule
+
+// CHECK-ERR: error: unsupported: instance of primitive `inv`
@@ -0,0 +1,8 @@
+// RUN: moore %s -e foo
+// FAIL
+
+module foo;
+    union packed { logic [7:0] x; logic [1:0][3:0] y; } u;
+endmodule
+
+// CHECK-ERR: error: unsupported: union type
@@ -0,0 +1,13 @@
+// RUN: moore %s -e foo
+// FAIL
+
+interface bus;
+    logic data;
+    modport master(output data);
+endinterface
+
+module foo;
+    virtual bus.master vif;
+endmodule
+
+// CHECK-ERR: error: unsupported: virtual interface type